@@ -0,0 +1,246 @@
+//! Kotlin and Swift bindings for helium-lib, generated with UniFFI.
+//!
+//! Generate the bindings from a release build of the library with:
+//!
+//! ```text
+//! cargo build --release -p helium-lib-ffi
+//! cargo run -p helium-lib-ffi --bin uniffi-bindgen -- generate \
+//!     --library target/release/libhelium_lib_ffi.so --language kotlin --out-dir out
+//! ```
+//!
+//! Keypairs, wallet files and mnemonics are handled locally. Transactions
+//! are built with a [`Client`] and returned as bincode serialized, signed,
+//! versioned transactions that can be inspected before sending them with
+//! [`Client::send`].
+
+use helium_lib::{
+    entity_key::{EncodedEntityKey, EntityKeyEncoding},
+    keypair::{self, Pubkey, Signer},
+    reward::{self, ClaimableToken},
+    solana_sdk::transaction::VersionedTransaction,
+    token::{self, TokenAmount},
+    TransactionOpts,
+};
+use helium_wallet_format::{
+    format::{self, Format},
+    pwhash::PwHash,
+    wallet::Wallet,
+};
+use std::{str::FromStr, sync::Arc};
+
+uniffi::setup_scaffolding!();
+
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum HeliumError {
+    #[error("{0}")]
+    Lib(#[from] helium_lib::error::Error),
+    #[error("{0}")]
+    Wallet(#[from] anyhow::Error),
+    #[error("invalid input: {0}")]
+    Input(String),
+}
+
+impl HeliumError {
+    fn input<S: ToString>(reason: S) -> Self {
+        Self::Input(reason.to_string())
+    }
+}
+
+type Result<T = ()> = std::result::Result<T, HeliumError>;
+
+/// Tokens that can be transferred
+#[derive(Debug, Clone, Copy, uniffi::Enum)]
+pub enum Token {
+    Sol,
+    Hnt,
+    Iot,
+    Mobile,
+}
+
+impl From<Token> for token::Token {
+    fn from(value: Token) -> Self {
+        match value {
+            Token::Sol => Self::Sol,
+            Token::Hnt => Self::Hnt,
+            Token::Iot => Self::Iot,
+            Token::Mobile => Self::Mobile,
+        }
+    }
+}
+
+/// Tokens that rewards can be claimed in
+#[derive(Debug, Clone, Copy, uniffi::Enum)]
+pub enum RewardToken {
+    Hnt,
+    Iot,
+    Mobile,
+}
+
+impl From<RewardToken> for ClaimableToken {
+    fn from(value: RewardToken) -> Self {
+        match value {
+            RewardToken::Hnt => Self::Hnt,
+            RewardToken::Iot => Self::Iot,
+            RewardToken::Mobile => Self::Mobile,
+        }
+    }
+}
+
+#[derive(uniffi::Object)]
+pub struct Keypair(Arc<keypair::Keypair>);
+
+#[uniffi::export]
+impl Keypair {
+    #[uniffi::constructor]
+    pub fn generate() -> Arc<Self> {
+        Arc::new(Self(Arc::new(keypair::Keypair::generate())))
+    }
+
+    /// Recreate a keypair from its mnemonic words. Both 24 word phrases and
+    /// the 12 word phrases of the Helium mobile app are accepted.
+    #[uniffi::constructor]
+    pub fn from_words(words: Vec<String>) -> Result<Arc<Self>> {
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        Ok(Arc::new(Self(keypair::Keypair::from_words(&words)?)))
+    }
+
+    /// The mnemonic words of the keypair
+    pub fn words(&self) -> Result<Vec<String>> {
+        let phrase = self
+            .0
+            .phrase()
+            .map_err(|err| HeliumError::from(helium_lib::error::Error::from(err)))?;
+        Ok(phrase.split(' ').map(ToString::to_string).collect())
+    }
+
+    /// The Solana address of the keypair
+    pub fn address(&self) -> String {
+        self.0.pubkey().to_string()
+    }
+
+    /// The Helium address of the keypair
+    pub fn helium_address(&self) -> Result<String> {
+        let helium_key =
+            keypair::to_helium_pubkey(&self.0.pubkey()).map_err(helium_lib::error::Error::from)?;
+        Ok(helium_key.to_string())
+    }
+
+    /// Sign an arbitrary message, returning the ed25519 signature
+    pub fn sign_message(&self, message: Vec<u8>) -> Result<Vec<u8>> {
+        Ok(self.0.sign(&message)?.as_ref().to_vec())
+    }
+}
+
+/// The wallet encryption uses libsodium, which has to be initialized once
+fn init_sodium() {
+    static INIT: std::sync::Once = std::sync::Once::new();
+    INIT.call_once(|| sodiumoxide::init().expect("Failed to intialize sodium"))
+}
+
+/// Encrypt a keypair with a password into the bytes of a wallet file, as
+/// written by `helium-wallet create basic`
+#[uniffi::export]
+pub fn encrypt_wallet(keypair: Arc<Keypair>, password: String) -> Result<Vec<u8>> {
+    init_sodium();
+    let format = Format::Basic(format::Basic {
+        pwhash: PwHash::argon2id13_default(),
+    });
+    let wallet = Wallet::encrypt(&keypair.0, password.as_bytes(), format)?;
+    let mut data = vec![];
+    wallet.write(&mut data)?;
+    Ok(data)
+}
+
+/// Decrypt the bytes of a basic wallet file with its password
+#[uniffi::export]
+pub fn decrypt_wallet(data: Vec<u8>, password: String) -> Result<Arc<Keypair>> {
+    init_sodium();
+    let wallet = Wallet::read(&mut data.as_slice())?;
+    if wallet.is_sharded() {
+        return Err(HeliumError::input("sharded wallets are not supported"));
+    }
+    Ok(Arc::new(Keypair(wallet.decrypt(password.as_bytes())?)))
+}
+
+/// A connection to a Solana RPC and DAS provider to build and send
+/// transactions with
+#[derive(uniffi::Object)]
+pub struct Client(helium_lib::client::Client);
+
+#[uniffi::export(async_runtime = "tokio")]
+impl Client {
+    /// Connect to the given RPC URL, or "m" and "d" for the default mainnet
+    /// and devnet providers. Clients share the key to asset and asset proof
+    /// caches of the process, so all clients have to use the same RPC URL.
+    #[uniffi::constructor]
+    pub fn new(url: String) -> Result<Arc<Self>> {
+        let client = helium_lib::client::Client::try_from(url.as_str())?;
+        helium_lib::init(client.solana_client.clone())?;
+        Ok(Arc::new(Self(client)))
+    }
+
+    /// Build a signed transaction transferring an amount of a token, in the
+    /// smallest unit of the token (lamports for SOL, bones for HNT), to the
+    /// given Solana address
+    pub async fn transfer(
+        &self,
+        keypair: Arc<Keypair>,
+        payee: String,
+        token: Token,
+        amount: u64,
+    ) -> Result<Vec<u8>> {
+        let payee = Pubkey::from_str(&payee).map_err(HeliumError::input)?;
+        let amount = TokenAmount::from_u64(token.into(), amount);
+        let (tx, _) = token::transfer(
+            &self.0,
+            &[(payee, amount)],
+            &keypair.0,
+            &TransactionOpts::default(),
+        )
+        .await?;
+        serialize(&tx)
+    }
+
+    /// Build a signed transaction claiming the pending rewards of a Hotspot,
+    /// given by its Helium address. Returns nothing when there is nothing to
+    /// claim.
+    pub async fn claim_rewards(
+        &self,
+        keypair: Arc<Keypair>,
+        token: RewardToken,
+        hotspot: String,
+    ) -> Result<Option<Vec<u8>>> {
+        let entity_key = EncodedEntityKey {
+            encoding: EntityKeyEncoding::B58,
+            entity_key: hotspot,
+        };
+        let claim = reward::claim(
+            &self.0,
+            token.into(),
+            None,
+            &entity_key,
+            &keypair.0,
+            &TransactionOpts::default(),
+        )
+        .await?;
+        claim.map(|(tx, _)| serialize(&tx)).transpose()
+    }
+
+    /// Send a serialized transaction, returning its signature
+    pub async fn send(&self, transaction: Vec<u8>) -> Result<String> {
+        let tx: VersionedTransaction = bincode::deserialize(&transaction)
+            .map_err(|err| HeliumError::input(format!("transaction: {err}")))?;
+        let signature = self
+            .0
+            .solana_client
+            .send_transaction(&tx)
+            .await
+            .map_err(helium_lib::error::Error::from)?;
+        Ok(signature.to_string())
+    }
+}
+
+fn serialize(tx: &VersionedTransaction) -> Result<Vec<u8>> {
+    bincode::serialize(tx).map_err(|err| HeliumError::input(format!("transaction: {err}")))
+}
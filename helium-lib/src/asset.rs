@@ -1,6 +1,7 @@
 use crate::{
+    anchor_spl::token::spl_token,
     bs58,
-    client::{DasClient, DasSearchAssetsParams, SolanaRpcClient},
+    client::{DasClient, DasPageParams, DasSearchAssetsParams, DasSortBy, SolanaRpcClient},
     dao::Dao,
     entity_key::{self, AsEntityKey},
     error::{DecodeError, Error},
@@ -9,9 +10,13 @@ use crate::{
     kta, message,
     priority_fee::{compute_budget_instruction, compute_price_instruction_for_accounts},
     programs::{SPL_ACCOUNT_COMPRESSION_PROGRAM_ID, SPL_NOOP_PROGRAM_ID},
-    solana_sdk::{instruction::AccountMeta, transaction::VersionedTransaction},
-    TransactionOpts,
+    solana_sdk::{
+        instruction::{AccountMeta, Instruction},
+        transaction::VersionedTransaction,
+    },
+    tx, TransactionOpts,
 };
+use futures::{stream, Future, Stream, TryStreamExt};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, result::Result as StdResult, str::FromStr};
@@ -41,11 +46,13 @@ pub async fn for_kta_with_proof<C: AsRef<DasClient>>(
     get_with_proof(client, &kta.asset).await
 }
 
+#[tracing::instrument(skip(client), level = "debug")]
 pub async fn get<C: AsRef<DasClient>>(client: &C, pubkey: &Pubkey) -> Result<Asset, Error> {
     let asset_response: Asset = client.as_ref().get_asset(pubkey).await?;
     Ok(asset_response)
 }
 
+#[tracing::instrument(skip(client), level = "debug")]
 pub async fn get_with_proof<C: AsRef<DasClient>>(
     client: &C,
     pubkey: &Pubkey,
@@ -100,14 +107,145 @@ pub mod canopy {
 
 pub mod proof {
     use super::*;
+    use crate::{
+        solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig},
+        solana_client::rpc_config::RpcAccountInfoConfig,
+    };
+    use spl_account_compression::state::ConcurrentMerkleTreeHeader;
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, OnceLock, RwLock,
+    };
+
+    /// Size of the tree header followed by the sequence number of the tree
+    const TREE_SEQUENCE_SLICE: usize = std::mem::size_of::<ConcurrentMerkleTreeHeader>() + 8;
+
+    static CACHE: OnceLock<ProofCache> = OnceLock::new();
+    static CACHE_ENABLED: AtomicBool = AtomicBool::new(true);
 
+    pub fn init(solana_client: Arc<SolanaRpcClient>) -> Result<(), Error> {
+        let cache = CACHE.get_or_init(|| ProofCache {
+            solana_client: solana_client.clone(),
+            proofs: RwLock::new(HashMap::new()),
+        });
+        if cache.solana_client.url() != solana_client.url() {
+            return Err(Error::CacheUrlMismatch);
+        }
+        Ok(())
+    }
+
+    /// Turn caching of asset proofs on or off. Caching is on by default once
+    /// the cache is initialized.
+    pub fn set_cache_enabled(enabled: bool) {
+        CACHE_ENABLED.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Drop the cached proof of an asset, like after changing its leaf
+    pub fn invalidate(pubkey: &Pubkey) {
+        if let Some(cache) = CACHE.get() {
+            cache
+                .proofs
+                .write()
+                .expect("proof cache lock")
+                .remove(pubkey);
+        }
+    }
+
+    /// Get the proof of an asset, from the cache if the cached proof is
+    /// still usable.
     pub async fn get<C: AsRef<DasClient>>(
         client: &C,
         pubkey: &Pubkey,
     ) -> Result<AssetProof, Error> {
+        match CACHE.get() {
+            Some(cache) if CACHE_ENABLED.load(Ordering::Relaxed) => cache.get(client, pubkey).await,
+            _ => fetch(client, pubkey).await,
+        }
+    }
+
+    async fn fetch<C: AsRef<DasClient>>(client: &C, pubkey: &Pubkey) -> Result<AssetProof, Error> {
         let asset_proof_response: AssetProof = client.as_ref().get_asset_proof(pubkey).await?;
         Ok(asset_proof_response)
     }
+
+    /// Caches asset proofs along with the sequence number of their tree when
+    /// the proof was fetched.
+    ///
+    /// The compression program accepts proofs against any root still in the
+    /// change log buffer of a tree, so a proof stays usable until the tree
+    /// has changed as often as the buffer is long. Since DAS may lag behind
+    /// the chain, cached proofs are only used for half of that.
+    struct ProofCache {
+        solana_client: Arc<SolanaRpcClient>,
+        proofs: RwLock<HashMap<Pubkey, (AssetProof, u64)>>,
+    }
+
+    /// The sequence number and change log buffer size of a tree
+    struct TreeSequence {
+        sequence: u64,
+        max_buffer_size: u64,
+    }
+
+    impl ProofCache {
+        async fn get<C: AsRef<DasClient>>(
+            &self,
+            client: &C,
+            pubkey: &Pubkey,
+        ) -> Result<AssetProof, Error> {
+            let cached = self
+                .proofs
+                .read()
+                .expect("proof cache lock")
+                .get(pubkey)
+                .cloned();
+            if let Some((proof, sequence)) = cached {
+                let tree = self.tree_sequence(&proof.tree_id).await?;
+                if tree.sequence.saturating_sub(sequence) < tree.max_buffer_size / 2 {
+                    tracing::trace!("proof cache hit");
+                    return Ok(proof);
+                }
+                tracing::trace!("proof cache stale");
+            }
+            let proof = fetch(client, pubkey).await?;
+            let tree = self.tree_sequence(&proof.tree_id).await?;
+            self.proofs
+                .write()
+                .expect("proof cache lock")
+                .insert(*pubkey, (proof.clone(), tree.sequence));
+            Ok(proof)
+        }
+
+        /// Read the sequence number of a tree without fetching the full tree
+        async fn tree_sequence(&self, tree: &Pubkey) -> Result<TreeSequence, Error> {
+            use helium_anchor_gen::anchor_lang::AnchorDeserialize;
+            let config = RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                data_slice: Some(UiDataSliceConfig {
+                    offset: 0,
+                    length: TREE_SEQUENCE_SLICE,
+                }),
+                ..Default::default()
+            };
+            let data = self
+                .solana_client
+                .get_account_with_config(tree, config)
+                .await?
+                .value
+                .ok_or_else(Error::account_not_found)?
+                .data;
+            let header = ConcurrentMerkleTreeHeader::deserialize(&mut &data[..])
+                .map_err(|_| DecodeError::other("invalid merkle tree header"))?;
+            let sequence = data
+                .get(TREE_SEQUENCE_SLICE - 8..TREE_SEQUENCE_SLICE)
+                .and_then(|bytes| bytes.try_into().ok())
+                .map(u64::from_le_bytes)
+                .ok_or_else(|| DecodeError::other("invalid merkle tree"))?;
+            Ok(TreeSequence {
+                sequence,
+                max_buffer_size: header.get_max_buffer_size().into(),
+            })
+        }
+    }
 }
 
 pub async fn search<C: AsRef<DasClient>>(
@@ -117,30 +255,116 @@ pub async fn search<C: AsRef<DasClient>>(
     Ok(client.as_ref().search_assets(params).await?)
 }
 
+/// Stream the pages of a DAS asset query in the given order, fetching each
+/// page as the stream is consumed. See [`DasPageParams`] for how pages are
+/// walked.
+pub fn pages<'a, F, Fut>(
+    sort_by: Option<DasSortBy>,
+    fetch: F,
+) -> impl Stream<Item = Result<AssetPage, Error>> + 'a
+where
+    F: Fn(DasPageParams) -> Fut + 'a,
+    Fut: Future<Output = Result<AssetPage, Error>> + 'a,
+{
+    stream::try_unfold(Some(DasPageParams::first(sort_by)), move |params| {
+        let page = params.clone().map(&fetch);
+        async move {
+            let (Some(params), Some(page)) = (params, page) else {
+                return Ok(None);
+            };
+            let page = page.await?;
+            let next_params = params.next(&page);
+            Ok(Some((page, next_params)))
+        }
+    })
+}
+
+/// Stream the assets of a paged DAS query, yielding the assets of each page
+/// as it arrives
+fn page_items<'a, F, Fut>(fetch: F) -> impl Stream<Item = Result<Asset, Error>> + 'a
+where
+    F: Fn(DasPageParams) -> Fut + 'a,
+    Fut: Future<Output = Result<AssetPage, Error>> + 'a,
+{
+    pages(None, fetch)
+        .map_ok(|page| stream::iter(page.items.into_iter().map(Ok)))
+        .try_flatten()
+}
+
+/// Stream all assets matching the given search, fetching the next page of
+/// results as the stream is consumed. The paging and order of the given
+/// params are replaced.
+pub fn search_stream<'a, C: AsRef<DasClient>>(
+    client: &'a C,
+    params: DasSearchAssetsParams,
+) -> impl Stream<Item = Result<Asset, Error>> + 'a {
+    page_items(move |page| search(client, params.clone().with_page(&page)))
+}
+
+/// Get all assets matching the given search, paging through the results.
+/// The paging and order of the given params are replaced.
+pub async fn search_all<C: AsRef<DasClient>>(
+    client: &C,
+    params: DasSearchAssetsParams,
+) -> Result<Vec<Asset>, Error> {
+    search_stream(client, params).try_collect().await
+}
+
+/// Stream the assets of the given creator held by an owner as the DAS
+/// result pages arrive
+pub fn for_owner_stream<'a, C: AsRef<DasClient>>(
+    client: &'a C,
+    creator: &Pubkey,
+    owner: &Pubkey,
+) -> impl Stream<Item = Result<Asset, Error>> + 'a {
+    search_stream(client, DasSearchAssetsParams::for_owner(*owner, *creator))
+}
+
+/// Get the assets of the given creator held by an owner
 pub async fn for_owner<C: AsRef<DasClient>>(
     client: &C,
     creator: &Pubkey,
     owner: &Pubkey,
 ) -> Result<Vec<Asset>, Error> {
-    let mut params = DasSearchAssetsParams::for_owner(*owner, *creator);
-    // Set to maximum documented limit
-    params.limit = 1000;
-    let mut results = vec![];
-    loop {
-        let page = client
-            .as_ref()
-            .search_assets(params.clone())
-            .await
-            .map_err(Error::from)?;
-        let fetch_count = page.items.len();
-        results.extend(page.items);
-        if fetch_count < params.limit as usize {
-            break;
-        }
-        params.page += 1;
-    }
+    for_owner_stream(client, creator, owner).try_collect().await
+}
+
+/// Stream all assets held by an owner as the DAS result pages arrive
+pub fn by_owner_stream<'a, C: AsRef<DasClient>>(
+    client: &'a C,
+    owner: &Pubkey,
+) -> impl Stream<Item = Result<Asset, Error>> + 'a {
+    let owner = *owner;
+    page_items(move |page| async move {
+        Ok::<_, Error>(client.as_ref().get_assets_by_owner(&owner, &page).await?)
+    })
+}
+
+/// Get all assets held by an owner
+pub async fn by_owner<C: AsRef<DasClient>>(
+    client: &C,
+    owner: &Pubkey,
+) -> Result<Vec<Asset>, Error> {
+    by_owner_stream(client, owner).try_collect().await
+}
 
-    Ok(results)
+/// Get all assets in a group, like the assets of a collection with the
+/// "collection" group key
+pub async fn by_group<C: AsRef<DasClient>>(
+    client: &C,
+    group_key: &str,
+    group_value: &Pubkey,
+) -> Result<Vec<Asset>, Error> {
+    page_items(|page| async move {
+        Ok::<_, Error>(
+            client
+                .as_ref()
+                .get_assets_by_group(group_key, group_value, &page)
+                .await?,
+        )
+    })
+    .try_collect()
+    .await
 }
 
 /// Get an unsigned transaction for an asset transfer
@@ -148,13 +372,52 @@ pub async fn for_owner<C: AsRef<DasClient>>(
 /// The asset is transferred from the owner to the given recipient
 /// Note that the owner is currently expected to sign this transaction and pay for
 /// transaction fees.
+///
+/// Compressed assets are transferred with bubblegum. Regular NFTs are moved
+/// to the associated token account of the recipient, which is created if
+/// needed, and the emptied token account of the owner is closed.
+/// Programmable NFTs are not supported.
 pub async fn transfer_message<C: AsRef<SolanaRpcClient> + AsRef<DasClient>>(
     client: &C,
     pubkey: &Pubkey,
     recipient: &Pubkey,
     opts: &TransactionOpts,
 ) -> Result<(message::VersionedMessage, u64), Error> {
-    let (asset, asset_proof) = get_with_proof(client, pubkey).await?;
+    let asset = get(client, pubkey).await?;
+    if !asset.compression.is_compressed()? {
+        let owner = asset.ownership.owner;
+        let token_account = token::account_for(client, &asset).await?;
+        let ixs = vec![
+            spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                &owner,
+                recipient,
+                &asset.id,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::transfer_checked(
+                &spl_token::id(),
+                &token_account,
+                &asset.id,
+                &spl_associated_token_account::get_associated_token_address(recipient, &asset.id),
+                &owner,
+                &[],
+                1,
+                0,
+            )?,
+            spl_token::instruction::close_account(
+                &spl_token::id(),
+                &token_account,
+                &owner,
+                &owner,
+                &[],
+            )?,
+        ];
+        return token::mk_message(client, ixs, &owner, opts).await;
+    }
+    let asset_proof = proof::get(client, pubkey).await?;
+    // The transaction changes the leaf of the asset, which invalidates its
+    // cached proof
+    proof::invalidate(pubkey);
 
     let leaf_delegate = asset.ownership.delegate.unwrap_or(asset.ownership.owner);
     let merkle_tree = asset_proof.tree_id;
@@ -184,12 +447,11 @@ pub async fn transfer_message<C: AsRef<SolanaRpcClient> + AsRef<DasClient>>(
 
     let ixs = &[
         compute_budget_instruction(200_000),
-        compute_price_instruction_for_accounts(client, &priority_fee_accounts, opts.fee_range())
-            .await?,
+        compute_price_instruction_for_accounts(client, &priority_fee_accounts, opts).await?,
         transfer_ix,
     ];
 
-    message::mk_message(client, ixs, &opts.lut_addresses, &asset.ownership.owner).await
+    message::mk_message(client, ixs, opts, &asset.ownership.owner).await
 }
 
 pub async fn transfer<C: AsRef<SolanaRpcClient> + AsRef<DasClient>>(
@@ -200,17 +462,48 @@ pub async fn transfer<C: AsRef<SolanaRpcClient> + AsRef<DasClient>>(
     opts: &TransactionOpts,
 ) -> Result<(VersionedTransaction, u64), Error> {
     let (msg, block_height) = transfer_message(client, pubkey, recipient, opts).await?;
-    let txn = VersionedTransaction::try_new(msg, &[keypair])?;
+    let txn = tx::partial_sign(msg, &[keypair])?;
     Ok((txn, block_height))
 }
 
 /// Get an unsigned burn transaction for an asset
+///
+/// Compressed assets are burned with bubblegum. For regular NFTs the token
+/// is burned and the token account of the owner closed, returning its rent
+/// to the owner. Programmable NFTs are not supported.
 pub async fn burn_message<C: AsRef<SolanaRpcClient> + AsRef<DasClient>>(
     client: &C,
     pubkey: &Pubkey,
     opts: &TransactionOpts,
 ) -> Result<(message::VersionedMessage, u64), Error> {
-    let (asset, asset_proof) = get_with_proof(client, pubkey).await?;
+    let asset = get(client, pubkey).await?;
+    if !asset.compression.is_compressed()? {
+        let owner = asset.ownership.owner;
+        let token_account = token::account_for(client, &asset).await?;
+        let ixs = vec![
+            spl_token::instruction::burn_checked(
+                &spl_token::id(),
+                &token_account,
+                &asset.id,
+                &owner,
+                &[],
+                1,
+                0,
+            )?,
+            spl_token::instruction::close_account(
+                &spl_token::id(),
+                &token_account,
+                &owner,
+                &owner,
+                &[],
+            )?,
+        ];
+        return token::mk_message(client, ixs, &owner, opts).await;
+    }
+    let asset_proof = proof::get(client, pubkey).await?;
+    // The transaction changes the leaf of the asset, which invalidates its
+    // cached proof
+    proof::invalidate(pubkey);
 
     let leaf_delegate = asset.ownership.delegate.unwrap_or(asset.ownership.owner);
     let merkle_tree = asset_proof.tree_id;
@@ -240,12 +533,11 @@ pub async fn burn_message<C: AsRef<SolanaRpcClient> + AsRef<DasClient>>(
 
     let ixs = &[
         compute_budget_instruction(100_000),
-        compute_price_instruction_for_accounts(client, &priority_fee_accounts, opts.fee_range())
-            .await?,
+        compute_price_instruction_for_accounts(client, &priority_fee_accounts, opts).await?,
         ix,
     ];
 
-    message::mk_message(client, ixs, &opts.lut_addresses, &asset.ownership.owner).await
+    message::mk_message(client, ixs, opts, &asset.ownership.owner).await
 }
 
 pub async fn burn<C: AsRef<SolanaRpcClient> + AsRef<DasClient>>(
@@ -255,16 +547,90 @@ pub async fn burn<C: AsRef<SolanaRpcClient> + AsRef<DasClient>>(
     opts: &TransactionOpts,
 ) -> Result<(VersionedTransaction, u64), Error> {
     let (msg, block_height) = burn_message(client, pubkey, opts).await?;
-    let txn = VersionedTransaction::try_new(msg, &[keypair])?;
+    let txn = tx::partial_sign(msg, &[keypair])?;
     Ok((txn, block_height))
 }
 
+/// Helpers for assets that are regular, uncompressed, NFTs held in a token
+/// account
+mod token {
+    use super::*;
+
+    /// Compute units for the token instructions of an NFT transfer or burn
+    const TOKEN_INSTRUCTIONS_BUDGET: u32 = 60_000;
+
+    /// The token account of the owner holding the given NFT. Only NFTs held
+    /// in the associated token account of the owner, under the token
+    /// program, are supported.
+    pub async fn account_for<C: AsRef<SolanaRpcClient>>(
+        client: &C,
+        asset: &Asset,
+    ) -> Result<Pubkey, Error> {
+        if asset.interface.as_deref() == Some("ProgrammableNFT") {
+            return Err(DecodeError::other("programmable NFTs are not supported").into());
+        }
+        let token_account = spl_associated_token_account::get_associated_token_address(
+            &asset.ownership.owner,
+            &asset.id,
+        );
+        let account = client
+            .as_ref()
+            .get_multiple_accounts(&[token_account])
+            .await?
+            .into_iter()
+            .next()
+            .flatten()
+            .ok_or_else(|| {
+                DecodeError::other(format!("no associated token account holds {}", asset.id))
+            })?;
+        if account.owner != spl_token::id() {
+            return Err(
+                DecodeError::other(format!("unsupported token program {}", account.owner)).into(),
+            );
+        }
+        Ok(token_account)
+    }
+
+    pub async fn mk_message<C: AsRef<SolanaRpcClient>>(
+        client: &C,
+        token_ixs: Vec<Instruction>,
+        payer: &Pubkey,
+        opts: &TransactionOpts,
+    ) -> Result<(message::VersionedMessage, u64), Error> {
+        let accounts: Vec<AccountMeta> = token_ixs
+            .iter()
+            .flat_map(|ix| ix.accounts.iter().cloned())
+            .collect();
+        let mut ixs = vec![
+            compute_budget_instruction(TOKEN_INSTRUCTIONS_BUDGET),
+            compute_price_instruction_for_accounts(client, &accounts, opts).await?,
+        ];
+        ixs.extend(token_ixs);
+        message::mk_message(client, &ixs, opts, payer).await
+    }
+}
+
 #[derive(Deserialize, Serialize, Clone)]
 pub struct AssetPage {
     pub total: u32,
     pub limit: u32,
+    /// The page number, absent when paging with a cursor
+    #[serde(default)]
     pub page: u32,
     pub items: Vec<Asset>,
+    /// The cursor for the next page, if the provider pages with cursors
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+}
+
+/// A page of the transaction signatures of a compressed asset. Each item is a
+/// pair of the signature and the kind of asset instruction, like "Transfer".
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct AssetSignaturePage {
+    pub total: u32,
+    pub limit: u32,
+    pub page: u32,
+    pub items: Vec<(String, String)>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -278,6 +644,10 @@ pub struct Asset {
     pub grouping: Vec<AssetGroup>,
     #[serde(skip_serializing_if = "std::ops::Not::not", default)]
     pub burnt: bool,
+    /// The kind of asset as reported by DAS, like "V1_NFT" or
+    /// "ProgrammableNFT"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interface: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -290,18 +660,28 @@ pub struct AssetCreator {
 
 pub type Hash = [u8; 32];
 
+/// The compression details of an asset. Uncompressed assets have no tree,
+/// so their hashes and tree are all zeroes.
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct AssetCompression {
+    /// Whether the asset is compressed, or `None` when DAS did not say
+    pub compressed: Option<bool>,
     #[serde(with = "serde_hash")]
     pub data_hash: Hash,
     #[serde(with = "serde_hash")]
     pub creator_hash: Hash,
     pub leaf_id: u64,
-    #[serde(with = "serde_pubkey")]
+    #[serde(with = "serde_tree")]
     pub tree: Pubkey,
 }
 
 impl AssetCompression {
+    /// Whether the asset is compressed, failing when that is not known
+    pub fn is_compressed(&self) -> StdResult<bool, DecodeError> {
+        self.compressed
+            .ok_or_else(|| DecodeError::other("asset compression unknown"))
+    }
+
     pub fn leaf_id(&self) -> StdResult<u32, DecodeError> {
         self.leaf_id.try_into().map_err(DecodeError::from)
     }
@@ -404,7 +784,9 @@ pub struct AssetContent {
 pub struct AssetMetadata {
     #[serde(default)]
     pub attributes: Vec<AssetMetadataAttribute>,
+    #[serde(default)]
     pub name: String,
+    #[serde(default)]
     pub symbol: String,
 }
 
@@ -442,6 +824,9 @@ pub mod serde_hash {
         D: serde::Deserializer<'de>,
     {
         let str = String::deserialize(deser)?;
+        if str.is_empty() {
+            return Ok(Hash::default());
+        }
         bs58::decode(&str)
             .into_vec()
             .map_err(|_| de::Error::custom("invalid hash"))?
@@ -450,3 +835,76 @@ pub mod serde_hash {
             .map_err(|_| de::Error::custom("invalid hash"))
     }
 }
+
+/// Like [`serde_pubkey`], but reads the empty tree of uncompressed assets as
+/// the default pubkey
+mod serde_tree {
+    use super::*;
+    use serde::de;
+
+    pub fn serialize<S>(value: &Pubkey, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serde_pubkey::serialize(value, serializer)
+    }
+
+    pub fn deserialize<'de, D>(deser: D) -> std::result::Result<Pubkey, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let str = String::deserialize(deser)?;
+        if str.is_empty() {
+            return Ok(Pubkey::default());
+        }
+        Pubkey::from_str(&str).map_err(|_| de::Error::custom("invalid public key"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uncompressed_asset() {
+        let asset: Asset = serde_json::from_value(serde_json::json!({
+            "id": "8vpLfAzhXK1T5Sn4DKXtJzJ6XNb5UXhYRfKAmWm5Rswm",
+            "interface": "V1_NFT",
+            "compression": {
+                "compressed": false,
+                "data_hash": "",
+                "creator_hash": "",
+                "leaf_id": 0,
+                "tree": "",
+            },
+            "creators": [],
+            "ownership": {
+                "owner": "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin",
+                "delegate": null,
+            },
+            "content": {
+                "metadata": { "name": "Position", "symbol": "" },
+                "json_uri": "https://example.com/position.json",
+            },
+            "grouping": [],
+        }))
+        .expect("asset");
+        assert_eq!(Some(false), asset.compression.compressed);
+        assert!(!asset.compression.is_compressed().expect("compression"));
+        assert_eq!(Pubkey::default(), asset.compression.tree);
+        assert_eq!(Some("V1_NFT"), asset.interface.as_deref());
+    }
+
+    #[test]
+    fn unknown_compression() {
+        let compression: AssetCompression = serde_json::from_value(serde_json::json!({
+            "data_hash": "",
+            "creator_hash": "",
+            "leaf_id": 0,
+            "tree": "",
+        }))
+        .expect("compression");
+        assert_eq!(None, compression.compressed);
+        assert!(compression.is_compressed().is_err());
+    }
+}
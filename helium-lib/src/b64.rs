@@ -3,18 +3,21 @@ use base64::{
     engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
     Engine,
 };
+#[cfg(feature = "client")]
 use helium_proto::Message;
 
 pub fn encode<T: AsRef<[u8]>>(v: T) -> String {
     STANDARD.encode(v.as_ref())
 }
 
+#[cfg(feature = "client")]
 pub fn encode_message<T: Message>(v: &T) -> Result<String, EncodeError> {
     let mut buf = vec![];
     v.encode(&mut buf).map_err(EncodeError::from)?;
     Ok(STANDARD.encode(buf))
 }
 
+#[cfg(feature = "client")]
 pub fn decode_message<T>(v: &str) -> Result<T, DecodeError>
 where
     T: Message + Default,
@@ -0,0 +1,130 @@
+//! Synchronous wrappers over [`client::Client`] and the common flows built
+//! on it, for callers that do not run an async runtime.
+//!
+//! A [`Client`] drives its requests on a single threaded runtime it owns, so
+//! it must not be used from within an async context. Flows without a wrapper
+//! here can be run with [`Client::block_on`].
+
+use crate::{
+    client,
+    entity_key::{EncodedEntityKey, KeySerialization},
+    error::{EncodeError, Error},
+    hotspot::{self, Hotspot},
+    keypair::{Keypair, Pubkey, Signature},
+    reward::{self, ClaimableToken, OracleReward},
+    solana_client::rpc_config::RpcSendTransactionConfig,
+    solana_sdk::transaction::VersionedTransaction,
+    token::{self, TokenAmount, TokenBalance},
+    tx, TransactionOpts,
+};
+use std::{collections::HashMap, future::Future};
+
+pub struct Client {
+    inner: client::Client,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl TryFrom<&str> for Client {
+    type Error = Error;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::new(client::Client::try_from(value)?)
+    }
+}
+
+impl Client {
+    /// Wrap an async client, initializing the library caches for it
+    pub fn new(inner: client::Client) -> Result<Self, Error> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|err| EncodeError::other(format!("runtime: {err}")))?;
+        crate::init(inner.solana_client.clone())?;
+        Ok(Self { inner, runtime })
+    }
+
+    /// The wrapped async client
+    pub fn inner(&self) -> &client::Client {
+        &self.inner
+    }
+
+    /// Run any future to completion on the runtime of this client
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    pub fn balance_for_address(&self, pubkey: &Pubkey) -> Result<Option<TokenBalance>, Error> {
+        self.block_on(token::balance_for_address(&self.inner, pubkey))
+    }
+
+    pub fn balance_for_addresses(&self, pubkeys: &[Pubkey]) -> Result<Vec<TokenBalance>, Error> {
+        self.block_on(token::balance_for_addresses(&self.inner, pubkeys))
+    }
+
+    pub fn hotspots_for_owner(&self, owner: &Pubkey) -> Result<Vec<Hotspot>, Error> {
+        self.block_on(hotspot::for_owner(&self.inner, owner))
+    }
+
+    pub fn pending_rewards(
+        &self,
+        token: ClaimableToken,
+        entity_keys: &[String],
+        encoding: KeySerialization,
+    ) -> Result<HashMap<String, OracleReward>, Error> {
+        self.block_on(reward::pending_many(
+            &self.inner,
+            token,
+            entity_keys,
+            encoding,
+        ))
+    }
+
+    /// Build and sign a transfer, see [`token::transfer`]
+    pub fn transfer(
+        &self,
+        transfers: &[(Pubkey, TokenAmount)],
+        keypair: &Keypair,
+        opts: &TransactionOpts,
+    ) -> Result<(VersionedTransaction, u64), Error> {
+        self.block_on(token::transfer(&self.inner, transfers, keypair, opts))
+    }
+
+    /// Build and sign a rewards claim, see [`reward::claim`]
+    pub fn claim(
+        &self,
+        token: ClaimableToken,
+        amount: Option<u64>,
+        entity_key: &EncodedEntityKey,
+        keypair: &Keypair,
+        opts: &TransactionOpts,
+    ) -> Result<Option<(VersionedTransaction, u64)>, Error> {
+        self.block_on(reward::claim(
+            &self.inner,
+            token,
+            amount,
+            entity_key,
+            keypair,
+            opts,
+        ))
+    }
+
+    /// Send a signed transaction and wait until it reaches the commitment
+    /// of the client, or can no longer land after the given block height
+    pub fn send_and_confirm(
+        &self,
+        tx: &VersionedTransaction,
+        last_valid_block_height: u64,
+    ) -> Result<Signature, Error> {
+        self.block_on(async {
+            let mut tracked =
+                tx::TrackedTransaction::send(&self.inner, tx, RpcSendTransactionConfig::default())
+                    .await?
+                    .with_last_valid_block_height(last_valid_block_height);
+            let commitment = self.inner.solana_client.commitment();
+            tracked.confirm(&self.inner, commitment).await?;
+            match tracked.error {
+                Some(err) => Err(err.into()),
+                None => Ok(tracked.signature),
+            }
+        })
+    }
+}
@@ -1,15 +1,869 @@
 use crate::{
     anchor_lang::{InstructionData, ToAccountMetas},
-    client::SolanaRpcClient,
-    error::Error,
+    anchor_spl,
+    client::{self, GetAnchorAccount, SolanaRpcClient},
+    dao::SubDao,
+    error::{DecodeError, EncodeError, Error},
     hexboosting,
     hexboosting::accounts::StartBoostV0,
-    keypair::{Keypair, Pubkey},
-    message, priority_fee,
+    keypair::{serde_pubkey, Keypair, Pubkey},
+    message, mobile_entity_manager, priority_fee,
     solana_sdk::{instruction::Instruction, signer::Signer, transaction::VersionedTransaction},
-    TransactionOpts,
+    token::{price::Price, Token, TokenAmount},
+    tx, TransactionOpts,
 };
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::prelude::*;
+use serde::Serialize;
+
+/// The name of the carrier boosts are made under by default
+pub const HELIUM_MOBILE_CARRIER: &str = "Helium Mobile";
+
+/// Boost prices are USD amounts with this many decimals
+const BOOST_PRICE_DECIMALS: u32 = 6;
+
+pub fn boost_config_key() -> Pubkey {
+    let (key, _) = Pubkey::find_program_address(
+        &[b"boost_config", Token::Mobile.mint().as_ref()],
+        &hexboosting::id(),
+    );
+    key
+}
+
+pub fn carrier_key(name: &str) -> Pubkey {
+    let (key, _) = Pubkey::find_program_address(
+        &[b"carrier", SubDao::Mobile.key().as_ref(), name.as_bytes()],
+        &mobile_entity_manager::id(),
+    );
+    key
+}
+
+/// A service provider that hexes are boosted under. Boosts must be signed
+/// by the hex boost authority of the carrier.
+#[derive(Debug, Clone, Serialize)]
+pub struct Carrier {
+    #[serde(with = "serde_pubkey")]
+    pub address: Pubkey,
+    pub name: String,
+    #[serde(with = "serde_pubkey")]
+    pub hexboost_authority: Pubkey,
+    pub approved: bool,
+}
+
+impl Carrier {
+    pub fn from_account(address: Pubkey, account: &mobile_entity_manager::CarrierV0) -> Self {
+        Self {
+            address,
+            name: account.name.clone(),
+            hexboost_authority: account.hexboost_authority,
+            approved: account.approved,
+        }
+    }
+
+    /// The keypairs to sign a boost with: the payer, and the given authority
+    /// when the payer is not the hex boost authority of this carrier.
+    fn signers<'a>(
+        &self,
+        keypair: &'a Keypair,
+        authority: Option<&'a Keypair>,
+    ) -> Result<Vec<&'a dyn Signer>, Error> {
+        if keypair.pubkey() == self.hexboost_authority {
+            return Ok(vec![&**keypair]);
+        }
+        match authority {
+            Some(authority) if authority.pubkey() == self.hexboost_authority => {
+                Ok(vec![&**keypair, &**authority])
+            }
+            _ => Err(EncodeError::other(format!(
+                "boosts for carrier {} must be signed by its hex boost authority {}",
+                self.name, self.hexboost_authority
+            ))
+            .into()),
+        }
+    }
+}
+
+pub async fn carrier<C: GetAnchorAccount>(client: &C, name: &str) -> Result<Carrier, Error> {
+    let address = carrier_key(name);
+    let account = client
+        .anchor_account::<mobile_entity_manager::CarrierV0>(&address)
+        .await?;
+    Ok(Carrier::from_account(address, &account))
+}
+
+pub fn boosted_hex_key(
+    carrier: &Pubkey,
+    hex: h3o::CellIndex,
+    device_type: BoostDeviceType,
+) -> Pubkey {
+    let (key, _) = Pubkey::find_program_address(
+        &[
+            b"boosted_hex",
+            boost_config_key().as_ref(),
+            carrier.as_ref(),
+            &u64::from(hex).to_le_bytes(),
+            &[device_type as u8],
+        ],
+        &hexboosting::id(),
+    );
+    key
+}
+
+pub mod serde_cell_index {
+    pub fn serialize<S>(value: &h3o::CellIndex, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        serializer.serialize_str(&value.to_string())
+    }
+}
+
+/// The device types a hex can be boosted for. Boosts for each device type of
+/// a hex are independent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "snake_case")]
+pub enum BoostDeviceType {
+    CbrsIndoor = 0,
+    CbrsOutdoor = 1,
+    WifiIndoor = 2,
+    WifiOutdoor = 3,
+}
+
+impl BoostDeviceType {
+    pub const ALL: [Self; 4] = [
+        Self::CbrsIndoor,
+        Self::CbrsOutdoor,
+        Self::WifiIndoor,
+        Self::WifiOutdoor,
+    ];
+}
+
+impl std::fmt::Display for BoostDeviceType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            Self::CbrsIndoor => "cbrs_indoor",
+            Self::CbrsOutdoor => "cbrs_outdoor",
+            Self::WifiIndoor => "wifi_indoor",
+            Self::WifiOutdoor => "wifi_outdoor",
+        };
+        f.write_str(str)
+    }
+}
+
+impl From<BoostDeviceType> for hexboosting::DeviceTypeV0 {
+    fn from(value: BoostDeviceType) -> Self {
+        match value {
+            BoostDeviceType::CbrsIndoor => Self::CbrsIndoor,
+            BoostDeviceType::CbrsOutdoor => Self::CbrsOutdoor,
+            BoostDeviceType::WifiIndoor => Self::WifiIndoor,
+            BoostDeviceType::WifiOutdoor => Self::WifiOutdoor,
+        }
+    }
+}
+
+impl From<hexboosting::DeviceTypeV0> for BoostDeviceType {
+    fn from(value: hexboosting::DeviceTypeV0) -> Self {
+        match value {
+            hexboosting::DeviceTypeV0::CbrsIndoor => Self::CbrsIndoor,
+            hexboosting::DeviceTypeV0::CbrsOutdoor => Self::CbrsOutdoor,
+            hexboosting::DeviceTypeV0::WifiIndoor => Self::WifiIndoor,
+            hexboosting::DeviceTypeV0::WifiOutdoor => Self::WifiOutdoor,
+        }
+    }
+}
+
+/// The global boost configuration
+#[derive(Debug, Clone, Serialize)]
+pub struct BoostConfig {
+    #[serde(with = "serde_pubkey")]
+    pub address: Pubkey,
+    #[serde(with = "serde_pubkey")]
+    pub price_oracle: Pubkey,
+    #[serde(with = "serde_pubkey")]
+    pub payment_mint: Pubkey,
+    /// The USD price of boosting a hex by 1x for one period
+    pub boost_price: Decimal,
+    /// Length of a boost period in seconds
+    pub period_length: u32,
+    pub minimum_periods: u16,
+    #[serde(with = "serde_pubkey")]
+    pub start_authority: Pubkey,
+}
+
+impl BoostConfig {
+    pub fn from_account(address: Pubkey, account: &hexboosting::BoostConfigV0) -> Self {
+        Self {
+            address,
+            price_oracle: account.price_oracle,
+            payment_mint: account.payment_mint,
+            boost_price: Decimal::new(account.boost_price as i64, BOOST_PRICE_DECIMALS),
+            period_length: account.period_length,
+            minimum_periods: account.minimum_periods,
+            start_authority: account.start_authority,
+        }
+    }
+
+    pub fn period_duration(&self) -> Duration {
+        Duration::seconds(self.period_length.into())
+    }
+}
+
+pub async fn boost_config<C: GetAnchorAccount>(client: &C) -> Result<BoostConfig, Error> {
+    let address = boost_config_key();
+    let account = client
+        .anchor_account::<hexboosting::BoostConfigV0>(&address)
+        .await?;
+    Ok(BoostConfig::from_account(address, &account))
+}
+
+/// The on-chain version of a boosted hex account
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoostedHexVersion {
+    /// Boosts made before device types were introduced, applying to all
+    /// device types
+    V0,
+    V1,
+}
+
+/// The boosts of a hex for a device type, normalized over the on-chain
+/// boosted hex account versions
+#[derive(Debug, Clone, Serialize)]
+pub struct BoostedHex {
+    #[serde(with = "serde_pubkey")]
+    pub address: Pubkey,
+    pub version: BoostedHexVersion,
+    #[serde(with = "serde_cell_index")]
+    pub hex: h3o::CellIndex,
+    /// The boosted device type, or none for v0 boosts which apply to all
+    /// device types
+    pub device_type: Option<BoostDeviceType>,
+    /// Start of the first boost period, set once the boost is activated
+    pub start: Option<DateTime<Utc>>,
+    /// Boost multiplier of each period, starting at the first period
+    pub boosts_by_period: Vec<u8>,
+}
+
+impl BoostedHex {
+    pub fn from_account(
+        address: Pubkey,
+        account: &hexboosting::BoostedHexV1,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            address,
+            version: BoostedHexVersion::V1,
+            hex: h3o::CellIndex::try_from(account.location).map_err(DecodeError::other)?,
+            device_type: Some(account.device_type.clone().into()),
+            start: start_time(account.start_ts),
+            boosts_by_period: account.boosts_by_period.clone(),
+        })
+    }
+
+    pub fn from_account_v0(
+        address: Pubkey,
+        account: &hexboosting::BoostedHexV0,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            address,
+            version: BoostedHexVersion::V0,
+            hex: h3o::CellIndex::try_from(account.location).map_err(DecodeError::other)?,
+            device_type: None,
+            start: start_time(account.start_ts),
+            boosts_by_period: account.boosts_by_period.clone(),
+        })
+    }
+
+    /// Decode a boosted hex account of any version from its raw account data
+    pub fn decode(address: Pubkey, data: &[u8]) -> Result<Self, Error> {
+        use crate::anchor_lang::{AccountDeserialize, Discriminator};
+        let mut data = data;
+        match data.get(..8) {
+            Some(discriminator) if discriminator == hexboosting::BoostedHexV1::DISCRIMINATOR => {
+                let account = hexboosting::BoostedHexV1::try_deserialize(&mut data)
+                    .map_err(DecodeError::other)?;
+                Self::from_account(address, &account)
+            }
+            Some(discriminator) if discriminator == hexboosting::BoostedHexV0::DISCRIMINATOR => {
+                let account = hexboosting::BoostedHexV0::try_deserialize(&mut data)
+                    .map_err(DecodeError::other)?;
+                Self::from_account_v0(address, &account)
+            }
+            _ => Err(DecodeError::other(format!("{address} is not a boosted hex account")).into()),
+        }
+    }
+
+    /// The end of the last boosted period, if the boost has started
+    pub fn end(&self, config: &BoostConfig) -> Option<DateTime<Utc>> {
+        self.start
+            .map(|start| start + config.period_duration() * self.boosts_by_period.len() as i32)
+    }
+
+    /// The index of the period the boost is in at the given time. Before the
+    /// boost is activated this is the first period.
+    pub fn current_period(&self, config: &BoostConfig, now: DateTime<Utc>) -> usize {
+        match self.start {
+            Some(start) if now > start && config.period_length > 0 => {
+                ((now - start).num_seconds() / config.period_length as i64) as usize
+            }
+            _ => 0,
+        }
+    }
+}
+
+/// Boosts that have not been activated have a zero start timestamp
+fn start_time(start_ts: i64) -> Option<DateTime<Utc>> {
+    (start_ts > 0)
+        .then(|| DateTime::from_timestamp(start_ts, 0))
+        .flatten()
+}
+
+/// Get the v1 boosts of a hex for a device type under the given carrier, if
+/// the hex has been boosted.
+pub async fn boosted_hex<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    carrier: &Pubkey,
+    hex: h3o::CellIndex,
+    device_type: BoostDeviceType,
+) -> Result<Option<BoostedHex>, Error> {
+    let address = boosted_hex_key(carrier, hex, device_type);
+    match client
+        .anchor_accounts::<hexboosting::BoostedHexV1>(&[address])
+        .await?
+        .pop()
+        .flatten()
+    {
+        Some(account) => Ok(Some(BoostedHex::from_account(address, &account)?)),
+        None => Ok(None),
+    }
+}
+
+/// The address of the v0 boosts of a hex, which predate device types
+pub fn boosted_hex_v0_key(carrier: &Pubkey, hex: h3o::CellIndex) -> Pubkey {
+    let (key, _) = Pubkey::find_program_address(
+        &[
+            b"boosted_hex",
+            boost_config_key().as_ref(),
+            carrier.as_ref(),
+            &u64::from(hex).to_le_bytes(),
+        ],
+        &hexboosting::id(),
+    );
+    key
+}
+
+/// Get all boosts of a hex under the given carrier, including v0 boosts.
+/// Only device types the hex has been boosted for are returned.
+pub async fn boosted_hexes<C: AsRef<SolanaRpcClient>>(
+    client: &C,
+    carrier: &Pubkey,
+    hex: h3o::CellIndex,
+) -> Result<Vec<BoostedHex>, Error> {
+    let addresses = BoostDeviceType::ALL
+        .map(|device_type| boosted_hex_key(carrier, hex, device_type))
+        .into_iter()
+        .chain([boosted_hex_v0_key(carrier, hex)])
+        .collect::<Vec<_>>();
+    let accounts = client.as_ref().get_multiple_accounts(&addresses).await?;
+    addresses
+        .into_iter()
+        .zip(accounts)
+        .filter_map(|(address, account)| account.map(|account| (address, account)))
+        .map(|(address, account)| BoostedHex::decode(address, &account.data))
+        .collect()
+}
+
+/// Offset of the boost config key in a v1 boosted hex account, after the
+/// discriminator and device type. V0 accounts start with the boost config.
+const BOOSTED_HEX_CONFIG_OFFSET: usize = 8 + 1;
+const BOOSTED_HEX_V0_CONFIG_OFFSET: usize = 8;
+
+/// Get all boosted hexes, optionally only those boosted for the given device
+/// type.
+///
+/// Boosted hexes are plain program accounts, so they are enumerated with a
+/// filtered `getProgramAccounts` on the MOBILE boost config, which requires
+/// an RPC provider that allows program account scans.
+pub async fn all_boosted_hexes<C: AsRef<SolanaRpcClient>>(
+    client: &C,
+    device_type: Option<BoostDeviceType>,
+) -> Result<Vec<BoostedHex>, Error> {
+    let boost_config = boost_config_key();
+    let mut boosted = client::anchor_program_accounts::<_, hexboosting::BoostedHexV1>(
+        client,
+        &hexboosting::id(),
+        BOOSTED_HEX_CONFIG_OFFSET,
+        &boost_config,
+    )
+    .await?
+    .into_iter()
+    .map(|(address, account)| BoostedHex::from_account(address, &account))
+    .collect::<Result<Vec<_>, _>>()?;
+    let v0 = client::anchor_program_accounts::<_, hexboosting::BoostedHexV0>(
+        client,
+        &hexboosting::id(),
+        BOOSTED_HEX_V0_CONFIG_OFFSET,
+        &boost_config,
+    )
+    .await?
+    .into_iter()
+    .map(|(address, account)| BoostedHex::from_account_v0(address, &account))
+    .collect::<Result<Vec<_>, _>>()?;
+    boosted.extend(v0);
+    // v0 boosts apply to all device types
+    if let Some(device_type) = device_type {
+        boosted.retain(
+            |boosted| !matches!(boosted.device_type, Some(boosted) if boosted != device_type),
+        );
+    }
+    Ok(boosted)
+}
+
+/// The cost of boosting a hex
+#[derive(Debug, Clone, Serialize)]
+pub struct BoostCost {
+    /// Sum of the boost multipliers over all boosted periods
+    pub boost_units: u64,
+    pub usd: Decimal,
+    pub mobile: TokenAmount,
+    pub mobile_price: Decimal,
+}
+
+impl BoostCost {
+    /// Compute the cost of the given total boost multiplier units at the
+    /// given MOBILE price.
+    pub fn from_price(
+        config: &BoostConfig,
+        boost_units: u64,
+        price: &Price,
+    ) -> Result<Self, Error> {
+        if price.price <= Decimal::ZERO {
+            return Err(DecodeError::other("invalid MOBILE price").into());
+        }
+        let usd = config.boost_price * Decimal::from(boost_units);
+        let mobile_scale = Decimal::from(10u64.pow(Token::Mobile.decimals().into()));
+        let mobile = (usd / price.price * mobile_scale)
+            .ceil()
+            .to_u64()
+            .ok_or_else(|| DecodeError::other("boost cost out of range"))?;
+        Ok(Self {
+            boost_units,
+            usd,
+            mobile: Token::Mobile.amount(mobile),
+            mobile_price: price.price,
+        })
+    }
+}
+
+/// Estimate the cost of boosting a hex at a 1x multiplier for a number of
+/// periods, using the on-chain boost price and the current MOBILE oracle
+/// price.
+///
+/// The boost config has a single price for all device types. Note that new
+/// boosts must cover at least [`BoostConfig::minimum_periods`] periods.
+pub async fn estimate_cost<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    periods: u16,
+) -> Result<BoostCost, Error> {
+    let config = boost_config(client).await?;
+    let price = crate::token::price::get(client, Token::Mobile).await?;
+    BoostCost::from_price(&config, periods.into(), &price)
+}
+
+/// A boost to add to a hex: the given multiplier for a number of periods
+#[derive(Debug, Clone, Copy)]
+pub struct HexBoost {
+    pub hex: h3o::CellIndex,
+    pub device_type: BoostDeviceType,
+    pub periods: u16,
+    pub multiplier: u8,
+}
+
+impl HexBoost {
+    /// A boost of the given hex and device type at a 1x multiplier
+    pub fn new(hex: h3o::CellIndex, device_type: BoostDeviceType, periods: u16) -> Self {
+        Self {
+            hex,
+            device_type,
+            periods,
+            multiplier: 1,
+        }
+    }
+
+    pub fn with_multiplier(mut self, multiplier: u8) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// The periods and amounts to boost, appended after the periods already
+    /// boosted in the given existing boost, or after the current period if
+    /// the existing boost has lapsed.
+    pub fn amounts(
+        &self,
+        existing: Option<&BoostedHex>,
+        config: &BoostConfig,
+        now: DateTime<Utc>,
+    ) -> Vec<hexboosting::BoostAmountV0> {
+        let first = existing
+            .map(|boosted| {
+                boosted
+                    .boosts_by_period
+                    .len()
+                    .max(boosted.current_period(config, now))
+            })
+            .unwrap_or_default();
+        (first..first + self.periods as usize)
+            .map(|period| hexboosting::BoostAmountV0 {
+                period: period as u16,
+                amount: self.multiplier,
+            })
+            .collect()
+    }
+
+    pub fn boost_units(&self) -> u64 {
+        self.periods as u64 * self.multiplier as u64
+    }
+}
+
+pub const BOOST_INSTRUCTION_BUDGET: u32 = 200_000;
+
+/// Construct the instruction that boosts a hex, paying for the boost by
+/// burning MOBILE from the payer.
+pub fn boost_hex_instruction(
+    config: &BoostConfig,
+    carrier: &Carrier,
+    boost: &HexBoost,
+    amounts: Vec<hexboosting::BoostAmountV0>,
+    payer: &Pubkey,
+) -> Instruction {
+    let accounts = hexboosting::accounts::BoostHexV1 {
+        payer: *payer,
+        boost_config: config.address,
+        carrier: carrier.address,
+        hexboost_authority: carrier.hexboost_authority,
+        price_oracle: config.price_oracle,
+        payment_mint: config.payment_mint,
+        payment_account: Token::Mobile.associated_token_adress(payer),
+        boosted_hex: boosted_hex_key(&carrier.address, boost.hex, boost.device_type),
+        system_program: solana_sdk::system_program::ID,
+        token_program: anchor_spl::token::ID,
+    };
+    Instruction {
+        program_id: hexboosting::id(),
+        accounts: accounts.to_account_metas(None),
+        data: hexboosting::instruction::BoostHexV1 {
+            _args: hexboosting::BoostHexArgsV1 {
+                location: u64::from(boost.hex),
+                version: 0,
+                amounts,
+                device_type: boost.device_type.into(),
+            },
+        }
+        .data(),
+    }
+}
+
+/// A planned boost of a hex with its cost
+#[derive(Debug, Clone, Serialize)]
+pub struct BoostPlan {
+    #[serde(with = "serde_pubkey")]
+    pub boosted_hex: Pubkey,
+    #[serde(with = "serde_cell_index")]
+    pub hex: h3o::CellIndex,
+    pub device_type: BoostDeviceType,
+    pub multiplier: u8,
+    /// First and last (inclusive) period index being boosted
+    pub periods: (u16, u16),
+    pub cost: BoostCost,
+    /// The end of the boosted periods, when the boost has already started
+    pub end: Option<DateTime<Utc>>,
+}
+
+/// Construct a message that boosts a hex under the given carrier for the
+/// given number of periods, appending to any existing boost of the hex.
+///
+/// Returns the message, its block height and the boost plan including the
+/// estimated MOBILE cost at the current MOBILE price.
+pub async fn boost_hex_message<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    carrier: &Carrier,
+    boost: &HexBoost,
+    payer: &Pubkey,
+    opts: &TransactionOpts,
+) -> Result<(message::VersionedMessage, u64, BoostPlan), Error> {
+    let config = boost_config(client).await?;
+    let existing = boosted_hex(client, &carrier.address, boost.hex, boost.device_type).await?;
+    mk_boost_message(
+        client,
+        &config,
+        carrier,
+        boost,
+        existing.as_ref(),
+        payer,
+        opts,
+    )
+    .await
+}
+
+/// Construct a message that extends the existing boost of a hex by the given
+/// number of periods.
+///
+/// The payer does not have to be the wallet that originally boosted the hex;
+/// it only pays for the added periods. When no multiplier is given the
+/// multiplier of the last boosted period is used. Fails if the hex has not
+/// been boosted for the given device type.
+pub async fn extend_boost_message<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    carrier: &Carrier,
+    boosted: &BoostedHex,
+    periods: u16,
+    multiplier: Option<u8>,
+    payer: &Pubkey,
+    opts: &TransactionOpts,
+) -> Result<(message::VersionedMessage, u64, BoostPlan), Error> {
+    let Some(device_type) = boosted.device_type else {
+        return Err(EncodeError::other("v0 boosts can not be extended").into());
+    };
+    let config = boost_config(client).await?;
+    let multiplier = multiplier
+        .or_else(|| boosted.boosts_by_period.last().copied())
+        .unwrap_or(1);
+    let boost = HexBoost {
+        hex: boosted.hex,
+        device_type,
+        periods,
+        multiplier,
+    };
+    mk_boost_message(client, &config, carrier, &boost, Some(boosted), payer, opts).await
+}
+
+/// Validate a boost and construct its instruction and plan. The minimum
+/// number of periods only applies to boosts of hexes that are not boosted yet.
+fn plan_boost(
+    config: &BoostConfig,
+    carrier: &Carrier,
+    boost: &HexBoost,
+    existing: Option<&BoostedHex>,
+    price: &Price,
+    payer: &Pubkey,
+) -> Result<(Instruction, BoostPlan), Error> {
+    if !carrier.approved {
+        return Err(EncodeError::other(format!("carrier {} is not approved", carrier.name)).into());
+    }
+    if boost.periods == 0 {
+        return Err(EncodeError::other("boosts must be at least 1 period").into());
+    }
+    if boost.multiplier == 0 {
+        return Err(EncodeError::other("boost multiplier must be at least 1").into());
+    }
+    if existing.is_none() && boost.periods < config.minimum_periods {
+        return Err(EncodeError::other(format!(
+            "new boosts must be at least {} periods",
+            config.minimum_periods
+        ))
+        .into());
+    }
+    let amounts = boost.amounts(existing, config, Utc::now());
+    let cost = BoostCost::from_price(config, boost.boost_units(), price)?;
+    let first = amounts
+        .first()
+        .map(|amount| amount.period)
+        .unwrap_or_default();
+    let last = amounts
+        .last()
+        .map(|amount| amount.period)
+        .unwrap_or_default();
+    let plan = BoostPlan {
+        boosted_hex: boosted_hex_key(&carrier.address, boost.hex, boost.device_type),
+        hex: boost.hex,
+        device_type: boost.device_type,
+        multiplier: boost.multiplier,
+        periods: (first, last),
+        cost,
+        end: existing
+            .and_then(|boosted| boosted.start)
+            .map(|start| start + config.period_duration() * (last as i32 + 1)),
+    };
+    let ix = boost_hex_instruction(config, carrier, boost, amounts, payer);
+    Ok((ix, plan))
+}
+
+async fn mk_boost_message<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    config: &BoostConfig,
+    carrier: &Carrier,
+    boost: &HexBoost,
+    existing: Option<&BoostedHex>,
+    payer: &Pubkey,
+    opts: &TransactionOpts,
+) -> Result<(message::VersionedMessage, u64, BoostPlan), Error> {
+    let price = crate::token::price::get(client, Token::Mobile).await?;
+    let (ix, plan) = plan_boost(config, carrier, boost, existing, &price, payer)?;
+    let ixs = &[
+        priority_fee::compute_budget_instruction(BOOST_INSTRUCTION_BUDGET),
+        priority_fee::compute_price_instruction_for_accounts(client, &ix.accounts, opts).await?,
+        ix,
+    ];
+    let (msg, block_height) = message::mk_message(client, ixs, opts, payer).await?;
+    Ok((msg, block_height, plan))
+}
+
+/// Hexes that could not be boosted, with the reason why
+pub type RejectedBoosts = Vec<(HexBoost, Error)>;
+
+/// Boosts of a number of hexes under a carrier, packed into as few
+/// transactions as transaction size and compute limits allow
+pub struct PackedBoosts {
+    carrier: Carrier,
+    packer: tx::TransactionPacker,
+    /// The boost plans of each packed transaction
+    pub plans: Vec<Vec<BoostPlan>>,
+    pub rejected: RejectedBoosts,
+}
+
+impl PackedBoosts {
+    /// The number of packed transactions
+    pub fn len(&self) -> usize {
+        self.plans.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plans.is_empty()
+    }
+
+    /// Construct the message of the packed transaction at the given index.
+    /// Every message gets a fresh blockhash, so construct each one just
+    /// before sending it.
+    pub async fn message<C: AsRef<SolanaRpcClient>>(
+        &self,
+        client: &C,
+        index: usize,
+        opts: &TransactionOpts,
+    ) -> Result<(message::VersionedMessage, u64), Error> {
+        self.packer.message(client, index, opts).await
+    }
+
+    /// Construct the packed transaction at the given index, paying with
+    /// MOBILE from the given keypair. See [`PackedBoosts::message`].
+    pub async fn transaction<C: AsRef<SolanaRpcClient>>(
+        &self,
+        client: &C,
+        index: usize,
+        keypair: &Keypair,
+        authority: Option<&Keypair>,
+        opts: &TransactionOpts,
+    ) -> Result<(VersionedTransaction, u64), Error> {
+        let signers = self.carrier.signers(keypair, authority)?;
+        let (msg, block_height) = self.message(client, index, opts).await?;
+        let txn = tx::partial_sign(msg, &signers)?;
+        Ok((txn, block_height))
+    }
+}
+
+/// Plan boosts of a number of hexes under the given carrier and pack them
+/// into transactions. Messages are only constructed when asked for, see
+/// [`PackedBoosts::message`].
+///
+/// Boosts that fail validation, or that repeat an earlier hex and device
+/// type, are returned as rejected rather than failing the whole set.
+pub async fn pack_boosts<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    carrier: &Carrier,
+    boosts: &[HexBoost],
+    payer: &Pubkey,
+    opts: &TransactionOpts,
+) -> Result<PackedBoosts, Error> {
+    let config = boost_config(client).await?;
+    let price = crate::token::price::get(client, Token::Mobile).await?;
+    let addresses = boosts
+        .iter()
+        .map(|boost| boosted_hex_key(&carrier.address, boost.hex, boost.device_type))
+        .collect::<Vec<_>>();
+    let existing = client
+        .anchor_accounts::<hexboosting::BoostedHexV1>(&addresses)
+        .await?;
+
+    let mut packer = tx::TransactionPacker::for_opts(client, *payer, opts).await?;
+    if *payer != carrier.hexboost_authority {
+        packer = packer.with_cosigner();
+    }
+    let mut plans: Vec<Vec<BoostPlan>> = vec![];
+    let mut rejected = vec![];
+    let mut seen = std::collections::HashSet::new();
+    for ((boost, address), account) in boosts.iter().zip(addresses).zip(existing) {
+        if !seen.insert(address) {
+            rejected.push((*boost, EncodeError::other("duplicate boost").into()));
+            continue;
+        }
+        let result = account
+            .map(|account| BoostedHex::from_account(address, &account))
+            .transpose()
+            .and_then(|existing| {
+                plan_boost(&config, carrier, boost, existing.as_ref(), &price, payer)
+            })
+            .and_then(|(ix, plan)| {
+                packer.push(ix, BOOST_INSTRUCTION_BUDGET)?;
+                Ok(plan)
+            });
+        match result {
+            Ok(plan) => {
+                plans.resize_with(packer.len(), Default::default);
+                plans[packer.len() - 1].push(plan);
+            }
+            Err(err) => rejected.push((*boost, err)),
+        }
+    }
+    Ok(PackedBoosts {
+        carrier: carrier.clone(),
+        packer,
+        plans,
+        rejected,
+    })
+}
+
+/// Boost a hex, paying with MOBILE from the given keypair. See
+/// [`boost_hex_message`].
+pub async fn boost_hex<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    carrier: &Carrier,
+    boost: &HexBoost,
+    keypair: &Keypair,
+    authority: Option<&Keypair>,
+    opts: &TransactionOpts,
+) -> Result<(VersionedTransaction, u64, BoostPlan), Error> {
+    let signers = carrier.signers(keypair, authority)?;
+    let (msg, block_height, plan) =
+        boost_hex_message(client, carrier, boost, &keypair.pubkey(), opts).await?;
+    let txn = tx::partial_sign(msg, &signers)?;
+    Ok((txn, block_height, plan))
+}
+
+/// Extend the existing boost of a hex, paying with MOBILE from the given
+/// keypair. See [`extend_boost_message`].
+pub async fn extend_boost<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    carrier: &Carrier,
+    boosted: &BoostedHex,
+    periods: u16,
+    multiplier: Option<u8>,
+    keypair: &Keypair,
+    authority: Option<&Keypair>,
+    opts: &TransactionOpts,
+) -> Result<(VersionedTransaction, u64, BoostPlan), Error> {
+    let signers = carrier.signers(keypair, authority)?;
+    let (msg, block_height, plan) = extend_boost_message(
+        client,
+        carrier,
+        boosted,
+        periods,
+        multiplier,
+        &keypair.pubkey(),
+        opts,
+    )
+    .await?;
+    let txn = tx::partial_sign(msg, &signers)?;
+    Ok((txn, block_height, plan))
+}
 
 pub trait StartBoostingHex {
     fn start_authority(&self) -> Pubkey;
@@ -18,60 +872,91 @@ pub trait StartBoostingHex {
     fn activation_ts(&self) -> DateTime<Utc>;
 }
 
+/// A start of a boosted hex by the boost config start authority, typically
+/// issued when the first boosted hotspot in the hex is seen
+#[derive(Debug, Clone)]
+pub struct StartBoost {
+    pub start_authority: Pubkey,
+    pub boost_config: Pubkey,
+    pub boosted_hex: Pubkey,
+    pub activation_ts: DateTime<Utc>,
+}
+
+impl StartBoost {
+    pub fn new(
+        config: &BoostConfig,
+        boosted_hex: &BoostedHex,
+        activation_ts: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            start_authority: config.start_authority,
+            boost_config: config.address,
+            boosted_hex: boosted_hex.address,
+            activation_ts,
+        }
+    }
+}
+
+impl StartBoostingHex for StartBoost {
+    fn start_authority(&self) -> Pubkey {
+        self.start_authority
+    }
+
+    fn boost_config(&self) -> Pubkey {
+        self.boost_config
+    }
+
+    fn boosted_hex(&self) -> Pubkey {
+        self.boosted_hex
+    }
+
+    fn activation_ts(&self) -> DateTime<Utc> {
+        self.activation_ts
+    }
+}
+
+pub fn start_boost_instruction(update: &impl StartBoostingHex) -> Instruction {
+    let accounts = StartBoostV0 {
+        start_authority: update.start_authority(),
+        boost_config: update.boost_config(),
+        boosted_hex: update.boosted_hex(),
+    };
+    Instruction {
+        program_id: hexboosting::id(),
+        accounts: accounts.to_account_metas(None),
+        data: hexboosting::instruction::StartBoostV0 {
+            _args: hexboosting::StartBoostArgsV0 {
+                start_ts: update.activation_ts().timestamp(),
+            },
+        }
+        .data(),
+    }
+}
+
 pub async fn start_boost_message<C: AsRef<SolanaRpcClient>>(
     client: &C,
     keypair: &Keypair,
     updates: impl IntoIterator<Item = impl StartBoostingHex>,
     opts: &TransactionOpts,
 ) -> Result<(message::VersionedMessage, u64), Error> {
-    fn mk_accounts(
-        start_authority: Pubkey,
-        boost_config: Pubkey,
-        boosted_hex: Pubkey,
-    ) -> impl ToAccountMetas {
-        StartBoostV0 {
-            start_authority,
-            boost_config,
-            boosted_hex,
-        }
-    }
-
-    let mut ix_accounts = vec![];
-    let mut start_ixs = vec![];
-    for update in updates {
-        let accounts = mk_accounts(
-            update.start_authority(),
-            update.boost_config(),
-            update.boosted_hex(),
-        );
-        let accounts = accounts.to_account_metas(None);
-        ix_accounts.extend_from_slice(&accounts);
-        let ix = Instruction {
-            program_id: hexboosting::id(),
-            accounts,
-            data: hexboosting::instruction::StartBoostV0 {
-                _args: hexboosting::StartBoostArgsV0 {
-                    start_ts: update.activation_ts().timestamp(),
-                },
-            }
-            .data(),
-        };
-        start_ixs.push(ix);
-    }
+    let start_ixs = updates
+        .into_iter()
+        .map(|update| start_boost_instruction(&update))
+        .collect::<Vec<_>>();
+    let ix_accounts = start_ixs
+        .iter()
+        .flat_map(|ix| ix.accounts.clone())
+        .collect::<Vec<_>>();
     let ixs = [
         &[
             priority_fee::compute_budget_instruction(150_000),
-            priority_fee::compute_price_instruction_for_accounts(
-                client,
-                &ix_accounts,
-                opts.fee_range(),
-            )
-            .await?,
+            priority_fee::compute_price_instruction_for_accounts(client, &ix_accounts, opts)
+                .await?,
         ],
         start_ixs.as_slice(),
     ]
     .concat();
-    message::mk_message(client, &ixs, &opts.lut_addresses, &keypair.pubkey()).await
+    message::mk_message(client, &ixs, opts, &keypair.pubkey()).await
 }
 
 pub async fn start_boost<C: AsRef<SolanaRpcClient>>(
@@ -81,6 +966,116 @@ pub async fn start_boost<C: AsRef<SolanaRpcClient>>(
     opts: &TransactionOpts,
 ) -> Result<(VersionedTransaction, u64), Error> {
     let (msg, block_height) = start_boost_message(client, keypair, updates, opts).await?;
-    let txn = VersionedTransaction::try_new(msg, &[keypair])?;
+    let txn = tx::partial_sign(msg, &[keypair])?;
     Ok((txn, block_height))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> BoostConfig {
+        BoostConfig {
+            address: Pubkey::default(),
+            price_oracle: Pubkey::default(),
+            payment_mint: *Token::Mobile.mint(),
+            boost_price: Decimal::new(5_000, BOOST_PRICE_DECIMALS),
+            period_length: 2_592_000,
+            minimum_periods: 6,
+            start_authority: Pubkey::default(),
+        }
+    }
+
+    fn boost(periods: u16) -> HexBoost {
+        HexBoost {
+            hex: h3o::CellIndex::try_from(0x8a1fb46622dffff).unwrap(),
+            device_type: BoostDeviceType::WifiIndoor,
+            periods,
+            multiplier: 2,
+        }
+    }
+
+    #[test]
+    fn boost_periods_append_to_existing() {
+        let config = config();
+        let now = Utc::now();
+        let periods = |amounts: Vec<hexboosting::BoostAmountV0>| {
+            amounts.iter().map(|a| a.period).collect::<Vec<_>>()
+        };
+        assert_eq!(vec![0, 1, 2], periods(boost(3).amounts(None, &config, now)));
+
+        let mut existing = BoostedHex {
+            address: Pubkey::default(),
+            version: BoostedHexVersion::V1,
+            hex: boost(3).hex,
+            device_type: Some(BoostDeviceType::WifiIndoor),
+            start: None,
+            boosts_by_period: vec![1; 6],
+        };
+        assert_eq!(
+            vec![6, 7],
+            periods(boost(2).amounts(Some(&existing), &config, now))
+        );
+        // a lapsed boost resumes at the current period
+        existing.start = Some(now - config.period_duration() * 10);
+        assert_eq!(
+            vec![10, 11],
+            periods(boost(2).amounts(Some(&existing), &config, now))
+        );
+    }
+
+    #[test]
+    fn boost_cost_in_mobile() {
+        let price = Price {
+            timestamp: Utc::now(),
+            price: Decimal::new(1, 3),
+            token: Token::Mobile,
+        };
+        let cost = BoostCost::from_price(&config(), boost(6).boost_units(), &price).unwrap();
+        assert_eq!(12, cost.boost_units);
+        assert_eq!(Decimal::new(6, 2), cost.usd);
+        assert_eq!(
+            60 * 10u64.pow(Token::Mobile.decimals().into()),
+            cost.mobile.amount
+        );
+    }
+
+    const FIXTURE_HEX: u64 = 0x8a1fb46622dffff;
+    const FIXTURE_START_TS: i64 = 1_717_200_000;
+
+    // Boosted hex account data for a wifi outdoor boost under the mainnet
+    // boost config, base64 encoded as returned by getAccountInfo
+    const BOOSTED_HEX_V1: &str = "p4aNBbLuwr0DnN4TvagnMIuucfyi2h4dcQTDkLVK8hG+5w7iGsVBCQX//y1iRvuhCIBkWmYAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAP8DAAAAAQUKAQAAAA==";
+    // The same boost as a v0 account, from before device types
+    const BOOSTED_HEX_V0: &str = "hZ9GW7FFok2c3hO9qCcwi65x/KLaHh1xBMOQtUryEb7nDuIaxUEJBf//LWJG+6EIgGRaZgAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA/wMAAAABBQoBAAAA";
+    // Data of another hexboosting account type
+    const BOOST_CONFIG: &str = "nC+wT4yg1rcAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA";
+
+    #[test]
+    fn decode_boosted_hex_versions() {
+        let address = Pubkey::new_unique();
+        let hex = h3o::CellIndex::try_from(FIXTURE_HEX).unwrap();
+
+        let v1 = crate::b64::decode(BOOSTED_HEX_V1).unwrap();
+        let boosted = BoostedHex::decode(address, &v1).unwrap();
+        assert_eq!(BoostedHexVersion::V1, boosted.version);
+        assert_eq!(Some(BoostDeviceType::WifiOutdoor), boosted.device_type);
+        assert_eq!(hex, boosted.hex);
+        assert_eq!(
+            Some(FIXTURE_START_TS),
+            boosted.start.map(|start| start.timestamp())
+        );
+        assert_eq!(vec![1, 5, 10], boosted.boosts_by_period);
+
+        let v0 = crate::b64::decode(BOOSTED_HEX_V0).unwrap();
+        let boosted = BoostedHex::decode(address, &v0).unwrap();
+        assert_eq!(BoostedHexVersion::V0, boosted.version);
+        assert_eq!(None, boosted.device_type);
+        assert_eq!(hex, boosted.hex);
+        assert_eq!(vec![1, 5, 10], boosted.boosts_by_period);
+
+        let config = crate::b64::decode(BOOST_CONFIG).unwrap();
+        assert!(BoostedHex::decode(address, &config).is_err());
+        assert!(BoostedHex::decode(address, &[]).is_err());
+    }
+}
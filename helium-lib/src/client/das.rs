@@ -0,0 +1,556 @@
+//! Client for the DAS API of RPC providers, which indexes compressed
+//! assets along with regular ones.
+
+use super::{sender, RetryPolicy, SOLANA_URL_MAINNET};
+use crate::{
+    asset,
+    error::{DecodeError, Error},
+    is_zero,
+    keypair::Pubkey,
+};
+use jsonrpc_client::{JsonRpcError, SendRequest};
+use std::{sync::Arc, time::Duration};
+use tracing::instrument;
+
+#[derive(
+    serde::Serialize, Default, Debug, Clone, std::hash::Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct DasSearchAssetsParams {
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub creator_verified: bool,
+    #[serde(
+        with = "keypair::serde_opt_pubkey",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub creator_address: Option<Pubkey>,
+    #[serde(
+        with = "keypair::serde_opt_pubkey",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub owner_address: Option<Pubkey>,
+    #[serde(skip_serializing_if = "is_zero")]
+    pub page: u32,
+    #[serde(skip_serializing_if = "is_zero")]
+    pub limit: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_by: Option<DasSortBy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grouping: Option<DasGrouping>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub burnt: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compressed: Option<bool>,
+}
+
+/// A group assets belong to, like a collection. Serialized as the key and
+/// value pair the DAS API expects.
+#[derive(Debug, Clone, std::hash::Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DasGrouping {
+    pub key: String,
+    pub value: Pubkey,
+}
+
+impl DasGrouping {
+    pub fn collection(collection: Pubkey) -> Self {
+        Self {
+            key: "collection".to_string(),
+            value: collection,
+        }
+    }
+}
+
+impl serde::Serialize for DasGrouping {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&(&self.key, self.value.to_string()), serializer)
+    }
+}
+
+/// Paging of the DAS asset queries.
+///
+/// Providers that support it page through results with a cursor, which
+/// only works when sorting by id and does not skip or repeat assets when
+/// assets change while paging. Other providers and sort orders use page
+/// numbers.
+#[derive(
+    serde::Serialize, Default, Debug, Clone, std::hash::Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct DasPageParams {
+    #[serde(skip_serializing_if = "is_zero")]
+    pub page: u32,
+    #[serde(skip_serializing_if = "is_zero")]
+    pub limit: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_by: Option<DasSortBy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+}
+
+impl DasPageParams {
+    /// Maximum number of assets per page
+    pub const MAX_LIMIT: u32 = 1000;
+
+    /// The first page of the largest size in the given order. Without an
+    /// order, pages are sorted by id so a cursor can be used.
+    pub fn first(sort_by: Option<DasSortBy>) -> Self {
+        Self {
+            page: 0,
+            limit: Self::MAX_LIMIT,
+            sort_by: sort_by.or(Some(DasSortBy::id())),
+            cursor: None,
+        }
+    }
+
+    /// The params for the page after the given one, or `None` if it was the
+    /// last page. Follows the cursor of the page if the provider returned
+    /// one, and the page number otherwise.
+    pub fn next(&self, page: &asset::AssetPage) -> Option<Self> {
+        if page.items.is_empty() || page.items.len() < self.limit as usize {
+            return None;
+        }
+        let mut next = self.clone();
+        match &page.cursor {
+            Some(cursor) => {
+                next.page = 0;
+                next.cursor = Some(cursor.clone());
+            }
+            None => {
+                next.page = self.page.max(1) + 1;
+                next.cursor = None;
+            }
+        }
+        Some(next)
+    }
+}
+
+#[derive(serde::Serialize, Debug, Clone, Copy, std::hash::Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub struct DasSortBy {
+    pub sort_by: DasSortKey,
+    pub sort_direction: DasSortDirection,
+}
+
+#[derive(serde::Serialize, Debug, Clone, Copy, std::hash::Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum DasSortKey {
+    Id,
+    Created,
+    Updated,
+    RecentAction,
+    None,
+}
+
+#[derive(serde::Serialize, Debug, Clone, Copy, std::hash::Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum DasSortDirection {
+    Asc,
+    Desc,
+}
+
+impl DasSortBy {
+    pub fn created() -> Self {
+        Self {
+            sort_by: DasSortKey::Created,
+            sort_direction: DasSortDirection::Asc,
+        }
+    }
+
+    pub fn id() -> Self {
+        Self {
+            sort_by: DasSortKey::Id,
+            sort_direction: DasSortDirection::Asc,
+        }
+    }
+}
+
+impl DasSearchAssetsParams {
+    pub fn for_owner(owner_address: Pubkey, creator_address: Pubkey) -> Self {
+        Self {
+            owner_address: Some(owner_address),
+            creator_address: Some(creator_address),
+            creator_verified: true,
+            page: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Search the assets of a collection, like the collection of the
+    /// Hotspots of a maker
+    pub fn for_collection(collection: Pubkey) -> Self {
+        Self {
+            grouping: Some(DasGrouping::collection(collection)),
+            page: 1,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_owner(mut self, owner: Pubkey) -> Self {
+        self.owner_address = Some(owner);
+        self
+    }
+
+    /// Only include assets with the given verified creator
+    pub fn with_creator(mut self, creator: Pubkey) -> Self {
+        self.creator_address = Some(creator);
+        self.creator_verified = true;
+        self
+    }
+
+    /// Only include burnt assets, or only assets that were not burnt
+    pub fn with_burnt(mut self, burnt: bool) -> Self {
+        self.burnt = Some(burnt);
+        self
+    }
+
+    /// Only include compressed assets, or only regular NFTs
+    pub fn with_compressed(mut self, compressed: bool) -> Self {
+        self.compressed = Some(compressed);
+        self
+    }
+
+    /// Use the given paging for the search
+    pub fn with_page(mut self, page: &DasPageParams) -> Self {
+        self.page = page.page;
+        self.limit = page.limit;
+        self.sort_by = page.sort_by;
+        self.cursor = page.cursor.clone();
+        self
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DasClientError {
+    #[error("jsonrpc: {0}")]
+    Rpc(#[from] jsonrpc_client::Error<reqwest::Error>),
+    #[error("json error {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+impl From<reqwest::Error> for DasClientError {
+    fn from(value: reqwest::Error) -> Self {
+        jsonrpc_client::Error::from(value).into()
+    }
+}
+
+impl From<JsonRpcError> for DasClientError {
+    fn from(value: JsonRpcError) -> Self {
+        Self::from(jsonrpc_client::Error::JsonRpc(value))
+    }
+}
+
+impl DasClientError {
+    pub fn is_account_not_found(&self) -> bool {
+        match self {
+            Self::Rpc(jsonrpc_client::Error::JsonRpc(jsonrpc_client::JsonRpcError {
+                message,
+                ..
+            })) => message.starts_with("Database Error: RecordNotFound"),
+            _other => false,
+        }
+    }
+}
+
+#[jsonrpc_client::api]
+pub trait DAS {}
+
+static USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"),);
+
+/// Client for the DAS API of an RPC provider.
+///
+/// Requests that fail for transient reasons are retried according to the
+/// retry policy, and can be limited to a maximum request rate to stay within
+/// the limits of a provider.
+#[jsonrpc_client::implement(DAS)]
+#[derive(Debug, Clone)]
+pub struct DasClient {
+    inner: reqwest::Client,
+    base_url: reqwest::Url,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<Arc<RateLimiter>>,
+}
+
+/// Spaces out requests to stay under a maximum request rate
+#[derive(Debug)]
+struct RateLimiter {
+    interval: Duration,
+    next: std::sync::Mutex<crate::time::Instant>,
+}
+
+impl RateLimiter {
+    fn new(requests_per_second: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(1) / requests_per_second.max(1),
+            next: std::sync::Mutex::new(crate::time::Instant::now()),
+        }
+    }
+
+    /// Wait for the next free request slot
+    async fn acquire(&self) {
+        let slot = {
+            let mut next = self.next.lock().expect("rate limiter lock");
+            let slot = (*next).max(crate::time::Instant::now());
+            *next = slot + self.interval;
+            slot
+        };
+        crate::time::sleep_until(slot).await;
+    }
+}
+
+impl Default for DasClient {
+    fn default() -> Self {
+        // safe to unwrap
+        Self::with_base_url(SOLANA_URL_MAINNET).unwrap()
+    }
+}
+
+impl DasClient {
+    pub fn with_base_url(url: &str) -> Result<Self, Error> {
+        Self::with_client(url, reqwest::Client::new())
+    }
+
+    pub fn with_client(url: &str, client: reqwest::Client) -> Result<Self, Error> {
+        let base_url = url.parse().map_err(DecodeError::from)?;
+        Ok(Self {
+            inner: client,
+            base_url,
+            retry_policy: RetryPolicy::default(),
+            rate_limiter: None,
+        })
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Limit requests to the given number per second
+    pub fn with_rate_limit(mut self, requests_per_second: u32) -> Self {
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(requests_per_second)));
+        self
+    }
+
+    #[instrument(skip(self), level = "trace")]
+    pub async fn get_asset(&self, address: &Pubkey) -> Result<asset::Asset, DasClientError> {
+        let body = jsonrpc_client::Request::new_v2("getAsset")
+            .with_argument("id".to_string(), address.to_string())?
+            .serialize()?;
+
+        let response = Result::from(
+            SendRequest::send_request::<asset::Asset>(self, self.base_url.clone(), body)
+                .await?
+                .payload,
+        )?;
+        Ok(response)
+    }
+
+    #[instrument(skip(self), level = "trace")]
+    pub async fn get_asset_proof(
+        &self,
+        address: &Pubkey,
+    ) -> Result<asset::AssetProof, DasClientError> {
+        let body = jsonrpc_client::Request::new_v2("getAssetProof")
+            .with_argument("id".to_string(), address.to_string())?
+            .serialize()?;
+
+        let response = Result::from(
+            SendRequest::send_request::<asset::AssetProof>(self, self.base_url.clone(), body)
+                .await?
+                .payload,
+        )?;
+        Ok(response)
+    }
+
+    #[instrument(skip(self), level = "trace")]
+    pub async fn get_signatures_for_asset(
+        &self,
+        address: &Pubkey,
+        page: u32,
+        limit: u32,
+    ) -> Result<asset::AssetSignaturePage, DasClientError> {
+        let body = jsonrpc_client::Request::new_v2("getSignaturesForAsset")
+            .with_argument("id".to_string(), address.to_string())?
+            .with_argument("page".to_string(), page)?
+            .with_argument("limit".to_string(), limit)?
+            .serialize()?;
+
+        let response = Result::from(
+            SendRequest::send_request::<asset::AssetSignaturePage>(
+                self,
+                self.base_url.clone(),
+                body,
+            )
+            .await?
+            .payload,
+        )?;
+        Ok(response)
+    }
+
+    #[instrument(skip(self), level = "trace")]
+    pub async fn get_assets_by_owner(
+        &self,
+        owner: &Pubkey,
+        page: &DasPageParams,
+    ) -> Result<asset::AssetPage, DasClientError> {
+        self.get_asset_page(
+            "getAssetsByOwner",
+            serde_json::json!({ "ownerAddress": owner.to_string() }),
+            page,
+        )
+        .await
+    }
+
+    /// Get a page of the assets in a group, like the assets of a collection
+    /// with the "collection" group key
+    #[instrument(skip(self), level = "trace")]
+    pub async fn get_assets_by_group(
+        &self,
+        group_key: &str,
+        group_value: &Pubkey,
+        page: &DasPageParams,
+    ) -> Result<asset::AssetPage, DasClientError> {
+        self.get_asset_page(
+            "getAssetsByGroup",
+            serde_json::json!({
+                "groupKey": group_key,
+                "groupValue": group_value.to_string(),
+            }),
+            page,
+        )
+        .await
+    }
+
+    async fn get_asset_page(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+        page: &DasPageParams,
+    ) -> Result<asset::AssetPage, DasClientError> {
+        let mut params = params.as_object().cloned().unwrap_or_default();
+        if let serde_json::Value::Object(page) = serde_json::to_value(page)? {
+            params.extend(page);
+        }
+        let mut body = jsonrpc_client::Request::new_v2(method);
+        body.params = jsonrpc_client::Params::ByName(params);
+        let response = Result::from(
+            SendRequest::send_request::<asset::AssetPage>(
+                self,
+                self.base_url.clone(),
+                body.serialize()?,
+            )
+            .await?
+            .payload,
+        )?;
+        Ok(response)
+    }
+
+    #[instrument(skip(self, params), level = "trace")]
+    pub async fn search_assets(
+        &self,
+        params: DasSearchAssetsParams,
+    ) -> Result<asset::AssetPage, DasClientError> {
+        let params =
+            serde_json::to_value(params).map(|value| value.as_object().unwrap().to_owned())?;
+        let mut body = jsonrpc_client::Request::new_v2("searchAssets");
+        body.params = jsonrpc_client::Params::ByName(params.to_owned());
+        let response = Result::from(
+            SendRequest::send_request::<asset::AssetPage>(
+                self,
+                self.base_url.clone(),
+                body.serialize()?,
+            )
+            .await?
+            .payload,
+        )?;
+        Ok(response)
+    }
+}
+
+#[async_trait::async_trait]
+impl jsonrpc_client::SendRequest for DasClient {
+    type Error = reqwest::Error;
+    async fn send_request<P>(
+        &self,
+        endpoint: jsonrpc_client::Url,
+        body: String,
+    ) -> Result<jsonrpc_client::Response<P>, Self::Error>
+    where
+        P: serde::de::DeserializeOwned,
+    {
+        let start = crate::time::Instant::now();
+        let mut attempt = 1;
+        loop {
+            if let Some(rate_limiter) = &self.rate_limiter {
+                rate_limiter.acquire().await;
+            }
+            match self.post(endpoint.clone(), body.clone()).await {
+                Err(err)
+                    if attempt < self.retry_policy.max_attempts
+                        && sender::is_retryable_http(&err) =>
+                {
+                    let backoff = self.retry_policy.backoff(attempt);
+                    tracing::debug!(attempt, ?backoff, %err, "retrying das request");
+                    crate::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+                result => {
+                    tracing::debug!(
+                        attempts = attempt,
+                        elapsed = ?start.elapsed(),
+                        ok = result.is_ok(),
+                        "das request done"
+                    );
+                    return result;
+                }
+            }
+        }
+    }
+}
+
+impl DasClient {
+    async fn post<P: serde::de::DeserializeOwned>(
+        &self,
+        endpoint: jsonrpc_client::Url,
+        body: String,
+    ) -> Result<jsonrpc_client::Response<P>, reqwest::Error> {
+        let response = self
+            .inner
+            .post(endpoint)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(reqwest::header::USER_AGENT, USER_AGENT)
+            .body(body)
+            .send()
+            .await?;
+        // Other error statuses come with a JSON-RPC error body
+        if sender::is_retryable_status(response.status()) {
+            response.error_for_status_ref()?;
+        }
+        response.json().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_params_json() {
+        let (collection, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let params = DasSearchAssetsParams::for_collection(collection)
+            .with_owner(owner)
+            .with_compressed(true)
+            .with_page(&DasPageParams::first(None));
+        assert_eq!(
+            serde_json::json!({
+                "ownerAddress": owner.to_string(),
+                "limit": DasPageParams::MAX_LIMIT,
+                "sortBy": {"sortBy": "id", "sortDirection": "asc"},
+                "grouping": ["collection", collection.to_string()],
+                "compressed": true,
+            }),
+            serde_json::to_value(params).expect("json params")
+        );
+    }
+}
@@ -0,0 +1,436 @@
+//! A [`SolanaRpc`] transport that sends JSON-RPC requests over plain HTTP.
+//!
+//! Unlike the `SolanaRpcClient` of the `client` feature it needs neither a tokio runtime nor native sockets, so the
+//! transaction builders can use it in the browser on `wasm32` targets too.
+
+use super::{sender, RetryPolicy, SolanaRpc, DEFAULT_REQUEST_TIMEOUT};
+use crate::{
+    b64,
+    error::{DecodeError, EncodeError, Error},
+    keypair::Pubkey,
+    solana_account_decoder::{UiAccount, UiAccountEncoding},
+    solana_rpc_client_api::{
+        client_error::Error as ClientError,
+        config::{
+            RpcAccountInfoConfig, RpcSendTransactionConfig, RpcSignatureStatusConfig,
+            RpcSimulateTransactionConfig,
+        },
+        custom_error::JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE,
+        error_object::RpcErrorObject,
+        request::{RpcError, RpcRequest, RpcResponseErrorData},
+        response::{Response, RpcBlockhash, RpcPrioritizationFee, RpcSimulateTransactionResult},
+    },
+    solana_sdk::{
+        account::Account, commitment_config::CommitmentConfig, hash::Hash, signature::Signature,
+        transaction::VersionedTransaction,
+    },
+    time::Instant,
+};
+use serde::de::DeserializeOwned;
+use solana_transaction_status::{TransactionStatus, UiTransactionEncoding};
+use std::{
+    future::Future,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// A [`SolanaRpc`] over JSON-RPC HTTP requests to a single endpoint.
+///
+/// Requests that fail for transient reasons are retried according to the
+/// retry policy, and every attempt is limited to the request timeout.
+#[derive(Debug, Clone)]
+pub struct HttpRpc {
+    inner: reqwest::Client,
+    url: String,
+    commitment: CommitmentConfig,
+    retry_policy: RetryPolicy,
+    timeout: Duration,
+    next_id: Arc<AtomicU64>,
+}
+
+impl HttpRpc {
+    pub fn new(url: &str) -> Self {
+        Self::with_client(url, reqwest::Client::new())
+    }
+
+    /// Send requests with the given HTTP client, like one with the API key
+    /// header of an RPC provider as default header
+    pub fn with_client(url: &str, client: reqwest::Client) -> Self {
+        Self {
+            inner: client,
+            url: url.to_string(),
+            commitment: CommitmentConfig::default(),
+            retry_policy: RetryPolicy::default(),
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+            next_id: Default::default(),
+        }
+    }
+
+    /// Set the commitment used for reads, blockhashes and confirmation
+    /// waits. Defaults to finalized.
+    pub fn with_commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Set the timeout of a single request. Retried requests get the full
+    /// timeout for every attempt.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Send a request, retrying it on transient failures, and decode its
+    /// result
+    pub async fn send<T: DeserializeOwned>(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> Result<T, Error> {
+        let start = Instant::now();
+        let mut attempt = 1;
+        let result = loop {
+            match self.send_once(request, params.clone()).await {
+                Err(err)
+                    if attempt < self.retry_policy.max_attempts && sender::is_retryable(&err) =>
+                {
+                    let backoff = self.retry_policy.backoff(attempt);
+                    tracing::debug!(attempt, ?backoff, %err, %request, "retrying rpc request");
+                    crate::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+                result => break result,
+            }
+        };
+        let elapsed = start.elapsed();
+        tracing::debug!(
+            attempts = attempt,
+            ?elapsed,
+            ok = result.is_ok(),
+            %request,
+            "rpc request done"
+        );
+        sender::observe_request(&request, elapsed, result.is_ok());
+        serde_json::from_value(result?)
+            .map_err(|err| ClientError::new_with_request(err.into(), request).into())
+    }
+
+    async fn send_once(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, ClientError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let body = request.build_request_json(id, params).to_string();
+        let post = async { Ok::<_, Error>(self.post(body).await) };
+        match crate::time::timeout(self.timeout, post).await {
+            Ok(result) => rpc_result(
+                request,
+                result.map_err(|err| ClientError::new_with_request(err.into(), request))?,
+            ),
+            Err(_) => Err(sender::timed_out(request, self.timeout)),
+        }
+    }
+
+    async fn post(&self, body: String) -> Result<serde_json::Value, reqwest::Error> {
+        let response = self
+            .inner
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .await?;
+        // Other error statuses come with a JSON-RPC error body
+        if sender::is_retryable_status(response.status()) {
+            response.error_for_status_ref()?;
+        }
+        response.json().await
+    }
+
+    fn account_config(&self) -> RpcAccountInfoConfig {
+        RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(self.commitment),
+            ..Default::default()
+        }
+    }
+}
+
+/// Mark a request future as `Send`, as the [`SolanaRpc`] methods require.
+/// Native requests are `Send` already.
+#[cfg(not(target_arch = "wasm32"))]
+fn send_future<F: Future + Send>(future: F) -> impl Future<Output = F::Output> + Send {
+    future
+}
+
+/// Mark a request future as `Send`, as the [`SolanaRpc`] methods require.
+/// Browser requests are not `Send`, but wasm32 runs them on a single thread
+/// so they are never actually sent to another one.
+#[cfg(target_arch = "wasm32")]
+fn send_future<F: Future>(future: F) -> impl Future<Output = F::Output> + Send {
+    send_wrapper::SendWrapper::new(future)
+}
+
+/// The result of a JSON-RPC response, or the error it carries
+fn rpc_result(
+    request: RpcRequest,
+    mut json: serde_json::Value,
+) -> Result<serde_json::Value, ClientError> {
+    if !json["error"].is_object() {
+        return Ok(json["result"].take());
+    }
+    let error = json["error"].take();
+    let RpcErrorObject { code, message } = serde_json::from_value(error.clone())
+        .map_err(|err| ClientError::new_with_request(err.into(), request))?;
+    // A failed preflight simulation comes with the simulation result, which
+    // holds the error and logs of the failed transaction
+    let data = match code {
+        JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE => {
+            serde_json::from_value(error["data"].clone())
+                .map(RpcResponseErrorData::SendTransactionPreflightFailure)
+                .unwrap_or(RpcResponseErrorData::Empty)
+        }
+        _ => RpcResponseErrorData::Empty,
+    };
+    let err = RpcError::RpcResponseError {
+        code,
+        message,
+        data,
+    };
+    Err(ClientError::new_with_request(err.into(), request))
+}
+
+/// The error of a missing account, the same one the native client fails
+/// with so it is recognized as an account that was not found
+fn account_not_found(pubkey: &Pubkey) -> ClientError {
+    ClientError::new_with_request(
+        RpcError::ForUser(format!("AccountNotFound: pubkey={pubkey}")).into(),
+        RpcRequest::GetAccountInfo,
+    )
+}
+
+fn decode_account(account: UiAccount) -> Result<Account, Error> {
+    account
+        .decode()
+        .ok_or_else(|| DecodeError::other("invalid account data").into())
+}
+
+fn encode_transaction(tx: &VersionedTransaction) -> Result<String, Error> {
+    let data = bincode::serialize(tx).map_err(EncodeError::from)?;
+    Ok(b64::encode(data))
+}
+
+impl SolanaRpc for HttpRpc {
+    fn commitment(&self) -> CommitmentConfig {
+        self.commitment
+    }
+
+    fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    fn get_multiple_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> impl Future<Output = Result<Vec<Option<Account>>, Error>> + Send {
+        let params = serde_json::json!([
+            pubkeys.iter().map(Pubkey::to_string).collect::<Vec<_>>(),
+            self.account_config(),
+        ]);
+        send_future(async move {
+            let response: Response<Vec<Option<UiAccount>>> =
+                self.send(RpcRequest::GetMultipleAccounts, params).await?;
+            response
+                .value
+                .into_iter()
+                .map(|account| account.map(decode_account).transpose())
+                .collect()
+        })
+    }
+
+    fn get_account(&self, pubkey: &Pubkey) -> impl Future<Output = Result<Account, Error>> + Send {
+        let pubkey = *pubkey;
+        let params = serde_json::json!([pubkey.to_string(), self.account_config()]);
+        send_future(async move {
+            let response: Response<Option<UiAccount>> =
+                self.send(RpcRequest::GetAccountInfo, params).await?;
+            match response.value {
+                Some(account) => decode_account(account),
+                None => Err(account_not_found(&pubkey).into()),
+            }
+        })
+    }
+
+    fn get_latest_blockhash_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> impl Future<Output = Result<(Hash, u64), Error>> + Send {
+        send_future(async move {
+            let response: Response<RpcBlockhash> = self
+                .send(
+                    RpcRequest::GetLatestBlockhash,
+                    serde_json::json!([commitment]),
+                )
+                .await?;
+            let blockhash = Hash::from_str(&response.value.blockhash)
+                .map_err(|_| DecodeError::other("invalid blockhash"))?;
+            Ok((blockhash, response.value.last_valid_block_height))
+        })
+    }
+
+    fn simulate_transaction(
+        &self,
+        tx: &VersionedTransaction,
+    ) -> impl Future<Output = Result<RpcSimulateTransactionResult, Error>> + Send {
+        let tx = encode_transaction(tx);
+        let config = RpcSimulateTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(self.commitment),
+            ..Default::default()
+        };
+        send_future(async move {
+            let response: Response<RpcSimulateTransactionResult> = self
+                .send(
+                    RpcRequest::SimulateTransaction,
+                    serde_json::json!([tx?, config]),
+                )
+                .await?;
+            Ok(response.value)
+        })
+    }
+
+    fn send_transaction(
+        &self,
+        tx: &VersionedTransaction,
+    ) -> impl Future<Output = Result<Signature, Error>> + Send {
+        let config = RpcSendTransactionConfig {
+            preflight_commitment: Some(self.commitment.commitment),
+            ..Default::default()
+        };
+        self.send_transaction_with_config(tx, config)
+    }
+
+    fn send_transaction_with_config(
+        &self,
+        tx: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> impl Future<Output = Result<Signature, Error>> + Send {
+        let tx = encode_transaction(tx);
+        let config = RpcSendTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            ..config
+        };
+        send_future(async move {
+            let signature: String = self
+                .send(
+                    RpcRequest::SendTransaction,
+                    serde_json::json!([tx?, config]),
+                )
+                .await?;
+            Ok(Signature::from_str(&signature).map_err(DecodeError::from)?)
+        })
+    }
+
+    fn get_signature_statuses_with_history(
+        &self,
+        signatures: &[Signature],
+    ) -> impl Future<Output = Result<Vec<Option<TransactionStatus>>, Error>> + Send {
+        let params = serde_json::json!([
+            signatures
+                .iter()
+                .map(Signature::to_string)
+                .collect::<Vec<_>>(),
+            RpcSignatureStatusConfig {
+                search_transaction_history: true,
+            },
+        ]);
+        send_future(async move {
+            let response: Response<Vec<Option<TransactionStatus>>> =
+                self.send(RpcRequest::GetSignatureStatuses, params).await?;
+            Ok(response.value)
+        })
+    }
+
+    fn get_block_height_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> impl Future<Output = Result<u64, Error>> + Send {
+        send_future(self.send(RpcRequest::GetBlockHeight, serde_json::json!([commitment])))
+    }
+
+    fn get_recent_prioritization_fees(
+        &self,
+        accounts: &[Pubkey],
+    ) -> impl Future<Output = Result<Vec<RpcPrioritizationFee>, Error>> + Send {
+        let params =
+            serde_json::json!([accounts.iter().map(Pubkey::to_string).collect::<Vec<_>>()]);
+        send_future(self.send(RpcRequest::GetRecentPrioritizationFees, params))
+    }
+
+    fn send_request(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> impl Future<Output = Result<serde_json::Value, Error>> + Send {
+        send_future(self.send(request, params))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_results_and_errors() {
+        let json = serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": 42});
+        assert_eq!(
+            serde_json::json!(42),
+            rpc_result(RpcRequest::GetBlockHeight, json).expect("result")
+        );
+
+        let json = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "error": {"code": -32005, "message": "Node is behind by 42 slots"},
+        });
+        let err = rpc_result(RpcRequest::GetBlockHeight, json).expect_err("error");
+        assert!(sender::is_retryable(&err));
+    }
+
+    #[test]
+    fn preflight_failure_is_transaction_error() {
+        let json = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "error": {
+                "code": -32002,
+                "message": "Transaction simulation failed",
+                "data": {
+                    "err": {"InstructionError": [1, {"Custom": 6001}]},
+                    "logs": ["Program log: failed"],
+                    "accounts": null,
+                    "unitsConsumed": 1000,
+                },
+            },
+        });
+        let err = Error::from(rpc_result(RpcRequest::SendTransaction, json).expect_err("error"));
+        let txn_error = err.transaction_error().expect("transaction error");
+        assert_eq!(Some(1), txn_error.instruction);
+        assert_eq!(vec!["Program log: failed".to_string()], txn_error.logs);
+    }
+
+    #[test]
+    fn missing_account_is_not_found() {
+        let err = Error::from(account_not_found(&Pubkey::new_unique()));
+        assert!(err.is_account_not_found());
+    }
+}
@@ -0,0 +1,327 @@
+use super::{GetAnchorAccount, SolanaRpc};
+use crate::{
+    anchor_lang::{AccountDeserialize, AccountSerialize, Owner},
+    error::{EncodeError, Error, TransactionError},
+    keypair::Pubkey,
+    solana_client::{
+        rpc_config::RpcSendTransactionConfig,
+        rpc_request::RpcRequest,
+        rpc_response::{RpcPrioritizationFee, RpcSimulateTransactionResult},
+    },
+    solana_sdk::{
+        account::Account, commitment_config::CommitmentConfig, hash::Hash, signature::Signature,
+        transaction::VersionedTransaction,
+    },
+};
+use solana_transaction_status::{TransactionConfirmationStatus, TransactionStatus};
+use std::{collections::HashMap, future::Future, sync::Mutex};
+
+/// Lamports given to accounts added with [`MockRpc::set_anchor_account`]
+const MOCK_ACCOUNT_LAMPORTS: u64 = 1_000_000_000;
+
+/// An in-memory [`SolanaRpc`] for testing code built on helium-lib without a
+/// validator.
+///
+/// Accounts are served from an in-memory map. Transactions are checked for
+/// valid signatures and recorded instead of executed, so tests can inspect
+/// the instructions that would have been sent. A sent transaction is reported
+/// as finalized, unless the current block height set with
+/// [`MockRpc::set_current_block_height`] is past the last valid block height
+/// of its blockhash, in which case it never lands. Simulations succeed unless a
+/// different result is set with [`MockRpc::set_simulation`]. No recent
+/// prioritization fees are reported, so priority fees are the minimum of the
+/// fee range, and other JSON-RPC requests fail.
+#[derive(Debug, Default)]
+pub struct MockRpc {
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    accounts: HashMap<Pubkey, Account>,
+    blockhash: Option<Hash>,
+    block_height: u64,
+    current_block_height: u64,
+    /// Last valid block height of every blockhash handed out
+    blockhashes: HashMap<Hash, u64>,
+    simulation: Option<RpcSimulateTransactionResult>,
+    sent: Vec<VersionedTransaction>,
+    statuses: HashMap<Signature, TransactionStatus>,
+}
+
+impl MockRpc {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("mock rpc lock")
+    }
+
+    pub fn set_account(&self, address: Pubkey, account: Account) {
+        self.state().accounts.insert(address, account);
+    }
+
+    /// Store an anchor account, serialized with its discriminator and owned
+    /// by its program
+    pub fn set_anchor_account<T: AccountSerialize + Owner>(
+        &self,
+        address: Pubkey,
+        value: &T,
+    ) -> Result<(), Error> {
+        let mut data = vec![];
+        value
+            .try_serialize(&mut data)
+            .map_err(|err| EncodeError::other(err.to_string()))?;
+        self.set_account(
+            address,
+            Account {
+                lamports: MOCK_ACCOUNT_LAMPORTS,
+                data,
+                owner: T::owner(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+        Ok(())
+    }
+
+    pub fn remove_account(&self, address: &Pubkey) -> Option<Account> {
+        self.state().accounts.remove(address)
+    }
+
+    /// Set the block height returned with the latest blockhash
+    pub fn set_block_height(&self, block_height: u64) {
+        self.state().block_height = block_height;
+    }
+
+    /// Replace the latest blockhash and the last block height it is valid
+    /// for
+    pub fn set_latest_blockhash(&self, blockhash: Hash, block_height: u64) {
+        let mut state = self.state();
+        state.blockhash = Some(blockhash);
+        state.block_height = block_height;
+    }
+
+    /// Set the current block height, which expires the blockhashes whose
+    /// last valid block height is lower
+    pub fn set_current_block_height(&self, block_height: u64) {
+        self.state().current_block_height = block_height;
+    }
+
+    /// Set the result of all following simulations
+    pub fn set_simulation(&self, simulation: RpcSimulateTransactionResult) {
+        self.state().simulation = Some(simulation);
+    }
+
+    /// The transactions sent so far, oldest first
+    pub fn sent_transactions(&self) -> Vec<VersionedTransaction> {
+        self.state().sent.clone()
+    }
+
+    /// The blockhash returned for every latest blockhash request
+    pub fn blockhash() -> Hash {
+        Hash::new_from_array([1; 32])
+    }
+}
+
+impl SolanaRpc for MockRpc {
+    fn commitment(&self) -> CommitmentConfig {
+        CommitmentConfig::confirmed()
+    }
+
+    fn url(&self) -> String {
+        "mock".to_string()
+    }
+
+    fn get_multiple_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> impl Future<Output = Result<Vec<Option<Account>>, Error>> + Send {
+        let state = self.state();
+        let accounts = pubkeys
+            .iter()
+            .map(|pubkey| state.accounts.get(pubkey).cloned())
+            .collect();
+        async move { Ok(accounts) }
+    }
+
+    fn get_account(&self, pubkey: &Pubkey) -> impl Future<Output = Result<Account, Error>> + Send {
+        let account = self
+            .state()
+            .accounts
+            .get(pubkey)
+            .cloned()
+            .ok_or_else(Error::account_not_found);
+        async move { account }
+    }
+
+    fn get_latest_blockhash_with_commitment(
+        &self,
+        _commitment: CommitmentConfig,
+    ) -> impl Future<Output = Result<(Hash, u64), Error>> + Send {
+        let mut state = self.state();
+        let blockhash = state.blockhash.unwrap_or_else(Self::blockhash);
+        let block_height = state.block_height;
+        state.blockhashes.insert(blockhash, block_height);
+        async move { Ok((blockhash, block_height)) }
+    }
+
+    fn simulate_transaction(
+        &self,
+        _tx: &VersionedTransaction,
+    ) -> impl Future<Output = Result<RpcSimulateTransactionResult, Error>> + Send {
+        let simulation =
+            self.state()
+                .simulation
+                .clone()
+                .unwrap_or_else(|| RpcSimulateTransactionResult {
+                    err: None,
+                    logs: Some(vec![]),
+                    accounts: None,
+                    units_consumed: Some(0),
+                    return_data: None,
+                    inner_instructions: None,
+                });
+        async move { Ok(simulation) }
+    }
+
+    fn send_transaction(
+        &self,
+        tx: &VersionedTransaction,
+    ) -> impl Future<Output = Result<Signature, Error>> + Send {
+        let result = if tx.verify_with_results().into_iter().all(|valid| valid) {
+            let mut state = self.state();
+            let current_block_height = state.current_block_height;
+            let expired = state
+                .blockhashes
+                .get(tx.message.recent_blockhash())
+                .is_some_and(|last_valid| current_block_height > *last_valid);
+            if !expired {
+                state.statuses.insert(
+                    tx.signatures[0],
+                    TransactionStatus {
+                        slot: current_block_height,
+                        confirmations: None,
+                        status: Ok(()),
+                        err: None,
+                        confirmation_status: Some(TransactionConfirmationStatus::Finalized),
+                    },
+                );
+            }
+            state.sent.push(tx.clone());
+            Ok(tx.signatures[0])
+        } else {
+            Err(TransactionError::new(
+                crate::solana_sdk::transaction::TransactionError::SignatureFailure,
+                vec![],
+            )
+            .into())
+        };
+        async move { result }
+    }
+
+    fn send_transaction_with_config(
+        &self,
+        tx: &VersionedTransaction,
+        _config: RpcSendTransactionConfig,
+    ) -> impl Future<Output = Result<Signature, Error>> + Send {
+        self.send_transaction(tx)
+    }
+
+    fn get_signature_statuses_with_history(
+        &self,
+        signatures: &[Signature],
+    ) -> impl Future<Output = Result<Vec<Option<TransactionStatus>>, Error>> + Send {
+        let state = self.state();
+        let statuses = signatures
+            .iter()
+            .map(|signature| state.statuses.get(signature).cloned())
+            .collect();
+        async move { Ok(statuses) }
+    }
+
+    fn get_block_height_with_commitment(
+        &self,
+        _commitment: CommitmentConfig,
+    ) -> impl Future<Output = Result<u64, Error>> + Send {
+        let block_height = self.state().current_block_height;
+        async move { Ok(block_height) }
+    }
+
+    fn get_recent_prioritization_fees(
+        &self,
+        _accounts: &[Pubkey],
+    ) -> impl Future<Output = Result<Vec<RpcPrioritizationFee>, Error>> + Send {
+        async move { Ok(vec![]) }
+    }
+
+    fn send_request(
+        &self,
+        request: RpcRequest,
+        _params: serde_json::Value,
+    ) -> impl Future<Output = Result<serde_json::Value, Error>> + Send {
+        let err = EncodeError::other(format!("{request} is not supported by the mock rpc"));
+        async move { Err(err.into()) }
+    }
+}
+
+#[async_trait::async_trait]
+impl GetAnchorAccount for MockRpc {
+    async fn anchor_account<T: AccountDeserialize>(&self, pubkey: &Pubkey) -> Result<T, Error> {
+        let account = SolanaRpc::get_account(self, pubkey).await?;
+        Ok(T::try_deserialize(&mut account.data.as_ref())?)
+    }
+
+    async fn anchor_accounts<T: AccountDeserialize + Send>(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Result<Vec<Option<T>>, Error> {
+        SolanaRpc::get_multiple_accounts(self, pubkeys)
+            .await?
+            .into_iter()
+            .map(|maybe_account| {
+                maybe_account
+                    .map(|account| T::try_deserialize(&mut account.data.as_ref()))
+                    .transpose()
+                    .map_err(Error::from)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        keypair::Keypair,
+        token::{self, Token, TokenAmount},
+        TransactionOpts,
+    };
+
+    #[tokio::test]
+    async fn transfer_is_recorded() {
+        let rpc = MockRpc::new();
+        rpc.set_block_height(42);
+        let keypair = Keypair::generate();
+        let payee = Pubkey::new_unique();
+        let opts = TransactionOpts {
+            lut_addresses: vec![],
+            ..Default::default()
+        };
+        let (tx, block_height) = token::transfer(
+            &rpc,
+            &[(payee, TokenAmount::from_u64(Token::Sol, 1))],
+            &keypair,
+            &opts,
+        )
+        .await
+        .expect("transfer");
+        assert_eq!(42, block_height);
+        assert_eq!(&MockRpc::blockhash(), tx.message.recent_blockhash());
+
+        let signature = rpc.send_transaction(&tx).await.expect("send");
+        assert_eq!(tx.signatures[0], signature);
+        assert_eq!(1, rpc.sent_transactions().len());
+    }
+}
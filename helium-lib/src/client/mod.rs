@@ -1,16 +1,55 @@
+use crate::error::Error;
+#[cfg(feature = "client")]
 use crate::{
-    anchor_lang::AccountDeserialize,
-    asset,
-    error::{DecodeError, Error},
-    is_zero,
+    anchor_lang::{AccountDeserialize, Discriminator},
+    error::DecodeError,
     keypair::{self, Pubkey},
-    solana_client,
+    solana_account_decoder::UiAccountEncoding,
+    solana_client::{
+        self,
+        rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+        rpc_filter::{Memcmp, RpcFilterType},
+    },
+    solana_sdk::commitment_config::CommitmentConfig,
 };
+#[cfg(feature = "client")]
 use futures::{stream, StreamExt, TryStreamExt};
+#[cfg(feature = "client")]
 use itertools::Itertools;
-use jsonrpc_client::{JsonRpcError, SendRequest};
+use std::time::Duration;
+#[cfg(feature = "client")]
 use std::{marker::Send, sync::Arc};
-use tracing::instrument;
+
+#[cfg(feature = "client")]
+pub mod das;
+pub mod http;
+#[cfg(all(feature = "client", any(test, feature = "test-utils")))]
+pub mod mock;
+pub mod rpc;
+pub mod sender;
+
+#[cfg(feature = "client")]
+pub use das::{
+    DasClient, DasClientError, DasGrouping, DasPageParams, DasSearchAssetsParams, DasSortBy,
+    DasSortDirection, DasSortKey, DAS,
+};
+pub use http::HttpRpc;
+pub use rpc::SolanaRpc;
+pub use sender::RetryPolicy;
+
+/// Timeout of a single request unless configured otherwise
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Run a future to completion within the given deadline, failing with
+/// [`Error::Timeout`] when the deadline passes first. The future can fail
+/// with any error that a helium-lib error converts into.
+pub async fn with_deadline<T, E, F>(deadline: Duration, future: F) -> Result<T, E>
+where
+    F: std::future::Future<Output = Result<T, E>>,
+    E: From<Error>,
+{
+    crate::time::timeout(deadline, future).await
+}
 
 pub static ONBOARDING_URL_MAINNET: &str = "https://onboarding.dewi.org/api/v3";
 pub static ONBOARDING_URL_DEVNET: &str = "https://onboarding.web.test-helium.com/api/v3";
@@ -30,13 +69,63 @@ pub static CERT_URL_MAINNET_ENV: &str = "CERT_MAINNET_URL";
 pub static CERT_URL_DEVNET_ENV: &str = "CERT_DEVNET_URL";
 pub static CERT_TOKEN_DEVNET_ENV: &str = "CERT_DEVNET_TOKEN";
 
+#[cfg(feature = "client")]
 pub use crate::hotspot::cert::Client as CertClient;
+#[cfg(feature = "client")]
 pub use solana_client::nonblocking::rpc_client::RpcClient as SolanaRpcClient;
 
 pub fn is_devnet(url: &str) -> bool {
     url == "d" || url.starts_with("devnet") || url.contains("test-helium")
 }
 
+/// Get the websocket url of the pubsub endpoint for a Solana RPC url
+pub fn websocket_url(rpc_url: &str) -> String {
+    if let Some(rest) = rpc_url.strip_prefix("https://") {
+        format!("wss://{rest}")
+    } else if let Some(rest) = rpc_url.strip_prefix("http://") {
+        format!("ws://{rest}")
+    } else {
+        rpc_url.to_string()
+    }
+}
+
+/// Get all accounts of the given anchor type owned by a program that have
+/// the given key at the given offset
+#[cfg(feature = "client")]
+pub(crate) async fn anchor_program_accounts<
+    C: AsRef<SolanaRpcClient>,
+    T: AccountDeserialize + Discriminator,
+>(
+    client: &C,
+    program_id: &Pubkey,
+    offset: usize,
+    key: &Pubkey,
+) -> Result<Vec<(Pubkey, T)>, Error> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &T::DISCRIMINATOR)),
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(offset, key.as_ref())),
+        ]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    client
+        .as_ref()
+        .get_program_accounts_with_config(program_id, config)
+        .await?
+        .into_iter()
+        .map(|(address, account)| {
+            T::try_deserialize(&mut account.data.as_slice())
+                .map(|decoded| (address, decoded))
+                .map_err(|err| DecodeError::other(err.to_string()).into())
+        })
+        .collect()
+}
+
+#[cfg(feature = "client")]
 #[derive(Clone)]
 pub struct Client {
     pub solana_client: Arc<SolanaRpcClient>,
@@ -44,6 +133,7 @@ pub struct Client {
     pub cert_client: Arc<CertClient>,
 }
 
+#[cfg(feature = "client")]
 #[async_trait::async_trait]
 pub trait GetAnchorAccount {
     async fn anchor_account<T: AccountDeserialize>(&self, pubkey: &Pubkey) -> Result<T, Error>;
@@ -53,6 +143,7 @@ pub trait GetAnchorAccount {
     ) -> Result<Vec<Option<T>>, Error>;
 }
 
+#[cfg(feature = "client")]
 #[async_trait::async_trait]
 impl GetAnchorAccount for SolanaRpcClient {
     async fn anchor_account<T: AccountDeserialize>(&self, pubkey: &Pubkey) -> Result<T, Error> {
@@ -94,6 +185,7 @@ impl GetAnchorAccount for SolanaRpcClient {
     }
 }
 
+#[cfg(feature = "client")]
 #[async_trait::async_trait]
 impl GetAnchorAccount for Client {
     async fn anchor_account<T: AccountDeserialize>(
@@ -110,21 +202,130 @@ impl GetAnchorAccount for Client {
     }
 }
 
+#[cfg(feature = "client")]
+impl Client {
+    pub fn builder(url: &str) -> ClientBuilder {
+        ClientBuilder::new(url)
+    }
+}
+
+#[cfg(feature = "client")]
 impl TryFrom<&str> for Client {
     type Error = Error;
     fn try_from(value: &str) -> Result<Self, Self::Error> {
+        ClientBuilder::new(value).build()
+    }
+}
+
+/// Builder for a [`Client`] with non-default RPC settings. The url is either
+/// a Solana RPC url or one of the `m`/`mainnet-beta` and `d`/`devnet`
+/// shortcuts, or a comma separated list of those to fail over between.
+/// DAS and certificate requests use the first url.
+#[cfg(feature = "client")]
+#[derive(Clone)]
+pub struct ClientBuilder {
+    url: String,
+    retry_policy: RetryPolicy,
+    commitment: CommitmentConfig,
+    timeout: Duration,
+    headers: Vec<(String, String)>,
+    das_rate_limit: Option<u32>,
+}
+
+/// Header values often hold API keys, so only header names are shown
+#[cfg(feature = "client")]
+impl std::fmt::Debug for ClientBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientBuilder")
+            .field("url", &self.url)
+            .field("retry_policy", &self.retry_policy)
+            .field("commitment", &self.commitment)
+            .field("timeout", &self.timeout)
+            .field("headers", &redacted_headers(&self.headers))
+            .field("das_rate_limit", &self.das_rate_limit)
+            .finish()
+    }
+}
+
+/// The given headers with their values redacted, for debug output
+pub fn redacted_headers(headers: &[(String, String)]) -> Vec<(&str, &str)> {
+    headers
+        .iter()
+        .map(|(name, _)| (name.as_str(), "<redacted>"))
+        .collect()
+}
+
+#[cfg(feature = "client")]
+impl ClientBuilder {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            retry_policy: RetryPolicy::default(),
+            commitment: CommitmentConfig::default(),
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+            headers: vec![],
+            das_rate_limit: None,
+        }
+    }
+
+    /// Add a header to every Solana RPC and DAS request, like the API key
+    /// header some RPC providers use for authentication. Header values are
+    /// marked sensitive and are not included in debug output.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Set the timeout of a single Solana RPC or DAS request. Retried
+    /// requests get the full timeout for every attempt.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the commitment used for reads, blockhashes and confirmation
+    /// waits. Defaults to finalized.
+    pub fn commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    /// Limit DAS requests to the given number per second
+    pub fn das_rate_limit(mut self, requests_per_second: u32) -> Self {
+        self.das_rate_limit = Some(requests_per_second);
+        self
+    }
+
+    /// Set the policy for retrying Solana RPC and DAS requests that fail for
+    /// transient reasons
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn build(self) -> Result<Client, Error> {
         fn maybe_env(key: &str) -> Option<String> {
             std::env::var(key).ok()
         }
         fn env_or(key: &str, default: &str) -> String {
             maybe_env(key).unwrap_or_else(|| default.to_string())
         }
-        let rpc_url = match value {
-            "m" | "mainnet-beta" => env_or(SOLANA_URL_MAINNET_ENV, SOLANA_URL_MAINNET),
-            "d" | "devnet" => env_or(SOLANA_URL_DEVNET_ENV, SOLANA_URL_DEVNET),
-            url => url.to_string(),
+        let rpc_urls = self
+            .url
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(|url| match url {
+                "m" | "mainnet-beta" => env_or(SOLANA_URL_MAINNET_ENV, SOLANA_URL_MAINNET),
+                "d" | "devnet" => env_or(SOLANA_URL_DEVNET_ENV, SOLANA_URL_DEVNET),
+                url => url.to_string(),
+            })
+            .collect::<Vec<_>>();
+        let Some(rpc_url) = rpc_urls.first().cloned() else {
+            return Err(DecodeError::other("no rpc url").into());
         };
-        let (cert_url, cert_token) = match value {
+        let first_url = self.url.split(',').next().unwrap_or_default().trim();
+        let (cert_url, cert_token) = match first_url {
             "d" | "devnet" => (
                 env_or(CERT_URL_DEVNET_ENV, CERT_URL_DEVNET),
                 maybe_env(CERT_TOKEN_DEVNET_ENV),
@@ -135,10 +336,62 @@ impl TryFrom<&str> for Client {
             ),
             _url => (env_or(CERT_URL_MAINNET_ENV, CERT_URL_MAINNET), None),
         };
-        let das_client = Arc::new(DasClient::with_base_url(&rpc_url)?);
-        let solana_client = Arc::new(SolanaRpcClient::new(rpc_url));
+        let mut headers = reqwest::header::HeaderMap::new();
+        for (name, value) in &self.headers {
+            let name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| DecodeError::other(format!("invalid header name: {name}")))?;
+            let mut value = reqwest::header::HeaderValue::from_str(value)
+                .map_err(|_| DecodeError::other(format!("invalid value for header {name}")))?;
+            value.set_sensitive(true);
+            headers.append(name, value);
+        }
+        let das_client = DasClient::with_client(
+            &rpc_url,
+            reqwest::Client::builder()
+                .default_headers(headers.clone())
+                .timeout(self.timeout)
+                .build()
+                .map_err(Error::from)?,
+        )?
+        .with_retry_policy(self.retry_policy);
+        let das_client = Arc::new(match self.das_rate_limit {
+            Some(requests_per_second) => das_client.with_rate_limit(requests_per_second),
+            None => das_client,
+        });
+        let mut rpc_headers = solana_client::http_sender::HttpSender::default_headers();
+        rpc_headers.extend(headers);
+        let rpc_http_client = reqwest::Client::builder()
+            .default_headers(rpc_headers)
+            .timeout(self.timeout)
+            .pool_idle_timeout(self.timeout)
+            .build()
+            .map_err(Error::from)?;
+        let config = solana_client::rpc_client::RpcClientConfig::with_commitment(self.commitment);
+        let mut endpoints = rpc_urls
+            .into_iter()
+            .map(|url| {
+                solana_client::http_sender::HttpSender::new_with_client(
+                    url,
+                    rpc_http_client.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let solana_client = if endpoints.len() == 1 {
+            SolanaRpcClient::new_sender(
+                sender::RetrySender::new(endpoints.remove(0), self.retry_policy)
+                    .with_request_timeout(self.timeout),
+                config,
+            )
+        } else {
+            SolanaRpcClient::new_sender(
+                sender::RetrySender::new(sender::FailoverSender::new(endpoints), self.retry_policy)
+                    .with_request_timeout(self.timeout),
+                config,
+            )
+        };
+        let solana_client = Arc::new(solana_client);
         let cert_client = Arc::new(CertClient::new(&cert_url, cert_token)?);
-        Ok(Self {
+        Ok(Client {
             solana_client,
             das_client,
             cert_client,
@@ -146,196 +399,28 @@ impl TryFrom<&str> for Client {
     }
 }
 
+#[cfg(feature = "client")]
 impl AsRef<SolanaRpcClient> for Client {
     fn as_ref(&self) -> &SolanaRpcClient {
         &self.solana_client
     }
 }
 
+#[cfg(feature = "client")]
 impl AsRef<DasClient> for Client {
     fn as_ref(&self) -> &DasClient {
         &self.das_client
     }
 }
 
+#[cfg(feature = "client")]
 impl AsRef<CertClient> for Client {
     fn as_ref(&self) -> &CertClient {
         &self.cert_client
     }
 }
 
-#[derive(
-    serde::Serialize, Default, Debug, Clone, std::hash::Hash, PartialEq, Eq, PartialOrd, Ord,
-)]
-#[serde(rename_all = "camelCase")]
-pub struct DasSearchAssetsParams {
-    #[serde(skip_serializing_if = "std::ops::Not::not")]
-    pub creator_verified: bool,
-    #[serde(
-        with = "keypair::serde_opt_pubkey",
-        skip_serializing_if = "Option::is_none"
-    )]
-    pub creator_address: Option<Pubkey>,
-    #[serde(
-        with = "keypair::serde_opt_pubkey",
-        skip_serializing_if = "Option::is_none"
-    )]
-    pub owner_address: Option<Pubkey>,
-    #[serde(skip_serializing_if = "is_zero")]
-    pub page: u32,
-    #[serde(skip_serializing_if = "is_zero")]
-    pub limit: u32,
-}
-
-impl DasSearchAssetsParams {
-    pub fn for_owner(owner_address: Pubkey, creator_address: Pubkey) -> Self {
-        Self {
-            owner_address: Some(owner_address),
-            creator_address: Some(creator_address),
-            creator_verified: true,
-            page: 1,
-            ..Default::default()
-        }
-    }
-}
-
-#[derive(Debug, thiserror::Error)]
-pub enum DasClientError {
-    #[error("jsonrpc: {0}")]
-    Rpc(#[from] jsonrpc_client::Error<reqwest::Error>),
-    #[error("json error {0}")]
-    Json(#[from] serde_json::Error),
-}
-
-impl From<reqwest::Error> for DasClientError {
-    fn from(value: reqwest::Error) -> Self {
-        jsonrpc_client::Error::from(value).into()
-    }
-}
-
-impl From<JsonRpcError> for DasClientError {
-    fn from(value: JsonRpcError) -> Self {
-        Self::from(jsonrpc_client::Error::JsonRpc(value))
-    }
-}
-
-impl DasClientError {
-    pub fn is_account_not_found(&self) -> bool {
-        match self {
-            Self::Rpc(jsonrpc_client::Error::JsonRpc(jsonrpc_client::JsonRpcError {
-                message,
-                ..
-            })) => message.starts_with("Database Error: RecordNotFound"),
-            _other => false,
-        }
-    }
-}
-
-#[jsonrpc_client::api]
-pub trait DAS {}
-
-static USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"),);
-
-#[jsonrpc_client::implement(DAS)]
-#[derive(Debug, Clone)]
-pub struct DasClient {
-    inner: reqwest::Client,
-    base_url: reqwest::Url,
-}
-
-impl Default for DasClient {
-    fn default() -> Self {
-        // safe to unwrap
-        Self::with_base_url(SOLANA_URL_MAINNET).unwrap()
-    }
-}
-
-impl DasClient {
-    pub fn with_base_url(url: &str) -> Result<Self, Error> {
-        let client = reqwest::Client::new();
-        let base_url = url.parse().map_err(DecodeError::from)?;
-        Ok(Self {
-            inner: client,
-            base_url,
-        })
-    }
-
-    #[instrument(skip(self), level = "trace")]
-    pub async fn get_asset(&self, address: &Pubkey) -> Result<asset::Asset, DasClientError> {
-        let body = jsonrpc_client::Request::new_v2("getAsset")
-            .with_argument("id".to_string(), address.to_string())?
-            .serialize()?;
-
-        let response = Result::from(
-            SendRequest::send_request::<asset::Asset>(self, self.base_url.clone(), body)
-                .await?
-                .payload,
-        )?;
-        Ok(response)
-    }
-
-    #[instrument(skip(self), level = "trace")]
-    pub async fn get_asset_proof(
-        &self,
-        address: &Pubkey,
-    ) -> Result<asset::AssetProof, DasClientError> {
-        let body = jsonrpc_client::Request::new_v2("getAssetProof")
-            .with_argument("id".to_string(), address.to_string())?
-            .serialize()?;
-
-        let response = Result::from(
-            SendRequest::send_request::<asset::AssetProof>(self, self.base_url.clone(), body)
-                .await?
-                .payload,
-        )?;
-        Ok(response)
-    }
-
-    #[instrument(skip(self, params), level = "trace")]
-    pub async fn search_assets(
-        &self,
-        params: DasSearchAssetsParams,
-    ) -> Result<asset::AssetPage, DasClientError> {
-        let params =
-            serde_json::to_value(params).map(|value| value.as_object().unwrap().to_owned())?;
-        let mut body = jsonrpc_client::Request::new_v2("searchAssets");
-        body.params = jsonrpc_client::Params::ByName(params.to_owned());
-        let response = Result::from(
-            SendRequest::send_request::<asset::AssetPage>(
-                self,
-                self.base_url.clone(),
-                body.serialize()?,
-            )
-            .await?
-            .payload,
-        )?;
-        Ok(response)
-    }
-}
-
-#[async_trait::async_trait]
-impl jsonrpc_client::SendRequest for DasClient {
-    type Error = reqwest::Error;
-    async fn send_request<P>(
-        &self,
-        endpoint: jsonrpc_client::Url,
-        body: String,
-    ) -> Result<jsonrpc_client::Response<P>, Self::Error>
-    where
-        P: serde::de::DeserializeOwned,
-    {
-        self.inner
-            .post(endpoint)
-            .header(reqwest::header::CONTENT_TYPE, "application/json")
-            .header(reqwest::header::USER_AGENT, USER_AGENT)
-            .body(body)
-            .send()
-            .await?
-            .json()
-            .await
-    }
-}
-
+#[cfg(feature = "client")]
 pub mod config {
     use super::*;
     use crate::{
@@ -353,6 +438,8 @@ pub mod config {
     pub const RPC_TIMEOUT: Duration = Duration::from_secs(5);
     pub const RPC_TCP_KEEPALIVE: Duration = Duration::from_secs(100);
 
+    pub const IOT_CONFIG_URL_MAINNET: &str = "https://config.iot.mainnet.helium.io:6080";
+
     trait MessageSign {
         /// Sign the given message
         fn sign<K: AsRef<helium_crypto::Keypair>>(&mut self, keypair: K) -> Result<(), Error>;
@@ -746,4 +833,192 @@ pub mod config {
             ))
         }
     }
+
+    pub mod route {
+        use super::*;
+        use crate::oui::{EuiPair, RoutingConfig, RoutingUpdate, SessionKeyFilter};
+        use helium_proto::services::iot_config::{
+            route_skf_update_req_v1::RouteSkfUpdateV1, ActionV1, EuiPairV1, RouteClient,
+            RouteEuisResV1, RouteGetEuisReqV1, RouteSkfListReqV1, RouteSkfUpdateReqV1,
+            RouteSkfUpdateResV1, RouteUpdateEuisReqV1,
+        };
+
+        impl_message_sign!(RouteGetEuisReqV1);
+        impl_message_sign!(RouteSkfListReqV1);
+        impl_message_sign!(RouteUpdateEuisReqV1);
+        impl_message_sign!(RouteSkfUpdateReqV1);
+        impl_message_verify!(RouteEuisResV1);
+        impl_message_verify!(RouteSkfUpdateResV1);
+
+        /// The maximum number of session key filter changes sent in one
+        /// update request
+        const SKF_UPDATE_BATCH_SIZE: usize = 100;
+
+        /// A client for the route service of the IoT config service, which
+        /// holds the EUIs and session key filters of an organization's routes
+        #[derive(Clone)]
+        pub struct Client {
+            keypair: Arc<helium_crypto::Keypair>,
+            client: RouteClient<Channel>,
+            address: Option<helium_crypto::PublicKey>,
+        }
+
+        fn timestamp() -> u64 {
+            chrono::Utc::now().timestamp_millis() as u64
+        }
+
+        impl Client {
+            /// Construct a client signing requests with the given keypair,
+            /// which must be the organization's or one of its delegate keys.
+            /// Responses are verified against the config service key if
+            /// given.
+            pub fn new(
+                uri: &str,
+                address: Option<helium_crypto::PublicKey>,
+                keypair: Arc<helium_crypto::Keypair>,
+            ) -> Result<Self, Error> {
+                let channel = channel_for_uri(uri)?;
+                let client = RouteClient::new(channel);
+                Ok(Self {
+                    client,
+                    address,
+                    keypair,
+                })
+            }
+
+            fn verify<M: MessageVerify>(&self, msg: &M) -> Result<(), Error> {
+                match &self.address {
+                    Some(address) => msg.verify(address),
+                    None => Ok(()),
+                }
+            }
+
+            /// Get the current EUIs and session key filters of a route
+            pub async fn config(&mut self, route_id: &str) -> Result<RoutingConfig, Error> {
+                let mut req = RouteGetEuisReqV1 {
+                    route_id: route_id.to_string(),
+                    timestamp: timestamp(),
+                    signer: self.keypair.public_key().into(),
+                    signature: vec![],
+                };
+                req.sign(&self.keypair)?;
+                let euis: Vec<EuiPair> = self
+                    .client
+                    .get_euis(req)
+                    .await?
+                    .into_inner()
+                    .map_ok(|pair| EuiPair {
+                        app_eui: pair.app_eui,
+                        dev_eui: pair.dev_eui,
+                    })
+                    .try_collect()
+                    .await?;
+
+                let mut req = RouteSkfListReqV1 {
+                    route_id: route_id.to_string(),
+                    timestamp: timestamp(),
+                    signer: self.keypair.public_key().into(),
+                    signature: vec![],
+                };
+                req.sign(&self.keypair)?;
+                let skfs: Vec<SessionKeyFilter> = self
+                    .client
+                    .list_skfs(req)
+                    .await?
+                    .into_inner()
+                    .map_ok(|skf| SessionKeyFilter {
+                        devaddr: skf.devaddr,
+                        session_key: skf.session_key,
+                        max_copies: skf.max_copies,
+                    })
+                    .try_collect()
+                    .await?;
+
+                Ok(RoutingConfig {
+                    route_id: route_id.to_string(),
+                    euis,
+                    skfs,
+                })
+            }
+
+            /// Apply the given changes to the EUIs and session key filters of a
+            /// route
+            pub async fn update(
+                &mut self,
+                route_id: &str,
+                update: &RoutingUpdate,
+            ) -> Result<(), Error> {
+                let eui_changes = update
+                    .euis
+                    .remove
+                    .iter()
+                    .map(|eui| (ActionV1::Remove, eui))
+                    .chain(update.euis.add.iter().map(|eui| (ActionV1::Add, eui)));
+                let mut eui_reqs = vec![];
+                for (action, eui) in eui_changes {
+                    let mut req = RouteUpdateEuisReqV1 {
+                        action: action.into(),
+                        eui_pair: Some(EuiPairV1 {
+                            route_id: route_id.to_string(),
+                            app_eui: eui.app_eui,
+                            dev_eui: eui.dev_eui,
+                        }),
+                        timestamp: timestamp(),
+                        signer: self.keypair.public_key().into(),
+                        signature: vec![],
+                    };
+                    req.sign(&self.keypair)?;
+                    eui_reqs.push(req);
+                }
+                if !eui_reqs.is_empty() {
+                    let res = self
+                        .client
+                        .update_euis(stream::iter(eui_reqs))
+                        .await?
+                        .into_inner();
+                    self.verify(&res)?;
+                }
+
+                let skf_changes: Vec<RouteSkfUpdateV1> = update
+                    .skfs
+                    .remove
+                    .iter()
+                    .map(|skf| (ActionV1::Remove, skf))
+                    .chain(update.skfs.add.iter().map(|skf| (ActionV1::Add, skf)))
+                    .map(|(action, skf)| RouteSkfUpdateV1 {
+                        devaddr: skf.devaddr,
+                        session_key: skf.session_key.clone(),
+                        action: action.into(),
+                        max_copies: skf.max_copies,
+                    })
+                    .collect();
+                for updates in skf_changes.chunks(SKF_UPDATE_BATCH_SIZE) {
+                    let mut req = RouteSkfUpdateReqV1 {
+                        route_id: route_id.to_string(),
+                        updates: updates.to_vec(),
+                        timestamp: timestamp(),
+                        signer: self.keypair.public_key().into(),
+                        signature: vec![],
+                    };
+                    req.sign(&self.keypair)?;
+                    let res = self.client.update_skfs(req).await?.into_inner();
+                    self.verify(&res)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "client"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_debug_redacts_headers() {
+        let builder = ClientBuilder::new("m").header("x-api-key", "secret");
+        let debug = format!("{builder:?}");
+        assert!(debug.contains("x-api-key"));
+        assert!(!debug.contains("secret"));
+    }
 }
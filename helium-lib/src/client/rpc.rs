@@ -0,0 +1,201 @@
+#[cfg(feature = "client")]
+use super::SolanaRpcClient;
+use crate::{
+    error::Error,
+    keypair::Pubkey,
+    solana_rpc_client_api::{
+        config::RpcSendTransactionConfig,
+        request::RpcRequest,
+        response::{RpcPrioritizationFee, RpcSimulateTransactionResult},
+    },
+    solana_sdk::{
+        account::Account, commitment_config::CommitmentConfig, hash::Hash, signature::Signature,
+        transaction::VersionedTransaction,
+    },
+};
+use solana_transaction_status::TransactionStatus;
+use std::future::Future;
+
+/// The Solana RPC operations helium-lib needs to look up accounts and
+/// build, simulate and send transactions.
+///
+/// Every type that derefs to a `SolanaRpcClient` through `AsRef`, like the
+/// `Client` of the `client` feature, implements this trait, as does the
+/// [`HttpRpc`](super::HttpRpc) transport which also works on wasm32.
+/// Applications can implement it for their own types, or use the in-memory
+/// `MockRpc` from the `test-utils` feature to test flows without a
+/// validator.
+pub trait SolanaRpc {
+    /// The commitment used for reads and blockhashes
+    fn commitment(&self) -> CommitmentConfig;
+
+    /// The url of the RPC endpoint in use
+    fn url(&self) -> String;
+
+    /// Get the given accounts, with `None` for accounts that do not exist
+    fn get_multiple_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> impl Future<Output = Result<Vec<Option<Account>>, Error>> + Send;
+
+    /// Get an account, failing with an account not found error if it does
+    /// not exist
+    fn get_account(&self, pubkey: &Pubkey) -> impl Future<Output = Result<Account, Error>> + Send;
+
+    /// Get the latest blockhash and the last block height it is valid for
+    /// at the given commitment
+    fn get_latest_blockhash_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> impl Future<Output = Result<(Hash, u64), Error>> + Send;
+
+    /// Get the latest blockhash at the commitment of the client
+    fn get_latest_blockhash(&self) -> impl Future<Output = Result<(Hash, u64), Error>> + Send {
+        self.get_latest_blockhash_with_commitment(self.commitment())
+    }
+
+    fn simulate_transaction(
+        &self,
+        tx: &VersionedTransaction,
+    ) -> impl Future<Output = Result<RpcSimulateTransactionResult, Error>> + Send;
+
+    fn send_transaction(
+        &self,
+        tx: &VersionedTransaction,
+    ) -> impl Future<Output = Result<Signature, Error>> + Send;
+
+    /// Send a transaction with the given preflight and retry options
+    fn send_transaction_with_config(
+        &self,
+        tx: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> impl Future<Output = Result<Signature, Error>> + Send;
+
+    /// Get the status of the given signatures from the full transaction
+    /// history, including transactions that were only processed so far.
+    /// Signatures that are not known are `None`.
+    fn get_signature_statuses_with_history(
+        &self,
+        signatures: &[Signature],
+    ) -> impl Future<Output = Result<Vec<Option<TransactionStatus>>, Error>> + Send;
+
+    /// Get the current block height at the given commitment
+    fn get_block_height_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> impl Future<Output = Result<u64, Error>> + Send;
+
+    /// Get the prioritization fees of recent slots for transactions that
+    /// write the given accounts
+    fn get_recent_prioritization_fees(
+        &self,
+        accounts: &[Pubkey],
+    ) -> impl Future<Output = Result<Vec<RpcPrioritizationFee>, Error>> + Send;
+
+    /// Send a JSON-RPC request that has no dedicated method, like provider
+    /// specific fee estimates
+    fn send_request(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> impl Future<Output = Result<serde_json::Value, Error>> + Send;
+}
+
+#[cfg(feature = "client")]
+impl<T: AsRef<SolanaRpcClient>> SolanaRpc for T {
+    fn commitment(&self) -> CommitmentConfig {
+        self.as_ref().commitment()
+    }
+
+    fn url(&self) -> String {
+        self.as_ref().url()
+    }
+
+    fn get_multiple_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> impl Future<Output = Result<Vec<Option<Account>>, Error>> + Send {
+        let client = self.as_ref();
+        async move { Ok(client.get_multiple_accounts(pubkeys).await?) }
+    }
+
+    fn get_account(&self, pubkey: &Pubkey) -> impl Future<Output = Result<Account, Error>> + Send {
+        let client = self.as_ref();
+        async move { Ok(client.get_account(pubkey).await?) }
+    }
+
+    fn get_latest_blockhash_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> impl Future<Output = Result<(Hash, u64), Error>> + Send {
+        let client = self.as_ref();
+        async move {
+            Ok(client
+                .get_latest_blockhash_with_commitment(commitment)
+                .await?)
+        }
+    }
+
+    fn simulate_transaction(
+        &self,
+        tx: &VersionedTransaction,
+    ) -> impl Future<Output = Result<RpcSimulateTransactionResult, Error>> + Send {
+        let client = self.as_ref();
+        async move { Ok(client.simulate_transaction(tx).await?.value) }
+    }
+
+    fn send_transaction(
+        &self,
+        tx: &VersionedTransaction,
+    ) -> impl Future<Output = Result<Signature, Error>> + Send {
+        let client = self.as_ref();
+        async move { Ok(client.send_transaction(tx).await?) }
+    }
+
+    fn send_transaction_with_config(
+        &self,
+        tx: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> impl Future<Output = Result<Signature, Error>> + Send {
+        let client = self.as_ref();
+        async move { Ok(client.send_transaction_with_config(tx, config).await?) }
+    }
+
+    fn get_signature_statuses_with_history(
+        &self,
+        signatures: &[Signature],
+    ) -> impl Future<Output = Result<Vec<Option<TransactionStatus>>, Error>> + Send {
+        let client = self.as_ref();
+        async move {
+            Ok(client
+                .get_signature_statuses_with_history(signatures)
+                .await?
+                .value)
+        }
+    }
+
+    fn get_block_height_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> impl Future<Output = Result<u64, Error>> + Send {
+        let client = self.as_ref();
+        async move { Ok(client.get_block_height_with_commitment(commitment).await?) }
+    }
+
+    fn get_recent_prioritization_fees(
+        &self,
+        accounts: &[Pubkey],
+    ) -> impl Future<Output = Result<Vec<RpcPrioritizationFee>, Error>> + Send {
+        let client = self.as_ref();
+        async move { Ok(client.get_recent_prioritization_fees(accounts).await?) }
+    }
+
+    fn send_request(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> impl Future<Output = Result<serde_json::Value, Error>> + Send {
+        let client = self.as_ref();
+        async move { Ok(client.send(request, params).await?) }
+    }
+}
@@ -0,0 +1,580 @@
+#[cfg(feature = "client")]
+use crate::solana_client::{
+    client_error::Result,
+    http_sender::HttpSender,
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+use crate::solana_rpc_client_api::{
+    client_error::{Error as ClientError, ErrorKind as ClientErrorKind},
+    request::{RpcError, RpcRequest},
+};
+#[cfg(feature = "client")]
+use crate::time::Instant;
+#[cfg(feature = "client")]
+use futures::future::join_all;
+#[cfg(feature = "client")]
+use std::sync::{
+    atomic::{AtomicU64, AtomicUsize, Ordering},
+    Mutex,
+};
+use std::{sync::OnceLock, time::Duration};
+#[cfg(feature = "client")]
+use tracing::Instrument;
+
+/// JSON-RPC server error codes for conditions that are expected to clear up
+/// when the request is retried, like a node that is behind or a block that
+/// is not available yet.
+const RETRYABLE_SERVER_ERRORS: &[i64] = &[
+    -32004, // block not available
+    -32005, // node unhealthy
+    -32014, // block status not available yet
+    -32016, // minimum context slot not reached
+];
+
+/// Policy for retrying RPC requests that fail for transient reasons
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total number of attempts for a request, including the first one
+    pub max_attempts: u32,
+    /// Backoff before the first retry, doubled for every following retry
+    pub initial_backoff: Duration,
+    /// Upper bound for the backoff between attempts
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// The exponential backoff after the given failed attempt, starting at 1.
+    /// The returned backoff is jittered between half and all of the
+    /// exponential backoff to avoid retrying in lock step with other clients.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_backoff);
+        let half = exponential / 2;
+        half + half.mul_f64(jitter())
+    }
+}
+
+/// A pseudo random value in [0, 1) that is good enough to spread out retries
+fn jitter() -> f64 {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.subsec_nanos())
+        .unwrap_or_default();
+    f64::from(nanos % 1_000) / 1_000.0
+}
+
+/// Whether an RPC error is transient, like a rate limit, timeout or a node
+/// that is behind, so the request can be retried
+pub fn is_retryable(err: &ClientError) -> bool {
+    match err.kind() {
+        ClientErrorKind::Io(_) => true,
+        ClientErrorKind::Reqwest(err) => is_retryable_http(err),
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => {
+            RETRYABLE_SERVER_ERRORS.contains(code)
+        }
+        _ => false,
+    }
+}
+
+/// Whether an HTTP request failed for transient reasons, like a timeout, a
+/// connection failure, a rate limit or a gateway error
+pub(crate) fn is_retryable_http(err: &reqwest::Error) -> bool {
+    err.is_timeout() || is_connect(err) || err.status().is_some_and(is_retryable_status)
+}
+
+/// Whether an HTTP request failed to connect
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn is_connect(err: &reqwest::Error) -> bool {
+    err.is_connect()
+}
+
+/// Whether an HTTP request failed to connect. Browsers do not tell failed
+/// connections apart from other failed fetches, so any failed fetch counts.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn is_connect(err: &reqwest::Error) -> bool {
+    err.is_request()
+}
+
+/// Whether an HTTP status is a rate limit or a gateway error
+pub(crate) fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    status.as_u16() == 429 || matches!(status.as_u16(), 502..=504)
+}
+
+/// Observer of completed RPC requests, called with the request, the time it
+/// took including retries and whether it succeeded
+pub type RequestObserver = fn(&RpcRequest, Duration, bool);
+
+static REQUEST_OBSERVER: OnceLock<RequestObserver> = OnceLock::new();
+
+/// Set the observer of completed RPC requests, for example to collect
+/// latency metrics. Only the first observer set is kept.
+pub fn set_request_observer(observer: RequestObserver) {
+    let _ = REQUEST_OBSERVER.set(observer);
+}
+
+/// Call the request observer, if any, with a completed request
+pub(crate) fn observe_request(request: &RpcRequest, elapsed: Duration, ok: bool) {
+    if let Some(observer) = REQUEST_OBSERVER.get() {
+        observer(request, elapsed, ok);
+    }
+}
+
+/// An HTTP RPC sender that retries transient failures with jittered
+/// exponential backoff.
+///
+/// Resending a signed transaction is safe since the network deduplicates
+/// transactions by signature, so all requests are retried alike.
+#[cfg(feature = "client")]
+pub struct RetrySender<S = HttpSender> {
+    inner: S,
+    policy: RetryPolicy,
+    /// The timeout of a single request, reported with transport timeouts
+    request_timeout: Option<Duration>,
+    /// Id of the next request, used to correlate the trace events of a
+    /// request and its retries
+    next_id: AtomicU64,
+}
+
+#[cfg(feature = "client")]
+impl<S> RetrySender<S> {
+    pub fn new(inner: S, policy: RetryPolicy) -> Self {
+        Self {
+            inner,
+            policy,
+            request_timeout: None,
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Report transport timeouts as [`RequestTimedOut`] with the given
+    /// request timeout, which converts into a helium-lib timeout error
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    fn map_timeout(&self, request: RpcRequest, err: ClientError) -> ClientError {
+        match (self.request_timeout, err.kind()) {
+            (Some(timeout), ClientErrorKind::Reqwest(reqwest_err)) if reqwest_err.is_timeout() => {
+                timed_out(request, timeout)
+            }
+            _ => err,
+        }
+    }
+}
+
+/// A request that timed out after the given request timeout. Carried as the
+/// source of an io error since RPC senders can only fail with client errors.
+#[derive(Debug, thiserror::Error)]
+#[error("request timed out after {0:?}")]
+pub struct RequestTimedOut(pub Duration);
+
+/// The client error for a request that timed out after the given request
+/// timeout
+pub(crate) fn timed_out(request: RpcRequest, timeout: Duration) -> ClientError {
+    ClientError::new_with_request(
+        ClientErrorKind::Io(std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            RequestTimedOut(timeout),
+        )),
+        request,
+    )
+}
+
+#[cfg(feature = "client")]
+#[async_trait::async_trait]
+impl<S: RpcSender + Send + Sync> RpcSender for RetrySender<S> {
+    async fn send(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let span = tracing::debug_span!("rpc", id, %request);
+        async move {
+            let start = Instant::now();
+            let mut attempt = 1;
+            loop {
+                match self.inner.send(request, params.clone()).await {
+                    Err(err) if attempt < self.policy.max_attempts && is_retryable(&err) => {
+                        let backoff = self.policy.backoff(attempt);
+                        tracing::debug!(attempt, ?backoff, %err, "retrying rpc request");
+                        crate::time::sleep(backoff).await;
+                        attempt += 1;
+                    }
+                    result => {
+                        let result = result.map_err(|err| self.map_timeout(request, err));
+                        let elapsed = start.elapsed();
+                        tracing::debug!(
+                            attempts = attempt,
+                            ?elapsed,
+                            ok = result.is_ok(),
+                            "rpc request done"
+                        );
+                        observe_request(&request, elapsed, result.is_ok());
+                        return result;
+                    }
+                }
+            }
+        }
+        .instrument(span)
+        .await
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.inner.get_transport_stats()
+    }
+
+    fn url(&self) -> String {
+        self.inner.url()
+    }
+}
+
+/// Endpoints more than this many slots behind the most recent slot seen by
+/// any endpoint are considered stale
+#[cfg(feature = "client")]
+const MAX_SLOT_LAG: u64 = 150;
+/// How often endpoints are health checked and ranked again
+#[cfg(feature = "client")]
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(300);
+
+/// An RPC sender over a number of endpoints that prefers the fastest healthy
+/// endpoint and fails over to the next one when a request fails for
+/// transient reasons.
+///
+/// Endpoints are health checked on first use and again periodically and
+/// after a failover. Unhealthy endpoints and endpoints whose slot lags
+/// behind the other endpoints are ranked last. Endpoints are also demoted as
+/// soon as the context slot of one of their responses lags behind.
+#[cfg(feature = "client")]
+pub struct FailoverSender<S = HttpSender> {
+    endpoints: Vec<S>,
+    /// Endpoint indices, best first
+    ranking: Mutex<Vec<usize>>,
+    /// Index into the ranking of the endpoint currently in use
+    current: AtomicUsize,
+    last_check: Mutex<Option<Instant>>,
+    /// The most recent slot seen from any endpoint
+    max_slot: AtomicU64,
+}
+
+/// The result of checking an endpoint
+#[cfg(feature = "client")]
+#[derive(Debug, Clone)]
+pub struct EndpointHealth {
+    pub url: String,
+    pub healthy: bool,
+    pub slot: Option<u64>,
+    pub latency: Option<Duration>,
+}
+
+#[cfg(feature = "client")]
+impl<S: RpcSender> FailoverSender<S> {
+    pub fn new(endpoints: Vec<S>) -> Self {
+        let ranking = (0..endpoints.len()).collect();
+        Self {
+            endpoints,
+            ranking: Mutex::new(ranking),
+            current: AtomicUsize::new(0),
+            last_check: Mutex::new(None),
+            max_slot: AtomicU64::new(0),
+        }
+    }
+
+    fn endpoint(&self) -> usize {
+        let ranking = self.ranking.lock().expect("ranking lock");
+        ranking[self.current.load(Ordering::Relaxed) % ranking.len()]
+    }
+
+    fn needs_check(&self) -> bool {
+        let last_check = *self.last_check.lock().expect("health check lock");
+        match last_check {
+            _ if self.endpoints.len() < 2 => false,
+            Some(checked) => checked.elapsed() >= HEALTH_CHECK_INTERVAL,
+            None => true,
+        }
+    }
+
+    /// Check the health, slot and latency of all endpoints and rank them
+    pub async fn health_check(&self) -> Vec<EndpointHealth> {
+        *self.last_check.lock().expect("health check lock") = Some(Instant::now());
+        let health = join_all(self.endpoints.iter().map(|endpoint| async move {
+            let start = Instant::now();
+            let healthy = endpoint
+                .send(RpcRequest::GetHealth, serde_json::Value::Null)
+                .await
+                .is_ok();
+            let latency = start.elapsed();
+            let slot = endpoint
+                .send(RpcRequest::GetSlot, serde_json::Value::Null)
+                .await
+                .ok()
+                .and_then(|slot| slot.as_u64());
+            EndpointHealth {
+                url: endpoint.url(),
+                healthy,
+                slot,
+                latency: healthy.then_some(latency),
+            }
+        }))
+        .await;
+
+        let max_slot = health.iter().filter_map(|health| health.slot).max();
+        if let Some(max_slot) = max_slot {
+            self.max_slot.fetch_max(max_slot, Ordering::Relaxed);
+        }
+        let usable = |health: &EndpointHealth| {
+            health.healthy
+                && matches!((health.slot, max_slot), (Some(slot), Some(max)) if max - slot <= MAX_SLOT_LAG)
+        };
+        let mut ranking = (0..health.len()).collect::<Vec<_>>();
+        ranking.sort_by_key(|&index| (!usable(&health[index]), health[index].latency));
+        tracing::debug!(?health, ?ranking, "ranked rpc endpoints");
+        *self.ranking.lock().expect("ranking lock") = ranking;
+        self.current.store(0, Ordering::Relaxed);
+        health
+    }
+
+    /// Demote the given endpoint when the slot of one of its responses lags
+    /// behind the most recent slot seen from any endpoint
+    fn observe_slot(&self, endpoint: usize, slot: u64) {
+        let max_slot = self.max_slot.fetch_max(slot, Ordering::Relaxed).max(slot);
+        if max_slot - slot <= MAX_SLOT_LAG || self.endpoints.len() < 2 {
+            return;
+        }
+        tracing::debug!(
+            url = self.endpoints[endpoint].url(),
+            slot,
+            max_slot,
+            "demoting lagging rpc endpoint"
+        );
+        let mut ranking = self.ranking.lock().expect("ranking lock");
+        ranking.retain(|&index| index != endpoint);
+        ranking.push(endpoint);
+        self.current.store(0, Ordering::Relaxed);
+    }
+}
+
+/// The slot a response was served at, from the slot request itself or from
+/// the context of responses that carry one
+#[cfg(feature = "client")]
+fn response_slot(request: RpcRequest, response: &serde_json::Value) -> Option<u64> {
+    match request {
+        RpcRequest::GetSlot => response.as_u64(),
+        _ => response
+            .get("context")
+            .and_then(|context| context.get("slot"))
+            .and_then(|slot| slot.as_u64()),
+    }
+}
+
+#[cfg(feature = "client")]
+#[async_trait::async_trait]
+impl<S: RpcSender + Send + Sync> RpcSender for FailoverSender<S> {
+    async fn send(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        if self.needs_check() {
+            self.health_check().await;
+        }
+        let mut failovers = 0;
+        loop {
+            let index = self.endpoint();
+            let endpoint = &self.endpoints[index];
+            match endpoint.send(request, params.clone()).await {
+                Ok(response) => {
+                    // A lagging response is still returned, the endpoint is
+                    // only avoided for the following requests
+                    if let Some(slot) = response_slot(request, &response) {
+                        self.observe_slot(index, slot);
+                    }
+                    return Ok(response);
+                }
+                Err(err) if failovers + 1 < self.endpoints.len() && is_retryable(&err) => {
+                    tracing::debug!(%request, url = endpoint.url(), %err, "failing over rpc endpoint");
+                    self.current.fetch_add(1, Ordering::Relaxed);
+                    // rank the endpoints again on the next request
+                    *self.last_check.lock().expect("health check lock") = None;
+                    failovers += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.endpoints
+            .iter()
+            .map(|endpoint| endpoint.get_transport_stats())
+            .fold(RpcTransportStats::default(), |mut total, stats| {
+                total.request_count += stats.request_count;
+                total.elapsed_time += stats.elapsed_time;
+                total.rate_limited_time += stats.rate_limited_time;
+                total
+            })
+    }
+
+    fn url(&self) -> String {
+        self.endpoints[self.endpoint()].url()
+    }
+}
+
+#[cfg(all(test, feature = "client"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_is_bounded() {
+        let policy = RetryPolicy::default();
+        for attempt in 1..10 {
+            let exponential =
+                (policy.initial_backoff * 2u32.pow(attempt - 1)).min(policy.max_backoff);
+            let backoff = policy.backoff(attempt);
+            assert!(backoff >= exponential / 2 && backoff <= exponential);
+        }
+        assert_eq!(1, RetryPolicy::none().max_attempts);
+        assert_eq!(1, RetryPolicy::default().with_max_attempts(0).max_attempts);
+    }
+
+    /// An endpoint answering health and slot requests, and every other
+    /// request with its url in a response context at its slot
+    struct MockEndpoint {
+        url: &'static str,
+        healthy: bool,
+        slot: AtomicU64,
+        down: std::sync::atomic::AtomicBool,
+    }
+
+    impl MockEndpoint {
+        fn new(url: &'static str, healthy: bool, slot: u64) -> Self {
+            Self {
+                url,
+                healthy,
+                slot: AtomicU64::new(slot),
+                down: Default::default(),
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl RpcSender for MockEndpoint {
+        async fn send(
+            &self,
+            request: RpcRequest,
+            _params: serde_json::Value,
+        ) -> Result<serde_json::Value> {
+            if self.down.load(Ordering::Relaxed) {
+                return Err(std::io::Error::other("endpoint down").into());
+            }
+            let slot = self.slot.load(Ordering::Relaxed);
+            match request {
+                RpcRequest::GetHealth if !self.healthy => {
+                    Err(std::io::Error::other("unhealthy").into())
+                }
+                RpcRequest::GetHealth => Ok(serde_json::json!("ok")),
+                RpcRequest::GetSlot => Ok(serde_json::json!(slot)),
+                _ => Ok(serde_json::json!({
+                    "context": { "slot": slot },
+                    "value": self.url,
+                })),
+            }
+        }
+
+        fn get_transport_stats(&self) -> RpcTransportStats {
+            RpcTransportStats::default()
+        }
+
+        fn url(&self) -> String {
+            self.url.to_string()
+        }
+    }
+
+    async fn send(sender: &FailoverSender<MockEndpoint>) -> serde_json::Value {
+        sender
+            .send(RpcRequest::GetBalance, serde_json::Value::Null)
+            .await
+            .expect("send")["value"]
+            .clone()
+    }
+
+    #[tokio::test]
+    async fn ranks_unhealthy_and_lagging_endpoints_last() {
+        let sender = FailoverSender::new(vec![
+            MockEndpoint::new("unhealthy", false, 1_000),
+            MockEndpoint::new("lagging", true, 1_000 - MAX_SLOT_LAG - 1),
+            MockEndpoint::new("good", true, 1_000),
+        ]);
+        let health = sender.health_check().await;
+        assert_eq!(3, health.len());
+        assert!(!health[0].healthy);
+        assert_eq!("good", send(&sender).await);
+        assert_eq!(2, *sender.ranking.lock().unwrap().first().unwrap());
+    }
+
+    #[tokio::test]
+    async fn fails_over_on_transient_errors() {
+        let sender = FailoverSender::new(vec![
+            MockEndpoint::new("first", true, 1_000),
+            MockEndpoint::new("second", true, 1_000),
+        ]);
+        sender.health_check().await;
+        let preferred = sender.endpoint();
+        sender.endpoints[preferred]
+            .down
+            .store(true, Ordering::Relaxed);
+        let other = sender.endpoints[1 - preferred].url;
+        assert_eq!(other, send(&sender).await);
+        // all endpoints down returns the error of the last one
+        sender.endpoints[1 - preferred]
+            .down
+            .store(true, Ordering::Relaxed);
+        assert!(sender
+            .send(RpcRequest::GetBalance, serde_json::Value::Null)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn demotes_endpoints_lagging_in_responses() {
+        let sender = FailoverSender::new(vec![
+            MockEndpoint::new("first", true, 1_000),
+            MockEndpoint::new("second", true, 1_000),
+        ]);
+        sender.health_check().await;
+        let preferred = sender.endpoint();
+        sender.endpoints[preferred]
+            .slot
+            .store(1_000 - MAX_SLOT_LAG - 1, Ordering::Relaxed);
+        // the lagging response is returned, but the next request moves on
+        assert_eq!(sender.endpoints[preferred].url, send(&sender).await);
+        assert_eq!(sender.endpoints[1 - preferred].url, send(&sender).await);
+        assert_eq!(1 - preferred, sender.endpoint());
+    }
+}
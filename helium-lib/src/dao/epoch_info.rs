@@ -0,0 +1,235 @@
+//! Decoded DAO and subdao state per rewards epoch
+use crate::{
+    client::{GetAnchorAccount, SolanaRpcClient},
+    dao::{epoch_start, Dao, SubDao},
+    error::{DecodeError, Error},
+    helium_sub_daos::{self, DaoEpochInfoV0, SubDaoEpochInfoV0},
+    keypair::{serde_pubkey, Pubkey},
+    token::{self, Token, TokenAmount},
+};
+use chrono::{DateTime, Utc};
+use futures::{stream, Stream, StreamExt, TryStreamExt};
+use itertools::Itertools;
+use rust_decimal::prelude::*;
+use serde::Serialize;
+use std::ops::Range;
+
+/// Utility scores are stored scaled by this many decimals
+const UTILITY_SCORE_DECIMALS: u32 = 12;
+
+/// Delegated veHNT totals in a subdao are stored scaled by this factor
+const VEHNT_PRECISION_FACTOR: u128 = 1_000_000_000_000;
+
+fn to_datetime(ts: i64) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(ts, 0)
+}
+
+/// Convert a native veHNT amount to a veHNT decimal
+fn ve_hnt(amount: u128) -> Result<Decimal, Error> {
+    let amount = i128::try_from(amount).map_err(DecodeError::other)?;
+    Ok(
+        Decimal::try_from_i128_with_scale(amount, Token::Hnt.decimals().into())
+            .map_err(DecodeError::other)?
+            .normalize(),
+    )
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SubDaoEpochInfo {
+    pub sub_dao: SubDao,
+    pub epoch: u64,
+    pub start: Option<DateTime<Utc>>,
+    #[serde(serialize_with = "token::serde_amount_value")]
+    pub dc_burned: TokenAmount,
+    #[serde(serialize_with = "token::serde_amount_value")]
+    pub dc_onboarding_fees_paid: TokenAmount,
+    /// veHNT delegated to the subdao at the start of the epoch
+    pub vehnt_at_epoch_start: Decimal,
+    /// veHNT of delegated positions whose lockup ends in the epoch
+    pub vehnt_in_closing_positions: Decimal,
+    /// The utility score of the subdao, once calculated for the epoch
+    pub utility_score: Option<Decimal>,
+    /// HNT emitted to the subdao for the epoch
+    #[serde(serialize_with = "token::serde_amount_value")]
+    pub hnt_rewards_issued: TokenAmount,
+    #[serde(serialize_with = "token::serde_amount_value")]
+    pub delegation_rewards_issued: TokenAmount,
+    pub rewards_issued_at: Option<DateTime<Utc>>,
+}
+
+impl SubDaoEpochInfo {
+    pub fn from_account(sub_dao: SubDao, account: &SubDaoEpochInfoV0) -> Result<Self, Error> {
+        let utility_score = account
+            .utility_score
+            .map(|score| {
+                i128::try_from(score)
+                    .map_err(DecodeError::other)
+                    .and_then(|score| {
+                        Decimal::try_from_i128_with_scale(score, UTILITY_SCORE_DECIMALS)
+                            .map_err(DecodeError::other)
+                    })
+                    .map(|score| score.normalize())
+            })
+            .transpose()?;
+        Ok(Self {
+            sub_dao,
+            epoch: account.epoch,
+            start: epoch_start(account.epoch),
+            dc_burned: Token::Dc.amount(account.dc_burned),
+            dc_onboarding_fees_paid: Token::Dc.amount(account.dc_onboarding_fees_paid),
+            vehnt_at_epoch_start: ve_hnt(account.vehnt_at_epoch_start.into())?,
+            vehnt_in_closing_positions: ve_hnt(
+                account.vehnt_in_closing_positions / VEHNT_PRECISION_FACTOR,
+            )?,
+            utility_score,
+            hnt_rewards_issued: Token::Hnt.amount(account.hnt_rewards_issued),
+            delegation_rewards_issued: sub_dao.token().amount(account.delegation_rewards_issued),
+            rewards_issued_at: account.rewards_issued_at.and_then(to_datetime),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DaoEpochInfo {
+    pub epoch: u64,
+    pub start: Option<DateTime<Utc>>,
+    #[serde(serialize_with = "token::serde_amount_value")]
+    pub total_rewards: TokenAmount,
+    #[serde(serialize_with = "token::serde_amount_value")]
+    pub current_hnt_supply: TokenAmount,
+    pub num_utility_scores_calculated: u32,
+    pub done_calculating_scores: bool,
+    pub done_issuing_rewards: bool,
+}
+
+impl From<&DaoEpochInfoV0> for DaoEpochInfo {
+    fn from(value: &DaoEpochInfoV0) -> Self {
+        Self {
+            epoch: value.epoch,
+            start: epoch_start(value.epoch),
+            total_rewards: Token::Hnt.amount(value.total_rewards),
+            current_hnt_supply: Token::Hnt.amount(value.current_hnt_supply),
+            num_utility_scores_calculated: value.num_utility_scores_calculated,
+            done_calculating_scores: value.done_calculating_scores,
+            done_issuing_rewards: value.done_issuing_rewards,
+        }
+    }
+}
+
+/// Get the epoch info of a subdao for the given epochs. Epochs without an
+/// epoch info account are `None`.
+pub async fn sub_dao<C: GetAnchorAccount>(
+    client: &C,
+    sub_dao: SubDao,
+    epochs: &[u64],
+) -> Result<Vec<Option<SubDaoEpochInfo>>, Error> {
+    let keys = epochs
+        .iter()
+        .map(|epoch| sub_dao.epoch_info_key_for(*epoch))
+        .collect_vec();
+    client
+        .anchor_accounts::<SubDaoEpochInfoV0>(&keys)
+        .await?
+        .iter()
+        .map(|info| {
+            info.as_ref()
+                .map(|info| SubDaoEpochInfo::from_account(sub_dao, info))
+                .transpose()
+        })
+        .try_collect()
+}
+
+/// The number of epochs fetched per request when streaming epoch history
+const HISTORY_BATCH_SIZE: usize = 100;
+
+/// Stream the epoch info of a subdao for a range of epochs, in epoch order.
+/// Epochs without an epoch info account are skipped.
+pub fn epoch_history<C: GetAnchorAccount>(
+    client: &C,
+    sub_dao: SubDao,
+    epochs: Range<u64>,
+) -> impl Stream<Item = Result<SubDaoEpochInfo, Error>> + '_ {
+    let batches = epochs
+        .chunks(HISTORY_BATCH_SIZE)
+        .into_iter()
+        .map(|batch| batch.collect_vec())
+        .collect_vec();
+    stream::iter(batches)
+        .then(move |batch| async move { self::sub_dao(client, sub_dao, &batch).await })
+        .map_ok(|infos| stream::iter(infos.into_iter().flatten().map(Ok::<_, Error>)))
+        .try_flatten()
+}
+
+/// Get the epoch info of the DAO for the given epochs. Epochs without an
+/// epoch info account are `None`.
+pub async fn dao<C: GetAnchorAccount>(
+    client: &C,
+    epochs: &[u64],
+) -> Result<Vec<Option<DaoEpochInfo>>, Error> {
+    let keys = epochs
+        .iter()
+        .map(|epoch| Dao::Hnt.epoch_info_key_for(*epoch))
+        .collect_vec();
+    Ok(client
+        .anchor_accounts::<DaoEpochInfoV0>(&keys)
+        .await?
+        .iter()
+        .map(|info| info.as_ref().map(DaoEpochInfo::from))
+        .collect())
+}
+
+/// The current state of a subdao
+#[derive(Debug, Clone, Serialize)]
+pub struct SubDaoInfo {
+    pub sub_dao: SubDao,
+    /// veHNT currently delegated to the subdao
+    pub vehnt_delegated: Decimal,
+    /// HNT emitted to the subdao per epoch at the current point of the
+    /// emission schedule
+    #[serde(serialize_with = "token::serde_amount_value")]
+    pub emissions_per_epoch: TokenAmount,
+    #[serde(with = "serde_pubkey")]
+    pub treasury: Pubkey,
+    #[serde(serialize_with = "token::serde_amount_value")]
+    pub treasury_balance: TokenAmount,
+    #[serde(with = "serde_pubkey")]
+    pub delegator_pool: Pubkey,
+    #[serde(serialize_with = "token::serde_amount_value")]
+    pub delegator_pool_balance: TokenAmount,
+}
+
+/// Get the current state of a subdao: delegated veHNT, emissions and
+/// treasury balances
+pub async fn sub_dao_info<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    sub_dao: SubDao,
+) -> Result<SubDaoInfo, Error> {
+    let account = client
+        .anchor_account::<helium_sub_daos::SubDaoV0>(&sub_dao.key())
+        .await?;
+    let now = Utc::now().timestamp();
+    let emissions_per_epoch = account
+        .emission_schedule
+        .iter()
+        .filter(|item| item.start_unix_time <= now)
+        .max_by_key(|item| item.start_unix_time)
+        .map(|item| item.emissions_per_epoch)
+        .unwrap_or_default();
+    let balances =
+        token::balances_for_many(client, &[account.treasury, account.delegator_pool]).await?;
+    let balance_or_zero = |idx: usize, token: Token| {
+        balances[idx]
+            .as_ref()
+            .map(|balance| balance.amount)
+            .unwrap_or_else(|| token.amount(0))
+    };
+    Ok(SubDaoInfo {
+        sub_dao,
+        vehnt_delegated: ve_hnt(account.vehnt_delegated / VEHNT_PRECISION_FACTOR)?,
+        emissions_per_epoch: Token::Hnt.amount(emissions_per_epoch),
+        treasury: account.treasury,
+        treasury_balance: balance_or_zero(0, Token::Hnt),
+        delegator_pool: account.delegator_pool,
+        delegator_pool_balance: balance_or_zero(1, sub_dao.token()),
+    })
+}
@@ -2,9 +2,25 @@ use crate::{
     data_credits, entity_key::AsEntityKey, helium_entity_manager, helium_sub_daos, keypair::Pubkey,
     programs::TOKEN_METADATA_PROGRAM_ID, rewards_oracle, token::Token,
 };
-use chrono::Timelike;
+use chrono::{DateTime, Utc};
 use sha2::{Digest, Sha256};
 
+pub mod epoch_info;
+pub use epoch_info::epoch_history;
+
+/// The length of a rewards epoch in seconds
+pub const EPOCH_LENGTH: i64 = 60 * 60 * 24;
+
+/// Get the rewards epoch the given time falls in
+pub fn epoch_for(time: DateTime<Utc>) -> u64 {
+    (time.timestamp() / EPOCH_LENGTH) as u64
+}
+
+/// Get the start time of the given rewards epoch
+pub fn epoch_start(epoch: u64) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(epoch as i64 * EPOCH_LENGTH, 0)
+}
+
 #[derive(
     Debug, Clone, Copy, Eq, PartialEq, Hash, serde::Serialize, serde::Deserialize, Default,
 )]
@@ -31,6 +47,14 @@ impl Dao {
         dao_key
     }
 
+    pub fn epoch_info_key_for(&self, epoch: u64) -> Pubkey {
+        let (key, _) = Pubkey::find_program_address(
+            &[b"dao_epoch_info", self.key().as_ref(), &epoch.to_le_bytes()],
+            &helium_sub_daos::ID,
+        );
+        key
+    }
+
     pub fn dataonly_config_key(&self) -> Pubkey {
         let (key, _) = Pubkey::find_program_address(
             &[b"data_only_config", self.key().as_ref()],
@@ -141,6 +165,12 @@ impl SubDao {
         [SubDao::Iot, SubDao::Mobile]
     }
 
+    pub fn from_key(key: &Pubkey) -> Option<Self> {
+        Self::all()
+            .into_iter()
+            .find(|sub_dao| sub_dao.key() == *key)
+    }
+
     pub fn key(&self) -> Pubkey {
         let (subdao_key, _) = Pubkey::find_program_address(
             &[b"sub_dao", self.token().mint().as_ref()],
@@ -212,9 +242,10 @@ impl SubDao {
     }
 
     pub fn epoch_info_key(&self) -> Pubkey {
-        const EPOCH_LENGTH: u32 = 60 * 60 * 24;
-        let epoch = chrono::Utc::now().second() / EPOCH_LENGTH;
+        self.epoch_info_key_for(epoch_for(Utc::now()))
+    }
 
+    pub fn epoch_info_key_for(&self, epoch: u64) -> Pubkey {
         let (key, _) = Pubkey::find_program_address(
             &[
                 "sub_dao_epoch_info".as_bytes(),
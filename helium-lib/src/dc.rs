@@ -5,25 +5,92 @@ use crate::{
     client::{GetAnchorAccount, SolanaRpcClient},
     dao::{Dao, SubDao},
     data_credits,
-    error::{DecodeError, Error},
-    keypair::{Keypair, Pubkey},
+    error::{DecodeError, EncodeError, Error},
+    keypair::{serde_pubkey, Keypair, Pubkey},
     message, priority_fee,
-    solana_sdk::{instruction::Instruction, signer::Signer, transaction::VersionedTransaction},
-    token::{Token, TokenAmount},
-    TransactionOpts,
+    solana_sdk::{
+        account::Account, instruction::Instruction, signer::Signer,
+        transaction::VersionedTransaction,
+    },
+    token::{self, price::Price, Token, TokenAmount},
+    tx, TransactionOpts,
 };
+use chrono::{DateTime, Utc};
 use helium_anchor_gen::{
     data_credits::accounts::BurnDelegatedDataCreditsV0,
     helium_sub_daos::{self, DaoV0, SubDaoV0},
 };
+use rust_decimal::prelude::*;
+use serde::Serialize;
 
-pub async fn mint_message<C: AsRef<SolanaRpcClient>>(
+/// A conversion between HNT and Data Credits at a given HNT price
+#[derive(Debug, Serialize, Clone)]
+pub struct Estimate {
+    pub hnt: TokenAmount,
+    pub dc: TokenAmount,
+    pub usd: Decimal,
+    pub hnt_price: Decimal,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Estimate {
+    /// Convert the given HNT or DC amount at the given HNT price.
+    ///
+    /// DC are worth a fixed amount of USD. HNT converted to DC rounds the
+    /// minted DC down, while DC converted to HNT rounds the HNT to burn up so
+    /// that burning the estimated HNT always mints at least the given DC.
+    pub fn from_price(amount: TokenAmount, price: &Price) -> Result<Self, Error> {
+        let dc_per_usd = Decimal::new(token::price::DC_PER_USD, 0);
+        let hnt_scale = Decimal::from(10u64.pow(Token::Hnt.decimals().into()));
+        let invalid_amount = || EncodeError::other(format!("invalid amount: {amount:?}"));
+        if price.price <= Decimal::ZERO {
+            return Err(EncodeError::other("invalid hnt price").into());
+        }
+        let (hnt, dc, usd) = match amount.token {
+            Token::Hnt => {
+                let usd = Decimal::from(amount.amount) / hnt_scale * price.price;
+                let dc = (usd * dc_per_usd)
+                    .floor()
+                    .to_u64()
+                    .ok_or_else(invalid_amount)?;
+                (amount.amount, dc, usd)
+            }
+            Token::Dc => {
+                let usd = Decimal::from(amount.amount) / dc_per_usd;
+                let hnt = (usd / price.price * hnt_scale)
+                    .ceil()
+                    .to_u64()
+                    .ok_or_else(invalid_amount)?;
+                (hnt, amount.amount, usd)
+            }
+            other => return Err(EncodeError::other(format!("invalid token type: {other}")).into()),
+        };
+        Ok(Self {
+            hnt: Token::Hnt.amount(hnt),
+            dc: Token::Dc.amount(dc),
+            usd,
+            hnt_price: price.price,
+            timestamp: price.timestamp,
+        })
+    }
+}
+
+/// Estimate the conversion of the given HNT or DC amount at the current HNT
+/// price as reported by the price oracle.
+pub async fn estimate<C: AsRef<SolanaRpcClient>>(
+    client: &C,
+    amount: TokenAmount,
+) -> Result<Estimate, Error> {
+    let price = token::price::get(client, Token::Hnt).await?;
+    Estimate::from_price(amount, &price)
+}
+
+pub async fn mint_instruction<C: AsRef<SolanaRpcClient>>(
     client: &C,
     amount: TokenAmount,
     payee: &Pubkey,
     payer: &Pubkey,
-    opts: &TransactionOpts,
-) -> Result<(message::VersionedMessage, u64), Error> {
+) -> Result<Instruction, Error> {
     fn token_amount_to_mint_args(
         amount: TokenAmount,
     ) -> Result<data_credits::MintDataCreditsArgsV0, DecodeError> {
@@ -75,19 +142,26 @@ pub async fn mint_message<C: AsRef<SolanaRpcClient>>(
         }
         .data(),
     };
+    Ok(ix)
+}
+
+pub const MINT_INSTRUCTION_BUDGET: u32 = 300_000;
 
+pub async fn mint_message<C: AsRef<SolanaRpcClient>>(
+    client: &C,
+    amount: TokenAmount,
+    payee: &Pubkey,
+    payer: &Pubkey,
+    opts: &TransactionOpts,
+) -> Result<(message::VersionedMessage, u64), Error> {
+    let ix = mint_instruction(client, amount, payee, payer).await?;
     let ixs = &[
-        priority_fee::compute_budget_instruction(300_000),
-        priority_fee::compute_price_instruction_for_accounts(
-            client,
-            &ix.accounts,
-            opts.fee_range(),
-        )
-        .await?,
+        priority_fee::compute_budget_instruction(MINT_INSTRUCTION_BUDGET),
+        priority_fee::compute_price_instruction_for_accounts(client, &ix.accounts, opts).await?,
         ix,
     ];
 
-    message::mk_message(client, ixs, &opts.lut_addresses, payer).await
+    message::mk_message(client, ixs, opts, payer).await
 }
 
 pub async fn mint<C: AsRef<SolanaRpcClient>>(
@@ -98,18 +172,21 @@ pub async fn mint<C: AsRef<SolanaRpcClient>>(
     opts: &TransactionOpts,
 ) -> Result<(VersionedTransaction, u64), Error> {
     let (msg, block_height) = mint_message(client, amount, payee, &keypair.pubkey(), opts).await?;
-    let txn = VersionedTransaction::try_new(msg, &[keypair])?;
+    let txn = tx::partial_sign(msg, &[keypair])?;
     Ok((txn, block_height))
 }
 
-pub async fn delegate_message<C: AsRef<SolanaRpcClient>>(
-    client: &C,
+/// Construct an instruction that delegates DC from the owner's wallet to the
+/// escrow account of a router payer key in a subdao.
+///
+/// The data credits program has no instruction to undelegate or redelegate,
+/// the escrowed DC are only released by the DC burns of the router's traffic.
+pub fn delegate_instruction(
     subdao: SubDao,
     payer_key: &str,
     amount: u64,
     owner: &Pubkey,
-    opts: &TransactionOpts,
-) -> Result<(message::VersionedMessage, u64), Error> {
+) -> Instruction {
     fn mk_accounts(delegated_dc_key: Pubkey, subdao: SubDao, owner: Pubkey) -> impl ToAccountMetas {
         data_credits::accounts::DelegateDataCreditsV0 {
             delegated_data_credits: delegated_dc_key,
@@ -128,7 +205,7 @@ pub async fn delegate_message<C: AsRef<SolanaRpcClient>>(
     }
 
     let delegated_dc_key = subdao.delegated_dc_key(payer_key);
-    let ix = Instruction {
+    Instruction {
         program_id: data_credits::id(),
         accounts: mk_accounts(delegated_dc_key, subdao, *owner).to_account_metas(None),
         data: data_credits::instruction::DelegateDataCreditsV0 {
@@ -138,19 +215,26 @@ pub async fn delegate_message<C: AsRef<SolanaRpcClient>>(
             },
         }
         .data(),
-    };
+    }
+}
 
+pub const DELEGATE_INSTRUCTION_BUDGET: u32 = 150_000;
+
+pub async fn delegate_message<C: AsRef<SolanaRpcClient>>(
+    client: &C,
+    subdao: SubDao,
+    payer_key: &str,
+    amount: u64,
+    owner: &Pubkey,
+    opts: &TransactionOpts,
+) -> Result<(message::VersionedMessage, u64), Error> {
+    let ix = delegate_instruction(subdao, payer_key, amount, owner);
     let ixs = &[
-        priority_fee::compute_budget_instruction(150_000),
-        priority_fee::compute_price_instruction_for_accounts(
-            client,
-            &ix.accounts,
-            opts.fee_range(),
-        )
-        .await?,
+        priority_fee::compute_budget_instruction(DELEGATE_INSTRUCTION_BUDGET),
+        priority_fee::compute_price_instruction_for_accounts(client, &ix.accounts, opts).await?,
         ix,
     ];
-    message::mk_message(client, ixs, &opts.lut_addresses, owner).await
+    message::mk_message(client, ixs, opts, owner).await
 }
 
 pub async fn delegate<C: AsRef<SolanaRpcClient>>(
@@ -163,10 +247,145 @@ pub async fn delegate<C: AsRef<SolanaRpcClient>>(
 ) -> Result<(VersionedTransaction, u64), Error> {
     let (msg, block_height) =
         delegate_message(client, subdao, payer_key, amount, &keypair.pubkey(), opts).await?;
-    let txn = VersionedTransaction::try_new(msg, &[keypair])?;
+    let txn = tx::partial_sign(msg, &[keypair])?;
+    Ok((txn, block_height))
+}
+
+/// Construct a message that mints the given number of DC to the owner's wallet
+/// and delegates them to a router payer key in a subdao in one transaction.
+pub async fn mint_and_delegate_message<C: AsRef<SolanaRpcClient>>(
+    client: &C,
+    subdao: SubDao,
+    payer_key: &str,
+    dc_amount: u64,
+    owner: &Pubkey,
+    opts: &TransactionOpts,
+) -> Result<(message::VersionedMessage, u64), Error> {
+    let mint_ix = mint_instruction(client, Token::Dc.amount(dc_amount), owner, owner).await?;
+    let delegate_ix = delegate_instruction(subdao, payer_key, dc_amount, owner);
+    let mut accounts = mint_ix.accounts.clone();
+    accounts.extend_from_slice(&delegate_ix.accounts);
+
+    let ixs = &[
+        priority_fee::compute_budget_instruction(
+            MINT_INSTRUCTION_BUDGET + DELEGATE_INSTRUCTION_BUDGET,
+        ),
+        priority_fee::compute_price_instruction_for_accounts(client, &accounts, opts).await?,
+        mint_ix,
+        delegate_ix,
+    ];
+    message::mk_message(client, ixs, opts, owner).await
+}
+
+pub async fn mint_and_delegate<C: AsRef<SolanaRpcClient>>(
+    client: &C,
+    subdao: SubDao,
+    payer_key: &str,
+    dc_amount: u64,
+    keypair: &Keypair,
+    opts: &TransactionOpts,
+) -> Result<(VersionedTransaction, u64), Error> {
+    let (msg, block_height) = mint_and_delegate_message(
+        client,
+        subdao,
+        payer_key,
+        dc_amount,
+        &keypair.pubkey(),
+        opts,
+    )
+    .await?;
+    let txn = tx::partial_sign(msg, &[keypair])?;
     Ok((txn, block_height))
 }
 
+/// The escrow account holding the DC delegated to a router payer key in a
+/// subdao
+#[derive(Debug, Serialize, Clone)]
+pub struct EscrowAccount {
+    pub router: String,
+    pub subdao: SubDao,
+    #[serde(with = "serde_pubkey")]
+    pub delegated_dc_key: Pubkey,
+    #[serde(with = "serde_pubkey")]
+    pub escrow_key: Pubkey,
+}
+
+/// Derive the delegated data credits and escrow account addresses for a
+/// router payer key in a subdao.
+pub fn escrow_account(subdao: SubDao, router_key: &str) -> EscrowAccount {
+    let delegated_dc_key = subdao.delegated_dc_key(router_key);
+    EscrowAccount {
+        router: router_key.to_string(),
+        subdao,
+        escrow_key: subdao.escrow_key(&delegated_dc_key),
+        delegated_dc_key,
+    }
+}
+
+impl EscrowAccount {
+    /// Decode the delegated balance from the escrow token account. A router
+    /// that was never delegated to has no escrow account and a zero balance,
+    /// as does one whose escrow account was closed and reports no lamports.
+    pub fn balance(self, account: Option<Account>) -> Result<DelegatedBalance, Error> {
+        let balance = match account.filter(|account| account.lamports > 0) {
+            Some(account) => {
+                let balance = token::balance_from_account(&self.escrow_key, account)?;
+                if balance.amount.token != Token::Dc {
+                    return Err(DecodeError::other(format!(
+                        "escrow account {} does not hold dc",
+                        self.escrow_key
+                    ))
+                    .into());
+                }
+                balance.amount
+            }
+            None => Token::Dc.amount(0),
+        };
+        Ok(DelegatedBalance {
+            escrow: self,
+            balance,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DelegatedBalance {
+    #[serde(flatten)]
+    pub escrow: EscrowAccount,
+    #[serde(serialize_with = "token::serde_amount_value")]
+    pub balance: TokenAmount,
+}
+
+/// Get the delegated DC balance of a router payer key in a subdao
+pub async fn delegated_balance<C: AsRef<SolanaRpcClient>>(
+    client: &C,
+    subdao: SubDao,
+    router_key: &str,
+) -> Result<DelegatedBalance, Error> {
+    let escrow = escrow_account(subdao, router_key);
+    let account = client
+        .as_ref()
+        .get_account_with_commitment(&escrow.escrow_key, client.as_ref().commitment())
+        .await?
+        .value;
+    escrow.balance(account)
+}
+
+/// Get the delegated DC balances for a list of escrow accounts, in the same
+/// order as the given accounts.
+pub async fn delegated_balances<C: AsRef<SolanaRpcClient>>(
+    client: &C,
+    escrows: Vec<EscrowAccount>,
+) -> Result<Vec<DelegatedBalance>, Error> {
+    let escrow_keys: Vec<Pubkey> = escrows.iter().map(|escrow| escrow.escrow_key).collect();
+    let accounts = token::accounts_for_many(client, &escrow_keys).await?;
+    escrows
+        .into_iter()
+        .zip(accounts)
+        .map(|(escrow, account)| escrow.balance(account))
+        .collect()
+}
+
 pub async fn burn_message<C: AsRef<SolanaRpcClient>>(
     client: &C,
     amount: u64,
@@ -199,15 +418,10 @@ pub async fn burn_message<C: AsRef<SolanaRpcClient>>(
 
     let ixs = &[
         priority_fee::compute_budget_instruction(150_000),
-        priority_fee::compute_price_instruction_for_accounts(
-            client,
-            &ix.accounts,
-            opts.fee_range(),
-        )
-        .await?,
+        priority_fee::compute_price_instruction_for_accounts(client, &ix.accounts, opts).await?,
         ix,
     ];
-    message::mk_message(client, ixs, &opts.lut_addresses, owner).await
+    message::mk_message(client, ixs, opts, owner).await
 }
 
 pub async fn burn<C: AsRef<SolanaRpcClient>>(
@@ -217,7 +431,7 @@ pub async fn burn<C: AsRef<SolanaRpcClient>>(
     opts: &TransactionOpts,
 ) -> Result<(VersionedTransaction, u64), Error> {
     let (msg, block_height) = burn_message(client, amount, &keypair.pubkey(), opts).await?;
-    let txn = VersionedTransaction::try_new(msg, &[keypair])?;
+    let txn = tx::partial_sign(msg, &[keypair])?;
     Ok((txn, block_height))
 }
 
@@ -280,15 +494,11 @@ pub async fn burn_delegated_message<C: AsRef<SolanaRpcClient>>(
 
     let ixs = &[
         priority_fee::compute_budget_instruction(150_000),
-        priority_fee::compute_price_instruction_for_accounts(
-            client,
-            &burn_ix.accounts,
-            opts.fee_range(),
-        )
-        .await?,
+        priority_fee::compute_price_instruction_for_accounts(client, &burn_ix.accounts, opts)
+            .await?,
         burn_ix,
     ];
-    message::mk_message(client, ixs, &opts.lut_addresses, payer).await
+    message::mk_message(client, ixs, opts, payer).await
 }
 
 pub async fn burn_delegated<C: AsRef<SolanaRpcClient>>(
@@ -302,6 +512,110 @@ pub async fn burn_delegated<C: AsRef<SolanaRpcClient>>(
     let (msg, block_height) =
         burn_delegated_message(client, sub_dao, amount, router_key, &keypair.pubkey(), opts)
             .await?;
-    let txn = VersionedTransaction::try_new(msg, &[keypair])?;
+    let txn = tx::partial_sign(msg, &[keypair])?;
     Ok((txn, block_height))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::{program_option::COption, program_pack::Pack};
+
+    const ROUTER_KEY: &str = "112qB3YaH5bZkCnKA5uRH7tBtGNv2Y5B4smv1jsmvGUzgKT71QpE";
+
+    fn token_account_fixture(mint: &Pubkey, amount: u64) -> Account {
+        let token_account = anchor_spl::token::spl_token::state::Account {
+            mint: *mint,
+            owner: Dao::dc_key(),
+            amount,
+            delegate: COption::None,
+            state: anchor_spl::token::spl_token::state::AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        };
+        let mut data = vec![0; anchor_spl::token::spl_token::state::Account::LEN];
+        token_account.pack_into_slice(&mut data);
+        Account {
+            lamports: 2_039_280,
+            data,
+            owner: anchor_spl::token::ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    fn hnt_price(price: &str) -> Price {
+        Price {
+            timestamp: Utc::now(),
+            price: Decimal::from_str(price).unwrap(),
+            token: Token::Hnt,
+        }
+    }
+
+    #[test]
+    fn estimate_hnt_to_dc() {
+        let estimate =
+            Estimate::from_price(Token::Hnt.amount(150_000_000), &hnt_price("4.25")).unwrap();
+        assert_eq!(Token::Dc.amount(637_500), estimate.dc);
+        assert_eq!(Decimal::from_str("6.375").unwrap(), estimate.usd);
+    }
+
+    #[test]
+    fn estimate_dc_to_hnt_rounds_up() {
+        let estimate = Estimate::from_price(Token::Dc.amount(100_000), &hnt_price("3")).unwrap();
+        // 1 USD at 3 USD/HNT is 0.333... HNT, rounded up to the nearest bone
+        assert_eq!(Token::Hnt.amount(33_333_334), estimate.hnt);
+        let back = Estimate::from_price(estimate.hnt, &hnt_price("3")).unwrap();
+        assert!(back.dc.amount >= 100_000);
+    }
+
+    #[test]
+    fn escrow_account_derivation() {
+        let escrow = escrow_account(SubDao::Iot, ROUTER_KEY);
+        assert_eq!(
+            SubDao::Iot.delegated_dc_key(ROUTER_KEY),
+            escrow.delegated_dc_key
+        );
+        assert_eq!(
+            SubDao::Iot.escrow_key(&escrow.delegated_dc_key),
+            escrow.escrow_key
+        );
+        // Delegations are scoped to their subdao
+        let mobile_escrow = escrow_account(SubDao::Mobile, ROUTER_KEY);
+        assert_ne!(escrow.delegated_dc_key, mobile_escrow.delegated_dc_key);
+        assert_ne!(escrow.escrow_key, mobile_escrow.escrow_key);
+    }
+
+    #[test]
+    fn escrow_balance_from_fixture() {
+        let escrow = escrow_account(SubDao::Iot, ROUTER_KEY);
+        let account = token_account_fixture(Token::Dc.mint(), 4_200_000);
+        let balance = escrow.balance(Some(account)).unwrap();
+        assert_eq!(Token::Dc.amount(4_200_000), balance.balance);
+        let json = serde_json::to_value(&balance).unwrap();
+        assert_eq!(4_200_000, json["balance"]);
+        assert_eq!(ROUTER_KEY, json["router"]);
+    }
+
+    #[test]
+    fn escrow_balance_missing_account_is_zero() {
+        let balance = escrow_account(SubDao::Mobile, ROUTER_KEY)
+            .balance(None)
+            .unwrap();
+        assert_eq!(Token::Dc.amount(0), balance.balance);
+    }
+
+    #[test]
+    fn escrow_balance_rejects_other_mints() {
+        let account = token_account_fixture(Token::Hnt.mint(), 1);
+        assert!(escrow_account(SubDao::Iot, ROUTER_KEY)
+            .balance(Some(account))
+            .is_err());
+    }
+
+    #[test]
+    fn estimate_rejects_other_tokens() {
+        assert!(Estimate::from_price(Token::Iot.amount(1), &hnt_price("1")).is_err());
+    }
+}
@@ -1,6 +1,10 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
-use crate::error::DecodeError;
+use crate::{
+    error::DecodeError,
+    helium_entity_manager::KeyToAssetV0,
+    keypair::{self, Pubkey},
+};
 use solana_sdk::bs58;
 
 pub trait AsEntityKey {
@@ -51,13 +55,22 @@ pub fn from_str(str: &str, encoding: KeySerialization) -> Result<Vec<u8>, Decode
     Ok(entity_key)
 }
 
-#[derive(Debug, Clone, serde::Serialize, Copy, Default)]
+/// The string forms an entity key can be given in.
+///
+/// Hotspot entity keys are the bytes of a helium public key, shown in b58.
+/// Other entities, like mobile carriers and IOT operations, use plain UTF-8
+/// strings as their entity key. Hex shows the raw entity key bytes of
+/// either, and the Solana form is the Solana address of an ed25519 Hotspot
+/// key.
+#[derive(Debug, Clone, serde::Serialize, Copy, Default, PartialEq, Eq)]
 #[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 #[serde(rename_all = "lowercase")]
 pub enum EntityKeyEncoding {
     #[default]
     B58,
     UTF8,
+    Hex,
+    Solana,
 }
 
 impl Display for EntityKeyEncoding {
@@ -65,15 +78,60 @@ impl Display for EntityKeyEncoding {
         match self {
             Self::UTF8 => f.write_str("utf8"),
             Self::B58 => f.write_str("b58"),
+            Self::Hex => f.write_str("hex"),
+            Self::Solana => f.write_str("solana"),
         }
     }
 }
 
+/// The on-chain serialization of entity keys decoded from the given
+/// encoding. All but UTF-8 decode to the raw key bytes, which are stored as
+/// b58 keys.
 impl From<EntityKeyEncoding> for KeySerialization {
     fn from(value: EntityKeyEncoding) -> Self {
         match value {
-            EntityKeyEncoding::B58 => KeySerialization::B58,
             EntityKeyEncoding::UTF8 => KeySerialization::UTF8,
+            EntityKeyEncoding::B58 | EntityKeyEncoding::Hex | EntityKeyEncoding::Solana => {
+                KeySerialization::B58
+            }
+        }
+    }
+}
+
+impl From<KeySerialization> for EntityKeyEncoding {
+    fn from(value: KeySerialization) -> Self {
+        match value {
+            KeySerialization::B58 => EntityKeyEncoding::B58,
+            KeySerialization::UTF8 => EntityKeyEncoding::UTF8,
+        }
+    }
+}
+
+/// Decode an entity key given in the given encoding into its bytes
+pub fn decode(str: &str, encoding: EntityKeyEncoding) -> Result<Vec<u8>, DecodeError> {
+    match encoding {
+        EntityKeyEncoding::B58 | EntityKeyEncoding::UTF8 => from_str(str, encoding.into()),
+        EntityKeyEncoding::Hex => Ok(hex::decode(str.trim_start_matches("0x"))?),
+        EntityKeyEncoding::Solana => {
+            let pubkey = Pubkey::from_str(str)?;
+            Ok(keypair::to_helium_pubkey(&pubkey)?.as_entity_key())
+        }
+    }
+}
+
+/// Encode entity key bytes in the given encoding. Fails for keys that are
+/// not valid UTF-8 when encoding as UTF-8, and for keys that are not
+/// ed25519 helium keys when encoding as a Solana address.
+pub fn encode(entity_key: &[u8], encoding: EntityKeyEncoding) -> Result<String, DecodeError> {
+    match encoding {
+        EntityKeyEncoding::B58 => Ok(bs58::encode(entity_key).into_string()),
+        EntityKeyEncoding::UTF8 => String::from_utf8(entity_key.to_vec())
+            .map_err(|_| DecodeError::other("entity key is not valid utf8")),
+        EntityKeyEncoding::Hex => Ok(hex::encode(entity_key)),
+        EntityKeyEncoding::Solana => {
+            let helium_key =
+                helium_crypto::PublicKey::from_str(&bs58::encode(entity_key).into_string())?;
+            Ok(keypair::to_pubkey(&helium_key)?.to_string())
         }
     }
 }
@@ -88,7 +146,30 @@ pub struct EncodedEntityKey {
 
 impl EncodedEntityKey {
     pub fn as_entity_key(&self) -> Result<Vec<u8>, DecodeError> {
-        from_str(&self.entity_key, self.encoding.into())
+        decode(&self.entity_key, self.encoding)
+    }
+
+    /// The same entity key in another encoding
+    pub fn convert(&self, encoding: EntityKeyEncoding) -> Result<Self, DecodeError> {
+        Ok(Self {
+            encoding,
+            entity_key: encode(&self.as_entity_key()?, encoding)?,
+        })
+    }
+}
+
+/// The entity key of a KTA, in the encoding it is serialized with on chain.
+/// Unlike [`crate::hotspot::entity_key_from_kta`] this works for entities
+/// whose key is not a helium public key.
+impl TryFrom<&KeyToAssetV0> for EncodedEntityKey {
+    type Error = DecodeError;
+
+    fn try_from(kta: &KeyToAssetV0) -> Result<Self, Self::Error> {
+        let encoding = EntityKeyEncoding::from(kta.key_serialization);
+        Ok(Self {
+            encoding,
+            entity_key: encode(&kta.entity_key, encoding)?,
+        })
     }
 }
 
@@ -106,3 +187,36 @@ impl From<&helium_crypto::PublicKey> for EncodedEntityKey {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodings_roundtrip() {
+        let pubkey = Pubkey::new_unique();
+        let entity_key = keypair::to_helium_pubkey(&pubkey)
+            .expect("helium key")
+            .as_entity_key();
+        assert_eq!(
+            pubkey.to_string(),
+            encode(&entity_key, EntityKeyEncoding::Solana).expect("solana")
+        );
+        for encoding in [
+            EntityKeyEncoding::B58,
+            EntityKeyEncoding::Hex,
+            EntityKeyEncoding::Solana,
+        ] {
+            let encoded = encode(&entity_key, encoding).expect("encode");
+            assert_eq!(entity_key, decode(&encoded, encoding).expect("decode"));
+        }
+        assert!(encode(&entity_key, EntityKeyEncoding::UTF8).is_err());
+
+        let carrier = decode("carrier", EntityKeyEncoding::UTF8).expect("utf8");
+        assert_eq!(
+            "carrier",
+            encode(&carrier, EntityKeyEncoding::UTF8).unwrap()
+        );
+        assert!(encode(&carrier, EntityKeyEncoding::Solana).is_err());
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{anchor_client, client, hotspot::cert, onboarding, solana_client, token};
+#[cfg(feature = "client")]
+use crate::{anchor_client, client, hotspot::cert, onboarding};
+#[cfg(feature = "rpc")]
+use crate::{solana_rpc_client_api::client_error::Error as ClientError, token};
 use std::{array::TryFromSliceError, num::TryFromIntError};
 use thiserror::Error;
 
@@ -7,30 +10,51 @@ pub enum Error {
     #[cfg(feature = "mnemonic")]
     #[error("mnemonic: {0}")]
     Mnemonic(#[from] helium_mnemonic::MnmemonicError),
+    #[cfg(feature = "client")]
     #[error("onboarding: {0}")]
     Onboarding(#[from] onboarding::OnboardingError),
+    #[cfg(feature = "client")]
     #[error("anchor client: {0}")]
     Anchor(#[from] anchor_client::ClientError),
+    #[cfg(feature = "rpc")]
     #[error("anchor lang: {0}")]
     AnchorLang(#[from] helium_anchor_gen::anchor_lang::error::Error),
+    #[cfg(feature = "client")]
     #[error("DAS client: {0}")]
     Das(#[from] client::DasClientError),
+    #[cfg(feature = "client")]
     #[error("cert client: {0}")]
     Cert(#[from] cert::ClientError),
+    #[cfg(feature = "client")]
     #[error("grpc: {0}")]
     Grpc(#[from] tonic::Status),
+    #[cfg(feature = "client")]
     #[error("service: {0}")]
     Service(#[from] helium_proto::services::Error),
+    #[cfg(feature = "rpc")]
     #[error("price client: {0}")]
     Price(#[from] token::price::PriceError),
+    #[cfg(feature = "rpc")]
     #[error("rest client: {0}")]
     Rest(#[from] reqwest::Error),
     #[error("system time: {0}")]
     Time(#[from] std::time::SystemTimeError),
     #[error("program: {0}")]
     Program(#[from] solana_program::program_error::ProgramError),
-    #[error("solana: {0}")]
-    Solana(Box<solana_client::client_error::ClientError>),
+    #[cfg(feature = "rpc")]
+    #[error("solana{}: {0}", rpc_method(.0))]
+    Solana(#[source] Box<ClientError>),
+    #[error("transaction: {0}")]
+    Transaction(#[source] Box<TransactionError>),
+    #[cfg(feature = "client")]
+    #[error("caches are already initialized for a different rpc url")]
+    CacheUrlMismatch,
+    #[error("timed out after {0:?}")]
+    Timeout(std::time::Duration),
+    #[error("transaction {0} expired")]
+    TransactionExpired(solana_sdk::signature::Signature),
+    #[error("fee budget exceeded: fee of {fee} lamports with {remaining} lamports left")]
+    FeeBudgetExceeded { fee: u64, remaining: u64 },
     #[error("instruction: {0}")]
     Instruction(#[from] solana_sdk::instruction::InstructionError),
     #[error("message: {0}")]
@@ -45,45 +69,268 @@ pub enum Error {
     Encode(#[from] EncodeError),
 }
 
-impl From<solana_client::client_error::ClientError> for Error {
-    fn from(value: solana_client::client_error::ClientError) -> Self {
+#[cfg(feature = "rpc")]
+impl From<ClientError> for Error {
+    fn from(value: ClientError) -> Self {
+        use crate::solana_rpc_client_api::{
+            client_error::ErrorKind as ClientErrorKind,
+            request::{RpcError, RpcResponseErrorData},
+            response::RpcSimulateTransactionResult,
+        };
+        // Transport timeouts are reported with the request timeout by the
+        // client senders
+        if let ClientErrorKind::Io(err) = value.kind() {
+            if let Some(timed_out) = err
+                .get_ref()
+                .and_then(|err| err.downcast_ref::<crate::client::sender::RequestTimedOut>())
+            {
+                return Self::Timeout(timed_out.0);
+            }
+        }
+        // A failed preflight simulation is a program failure rather than an
+        // RPC failure, so decode it while keeping the RPC error as source
+        if let ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            data:
+                RpcResponseErrorData::SendTransactionPreflightFailure(RpcSimulateTransactionResult {
+                    err: Some(err),
+                    logs,
+                    ..
+                }),
+            ..
+        }) = value.kind()
+        {
+            let mut txn_error =
+                TransactionError::new(err.clone(), logs.clone().unwrap_or_default());
+            txn_error.rpc = Some(Box::new(value));
+            return txn_error.into();
+        }
         Self::Solana(Box::new(value))
     }
 }
 
+impl From<TransactionError> for Error {
+    fn from(value: TransactionError) -> Self {
+        Self::Transaction(Box::new(value))
+    }
+}
+
+#[cfg(feature = "rpc")]
+fn is_transient_reqwest(err: &reqwest::Error) -> bool {
+    err.is_timeout()
+        || crate::client::sender::is_connect(err)
+        || err
+            .status()
+            .is_some_and(|status| status.is_server_error() || status.as_u16() == 429)
+}
+
+#[cfg(feature = "rpc")]
+fn rpc_method(err: &ClientError) -> String {
+    err.request()
+        .map(|request| format!(" {request}"))
+        .unwrap_or_default()
+}
+
 impl Error {
+    /// The decoded transaction failure, if this error is a failed transaction
+    pub fn transaction_error(&self) -> Option<&TransactionError> {
+        match self {
+            Self::Transaction(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    /// Whether this error is a timeout, either of a single request or of
+    /// an overall deadline
+    pub fn is_timeout(&self) -> bool {
+        match self {
+            Self::Timeout(_) => true,
+            #[cfg(feature = "rpc")]
+            Self::Rest(err) => err.is_timeout(),
+            #[cfg(feature = "rpc")]
+            Self::Solana(err) => {
+                use crate::solana_rpc_client_api::client_error::ErrorKind as ClientErrorKind;
+                matches!(err.kind(), ClientErrorKind::Reqwest(err) if err.is_timeout())
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether this error is likely to go away when retried, like timeouts,
+    /// connection failures and server errors. Errors returned by programs
+    /// or for invalid requests are not transient.
+    pub fn is_transient(&self) -> bool {
+        if self.is_timeout() {
+            return true;
+        }
+        match self {
+            #[cfg(feature = "client")]
+            Self::Onboarding(err) => err.is_retryable(),
+            #[cfg(feature = "rpc")]
+            Self::Rest(err) => is_transient_reqwest(err),
+            #[cfg(feature = "client")]
+            Self::Das(err) => {
+                std::iter::successors(Some(err as &dyn std::error::Error), |err| err.source())
+                    .find_map(|err| err.downcast_ref::<reqwest::Error>())
+                    .is_some_and(is_transient_reqwest)
+            }
+            #[cfg(feature = "client")]
+            Self::Grpc(status) => matches!(
+                status.code(),
+                tonic::Code::Unavailable | tonic::Code::DeadlineExceeded
+            ),
+            #[cfg(feature = "rpc")]
+            Self::Solana(err) => {
+                use crate::solana_rpc_client_api::client_error::ErrorKind as ClientErrorKind;
+                match err.kind() {
+                    ClientErrorKind::Io(_) => true,
+                    ClientErrorKind::Reqwest(err) => is_transient_reqwest(err),
+                    _ => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    #[cfg(feature = "client")]
     pub fn account_not_found() -> Self {
         anchor_client::ClientError::AccountNotFound.into()
     }
 
+    #[cfg(feature = "rpc")]
     pub fn is_account_not_found(&self) -> bool {
-        use solana_client::{
-            client_error::{
-                ClientError as SolanaClientError, ClientErrorKind as SolanaClientErrorKind,
-            },
-            rpc_request::RpcError as SolanaClientRpcError,
+        use crate::solana_rpc_client_api::{
+            client_error::ErrorKind as SolanaClientErrorKind,
+            request::RpcError as SolanaClientRpcError,
         };
         match self {
+            #[cfg(feature = "client")]
             Self::Anchor(anchor_client::ClientError::AccountNotFound) => true,
-            Self::Solana(client_error) => matches!(client_error.as_ref(), SolanaClientError {
+            Self::Solana(client_error) => matches!(client_error.as_ref(), ClientError {
                     kind: SolanaClientErrorKind::RpcError(SolanaClientRpcError::ForUser(msg)),
                     ..
                 } if msg.starts_with("AccountNotFound")),
-
+            #[cfg(feature = "client")]
             Self::Das(das_error) => das_error.is_account_not_found(),
             _ => false,
         }
     }
+
+    /// Turn an account not found error into `None`, keeping all other errors
+    #[cfg(feature = "rpc")]
+    pub fn not_found_as_none<T>(result: Result<T, Self>) -> Result<Option<T>, Self> {
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(err) if err.is_account_not_found() => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// A transaction that failed in simulation or on chain, with the failing
+/// instruction and program error decoded where possible
+#[derive(Debug, Error)]
+pub struct TransactionError {
+    pub error: solana_sdk::transaction::TransactionError,
+    /// Index of the failing instruction, if an instruction failed
+    pub instruction: Option<u8>,
+    /// The custom program error of the failing instruction, if any
+    pub program_error: Option<ProgramError>,
+    /// Program logs of the failed transaction, when available
+    pub logs: Vec<String>,
+    #[cfg(feature = "rpc")]
+    #[source]
+    rpc: Option<Box<ClientError>>,
+}
+
+/// A custom program error code with the anchor error name and message, when
+/// the program logged them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramError {
+    pub code: u32,
+    pub name: Option<String>,
+    pub message: Option<String>,
+}
+
+impl TransactionError {
+    pub fn new(error: solana_sdk::transaction::TransactionError, logs: Vec<String>) -> Self {
+        use solana_sdk::{
+            instruction::InstructionError, transaction::TransactionError as TxnError,
+        };
+        let (instruction, program_error) = match &error {
+            TxnError::InstructionError(index, InstructionError::Custom(code)) => {
+                (Some(*index), Some(ProgramError::from_logs(*code, &logs)))
+            }
+            TxnError::InstructionError(index, _) => (Some(*index), None),
+            _ => (None, None),
+        };
+        Self {
+            error,
+            instruction,
+            program_error,
+            logs,
+            #[cfg(feature = "rpc")]
+            rpc: None,
+        }
+    }
+}
+
+impl std::fmt::Display for TransactionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.instruction, &self.program_error) {
+            (Some(index), Some(program_error)) => {
+                write!(f, "instruction {index} failed: {program_error}")
+            }
+            (Some(index), None) => write!(f, "instruction {index} failed: {}", self.error),
+            _ => self.error.fmt(f),
+        }
+    }
+}
+
+impl ProgramError {
+    /// Look up the anchor error for the given code in program logs, which
+    /// anchor logs as `AnchorError ... Error Code: <name>. Error Number:
+    /// <code>. Error Message: <message>.`
+    fn from_logs(code: u32, logs: &[String]) -> Self {
+        let number = format!("Error Number: {code}.");
+        let anchor_log = logs
+            .iter()
+            .find(|log| log.contains("AnchorError") && log.contains(&number));
+        let field = |log: &String, label: &str| {
+            log.split_once(label)
+                .map(|(_, rest)| rest.split_once(". ").map_or(rest, |(value, _)| value))
+                .map(|value| value.trim().trim_end_matches('.').to_string())
+        };
+        Self {
+            code,
+            name: anchor_log.and_then(|log| field(log, "Error Code:")),
+            message: anchor_log.and_then(|log| field(log, "Error Message:")),
+        }
+    }
+}
+
+impl std::fmt::Display for ProgramError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "custom program error {:#x}", self.code)?;
+        if let Some(name) = &self.name {
+            write!(f, " ({name})")?;
+        }
+        if let Some(message) = &self.message {
+            write!(f, ": {message}")?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Error)]
 pub enum EncodeError {
+    #[cfg(feature = "client")]
     #[error("proto: {0}")]
     Proto(#[from] helium_proto::EncodeError),
     #[error("json: {0}")]
     Json(#[from] serde_json::Error),
     #[error("bincode: {0}")]
     Bincode(#[from] bincode::Error),
+    #[cfg(feature = "client")]
     #[error("h3: {0}")]
     H3(#[from] h3o::error::InvalidLatLng),
     #[error("encode: {0}")]
@@ -108,6 +355,7 @@ pub enum DecodeError {
     Hex(#[from] hex::FromHexError), // decode
     #[error("base64: {0}")]
     Base64(#[from] base64::DecodeError), // decode
+    #[cfg(feature = "client")]
     #[error("proto: {0}")]
     Proto(#[from] helium_proto::DecodeError), // decode
     #[error("base58: {0}")]
@@ -131,3 +379,43 @@ impl DecodeError {
         Self::Decode(reason.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{instruction::InstructionError, transaction::TransactionError as TxnError};
+
+    #[test]
+    fn decode_anchor_program_error() {
+        let logs = vec![
+            "Program hexbfQ invoke [1]".to_string(),
+            "Program log: AnchorError occurred. Error Code: BelowMinimumBoost. Error Number: 6001. Error Message: Must boost for the minimum boosting duration.".to_string(),
+        ];
+        let err = TransactionError::new(
+            TxnError::InstructionError(2, InstructionError::Custom(6001)),
+            logs,
+        );
+        assert_eq!(Some(2), err.instruction);
+        assert_eq!(
+            Some(ProgramError {
+                code: 6001,
+                name: Some("BelowMinimumBoost".to_string()),
+                message: Some("Must boost for the minimum boosting duration".to_string()),
+            }),
+            err.program_error
+        );
+        assert_eq!(
+            "instruction 2 failed: custom program error 0x1771 (BelowMinimumBoost): Must boost for the minimum boosting duration",
+            err.to_string()
+        );
+
+        let err = TransactionError::new(
+            TxnError::InstructionError(0, InstructionError::Custom(1)),
+            vec![],
+        );
+        assert_eq!(
+            "instruction 0 failed: custom program error 0x1",
+            err.to_string()
+        );
+    }
+}
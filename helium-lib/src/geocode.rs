@@ -0,0 +1,84 @@
+use crate::error::{DecodeError, Error};
+use serde::{Deserialize, Serialize};
+
+pub const NOMINATIM_URL: &str = "https://nominatim.openstreetmap.org";
+
+/// The coordinates a street address resolved to.
+#[derive(Debug, Clone, Serialize)]
+pub struct GeocodedAddress {
+    /// The full address as reported by the geocoding provider
+    pub address: String,
+    pub lat: f64,
+    pub lon: f64,
+}
+
+/// A provider resolving street addresses to coordinates.
+#[async_trait::async_trait]
+pub trait Geocoder {
+    /// Resolve the given address to its best matching coordinates, or `None`
+    /// if the address could not be found.
+    async fn geocode(&self, address: &str) -> Result<Option<GeocodedAddress>, Error>;
+}
+
+/// A geocoder backed by an OpenStreetMap Nominatim server.
+pub struct Nominatim {
+    base_url: String,
+    inner: reqwest::Client,
+}
+
+impl Default for Nominatim {
+    fn default() -> Self {
+        Self::new(NOMINATIM_URL)
+    }
+}
+
+impl Nominatim {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            inner: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Geocoder for Nominatim {
+    async fn geocode(&self, address: &str) -> Result<Option<GeocodedAddress>, Error> {
+        // Nominatim reports coordinates as strings
+        #[derive(Deserialize)]
+        struct Place {
+            display_name: String,
+            lat: String,
+            lon: String,
+        }
+        fn parse_coordinate(value: &str) -> Result<f64, DecodeError> {
+            value
+                .parse()
+                .map_err(|_| DecodeError::other(format!("invalid coordinate: {value}")))
+        }
+
+        let url = format!("{}/search", self.base_url);
+        let places: Vec<Place> = self
+            .inner
+            .get(&url)
+            // Nominatim's usage policy requires an identifying user agent
+            .header(reqwest::header::USER_AGENT, "helium-wallet")
+            .query(&[("q", address), ("format", "json"), ("limit", "1")])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        places
+            .into_iter()
+            .next()
+            .map(|place| -> Result<GeocodedAddress, Error> {
+                Ok(GeocodedAddress {
+                    lat: parse_coordinate(&place.lat)?,
+                    lon: parse_coordinate(&place.lon)?,
+                    address: place.display_name,
+                })
+            })
+            .transpose()
+    }
+}
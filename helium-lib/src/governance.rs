@@ -0,0 +1,342 @@
+//! Governance proposals and voting with veHNT positions
+use crate::{
+    anchor_lang::{InstructionData, ToAccountMetas},
+    client::{self, GetAnchorAccount, SolanaRpcClient},
+    error::{DecodeError, EncodeError, Error},
+    keypair::{serde_pubkey, Keypair, Pubkey},
+    message,
+    positions::{Position, VoteWeights},
+    priority_fee,
+    programs::ORGANIZATION_PROGRAM_ID,
+    proposal,
+    solana_sdk::{instruction::Instruction, signer::Signer, transaction::VersionedTransaction},
+    token::Token,
+    tx, voter_stake_registry, TransactionOpts,
+};
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use rust_decimal::prelude::*;
+use serde::Serialize;
+
+/// The name of the organization HNT governance proposals are created in
+pub const HELIUM_ORGANIZATION: &str = "Helium";
+
+pub fn organization_key(name: &str) -> Pubkey {
+    let (key, _) = Pubkey::find_program_address(
+        &[b"organization", name.as_bytes()],
+        &ORGANIZATION_PROGRAM_ID,
+    );
+    key
+}
+
+/// The key of the marker recording the votes of a position on a proposal
+pub fn vote_marker_key(mint: &Pubkey, proposal: &Pubkey) -> Pubkey {
+    let (key, _) = Pubkey::find_program_address(
+        &[b"marker", mint.as_ref(), proposal.as_ref()],
+        &voter_stake_registry::id(),
+    );
+    key
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "lowercase", tag = "state")]
+pub enum ProposalState {
+    Draft,
+    Cancelled,
+    Voting {
+        start: Option<DateTime<Utc>>,
+    },
+    Resolved {
+        choices: Vec<u16>,
+        end: Option<DateTime<Utc>>,
+    },
+    Custom {
+        name: String,
+    },
+}
+
+impl From<&proposal::ProposalState> for ProposalState {
+    fn from(value: &proposal::ProposalState) -> Self {
+        match value {
+            proposal::ProposalState::Draft => Self::Draft,
+            proposal::ProposalState::Cancelled => Self::Cancelled,
+            proposal::ProposalState::Voting { start_ts } => Self::Voting {
+                start: DateTime::from_timestamp(*start_ts, 0),
+            },
+            proposal::ProposalState::Resolved { choices, end_ts } => Self::Resolved {
+                choices: choices.clone(),
+                end: DateTime::from_timestamp(*end_ts, 0),
+            },
+            proposal::ProposalState::Custom { name, .. } => Self::Custom { name: name.clone() },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Choice {
+    pub index: u16,
+    pub name: String,
+    /// The veHNT voted for this choice
+    pub weight: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Proposal {
+    #[serde(with = "serde_pubkey")]
+    pub address: Pubkey,
+    pub name: String,
+    pub uri: String,
+    #[serde(flatten)]
+    pub state: ProposalState,
+    pub created_at: Option<DateTime<Utc>>,
+    pub max_choices_per_voter: u16,
+    pub tags: Vec<String>,
+    pub choices: Vec<Choice>,
+}
+
+impl Proposal {
+    pub fn is_open(&self) -> bool {
+        matches!(self.state, ProposalState::Voting { .. })
+    }
+
+    fn from_account(address: Pubkey, account: &proposal::ProposalV0) -> Result<Self, Error> {
+        let choices = account
+            .choices
+            .iter()
+            .enumerate()
+            .map(|(index, choice)| -> Result<Choice, Error> {
+                let weight = i128::try_from(choice.weight).map_err(DecodeError::other)?;
+                Ok(Choice {
+                    index: index as u16,
+                    name: choice.name.clone(),
+                    weight: Decimal::from_i128_with_scale(weight, Token::Hnt.decimals().into())
+                        .normalize(),
+                })
+            })
+            .try_collect()?;
+        Ok(Self {
+            address,
+            name: account.name.clone(),
+            uri: account.uri.clone(),
+            state: ProposalState::from(&account.state),
+            created_at: DateTime::from_timestamp(account.created_at, 0),
+            max_choices_per_voter: account.max_choices_per_voter,
+            tags: account.tags.clone(),
+            choices,
+        })
+    }
+}
+
+/// Get a proposal by address
+pub async fn get<C: GetAnchorAccount>(client: &C, address: &Pubkey) -> Result<Proposal, Error> {
+    let account = client
+        .anchor_account::<proposal::ProposalV0>(address)
+        .await?;
+    Proposal::from_account(*address, &account)
+}
+
+/// Get all proposals created in the given organization, newest first
+pub async fn proposals<C: AsRef<SolanaRpcClient>>(
+    client: &C,
+    organization: &Pubkey,
+) -> Result<Vec<Proposal>, Error> {
+    // The namespace directly follows the discriminator
+    const NAMESPACE_OFFSET: usize = 8;
+    let mut proposals: Vec<Proposal> = client::anchor_program_accounts::<_, proposal::ProposalV0>(
+        client,
+        &proposal::id(),
+        NAMESPACE_OFFSET,
+        organization,
+    )
+    .await?
+    .iter()
+    .map(|(address, account)| Proposal::from_account(*address, account))
+    .try_collect()?;
+    proposals.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(proposals)
+}
+
+/// A position vote to be cast, with the voting power it applies
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionVote {
+    #[serde(with = "serde_pubkey")]
+    pub position: Pubkey,
+    pub weight: Decimal,
+}
+
+/// Get the positions that can still vote for the given choice, with the
+/// voting power each of them would apply.
+///
+/// Positions without voting power, that already voted for the choice, or
+/// that already voted for as many choices as the proposal allows per voter,
+/// are left out.
+pub async fn eligible_votes<'a, C: GetAnchorAccount>(
+    client: &C,
+    proposal: &Proposal,
+    choice: u16,
+    positions: &'a [Position],
+    weights: &VoteWeights,
+) -> Result<Vec<(&'a Position, PositionVote)>, Error> {
+    let now = Utc::now();
+    let marker_keys = positions
+        .iter()
+        .map(|position| vote_marker_key(&position.mint, &proposal.address))
+        .collect_vec();
+    let markers = client
+        .anchor_accounts::<voter_stake_registry::VoteMarkerV0>(&marker_keys)
+        .await?;
+    let mut votes = vec![];
+    for (position, marker) in positions.iter().zip(markers) {
+        if marker.is_some_and(|marker| {
+            marker.choices.contains(&choice)
+                || marker.choices.len() >= usize::from(proposal.max_choices_per_voter)
+        }) {
+            continue;
+        }
+        let power =
+            weights.voting_power(position.amount.amount, position.lockup.locked_secs_at(now));
+        if power == 0 {
+            continue;
+        }
+        let weight = Decimal::from_i128_with_scale(
+            i128::try_from(power).map_err(DecodeError::other)?,
+            weights.decimals(position.amount.token),
+        );
+        votes.push((
+            position,
+            PositionVote {
+                position: position.key,
+                weight: weight.normalize(),
+            },
+        ));
+    }
+    Ok(votes)
+}
+
+/// Construct the instruction that votes for a choice on a proposal with a
+/// position owned by the voter.
+pub fn vote_instruction(
+    proposal_key: &Pubkey,
+    proposal_config_key: &Pubkey,
+    proposal_config: &proposal::ProposalConfigV0,
+    position: &Position,
+    choice: u16,
+    voter: &Pubkey,
+) -> Instruction {
+    let accounts = voter_stake_registry::accounts::VoteV0 {
+        payer: *voter,
+        marker: vote_marker_key(&position.mint, proposal_key),
+        registrar: position.registrar,
+        voter: *voter,
+        position: position.key,
+        mint: position.mint,
+        token_account: spl_associated_token_account::get_associated_token_address(
+            voter,
+            &position.mint,
+        ),
+        proposal: *proposal_key,
+        proposal_config: *proposal_config_key,
+        state_controller: proposal_config.state_controller,
+        on_vote_hook: proposal_config.on_vote_hook,
+        proposal_program: proposal::id(),
+        system_program: solana_sdk::system_program::ID,
+    };
+    Instruction {
+        program_id: voter_stake_registry::id(),
+        accounts: accounts.to_account_metas(None),
+        data: voter_stake_registry::instruction::VoteV0 {
+            _args: voter_stake_registry::VoteArgsV0 { choice },
+        }
+        .data(),
+    }
+}
+
+/// The maximum number of positions to vote with in a single transaction
+pub const MAX_VOTES_PER_TXN: usize = 4;
+
+pub const VOTE_INSTRUCTION_BUDGET: u32 = 200_000;
+
+/// Construct a message that votes for a choice on an open proposal with each
+/// of the given positions. At most [`MAX_VOTES_PER_TXN`] positions fit in a
+/// single message.
+pub async fn vote_message<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    proposal_key: &Pubkey,
+    choice: u16,
+    positions: &[&Position],
+    voter: &Pubkey,
+    opts: &TransactionOpts,
+) -> Result<(message::VersionedMessage, u64), Error> {
+    if positions.is_empty() || positions.len() > MAX_VOTES_PER_TXN {
+        return Err(EncodeError::other(format!(
+            "can vote with between 1 and {MAX_VOTES_PER_TXN} positions at a time"
+        ))
+        .into());
+    }
+    let proposal = client
+        .anchor_account::<proposal::ProposalV0>(proposal_key)
+        .await?;
+    if !matches!(proposal.state, proposal::ProposalState::Voting { .. }) {
+        return Err(EncodeError::other(format!("proposal {proposal_key} is not open")).into());
+    }
+    if choice as usize >= proposal.choices.len() {
+        return Err(
+            EncodeError::other(format!("proposal {proposal_key} has no choice {choice}")).into(),
+        );
+    }
+    let proposal_config = client
+        .anchor_account::<proposal::ProposalConfigV0>(&proposal.proposal_config)
+        .await?;
+
+    let vote_ixs = positions
+        .iter()
+        .map(|position| {
+            vote_instruction(
+                proposal_key,
+                &proposal.proposal_config,
+                &proposal_config,
+                position,
+                choice,
+                voter,
+            )
+        })
+        .collect_vec();
+    let accounts = vote_ixs
+        .iter()
+        .flat_map(|ix| ix.accounts.iter().cloned())
+        .collect_vec();
+    let ixs = [
+        vec![
+            priority_fee::compute_budget_instruction(
+                VOTE_INSTRUCTION_BUDGET * vote_ixs.len() as u32,
+            ),
+            priority_fee::compute_price_instruction_for_accounts(client, &accounts, opts).await?,
+        ],
+        vote_ixs,
+    ]
+    .concat();
+    message::mk_message(client, &ixs, opts, voter).await
+}
+
+/// Vote for a choice on a proposal with positions owned by the given
+/// keypair. See [`vote_message`].
+pub async fn vote<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    proposal_key: &Pubkey,
+    choice: u16,
+    positions: &[&Position],
+    keypair: &Keypair,
+    opts: &TransactionOpts,
+) -> Result<(VersionedTransaction, u64), Error> {
+    let (msg, block_height) = vote_message(
+        client,
+        proposal_key,
+        choice,
+        positions,
+        &keypair.pubkey(),
+        opts,
+    )
+    .await?;
+    let txn = tx::partial_sign(msg, &[keypair])?;
+    Ok((txn, block_height))
+}
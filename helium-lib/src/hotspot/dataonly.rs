@@ -15,7 +15,7 @@ use crate::{
     },
     solana_sdk::{instruction::Instruction, signature::Signer, transaction::Transaction},
     token::Token,
-    TransactionOpts,
+    tx, TransactionOpts,
 };
 use helium_crypto::{PublicKey, Sign};
 use helium_proto::{BlockchainTxn, BlockchainTxnAddGatewayV1, Message, Txn};
@@ -99,13 +99,13 @@ mod iot {
             priority_fee::compute_price_instruction_for_accounts(
                 client,
                 &onboard_ix.accounts,
-                opts.fee_range(),
+                opts,
             )
             .await?,
             onboard_ix,
         ];
 
-        message::mk_message(client, ixs, &opts.lut_addresses, owner).await
+        message::mk_message(client, ixs, opts, owner).await
     }
 }
 
@@ -187,16 +187,21 @@ mod mobile {
             priority_fee::compute_price_instruction_for_accounts(
                 client,
                 &onboard_ix.accounts,
-                opts.fee_range(),
+                opts,
             )
             .await?,
             onboard_ix,
         ];
 
-        message::mk_message(client, ixs, &opts.lut_addresses, owner).await
+        message::mk_message(client, ixs, opts, owner).await
     }
 }
 
+/// Construct the message to onboard a data-only Hotspot to the given subdao.
+///
+/// Data-only Mobile onboarding only asserts the location. Any deployment info
+/// in the given assertion is not included and has to be set with a follow-up
+/// [`hotspot::direct_update`] once the Hotspot is onboarded.
 pub async fn onboard_message<C: AsRef<DasClient> + AsRef<SolanaRpcClient> + GetAnchorAccount>(
     client: &C,
     subdao: SubDao,
@@ -205,6 +210,9 @@ pub async fn onboard_message<C: AsRef<DasClient> + AsRef<SolanaRpcClient> + GetA
     owner: &Pubkey,
     opts: &TransactionOpts,
 ) -> Result<(message::VersionedMessage, u64), Error> {
+    if assertion.subdao() != subdao {
+        return Err(EncodeError::other(format!("assertion is not for subdao {subdao}")).into());
+    }
     match subdao {
         SubDao::Iot => iot::onboard_message(client, hotspot_key, assertion, owner, opts).await,
         SubDao::Mobile => {
@@ -230,7 +238,7 @@ pub async fn onboard<C: AsRef<DasClient> + AsRef<SolanaRpcClient> + GetAnchorAcc
         opts,
     )
     .await?;
-    let txn = VersionedTransaction::try_new(msg, &[keypair])?;
+    let txn = tx::partial_sign(msg, &[keypair])?;
     Ok((txn, block_height))
 }
 
@@ -291,12 +299,12 @@ pub async fn issue_transaction<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
 
     let ixs = &[
         priority_fee::compute_budget_instruction(300_000),
-        priority_fee::compute_price_instruction_for_accounts(client, &accounts, opts.fee_range())
-            .await?,
+        priority_fee::compute_price_instruction_for_accounts(client, &accounts, opts).await?,
         issue_ix,
     ];
 
-    let (txn, latest_block_height) = mk_transaction_with_blockhash(client, ixs, &owner).await?;
+    let (txn, latest_block_height) =
+        mk_transaction_with_blockhash(client, ixs, opts, &owner).await?;
 
     let sig = add_tx.gateway_signature.clone();
     add_tx.gateway_signature = vec![];
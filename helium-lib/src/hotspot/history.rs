@@ -0,0 +1,259 @@
+use crate::{
+    anchor_client::solana_client::{
+        rpc_client::GetConfirmedSignaturesForAddress2Config, rpc_config::RpcTransactionConfig,
+    },
+    client::{DasClient, SolanaRpcClient},
+    dao::SubDao,
+    error::{DecodeError, Error},
+    hotspot::{info, CommittedHotspotInfoUpdate, HotspotInfoUpdate},
+    keypair::{serde_pubkey, Pubkey},
+    kta,
+    solana_sdk::{commitment_config::CommitmentConfig, signature::Signature},
+};
+use chrono::{DateTime, Utc};
+use futures::{
+    stream::{self, StreamExt, TryStreamExt},
+    TryFutureExt,
+};
+use serde::Serialize;
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction, UiInstruction, UiMessage,
+    UiParsedInstruction, UiTransactionEncoding,
+};
+use std::str::FromStr;
+
+/// Number of signatures fetched per DAS or RPC request
+const SIGNATURE_PAGE_LIMIT: u32 = 1000;
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "snake_case", tag = "event")]
+pub enum HotspotEventKind {
+    /// The Hotspot asset was minted
+    Issued,
+    /// The Hotspot asset was transferred to a new owner
+    Transferred {
+        #[serde(with = "serde_pubkey")]
+        owner: Pubkey,
+    },
+    /// The Hotspot was onboarded to a subdao
+    Onboarded {
+        sub_dao: SubDao,
+        update: HotspotInfoUpdate,
+    },
+    /// The Hotspot location or other metadata was asserted in a subdao
+    Asserted {
+        sub_dao: SubDao,
+        update: HotspotInfoUpdate,
+    },
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct HotspotEvent {
+    pub block: u64,
+    pub timestamp: DateTime<Utc>,
+    pub signature: String,
+    #[serde(flatten)]
+    pub kind: HotspotEventKind,
+}
+
+/// Get the history of a Hotspot, oldest event first.
+///
+/// Ownership events are derived from the transaction history of the Hotspot
+/// asset, while onboarding and assert events come from the full transaction
+/// history of its info account in each subdao. Events are classified by the
+/// program and instruction they were part of.
+pub async fn history<C: AsRef<SolanaRpcClient> + AsRef<DasClient>>(
+    client: &C,
+    hotspot_key: &helium_crypto::PublicKey,
+) -> Result<Vec<HotspotEvent>, Error> {
+    let kta = kta::for_entity_key(hotspot_key).await?;
+    let mut events = asset_events(client, &kta.asset).await?;
+
+    for subdao in SubDao::all() {
+        let info_key = subdao.info_key(hotspot_key);
+        let signatures = signatures_for_address(client, &info_key).await?;
+        let info_events: Vec<HotspotEvent> = stream::iter(signatures)
+            .map(|signature| get_transaction(client, signature))
+            .buffered(5)
+            .try_filter_map(|txn| async move {
+                let onboard = info::is_onboard_transaction(&txn);
+                let Some(update) = CommittedHotspotInfoUpdate::from_transaction(txn)? else {
+                    return Ok(None);
+                };
+                let kind = if onboard {
+                    HotspotEventKind::Onboarded {
+                        sub_dao: subdao,
+                        update: update.update,
+                    }
+                } else {
+                    HotspotEventKind::Asserted {
+                        sub_dao: subdao,
+                        update: update.update,
+                    }
+                };
+                Ok(Some(HotspotEvent {
+                    block: update.block,
+                    timestamp: update.timestamp,
+                    signature: update.signature,
+                    kind,
+                }))
+            })
+            .try_collect()
+            .await?;
+        events.extend(info_events);
+    }
+
+    events.sort_by_key(|event| event.block);
+    Ok(events)
+}
+
+/// The signatures of all successful transactions for an address, paging back
+/// through its history with a `before` cursor
+pub(crate) async fn signatures_for_address<C: AsRef<SolanaRpcClient>>(
+    client: &C,
+    address: &Pubkey,
+) -> Result<Vec<Signature>, Error> {
+    let mut signatures = vec![];
+    let mut before = None;
+    loop {
+        let page = client
+            .as_ref()
+            .get_signatures_for_address_with_config(
+                address,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    limit: Some(SIGNATURE_PAGE_LIMIT as usize),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        let page_len = page.len();
+        if let Some(last) = page.last() {
+            before = Some(Signature::from_str(&last.signature).map_err(DecodeError::from)?);
+        }
+        for status in page.into_iter().filter(|status| status.err.is_none()) {
+            signatures.push(Signature::from_str(&status.signature).map_err(DecodeError::from)?);
+        }
+        if page_len < SIGNATURE_PAGE_LIMIT as usize {
+            break;
+        }
+    }
+    Ok(signatures)
+}
+
+pub(crate) async fn get_transaction<C: AsRef<SolanaRpcClient>>(
+    client: &C,
+    signature: Signature,
+) -> Result<EncodedConfirmedTransactionWithStatusMeta, Error> {
+    client
+        .as_ref()
+        .get_transaction_with_config(
+            &signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::JsonParsed),
+                commitment: Some(CommitmentConfig::finalized()),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+        .map_err(Error::from)
+        .await
+}
+
+async fn asset_events<C: AsRef<SolanaRpcClient> + AsRef<DasClient>>(
+    client: &C,
+    asset: &Pubkey,
+) -> Result<Vec<HotspotEvent>, Error> {
+    let das_client: &DasClient = client.as_ref();
+    let mut signatures = vec![];
+    let mut page = 1;
+    loop {
+        let signature_page = das_client
+            .get_signatures_for_asset(asset, page, SIGNATURE_PAGE_LIMIT)
+            .await?;
+        let page_len = signature_page.items.len();
+        // Only fetch the transactions that may change ownership. They are
+        // classified by their bubblegum instruction once fetched.
+        signatures.extend(
+            signature_page
+                .items
+                .into_iter()
+                .filter(|(_, kind)| matches!(kind.as_str(), "MintToCollectionV1" | "Transfer"))
+                .map(|(signature, _)| signature),
+        );
+        if page_len < SIGNATURE_PAGE_LIMIT as usize {
+            break;
+        }
+        page += 1;
+    }
+
+    stream::iter(signatures)
+        .map(|signature| async move {
+            let signature = Signature::from_str(&signature).map_err(DecodeError::from)?;
+            get_transaction(client, signature).await
+        })
+        .buffered(5)
+        .try_filter_map(|txn| async move {
+            HotspotEvent::from_asset_transaction(txn).map_err(Error::from)
+        })
+        .try_collect()
+        .await
+}
+
+impl HotspotEvent {
+    fn from_asset_transaction(
+        txn: EncodedConfirmedTransactionWithStatusMeta,
+    ) -> Result<Option<Self>, DecodeError> {
+        if let Some(meta) = &txn.transaction.meta {
+            if meta.err.is_some() {
+                return Ok(None);
+            }
+        }
+        let Some(timestamp) = txn
+            .block_time
+            .and_then(|block_time| DateTime::from_timestamp(block_time, 0))
+        else {
+            return Err(DecodeError::other("no valid block time found"));
+        };
+        let EncodedTransaction::Json(ui_txn) = txn.transaction.transaction else {
+            return Err(DecodeError::other("not a json encoded transaction"));
+        };
+        let UiMessage::Parsed(ui_msg) = ui_txn.message else {
+            return Err(DecodeError::other("not a parsed transaction message"));
+        };
+        let Some(kind) = ui_msg.instructions.into_iter().find_map(asset_event_kind) else {
+            return Ok(None);
+        };
+        Ok(Some(Self {
+            block: txn.slot,
+            timestamp,
+            signature: ui_txn.signatures[0].clone(),
+            kind,
+        }))
+    }
+}
+
+/// The ownership event of the given instruction if it is a bubblegum mint
+/// or transfer
+fn asset_event_kind(ixn: UiInstruction) -> Option<HotspotEventKind> {
+    // The new leaf owner follows the tree config, leaf owner and delegate
+    const NEW_LEAF_OWNER_INDEX: usize = 3;
+    let UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(decoded)) = ixn else {
+        return None;
+    };
+    if decoded.program_id != mpl_bubblegum::ID.to_string() {
+        return None;
+    }
+    let data = solana_sdk::bs58::decode(&decoded.data).into_vec().ok()?;
+    if data.len() < 8 {
+        return None;
+    }
+    match mpl_bubblegum::get_instruction_type(&data) {
+        mpl_bubblegum::InstructionName::MintToCollectionV1 => Some(HotspotEventKind::Issued),
+        mpl_bubblegum::InstructionName::Transfer => decoded
+            .accounts
+            .get(NEW_LEAF_OWNER_INDEX)
+            .and_then(|account| Pubkey::from_str(account).ok())
+            .map(|owner| HotspotEventKind::Transferred { owner }),
+        _ => None,
+    }
+}
@@ -7,10 +7,13 @@ use crate::{
     dao::SubDao,
     entity_key::AsEntityKey,
     error::{DecodeError, Error},
-    helium_entity_manager,
-    hotspot::{CommittedHotspotInfoUpdate, HotspotInfo, HotspotInfoUpdate},
+    helium_entity_manager, helium_sub_daos,
+    hotspot::{
+        CommittedHotspotInfoUpdate, HotspotInfo, HotspotInfoUpdate, HotspotMode, MobileDeviceType,
+    },
     keypair::Pubkey,
     solana_sdk::{commitment_config::CommitmentConfig, signature::Signature},
+    token::{Token, TokenAmount},
 };
 use chrono::DateTime;
 use futures::{
@@ -103,6 +106,118 @@ pub async fn for_entity_key<C: GetAnchorAccount>(
         .await
 }
 
+/// Compute the Data Credit fee charged for the given update of a Hotspot.
+///
+/// A location staking fee is only charged when the update changes the asserted
+/// location. The fee depends on the Hotspot mode and, for Mobile Hotspots, the
+/// device type, as configured in the subdao's rewardable entity config.
+pub async fn update_fee<C: GetAnchorAccount>(
+    client: &C,
+    key: &helium_crypto::PublicKey,
+    update: &HotspotInfoUpdate,
+) -> Result<TokenAmount, Error> {
+    let subdao = update.subdao();
+    let info = for_entity_key_in_subdao(client, subdao, key)
+        .await?
+        .ok_or_else(|| DecodeError::other(format!("hotspot not onboarded to {subdao}")))?;
+    let new_location = update.location_u64();
+    if new_location.is_none() || new_location == info.location_u64() {
+        return Ok(Token::Dc.amount(0));
+    }
+    let fees = fees(
+        client,
+        subdao,
+        info.mode(),
+        info.mobile_device_type().unwrap_or_default(),
+    )
+    .await?;
+    Ok(fees.location)
+}
+
+/// The Data Credit fees for onboarding a Hotspot and asserting its location
+#[derive(Debug, Serialize, Clone)]
+pub struct HotspotFees {
+    pub onboard: TokenAmount,
+    pub location: TokenAmount,
+}
+
+/// Get the Data Credit fees for onboarding and asserting a Hotspot of the
+/// given mode in a subdao.
+///
+/// The device type is only used for subdaos that charge fees by device type.
+pub async fn fees<C: GetAnchorAccount>(
+    client: &C,
+    subdao: SubDao,
+    mode: HotspotMode,
+    device_type: MobileDeviceType,
+) -> Result<HotspotFees, Error> {
+    use helium_entity_manager::ConfigSettingsV0;
+
+    let config = client
+        .anchor_account::<helium_entity_manager::RewardableEntityConfigV0>(
+            &subdao.rewardable_entity_config_key(),
+        )
+        .await?;
+    let device_fees = |fees: Vec<(MobileDeviceType, u64, u64)>| {
+        fees.into_iter()
+            .find_map(|(fee_device, onboard, location)| {
+                (fee_device == device_type).then_some((onboard, location))
+            })
+            .ok_or_else(|| DecodeError::other(format!("no fees for device type {device_type}")))
+    };
+    let (onboard, location) = match config.settings {
+        ConfigSettingsV0::IotConfig {
+            full_location_staking_fee,
+            dataonly_location_staking_fee,
+            ..
+        }
+        | ConfigSettingsV0::MobileConfig {
+            full_location_staking_fee,
+            dataonly_location_staking_fee,
+        } => {
+            // Onboarding fees for mode based configs are set on the subdao
+            let sub_dao = client
+                .anchor_account::<helium_sub_daos::SubDaoV0>(&subdao.key())
+                .await?;
+            match mode {
+                HotspotMode::Full => (sub_dao.onboarding_dc_fee, full_location_staking_fee),
+                HotspotMode::DataOnly => (
+                    sub_dao.onboarding_data_only_dc_fee,
+                    dataonly_location_staking_fee,
+                ),
+            }
+        }
+        ConfigSettingsV0::MobileConfigV1 { fees_by_device } => device_fees(
+            fees_by_device
+                .into_iter()
+                .map(|fees| {
+                    (
+                        fees.device_type.into(),
+                        fees.dc_onboarding_fee,
+                        fees.location_staking_fee,
+                    )
+                })
+                .collect(),
+        )?,
+        ConfigSettingsV0::MobileConfigV2 { fees_by_device } => device_fees(
+            fees_by_device
+                .into_iter()
+                .map(|fees| {
+                    (
+                        fees.device_type.into(),
+                        fees.dc_onboarding_fee,
+                        fees.location_staking_fee,
+                    )
+                })
+                .collect(),
+        )?,
+    };
+    Ok(HotspotFees {
+        onboard: Token::Dc.amount(onboard),
+        location: Token::Dc.amount(location),
+    })
+}
+
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct HotspotInfoUpdateParams {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -167,8 +282,40 @@ pub async fn updates<C: AsRef<SolanaRpcClient>>(
     Ok(updates)
 }
 
+/// Whether the given transaction onboards a Hotspot to a subdao, rather than
+/// updating the info of an onboarded Hotspot, going by the program id and
+/// discriminator of its instructions
+pub(crate) fn is_onboard_transaction(txn: &EncodedConfirmedTransactionWithStatusMeta) -> bool {
+    let EncodedTransaction::Json(ui_txn) = &txn.transaction.transaction else {
+        return false;
+    };
+    let UiMessage::Parsed(ui_msg) = &ui_txn.message else {
+        return false;
+    };
+    ui_msg.instructions.iter().any(|ixn| {
+        let UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(decoded)) = ixn else {
+            return false;
+        };
+        if decoded.program_id != helium_entity_manager::id().to_string() {
+            return false;
+        }
+        let Ok(data) = solana_sdk::bs58::decode(&decoded.data).into_vec() else {
+            return false;
+        };
+        data.len() >= 8
+            && [
+                OnboardIotHotspotV0::DISCRIMINATOR,
+                OnboardDataOnlyIotHotspotV0::DISCRIMINATOR,
+                OnboardMobileHotspotV0::DISCRIMINATOR,
+                OnboardDataOnlyMobileHotspotV0::DISCRIMINATOR,
+            ]
+            .iter()
+            .any(|discriminator| data[..8] == discriminator[..])
+    })
+}
+
 impl CommittedHotspotInfoUpdate {
-    fn from_transaction(
+    pub(crate) fn from_transaction(
         txn: EncodedConfirmedTransactionWithStatusMeta,
     ) -> Result<Option<Self>, DecodeError> {
         // don't handle failed transactions
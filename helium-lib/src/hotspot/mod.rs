@@ -1,7 +1,7 @@
 use crate::{
     anchor_lang::{InstructionData, ToAccountMetas},
     anchor_spl, asset, bs58,
-    client::{DasClient, DasSearchAssetsParams, GetAnchorAccount, SolanaRpcClient},
+    client::{DasClient, DasSearchAssetsParams, DasSortBy, GetAnchorAccount, SolanaRpcClient},
     dao::{Dao, SubDao},
     data_credits,
     error::{DecodeError, EncodeError, Error},
@@ -14,19 +14,23 @@ use crate::{
         signer::Signer,
         transaction::VersionedTransaction,
     },
-    token::Token,
-    TransactionOpts,
+    token::{Token, TokenAmount},
+    tx, TransactionOpts,
 };
 use angry_purple_tiger::AnimalName;
 use chrono::Utc;
-use futures::TryFutureExt;
-use itertools::{izip, Itertools};
+use futures::{
+    stream::{self, Stream},
+    FutureExt, TryFutureExt, TryStreamExt,
+};
+use itertools::Itertools;
 use rust_decimal::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, hash::Hash, str::FromStr};
 
 pub mod cert;
 pub mod dataonly;
+pub mod history;
 pub mod info;
 
 pub const HOTSPOT_CREATOR: Pubkey = pubkey!("Fv5hf1Fg58htfC7YEXKNEfkpuogUUQDDTLgjGWxxv48H");
@@ -47,21 +51,67 @@ pub async fn for_owner<C: AsRef<DasClient>>(
     client: &C,
     owner: &Pubkey,
 ) -> Result<Vec<Hotspot>, Error> {
-    let assets = asset::for_owner(client, &HOTSPOT_CREATOR, owner).await?;
-    // Get all kta keys for the Hotspots in the assets for the given owner
-    let (kta_keys, hotspot_assets): (Vec<Pubkey>, Vec<asset::Asset>) = assets
-        .into_iter()
-        .filter(|asset| asset.is_symbol("HOTSPOT"))
-        .map(|asset| asset.kta_key().map(|kta_key| (kta_key, asset)))
-        .collect::<Result<Vec<(Pubkey, asset::Asset)>, Error>>()?
-        .into_iter()
-        .unzip();
-    // Get all ktas in one go
-    let ktas = kta::get_many(&kta_keys).await?;
-    // Then construct Hotspots from assets and ktas
-    izip!(ktas, hotspot_assets)
-        .map(|(kta, asset)| Hotspot::from_asset_with_kta(kta, asset))
-        .try_collect()
+    for_owner_stream(client, owner, None).try_collect().await
+}
+
+/// Stream the Hotspots for a given owner, walking through the DAS search pages
+/// as the stream is consumed. Hotspots are yielded as soon as their page
+/// arrives, so callers can show them progressively.
+///
+/// The key to asset accounts are looked up for each page as it is fetched
+/// which keeps the size of each request bounded for owners with large fleets.
+pub fn for_owner_stream<'a, C: AsRef<DasClient>>(
+    client: &'a C,
+    owner: &Pubkey,
+    sort_by: Option<DasSortBy>,
+) -> impl Stream<Item = Result<Hotspot, Error>> + 'a {
+    let params = DasSearchAssetsParams::for_owner(*owner, HOTSPOT_CREATOR);
+    asset::pages(sort_by, move |page| {
+        asset::search(client, params.clone().with_page(&page))
+    })
+    .and_then(|mut asset_page| async move {
+        asset_page.items.retain(|asset| asset.is_symbol("HOTSPOT"));
+        let page = HotspotPage::from_asset_page(asset_page).await?;
+        Ok(stream::iter(page.items.into_iter().map(Ok::<_, Error>)))
+    })
+    .try_flatten()
+}
+
+/// The collection of the Hotspots issued by a maker, for searching them with
+/// [`DasSearchAssetsParams::for_collection`]
+pub async fn maker_collection<C: GetAnchorAccount>(
+    client: &C,
+    maker: &Pubkey,
+) -> Result<Pubkey, Error> {
+    let maker = client
+        .anchor_account::<helium_entity_manager::MakerV0>(maker)
+        .await?;
+    Ok(maker.collection)
+}
+
+/// Attach the on-chain info for the given subdaos to each of the given
+/// Hotspots. Info accounts are fetched in batches per subdao.
+pub async fn with_info<C: GetAnchorAccount>(
+    client: &C,
+    subdaos: &[SubDao],
+    mut hotspots: Vec<Hotspot>,
+) -> Result<Vec<Hotspot>, Error> {
+    for subdao in subdaos {
+        let info_keys: Vec<Pubkey> = hotspots
+            .iter()
+            .map(|hotspot| subdao.info_key(&hotspot.key))
+            .collect();
+        let infos = info::get_many(client, *subdao, &info_keys).await?;
+        for (hotspot, info) in hotspots.iter_mut().zip(infos) {
+            if let Some(info) = info {
+                hotspot
+                    .info
+                    .get_or_insert_with(HashMap::new)
+                    .insert(*subdao, info);
+            }
+        }
+    }
+    Ok(hotspots)
 }
 
 pub async fn search<C: AsRef<DasClient>>(
@@ -109,6 +159,78 @@ pub async fn get_with_info<C: AsRef<DasClient> + GetAnchorAccount>(
     Ok(hotspot)
 }
 
+/// Get the merged on-chain view of a Hotspot: its asset, key to asset account,
+/// subdao info and on-chain status, and the maker that onboarded it.
+///
+/// The maker is looked up through the given onboarding server, and is left
+/// out if no server is given or the server does not know the Hotspot.
+pub async fn details<C: AsRef<DasClient> + GetAnchorAccount>(
+    client: &C,
+    onboarding_server: Option<String>,
+    hotspot_key: &helium_crypto::PublicKey,
+) -> Result<HotspotDetails, Error> {
+    let (mut hotspot, iot_info, mobile_info) = futures::try_join!(
+        get(client, hotspot_key),
+        client
+            .anchor_account::<helium_entity_manager::IotHotspotInfoV0>(
+                &SubDao::Iot.info_key(hotspot_key)
+            )
+            .map(Error::not_found_as_none),
+        client
+            .anchor_account::<helium_entity_manager::MobileHotspotInfoV0>(
+                &SubDao::Mobile.info_key(hotspot_key)
+            )
+            .map(Error::not_found_as_none),
+    )?;
+
+    let mut info = HashMap::new();
+    let mut status = HashMap::new();
+    if let Some(iot_info) = iot_info {
+        status.insert(
+            SubDao::Iot,
+            HotspotStatus {
+                active: iot_info.is_active,
+                onboarding_fee_paid: Token::Dc.amount(iot_info.dc_onboarding_fee_paid),
+            },
+        );
+        info.insert(SubDao::Iot, HotspotInfo::from(iot_info));
+    }
+    if let Some(mobile_info) = mobile_info {
+        status.insert(
+            SubDao::Mobile,
+            HotspotStatus {
+                active: mobile_info.is_active,
+                onboarding_fee_paid: Token::Dc.amount(mobile_info.dc_onboarding_fee_paid),
+            },
+        );
+        info.insert(SubDao::Mobile, HotspotInfo::from(mobile_info));
+    }
+    if !info.is_empty() {
+        hotspot.info = Some(info);
+    }
+
+    let maker = match onboarding_server {
+        Some(server) => match onboarding::Client::new(&server)
+            .get_hotspot(hotspot_key)
+            .await
+        {
+            Ok(onboarding_hotspot) => Some(onboarding_hotspot.maker),
+            Err(onboarding::OnboardingError::NotFound) => None,
+            Err(err) => return Err(err.into()),
+        },
+        None => None,
+    };
+    let claimed = maker.as_ref().map(|maker| maker.address != hotspot.owner);
+
+    Ok(HotspotDetails {
+        kta: Dao::Hnt.entity_key_to_kta_key(hotspot_key),
+        hotspot,
+        maker,
+        claimed,
+        status,
+    })
+}
+
 pub async fn direct_update_message<C: AsRef<SolanaRpcClient> + AsRef<DasClient>>(
     client: &C,
     hotspot: &helium_crypto::PublicKey,
@@ -191,7 +313,7 @@ pub async fn direct_update_message<C: AsRef<SolanaRpcClient> + AsRef<DasClient>>
         SubDao::Mobile => {
             mk_update_data!(IxUpdateMobileInfo, ArgsUpdateMobileInfo,
             location: update.location_u64(),
-            deployment_info: None,
+            deployment_info: update.deployment_info_v0()?,
             )
         }
     };
@@ -203,12 +325,11 @@ pub async fn direct_update_message<C: AsRef<SolanaRpcClient> + AsRef<DasClient>>
 
     let ixs = &[
         priority_fee::compute_budget_instruction(200_000),
-        priority_fee::compute_price_instruction_for_accounts(client, &accounts, opts.fee_range())
-            .await?,
+        priority_fee::compute_price_instruction_for_accounts(client, &accounts, opts).await?,
         ix,
     ];
 
-    message::mk_message(client, ixs, &opts.lut_addresses, owner).await
+    message::mk_message(client, ixs, opts, owner).await
 }
 
 pub async fn direct_update<C: AsRef<SolanaRpcClient> + AsRef<DasClient>>(
@@ -220,7 +341,7 @@ pub async fn direct_update<C: AsRef<SolanaRpcClient> + AsRef<DasClient>>(
 ) -> Result<(VersionedTransaction, u64), Error> {
     let (msg, block_height) =
         direct_update_message(client, hotspot, update, &keypair.pubkey(), opts).await?;
-    let txn = VersionedTransaction::try_new(msg, &[keypair])?;
+    let txn = tx::partial_sign(msg, &[keypair])?;
     Ok((txn, block_height))
 }
 
@@ -396,6 +517,28 @@ impl Hotspot {
     }
 }
 
+/// The on-chain status of a Hotspot in a subdao
+#[derive(Debug, Serialize, Clone, Copy)]
+pub struct HotspotStatus {
+    pub active: bool,
+    pub onboarding_fee_paid: TokenAmount,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HotspotDetails {
+    #[serde(flatten)]
+    pub hotspot: Hotspot,
+    #[serde(with = "serde_pubkey")]
+    pub kta: Pubkey,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maker: Option<onboarding::Maker>,
+    /// Whether the Hotspot has been transferred away from its maker. Only
+    /// known when the maker is known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub claimed: Option<bool>,
+    pub status: HashMap<SubDao, HotspotStatus>,
+}
+
 #[derive(Serialize, Debug, Clone, Copy, Deserialize)]
 pub struct HotspotGeo {
     pub lat: f64,
@@ -538,6 +681,37 @@ pub enum MobileDeploymentInfo {
     },
 }
 
+impl MobileDeploymentInfo {
+    /// Construct WiFi deployment info. Angles are given in degrees and kept to
+    /// two decimals, the precision stored on chain.
+    pub fn wifi(
+        antenna: u32,
+        elevation: i32,
+        azimuth: f64,
+        mechanical_down_tilt: f64,
+        electrical_down_tilt: f64,
+    ) -> Result<Self, EncodeError> {
+        fn to_decimal(value: f64, name: &str) -> Result<Decimal, EncodeError> {
+            Decimal::from_f64(value)
+                .filter(|dec| !dec.is_sign_negative())
+                .map(|dec| dec.trunc_with_scale(2))
+                .ok_or_else(|| EncodeError::other(format!("invalid {name}: {value}")))
+        }
+        if !(0.0..360.0).contains(&azimuth) {
+            return Err(EncodeError::other(format!(
+                "azimuth must be in [0, 360): {azimuth}"
+            )));
+        }
+        Ok(Self::WifiInfo {
+            antenna,
+            elevation,
+            azimuth: to_decimal(azimuth, "azimuth")?,
+            mechanical_down_tilt: to_decimal(mechanical_down_tilt, "mechanical down tilt")?,
+            electrical_down_tilt: to_decimal(electrical_down_tilt, "electrical down tilt")?,
+        })
+    }
+}
+
 #[derive(Debug, Serialize, Clone, Hash, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub struct CbrsRadioInfo {
@@ -559,6 +733,9 @@ pub struct CommittedHotspotInfoUpdate {
     pub update: HotspotInfoUpdate,
 }
 
+/// The H3 resolution Hotspot locations are asserted at
+pub const LOCATION_RESOLUTION: h3o::Resolution = h3o::Resolution::Twelve;
+
 #[derive(Debug, Serialize, Clone)]
 #[serde(rename_all = "lowercase", untagged)]
 pub enum HotspotInfoUpdate {
@@ -575,6 +752,8 @@ pub enum HotspotInfoUpdate {
         #[serde(flatten)]
         #[serde(skip_serializing_if = "Option::is_none")]
         location: Option<HotspotLocation>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        deployment_info: Option<MobileDeploymentInfo>,
     },
 }
 
@@ -593,7 +772,10 @@ impl HotspotInfoUpdate {
                 elevation: None,
                 location: None,
             },
-            SubDao::Mobile => Self::Mobile { location: None },
+            SubDao::Mobile => Self::Mobile {
+                location: None,
+                deployment_info: None,
+            },
         }
     }
 
@@ -617,12 +799,26 @@ impl HotspotInfoUpdate {
         self
     }
 
+    /// Set the location to the given H3 cell, which must be at the resolution
+    /// Hotspot locations are asserted at.
+    pub fn set_cell(self, cell: Option<h3o::CellIndex>) -> Result<Self, EncodeError> {
+        if let Some(cell) = cell {
+            if cell.resolution() != LOCATION_RESOLUTION {
+                return Err(EncodeError::other(format!(
+                    "h3 cell {cell} has resolution {}, expected {LOCATION_RESOLUTION}",
+                    cell.resolution()
+                )));
+            }
+        }
+        Ok(self.set_location(cell))
+    }
+
     pub fn set_geo(self, lat: Option<f64>, lon: Option<f64>) -> Result<Self, EncodeError> {
         let location: Option<h3o::CellIndex> = match (lat, lon) {
             (Some(lat), Some(lon)) => Some(
                 h3o::LatLng::new(lat, lon)
                     .map_err(EncodeError::from)?
-                    .to_cell(h3o::Resolution::Twelve),
+                    .to_cell(LOCATION_RESOLUTION),
             ),
             (None, None) => None,
             _ => return Err(EncodeError::other("Both lat and lon must be specified")),
@@ -676,6 +872,35 @@ impl HotspotInfoUpdate {
         }
         self
     }
+
+    pub fn deployment_info(&self) -> &Option<MobileDeploymentInfo> {
+        match self {
+            Self::Iot { .. } => &None,
+            Self::Mobile {
+                deployment_info, ..
+            } => deployment_info,
+        }
+    }
+
+    pub fn set_deployment_info(mut self, new_info: Option<MobileDeploymentInfo>) -> Self {
+        if let Self::Mobile {
+            ref mut deployment_info,
+            ..
+        } = self
+        {
+            *deployment_info = new_info
+        };
+        self
+    }
+
+    pub fn deployment_info_v0(
+        &self,
+    ) -> Result<Option<helium_entity_manager::MobileDeploymentInfoV0>, EncodeError> {
+        self.deployment_info()
+            .as_ref()
+            .map(helium_entity_manager::MobileDeploymentInfoV0::try_from)
+            .transpose()
+    }
 }
 
 #[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, Default, Hash, Deserialize)]
@@ -828,6 +1053,44 @@ impl From<helium_entity_manager::MobileDeploymentInfoV0> for MobileDeploymentInf
     }
 }
 
+impl TryFrom<&MobileDeploymentInfo> for helium_entity_manager::MobileDeploymentInfoV0 {
+    type Error = EncodeError;
+    fn try_from(value: &MobileDeploymentInfo) -> Result<Self, Self::Error> {
+        // Angles are stored on chain in hundredths of a degree
+        fn to_u16(value: Decimal, name: &str) -> Result<u16, EncodeError> {
+            (value * Decimal::ONE_HUNDRED)
+                .trunc()
+                .to_u16()
+                .ok_or_else(|| EncodeError::other(format!("invalid {name}: {value}")))
+        }
+        let info = match value {
+            MobileDeploymentInfo::WifiInfo {
+                antenna,
+                elevation,
+                azimuth,
+                mechanical_down_tilt,
+                electrical_down_tilt,
+            } => Self::WifiInfoV0 {
+                antenna: *antenna,
+                elevation: *elevation,
+                azimuth: to_u16(*azimuth, "azimuth")?,
+                mechanical_down_tilt: to_u16(*mechanical_down_tilt, "mechanical down tilt")?,
+                electrical_down_tilt: to_u16(*electrical_down_tilt, "electrical down tilt")?,
+            },
+            MobileDeploymentInfo::CbrsInfo { radio_infos } => Self::CbrsInfoV0 {
+                radio_infos: radio_infos
+                    .iter()
+                    .map(|info| helium_entity_manager::RadioInfoV0 {
+                        radio_id: info.radio_id.clone(),
+                        elevation: info.elevation,
+                    })
+                    .collect(),
+            },
+        };
+        Ok(info)
+    }
+}
+
 impl From<helium_entity_manager::RadioInfoV0> for CbrsRadioInfo {
     fn from(value: helium_entity_manager::RadioInfoV0) -> Self {
         Self {
@@ -871,6 +1134,7 @@ impl From<helium_entity_manager::UpdateMobileInfoArgsV0> for HotspotInfoUpdate {
     fn from(value: helium_entity_manager::UpdateMobileInfoArgsV0) -> Self {
         Self::Mobile {
             location: HotspotLocation::from_maybe(value.location),
+            deployment_info: value.deployment_info.map(MobileDeploymentInfo::from),
         }
     }
 }
@@ -879,6 +1143,7 @@ impl From<helium_entity_manager::OnboardMobileHotspotArgsV0> for HotspotInfoUpda
     fn from(value: helium_entity_manager::OnboardMobileHotspotArgsV0) -> Self {
         Self::Mobile {
             location: HotspotLocation::from_maybe(value.location),
+            deployment_info: value.deployment_info.map(MobileDeploymentInfo::from),
         }
     }
 }
@@ -887,6 +1152,7 @@ impl From<helium_entity_manager::OnboardDataOnlyMobileHotspotArgsV0> for Hotspot
     fn from(value: helium_entity_manager::OnboardDataOnlyMobileHotspotArgsV0) -> Self {
         Self::Mobile {
             location: HotspotLocation::from_maybe(value.location),
+            deployment_info: None,
         }
     }
 }
@@ -125,6 +125,22 @@ impl Keypair {
         Ok(self.try_sign_message(msg)?)
     }
 
+    /// The address of this keypair, as the solana-sdk independent type
+    pub fn address(&self) -> crate::primitives::Address {
+        self.pubkey().into()
+    }
+
+    /// Convert to the equivalent Helium keypair, used to sign requests to
+    /// Helium services like the config service.
+    pub fn to_helium_keypair(&self) -> Result<helium_crypto::Keypair, Error> {
+        let mut bytes = vec![u8::from(helium_crypto::KeyTag {
+            network: helium_crypto::Network::MainNet,
+            key_type: helium_crypto::KeyType::Ed25519,
+        })];
+        bytes.extend_from_slice(&self.secret());
+        Ok(helium_crypto::Keypair::try_from(&bytes[..])?)
+    }
+
     /// Return the mnemonic phrase that can be used to recreate this Keypair.
     /// This function is implemented here to avoid passing the secret between
     /// too many modules.
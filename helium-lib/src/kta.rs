@@ -3,6 +3,7 @@ use crate::{
     error::Error, helium_entity_manager::KeyToAssetV0, keypair::Pubkey,
     solana_sdk::account::Account,
 };
+use chrono::Utc;
 use futures::{stream, StreamExt, TryFutureExt, TryStreamExt};
 use itertools::Itertools;
 use std::{
@@ -11,15 +12,23 @@ use std::{
 };
 
 pub fn init(solana_client: Arc<SolanaRpcClient>) -> Result<(), Error> {
-    let _ = CACHE.set(KtaCache::new(solana_client)?);
+    if CACHE.get().is_none() {
+        let _ = CACHE.set(KtaCache::new(solana_client.clone())?);
+    }
+    let cache = CACHE.get().expect("kta cache");
+    if cache.solana_client.url() != solana_client.url() {
+        return Err(Error::CacheUrlMismatch);
+    }
     Ok(())
 }
 
+#[tracing::instrument(level = "debug")]
 pub async fn get(kta_key: &Pubkey) -> Result<KeyToAssetV0, Error> {
     let cache = CACHE.get().ok_or_else(Error::account_not_found)?;
     cache.get(kta_key).await
 }
 
+#[tracing::instrument(skip_all, fields(count = kta_keys.len()), level = "debug")]
 pub async fn get_many(kta_keys: &[Pubkey]) -> Result<Vec<KeyToAssetV0>, Error> {
     let cache = CACHE.get().ok_or_else(Error::account_not_found)?;
     cache.get_many(kta_keys).await
@@ -44,9 +53,164 @@ where
     get_many(&kta_keys).await
 }
 
+/// Load the KTAs of the given entity keys into the cache, in batched
+/// `getMultipleAccounts` requests, so that following lookups of a single
+/// entity key are served from the cache.
+///
+/// Entities without a KTA are skipped rather than failing the prefetch.
+/// Returns the number of given entities that have a KTA.
+#[tracing::instrument(skip_all, fields(count = entity_keys.len()), level = "debug")]
+pub async fn prefetch<E>(entity_keys: &[E]) -> Result<usize, Error>
+where
+    E: AsEntityKey,
+{
+    let cache = CACHE.get().ok_or_else(Error::account_not_found)?;
+    let kta_keys = entity_keys
+        .iter()
+        .map(|entity_key| Dao::Hnt.entity_key_to_kta_key(entity_key))
+        .collect::<Vec<Pubkey>>();
+    cache.fetch_missing(&kta_keys).await?;
+    let cache = cache.cache_read();
+    Ok(kta_keys
+        .iter()
+        .filter(|key| cache.contains_key(key))
+        .count())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use persist::{load, save};
+
+/// Persisting the cache to a file, which is not available in the browser
+#[cfg(not(target_arch = "wasm32"))]
+mod persist {
+    use super::*;
+    use crate::{
+        anchor_lang::AccountSerialize,
+        error::{DecodeError, EncodeError},
+    };
+    use serde::{Deserialize, Serialize};
+    use std::{fs, path::Path, time::Duration};
+
+    /// Load KTAs persisted with [`save`] into the cache, leaving out the ones
+    /// fetched longer than `max_age` ago. A missing file loads nothing.
+    ///
+    /// KTAs map an entity key to its asset and never change once created, so
+    /// they can be reused across processes for a long time. Returns the number
+    /// of KTAs loaded.
+    pub fn load(path: &Path, max_age: Duration) -> Result<usize, Error> {
+        let cache = CACHE.get().ok_or_else(Error::account_not_found)?;
+        let data = match fs::read(path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(err) => return Err(DecodeError::from(err).into()),
+        };
+        let file: KtaFile = bincode::deserialize(&data).map_err(DecodeError::from)?;
+        if file.version != KtaFile::VERSION {
+            return Ok(0);
+        }
+        let oldest = Utc::now().timestamp() - i64::try_from(max_age.as_secs()).unwrap_or(i64::MAX);
+        let loaded: Vec<(Pubkey, CachedKta)> = file
+            .entries
+            .into_iter()
+            .filter(|entry| entry.fetched_at >= oldest)
+            .map(|entry| {
+                KeyToAssetV0::try_deserialize(&mut entry.data.as_ref()).map(|kta| {
+                    (
+                        entry.key,
+                        CachedKta {
+                            kta,
+                            fetched_at: entry.fetched_at,
+                        },
+                    )
+                })
+            })
+            .try_collect()?;
+        let count = loaded.len();
+        let mut cache = cache.cache_write();
+        for (key, kta) in loaded {
+            cache.entry(key).or_insert(kta);
+        }
+        tracing::debug!(count, ?path, "kta cache loaded");
+        Ok(count)
+    }
+
+    /// Persist the cached KTAs to the given file for a later [`load`], replacing
+    /// its content. Returns the number of KTAs saved.
+    pub fn save(path: &Path) -> Result<usize, Error> {
+        let cache = CACHE.get().ok_or_else(Error::account_not_found)?;
+        let entries: Vec<StoredKta> = cache
+            .cache_read()
+            .iter()
+            .map(|(key, cached)| {
+                let mut data = vec![];
+                cached
+                    .kta
+                    .try_serialize(&mut data)
+                    .map(|_| StoredKta {
+                        key: *key,
+                        fetched_at: cached.fetched_at,
+                        data,
+                    })
+                    .map_err(|err| EncodeError::other(err.to_string()))
+            })
+            .try_collect()?;
+        let count = entries.len();
+        let data = bincode::serialize(&KtaFile {
+            version: KtaFile::VERSION,
+            entries,
+        })
+        .map_err(EncodeError::from)?;
+        // Write to a temporary file first so that a concurrent run never loads a
+        // partially written cache
+        let io_err = |err: std::io::Error| EncodeError::other(format!("{}: {err}", path.display()));
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(io_err)?;
+        }
+        let tmp_path = path.with_extension(format!("tmp.{}", std::process::id()));
+        fs::write(&tmp_path, data).map_err(io_err)?;
+        fs::rename(&tmp_path, path).map_err(io_err)?;
+        tracing::debug!(count, ?path, "kta cache saved");
+        Ok(count)
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct KtaFile {
+        version: u8,
+        entries: Vec<StoredKta>,
+    }
+
+    impl KtaFile {
+        const VERSION: u8 = 1;
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct StoredKta {
+        key: Pubkey,
+        /// Unix timestamp, in seconds, of when the KTA was fetched
+        fetched_at: i64,
+        /// The KTA account data
+        data: Vec<u8>,
+    }
+}
+
 static CACHE: OnceLock<KtaCache> = OnceLock::new();
 
-type KtaCacheMap = HashMap<Pubkey, KeyToAssetV0>;
+#[derive(Clone)]
+struct CachedKta {
+    kta: KeyToAssetV0,
+    fetched_at: i64,
+}
+
+impl From<KeyToAssetV0> for CachedKta {
+    fn from(kta: KeyToAssetV0) -> Self {
+        Self {
+            kta,
+            fetched_at: Utc::now().timestamp(),
+        }
+    }
+}
+
+type KtaCacheMap = HashMap<Pubkey, CachedKta>;
 struct KtaCache {
     solana_client: Arc<SolanaRpcClient>,
     cache: RwLock<KtaCacheMap>,
@@ -70,8 +234,9 @@ impl KtaCache {
     }
 
     async fn get(&self, kta_key: &Pubkey) -> Result<KeyToAssetV0, Error> {
-        if let Some(account) = self.cache_read().get(kta_key) {
-            return Ok(account.clone());
+        if let Some(cached) = self.cache_read().get(kta_key) {
+            tracing::trace!("kta cache hit");
+            return Ok(cached.kta.clone());
         }
 
         let kta = self
@@ -85,21 +250,28 @@ impl KtaCache {
         // NOTE: Holding lock across an await will not work with std::sync
         // Since sync::RwLock is much faster than sync options we take the hit
         // of multiple requests for the same kta_key before the key is found
-        self.cache_write().insert(*kta_key, kta.clone());
+        self.cache_write().insert(*kta_key, kta.clone().into());
         Ok(kta)
     }
 
-    async fn get_many(&self, kta_keys: &[Pubkey]) -> Result<Vec<KeyToAssetV0>, Error> {
+    /// Fetch the KTAs for the given keys that are not cached yet. Keys
+    /// without an account are left out of the cache.
+    async fn fetch_missing(&self, kta_keys: &[Pubkey]) -> Result<(), Error> {
         let missing_keys: Vec<Pubkey> = {
             let cache = self.cache_read();
             kta_keys
                 .iter()
                 .filter(|key| !cache.contains_key(key))
+                .unique()
                 .copied()
                 .collect()
         };
+        tracing::debug!(missing = missing_keys.len(), "kta cache lookup");
+        if missing_keys.is_empty() {
+            return Ok(());
+        }
 
-        let mut missing_accounts = stream::iter(missing_keys.clone())
+        let missing_accounts = stream::iter(missing_keys.clone())
             // Chunk into documented max keys to pass to getMultipleAccounts
             .chunks(100)
             .map(|key_chunk| async move {
@@ -113,30 +285,32 @@ impl KtaCache {
             .into_iter()
             .flatten()
             .collect_vec();
-        {
-            let mut cache = self.cache_write();
-            missing_keys
-                .into_iter()
-                .zip(missing_accounts.iter_mut())
-                .map(|(key, maybe_account)| {
-                    let Some(account) = maybe_account.as_mut() else {
-                        return Err(Error::account_not_found());
-                    };
+        let fetched: Vec<(Pubkey, CachedKta)> = missing_keys
+            .into_iter()
+            .zip(missing_accounts)
+            .filter_map(|(key, maybe_account)| {
+                maybe_account.map(|account| {
                     KeyToAssetV0::try_deserialize(&mut account.data.as_ref())
                         .map_err(Error::from)
-                        .map(|kta| (key, kta))
+                        .map(|kta| (key, kta.into()))
                 })
-                .map_ok(|(key, kta)| {
-                    cache.insert(key, kta);
-                })
-                .try_collect::<_, (), _>()?;
-        }
-        {
-            let cache = self.cache_read();
-            kta_keys
-                .iter()
-                .map(|key| cache.get(key).cloned().ok_or(Error::account_not_found()))
-                .try_collect()
-        }
+            })
+            .try_collect()?;
+        self.cache_write().extend(fetched);
+        Ok(())
+    }
+
+    async fn get_many(&self, kta_keys: &[Pubkey]) -> Result<Vec<KeyToAssetV0>, Error> {
+        self.fetch_missing(kta_keys).await?;
+        let cache = self.cache_read();
+        kta_keys
+            .iter()
+            .map(|key| {
+                cache
+                    .get(key)
+                    .map(|cached| cached.kta.clone())
+                    .ok_or(Error::account_not_found())
+            })
+            .try_collect()
     }
 }
@@ -1,53 +1,111 @@
+#[cfg(feature = "client")]
 pub mod asset;
 pub mod b64;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub mod blocking;
+#[cfg(feature = "rpc")]
 pub mod client;
 
+#[cfg(feature = "client")]
 pub mod boosting;
+#[cfg(feature = "client")]
 pub mod dao;
+#[cfg(feature = "client")]
 pub mod dc;
+#[cfg(feature = "client")]
 pub mod entity_key;
 pub mod error;
+#[cfg(feature = "client")]
+pub mod geocode;
+#[cfg(feature = "client")]
+pub mod governance;
+#[cfg(feature = "client")]
 pub mod hotspot;
 pub mod keypair;
+#[cfg(feature = "client")]
 pub mod kta;
+#[cfg(feature = "client")]
 pub mod memo;
+#[cfg(feature = "rpc")]
 pub mod message;
+#[cfg(feature = "client")]
+pub mod migration;
+#[cfg(feature = "client")]
 pub mod onboarding;
+#[cfg(feature = "client")]
+pub mod oui;
+#[cfg(feature = "client")]
+pub mod positions;
+pub mod prelude;
+pub mod primitives;
+#[cfg(feature = "rpc")]
 pub mod priority_fee;
 pub mod programs;
+#[cfg(feature = "client")]
+pub mod rent;
+#[cfg(feature = "client")]
 pub mod reward;
+#[cfg(feature = "client")]
+pub mod stats;
+#[cfg(feature = "rpc")]
+pub(crate) mod time;
+#[cfg(feature = "rpc")]
 pub mod token;
+#[cfg(feature = "client")]
+pub mod treasury;
+pub mod tx;
+#[cfg(feature = "client")]
+pub mod watch;
 
+// The re-exported dependencies below follow their own versions rather than
+// the semver of this crate, see the prelude module
+#[cfg(feature = "client")]
 pub use anchor_client;
+#[cfg(feature = "client")]
 pub use anchor_client::solana_client;
+#[cfg(feature = "rpc")]
 pub use anchor_spl;
+#[cfg(feature = "client")]
+pub use h3o;
+#[cfg(feature = "rpc")]
 pub use helium_anchor_gen::{
     anchor_lang, circuit_breaker, data_credits, helium_entity_manager, helium_sub_daos,
-    hexboosting, lazy_distributor, rewards_oracle,
+    hexboosting, iot_routing_manager, lazy_distributor, lazy_transactions, mobile_entity_manager,
+    proposal, rewards_oracle, treasury_management, voter_stake_registry,
 };
+#[cfg(feature = "rpc")]
+pub use solana_account_decoder;
+#[cfg(feature = "rpc")]
+pub use solana_rpc_client_api;
 pub use solana_sdk;
 pub use solana_sdk::bs58;
 
+#[cfg(feature = "client")]
 pub(crate) trait Zero {
     const ZERO: Self;
 }
 
+#[cfg(feature = "client")]
 impl Zero for u32 {
     const ZERO: Self = 0;
 }
 
+#[cfg(feature = "client")]
 impl Zero for i32 {
     const ZERO: Self = 0;
 }
 
+#[cfg(feature = "client")]
 impl Zero for u16 {
     const ZERO: Self = 0;
 }
 
+#[cfg(feature = "client")]
 impl Zero for rust_decimal::Decimal {
     const ZERO: Self = rust_decimal::Decimal::ZERO;
 }
 
+#[cfg(feature = "client")]
 pub(crate) fn is_zero<T>(value: &T) -> bool
 where
     T: PartialEq + Zero,
@@ -55,48 +113,183 @@ where
     value == &T::ZERO
 }
 
-use client::SolanaRpcClient;
+#[cfg(feature = "rpc")]
+use client::SolanaRpc;
+#[cfg(feature = "rpc")]
 use error::Error;
+#[cfg(feature = "rpc")]
 use keypair::Pubkey;
-use solana_sdk::{instruction::Instruction, transaction::Transaction};
+#[cfg(feature = "rpc")]
+use solana_sdk::{
+    commitment_config::CommitmentConfig, instruction::Instruction, transaction::Transaction,
+};
+#[cfg(feature = "rpc")]
 use std::{ops::RangeInclusive, sync::Arc};
 
+/// Initialize the process wide KTA and asset proof caches with the RPC client
+/// they fetch through.
+///
+/// The caches stay bound to the first client. Initializing them again with a
+/// client for a different RPC URL fails with
+/// [`Error::CacheUrlMismatch`](error::Error::CacheUrlMismatch), rather than
+/// resolving lookups against the first cluster.
+#[cfg(feature = "client")]
 pub fn init(solana_client: Arc<client::SolanaRpcClient>) -> Result<(), error::Error> {
+    asset::proof::init(solana_client.clone())?;
     kta::init(solana_client)
 }
 
+/// Options for the transactions built by helium-lib. Construct them with
+/// [`TransactionOpts::builder`] to override only some of the defaults.
+#[cfg(feature = "rpc")]
 pub struct TransactionOpts {
     pub min_priority_fee: u64,
     pub max_priority_fee: u64,
     pub lut_addresses: Vec<Pubkey>,
+    /// Margin over the simulated compute units to set the compute unit limit
+    /// to, as a fraction. `None` keeps the compute unit limit of the
+    /// instructions and skips the simulation.
+    pub compute_unit_margin: Option<f64>,
+    /// A fixed compute unit limit replacing the one of the instructions.
+    /// Takes precedence over the compute unit margin.
+    pub compute_unit_limit: Option<u32>,
+    pub priority_fee_strategy: priority_fee::PriorityFeeStrategy,
+    /// Budget for the fees of all transactions built with these options
+    pub fee_budget: Option<Arc<priority_fee::FeeBudget>>,
+    /// Account paying the transaction fees in place of the payer of the
+    /// action. The fee payer has to sign the transaction as well.
+    pub fee_payer: Option<Pubkey>,
+    /// Durable nonce to use in place of a recent blockhash
+    pub durable_nonce: Option<message::DurableNonce>,
+    /// Commitment of the blockhash, the client commitment if not set
+    pub commitment: Option<CommitmentConfig>,
 }
 
+#[cfg(feature = "rpc")]
 impl Default for TransactionOpts {
     fn default() -> Self {
         Self {
             min_priority_fee: priority_fee::MIN_PRIORITY_FEE,
             max_priority_fee: priority_fee::MAX_PRIORITY_FEE,
             lut_addresses: vec![message::COMMON_LUT],
+            compute_unit_margin: Some(priority_fee::DEFAULT_COMPUTE_UNIT_MARGIN),
+            compute_unit_limit: None,
+            priority_fee_strategy: Default::default(),
+            fee_budget: None,
+            fee_payer: None,
+            durable_nonce: None,
+            commitment: None,
         }
     }
 }
 
+#[cfg(feature = "rpc")]
 impl TransactionOpts {
+    pub fn builder() -> TransactionOptsBuilder {
+        TransactionOptsBuilder::default()
+    }
+
     fn fee_range(&self) -> RangeInclusive<u64> {
         RangeInclusive::new(self.min_priority_fee, self.max_priority_fee)
     }
 }
 
-pub async fn mk_transaction_with_blockhash<C: AsRef<SolanaRpcClient>>(
+/// Builder for [`TransactionOpts`], starting from the defaults
+#[cfg(feature = "rpc")]
+#[derive(Default)]
+pub struct TransactionOptsBuilder {
+    opts: TransactionOpts,
+}
+
+#[cfg(feature = "rpc")]
+impl TransactionOptsBuilder {
+    /// Pay transaction fees from the given account instead of the payer of
+    /// the action
+    pub fn fee_payer(mut self, fee_payer: Pubkey) -> Self {
+        self.opts.fee_payer = Some(fee_payer);
+        self
+    }
+
+    /// Limit estimated priority fees to the given range of micro lamports
+    /// per compute unit
+    pub fn priority_fee_range(mut self, min_priority_fee: u64, max_priority_fee: u64) -> Self {
+        self.opts.min_priority_fee = min_priority_fee;
+        self.opts.max_priority_fee = max_priority_fee;
+        self
+    }
+
+    pub fn priority_fee_strategy(mut self, strategy: priority_fee::PriorityFeeStrategy) -> Self {
+        self.opts.priority_fee_strategy = strategy;
+        self
+    }
+
+    /// Use a fixed compute unit price in micro lamports instead of an
+    /// estimate
+    pub fn compute_unit_price(self, compute_unit_price: u64) -> Self {
+        self.priority_fee_strategy(priority_fee::PriorityFeeStrategy::Fixed(compute_unit_price))
+    }
+
+    /// Use a fixed compute unit limit instead of sizing it by simulation
+    pub fn compute_unit_limit(mut self, compute_unit_limit: u32) -> Self {
+        self.opts.compute_unit_limit = Some(compute_unit_limit);
+        self
+    }
+
+    /// Set the margin over simulated compute units, or `None` to keep the
+    /// compute unit limit of the instructions
+    pub fn compute_unit_margin(mut self, compute_unit_margin: Option<f64>) -> Self {
+        self.opts.compute_unit_margin = compute_unit_margin;
+        self
+    }
+
+    pub fn fee_budget(mut self, fee_budget: Arc<priority_fee::FeeBudget>) -> Self {
+        self.opts.fee_budget = Some(fee_budget);
+        self
+    }
+
+    /// Use the blockhash stored in the given nonce account, advanced by the
+    /// given authority, so transactions do not expire
+    pub fn durable_nonce(mut self, account: Pubkey, authority: Pubkey) -> Self {
+        self.opts.durable_nonce = Some(message::DurableNonce { account, authority });
+        self
+    }
+
+    pub fn lut_addresses(mut self, lut_addresses: Vec<Pubkey>) -> Self {
+        self.opts.lut_addresses = lut_addresses;
+        self
+    }
+
+    pub fn commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.opts.commitment = Some(commitment);
+        self
+    }
+
+    pub fn build(self) -> TransactionOpts {
+        self.opts
+    }
+}
+
+/// Construct a legacy transaction for the given instructions with a recent
+/// blockhash. Like [`message::mk_message`], the fee of the transaction is
+/// taken from the fee budget of the options, failing when the budget can not
+/// cover it.
+#[cfg(feature = "rpc")]
+pub async fn mk_transaction_with_blockhash<C: SolanaRpc>(
     client: &C,
     ixs: &[Instruction],
+    opts: &TransactionOpts,
     payer: &Pubkey,
 ) -> Result<(Transaction, u64), Error> {
-    let mut txn = Transaction::new_with_payer(ixs, Some(payer));
-    let solana_client = AsRef::<SolanaRpcClient>::as_ref(client);
-    let (latest_blockhash, latest_block_height) = solana_client
-        .get_latest_blockhash_with_commitment(solana_client.commitment())
-        .await?;
-    txn.message.recent_blockhash = latest_blockhash;
-    Ok((txn, latest_block_height))
+    let (blockhash, last_valid_block_height) = message::blockhash(client, opts).await?;
+    let ixs = message::with_durable_nonce(ixs, opts);
+    let fee_payer = opts.fee_payer.as_ref().unwrap_or(payer);
+    let mut txn = Transaction::new_with_payer(&ixs, Some(fee_payer));
+    txn.message.recent_blockhash = blockhash;
+    let fee =
+        priority_fee::transaction_fee(&ixs, txn.message.header.num_required_signatures.into());
+    tracing::debug!(fee, "transaction fee");
+    if let Some(budget) = &opts.fee_budget {
+        budget.spend(fee)?;
+    }
+    Ok((txn, last_valid_block_height))
 }
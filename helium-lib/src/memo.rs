@@ -4,7 +4,7 @@ use crate::{
     keypair::{Keypair, Pubkey},
     message, priority_fee,
     solana_sdk::{signer::Signer, transaction::VersionedTransaction},
-    TransactionOpts,
+    tx, TransactionOpts,
 };
 
 pub async fn memo_message<C: AsRef<SolanaRpcClient>>(
@@ -16,16 +16,11 @@ pub async fn memo_message<C: AsRef<SolanaRpcClient>>(
     let ix = spl_memo::build_memo(data.as_bytes(), &[pubkey]);
     let ixs = &[
         priority_fee::compute_budget_instruction(200_000),
-        priority_fee::compute_price_instruction_for_accounts(
-            client,
-            &ix.accounts,
-            opts.fee_range(),
-        )
-        .await?,
+        priority_fee::compute_price_instruction_for_accounts(client, &ix.accounts, opts).await?,
         ix,
     ];
 
-    message::mk_message(client, ixs, &opts.lut_addresses, pubkey).await
+    message::mk_message(client, ixs, opts, pubkey).await
 }
 
 pub async fn memo<C: AsRef<SolanaRpcClient>>(
@@ -35,6 +30,6 @@ pub async fn memo<C: AsRef<SolanaRpcClient>>(
     opts: &TransactionOpts,
 ) -> Result<(VersionedTransaction, u64), Error> {
     let (msg, block_height) = memo_message(client, data, &keypair.pubkey(), opts).await?;
-    let txn = VersionedTransaction::try_new(msg, &[keypair])?;
+    let txn = tx::partial_sign(msg, &[keypair])?;
     Ok((txn, block_height))
 }
@@ -1,12 +1,20 @@
 use crate::{
-    client::SolanaRpcClient,
+    client::SolanaRpc,
+    error::DecodeError,
     keypair::pubkey,
+    priority_fee,
+    solana_rpc_client_api::response::RpcSimulateTransactionResult,
     solana_sdk::{
         address_lookup_table::{state::AddressLookupTable, AddressLookupTableAccount},
+        hash::Hash,
         instruction::Instruction,
         message::v0,
+        nonce::state::{State as NonceState, Versions as NonceVersions},
+        signature::Signature,
+        system_instruction,
+        transaction::VersionedTransaction,
     },
-    Error, Pubkey,
+    Error, Pubkey, TransactionOpts,
 };
 use itertools::Itertools;
 
@@ -15,43 +23,174 @@ pub const COMMON_LUT: Pubkey = pubkey!("43eY9L2spbM2b1MPDFFBStUiFGt29ziZ1nc1xbpz
 
 pub use solana_sdk::message::VersionedMessage;
 
-pub async fn get_lut_accounts<C: AsRef<SolanaRpcClient>>(
+pub async fn get_lut_accounts<C: SolanaRpc>(
     client: &C,
     addresses: &[Pubkey],
 ) -> Result<Vec<AddressLookupTableAccount>, Error> {
-    itertools::izip!(
-        addresses,
-        client.as_ref().get_multiple_accounts(addresses).await?
-    )
-    .filter_map(|(address, maybe_account)| {
-        maybe_account.map(|account| {
-            AddressLookupTable::deserialize(&account.data)
-                .map_err(Error::from)
-                .map(|lut| AddressLookupTableAccount {
-                    key: *address,
-                    addresses: lut.addresses.to_vec(),
-                })
+    itertools::izip!(addresses, client.get_multiple_accounts(addresses).await?)
+        .filter_map(|(address, maybe_account)| {
+            maybe_account.map(|account| {
+                AddressLookupTable::deserialize(&account.data)
+                    .map_err(Error::from)
+                    .map(|lut| AddressLookupTableAccount {
+                        key: *address,
+                        addresses: lut.addresses.to_vec(),
+                    })
+            })
         })
-    })
-    .try_collect()
+        .try_collect()
 }
 
-pub async fn mk_message<C: AsRef<SolanaRpcClient>>(
+/// A durable nonce account and the authority that advances it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DurableNonce {
+    pub account: Pubkey,
+    pub authority: Pubkey,
+}
+
+impl DurableNonce {
+    pub fn advance_instruction(&self) -> Instruction {
+        system_instruction::advance_nonce_account(&self.account, &self.authority)
+    }
+}
+
+/// The blockhash for a transaction with the given options and the last block
+/// height the transaction is valid for. With a durable nonce this is the
+/// nonce stored in the nonce account, which does not expire.
+pub async fn blockhash<C: SolanaRpc>(
+    client: &C,
+    opts: &TransactionOpts,
+) -> Result<(Hash, u64), Error> {
+    let Some(nonce) = &opts.durable_nonce else {
+        let commitment = opts.commitment.unwrap_or_else(|| client.commitment());
+        return client
+            .get_latest_blockhash_with_commitment(commitment)
+            .await;
+    };
+    let account = client.get_account(&nonce.account).await?;
+    let versions: NonceVersions = bincode::deserialize(&account.data)
+        .map_err(|err| DecodeError::other(format!("invalid nonce account: {err}")))?;
+    match versions.state() {
+        NonceState::Initialized(data) => Ok((data.blockhash(), u64::MAX)),
+        NonceState::Uninitialized => {
+            Err(DecodeError::other("nonce account not initialized").into())
+        }
+    }
+}
+
+/// The given instructions, preceded by the nonce advance instruction when
+/// the options use a durable nonce
+pub fn with_durable_nonce(ixs: &[Instruction], opts: &TransactionOpts) -> Vec<Instruction> {
+    opts.durable_nonce
+        .iter()
+        .map(DurableNonce::advance_instruction)
+        .chain(ixs.iter().cloned())
+        .collect()
+}
+
+/// Construct a message for the given instructions using the lookup tables,
+/// fee payer and blockhash or durable nonce of the given options.
+///
+/// With a compute unit margin in the options the message is simulated first
+/// and its compute unit limit is set to the consumed units plus the margin.
+/// The instructions are used as given when the simulation fails, so the
+/// failure surfaces when the transaction is sent or simulated by the caller.
+///
+/// With a fee budget in the options the fee of the message is taken from the
+/// budget, failing when the budget can not cover it.
+#[tracing::instrument(skip_all, fields(instructions = ixs.len(), %payer))]
+pub async fn mk_message<C: SolanaRpc>(
     client: &C,
     ixs: &[Instruction],
-    lut_accounts: &[Pubkey],
+    opts: &TransactionOpts,
     payer: &Pubkey,
 ) -> Result<(VersionedMessage, u64), Error> {
-    let solana_client = AsRef::<SolanaRpcClient>::as_ref(client);
-    let lut_accounts = get_lut_accounts(client, lut_accounts).await?;
-    let (recent_blockhash, recent_blockheight) = solana_client
-        .get_latest_blockhash_with_commitment(solana_client.commitment())
-        .await?;
-    let msg = VersionedMessage::V0(v0::Message::try_compile(
-        payer,
-        ixs,
-        &lut_accounts,
-        recent_blockhash,
-    )?);
+    let lut_accounts = get_lut_accounts(client, &opts.lut_addresses).await?;
+    let (recent_blockhash, recent_blockheight) = blockhash(client, opts).await?;
+    tracing::debug!(%recent_blockhash, recent_blockheight, "building message");
+    let fee_payer = opts.fee_payer.as_ref().unwrap_or(payer);
+    let compile = |ixs: &[Instruction]| -> Result<VersionedMessage, Error> {
+        Ok(VersionedMessage::V0(v0::Message::try_compile(
+            fee_payer,
+            &with_durable_nonce(ixs, opts),
+            &lut_accounts,
+            recent_blockhash,
+        )?))
+    };
+    let ixs = match (opts.compute_unit_limit, opts.compute_unit_margin) {
+        (Some(compute_limit), _) => priority_fee::with_compute_unit_limit(ixs, compute_limit),
+        (None, Some(margin)) => {
+            let simulation_msg = compile(&priority_fee::with_compute_unit_limit(
+                ixs,
+                priority_fee::MAX_COMPUTE_UNITS,
+            ))?;
+            match simulated_compute_units(client, simulation_msg).await {
+                Some(units_consumed) => {
+                    let compute_limit = priority_fee::compute_unit_limit(units_consumed, margin);
+                    tracing::debug!(units_consumed, compute_limit, "set compute unit limit");
+                    priority_fee::with_compute_unit_limit(ixs, compute_limit)
+                }
+                None => ixs.to_vec(),
+            }
+        }
+        (None, None) => ixs.to_vec(),
+    };
+    let msg = compile(&ixs)?;
+    let fee = priority_fee::transaction_fee(&ixs, msg.header().num_required_signatures.into());
+    tracing::debug!(fee, "transaction fee");
+    if let Some(budget) = &opts.fee_budget {
+        budget.spend(fee)?;
+    }
     Ok((msg, recent_blockheight))
 }
+
+/// The compute units the given message consumes in an unsigned simulation,
+/// or `None` if the simulation failed
+async fn simulated_compute_units<C: SolanaRpc>(client: &C, msg: VersionedMessage) -> Option<u64> {
+    let tx = VersionedTransaction {
+        signatures: vec![Signature::default(); msg.header().num_required_signatures.into()],
+        message: msg,
+    };
+    match client.simulate_transaction(&tx).await {
+        Ok(RpcSimulateTransactionResult {
+            err: None,
+            units_consumed: Some(units_consumed),
+            ..
+        }) if units_consumed > 0 => Some(units_consumed),
+        Ok(result) => {
+            tracing::debug!(err = ?result.err, "compute unit simulation failed");
+            None
+        }
+        Err(err) => {
+            tracing::debug!(%err, "compute unit simulation failed");
+            None
+        }
+    }
+}
+
+/// Whether a transaction with the given instructions and number of signers
+/// fits in a single packet. Lookup tables are not used for the estimate, so
+/// the result is conservative.
+pub fn fits_in_transaction(ixs: &[Instruction], payer: &Pubkey, signers: usize) -> bool {
+    fits_in_transaction_with_luts(ixs, payer, signers, &[])
+}
+
+/// Like [`fits_in_transaction`], but with the accounts in the given lookup
+/// tables taking up less space
+pub fn fits_in_transaction_with_luts(
+    ixs: &[Instruction],
+    payer: &Pubkey,
+    signers: usize,
+    lut_accounts: &[AddressLookupTableAccount],
+) -> bool {
+    let Ok(msg) = v0::Message::try_compile(payer, ixs, lut_accounts, Default::default()) else {
+        return false;
+    };
+    let Ok(msg_size) = bincode::serialized_size(&VersionedMessage::V0(msg)) else {
+        return false;
+    };
+    // signatures are prefixed with a compact length, which is a single byte
+    // for any practical number of signers
+    let size = 1 + signers * 64 + msg_size as usize;
+    size <= solana_sdk::packet::PACKET_DATA_SIZE
+}
@@ -0,0 +1,162 @@
+use crate::{
+    anchor_lang::{AnchorDeserialize, Discriminator},
+    client::{GetAnchorAccount, SolanaRpcClient},
+    error::{DecodeError, Error},
+    hotspot::history::{get_transaction, signatures_for_address},
+    keypair::{serde_pubkey, Pubkey},
+    lazy_transactions::{self, instruction::ExecuteTransactionV0, ExecuteTransactionArgsV0},
+};
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use serde::Serialize;
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction, UiInstruction, UiMessage,
+    UiParsedInstruction,
+};
+use std::str::FromStr;
+
+/// Name of the lazy transactions account the Solana migration was executed
+/// through
+pub const LAZY_TRANSACTIONS_NAME: &str = "nJWGUMOK";
+
+pub fn lazy_transactions_key() -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"lazy_transactions", LAZY_TRANSACTIONS_NAME.as_bytes()],
+        &lazy_transactions::id(),
+    )
+    .0
+}
+
+/// A migration transaction that was executed for a wallet
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationTransaction {
+    /// Index of the transaction in the migration merkle tree
+    pub index: u32,
+    pub block: u64,
+    pub timestamp: DateTime<Utc>,
+    pub signature: String,
+    /// Accounts the migration transaction created or touched
+    #[serde(skip)]
+    pub accounts: Vec<Pubkey>,
+}
+
+impl MigrationTransaction {
+    pub fn touches(&self, account: &Pubkey) -> bool {
+        self.accounts.contains(account)
+    }
+}
+
+/// The executed transactions bitmap of the migration. Bit `index` is set once
+/// the migration transaction with that index has been executed.
+#[derive(Debug, Clone)]
+pub struct ExecutedTransactions(Vec<u8>);
+
+impl ExecutedTransactions {
+    pub fn is_executed(&self, index: u32) -> bool {
+        // The first byte of the bitmap account is reserved
+        let mask = 1 << (7 - (index % 8));
+        self.0
+            .get(1 + index as usize / 8)
+            .is_some_and(|byte| byte & mask != 0)
+    }
+}
+
+/// Get the executed transactions bitmap of the migration
+pub async fn executed_transactions<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+) -> Result<ExecutedTransactions, Error> {
+    let lazy_transactions = client
+        .anchor_account::<lazy_transactions::LazyTransactionsV0>(&lazy_transactions_key())
+        .await?;
+    let account = client
+        .as_ref()
+        .get_account(&lazy_transactions.executed_transactions)
+        .await?;
+    Ok(ExecutedTransactions(account.data))
+}
+
+/// Get the migration transactions that were executed for the given wallet,
+/// oldest first.
+///
+/// Migration transactions are found in the transaction history of the wallet
+/// and only returned when the migration state marks them as executed.
+pub async fn for_wallet<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    wallet: &Pubkey,
+) -> Result<Vec<MigrationTransaction>, Error> {
+    let executed = executed_transactions(client).await?;
+    let signatures = signatures_for_address(client, wallet).await?;
+    let mut transactions: Vec<MigrationTransaction> = stream::iter(signatures)
+        .map(|signature| get_transaction(client, signature))
+        .buffered(5)
+        .try_filter_map(|txn| async move {
+            MigrationTransaction::from_transaction(txn).map_err(Error::from)
+        })
+        .try_collect::<Vec<Vec<MigrationTransaction>>>()
+        .await?
+        .into_iter()
+        .flatten()
+        .filter(|txn| executed.is_executed(txn.index))
+        .collect();
+    transactions.sort_by_key(|txn| txn.block);
+    Ok(transactions)
+}
+
+impl MigrationTransaction {
+    fn from_transaction(
+        txn: EncodedConfirmedTransactionWithStatusMeta,
+    ) -> Result<Option<Vec<Self>>, DecodeError> {
+        if let Some(meta) = &txn.transaction.meta {
+            if meta.err.is_some() {
+                return Ok(None);
+            }
+        }
+        let EncodedTransaction::Json(ui_txn) = txn.transaction.transaction else {
+            return Err(DecodeError::other("not a json encoded transaction"));
+        };
+        let UiMessage::Parsed(ui_msg) = ui_txn.message else {
+            return Err(DecodeError::other("not a parsed transaction message"));
+        };
+        let mut executions = vec![];
+        for ixn in ui_msg.instructions {
+            let UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(decoded)) = ixn else {
+                continue;
+            };
+            if decoded.program_id != lazy_transactions::id().to_string() {
+                continue;
+            }
+            let data = solana_sdk::bs58::decode(&decoded.data).into_vec()?;
+            if data.len() < 8 || data[..8] != ExecuteTransactionV0::DISCRIMINATOR[..] {
+                continue;
+            }
+            let args = ExecuteTransactionArgsV0::deserialize(&mut &data[8..])?;
+            let accounts = decoded
+                .accounts
+                .iter()
+                .map(|account| Pubkey::from_str(account))
+                .collect::<Result<Vec<_>, _>>()?;
+            executions.push((args.index, accounts));
+        }
+        if executions.is_empty() {
+            return Ok(None);
+        }
+        let Some(timestamp) = txn
+            .block_time
+            .and_then(|block_time| DateTime::from_timestamp(block_time, 0))
+        else {
+            return Err(DecodeError::other("no valid block time found"));
+        };
+        Ok(Some(
+            executions
+                .into_iter()
+                .map(|(index, accounts)| Self {
+                    index,
+                    block: txn.slot,
+                    timestamp,
+                    signature: ui_txn.signatures[0].clone(),
+                    accounts,
+                })
+                .collect(),
+        ))
+    }
+}
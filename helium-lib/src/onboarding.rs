@@ -1,10 +1,23 @@
-use crate::{hotspot::HotspotInfoUpdate, keypair};
+use crate::{
+    client::{GetAnchorAccount, RetryPolicy, SolanaRpcClient},
+    dao::SubDao,
+    error::Error,
+    helium_entity_manager,
+    hotspot::{info, HotspotInfoUpdate, HotspotMode, MobileDeviceType},
+    keypair, kta,
+    token::{self, Token, TokenAmount},
+};
 use futures::TryFutureExt;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::marker::Send;
 
+/// The number of times a request is retried on transient errors by default
+pub const DEFAULT_RETRIES: u32 = 2;
+
 pub struct Client {
     base_url: String,
+    api_key: Option<String>,
+    retries: u32,
     inner: reqwest::Client,
 }
 
@@ -12,21 +25,70 @@ impl Client {
     pub fn new(base_url: &str) -> Self {
         Self {
             base_url: base_url.to_string(),
+            api_key: None,
+            retries: DEFAULT_RETRIES,
             inner: reqwest::Client::new(),
         }
     }
 
-    pub async fn get<T>(&self, path: &str) -> Result<T, OnboardingError>
+    /// Authenticate requests with the given maker API key. Maker operations
+    /// like creating Hotspots require one.
+    pub fn with_api_key(mut self, api_key: &str) -> Self {
+        self.api_key = Some(api_key.to_string());
+        self
+    }
+
+    /// Set the number of times a request is retried on connection errors,
+    /// timeouts and server errors
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    async fn request<T, P>(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        params: Option<&P>,
+    ) -> Result<T, OnboardingError>
     where
         T: 'static + DeserializeOwned + Send,
+        P: Serialize + ?Sized,
     {
         let url = format!("{}{}", &self.base_url, path);
-        let resp = self.inner.get(&url).send().await?;
-        let onboarding_resp = resp.json::<OnboardingResponse<T>>().await?;
-        if !onboarding_resp.success {
-            return Err(OnboardingError::from(onboarding_resp));
+        let send = || async {
+            let mut request = self.inner.request(method.clone(), &url);
+            if let Some(api_key) = &self.api_key {
+                request = request.header(reqwest::header::AUTHORIZATION, api_key);
+            }
+            if let Some(params) = params {
+                request = request.json(params);
+            }
+            let resp = request.send().await?;
+            let onboarding_resp = resp.json::<OnboardingResponse<T>>().await?;
+            if !onboarding_resp.success {
+                return Err(OnboardingError::from(onboarding_resp));
+            }
+            onboarding_resp.data.ok_or_else(|| OnboardingError::NoData)
+        };
+        let mut attempt = 0;
+        loop {
+            match send().await {
+                Err(err) if err.is_retryable() && attempt < self.retries => {
+                    attempt += 1;
+                    crate::time::sleep(RetryPolicy::default().backoff(attempt)).await;
+                }
+                result => return result,
+            }
         }
-        onboarding_resp.data.ok_or_else(|| OnboardingError::NoData)
+    }
+
+    pub async fn get<T>(&self, path: &str) -> Result<T, OnboardingError>
+    where
+        T: 'static + DeserializeOwned + Send,
+    {
+        self.request::<T, ()>(reqwest::Method::GET, path, None)
+            .await
     }
 
     pub async fn post<T, P>(&self, path: &str, params: &P) -> Result<T, OnboardingError>
@@ -34,13 +96,8 @@ impl Client {
         T: 'static + DeserializeOwned + Send,
         P: Serialize + ?Sized,
     {
-        let url = format!("{}{}", &self.base_url, path);
-        let resp = self.inner.post(&url).json(&params).send().await?;
-        let onboarding_resp = resp.json::<OnboardingResponse<T>>().await?;
-        if !onboarding_resp.success {
-            return Err(OnboardingError::from(onboarding_resp));
-        }
-        onboarding_resp.data.ok_or_else(|| OnboardingError::NoData)
+        self.request(reqwest::Method::POST, path, Some(params))
+            .await
     }
 
     pub async fn get_hotspot(
@@ -89,6 +146,73 @@ impl Client {
         })
         .await
     }
+
+    /// Register a Hotspot key with the onboarding server so it can be issued
+    /// and onboarded by its maker. Requires a maker API key.
+    pub async fn create_hotspot(&self, params: &CreateHotspot) -> Result<Hotspot, OnboardingError> {
+        if self.api_key.is_none() {
+            return Err(OnboardingError::Unauthorized(
+                "maker api key required".to_string(),
+            ));
+        }
+        self.post::<Hotspot, _>("/hotspots", params).await
+    }
+
+    /// Get the maker paid transaction that onboards a Hotspot to a subdao
+    /// with the given location info.
+    pub async fn get_onboard_txn(
+        &self,
+        hotspot: &helium_crypto::PublicKey,
+        update: HotspotInfoUpdate,
+    ) -> Result<solana_sdk::transaction::Transaction, OnboardingError> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct OnboardParams {
+            entity_key: helium_crypto::PublicKey,
+            location: Option<String>,
+            gain: Option<i32>,
+            elevation: Option<i32>,
+        }
+
+        let params = OnboardParams {
+            entity_key: hotspot.clone(),
+            location: update
+                .location()
+                .map(|location| u64::from(location).to_string()),
+            gain: update.gain_i32(),
+            elevation: update.elevation().to_owned(),
+        };
+        self.post::<OnboardingResponseTransactions, _>(
+            &format!("/transactions/{}/onboard", update.subdao()),
+            &params,
+        )
+        .and_then(|resp_data| async move {
+            let txn = resp_data
+                .solana_transactions
+                .first()
+                .ok_or(OnboardingError::NoData)?;
+            bincode::deserialize::<solana_sdk::transaction::Transaction>(&txn.data)
+                .map_err(|_| OnboardingError::InvalidData)
+        })
+        .await
+    }
+}
+
+/// The parameters to register a Hotspot with the onboarding server
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateHotspot {
+    pub onboarding_key: helium_crypto::PublicKey,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mac_wlan0: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mac_eth0: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rpi_serial: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub helium_serial: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batch: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -115,17 +239,34 @@ pub enum OnboardingError {
     Client(#[from] reqwest::Error),
     #[error("onboarding response: {code} {reason}")]
     Server { code: u32, reason: String },
+    #[error("not found")]
+    NotFound,
+    #[error("unauthorized: {0}")]
+    Unauthorized(String),
     #[error("no data in response")]
     NoData,
     #[error("invalid data in response")]
     InvalidData,
 }
 
+impl OnboardingError {
+    /// Whether the request may succeed when retried
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Client(err) => err.is_connect() || err.is_timeout(),
+            Self::Server { code, .. } => *code >= 500,
+            _ => false,
+        }
+    }
+}
+
 impl<T> From<OnboardingResponse<T>> for OnboardingError {
     fn from(value: OnboardingResponse<T>) -> Self {
-        Self::Server {
-            code: value.code,
-            reason: value.error_message.unwrap_or("unknown".to_string()),
+        let reason = value.error_message.unwrap_or("unknown".to_string());
+        match value.code {
+            401 | 403 => Self::Unauthorized(reason),
+            404 => Self::NotFound,
+            code => Self::Server { code, reason },
         }
     }
 }
@@ -149,3 +290,108 @@ struct OnboardingResponseTransactions {
 struct OnboardingResponseTransaction {
     data: Vec<u8>,
 }
+
+/// What onboarding a Hotspot to a subdao costs its maker
+#[derive(Debug, Serialize)]
+pub struct OnboardQuote {
+    pub subdao: SubDao,
+    pub mode: HotspotMode,
+    pub maker: Maker,
+    pub onboard_fee: TokenAmount,
+    pub location_fee: TokenAmount,
+    /// The DC balance of the maker's issuing authority the fees are paid from
+    pub maker_balance: TokenAmount,
+    /// Whether the maker's balance covers the onboarding and location fees
+    pub covered: bool,
+}
+
+/// Quote the maker paid onboarding of a registered Hotspot to a subdao.
+pub async fn quote<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    onboarding_client: &Client,
+    hotspot: &helium_crypto::PublicKey,
+    subdao: SubDao,
+    mode: HotspotMode,
+    device_type: MobileDeviceType,
+) -> Result<OnboardQuote, Error> {
+    let maker = onboarding_client.get_hotspot(hotspot).await?.maker;
+    let fees = info::fees(client, subdao, mode, device_type).await?;
+    let maker_account = client
+        .anchor_account::<helium_entity_manager::MakerV0>(&maker.address)
+        .await?;
+    let maker_balance = token::balance_for_address(
+        client,
+        &Token::Dc.associated_token_adress(&maker_account.issuing_authority),
+    )
+    .await?
+    .map(|balance| balance.amount)
+    .unwrap_or_else(|| Token::Dc.amount(0));
+    Ok(OnboardQuote {
+        subdao,
+        mode,
+        covered: maker_balance.amount >= fees.onboard.amount + fees.location.amount,
+        maker,
+        onboard_fee: fees.onboard,
+        location_fee: fees.location,
+        maker_balance,
+    })
+}
+
+/// The onboarding status of a Hotspot key
+#[derive(Debug, Serialize)]
+pub struct KeyStatus {
+    /// Whether the key is registered with the onboarding server
+    pub registered: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maker: Option<Maker>,
+    /// Whether the Hotspot has been issued on chain
+    pub issued: bool,
+    /// The subdaos the Hotspot has been onboarded to
+    pub onboarded: Vec<SubDao>,
+}
+
+/// Get the onboarding status of a Hotspot key from the onboarding server and
+/// its on-chain accounts.
+pub async fn key_status<C: GetAnchorAccount>(
+    client: &C,
+    onboarding_client: &Client,
+    hotspot: &helium_crypto::PublicKey,
+) -> Result<KeyStatus, Error> {
+    let maker = match onboarding_client.get_hotspot(hotspot).await {
+        Ok(onboarding_hotspot) => Some(onboarding_hotspot.maker),
+        Err(OnboardingError::NotFound) => None,
+        Err(err) => return Err(err.into()),
+    };
+    let issued = Error::not_found_as_none(kta::for_entity_key(hotspot).await)?.is_some();
+    let mut onboarded = vec![];
+    if issued {
+        if Error::not_found_as_none(
+            client
+                .anchor_account::<helium_entity_manager::IotHotspotInfoV0>(
+                    &SubDao::Iot.info_key(hotspot),
+                )
+                .await,
+        )?
+        .is_some()
+        {
+            onboarded.push(SubDao::Iot);
+        }
+        if Error::not_found_as_none(
+            client
+                .anchor_account::<helium_entity_manager::MobileHotspotInfoV0>(
+                    &SubDao::Mobile.info_key(hotspot),
+                )
+                .await,
+        )?
+        .is_some()
+        {
+            onboarded.push(SubDao::Mobile);
+        }
+    }
+    Ok(KeyStatus {
+        registered: maker.is_some(),
+        maker,
+        issued,
+        onboarded,
+    })
+}
@@ -0,0 +1,661 @@
+//! Organizations (OUIs), net ids and devaddr constraints managed by the IoT
+//! routing manager program.
+use crate::{
+    anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas},
+    anchor_spl,
+    client::{self, GetAnchorAccount, SolanaRpcClient},
+    dao::{Dao, SubDao},
+    error::{EncodeError, Error},
+    helium_entity_manager, iot_routing_manager,
+    keypair::{serde_pubkey, Keypair, Pubkey},
+    message, priority_fee,
+    programs::{
+        SPL_ACCOUNT_COMPRESSION_PROGRAM_ID, SPL_NOOP_PROGRAM_ID, TOKEN_METADATA_PROGRAM_ID,
+    },
+    solana_sdk::{instruction::Instruction, signer::Signer, transaction::VersionedTransaction},
+    token::Token,
+    tx, TransactionOpts,
+};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use iot_routing_manager::{DevaddrConstraintV0, IotRoutingManagerV0, NetIdV0, OrganizationV0};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The size of the merkle proof for the shared tree organizations are issued
+/// into
+const SHARED_MERKLE_PROOF_SIZE: u8 = 3;
+
+/// Devaddrs are handed out to organizations in blocks of this many addresses
+pub const DEVADDR_BLOCK_SIZE: u64 = 8;
+
+pub fn routing_manager_key() -> Pubkey {
+    let (key, _) = Pubkey::find_program_address(
+        &[b"routing_manager", SubDao::Iot.key().as_ref()],
+        &iot_routing_manager::id(),
+    );
+    key
+}
+
+pub fn net_id_key(net_id: u32) -> Pubkey {
+    let (key, _) = Pubkey::find_program_address(
+        &[
+            b"net_id",
+            routing_manager_key().as_ref(),
+            &net_id.to_le_bytes(),
+        ],
+        &iot_routing_manager::id(),
+    );
+    key
+}
+
+pub fn organization_key(oui: u64) -> Pubkey {
+    let (key, _) = Pubkey::find_program_address(
+        &[
+            b"organization",
+            routing_manager_key().as_ref(),
+            &oui.to_le_bytes(),
+        ],
+        &iot_routing_manager::id(),
+    );
+    key
+}
+
+pub fn devaddr_constraint_key(organization_key: &Pubkey, start_addr: u64) -> Pubkey {
+    let (key, _) = Pubkey::find_program_address(
+        &[
+            b"devaddr_constraint",
+            organization_key.as_ref(),
+            &start_addr.to_le_bytes(),
+        ],
+        &iot_routing_manager::id(),
+    );
+    key
+}
+
+fn program_approval_key() -> Pubkey {
+    let (key, _) = Pubkey::find_program_address(
+        &[
+            b"program_approval",
+            Dao::Hnt.key().as_ref(),
+            iot_routing_manager::id().as_ref(),
+        ],
+        &helium_entity_manager::id(),
+    );
+    key
+}
+
+fn shared_merkle_key() -> Pubkey {
+    let (key, _) = Pubkey::find_program_address(
+        &[b"shared_merkle", &[SHARED_MERKLE_PROOF_SIZE]],
+        &helium_entity_manager::id(),
+    );
+    key
+}
+
+/// The entity key an organization is issued under
+pub fn entity_key(oui: u64) -> String {
+    format!("OUI_{oui}")
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DevaddrConstraint {
+    #[serde(with = "serde_pubkey")]
+    pub address: Pubkey,
+    pub start_addr: u64,
+    pub end_addr: u64,
+}
+
+impl DevaddrConstraint {
+    fn from_account(address: Pubkey, account: DevaddrConstraintV0) -> Self {
+        Self {
+            address,
+            start_addr: account.start_addr,
+            end_addr: account.end_addr,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Organization {
+    #[serde(with = "serde_pubkey")]
+    pub address: Pubkey,
+    pub oui: u64,
+    pub net_id: u32,
+    #[serde(with = "serde_pubkey")]
+    pub authority: Pubkey,
+    pub escrow_key: String,
+    pub approved: bool,
+    pub devaddr_constraints: Vec<DevaddrConstraint>,
+}
+
+/// Get the devaddr constraints of the organization at the given address
+pub async fn devaddr_constraints<C: AsRef<SolanaRpcClient>>(
+    client: &C,
+    organization_key: &Pubkey,
+) -> Result<Vec<DevaddrConstraint>, Error> {
+    // The organization follows the discriminator, routing manager and net id
+    const ORGANIZATION_OFFSET: usize = 8 + 32 + 32;
+    let mut constraints: Vec<DevaddrConstraint> =
+        client::anchor_program_accounts::<_, DevaddrConstraintV0>(
+            client,
+            &iot_routing_manager::id(),
+            ORGANIZATION_OFFSET,
+            organization_key,
+        )
+        .await?
+        .into_iter()
+        .map(|(address, account)| DevaddrConstraint::from_account(address, account))
+        .collect();
+    constraints.sort_by_key(|constraint| constraint.start_addr);
+    Ok(constraints)
+}
+
+async fn organization<C: AsRef<SolanaRpcClient>>(
+    client: &C,
+    address: Pubkey,
+    account: OrganizationV0,
+    net_id: u32,
+) -> Result<Organization, Error> {
+    Ok(Organization {
+        address,
+        oui: account.oui,
+        net_id,
+        authority: account.authority,
+        escrow_key: account.escrow_key,
+        approved: account.approved,
+        devaddr_constraints: devaddr_constraints(client, &address).await?,
+    })
+}
+
+/// Get the organization with the given OUI
+pub async fn get<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    oui: u64,
+) -> Result<Organization, Error> {
+    let address = organization_key(oui);
+    let account = client.anchor_account::<OrganizationV0>(&address).await?;
+    let net_id = client.anchor_account::<NetIdV0>(&account.net_id).await?;
+    organization(client, address, account, net_id.id).await
+}
+
+/// Get all organizations with the given authority, ordered by OUI
+pub async fn for_authority<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    authority: &Pubkey,
+) -> Result<Vec<Organization>, Error> {
+    const AUTHORITY_OFFSET: usize = 8 + 32 + 32;
+    let accounts = client::anchor_program_accounts::<_, OrganizationV0>(
+        client,
+        &iot_routing_manager::id(),
+        AUTHORITY_OFFSET,
+        authority,
+    )
+    .await?;
+    let net_id_keys: Vec<Pubkey> = accounts
+        .iter()
+        .map(|(_, account)| account.net_id)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let net_ids: BTreeMap<Pubkey, u32> = net_id_keys
+        .iter()
+        .zip(client.anchor_accounts::<NetIdV0>(&net_id_keys).await?)
+        .map(|(key, net_id)| {
+            net_id
+                .map(|net_id| (*key, net_id.id))
+                .ok_or_else(Error::account_not_found)
+        })
+        .collect::<Result<_, Error>>()?;
+    let mut organizations: Vec<Organization> = stream::iter(accounts)
+        .map(|(address, account)| {
+            let net_id = net_ids[&account.net_id];
+            organization(client, address, account, net_id)
+        })
+        .buffered(5)
+        .try_collect()
+        .await?;
+    organizations.sort_by_key(|organization| organization.oui);
+    Ok(organizations)
+}
+
+pub const CREATE_INSTRUCTION_BUDGET: u32 = 500_000;
+
+/// Construct a message that creates a new organization in the given net id.
+///
+/// The organization is assigned the next free OUI of the routing manager and
+/// the OUI fee is burned from the payer's IOT. Returns the message, its
+/// block height and the assigned OUI.
+pub async fn create_message<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    net_id: u32,
+    authority: &Pubkey,
+    payer: &Pubkey,
+    opts: &TransactionOpts,
+) -> Result<(message::VersionedMessage, u64, u64), Error> {
+    let routing_manager_key = routing_manager_key();
+    let routing_manager = client
+        .anchor_account::<IotRoutingManagerV0>(&routing_manager_key)
+        .await?;
+    let shared_merkle = client
+        .anchor_account::<helium_entity_manager::SharedMerkleV0>(&shared_merkle_key())
+        .await?;
+    let oui = routing_manager.next_oui_id;
+    let organization = organization_key(oui);
+    let dao = Dao::Hnt;
+
+    let accounts = iot_routing_manager::accounts::InitializeOrganizationV0 {
+        payer: *payer,
+        program_approval: program_approval_key(),
+        routing_manager: routing_manager_key,
+        net_id: net_id_key(net_id),
+        iot_mint: routing_manager.iot_mint,
+        payer_iot_account: Token::Iot.associated_token_adress(payer),
+        iot_price_oracle: routing_manager.iot_price_oracle,
+        authority: *authority,
+        bubblegum_signer: dao.bubblegum_signer(),
+        shared_merkle: shared_merkle_key(),
+        helium_entity_manager_program: helium_entity_manager::id(),
+        dao: dao.key(),
+        sub_dao: SubDao::Iot.key(),
+        key_to_asset: dao.entity_key_to_kta_key(&entity_key(oui)),
+        tree_authority: dao.merkle_tree_authority(&shared_merkle.merkle_tree),
+        recipient: *authority,
+        organization,
+        collection: routing_manager.collection,
+        collection_metadata: dao.collection_metadata_key(&routing_manager.collection),
+        collection_master_edition: dao.collection_master_edition_key(&routing_manager.collection),
+        entity_creator: dao.entity_creator_key(),
+        merkle_tree: shared_merkle.merkle_tree,
+        token_metadata_program: TOKEN_METADATA_PROGRAM_ID,
+        log_wrapper: SPL_NOOP_PROGRAM_ID,
+        bubblegum_program: mpl_bubblegum::ID,
+        compression_program: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+        token_program: anchor_spl::token::ID,
+        system_program: solana_sdk::system_program::ID,
+    };
+    let ix = Instruction {
+        program_id: iot_routing_manager::id(),
+        accounts: accounts.to_account_metas(None),
+        data: iot_routing_manager::instruction::InitializeOrganizationV0 {}.data(),
+    };
+    let ixs = &[
+        priority_fee::compute_budget_instruction(CREATE_INSTRUCTION_BUDGET),
+        priority_fee::compute_price_instruction_for_accounts(client, &ix.accounts, opts).await?,
+        ix,
+    ];
+    let (msg, block_height) = message::mk_message(client, ixs, opts, payer).await?;
+    Ok((msg, block_height, oui))
+}
+
+/// Create a new organization in the given net id. Returns the signed
+/// transaction, its block height and the assigned OUI.
+pub async fn create<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    net_id: u32,
+    authority: &Pubkey,
+    keypair: &Keypair,
+    opts: &TransactionOpts,
+) -> Result<(VersionedTransaction, u64, u64), Error> {
+    let (msg, block_height, oui) =
+        create_message(client, net_id, authority, &keypair.pubkey(), opts).await?;
+    let txn = tx::partial_sign(msg, &[keypair])?;
+    Ok((txn, block_height, oui))
+}
+
+/// An update to an existing organization
+#[derive(Debug, Clone, Default)]
+pub struct OrganizationUpdate {
+    new_authority: Option<Pubkey>,
+    devaddrs: Option<u64>,
+}
+
+impl OrganizationUpdate {
+    /// Transfer the organization to a new authority
+    pub fn set_authority(mut self, authority: Pubkey) -> Self {
+        self.new_authority = Some(authority);
+        self
+    }
+
+    /// Add a devaddr constraint with the given number of devaddrs from the
+    /// organization's net id. The number of devaddrs must be a multiple of
+    /// [`DEVADDR_BLOCK_SIZE`].
+    pub fn add_devaddrs(mut self, count: u64) -> Self {
+        self.devaddrs = Some(count);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.new_authority.is_none() && self.devaddrs.is_none()
+    }
+}
+
+pub const UPDATE_INSTRUCTION_BUDGET: u32 = 100_000;
+
+/// Construct a message that applies the given update to the organization with
+/// the given OUI. Devaddr constraints are added before any authority change so
+/// both can be signed for by the current authority.
+pub async fn update_message<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    oui: u64,
+    update: OrganizationUpdate,
+    authority: &Pubkey,
+    opts: &TransactionOpts,
+) -> Result<(message::VersionedMessage, u64), Error> {
+    let organization_key = organization_key(oui);
+    let organization = client
+        .anchor_account::<OrganizationV0>(&organization_key)
+        .await?;
+    let mut ixs = vec![];
+    if let Some(devaddrs) = update.devaddrs {
+        if devaddrs == 0 || devaddrs % DEVADDR_BLOCK_SIZE != 0 {
+            return Err(EncodeError::other(format!(
+                "devaddrs must be added in multiples of {DEVADDR_BLOCK_SIZE}"
+            ))
+            .into());
+        }
+        let num_blocks = u32::try_from(devaddrs / DEVADDR_BLOCK_SIZE)
+            .map_err(|_| EncodeError::other("too many devaddrs"))?;
+        let (routing_manager, net_id) = match client
+            .as_ref()
+            .get_multiple_accounts(&[organization.routing_manager, organization.net_id])
+            .await?
+            .as_slice()
+        {
+            [Some(routing_manager), Some(net_id)] => (
+                IotRoutingManagerV0::try_deserialize(&mut routing_manager.data.as_slice())?,
+                NetIdV0::try_deserialize(&mut net_id.data.as_slice())?,
+            ),
+            _ => return Err(Error::account_not_found()),
+        };
+        let accounts = iot_routing_manager::accounts::InitializeDevaddrConstraintV0 {
+            payer: *authority,
+            authority: *authority,
+            net_id: organization.net_id,
+            routing_manager: organization.routing_manager,
+            organization: organization_key,
+            iot_mint: routing_manager.iot_mint,
+            payer_iot_account: Token::Iot.associated_token_adress(authority),
+            iot_price_oracle: routing_manager.iot_price_oracle,
+            devaddr_constraint: devaddr_constraint_key(
+                &organization_key,
+                net_id.current_addr_offset,
+            ),
+            token_program: anchor_spl::token::ID,
+            system_program: solana_sdk::system_program::ID,
+        };
+        ixs.push(Instruction {
+            program_id: iot_routing_manager::id(),
+            accounts: accounts.to_account_metas(None),
+            data: iot_routing_manager::instruction::InitializeDevaddrConstraintV0 {
+                _args: iot_routing_manager::InitializeDevaddrConstraintArgsV0 { num_blocks },
+            }
+            .data(),
+        });
+    }
+    if let Some(new_authority) = update.new_authority {
+        let accounts = iot_routing_manager::accounts::UpdateOrganizationV0 {
+            authority: *authority,
+            organization: organization_key,
+        };
+        ixs.push(Instruction {
+            program_id: iot_routing_manager::id(),
+            accounts: accounts.to_account_metas(None),
+            data: iot_routing_manager::instruction::UpdateOrganizationV0 {
+                _args: iot_routing_manager::UpdateOrganizationArgsV0 {
+                    new_authority: Some(new_authority),
+                },
+            }
+            .data(),
+        });
+    }
+    if ixs.is_empty() {
+        return Err(EncodeError::other("empty organization update").into());
+    }
+
+    let accounts: Vec<_> = ixs.iter().flat_map(|ix| ix.accounts.clone()).collect();
+    let mut budget_ixs = vec![
+        priority_fee::compute_budget_instruction(UPDATE_INSTRUCTION_BUDGET * ixs.len() as u32),
+        priority_fee::compute_price_instruction_for_accounts(client, &accounts, opts).await?,
+    ];
+    budget_ixs.extend(ixs);
+    message::mk_message(client, &budget_ixs, opts, authority).await
+}
+
+pub async fn update<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    oui: u64,
+    update: OrganizationUpdate,
+    keypair: &Keypair,
+    opts: &TransactionOpts,
+) -> Result<(VersionedTransaction, u64), Error> {
+    let (msg, block_height) = update_message(client, oui, update, &keypair.pubkey(), opts).await?;
+    let txn = tx::partial_sign(msg, &[keypair])?;
+    Ok((txn, block_height))
+}
+
+mod serde_hex {
+    use serde::de::{self, Deserialize};
+
+    pub fn serialize<S, T>(value: &T, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+        T: Into<u64> + Copy,
+    {
+        let width = std::mem::size_of::<T>() * 2;
+        serializer.serialize_str(&format!("{:0width$x}", (*value).into()))
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> std::result::Result<T, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+        T: TryFrom<u64>,
+    {
+        let str = String::deserialize(deserializer)?;
+        let value = u64::from_str_radix(str.trim_start_matches("0x"), 16)
+            .map_err(|_| de::Error::custom(format!("invalid hex value: {str}")))?;
+        T::try_from(value).map_err(|_| de::Error::custom(format!("hex value out of range: {str}")))
+    }
+}
+
+/// An app and device EUI pair routed to a route
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct EuiPair {
+    #[serde(with = "serde_hex")]
+    pub app_eui: u64,
+    #[serde(with = "serde_hex")]
+    pub dev_eui: u64,
+}
+
+fn default_max_copies() -> u32 {
+    1
+}
+
+/// A session key filter letting packets for a devaddr and session key through
+/// to a route
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionKeyFilter {
+    #[serde(with = "serde_hex")]
+    pub devaddr: u32,
+    pub session_key: String,
+    #[serde(default = "default_max_copies")]
+    pub max_copies: u32,
+}
+
+/// Entries to add to and remove from a list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Changes<T> {
+    #[serde(default)]
+    pub add: Vec<T>,
+    #[serde(default)]
+    pub remove: Vec<T>,
+}
+
+impl<T> Default for Changes<T> {
+    fn default() -> Self {
+        Self {
+            add: vec![],
+            remove: vec![],
+        }
+    }
+}
+
+impl<T> Changes<T> {
+    pub fn is_empty(&self) -> bool {
+        self.add.is_empty() && self.remove.is_empty()
+    }
+}
+
+/// Changes to the EUIs and session key filters of a route, usually read
+/// from a JSON file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RoutingUpdate {
+    #[serde(default)]
+    pub euis: Changes<EuiPair>,
+    #[serde(default)]
+    pub skfs: Changes<SessionKeyFilter>,
+}
+
+impl RoutingUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.euis.is_empty() && self.skfs.is_empty()
+    }
+
+    /// Check that all added session key filters are for devaddrs in one of
+    /// the given devaddr constraints of the route's organization.
+    pub fn check_devaddrs(&self, constraints: &[DevaddrConstraint]) -> Result<(), Error> {
+        match self.skfs.add.iter().find(|skf| {
+            !constraints.iter().any(|constraint| {
+                (constraint.start_addr..=constraint.end_addr).contains(&u64::from(skf.devaddr))
+            })
+        }) {
+            Some(skf) => Err(EncodeError::other(format!(
+                "devaddr {:08x} is outside the organization's devaddr constraints",
+                skf.devaddr
+            ))
+            .into()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The EUIs and session key filters of a route
+#[derive(Debug, Clone, Serialize)]
+pub struct RoutingConfig {
+    pub route_id: String,
+    pub euis: Vec<EuiPair>,
+    pub skfs: Vec<SessionKeyFilter>,
+}
+
+impl RoutingConfig {
+    /// Get the config resulting from applying the given update. Removals are
+    /// applied before additions, and session key filters are matched on
+    /// devaddr and session key, so an update can change the max copies of an
+    /// existing filter by adding it again.
+    pub fn apply(&self, update: &RoutingUpdate) -> Self {
+        let mut euis: BTreeSet<EuiPair> = self.euis.iter().copied().collect();
+        for eui in &update.euis.remove {
+            euis.remove(eui);
+        }
+        euis.extend(update.euis.add.iter().copied());
+
+        let mut skfs: BTreeMap<(u32, String), u32> = self
+            .skfs
+            .iter()
+            .map(|skf| ((skf.devaddr, skf.session_key.clone()), skf.max_copies))
+            .collect();
+        for skf in &update.skfs.remove {
+            skfs.remove(&(skf.devaddr, skf.session_key.clone()));
+        }
+        for skf in &update.skfs.add {
+            skfs.insert((skf.devaddr, skf.session_key.clone()), skf.max_copies);
+        }
+
+        Self {
+            route_id: self.route_id.clone(),
+            euis: euis.into_iter().collect(),
+            skfs: skfs
+                .into_iter()
+                .map(|((devaddr, session_key), max_copies)| SessionKeyFilter {
+                    devaddr,
+                    session_key,
+                    max_copies,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UPDATE: &str = r#"{
+        "euis": {
+            "add": [{"app_eui": "0000000000000002", "dev_eui": "00000000000000aa"}],
+            "remove": [{"app_eui": "0x1", "dev_eui": "0xaa"}]
+        },
+        "skfs": {
+            "add": [{"devaddr": "48000001", "session_key": "key-1", "max_copies": 3}]
+        }
+    }"#;
+
+    #[test]
+    fn routing_update_from_json() {
+        let update: RoutingUpdate = serde_json::from_str(UPDATE).unwrap();
+        assert_eq!(
+            vec![EuiPair {
+                app_eui: 1,
+                dev_eui: 0xaa
+            }],
+            update.euis.remove
+        );
+        assert_eq!(0x4800_0001, update.skfs.add[0].devaddr);
+        assert!(update.skfs.remove.is_empty());
+
+        let json = serde_json::to_value(&update.euis.add[0]).unwrap();
+        assert_eq!("0000000000000002", json["app_eui"]);
+    }
+
+    #[test]
+    fn routing_config_apply() {
+        let config = RoutingConfig {
+            route_id: "route".to_string(),
+            euis: vec![EuiPair {
+                app_eui: 1,
+                dev_eui: 0xaa,
+            }],
+            skfs: vec![SessionKeyFilter {
+                devaddr: 0x4800_0001,
+                session_key: "key-1".to_string(),
+                max_copies: 1,
+            }],
+        };
+        let update: RoutingUpdate = serde_json::from_str(UPDATE).unwrap();
+        let result = config.apply(&update);
+        assert_eq!(
+            vec![EuiPair {
+                app_eui: 2,
+                dev_eui: 0xaa
+            }],
+            result.euis
+        );
+        assert_eq!(1, result.skfs.len());
+        assert_eq!(3, result.skfs[0].max_copies);
+    }
+
+    #[test]
+    fn routing_update_checks_devaddrs() {
+        let update: RoutingUpdate = serde_json::from_str(UPDATE).unwrap();
+        let constraint = |start_addr, end_addr| DevaddrConstraint {
+            address: Pubkey::default(),
+            start_addr,
+            end_addr,
+        };
+        assert!(update
+            .check_devaddrs(&[constraint(0x4800_0000, 0x4800_0007)])
+            .is_ok());
+        assert!(update
+            .check_devaddrs(&[constraint(0x4800_0008, 0x4800_000f)])
+            .is_err());
+    }
+}
@@ -0,0 +1,1031 @@
+use crate::{
+    anchor_lang::{InstructionData, ToAccountMetas},
+    anchor_spl, circuit_breaker,
+    client::{GetAnchorAccount, SolanaRpc, SolanaRpcClient},
+    dao::{self, Dao, SubDao},
+    error::{DecodeError, EncodeError, Error},
+    helium_sub_daos,
+    keypair::{serde_pubkey, Keypair, Pubkey},
+    message, priority_fee,
+    programs::TOKEN_METADATA_PROGRAM_ID,
+    solana_sdk::{instruction::Instruction, signer::Signer, transaction::VersionedTransaction},
+    token::{self, Token, TokenAmount},
+    tx, voter_stake_registry, TransactionOpts,
+};
+use chrono::{DateTime, Duration, Utc};
+use futures::{stream, StreamExt, TryStreamExt};
+use itertools::Itertools;
+use rust_decimal::prelude::*;
+use serde::Serialize;
+use std::{collections::HashMap, ops::Range};
+
+pub fn position_key(mint: &Pubkey) -> Pubkey {
+    let (key, _) =
+        Pubkey::find_program_address(&[b"position", mint.as_ref()], &voter_stake_registry::id());
+    key
+}
+
+pub fn delegated_position_key(position_key: &Pubkey) -> Pubkey {
+    let (key, _) = Pubkey::find_program_address(
+        &[b"delegated_position", position_key.as_ref()],
+        &helium_sub_daos::id(),
+    );
+    key
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LockupKind {
+    None,
+    Cliff,
+    Constant,
+}
+
+impl std::fmt::Display for LockupKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            Self::None => "none",
+            Self::Cliff => "cliff",
+            Self::Constant => "constant",
+        };
+        f.write_str(str)
+    }
+}
+
+impl From<voter_stake_registry::LockupKind> for LockupKind {
+    fn from(value: voter_stake_registry::LockupKind) -> Self {
+        match value {
+            voter_stake_registry::LockupKind::None => Self::None,
+            voter_stake_registry::LockupKind::Cliff => Self::Cliff,
+            voter_stake_registry::LockupKind::Constant => Self::Constant,
+        }
+    }
+}
+
+impl From<LockupKind> for voter_stake_registry::LockupKind {
+    fn from(value: LockupKind) -> Self {
+        match value {
+            LockupKind::None => Self::None,
+            LockupKind::Cliff => Self::Cliff,
+            LockupKind::Constant => Self::Constant,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Lockup {
+    pub kind: LockupKind,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl TryFrom<&voter_stake_registry::Lockup> for Lockup {
+    type Error = Error;
+    fn try_from(value: &voter_stake_registry::Lockup) -> Result<Self, Self::Error> {
+        fn to_datetime(ts: i64) -> Result<DateTime<Utc>, Error> {
+            DateTime::from_timestamp(ts, 0)
+                .ok_or_else(|| DecodeError::other(format!("invalid lockup timestamp {ts}")).into())
+        }
+        Ok(Self {
+            kind: value.kind.into(),
+            start: to_datetime(value.start_ts)?,
+            end: to_datetime(value.end_ts)?,
+        })
+    }
+}
+
+impl Lockup {
+    /// The number of seconds that count as locked for voting power at the
+    /// given time. Constant lockups do not decay so their full duration
+    /// counts until they are unlocked.
+    pub fn locked_secs_at(&self, time: DateTime<Utc>) -> u64 {
+        let secs = match self.kind {
+            LockupKind::None => 0,
+            LockupKind::Cliff => (self.end - time).num_seconds(),
+            LockupKind::Constant => (self.end - self.start).num_seconds(),
+        };
+        secs.max(0) as u64
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Delegation {
+    pub sub_dao: SubDao,
+    pub last_claimed_epoch: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Position {
+    #[serde(with = "serde_pubkey")]
+    pub key: Pubkey,
+    #[serde(with = "serde_pubkey")]
+    pub mint: Pubkey,
+    #[serde(with = "serde_pubkey")]
+    pub registrar: Pubkey,
+    pub amount: TokenAmount,
+    pub lockup: Lockup,
+    /// End of the boosted voting power of positions created during the
+    /// genesis period
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub genesis_end: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delegation: Option<Delegation>,
+}
+
+impl Position {
+    pub fn is_delegated(&self) -> bool {
+        self.delegation.is_some()
+    }
+
+    /// The epochs a delegated position can claim rewards for, which are the
+    /// completed epochs since the last claimed epoch. Delegation rewards stop
+    /// accruing once the lockup has expired.
+    pub fn unclaimed_epochs(&self, now: DateTime<Utc>) -> Range<u64> {
+        let Some(delegation) = &self.delegation else {
+            return 0..0;
+        };
+        let end = dao::epoch_for(now).min(dao::epoch_for(self.lockup.end) + 1);
+        (delegation.last_claimed_epoch + 1)..end.max(delegation.last_claimed_epoch + 1)
+    }
+}
+
+/// Get all voter stake registry positions held by the given owner, including
+/// whether (and to which subdao) they are delegated.
+pub async fn for_owner<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    owner: &Pubkey,
+) -> Result<Vec<Position>, Error> {
+    // Position NFTs are regular (uncompressed) NFTs so a token account scan is
+    // sufficient to find candidate mints
+    let mints = token::token_accounts_for_owner(client, owner)
+        .await?
+        .into_iter()
+        .filter(|(_, _, token_account)| token_account.amount == 1)
+        .map(|(_, _, token_account)| token_account.mint)
+        .collect_vec();
+    let position_keys = mints.iter().map(position_key).collect_vec();
+    let positions: Vec<(Pubkey, Pubkey, voter_stake_registry::PositionV0)> = mints
+        .into_iter()
+        .zip(position_keys.iter().copied())
+        .zip(
+            client
+                .anchor_accounts::<voter_stake_registry::PositionV0>(&position_keys)
+                .await?,
+        )
+        .filter_map(|((mint, key), position)| position.map(|position| (mint, key, position)))
+        .collect();
+    if positions.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let registrar_keys = positions
+        .iter()
+        .map(|(_, _, position)| position.registrar)
+        .unique()
+        .collect_vec();
+    let registrars: HashMap<Pubkey, voter_stake_registry::RegistrarV0> = registrar_keys
+        .iter()
+        .copied()
+        .zip(
+            client
+                .anchor_accounts::<voter_stake_registry::RegistrarV0>(&registrar_keys)
+                .await?,
+        )
+        .filter_map(|(key, registrar)| registrar.map(|registrar| (key, registrar)))
+        .collect();
+
+    let delegated_keys = positions
+        .iter()
+        .map(|(_, key, _)| delegated_position_key(key))
+        .collect_vec();
+    let delegations = client
+        .anchor_accounts::<helium_sub_daos::DelegatedPositionV0>(&delegated_keys)
+        .await?;
+
+    positions
+        .into_iter()
+        .zip(delegations)
+        .map(
+            |((mint, key, position), delegated)| -> Result<Position, Error> {
+                let voting_mint = registrars
+                    .get(&position.registrar)
+                    .and_then(|registrar| {
+                        registrar
+                            .voting_mints
+                            .get(position.voting_mint_config_idx as usize)
+                    })
+                    .map(|config| config.mint)
+                    .ok_or_else(|| {
+                        DecodeError::other(format!("no voting mint for position {key}"))
+                    })?;
+                let token = Token::from_mint(voting_mint).ok_or_else(|| {
+                    DecodeError::other(format!("unsupported voting mint {voting_mint}"))
+                })?;
+                let delegation = delegated.and_then(|delegated| {
+                    SubDao::from_key(&delegated.sub_dao).map(|sub_dao| Delegation {
+                        sub_dao,
+                        last_claimed_epoch: delegated.last_claimed_epoch,
+                    })
+                });
+                Ok(Position {
+                    key,
+                    mint,
+                    registrar: position.registrar,
+                    amount: token.amount(position.amount_deposited_native),
+                    lockup: Lockup::try_from(&position.lockup)?,
+                    genesis_end: (position.genesis_end > 0)
+                        .then(|| DateTime::from_timestamp(position.genesis_end, 0))
+                        .flatten(),
+                    delegation,
+                })
+            },
+        )
+        .try_collect()
+}
+
+/// Get the positions for each of the given owners, in the order the owners are
+/// given.
+pub async fn for_owners<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    owners: &[Pubkey],
+) -> Result<Vec<Vec<Position>>, Error> {
+    stream::iter(owners)
+        .map(|owner| for_owner(client, owner))
+        .buffered(5)
+        .try_collect()
+        .await
+}
+
+/// The number of seconds in a lockup period. Helium registrars count lockup
+/// periods in days.
+pub const SECS_PER_PERIOD: i64 = 60 * 60 * 24;
+
+/// Vote weight factors of a registrar voting mint are scaled by this value
+const SCALED_FACTOR_BASE: u128 = 1_000_000_000;
+
+/// The vote weight parameters of a registrar's voting mint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VoteWeights {
+    pub baseline_factor: u64,
+    pub max_extra_lockup_factor: u64,
+    pub lockup_saturation_secs: u64,
+    pub digit_shift: i8,
+}
+
+impl From<&voter_stake_registry::VotingMintConfigV0> for VoteWeights {
+    fn from(value: &voter_stake_registry::VotingMintConfigV0) -> Self {
+        Self {
+            baseline_factor: value.baseline_vote_weight_scaled_factor,
+            max_extra_lockup_factor: value.max_extra_lockup_vote_weight_scaled_factor,
+            lockup_saturation_secs: value.lockup_saturation_secs,
+            digit_shift: value.digit_shift,
+        }
+    }
+}
+
+impl VoteWeights {
+    /// The voting power, in native veHNT units, of the given native amount
+    /// locked for the given number of seconds.
+    pub fn voting_power(&self, amount: u64, locked_secs: u64) -> u128 {
+        let amount = match self.digit_shift {
+            shift if shift >= 0 => amount as u128 * 10u128.pow(shift as u32),
+            shift => amount as u128 / 10u128.pow(shift.unsigned_abs() as u32),
+        };
+        let baseline = amount * self.baseline_factor as u128 / SCALED_FACTOR_BASE;
+        if self.lockup_saturation_secs == 0 {
+            return baseline;
+        }
+        let max_locked = amount * self.max_extra_lockup_factor as u128 / SCALED_FACTOR_BASE;
+        let locked_secs = locked_secs.min(self.lockup_saturation_secs) as u128;
+        baseline + max_locked * locked_secs / self.lockup_saturation_secs as u128
+    }
+
+    /// The decimals of veHNT amounts, which are shifted from the voting mint
+    /// decimals by the digit shift.
+    pub fn decimals(&self, token: Token) -> u32 {
+        (token.decimals() as i32 + self.digit_shift as i32).max(0) as u32
+    }
+}
+
+/// The expected result of creating a new position
+#[derive(Debug, Clone, Serialize)]
+pub struct NewPosition {
+    #[serde(with = "serde_pubkey")]
+    pub key: Pubkey,
+    #[serde(with = "serde_pubkey")]
+    pub mint: Pubkey,
+    pub amount: TokenAmount,
+    pub lockup: Lockup,
+    pub ve_hnt: Decimal,
+    pub multiplier: Decimal,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delegated_to: Option<SubDao>,
+}
+
+/// Parameters for a new position
+#[derive(Debug, Clone, Copy)]
+pub struct PositionArgs {
+    pub amount: TokenAmount,
+    pub kind: LockupKind,
+    pub days: u32,
+    pub delegate: Option<SubDao>,
+}
+
+impl PositionArgs {
+    fn lockup(&self, start: DateTime<Utc>) -> Lockup {
+        Lockup {
+            kind: self.kind,
+            start,
+            end: start + Duration::seconds(self.days as i64 * SECS_PER_PERIOD),
+        }
+    }
+
+    /// Estimate the position that results from these parameters, using the
+    /// vote weights of the given voting mint.
+    pub fn estimate(
+        &self,
+        mint: &Pubkey,
+        weights: &VoteWeights,
+        start: DateTime<Utc>,
+    ) -> Result<NewPosition, Error> {
+        let lockup = self.lockup(start);
+        let power = weights.voting_power(self.amount.amount, lockup.locked_secs_at(start));
+        let ve_hnt = Decimal::from_i128_with_scale(
+            i128::try_from(power).map_err(EncodeError::other)?,
+            weights.decimals(self.amount.token),
+        );
+        let amount = Decimal::from_i128_with_scale(
+            self.amount.amount as i128,
+            self.amount.token.decimals().into(),
+        );
+        let multiplier = if amount.is_zero() {
+            Decimal::ZERO
+        } else {
+            (ve_hnt / amount).round_dp(4)
+        };
+        Ok(NewPosition {
+            key: position_key(mint),
+            mint: *mint,
+            amount: self.amount,
+            lockup,
+            ve_hnt: ve_hnt.normalize(),
+            multiplier: multiplier.normalize(),
+            delegated_to: self.delegate,
+        })
+    }
+}
+
+/// Get the registrar of the HNT dao and the vote weights of its HNT voting mint
+pub async fn hnt_registrar<C: GetAnchorAccount>(
+    client: &C,
+) -> Result<(Pubkey, voter_stake_registry::RegistrarV0, VoteWeights), Error> {
+    let dao = client
+        .anchor_account::<helium_sub_daos::DaoV0>(&Dao::Hnt.key())
+        .await?;
+    let registrar = client
+        .anchor_account::<voter_stake_registry::RegistrarV0>(&dao.registrar)
+        .await?;
+    let weights = registrar
+        .voting_mints
+        .iter()
+        .find(|config| config.mint == *Token::Hnt.mint())
+        .map(VoteWeights::from)
+        .ok_or_else(|| DecodeError::other("registrar has no HNT voting mint"))?;
+    Ok((dao.registrar, registrar, weights))
+}
+
+pub const CREATE_INSTRUCTION_BUDGET: u32 = 400_000;
+
+/// Construct a message that locks HNT from the owner in a new position, and
+/// optionally delegates it to a subdao in the same transaction.
+///
+/// The position NFT is minted to the owner using the given (new) mint, which
+/// has to sign the resulting transaction along with the owner. Returns the
+/// message, its block height and the expected position.
+pub async fn create_message<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    args: PositionArgs,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    opts: &TransactionOpts,
+) -> Result<(message::VersionedMessage, u64, NewPosition), Error> {
+    if args.amount.token != Token::Hnt {
+        return Err(EncodeError::other("positions can only lock HNT").into());
+    }
+    if args.kind == LockupKind::None || args.days == 0 {
+        return Err(EncodeError::other("positions need a lockup of at least one day").into());
+    }
+    let (registrar_key, registrar, weights) = hnt_registrar(client).await?;
+    let position = args.estimate(mint, &weights, Utc::now())?;
+    let position_token_account =
+        spl_associated_token_account::get_associated_token_address(owner, mint);
+
+    let init_accounts = voter_stake_registry::accounts::InitializePositionV0 {
+        payer: *owner,
+        collection: registrar.collection,
+        collection_metadata: Dao::Hnt.collection_metadata_key(&registrar.collection),
+        collection_master_edition: Dao::Hnt.collection_master_edition_key(&registrar.collection),
+        registrar: registrar_key,
+        position: position.key,
+        mint: *mint,
+        metadata: Dao::Hnt.collection_metadata_key(mint),
+        master_edition: Dao::Hnt.collection_master_edition_key(mint),
+        position_token_account,
+        position_authority: *owner,
+        token_program: anchor_spl::token::ID,
+        associated_token_program: anchor_spl::associated_token::ID,
+        system_program: solana_sdk::system_program::ID,
+        rent: solana_sdk::sysvar::rent::ID,
+        token_metadata_program: TOKEN_METADATA_PROGRAM_ID,
+    };
+    let init_ix = Instruction {
+        program_id: voter_stake_registry::id(),
+        accounts: init_accounts.to_account_metas(None),
+        data: voter_stake_registry::instruction::InitializePositionV0 {
+            _args: voter_stake_registry::InitializePositionArgsV0 {
+                kind: args.kind.into(),
+                periods: args.days,
+            },
+        }
+        .data(),
+    };
+
+    let deposit_accounts = voter_stake_registry::accounts::DepositV0 {
+        registrar: registrar_key,
+        position: position.key,
+        vault: Token::Hnt.associated_token_adress(&position.key),
+        mint: *Token::Hnt.mint(),
+        deposit_token: Token::Hnt.associated_token_adress(owner),
+        deposit_authority: *owner,
+        payer: *owner,
+        associated_token_program: anchor_spl::associated_token::ID,
+        token_program: anchor_spl::token::ID,
+        system_program: solana_sdk::system_program::ID,
+    };
+    let deposit_ix = Instruction {
+        program_id: voter_stake_registry::id(),
+        accounts: deposit_accounts.to_account_metas(None),
+        data: voter_stake_registry::instruction::DepositV0 {
+            _args: voter_stake_registry::DepositArgsV0 {
+                amount: args.amount.amount,
+            },
+        }
+        .data(),
+    };
+
+    let mut ixs = vec![init_ix, deposit_ix];
+    if let Some(sub_dao) = args.delegate {
+        let new_position = Position {
+            key: position.key,
+            mint: *mint,
+            registrar: registrar_key,
+            amount: args.amount,
+            lockup: position.lockup.clone(),
+            genesis_end: None,
+            delegation: None,
+        };
+        ixs.push(delegate_instruction(&new_position, sub_dao, owner));
+    }
+
+    let accounts = ixs
+        .iter()
+        .flat_map(|ix| ix.accounts.iter().cloned())
+        .collect_vec();
+    let ixs = [
+        vec![
+            priority_fee::compute_budget_instruction(CREATE_INSTRUCTION_BUDGET),
+            priority_fee::compute_price_instruction_for_accounts(client, &accounts, opts).await?,
+        ],
+        ixs,
+    ]
+    .concat();
+    let (msg, block_height) = message::mk_message(client, &ixs, opts, owner).await?;
+    Ok((msg, block_height, position))
+}
+
+/// Lock HNT from the given keypair in a new position. Returns the signed
+/// transaction, its block height and the expected position.
+pub async fn create<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    args: PositionArgs,
+    keypair: &Keypair,
+    opts: &TransactionOpts,
+) -> Result<(VersionedTransaction, u64, NewPosition), Error> {
+    let mint = Keypair::generate();
+    let (msg, block_height, position) =
+        create_message(client, args, &mint.pubkey(), &keypair.pubkey(), opts).await?;
+    let txn = tx::partial_sign(msg, &[&**keypair, &*mint])?;
+    Ok((txn, block_height, position))
+}
+
+/// Delegation rewards a position can claim
+#[derive(Debug, Clone, Serialize)]
+pub struct ClaimableRewards {
+    pub sub_dao: SubDao,
+    /// Epochs with issued rewards that have not been claimed yet
+    pub epochs: Vec<u64>,
+    /// The estimated rewards for the unclaimed epochs
+    pub amount: TokenAmount,
+}
+
+/// Get the delegation rewards the given position can claim, or `None` if the
+/// position is not delegated.
+///
+/// The claimable amount is estimated from the share of the position's voting
+/// power in each subdao epoch, so it may differ slightly from what a claim
+/// pays out.
+pub async fn claimable_rewards<C: GetAnchorAccount>(
+    client: &C,
+    position: &Position,
+    weights: &VoteWeights,
+) -> Result<Option<ClaimableRewards>, Error> {
+    let Some(delegation) = &position.delegation else {
+        return Ok(None);
+    };
+    let mut claimable = ClaimableRewards {
+        sub_dao: delegation.sub_dao,
+        epochs: vec![],
+        amount: delegation.sub_dao.token().amount(0),
+    };
+    let mut amount: u128 = 0;
+    for (epoch, info) in unclaimed_epoch_infos(client, position).await? {
+        // Rewards can only be claimed once they have been issued for an epoch
+        let Some(info) = info.filter(|info| info.rewards_issued_at.is_some()) else {
+            continue;
+        };
+        claimable.epochs.push(epoch);
+        let Some(epoch_start) = dao::epoch_start(epoch) else {
+            continue;
+        };
+        if info.vehnt_at_epoch_start > 0 {
+            let power = weights.voting_power(
+                position.amount.amount,
+                position.lockup.locked_secs_at(epoch_start),
+            );
+            amount +=
+                info.delegation_rewards_issued as u128 * power / info.vehnt_at_epoch_start as u128;
+        }
+    }
+    claimable.amount.amount = u64::try_from(amount).map_err(DecodeError::other)?;
+    Ok(Some(claimable))
+}
+
+async fn unclaimed_epoch_infos<C: GetAnchorAccount>(
+    client: &C,
+    position: &Position,
+) -> Result<Vec<(u64, Option<helium_sub_daos::SubDaoEpochInfoV0>)>, Error> {
+    let sub_dao = delegated_sub_dao(position)?;
+    let epochs = position.unclaimed_epochs(Utc::now());
+    let keys = epochs
+        .clone()
+        .map(|epoch| sub_dao.epoch_info_key_for(epoch))
+        .collect_vec();
+    let infos = client
+        .anchor_accounts::<helium_sub_daos::SubDaoEpochInfoV0>(&keys)
+        .await?;
+    Ok(epochs.zip(infos).collect())
+}
+
+/// The maximum number of epochs to claim in a single transaction
+pub const MAX_CLAIM_EPOCHS_PER_TXN: usize = 5;
+
+pub const CLAIM_INSTRUCTION_BUDGET: u32 = 200_000;
+
+/// Construct the instruction that claims the delegation rewards of a position
+/// for a single epoch to the position owner.
+pub fn claim_instruction(
+    position: &Position,
+    delegator_pool: &Pubkey,
+    owner: &Pubkey,
+    epoch: u64,
+) -> Result<Instruction, Error> {
+    let sub_dao = delegated_sub_dao(position)?;
+    let accounts = helium_sub_daos::accounts::ClaimRewardsV0 {
+        position: position.key,
+        mint: position.mint,
+        position_token_account: spl_associated_token_account::get_associated_token_address(
+            owner,
+            &position.mint,
+        ),
+        position_authority: *owner,
+        registrar: position.registrar,
+        dao: Dao::Hnt.key(),
+        sub_dao: sub_dao.key(),
+        delegated_position: delegated_position_key(&position.key),
+        dnt_mint: *sub_dao.token().mint(),
+        sub_dao_epoch_info: sub_dao.epoch_info_key_for(epoch),
+        delegator_pool: *delegator_pool,
+        delegator_ata: sub_dao.token().associated_token_adress(owner),
+        delegator_pool_circuit_breaker: delegator_pool_circuit_breaker_key(delegator_pool),
+        vsr_program: voter_stake_registry::id(),
+        system_program: solana_sdk::system_program::ID,
+        circuit_breaker_program: circuit_breaker::id(),
+        associated_token_program: anchor_spl::associated_token::ID,
+        token_program: anchor_spl::token::ID,
+    };
+    Ok(Instruction {
+        program_id: helium_sub_daos::id(),
+        accounts: accounts.to_account_metas(None),
+        data: helium_sub_daos::instruction::ClaimRewardsV0 {
+            _args: helium_sub_daos::ClaimRewardsArgsV0 { epoch },
+        }
+        .data(),
+    })
+}
+
+fn delegated_sub_dao(position: &Position) -> Result<SubDao, Error> {
+    position
+        .delegation
+        .as_ref()
+        .map(|delegation| delegation.sub_dao)
+        .ok_or_else(|| {
+            EncodeError::other(format!("position {} is not delegated", position.key)).into()
+        })
+}
+
+fn delegator_pool_circuit_breaker_key(delegator_pool: &Pubkey) -> Pubkey {
+    let (key, _) = Pubkey::find_program_address(
+        &[b"account_windowed_breaker", delegator_pool.as_ref()],
+        &circuit_breaker::id(),
+    );
+    key
+}
+
+/// Construct the claim instructions for the given epochs of a delegated
+/// position
+pub async fn claim_instructions<C: GetAnchorAccount>(
+    client: &C,
+    position: &Position,
+    owner: &Pubkey,
+    epochs: &[u64],
+) -> Result<Vec<Instruction>, Error> {
+    let sub_dao = delegated_sub_dao(position)?;
+    let delegator_pool = client
+        .anchor_account::<helium_sub_daos::SubDaoV0>(&sub_dao.key())
+        .await?
+        .delegator_pool;
+    epochs
+        .iter()
+        .map(|epoch| claim_instruction(position, &delegator_pool, owner, *epoch))
+        .try_collect()
+}
+
+/// Construct a message that claims the delegation rewards of a position for
+/// the given epochs. At most [`MAX_CLAIM_EPOCHS_PER_TXN`] epochs fit in a
+/// single message.
+pub async fn claim_message<C: SolanaRpc + GetAnchorAccount>(
+    client: &C,
+    position: &Position,
+    epochs: &[u64],
+    owner: &Pubkey,
+    opts: &TransactionOpts,
+) -> Result<(message::VersionedMessage, u64), Error> {
+    if epochs.is_empty() || epochs.len() > MAX_CLAIM_EPOCHS_PER_TXN {
+        return Err(EncodeError::other(format!(
+            "can claim between 1 and {MAX_CLAIM_EPOCHS_PER_TXN} epochs at a time"
+        ))
+        .into());
+    }
+    let claim_ixs = claim_instructions(client, position, owner, epochs).await?;
+    let accounts = claim_ixs
+        .iter()
+        .flat_map(|ix| ix.accounts.iter().cloned())
+        .collect_vec();
+    let ixs = [
+        vec![
+            priority_fee::compute_budget_instruction(
+                CLAIM_INSTRUCTION_BUDGET * claim_ixs.len() as u32,
+            ),
+            priority_fee::compute_price_instruction_for_accounts(client, &accounts, opts).await?,
+        ],
+        claim_ixs,
+    ]
+    .concat();
+    message::mk_message(client, &ixs, opts, owner).await
+}
+
+/// Claim the delegation rewards of a position for the given epochs to the
+/// given keypair, which must own the position.
+pub async fn claim<C: SolanaRpc + GetAnchorAccount>(
+    client: &C,
+    position: &Position,
+    epochs: &[u64],
+    keypair: &Keypair,
+    opts: &TransactionOpts,
+) -> Result<(VersionedTransaction, u64), Error> {
+    let (msg, block_height) =
+        claim_message(client, position, epochs, &keypair.pubkey(), opts).await?;
+    let txn = tx::partial_sign(msg, &[keypair])?;
+    Ok((txn, block_height))
+}
+
+/// Claims of the delegation rewards of a position, packed into as few
+/// transactions as transaction size and compute limits allow
+pub struct PackedClaims {
+    packer: tx::TransactionPacker,
+    /// The epochs claimed by each packed transaction, oldest first
+    pub epochs: Vec<Vec<u64>>,
+}
+
+impl PackedClaims {
+    /// The number of packed transactions
+    pub fn len(&self) -> usize {
+        self.epochs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.epochs.is_empty()
+    }
+
+    /// Construct the message of the packed transaction at the given index.
+    /// Every message gets a fresh blockhash, so construct each one just
+    /// before sending it.
+    pub async fn message<C: SolanaRpc>(
+        &self,
+        client: &C,
+        index: usize,
+        opts: &TransactionOpts,
+    ) -> Result<(message::VersionedMessage, u64), Error> {
+        self.packer.message(client, index, opts).await
+    }
+
+    /// Construct the packed transaction at the given index, signed by the
+    /// given keypair, which must own the position. See
+    /// [`PackedClaims::message`].
+    pub async fn transaction<C: SolanaRpc>(
+        &self,
+        client: &C,
+        index: usize,
+        keypair: &Keypair,
+        opts: &TransactionOpts,
+    ) -> Result<(VersionedTransaction, u64), Error> {
+        let (msg, block_height) = self.message(client, index, opts).await?;
+        let txn = tx::partial_sign(msg, &[keypair])?;
+        Ok((txn, block_height))
+    }
+}
+
+/// Pack the claims of the delegation rewards of a position for the given
+/// epochs into transactions, keeping the epochs in order. Messages are only
+/// constructed when asked for, see [`PackedClaims::message`].
+pub async fn pack_claims<C: SolanaRpc + GetAnchorAccount>(
+    client: &C,
+    position: &Position,
+    epochs: &[u64],
+    owner: &Pubkey,
+    opts: &TransactionOpts,
+) -> Result<PackedClaims, Error> {
+    let claim_ixs = claim_instructions(client, position, owner, epochs).await?;
+    let mut packer = tx::TransactionPacker::for_opts(client, *owner, opts).await?;
+    let mut packed: Vec<Vec<u64>> = vec![];
+    for (epoch, ix) in epochs.iter().zip(claim_ixs) {
+        packer.push(ix, CLAIM_INSTRUCTION_BUDGET)?;
+        packed.resize_with(packer.len(), Default::default);
+        packed[packer.len() - 1].push(*epoch);
+    }
+    Ok(PackedClaims {
+        packer,
+        epochs: packed,
+    })
+}
+
+/// Get the subdao epoch info keys for the epochs a position stops counting
+/// towards a subdao's voting power: when the lockup closes and when the
+/// genesis multiplier ends.
+fn delegation_epoch_info_keys(position: &Position, sub_dao: SubDao) -> (Pubkey, Pubkey) {
+    let closing = sub_dao.epoch_info_key_for(dao::epoch_for(position.lockup.end));
+    let genesis_end = match position.genesis_end {
+        Some(genesis_end) if genesis_end > Utc::now() => {
+            sub_dao.epoch_info_key_for(dao::epoch_for(genesis_end))
+        }
+        _ => closing,
+    };
+    (closing, genesis_end)
+}
+
+/// Construct the instruction that delegates an undelegated position to a
+/// subdao
+pub fn delegate_instruction(position: &Position, sub_dao: SubDao, owner: &Pubkey) -> Instruction {
+    let (closing_time_sub_dao_epoch_info, genesis_end_sub_dao_epoch_info) =
+        delegation_epoch_info_keys(position, sub_dao);
+    let accounts = helium_sub_daos::accounts::DelegateV0 {
+        payer: *owner,
+        position: position.key,
+        mint: position.mint,
+        position_token_account: spl_associated_token_account::get_associated_token_address(
+            owner,
+            &position.mint,
+        ),
+        position_authority: *owner,
+        registrar: position.registrar,
+        dao: Dao::Hnt.key(),
+        sub_dao: sub_dao.key(),
+        sub_dao_epoch_info: sub_dao.epoch_info_key(),
+        closing_time_sub_dao_epoch_info,
+        genesis_end_sub_dao_epoch_info,
+        delegated_position: delegated_position_key(&position.key),
+        vsr_program: voter_stake_registry::id(),
+        system_program: solana_sdk::system_program::ID,
+    };
+    Instruction {
+        program_id: helium_sub_daos::id(),
+        accounts: accounts.to_account_metas(None),
+        data: helium_sub_daos::instruction::DelegateV0 {}.data(),
+    }
+}
+
+/// Construct the instruction that closes the delegation of a delegated
+/// position. All rewards up to the current epoch must have been claimed.
+pub fn close_delegation_instruction(
+    position: &Position,
+    owner: &Pubkey,
+) -> Result<Instruction, Error> {
+    let sub_dao = delegated_sub_dao(position)?;
+    let (closing_time_sub_dao_epoch_info, genesis_end_sub_dao_epoch_info) =
+        delegation_epoch_info_keys(position, sub_dao);
+    let accounts = helium_sub_daos::accounts::CloseDelegationV0 {
+        payer: *owner,
+        position: position.key,
+        mint: position.mint,
+        position_token_account: spl_associated_token_account::get_associated_token_address(
+            owner,
+            &position.mint,
+        ),
+        position_authority: *owner,
+        registrar: position.registrar,
+        dao: Dao::Hnt.key(),
+        sub_dao: sub_dao.key(),
+        delegated_position: delegated_position_key(&position.key),
+        sub_dao_epoch_info: sub_dao.epoch_info_key(),
+        closing_time_sub_dao_epoch_info,
+        genesis_end_sub_dao_epoch_info,
+        system_program: solana_sdk::system_program::ID,
+    };
+    Ok(Instruction {
+        program_id: helium_sub_daos::id(),
+        accounts: accounts.to_account_metas(None),
+        data: helium_sub_daos::instruction::CloseDelegationV0 {}.data(),
+    })
+}
+
+pub const DELEGATE_INSTRUCTION_BUDGET: u32 = 250_000;
+
+/// Construct a message that changes the delegation of a position: delegating
+/// it to the given subdao, or undelegating it when no subdao is given.
+///
+/// A delegated position has to claim all of its rewards before its delegation
+/// can change. Unclaimed epochs are claimed in the same message, so at most
+/// [`MAX_CLAIM_EPOCHS_PER_TXN`] epochs can be unclaimed; use [`claim`] to
+/// catch up on older epochs first. All unclaimed epochs must have had their
+/// rewards issued.
+pub async fn delegate_message<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    position: &Position,
+    sub_dao: Option<SubDao>,
+    owner: &Pubkey,
+    opts: &TransactionOpts,
+) -> Result<(message::VersionedMessage, u64), Error> {
+    let current = position
+        .delegation
+        .as_ref()
+        .map(|delegation| delegation.sub_dao);
+    if current == sub_dao {
+        return Err(match sub_dao {
+            Some(sub_dao) => EncodeError::other(format!(
+                "position {} is already delegated to {sub_dao}",
+                position.key
+            )),
+            None => EncodeError::other(format!("position {} is not delegated", position.key)),
+        }
+        .into());
+    }
+
+    let mut ixs = vec![];
+    let mut claim_count = 0;
+    if position.is_delegated() {
+        let mut epochs = vec![];
+        for (epoch, info) in unclaimed_epoch_infos(client, position).await? {
+            if !info.is_some_and(|info| info.rewards_issued_at.is_some()) {
+                return Err(EncodeError::other(format!(
+                    "rewards for epoch {epoch} have not been issued yet"
+                ))
+                .into());
+            }
+            epochs.push(epoch);
+        }
+        if epochs.len() > MAX_CLAIM_EPOCHS_PER_TXN {
+            return Err(EncodeError::other(format!(
+                "position {} has {} unclaimed epochs, claim rewards first",
+                position.key,
+                epochs.len()
+            ))
+            .into());
+        }
+        claim_count = epochs.len();
+        ixs.extend(claim_instructions(client, position, owner, &epochs).await?);
+        ixs.push(close_delegation_instruction(position, owner)?);
+    }
+    if let Some(sub_dao) = sub_dao {
+        ixs.push(delegate_instruction(position, sub_dao, owner));
+    }
+
+    let accounts = ixs
+        .iter()
+        .flat_map(|ix| ix.accounts.iter().cloned())
+        .collect_vec();
+    let budget = (claim_count as u32 * CLAIM_INSTRUCTION_BUDGET
+        + (ixs.len() - claim_count) as u32 * DELEGATE_INSTRUCTION_BUDGET)
+        .min(priority_fee::MAX_COMPUTE_UNITS);
+    let ixs = [
+        vec![
+            priority_fee::compute_budget_instruction(budget),
+            priority_fee::compute_price_instruction_for_accounts(client, &accounts, opts).await?,
+        ],
+        ixs,
+    ]
+    .concat();
+    message::mk_message(client, &ixs, opts, owner).await
+}
+
+/// Change the delegation of a position owned by the given keypair. See
+/// [`delegate_message`].
+pub async fn delegate<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    position: &Position,
+    sub_dao: Option<SubDao>,
+    keypair: &Keypair,
+    opts: &TransactionOpts,
+) -> Result<(VersionedTransaction, u64), Error> {
+    let (msg, block_height) =
+        delegate_message(client, position, sub_dao, &keypair.pubkey(), opts).await?;
+    let txn = tx::partial_sign(msg, &[keypair])?;
+    Ok((txn, block_height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Roughly the mainnet HNT voting mint: no baseline weight, up to 100x
+    // for a four year lockup
+    const WEIGHTS: VoteWeights = VoteWeights {
+        baseline_factor: 0,
+        max_extra_lockup_factor: 100_000_000_000,
+        lockup_saturation_secs: 4 * 365 * 24 * 60 * 60,
+        digit_shift: 0,
+    };
+
+    fn args(hnt: u64, days: u32) -> PositionArgs {
+        PositionArgs {
+            amount: Token::Hnt.amount(hnt * 100_000_000),
+            kind: LockupKind::Cliff,
+            days,
+            delegate: None,
+        }
+    }
+
+    #[test]
+    fn voting_power_saturates() {
+        let full = WEIGHTS.voting_power(100, 4 * 365 * 24 * 60 * 60);
+        assert_eq!(10_000, full);
+        assert_eq!(full, WEIGHTS.voting_power(100, 5 * 365 * 24 * 60 * 60));
+        assert_eq!(5_000, WEIGHTS.voting_power(100, 2 * 365 * 24 * 60 * 60));
+    }
+
+    #[test]
+    fn estimate_new_position() {
+        let mint = Pubkey::new_unique();
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let position = args(10, 730).estimate(&mint, &WEIGHTS, start).unwrap();
+        assert_eq!(Decimal::from(50), position.multiplier);
+        assert_eq!(Decimal::from(500), position.ve_hnt);
+        assert_eq!(position_key(&mint), position.key);
+        assert_eq!(start + Duration::days(730), position.lockup.end,);
+    }
+
+    #[test]
+    fn unclaimed_epochs() {
+        let start = dao::epoch_start(100).unwrap();
+        let mut position = Position {
+            key: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            registrar: Pubkey::new_unique(),
+            amount: Token::Hnt.amount(100),
+            lockup: args(1, 30).lockup(start),
+            genesis_end: None,
+            delegation: None,
+        };
+        let now = dao::epoch_start(110).unwrap();
+        assert!(position.unclaimed_epochs(now).is_empty());
+
+        position.delegation = Some(Delegation {
+            sub_dao: SubDao::Iot,
+            last_claimed_epoch: 104,
+        });
+        // The current epoch is still in progress
+        assert_eq!(105..110, position.unclaimed_epochs(now));
+        // Nothing accrues after the lockup ends
+        let later = dao::epoch_start(200).unwrap();
+        assert_eq!(105..131, position.unclaimed_epochs(later));
+    }
+}
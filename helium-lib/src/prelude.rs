@@ -0,0 +1,35 @@
+//! The curated, semver guarded API of helium-lib.
+//!
+//! Everything exported here only changes incompatibly with a new major
+//! version of helium-lib. Addresses and signatures are the version
+//! independent [`Address`] and [`Signature`] rather than their solana-sdk
+//! counterparts.
+//!
+//! The modules of the crate, and the re-exported `solana_sdk`,
+//! `anchor_client` and program crates, follow the versions of those
+//! dependencies instead and can change with any dependency update.
+//!
+//! ```ignore
+//! use helium_lib::prelude::*;
+//! ```
+
+pub use crate::{
+    error::{DecodeError, EncodeError, Error},
+    keypair::Keypair,
+    primitives::{Address, Signature},
+};
+
+#[cfg(feature = "rpc")]
+pub use crate::{
+    token::{Token, TokenAmount},
+    TransactionOpts,
+};
+
+#[cfg(feature = "client")]
+pub use crate::{
+    client::Client,
+    dao::SubDao,
+    entity_key::{EncodedEntityKey, EntityKeyEncoding},
+    hotspot::Hotspot,
+    reward::ClaimableToken,
+};
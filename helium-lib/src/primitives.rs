@@ -0,0 +1,176 @@
+//! Address and signature types that do not change with solana-sdk versions.
+//!
+//! Both are plain byte arrays with the usual base58 string form. They
+//! convert to and from the solana-sdk types, so they can be passed to the
+//! rest of helium-lib, but only the conversions are tied to the solana-sdk
+//! version in use.
+
+use crate::{error::DecodeError, keypair, solana_sdk::bs58};
+use std::{fmt, str::FromStr};
+
+/// A Solana account address
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Address([u8; 32]);
+
+/// An ed25519 signature, like the signature identifying a transaction
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Signature([u8; 64]);
+
+impl Address {
+    pub const fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    pub const fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+}
+
+impl Signature {
+    pub const fn from_bytes(bytes: [u8; 64]) -> Self {
+        Self(bytes)
+    }
+
+    pub const fn to_bytes(&self) -> [u8; 64] {
+        self.0
+    }
+}
+
+impl AsRef<[u8]> for Address {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for Signature {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl TryFrom<&[u8]> for Address {
+    type Error = DecodeError;
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Ok(Self(value.try_into()?))
+    }
+}
+
+impl TryFrom<&[u8]> for Signature {
+    type Error = DecodeError;
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Ok(Self(value.try_into()?))
+    }
+}
+
+impl FromStr for Address {
+    type Err = DecodeError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(bs58::decode(s).into_vec()?.as_slice())
+    }
+}
+
+impl FromStr for Signature {
+    type Err = DecodeError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(bs58::decode(s).into_vec()?.as_slice())
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&bs58::encode(self.0).into_string())
+    }
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&bs58::encode(self.0).into_string())
+    }
+}
+
+impl fmt::Debug for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Address({self})")
+    }
+}
+
+impl fmt::Debug for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Signature({self})")
+    }
+}
+
+impl serde::Serialize for Address {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Address {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let str = String::deserialize(deserializer)?;
+        str.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl serde::Serialize for Signature {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Signature {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let str = String::deserialize(deserializer)?;
+        str.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl From<keypair::Pubkey> for Address {
+    fn from(value: keypair::Pubkey) -> Self {
+        Self(value.to_bytes())
+    }
+}
+
+impl From<Address> for keypair::Pubkey {
+    fn from(value: Address) -> Self {
+        Self::new_from_array(value.0)
+    }
+}
+
+impl From<keypair::Signature> for Signature {
+    fn from(value: keypair::Signature) -> Self {
+        let mut bytes = [0; 64];
+        bytes.copy_from_slice(value.as_ref());
+        Self(bytes)
+    }
+}
+
+impl From<Signature> for keypair::Signature {
+    fn from(value: Signature) -> Self {
+        Self::from(value.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let pubkey = keypair::Pubkey::new_unique();
+        let address = Address::from(pubkey);
+        assert_eq!(pubkey.to_string(), address.to_string());
+        assert_eq!(address, pubkey.to_string().parse().expect("address"));
+        assert_eq!(pubkey, keypair::Pubkey::from(address));
+        let json = serde_json::to_string(&address).expect("json");
+        assert_eq!(address, serde_json::from_str(&json).expect("address"));
+
+        let signature = keypair::Signature::from([7; 64]);
+        assert_eq!(
+            signature.to_string(),
+            Signature::from(signature).to_string()
+        );
+        assert!("not base58!".parse::<Address>().is_err());
+    }
+}
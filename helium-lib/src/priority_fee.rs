@@ -1,28 +1,209 @@
-use std::ops::RangeInclusive;
+use std::{
+    fmt,
+    ops::RangeInclusive,
+    str::FromStr,
+    sync::atomic::{AtomicU64, Ordering},
+};
 
 use crate::{
-    anchor_lang::ToAccountMetas, client::SolanaRpcClient, error::Error, keypair::Pubkey,
-    solana_client,
+    anchor_lang::ToAccountMetas,
+    client::SolanaRpc,
+    error::{DecodeError, Error},
+    keypair::Pubkey,
+    solana_rpc_client_api, TransactionOpts,
 };
 use itertools::Itertools;
 
 pub const MAX_RECENT_PRIORITY_FEE_ACCOUNTS: usize = 128;
 pub const MIN_PRIORITY_FEE: u64 = 1;
 pub const MAX_PRIORITY_FEE: u64 = 2500000;
+/// Maximum compute units a transaction can request
+pub const MAX_COMPUTE_UNITS: u32 = 1_400_000;
+/// Default margin added to the simulated compute units of a transaction
+pub const DEFAULT_COMPUTE_UNIT_MARGIN: f64 = 0.1;
+/// Base fee in lamports for every signature of a transaction
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+/// Compute units a transaction gets per instruction without an explicit
+/// compute unit limit
+const DEFAULT_INSTRUCTION_COMPUTE_UNITS: u32 = 200_000;
+
+/// A budget of lamports to spend on the fees of all transactions built with
+/// the transaction options holding it, so a long running process can cap
+/// its fee spend when priority fees spike.
+///
+/// Fees are taken from the budget when a transaction message is built, so
+/// transactions that are built but never sent still count against it.
+#[derive(Debug)]
+pub struct FeeBudget {
+    limit: u64,
+    spent: AtomicU64,
+}
+
+impl FeeBudget {
+    pub fn new(limit: u64) -> Self {
+        Self {
+            limit,
+            spent: AtomicU64::new(0),
+        }
+    }
+
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    pub fn spent(&self) -> u64 {
+        self.spent.load(Ordering::Relaxed)
+    }
+
+    pub fn remaining(&self) -> u64 {
+        self.limit.saturating_sub(self.spent())
+    }
+
+    /// Take the given fee from the budget, failing without taking anything
+    /// if the remaining budget can not cover it
+    pub fn spend(&self, fee: u64) -> Result<(), Error> {
+        self.spent
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |spent| {
+                spent.checked_add(fee).filter(|spent| *spent <= self.limit)
+            })
+            .map(|_| ())
+            .map_err(|spent| Error::FeeBudgetExceeded {
+                fee,
+                remaining: self.limit.saturating_sub(spent),
+            })
+    }
+}
+
+/// The maximum fee in lamports of a transaction with the given instructions
+/// and number of signatures: the signature fees plus the compute unit price
+/// times the compute unit limit.
+pub fn transaction_fee(ixs: &[solana_sdk::instruction::Instruction], signatures: u64) -> u64 {
+    use solana_sdk::compute_budget;
+    let limit_tag = compute_budget_instruction(0).data[0];
+    let price_tag = compute_price_instruction(0).data[0];
+    let mut compute_limit = None;
+    let mut compute_price = 0;
+    for ix in ixs
+        .iter()
+        .filter(|ix| ix.program_id == compute_budget::id())
+    {
+        match ix.data.split_first() {
+            Some((tag, data)) if *tag == limit_tag => {
+                compute_limit = data.try_into().ok().map(u32::from_le_bytes);
+            }
+            Some((tag, data)) if *tag == price_tag => {
+                compute_price = data.try_into().map(u64::from_le_bytes).unwrap_or_default();
+            }
+            _ => (),
+        }
+    }
+    let compute_limit = compute_limit.unwrap_or_else(|| {
+        let instructions = ixs
+            .iter()
+            .filter(|ix| ix.program_id != compute_budget::id())
+            .count() as u32;
+        (DEFAULT_INSTRUCTION_COMPUTE_UNITS * instructions).min(MAX_COMPUTE_UNITS)
+    });
+    // the compute unit price is in micro lamports
+    let priority_fee = (u128::from(compute_limit) * u128::from(compute_price)).div_ceil(1_000_000);
+    signatures * LAMPORTS_PER_SIGNATURE + priority_fee as u64
+}
+
+/// The fee in lamports of a compiled message, see [`transaction_fee`]
+pub fn message_fee(msg: &solana_sdk::message::VersionedMessage) -> u64 {
+    // Programs are never loaded from lookup tables, so the program ids of
+    // all instructions are in the static keys
+    let keys = msg.static_account_keys();
+    let ixs = msg
+        .instructions()
+        .iter()
+        .filter_map(|ix| {
+            keys.get(usize::from(ix.program_id_index))
+                .map(|program_id| solana_sdk::instruction::Instruction {
+                    program_id: *program_id,
+                    accounts: vec![],
+                    data: ix.data.clone(),
+                })
+        })
+        .collect::<Vec<_>>();
+    transaction_fee(&ixs, msg.header().num_required_signatures.into())
+}
+
+/// How the priority fee of a transaction is chosen. Estimates are limited to
+/// the fee range of the transaction options.
+///
+/// Strategies parse from `auto`, `helius`, `percentile:<0-100>` and
+/// `fixed:<micro lamports>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PriorityFeeStrategy {
+    /// The Helius estimate for Helius RPC urls, and the median of recent
+    /// fees otherwise
+    #[default]
+    Auto,
+    /// The given percentile of the maximum fees paid in recent slots for the
+    /// writable accounts of the transaction
+    Percentile(u8),
+    /// The Helius priority fee API, which requires a Helius RPC url
+    Helius,
+    /// A fixed fee in micro lamports per compute unit
+    Fixed(u64),
+}
+
+impl fmt::Display for PriorityFeeStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Auto => f.write_str("auto"),
+            Self::Percentile(percentile) => write!(f, "percentile:{percentile}"),
+            Self::Helius => f.write_str("helius"),
+            Self::Fixed(fee) => write!(f, "fixed:{fee}"),
+        }
+    }
+}
 
-pub async fn get_estimate<C: AsRef<SolanaRpcClient>>(
+impl FromStr for PriorityFeeStrategy {
+    type Err = DecodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || DecodeError::other(format!("invalid priority fee strategy: {s}"));
+        match s.split_once(':') {
+            None if s == "auto" => Ok(Self::Auto),
+            None if s == "helius" => Ok(Self::Helius),
+            None if s == "median" => Ok(Self::Percentile(50)),
+            Some(("percentile", percentile)) => percentile
+                .parse()
+                .ok()
+                .filter(|percentile| *percentile <= 100)
+                .map(Self::Percentile)
+                .ok_or_else(invalid),
+            Some(("fixed", fee)) => fee.parse().map(Self::Fixed).map_err(|_| invalid()),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+pub async fn get_estimate<C: SolanaRpc>(
     client: &C,
     accounts: &impl ToAccountMetas,
-    fee_range: RangeInclusive<u64>,
+    opts: &TransactionOpts,
 ) -> Result<u64, Error> {
-    let client_url = client.as_ref().url();
-    if client_url.contains("mainnet.helius") {
-        helius::get_estimate(client, accounts, fee_range).await
-    } else {
-        base::get_estimate(client, accounts, fee_range).await
+    let fee_range = opts.fee_range();
+    match opts.priority_fee_strategy {
+        PriorityFeeStrategy::Auto if client.url().contains("mainnet.helius") => {
+            helius::get_estimate(client, accounts, fee_range).await
+        }
+        PriorityFeeStrategy::Auto => base::get_estimate(client, accounts, fee_range).await,
+        PriorityFeeStrategy::Percentile(percentile) => {
+            base::get_percentile(client, accounts, percentile, fee_range).await
+        }
+        PriorityFeeStrategy::Helius => helius::get_estimate(client, accounts, fee_range).await,
+        PriorityFeeStrategy::Fixed(fee) => Ok(clamp(fee, &fee_range)),
     }
 }
 
+fn clamp(fee: u64, fee_range: &RangeInclusive<u64>) -> u64 {
+    fee.min(*fee_range.end()).max(*fee_range.start())
+}
+
 fn account_keys(accounts: &impl ToAccountMetas) -> impl Iterator<Item = Pubkey> {
     accounts
         .to_account_metas(None)
@@ -38,7 +219,7 @@ mod helius {
     use serde::Deserialize;
     use serde_json::json;
 
-    pub async fn get_estimate<C: AsRef<SolanaRpcClient>>(
+    pub async fn get_estimate<C: SolanaRpc>(
         client: &C,
         accounts: &impl ToAccountMetas,
         fee_range: RangeInclusive<u64>,
@@ -48,7 +229,7 @@ mod helius {
         struct Response {
             priority_fee_estimate: f64,
         }
-        let request = solana_client::rpc_request::RpcRequest::Custom {
+        let request = solana_rpc_client_api::request::RpcRequest::Custom {
             method: "getPriorityFeeEstimate",
         };
         let account_keys: Vec<_> = account_keys(accounts).map(|v| v.to_string()).collect();
@@ -62,7 +243,9 @@ mod helius {
             }
         ]);
 
-        let response: Response = client.as_ref().send(request, params).await?;
+        let response: Response =
+            serde_json::from_value(client.send_request(request, params).await?)
+                .map_err(DecodeError::other)?;
         Ok((response.priority_fee_estimate.ceil() as u64)
             .min(*fee_range.end())
             .max(*fee_range.start()))
@@ -72,16 +255,13 @@ mod helius {
 mod base {
     use super::*;
 
-    pub async fn get_estimate<C: AsRef<SolanaRpcClient>>(
+    /// The maximum fee of each of the most recent 20 slots, sorted
+    async fn recent_max_fees<C: SolanaRpc>(
         client: &C,
         accounts: &impl ToAccountMetas,
-        fee_range: RangeInclusive<u64>,
-    ) -> Result<u64, Error> {
+    ) -> Result<Vec<u64>, Error> {
         let account_keys: Vec<_> = account_keys(accounts).collect();
-        let recent_fees = client
-            .as_ref()
-            .get_recent_prioritization_fees(&account_keys)
-            .await?;
+        let recent_fees = client.get_recent_prioritization_fees(&account_keys).await?;
         let mut max_per_slot = Vec::new();
         for (slot, fees) in &recent_fees.into_iter().group_by(|x| x.slot) {
             let Some(maximum) = fees.map(|x| x.prioritization_fee).max() else {
@@ -93,6 +273,15 @@ mod base {
         max_per_slot.sort_by(|a, b| a.0.cmp(&b.0).reverse());
         let mut max_per_slot: Vec<_> = max_per_slot.into_iter().take(20).map(|x| x.1).collect();
         max_per_slot.sort();
+        Ok(max_per_slot)
+    }
+
+    pub async fn get_estimate<C: SolanaRpc>(
+        client: &C,
+        accounts: &impl ToAccountMetas,
+        fee_range: RangeInclusive<u64>,
+    ) -> Result<u64, Error> {
+        let max_per_slot = recent_max_fees(client, accounts).await?;
         // Get the median:
         let num_recent_fees = max_per_slot.len();
         let mid = num_recent_fees / 2;
@@ -108,21 +297,155 @@ mod base {
         .max(*fee_range.start());
         Ok(estimate)
     }
+
+    pub async fn get_percentile<C: SolanaRpc>(
+        client: &C,
+        accounts: &impl ToAccountMetas,
+        percentile: u8,
+        fee_range: RangeInclusive<u64>,
+    ) -> Result<u64, Error> {
+        let max_per_slot = recent_max_fees(client, accounts).await?;
+        Ok(clamp(percentile_of(&max_per_slot, percentile), &fee_range))
+    }
+
+    /// The nearest rank percentile of sorted fees, or 0 without fees
+    pub(super) fn percentile_of(sorted_fees: &[u64], percentile: u8) -> u64 {
+        let rank = (sorted_fees.len() * usize::from(percentile.min(100))).div_ceil(100);
+        sorted_fees
+            .get(rank.saturating_sub(1))
+            .copied()
+            .unwrap_or_default()
+    }
 }
 
 pub fn compute_budget_instruction(compute_limit: u32) -> solana_sdk::instruction::Instruction {
     solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_limit(compute_limit)
 }
 
+/// The compute unit limit for a transaction that consumed the given units in
+/// simulation, with the given margin as a fraction of the consumed units
+pub fn compute_unit_limit(units_consumed: u64, margin: f64) -> u32 {
+    let limit = (units_consumed as f64 * (1.0 + margin.max(0.0))).ceil();
+    limit.min(MAX_COMPUTE_UNITS as f64) as u32
+}
+
+/// Replace the compute unit limit instruction in the given instructions, or
+/// add one in front if there is none
+pub fn with_compute_unit_limit(
+    ixs: &[solana_sdk::instruction::Instruction],
+    compute_limit: u32,
+) -> Vec<solana_sdk::instruction::Instruction> {
+    let limit_ix = compute_budget_instruction(compute_limit);
+    let is_limit_ix = |ix: &solana_sdk::instruction::Instruction| {
+        ix.program_id == limit_ix.program_id && ix.data.first() == limit_ix.data.first()
+    };
+    if ixs.iter().any(is_limit_ix) {
+        ixs.iter()
+            .map(|ix| {
+                if is_limit_ix(ix) {
+                    limit_ix.clone()
+                } else {
+                    ix.clone()
+                }
+            })
+            .collect()
+    } else {
+        std::iter::once(limit_ix)
+            .chain(ixs.iter().cloned())
+            .collect()
+    }
+}
+
 pub fn compute_price_instruction(priority_fee: u64) -> solana_sdk::instruction::Instruction {
     solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_price(priority_fee)
 }
 
-pub async fn compute_price_instruction_for_accounts<C: AsRef<SolanaRpcClient>>(
+pub async fn compute_price_instruction_for_accounts<C: SolanaRpc>(
     client: &C,
     accounts: &impl ToAccountMetas,
-    fee_range: RangeInclusive<u64>,
+    opts: &TransactionOpts,
 ) -> Result<solana_sdk::instruction::Instruction, Error> {
-    let priority_fee = get_estimate(client, accounts, fee_range).await?;
+    let priority_fee = get_estimate(client, accounts, opts).await?;
+    tracing::debug!(priority_fee, "estimated priority fee");
     Ok(compute_price_instruction(priority_fee))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compute_unit_limit_replaces_instruction() {
+        assert_eq!(110_000, compute_unit_limit(100_000, 0.1));
+        assert_eq!(MAX_COMPUTE_UNITS, compute_unit_limit(1_300_000, 0.5));
+
+        let transfer = solana_sdk::system_instruction::transfer(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            1,
+        );
+        let ixs = vec![
+            compute_budget_instruction(200_000),
+            compute_price_instruction(1),
+            transfer.clone(),
+        ];
+        let limited = with_compute_unit_limit(&ixs, 1_000);
+        assert_eq!(
+            vec![
+                compute_budget_instruction(1_000),
+                compute_price_instruction(1),
+                transfer.clone()
+            ],
+            limited
+        );
+        let added = with_compute_unit_limit(&[transfer.clone()], 1_000);
+        assert_eq!(vec![compute_budget_instruction(1_000), transfer], added);
+    }
+
+    #[test]
+    fn fee_budget_caps_spend() {
+        let ixs = [
+            compute_budget_instruction(100_000),
+            compute_price_instruction(1_000_000),
+        ];
+        assert_eq!(LAMPORTS_PER_SIGNATURE + 100_000, transaction_fee(&ixs, 1));
+        let payer = Pubkey::new_unique();
+        let msg = solana_sdk::message::VersionedMessage::Legacy(solana_sdk::message::Message::new(
+            &ixs,
+            Some(&payer),
+        ));
+        assert_eq!(transaction_fee(&ixs, 1), message_fee(&msg));
+
+        let budget = FeeBudget::new(150_000);
+        assert!(budget.spend(transaction_fee(&ixs, 1)).is_ok());
+        assert!(matches!(
+            budget.spend(transaction_fee(&ixs, 1)),
+            Err(Error::FeeBudgetExceeded {
+                remaining: 45_000,
+                ..
+            })
+        ));
+        assert_eq!(105_000, budget.spent());
+    }
+
+    #[test]
+    fn strategy_parsing_and_percentiles() {
+        for strategy in [
+            PriorityFeeStrategy::Auto,
+            PriorityFeeStrategy::Helius,
+            PriorityFeeStrategy::Percentile(75),
+            PriorityFeeStrategy::Fixed(5_000),
+        ] {
+            assert_eq!(Some(strategy), strategy.to_string().parse().ok());
+        }
+        assert!("percentile:101".parse::<PriorityFeeStrategy>().is_err());
+        assert!("fixed".parse::<PriorityFeeStrategy>().is_err());
+
+        let fees = [10, 20, 30, 40, 50];
+        assert_eq!(0, base::percentile_of(&[], 50));
+        assert_eq!(10, base::percentile_of(&fees, 0));
+        assert_eq!(30, base::percentile_of(&fees, 50));
+        assert_eq!(40, base::percentile_of(&fees, 75));
+        assert_eq!(50, base::percentile_of(&fees, 100));
+    }
+}
@@ -7,3 +7,5 @@ pub const SPL_ACCOUNT_COMPRESSION_PROGRAM_ID: Pubkey =
     pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
 
 pub const SPL_NOOP_PROGRAM_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+pub const ORGANIZATION_PROGRAM_ID: Pubkey = pubkey!("orgdXvHVLkWgBYerptASkAwkZAE563CJUu717dMNx5f");
@@ -0,0 +1,118 @@
+use crate::{
+    client::SolanaRpcClient,
+    error::Error,
+    keypair::{serde_opt_pubkey, serde_pubkey, Pubkey},
+    solana_client::{
+        rpc_config::RpcProgramAccountsConfig,
+        rpc_filter::{Memcmp, RpcFilterType},
+    },
+    solana_sdk::{account::Account, nonce, system_program},
+    token::{self, Token, TokenAmount},
+};
+use serde::Serialize;
+use std::collections::{hash_map::Entry, HashMap};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RentAccountKind {
+    Token,
+    Nonce,
+}
+
+/// An account holding lamports on behalf of a wallet, either because the wallet
+/// owns it or because the wallet is its authority.
+#[derive(Debug, Clone, Serialize)]
+pub struct RentAccount {
+    #[serde(with = "serde_pubkey")]
+    pub address: Pubkey,
+    pub kind: RentAccountKind,
+    #[serde(with = "serde_opt_pubkey", skip_serializing_if = "Option::is_none")]
+    pub mint: Option<Pubkey>,
+    #[serde(serialize_with = "token::serde_amount_value")]
+    pub lamports: TokenAmount,
+    #[serde(serialize_with = "token::serde_amount_value")]
+    pub rent_exempt_minimum: TokenAmount,
+    /// Whether the account can be closed by the wallet to recover all of its
+    /// lamports
+    pub closable: bool,
+}
+
+/// Get all token and nonce accounts the given wallet holds rent for.
+///
+/// Token accounts are closable when they hold no tokens. Nonce accounts can
+/// always be closed by withdrawing their full balance.
+pub async fn accounts_for_owner<C: AsRef<SolanaRpcClient>>(
+    client: &C,
+    owner: &Pubkey,
+) -> Result<Vec<RentAccount>, Error> {
+    let token_accounts = token::token_accounts_for_owner(client, owner).await?;
+    let nonce_accounts = nonce_accounts_for_authority(client, owner).await?;
+
+    let mut rent_minimums: HashMap<usize, u64> = HashMap::new();
+    for data_len in token_accounts
+        .iter()
+        .map(|(_, account, _)| account.data.len())
+        .chain(nonce_accounts.iter().map(|(_, account)| account.data.len()))
+    {
+        if let Entry::Vacant(entry) = rent_minimums.entry(data_len) {
+            let minimum = client
+                .as_ref()
+                .get_minimum_balance_for_rent_exemption(data_len)
+                .await?;
+            entry.insert(minimum);
+        }
+    }
+    let rent_minimum = |account: &Account| {
+        Token::Sol.amount(
+            rent_minimums
+                .get(&account.data.len())
+                .copied()
+                .unwrap_or_default(),
+        )
+    };
+
+    let token_accounts = token_accounts
+        .into_iter()
+        .map(|(address, account, token_account)| RentAccount {
+            address,
+            kind: RentAccountKind::Token,
+            mint: Some(token_account.mint),
+            lamports: Token::Sol.amount(account.lamports),
+            rent_exempt_minimum: rent_minimum(&account),
+            closable: token_account.amount == 0,
+        });
+    let nonce_accounts = nonce_accounts
+        .into_iter()
+        .map(|(address, account)| RentAccount {
+            address,
+            kind: RentAccountKind::Nonce,
+            mint: None,
+            lamports: Token::Sol.amount(account.lamports),
+            rent_exempt_minimum: rent_minimum(&account),
+            closable: true,
+        });
+    Ok(token_accounts.chain(nonce_accounts).collect())
+}
+
+async fn nonce_accounts_for_authority<C: AsRef<SolanaRpcClient>>(
+    client: &C,
+    authority: &Pubkey,
+) -> Result<Vec<(Pubkey, Account)>, Error> {
+    // The nonce authority follows the version and state discriminators
+    const AUTHORITY_OFFSET: usize = 8;
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![
+            RpcFilterType::DataSize(nonce::State::size() as u64),
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                AUTHORITY_OFFSET,
+                authority.as_ref(),
+            )),
+        ]),
+        ..Default::default()
+    };
+    let accounts = client
+        .as_ref()
+        .get_program_accounts_with_config(&system_program::ID, config)
+        .await?;
+    Ok(accounts)
+}
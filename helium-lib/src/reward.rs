@@ -1,32 +1,41 @@
 use crate::{
+    anchor_client::solana_client::{
+        rpc_client::GetConfirmedSignaturesForAddress2Config, rpc_config::RpcTransactionConfig,
+    },
     anchor_lang::{InstructionData, ToAccountMetas},
     asset, circuit_breaker,
-    client::{DasClient, GetAnchorAccount, SolanaRpcClient},
-    dao::Dao,
+    client::{DasClient, GetAnchorAccount, RetryPolicy, SolanaRpc, SolanaRpcClient},
+    dao::{Dao, SubDao},
     entity_key::{self, AsEntityKey, KeySerialization},
     error::{DecodeError, EncodeError, Error},
     helium_entity_manager,
-    keypair::{Keypair, Pubkey},
+    keypair::{serde_opt_pubkey, Keypair, Pubkey},
     kta, lazy_distributor, message, mk_transaction_with_blockhash, priority_fee,
     programs::SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
     rewards_oracle,
     solana_client::rpc_client::SerializableTransaction,
     solana_sdk::{
-        instruction::Instruction,
+        commitment_config::CommitmentConfig,
+        instruction::{AccountMeta, Instruction},
+        signature::Signature,
         signer::Signer,
         transaction::{Transaction, VersionedTransaction},
     },
     token::{Token, TokenAmount},
-    TransactionOpts,
+    tx, TransactionOpts,
 };
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use futures::{
     stream::{self, StreamExt, TryStreamExt},
     TryFutureExt,
 };
 use itertools::{izip, Itertools};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, EncodedConfirmedTransactionWithStatusMeta,
+    EncodedTransaction, UiTransactionEncoding, UiTransactionTokenBalance,
+};
+use std::{collections::HashMap, str::FromStr};
 
 #[derive(Debug, Serialize, Clone)]
 pub struct Oracle {
@@ -70,6 +79,15 @@ impl From<ClaimableToken> for Token {
     }
 }
 
+impl From<SubDao> for ClaimableToken {
+    fn from(value: SubDao) -> Self {
+        match value {
+            SubDao::Iot => Self::Iot,
+            SubDao::Mobile => Self::Mobile,
+        }
+    }
+}
+
 impl ClaimableToken {
     pub fn mint(&self) -> &Pubkey {
         match self {
@@ -166,6 +184,34 @@ pub async fn max_claim<C: GetAnchorAccount>(
     Ok(Token::from(token).amount(threshold - remaining))
 }
 
+/// Compute units requested for a claim of an already initialized recipient
+pub const CLAIM_INSTRUCTION_BUDGET: u32 = 200_000;
+/// Base fee in lamports for each signature on a transaction
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// Estimate the fee, in SOL, of a claim transaction for the given token.
+///
+/// The estimate covers the signatures of the payer and the rewards oracle and
+/// the current priority fee for the lazy distributor accounts a claim writes
+/// to. The one time rent and compute for initializing a recipient account is
+/// not included.
+pub async fn claim_fee<C: SolanaRpc + GetAnchorAccount>(
+    client: &C,
+    token: ClaimableToken,
+    opts: &TransactionOpts,
+) -> Result<TokenAmount, Error> {
+    let ld_account = lazy_distributor(client, token).await?;
+    let accounts = vec![
+        AccountMeta::new(token.lazy_distributor_key(), false),
+        AccountMeta::new(ld_account.rewards_escrow, false),
+        AccountMeta::new(lazy_distributor_circuit_breaker(&ld_account), false),
+    ];
+    let priority_fee = priority_fee::get_estimate(client, &accounts, opts).await?;
+    let lamports = 2 * LAMPORTS_PER_SIGNATURE
+        + priority_fee * u64::from(CLAIM_INSTRUCTION_BUDGET + 1) / 1_000_000;
+    Ok(Token::Sol.amount(lamports))
+}
+
 async fn set_current_rewards_instruction(
     token: ClaimableToken,
     kta_key: Pubkey,
@@ -197,6 +243,77 @@ async fn set_current_rewards_instruction(
     Ok(ix)
 }
 
+/// Construct a message for a rewards oracle to set the current lifetime
+/// rewards of an entity.
+///
+/// The payer must be one of the oracles configured for the token's lazy
+/// distributor, and the entity's recipient account must already be
+/// initialized.
+pub async fn set_current_rewards_message<
+    E: AsEntityKey,
+    C: AsRef<SolanaRpcClient> + GetAnchorAccount,
+>(
+    client: &C,
+    token: ClaimableToken,
+    entity_key: &E,
+    current_rewards: u64,
+    oracle: &Pubkey,
+    opts: &TransactionOpts,
+) -> Result<(message::VersionedMessage, u64), Error> {
+    let ld_account = lazy_distributor(client, token).await?;
+    let Some((index, oracle_config)) = ld_account
+        .oracles
+        .into_iter()
+        .enumerate()
+        .find(|(_, config)| config.oracle == *oracle)
+    else {
+        return Err(EncodeError::other(format!(
+            "{oracle} is not an oracle for the {} lazy distributor",
+            Token::from(token)
+        ))
+        .into());
+    };
+    let entity_key = entity_key.as_entity_key();
+    let kta_key = Dao::Hnt.entity_key_to_kta_key(&entity_key);
+    let kta = kta::for_entity_key(&entity_key).await?;
+    if recipient::for_kta(client, token, &kta).await?.is_none() {
+        return Err(Error::account_not_found());
+    }
+    let reward = OracleReward {
+        oracle: oracle_config.into(),
+        index: index as u16,
+        reward: Token::from(token).amount(current_rewards),
+    };
+    let ix = set_current_rewards_instruction(token, kta_key, &kta, &reward).await?;
+    let ixs = &[
+        priority_fee::compute_budget_instruction(100_000),
+        priority_fee::compute_price_instruction_for_accounts(client, &ix.accounts, opts).await?,
+        ix,
+    ];
+    message::mk_message(client, ixs, opts, oracle).await
+}
+
+pub async fn set_current_rewards<E: AsEntityKey, C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    token: ClaimableToken,
+    entity_key: &E,
+    current_rewards: u64,
+    keypair: &Keypair,
+    opts: &TransactionOpts,
+) -> Result<(VersionedTransaction, u64), Error> {
+    let (msg, block_height) = set_current_rewards_message(
+        client,
+        token,
+        entity_key,
+        current_rewards,
+        &keypair.pubkey(),
+        opts,
+    )
+    .await?;
+    let txn = tx::partial_sign(msg, &[keypair])?;
+    Ok((txn, block_height))
+}
+
 pub async fn distribute_rewards_instruction<C: AsRef<DasClient> + GetAnchorAccount>(
     client: &C,
     token: ClaimableToken,
@@ -266,6 +383,7 @@ pub async fn distribute_rewards_instruction<C: AsRef<DasClient> + GetAnchorAccou
     Ok(ix)
 }
 
+#[tracing::instrument(skip_all, fields(?token, %encoded_entity_key))]
 pub async fn claim<C: AsRef<DasClient> + AsRef<SolanaRpcClient> + GetAnchorAccount>(
     client: &C,
     token: ClaimableToken,
@@ -287,10 +405,11 @@ pub async fn claim<C: AsRef<DasClient> + AsRef<SolanaRpcClient> + GetAnchorAccou
         return Ok(None);
     };
 
-    txn.try_sign(&[keypair], *txn.get_recent_blockhash())?;
+    txn.try_partial_sign(&[keypair], *txn.get_recent_blockhash())?;
     Ok(Some((txn.into(), block_height)))
 }
 
+#[tracing::instrument(skip_all, fields(?token, %encoded_entity_key, ?amount))]
 pub async fn claim_transaction<C: AsRef<DasClient> + AsRef<SolanaRpcClient> + GetAnchorAccount>(
     client: &C,
     token: ClaimableToken,
@@ -299,6 +418,58 @@ pub async fn claim_transaction<C: AsRef<DasClient> + AsRef<SolanaRpcClient> + Ge
     payer: &Pubkey,
     opts: &TransactionOpts,
 ) -> Result<Option<(Transaction, u64)>, Error> {
+    Ok(
+        claim_transaction_with_amount(client, token, amount, encoded_entity_key, payer, opts)
+            .await?
+            .map(|(txn, block_height, _)| (txn, block_height)),
+    )
+}
+
+/// Construct an oracle signed claim transaction along with the amount it
+/// claims
+async fn claim_transaction_with_amount<
+    C: AsRef<DasClient> + AsRef<SolanaRpcClient> + GetAnchorAccount,
+>(
+    client: &C,
+    token: ClaimableToken,
+    amount: Option<u64>,
+    encoded_entity_key: &entity_key::EncodedEntityKey,
+    payer: &Pubkey,
+    opts: &TransactionOpts,
+) -> Result<Option<(Transaction, u64, TokenAmount)>, Error> {
+    let Some(claim) = claim_instructions(client, token, amount, encoded_entity_key, payer).await?
+    else {
+        return Ok(None);
+    };
+    let (txn, latest_block_height) = oracle_signed_transaction(
+        client,
+        &claim.oracle_url,
+        &claim.ixs,
+        claim.compute_units,
+        payer,
+        opts,
+    )
+    .await?;
+    Ok(Some((txn, latest_block_height, claim.amount)))
+}
+
+/// The instructions claiming the rewards of a single entity
+#[derive(Debug)]
+struct ClaimInstructions {
+    ixs: Vec<Instruction>,
+    compute_units: u32,
+    amount: TokenAmount,
+    /// Url of the rewards oracle that has to sign the claim
+    oracle_url: String,
+}
+
+async fn claim_instructions<C: AsRef<DasClient> + AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    token: ClaimableToken,
+    amount: Option<u64>,
+    encoded_entity_key: &entity_key::EncodedEntityKey,
+    payer: &Pubkey,
+) -> Result<Option<ClaimInstructions>, Error> {
     let entity_key_string = encoded_entity_key.to_string();
     let pending = pending(
         client,
@@ -359,30 +530,249 @@ pub async fn claim_transaction<C: AsRef<DasClient> + AsRef<SolanaRpcClient> + Ge
         *payer,
     )
     .await?;
-    let mut ixs_accounts = vec![];
-    if let Some(ix) = &init_ix {
-        ixs_accounts.extend_from_slice(&ix.accounts);
+    let mut ixs = vec![];
+    if let Some(ix) = init_ix {
+        ixs.push(ix);
     }
-    ixs_accounts.extend_from_slice(&set_current_ix.accounts);
-    ixs_accounts.extend_from_slice(&distribute_ix.accounts);
+    ixs.extend_from_slice(&[set_current_ix, distribute_ix]);
+
+    Ok(Some(ClaimInstructions {
+        ixs,
+        compute_units: init_budget + CLAIM_INSTRUCTION_BUDGET,
+        amount: Token::from(token).amount(to_claim),
+        oracle_url: lifetime_rewards.oracle.url,
+    }))
+}
 
+/// Construct a transaction for the given claim instructions, with a compute
+/// budget and priority fee, and have it signed by the given rewards oracle
+async fn oracle_signed_transaction<C: SolanaRpc>(
+    client: &C,
+    oracle_url: &str,
+    claim_ixs: &[Instruction],
+    compute_units: u32,
+    payer: &Pubkey,
+    opts: &TransactionOpts,
+) -> Result<(Transaction, u64), Error> {
+    let accounts: Vec<AccountMeta> = claim_ixs
+        .iter()
+        .flat_map(|ix| ix.accounts.iter().cloned())
+        .collect();
     let mut ixs = vec![
-        priority_fee::compute_budget_instruction(init_budget + 200_000),
-        priority_fee::compute_price_instruction_for_accounts(
-            client,
-            &ixs_accounts,
-            opts.fee_range(),
-        )
-        .await?,
+        priority_fee::compute_budget_instruction(compute_units),
+        priority_fee::compute_price_instruction_for_accounts(client, &accounts, opts).await?,
     ];
-    if let Some(ix) = init_ix {
-        ixs.push(ix);
+    ixs.extend_from_slice(claim_ixs);
+
+    let (txn, latest_block_height) =
+        mk_transaction_with_blockhash(client, &ixs, opts, payer).await?;
+    let signed_txn = oracle_sign(oracle_url, txn).await?;
+    Ok((signed_txn, latest_block_height))
+}
+
+/// Options for [`claim_many`]
+#[derive(Debug, Clone, Copy)]
+pub struct ClaimManyOpts {
+    /// Maximum number of claims prepared concurrently
+    pub concurrency: usize,
+    /// Number of times preparing a claim or getting it signed is retried on
+    /// a transient error before it is reported as failed
+    pub retries: usize,
+    /// Number of prepared claims that are packed into transactions together
+    pub pack_size: usize,
+}
+
+impl Default for ClaimManyOpts {
+    fn default() -> Self {
+        Self {
+            concurrency: 5,
+            retries: 2,
+            pack_size: 20,
+        }
     }
-    ixs.extend_from_slice(&[set_current_ix, distribute_ix]);
+}
+
+#[derive(Debug)]
+pub enum ClaimOutcome {
+    /// A claim transaction, signed by the payer and the rewards oracle, ready
+    /// to be submitted. The amounts claimed are in the order of the entity
+    /// keys of the result.
+    Ready {
+        txn: VersionedTransaction,
+        block_height: u64,
+        amounts: Vec<TokenAmount>,
+    },
+    /// The entity had no rewards to claim
+    Skipped,
+    /// The claim transaction could not be prepared
+    Failed(Error),
+}
+
+/// A claim result as emitted by [`claim_many`], covering all entities
+/// claimed in a single transaction, or a single skipped or failed entity
+#[derive(Debug)]
+pub struct ClaimResult {
+    pub entity_keys: Vec<entity_key::EncodedEntityKey>,
+    /// Number of entities processed so far, including these
+    pub completed: usize,
+    /// Total number of entities being claimed
+    pub total: usize,
+    pub outcome: ClaimOutcome,
+}
+
+/// Retry the given operation on transient errors, up to the given number of
+/// times, backing off between attempts
+async fn with_retries<T, F, Fut>(retries: usize, f: F) -> Result<T, Error>
+where
+    F: Fn() -> Fut,
+    Fut: std::future::Future<Output = Result<T, Error>>,
+{
+    let mut attempt = 0;
+    loop {
+        match f().await {
+            Err(err) if err.is_transient() && attempt < retries => {
+                attempt += 1;
+                crate::time::sleep(RetryPolicy::default().backoff(attempt as u32)).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Prepare claim transactions for many entities.
+///
+/// The claim instructions of entities are prepared concurrently, and every
+/// [`ClaimManyOpts::pack_size`] prepared claims are packed into as few
+/// transactions as fit, per rewards oracle, before each transaction is signed
+/// by the oracle. Preparing and signing is retried on transient errors as
+/// configured in the given options, while other errors fail the claim right
+/// away. Results are emitted as they complete, which may not be in the order
+/// of the given entities, and carry the overall progress so callers can
+/// report it. Submitting the returned transactions is up to the caller.
+///
+/// The KTAs of all entities are fetched in bulk before the first claim is
+/// prepared.
+pub fn claim_many<'a, C: AsRef<DasClient> + AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &'a C,
+    token: ClaimableToken,
+    entity_keys: Vec<entity_key::EncodedEntityKey>,
+    keypair: &'a Keypair,
+    transaction_opts: &'a TransactionOpts,
+    opts: ClaimManyOpts,
+) -> impl futures::Stream<Item = ClaimResult> + 'a {
+    let total = entity_keys.len();
+    let payer = keypair.pubkey();
+    stream::once(async move {
+        let decoded_keys: Vec<Vec<u8>> = entity_keys
+            .iter()
+            .filter_map(|entity_key| entity_key.as_entity_key().ok())
+            .collect();
+        // A failed prefetch only means every claim looks up its own KTA
+        if let Err(err) = kta::prefetch(&decoded_keys).await {
+            tracing::debug!(%err, "kta prefetch failed");
+        }
+        stream::iter(entity_keys)
+    })
+    .flatten()
+    .map(move |entity_key| async move {
+        let claim = with_retries(opts.retries, || {
+            claim_instructions(client, token, None, &entity_key, &payer)
+        })
+        .await;
+        (entity_key, claim)
+    })
+    .buffer_unordered(opts.concurrency.max(1))
+    .chunks(opts.pack_size.max(1))
+    .then(move |prepared| pack_claims(client, prepared, keypair, transaction_opts, opts))
+    .flat_map(stream::iter)
+    .scan(0, move |completed, (entity_keys, outcome)| {
+        *completed += entity_keys.len();
+        futures::future::ready(Some(ClaimResult {
+            entity_keys,
+            completed: *completed,
+            total,
+            outcome,
+        }))
+    })
+}
+
+/// Pack prepared claims into oracle signed transactions, passing through
+/// skipped and failed claims
+async fn pack_claims<C: SolanaRpc>(
+    client: &C,
+    prepared: Vec<(
+        entity_key::EncodedEntityKey,
+        Result<Option<ClaimInstructions>, Error>,
+    )>,
+    keypair: &Keypair,
+    transaction_opts: &TransactionOpts,
+    opts: ClaimManyOpts,
+) -> Vec<(Vec<entity_key::EncodedEntityKey>, ClaimOutcome)> {
+    let payer = keypair.pubkey();
+    let mut results = vec![];
+    let mut by_oracle: HashMap<String, Vec<(entity_key::EncodedEntityKey, ClaimInstructions)>> =
+        HashMap::new();
+    for (entity_key, claim) in prepared {
+        match claim {
+            Ok(Some(claim)) => by_oracle
+                .entry(claim.oracle_url.clone())
+                .or_default()
+                .push((entity_key, claim)),
+            Ok(None) => results.push((vec![entity_key], ClaimOutcome::Skipped)),
+            Err(err) => results.push((vec![entity_key], ClaimOutcome::Failed(err))),
+        }
+    }
+
+    for (oracle_url, claims) in by_oracle {
+        // Claims are signed by the oracle as well as the payer, fee payer and
+        // nonce authority
+        let mut signers = vec![payer];
+        signers.extend(transaction_opts.fee_payer);
+        signers.extend(transaction_opts.durable_nonce.map(|nonce| nonce.authority));
+        signers.sort_unstable();
+        signers.dedup();
+        let mut packer = tx::TransactionPacker::new(transaction_opts.fee_payer.unwrap_or(payer))
+            .with_signers(signers.len() + 1)
+            .with_durable_nonce(transaction_opts.durable_nonce);
+        let mut batches: Vec<(Vec<entity_key::EncodedEntityKey>, Vec<TokenAmount>)> = vec![];
+        for (entity_key, claim) in claims {
+            if let Err(err) = packer.push_group(claim.ixs, claim.compute_units) {
+                results.push((vec![entity_key], ClaimOutcome::Failed(err)));
+                continue;
+            }
+            batches.resize_with(packer.len(), Default::default);
+            let (entity_keys, amounts) = &mut batches[packer.len() - 1];
+            entity_keys.push(entity_key);
+            amounts.push(claim.amount);
+        }
 
-    let (txn, latest_block_height) = mk_transaction_with_blockhash(client, &ixs, payer).await?;
-    let signed_txn = oracle_sign(&lifetime_rewards.oracle.url, txn).await?;
-    Ok(Some((signed_txn, latest_block_height)))
+        for ((ixs, compute_units), (entity_keys, amounts)) in packer.batches().zip(batches) {
+            let signed = with_retries(opts.retries, || async {
+                let (mut txn, block_height) = oracle_signed_transaction(
+                    client,
+                    &oracle_url,
+                    ixs,
+                    compute_units,
+                    &payer,
+                    transaction_opts,
+                )
+                .await?;
+                txn.try_partial_sign(&[keypair], *txn.get_recent_blockhash())?;
+                Ok((txn, block_height))
+            })
+            .await;
+            let outcome = match signed {
+                Ok((txn, block_height)) => ClaimOutcome::Ready {
+                    txn: txn.into(),
+                    block_height,
+                    amounts,
+                },
+                Err(err) => ClaimOutcome::Failed(err),
+            };
+            results.push((entity_keys, outcome));
+        }
+    }
+    results
 }
 
 pub async fn pending<C: GetAnchorAccount>(
@@ -441,6 +831,31 @@ pub async fn pending<C: GetAnchorAccount>(
     Ok(entity_key_rewards)
 }
 
+/// Number of entities whose rewards are requested from the oracles in a single
+/// request by [`pending_many`]
+pub const PENDING_BATCH_SIZE: usize = 100;
+
+/// Get the pending rewards for a large number of entities.
+///
+/// Entities are looked up in batches of [`PENDING_BATCH_SIZE`], with a few
+/// batches in flight at a time. Entities without pending rewards are not
+/// included in the result.
+pub async fn pending_many<C: GetAnchorAccount>(
+    client: &C,
+    token: ClaimableToken,
+    entity_key_strings: &[String],
+    entity_key_encoding: KeySerialization,
+) -> Result<HashMap<String, OracleReward>, Error> {
+    stream::iter(entity_key_strings.chunks(PENDING_BATCH_SIZE))
+        .map(|chunk| pending(client, token, chunk, entity_key_encoding))
+        .buffered(5)
+        .try_fold(HashMap::new(), |mut result, pending| async move {
+            result.extend(pending);
+            Ok(result)
+        })
+        .await
+}
+
 pub async fn lifetime<C: GetAnchorAccount>(
     client: &C,
     token: ClaimableToken,
@@ -471,6 +886,175 @@ pub async fn lifetime<C: GetAnchorAccount>(
         .await
 }
 
+/// A past distribution of rewards out of the lazy distributor escrow
+#[derive(Debug, Serialize, Clone)]
+pub struct ClaimedReward {
+    pub block: u64,
+    pub timestamp: DateTime<Utc>,
+    pub signature: String,
+    /// The owner of the token account the rewards were distributed to, if
+    /// reported by the transaction
+    #[serde(with = "serde_opt_pubkey")]
+    pub destination: Option<Pubkey>,
+    pub amount: TokenAmount,
+}
+
+/// Number of recipient signatures fetched per RPC request by [`history`]
+const HISTORY_PAGE_LIMIT: usize = 1000;
+
+/// Get the rewards claimed for an entity, newest claim first.
+///
+/// Claims are found by walking the transaction history of the entity's
+/// recipient account back to the given start time, if any. The amount of each
+/// claim is the amount that left the lazy distributor's rewards escrow in
+/// that transaction, so transactions that only initialize the recipient or set
+/// its current rewards are not included.
+pub async fn history<E: AsEntityKey, C: AsRef<SolanaRpcClient>>(
+    client: &C,
+    token: ClaimableToken,
+    entity_key: &E,
+    since: Option<DateTime<Utc>>,
+) -> Result<Vec<ClaimedReward>, Error> {
+    let kta = kta::for_entity_key(entity_key).await?;
+    let recipient_key = token.receipient_key_from_kta(&kta);
+    let since = since.map(|since| since.timestamp());
+    let solana_client: &SolanaRpcClient = client.as_ref();
+
+    let mut signatures = vec![];
+    let mut before = None;
+    loop {
+        let page = solana_client
+            .get_signatures_for_address_with_config(
+                &recipient_key,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    limit: Some(HISTORY_PAGE_LIMIT),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        let page_len = page.len();
+        let Some(last) = page.last() else {
+            break;
+        };
+        before = Some(Signature::from_str(&last.signature).map_err(DecodeError::from)?);
+        let reached_since = matches!((since, last.block_time), (Some(since), Some(block_time)) if block_time < since);
+        signatures.extend(page.into_iter().filter(|signature| {
+            signature.err.is_none()
+                && match (since, signature.block_time) {
+                    (Some(since), Some(block_time)) => block_time >= since,
+                    _ => true,
+                }
+        }));
+        if reached_since || page_len < HISTORY_PAGE_LIMIT {
+            break;
+        }
+    }
+
+    let lazy_distributor_key = token.lazy_distributor_key();
+    stream::iter(signatures)
+        .map(|signature| async move {
+            let signature = Signature::from_str(&signature.signature).map_err(DecodeError::from)?;
+            solana_client
+                .get_transaction_with_config(
+                    &signature,
+                    RpcTransactionConfig {
+                        encoding: Some(UiTransactionEncoding::JsonParsed),
+                        commitment: Some(CommitmentConfig::finalized()),
+                        max_supported_transaction_version: Some(0),
+                    },
+                )
+                .map_err(Error::from)
+                .await
+        })
+        .buffered(5)
+        .try_filter_map(|txn| async move {
+            ClaimedReward::from_transaction(token, &lazy_distributor_key, txn).map_err(Error::from)
+        })
+        .try_collect()
+        .await
+}
+
+impl ClaimedReward {
+    fn from_transaction(
+        token: ClaimableToken,
+        lazy_distributor_key: &Pubkey,
+        txn: EncodedConfirmedTransactionWithStatusMeta,
+    ) -> Result<Option<Self>, DecodeError> {
+        let Some(meta) = txn.transaction.meta else {
+            return Err(DecodeError::other("no transaction status found"));
+        };
+        if meta.err.is_some() {
+            return Ok(None);
+        }
+        let (OptionSerializer::Some(pre_balances), OptionSerializer::Some(post_balances)) =
+            (meta.pre_token_balances, meta.post_token_balances)
+        else {
+            return Ok(None);
+        };
+        let mint = token.mint().to_string();
+        let lazy_distributor_key = lazy_distributor_key.to_string();
+        // Token balances of the given mint keyed by account index
+        let balances =
+            |balances: &[UiTransactionTokenBalance]| -> HashMap<u8, (Option<String>, u64)> {
+                balances
+                    .iter()
+                    .filter(|balance| balance.mint == mint)
+                    .map(|balance| {
+                        let owner = match &balance.owner {
+                            OptionSerializer::Some(owner) => Some(owner.clone()),
+                            _ => None,
+                        };
+                        let amount = balance.ui_token_amount.amount.parse().unwrap_or_default();
+                        (balance.account_index, (owner, amount))
+                    })
+                    .collect()
+            };
+        let pre_balances = balances(&pre_balances);
+        let post_balances = balances(&post_balances);
+        let balance_change = |index: &u8, post: u64| {
+            let pre = pre_balances
+                .get(index)
+                .map(|(_, amount)| *amount)
+                .unwrap_or_default();
+            post as i128 - pre as i128
+        };
+
+        let mut amount = 0;
+        let mut destination = None;
+        for (index, (owner, post)) in &post_balances {
+            let change = balance_change(index, *post);
+            if owner.as_deref() == Some(lazy_distributor_key.as_str()) {
+                amount = u64::try_from(-change).unwrap_or_default();
+            } else if change > 0 {
+                destination = owner
+                    .as_deref()
+                    .and_then(|owner| Pubkey::from_str(owner).ok());
+            }
+        }
+        if amount == 0 {
+            return Ok(None);
+        }
+
+        let Some(timestamp) = txn
+            .block_time
+            .and_then(|block_time| DateTime::from_timestamp(block_time, 0))
+        else {
+            return Err(DecodeError::other("no valid block time found"));
+        };
+        let EncodedTransaction::Json(ui_txn) = txn.transaction.transaction else {
+            return Err(DecodeError::other("not a json encoded transaction"));
+        };
+        Ok(Some(Self {
+            block: txn.slot,
+            timestamp,
+            signature: ui_txn.signatures[0].clone(),
+            destination,
+            amount: Token::from(token).amount(amount),
+        }))
+    }
+}
+
 async fn oracle_sign(oracle: &str, txn: Transaction) -> Result<Transaction, Error> {
     #[derive(Debug, Serialize, Deserialize)]
     struct Data {
@@ -626,15 +1210,11 @@ pub mod recipient {
         let ix = init_instruction(token, &kta, &asset, &asset_proof, payer).await?;
         let ixs = &[
             priority_fee::compute_budget_instruction(INIT_INSTRUCTION_BUDGET),
-            priority_fee::compute_price_instruction_for_accounts(
-                client,
-                &ix.accounts,
-                opts.fee_range(),
-            )
-            .await?,
+            priority_fee::compute_price_instruction_for_accounts(client, &ix.accounts, opts)
+                .await?,
             ix,
         ];
-        message::mk_message(client, ixs, &opts.lut_addresses, payer).await
+        message::mk_message(client, ixs, opts, payer).await
     }
 
     pub async fn init<E: AsEntityKey, C: AsRef<SolanaRpcClient> + AsRef<DasClient>>(
@@ -646,7 +1226,98 @@ pub mod recipient {
     ) -> Result<(VersionedTransaction, u64), Error> {
         let (msg, block_height) =
             init_message(client, token, entity_key, &keypair.pubkey(), opts).await?;
-        let txn = VersionedTransaction::try_new(msg, &[keypair])?;
+        let txn = tx::partial_sign(msg, &[keypair])?;
+        Ok((txn, block_height))
+    }
+
+    pub async fn update_destination_instruction(
+        token: ClaimableToken,
+        kta: &helium_entity_manager::KeyToAssetV0,
+        asset: &asset::Asset,
+        asset_proof: &asset::AssetProof,
+        destination: &Pubkey,
+    ) -> Result<Instruction, Error> {
+        let mut accounts = lazy_distributor::accounts::UpdateCompressionDestinationV0 {
+            recipient: token.receipient_key_from_kta(kta),
+            owner: asset.ownership.owner,
+            destination: *destination,
+            merkle_tree: asset.compression.tree,
+            compression_program: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+        }
+        .to_account_metas(None);
+        accounts.extend_from_slice(&asset_proof.proof(Some(3))?);
+
+        let ix = Instruction {
+            program_id: lazy_distributor::id(),
+            accounts,
+            data: lazy_distributor::instruction::UpdateCompressionDestinationV0 {
+                _args: lazy_distributor::UpdateCompressionDestinationArgsV0 {
+                    data_hash: asset.compression.data_hash,
+                    creator_hash: asset.compression.creator_hash,
+                    root: asset_proof.root.to_bytes(),
+                    index: asset.compression.leaf_id()?,
+                },
+            }
+            .data(),
+        };
+        Ok(ix)
+    }
+
+    pub const UPDATE_DESTINATION_INSTRUCTION_BUDGET: u32 = 100_000;
+
+    /// Construct a message that sets the account rewards for the given entity
+    /// are distributed to.
+    ///
+    /// The recipient account for the entity must already be initialized, and
+    /// the message must be signed by the owner of the entity's asset. Passing
+    /// the default pubkey as destination resets distributions to go to the
+    /// asset owner.
+    pub async fn update_destination_message<
+        E: AsEntityKey,
+        C: AsRef<SolanaRpcClient> + AsRef<DasClient>,
+    >(
+        client: &C,
+        token: ClaimableToken,
+        entity_key: &E,
+        destination: &Pubkey,
+        payer: &Pubkey,
+        opts: &TransactionOpts,
+    ) -> Result<(message::VersionedMessage, u64), Error> {
+        let kta = kta::for_entity_key(entity_key).await?;
+        let (asset, asset_proof) = asset::for_kta_with_proof(client, &kta).await?;
+
+        let ix =
+            update_destination_instruction(token, &kta, &asset, &asset_proof, destination).await?;
+        let ixs = &[
+            priority_fee::compute_budget_instruction(UPDATE_DESTINATION_INSTRUCTION_BUDGET),
+            priority_fee::compute_price_instruction_for_accounts(client, &ix.accounts, opts)
+                .await?,
+            ix,
+        ];
+        message::mk_message(client, ixs, opts, payer).await
+    }
+
+    pub async fn update_destination<
+        E: AsEntityKey,
+        C: AsRef<SolanaRpcClient> + AsRef<DasClient>,
+    >(
+        client: &C,
+        token: ClaimableToken,
+        entity_key: &E,
+        destination: &Pubkey,
+        keypair: &Keypair,
+        opts: &TransactionOpts,
+    ) -> Result<(VersionedTransaction, u64), Error> {
+        let (msg, block_height) = update_destination_message(
+            client,
+            token,
+            entity_key,
+            destination,
+            &keypair.pubkey(),
+            opts,
+        )
+        .await?;
+        let txn = tx::partial_sign(msg, &[keypair])?;
         Ok((txn, block_height))
     }
 }
@@ -667,3 +1338,37 @@ fn value_to_token_amount(
 
     Ok(TokenAmount::from_u64(token.into(), value))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::mock::MockRpc;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn claim_fails_when_fee_budget_is_exhausted() {
+        let rpc = MockRpc::new();
+        let payer = Pubkey::new_unique();
+        let budget = Arc::new(priority_fee::FeeBudget::new(1_000));
+        let opts = TransactionOpts::builder()
+            .fee_budget(budget.clone())
+            .build();
+        let claim_ix = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[],
+            vec![AccountMeta::new(payer, true)],
+        );
+        // The budget is checked before the oracle is asked to sign
+        let result = oracle_signed_transaction(
+            &rpc,
+            "http://127.0.0.1:0",
+            &[claim_ix],
+            CLAIM_INSTRUCTION_BUDGET,
+            &payer,
+            &opts,
+        )
+        .await;
+        assert!(matches!(result, Err(Error::FeeBudgetExceeded { .. })));
+        assert_eq!(0, budget.spent());
+    }
+}
@@ -0,0 +1,96 @@
+//! Network statistics aggregated from the per epoch accounts of the DAO and
+//! its subdaos.
+use crate::{
+    client::GetAnchorAccount,
+    dao::{self, epoch_info, SubDao},
+    error::Error,
+    token::{self, Token, TokenAmount},
+};
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::*;
+use serde::Serialize;
+use std::ops::Range;
+
+/// Get the range of epochs covering the given period up to and including the
+/// current, still in progress, epoch.
+pub fn epochs_for_period(period: std::time::Duration) -> Range<u64> {
+    let end = dao::epoch_for(Utc::now()) + 1;
+    let count = period.as_secs().div_ceil(dao::EPOCH_LENGTH as u64).max(1);
+    end.saturating_sub(count)..end
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct EpochDcBurn {
+    pub epoch: u64,
+    pub start: Option<DateTime<Utc>>,
+    #[serde(serialize_with = "token::serde_amount_value")]
+    pub dc_burned: TokenAmount,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DcBurn {
+    pub subdao: SubDao,
+    #[serde(serialize_with = "token::serde_amount_value")]
+    pub dc_burned: TokenAmount,
+    /// The USD value of the burned DC
+    pub usd: Decimal,
+    pub epochs: Vec<EpochDcBurn>,
+}
+
+/// Get the DC burned in a subdao for each of the given epochs.
+///
+/// Epochs without an epoch info account, because nothing happened in the
+/// subdao or the epoch has not started, are left out.
+pub async fn dc_burn<C: GetAnchorAccount>(
+    client: &C,
+    subdao: SubDao,
+    epochs: Range<u64>,
+) -> Result<DcBurn, Error> {
+    let epochs: Vec<EpochDcBurn> = epoch_info::sub_dao(client, subdao, &epochs.collect::<Vec<_>>())
+        .await?
+        .into_iter()
+        .flatten()
+        .map(|info| EpochDcBurn {
+            epoch: info.epoch,
+            start: info.start,
+            dc_burned: info.dc_burned,
+        })
+        .collect();
+    let dc_burned = Token::Dc.amount(epochs.iter().map(|epoch| epoch.dc_burned.amount).sum());
+    Ok(DcBurn {
+        subdao,
+        usd: Decimal::from(dc_burned.amount) / Decimal::from(token::price::DC_PER_USD),
+        dc_burned,
+        epochs,
+    })
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct EpochEmissions {
+    pub epoch: u64,
+    pub start: Option<DateTime<Utc>>,
+    #[serde(serialize_with = "token::serde_amount_value")]
+    pub hnt_emitted: TokenAmount,
+    #[serde(serialize_with = "token::serde_amount_value")]
+    pub hnt_supply: TokenAmount,
+}
+
+/// Get the HNT emitted by the DAO and the HNT supply at each of the given
+/// epochs. Epochs without an epoch info account are left out.
+pub async fn emissions<C: GetAnchorAccount>(
+    client: &C,
+    epochs: Range<u64>,
+) -> Result<Vec<EpochEmissions>, Error> {
+    let emissions = epoch_info::dao(client, &epochs.collect::<Vec<_>>())
+        .await?
+        .into_iter()
+        .flatten()
+        .map(|info| EpochEmissions {
+            epoch: info.epoch,
+            start: info.start,
+            hnt_emitted: info.total_rewards,
+            hnt_supply: info.current_hnt_supply,
+        })
+        .collect();
+    Ok(emissions)
+}
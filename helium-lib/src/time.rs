@@ -0,0 +1,53 @@
+//! Timers used by helium-lib, independent of the async runtime of the
+//! target. Native targets use tokio timers, while `wasm32` targets use
+//! browser timers since tokio timers need a tokio runtime.
+
+use crate::error::Error;
+use std::{future::Future, time::Duration};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use tokio::time::Instant;
+#[cfg(target_arch = "wasm32")]
+pub(crate) use web_time::Instant;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn sleep(duration: Duration) {
+    futures_timer::Delay::new(duration).await
+}
+
+#[cfg(feature = "client")]
+pub(crate) async fn sleep_until(deadline: Instant) {
+    sleep(deadline.saturating_duration_since(Instant::now())).await
+}
+
+/// Run a future to completion, failing with [`Error::Timeout`] when the
+/// given duration passes first
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn timeout<T, E, F>(duration: Duration, future: F) -> Result<T, E>
+where
+    F: Future<Output = Result<T, E>>,
+    E: From<Error>,
+{
+    tokio::time::timeout(duration, future)
+        .await
+        .unwrap_or_else(|_| Err(Error::Timeout(duration).into()))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn timeout<T, E, F>(duration: Duration, future: F) -> Result<T, E>
+where
+    F: Future<Output = Result<T, E>>,
+    E: From<Error>,
+{
+    use futures::future::{select, Either};
+    let future = std::pin::pin!(future);
+    match select(future, futures_timer::Delay::new(duration)).await {
+        Either::Left((result, _)) => result,
+        Either::Right(_) => Err(Error::Timeout(duration).into()),
+    }
+}
@@ -1,15 +1,15 @@
 use crate::{
     anchor_lang::AccountDeserialize,
     anchor_spl,
-    client::SolanaRpcClient,
+    client::SolanaRpc,
     error::{DecodeError, Error},
     keypair::{serde_pubkey, Keypair, Pubkey},
     message,
     solana_sdk::{
-        commitment_config::CommitmentConfig, signer::Signer, system_instruction,
+        account::Account, instruction::Instruction, signer::Signer, system_instruction,
         transaction::VersionedTransaction,
     },
-    TransactionOpts,
+    tx, TransactionOpts,
 };
 use chrono::{DateTime, Duration, Utc};
 use futures::stream::{self, StreamExt, TryStreamExt};
@@ -37,9 +37,11 @@ lazy_static::lazy_static! {
 
     static ref DC_MINT: Pubkey = Pubkey::from_str("dcuc8Amr83Wz27ZkQ2K9NS6r8zRpf1J6cvArEBDZDmm").unwrap();
     static ref SOL_MINT: Pubkey = solana_sdk::system_program::ID;
+    static ref SOL_PRICE_KEY: Pubkey = Pubkey::from_str("7UVimffxr9ow1uXYxsr4LHAcV58mLzhmwaeKvJ1pjLiE").unwrap();
+    static ref SOL_PRICE_FEED: price::FeedId = price::feed_from_hex("ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d").unwrap();
 }
 
-pub async fn burn_message<C: AsRef<SolanaRpcClient>>(
+pub async fn burn_message<C: SolanaRpc>(
     client: &C,
     token_amount: &TokenAmount,
     payer: &Pubkey,
@@ -63,21 +65,63 @@ pub async fn burn_message<C: AsRef<SolanaRpcClient>>(
         }
     };
 
-    message::mk_message(client, &[ix], &opts.lut_addresses, payer).await
+    message::mk_message(client, &[ix], opts, payer).await
 }
 
-pub async fn burn<C: AsRef<SolanaRpcClient>>(
+pub async fn burn<C: SolanaRpc>(
     client: &C,
     token_amount: &TokenAmount,
     keypair: &Keypair,
     opts: &TransactionOpts,
 ) -> Result<(VersionedTransaction, u64), Error> {
     let (msg, block_height) = burn_message(client, token_amount, &keypair.pubkey(), opts).await?;
-    let txn = VersionedTransaction::try_new(msg, &[keypair])?;
+    let txn = tx::partial_sign(msg, &[keypair])?;
     Ok((txn, block_height))
 }
 
-pub async fn transfer_message<C: AsRef<SolanaRpcClient>>(
+/// Compute units a SOL transfer needs
+const SOL_TRANSFER_COMPUTE_UNITS: u32 = 300;
+/// Compute units a token transfer needs, including creating the associated
+/// token account of the payee
+const TOKEN_TRANSFER_COMPUTE_UNITS: u32 = 40_000;
+
+/// The instructions of a single payment, along with the compute units they
+/// need
+fn transfer_instructions(
+    payer: &Pubkey,
+    payee: &Pubkey,
+    token_amount: &TokenAmount,
+) -> Result<(Vec<Instruction>, u32), Error> {
+    match token_amount.token.mint() {
+        spl_mint if spl_mint == Token::Sol.mint() => {
+            let ix = system_instruction::transfer(payer, payee, token_amount.amount);
+            Ok((vec![ix], SOL_TRANSFER_COMPUTE_UNITS))
+        }
+        spl_mint => {
+            let source_pubkey = token_amount.token.associated_token_adress(payer);
+            let destination_pubkey = token_amount.token.associated_token_adress(payee);
+            let create_ix = spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                payer,
+                payee,
+                spl_mint,
+                &anchor_spl::token::spl_token::id(),
+            );
+            let transfer_ix = anchor_spl::token::spl_token::instruction::transfer_checked(
+                &anchor_spl::token::spl_token::id(),
+                &source_pubkey,
+                token_amount.token.mint(),
+                &destination_pubkey,
+                payer,
+                &[],
+                token_amount.amount,
+                token_amount.token.decimals(),
+            )?;
+            Ok((vec![create_ix, transfer_ix], TOKEN_TRANSFER_COMPUTE_UNITS))
+        }
+    }
+}
+
+pub async fn transfer_message<C: SolanaRpc>(
     client: &C,
     transfers: &[(Pubkey, TokenAmount)],
     payer: &Pubkey,
@@ -85,84 +129,206 @@ pub async fn transfer_message<C: AsRef<SolanaRpcClient>>(
 ) -> Result<(message::VersionedMessage, u64), Error> {
     let mut ixs = vec![];
     for (payee, token_amount) in transfers {
-        match token_amount.token.mint() {
-            spl_mint if spl_mint == Token::Sol.mint() => {
-                let ix = system_instruction::transfer(payer, payee, token_amount.amount);
-                ixs.push(ix);
-            }
-            spl_mint => {
-                let source_pubkey = token_amount.token.associated_token_adress(payer);
-                let destination_pubkey = token_amount.token.associated_token_adress(payee);
-                let ix = spl_associated_token_account::instruction::create_associated_token_account_idempotent(
-                    payer,
-                    payee,
-                    spl_mint,
-                    &anchor_spl::token::spl_token::id(),
-                );
-                ixs.push(ix);
-
-                let ix = anchor_spl::token::spl_token::instruction::transfer_checked(
-                    &anchor_spl::token::spl_token::id(),
-                    &source_pubkey,
-                    token_amount.token.mint(),
-                    &destination_pubkey,
-                    payer,
-                    &[],
-                    token_amount.amount,
-                    token_amount.token.decimals(),
-                )?;
-                ixs.push(ix);
-            }
-        }
+        ixs.extend(transfer_instructions(payer, payee, token_amount)?.0);
     }
-    message::mk_message(client, &ixs, &opts.lut_addresses, payer).await
+    message::mk_message(client, &ixs, opts, payer).await
 }
 
-pub async fn transfer<C: AsRef<SolanaRpcClient>>(
+pub async fn transfer<C: SolanaRpc>(
     client: &C,
     transfers: &[(Pubkey, TokenAmount)],
     keypair: &Keypair,
     opts: &TransactionOpts,
 ) -> Result<(VersionedTransaction, u64), Error> {
     let (msg, block_height) = transfer_message(client, transfers, &keypair.pubkey(), opts).await?;
-    let txn = VersionedTransaction::try_new(msg, &[keypair])?;
+    let txn = tx::partial_sign(msg, &[keypair])?;
     Ok((txn, block_height))
 }
 
-pub async fn balance_for_address<C: AsRef<SolanaRpcClient>>(
+/// Payments packed into as few transactions as transaction size and
+/// compute limits allow
+pub struct PackedTransfers {
+    packer: tx::TransactionPacker,
+    /// The payments of each packed transaction
+    pub transfers: Vec<Vec<(Pubkey, TokenAmount)>>,
+}
+
+impl PackedTransfers {
+    /// The number of packed transactions
+    pub fn len(&self) -> usize {
+        self.transfers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transfers.is_empty()
+    }
+
+    /// Construct the message of the packed transaction at the given index.
+    /// Every message gets a fresh blockhash, so construct each one just
+    /// before sending it.
+    pub async fn message<C: SolanaRpc>(
+        &self,
+        client: &C,
+        index: usize,
+        opts: &TransactionOpts,
+    ) -> Result<(message::VersionedMessage, u64), Error> {
+        self.packer.message(client, index, opts).await
+    }
+
+    /// Construct the packed transaction at the given index, signed by the
+    /// given keypair. See [`PackedTransfers::message`].
+    pub async fn transaction<C: SolanaRpc>(
+        &self,
+        client: &C,
+        index: usize,
+        keypair: &Keypair,
+        opts: &TransactionOpts,
+    ) -> Result<(VersionedTransaction, u64), Error> {
+        let (msg, block_height) = self.message(client, index, opts).await?;
+        let txn = tx::partial_sign(msg, &[keypair])?;
+        Ok((txn, block_height))
+    }
+}
+
+/// Pack payments from the given payer into transactions. The instructions
+/// of each payment stay in the same transaction. Messages are only
+/// constructed when asked for, see [`PackedTransfers::message`].
+pub async fn pack_transfers<C: SolanaRpc>(
+    client: &C,
+    transfers: &[(Pubkey, TokenAmount)],
+    payer: &Pubkey,
+    opts: &TransactionOpts,
+) -> Result<PackedTransfers, Error> {
+    let mut packer = tx::TransactionPacker::for_opts(client, *payer, opts).await?;
+    let mut packed: Vec<Vec<(Pubkey, TokenAmount)>> = vec![];
+    for (payee, token_amount) in transfers {
+        let (ixs, compute_units) = transfer_instructions(payer, payee, token_amount)?;
+        packer.push_group(ixs, compute_units)?;
+        packed.resize_with(packer.len(), Default::default);
+        packed[packer.len() - 1].push((*payee, *token_amount));
+    }
+    Ok(PackedTransfers {
+        packer,
+        transfers: packed,
+    })
+}
+
+pub async fn balance_for_address<C: SolanaRpc>(
     client: &C,
     pubkey: &Pubkey,
 ) -> Result<Option<TokenBalance>, Error> {
-    match client
-        .as_ref()
-        .get_account_with_commitment(pubkey, CommitmentConfig::confirmed())
+    client
+        .get_multiple_accounts(&[*pubkey])
         .await?
-        .value
-    {
-        Some(account) if account.owner == solana_sdk::system_program::ID => {
-            Ok(Some(Token::Sol.to_balance(*pubkey, account.lamports)))
-        }
-        Some(account) => {
-            let token_account =
-                anchor_spl::token::TokenAccount::try_deserialize(&mut account.data.as_slice())?;
-            let token = Token::from_mint(token_account.mint)
-                .ok_or_else(|| DecodeError::other("Invalid mint"))?;
-            Ok(Some(token.to_balance(*pubkey, token_account.amount)))
-        }
-        None => Ok(None),
-    }
+        .into_iter()
+        .flatten()
+        .next()
+        .map(|account| balance_from_account(pubkey, account))
+        .transpose()
 }
 
-pub async fn balance_for_addresses<C: AsRef<SolanaRpcClient>>(
+pub async fn balance_for_addresses<C: SolanaRpc>(
     client: &C,
     pubkeys: &[Pubkey],
 ) -> Result<Vec<TokenBalance>, Error> {
-    stream::iter(pubkeys)
-        .map(|pubkey| balance_for_address(client, pubkey))
-        .buffered(10)
-        .filter_map(|result| async { result.transpose() })
-        .try_collect()
-        .await
+    let balances = balances_for_many(client, pubkeys).await?;
+    Ok(balances.into_iter().flatten().collect())
+}
+
+/// Get the balances for a list of (token) account addresses using batched
+/// `getMultipleAccounts` calls.
+///
+/// The returned list is in the same order as the given addresses, with `None`
+/// for accounts that do not exist.
+pub async fn balances_for_many<C: SolanaRpc>(
+    client: &C,
+    pubkeys: &[Pubkey],
+) -> Result<Vec<Option<TokenBalance>>, Error> {
+    let accounts = accounts_for_many(client, pubkeys).await?;
+    pubkeys
+        .iter()
+        .zip(accounts)
+        .map(|(pubkey, maybe_account)| {
+            maybe_account
+                .map(|account| balance_from_account(pubkey, account))
+                .transpose()
+        })
+        .collect()
+}
+
+/// Get the raw accounts for a list of addresses using batched
+/// `getMultipleAccounts` calls, in the same order as the given addresses.
+pub(crate) async fn accounts_for_many<C: SolanaRpc>(
+    client: &C,
+    pubkeys: &[Pubkey],
+) -> Result<Vec<Option<Account>>, Error> {
+    let accounts = stream::iter(pubkeys.to_vec())
+        // Chunk into documented max keys to pass to getMultipleAccounts
+        .chunks(100)
+        .map(|key_chunk| async move { client.get_multiple_accounts(&key_chunk).await })
+        .buffered(5)
+        .try_collect::<Vec<Vec<Option<Account>>>>()
+        .await?
+        .into_iter()
+        .flatten()
+        .collect();
+    Ok(accounts)
+}
+
+pub(crate) fn balance_from_account(
+    pubkey: &Pubkey,
+    account: Account,
+) -> Result<TokenBalance, Error> {
+    if account.owner == solana_sdk::system_program::ID {
+        return Ok(Token::Sol.to_balance(*pubkey, account.lamports));
+    }
+    let token_account =
+        anchor_spl::token::TokenAccount::try_deserialize(&mut account.data.as_slice())?;
+    let token =
+        Token::from_mint(token_account.mint).ok_or_else(|| DecodeError::other("Invalid mint"))?;
+    Ok(token.to_balance(*pubkey, token_account.amount))
+}
+
+/// Get all (SPL token program) token accounts owned by the given owner, along
+/// with the raw account they were decoded from.
+pub async fn token_accounts_for_owner<C: SolanaRpc>(
+    client: &C,
+    owner: &Pubkey,
+) -> Result<Vec<(Pubkey, Account, anchor_spl::token::TokenAccount)>, Error> {
+    use crate::solana_rpc_client_api::{
+        request::RpcRequest,
+        response::{Response, RpcKeyedAccount},
+    };
+    let params = serde_json::json!([
+        owner.to_string(),
+        { "programId": anchor_spl::token::ID.to_string() },
+        {
+            "encoding": "base64",
+            "commitment": client.commitment().commitment.to_string(),
+        },
+    ]);
+    let response: Response<Vec<RpcKeyedAccount>> = serde_json::from_value(
+        client
+            .send_request(RpcRequest::GetTokenAccountsByOwner, params)
+            .await?,
+    )
+    .map_err(DecodeError::other)?;
+    response
+        .value
+        .into_iter()
+        .filter_map(|keyed| {
+            keyed
+                .account
+                .decode::<Account>()
+                .map(|account| (keyed.pubkey, account))
+        })
+        .map(|(address, account)| -> Result<_, Error> {
+            let address = Pubkey::from_str(&address).map_err(DecodeError::from)?;
+            let token_account =
+                anchor_spl::token::TokenAccount::try_deserialize(&mut account.data.as_slice())?;
+            Ok((address, account, token_account))
+        })
+        .collect()
 }
 
 pub mod price {
@@ -202,7 +368,7 @@ pub mod price {
         Ok(feed_id)
     }
 
-    pub async fn get_with_max_age<C: AsRef<SolanaRpcClient>>(
+    pub async fn get_with_max_age<C: SolanaRpc>(
         client: &C,
         token: Token,
         max_age: Duration,
@@ -210,7 +376,7 @@ pub mod price {
         use helium_anchor_gen::anchor_lang::AccountDeserialize;
         let price_key = token.price_key().ok_or(PriceError::InvalidToken(token))?;
         let price_feed = token.price_feed().ok_or(PriceError::InvalidToken(token))?;
-        let account = client.as_ref().get_account(price_key).await?;
+        let account = client.get_account(price_key).await?;
         let PriceUpdateV2 { price_message, .. } =
             PriceUpdateV2::try_deserialize(&mut account.data.as_slice())?;
 
@@ -246,7 +412,7 @@ pub mod price {
         })
     }
 
-    pub async fn get<C: AsRef<SolanaRpcClient>>(client: &C, token: Token) -> Result<Price, Error> {
+    pub async fn get<C: SolanaRpc>(client: &C, token: Token) -> Result<Price, Error> {
         get_with_max_age(client, token, Duration::minutes(10)).await
     }
 }
@@ -321,6 +487,10 @@ impl Token {
         Self::from_allowed(s, &[Self::Iot, Self::Mobile, Self::Hnt, Self::Sol])
     }
 
+    pub fn redeemable_value_parser(s: &str) -> StdResult<Self, TokenError> {
+        Self::from_allowed(s, &[Self::Iot, Self::Mobile])
+    }
+
     pub fn pricekey_value_parser(s: &str) -> StdResult<Self, TokenError> {
         Self::from_allowed(s, &[Self::Iot, Self::Mobile, Self::Hnt])
     }
@@ -348,7 +518,7 @@ impl Token {
     }
 }
 
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct TokenBalance {
     #[serde(with = "serde_pubkey")]
     pub address: Pubkey,
@@ -458,6 +628,7 @@ impl Token {
             Self::Hnt => Some(&HNT_PRICE_KEY),
             Self::Iot => Some(&IOT_PRICE_KEY),
             Self::Mobile => Some(&MOBILE_PRICE_KEY),
+            Self::Sol => Some(&SOL_PRICE_KEY),
             _ => None,
         }
     }
@@ -467,6 +638,7 @@ impl Token {
             Self::Hnt => Some(&HNT_PRICE_FEED),
             Self::Iot => Some(&IOT_PRICE_FEED),
             Self::Mobile => Some(&MOBILE_PRICE_FEED),
+            Self::Sol => Some(&SOL_PRICE_FEED),
             _ => None,
         }
     }
@@ -482,3 +654,32 @@ impl Token {
         }
     }
 }
+
+#[cfg(all(test, feature = "client"))]
+mod tests {
+    use super::*;
+    use crate::client::mock::MockRpc;
+
+    #[tokio::test]
+    async fn transfers_are_packed() {
+        let rpc = MockRpc::new();
+        let payer = Pubkey::new_unique();
+        let opts = TransactionOpts {
+            lut_addresses: vec![],
+            ..Default::default()
+        };
+        let transfers = (0..50)
+            .map(|_| (Pubkey::new_unique(), Token::Hnt.amount(1)))
+            .collect::<Vec<_>>();
+        let packed = pack_transfers(&rpc, &transfers, &payer, &opts)
+            .await
+            .expect("pack");
+        assert!(packed.len() > 1);
+        assert_eq!(transfers, packed.transfers.concat());
+        // every payment keeps its account creation and transfer together
+        for (ixs, _) in packed.packer.batches() {
+            assert_eq!(0, ixs.len() % 2);
+        }
+        packed.message(&rpc, 0, &opts).await.expect("message");
+    }
+}
@@ -0,0 +1,215 @@
+//! Redemption of subdao tokens (IOT, MOBILE) for HNT through the treasury
+//! management program
+use crate::{
+    anchor_lang::{InstructionData, ToAccountMetas},
+    anchor_spl, circuit_breaker,
+    client::{GetAnchorAccount, SolanaRpcClient},
+    error::{DecodeError, EncodeError, Error},
+    keypair::{Keypair, Pubkey},
+    message, priority_fee,
+    solana_sdk::{instruction::Instruction, signer::Signer, transaction::VersionedTransaction},
+    token::{Token, TokenAmount},
+    treasury_management::{self, Curve, TreasuryManagementV0},
+    tx, TransactionOpts,
+};
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::*;
+use serde::Serialize;
+
+/// Curve parameters are stored scaled by this many decimals
+const CURVE_DECIMALS: u32 = 12;
+
+pub fn treasury_management_key(token: Token) -> Pubkey {
+    let (key, _) = Pubkey::find_program_address(
+        &[b"treasury_management", token.mint().as_ref()],
+        &treasury_management::id(),
+    );
+    key
+}
+
+fn treasury_circuit_breaker_key(treasury: &Pubkey) -> Pubkey {
+    let (key, _) = Pubkey::find_program_address(
+        &[b"account_windowed_breaker", treasury.as_ref()],
+        &circuit_breaker::id(),
+    );
+    key
+}
+
+/// A quote for redeeming an amount of a subdao token for HNT
+#[derive(Debug, Clone, Serialize)]
+pub struct Quote {
+    /// The amount of subdao tokens to redeem
+    pub amount: TokenAmount,
+    /// The HNT expected for the redeemed amount at the current treasury state
+    pub output: TokenAmount,
+    /// The minimum HNT to accept, after allowing for slippage
+    pub min_output: TokenAmount,
+    /// The effective price, in HNT per subdao token
+    pub price: Decimal,
+    /// Allowed slippage in basis points
+    pub slippage_bps: u16,
+    /// Time after which the treasury no longer allows redemptions
+    pub freeze_time: Option<DateTime<Utc>>,
+}
+
+/// Compute the treasury output for redeeming `amount` out of `supply` tokens
+/// against a treasury holding `reserve`, following the treasury's curve.
+///
+/// The exponential curve pays out `reserve * (1 - (1 - amount / supply) ^ (k
+/// + 1))`, which for k = 0 is the pro-rata share of the reserve. The output
+/// is computed in decimals and rounded down. Returns `None` if the curve can
+/// not be evaluated for the given values.
+pub fn redemption_output(curve: &Curve, reserve: u64, supply: u64, amount: u64) -> Option<u64> {
+    if supply == 0 || amount == 0 {
+        return Some(0);
+    }
+    let Curve::ExponentialCurveV0 { k } = curve;
+    let exponent = Decimal::try_from_i128_with_scale(i128::try_from(*k).ok()?, CURVE_DECIMALS)
+        .ok()?
+        .checked_add(Decimal::ONE)?;
+    let remaining = Decimal::ONE - Decimal::from(amount.min(supply)) / Decimal::from(supply);
+    let share = if remaining.is_zero() {
+        Decimal::ONE
+    } else {
+        Decimal::ONE - remaining.checked_powd(exponent)?
+    };
+    Decimal::from(reserve).checked_mul(share)?.floor().to_u64()
+}
+
+/// Get a quote for redeeming the given IOT or MOBILE amount for HNT, allowing
+/// for the given slippage in basis points.
+pub async fn quote<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    amount: TokenAmount,
+    slippage_bps: u16,
+) -> Result<Quote, Error> {
+    if !matches!(amount.token, Token::Iot | Token::Mobile) {
+        return Err(EncodeError::other(format!("{} can not be redeemed", amount.token)).into());
+    }
+    let treasury_management = client
+        .anchor_account::<TreasuryManagementV0>(&treasury_management_key(amount.token))
+        .await?;
+    let supply = client
+        .anchor_account::<anchor_spl::token::Mint>(amount.token.mint())
+        .await?
+        .supply;
+    let reserve = client
+        .anchor_account::<anchor_spl::token::TokenAccount>(&treasury_management.treasury)
+        .await?
+        .amount;
+    let output = redemption_output(&treasury_management.curve, reserve, supply, amount.amount)
+        .ok_or_else(|| DecodeError::other("invalid treasury curve"))?;
+    let min_output = (output as u128 * (10_000 - slippage_bps.min(10_000) as u128) / 10_000) as u64;
+    let price = if amount.amount == 0 {
+        Decimal::ZERO
+    } else {
+        let output = Decimal::new(
+            i64::try_from(output).map_err(DecodeError::other)?,
+            Token::Hnt.decimals().into(),
+        );
+        let input = Decimal::new(
+            i64::try_from(amount.amount).map_err(DecodeError::other)?,
+            amount.token.decimals().into(),
+        );
+        (output / input).round_dp(Token::Hnt.decimals().into())
+    };
+    Ok(Quote {
+        amount,
+        output: Token::Hnt.amount(output),
+        min_output: Token::Hnt.amount(min_output),
+        price: price.normalize(),
+        slippage_bps,
+        freeze_time: DateTime::from_timestamp(treasury_management.freeze_unix_time, 0),
+    })
+}
+
+pub const REDEEM_INSTRUCTION_BUDGET: u32 = 100_000;
+
+/// Construct a message that redeems the quoted amount for HNT, failing if
+/// less than the quoted minimum output would be received.
+pub async fn swap_message<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    quote: &Quote,
+    owner: &Pubkey,
+    opts: &TransactionOpts,
+) -> Result<(message::VersionedMessage, u64), Error> {
+    let token = quote.amount.token;
+    let treasury_management_key = treasury_management_key(token);
+    let treasury_management = client
+        .anchor_account::<TreasuryManagementV0>(&treasury_management_key)
+        .await?;
+    let accounts = treasury_management::accounts::RedeemV0 {
+        treasury_management: treasury_management_key,
+        treasury_mint: treasury_management.treasury_mint,
+        supply_mint: treasury_management.supply_mint,
+        treasury: treasury_management.treasury,
+        circuit_breaker: treasury_circuit_breaker_key(&treasury_management.treasury),
+        from: token.associated_token_adress(owner),
+        to: Token::Hnt.associated_token_adress(owner),
+        owner: *owner,
+        circuit_breaker_program: circuit_breaker::id(),
+        token_program: anchor_spl::token::ID,
+    };
+    let redeem_ix = Instruction {
+        program_id: treasury_management::id(),
+        accounts: accounts.to_account_metas(None),
+        data: treasury_management::instruction::RedeemV0 {
+            _args: treasury_management::RedeemArgsV0 {
+                amount: quote.amount.amount,
+                expected_output_amount: quote.min_output.amount,
+            },
+        }
+        .data(),
+    };
+    let ixs = &[
+        priority_fee::compute_budget_instruction(REDEEM_INSTRUCTION_BUDGET),
+        priority_fee::compute_price_instruction_for_accounts(client, &redeem_ix.accounts, opts)
+            .await?,
+        spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+            owner,
+            owner,
+            Token::Hnt.mint(),
+            &anchor_spl::token::spl_token::id(),
+        ),
+        redeem_ix,
+    ];
+    message::mk_message(client, ixs, opts, owner).await
+}
+
+pub async fn swap<C: AsRef<SolanaRpcClient> + GetAnchorAccount>(
+    client: &C,
+    quote: &Quote,
+    keypair: &Keypair,
+    opts: &TransactionOpts,
+) -> Result<(VersionedTransaction, u64), Error> {
+    let (msg, block_height) = swap_message(client, quote, &keypair.pubkey(), opts).await?;
+    let txn = tx::partial_sign(msg, &[keypair])?;
+    Ok((txn, block_height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear_curve_is_pro_rata() {
+        let curve = Curve::ExponentialCurveV0 { k: 0 };
+        assert_eq!(Some(250), redemption_output(&curve, 1_000, 4_000, 1_000));
+        assert_eq!(Some(1_000), redemption_output(&curve, 1_000, 4_000, 4_000));
+        assert_eq!(Some(0), redemption_output(&curve, 1_000, 0, 1_000));
+    }
+
+    #[test]
+    fn exponential_curve_pays_more_for_larger_redemptions() {
+        // k = 1: redeeming half the supply pays out three quarters
+        let curve = Curve::ExponentialCurveV0 {
+            k: 10u128.pow(CURVE_DECIMALS),
+        };
+        assert_eq!(Some(750), redemption_output(&curve, 1_000, 4_000, 2_000));
+        // Large reserves are paid out exactly
+        assert_eq!(
+            Some(7_500_000_000_000_000),
+            redemption_output(&curve, 10_000_000_000_000_000, 4_000, 2_000)
+        );
+    }
+}
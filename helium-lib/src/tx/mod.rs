@@ -0,0 +1,248 @@
+#[cfg(feature = "rpc")]
+use crate::{
+    client::SolanaRpc, error::TransactionError,
+    solana_rpc_client_api::config::RpcSendTransactionConfig,
+    solana_sdk::commitment_config::CommitmentConfig,
+};
+use crate::{
+    error::{EncodeError, Error},
+    keypair::{Pubkey, Signature},
+    solana_sdk::{
+        message::VersionedMessage, signer::signers::Signers, transaction::VersionedTransaction,
+    },
+};
+#[cfg(feature = "rpc")]
+use std::time::Duration;
+
+#[cfg(feature = "client")]
+pub mod outcome;
+#[cfg(feature = "rpc")]
+pub mod packer;
+#[cfg(feature = "client")]
+pub mod tracked;
+
+#[cfg(feature = "rpc")]
+pub use packer::TransactionPacker;
+#[cfg(feature = "client")]
+pub use tracked::{TrackedStatus, TrackedTransaction};
+
+/// Number of times [`send_and_confirm`] re-signs and resubmits an expired
+/// transaction by default
+#[cfg(feature = "rpc")]
+pub const DEFAULT_RESUBMISSIONS: u32 = 3;
+/// Interval between signature status checks while waiting for confirmation
+#[cfg(feature = "rpc")]
+pub(crate) const CONFIRM_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Sign a message with the given signers, leaving the signatures of any
+/// other required signers empty. This allows a separate fee payer, or any
+/// other signer, to add its signature later with [`sign`].
+pub fn partial_sign<T: Signers + ?Sized>(
+    message: VersionedMessage,
+    signers: &T,
+) -> Result<VersionedTransaction, Error> {
+    let mut tx = VersionedTransaction {
+        signatures: vec![Signature::default(); message.header().num_required_signatures.into()],
+        message,
+    };
+    sign(&mut tx, signers)?;
+    Ok(tx)
+}
+
+/// Add the signatures of the given signers to a transaction. Fails if one of
+/// the signers is not a required signer of the transaction.
+pub fn sign<T: Signers + ?Sized>(tx: &mut VersionedTransaction, signers: &T) -> Result<(), Error> {
+    let required = &tx.message.static_account_keys()[..tx.signatures.len()];
+    let positions = signers
+        .pubkeys()
+        .iter()
+        .map(|pubkey| {
+            required
+                .iter()
+                .position(|key| key == pubkey)
+                .ok_or_else(|| EncodeError::other(format!("{pubkey} is not a required signer")))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let signatures = signers.try_sign_message(&tx.message.serialize())?;
+    for (position, signature) in positions.into_iter().zip(signatures) {
+        tx.signatures[position] = signature;
+    }
+    Ok(())
+}
+
+/// The required signers of a transaction that did not sign it yet
+pub fn missing_signers(tx: &VersionedTransaction) -> Vec<Pubkey> {
+    tx.message
+        .static_account_keys()
+        .iter()
+        .zip(&tx.signatures)
+        .filter(|(_, signature)| **signature == Signature::default())
+        .map(|(key, _)| *key)
+        .collect()
+}
+
+/// Sign, send and confirm a transaction for the given message at the
+/// commitment of the client.
+///
+/// The message is expected to come from one of the message builders, along
+/// with the last block height its blockhash is valid for. While waiting for
+/// confirmation the transaction is rebroadcast until it is seen, at any
+/// commitment, in the signature history. It is only signed again with a new
+/// blockhash and resubmitted, up to the given number of times, once the
+/// finalized block height passes its last valid block height without it
+/// landing, so the old and new transaction can never both land.
+#[cfg(feature = "rpc")]
+pub async fn send_and_confirm<C, T>(
+    client: &C,
+    mut msg: VersionedMessage,
+    mut last_valid_block_height: u64,
+    signers: &T,
+    max_resubmissions: u32,
+    config: RpcSendTransactionConfig,
+) -> Result<Signature, Error>
+where
+    C: SolanaRpc,
+    T: Signers + ?Sized,
+{
+    let commitment = client.commitment();
+    let mut resubmissions = 0;
+    loop {
+        let tx = VersionedTransaction::try_new(msg.clone(), signers)?;
+        let signature = tx.signatures[0];
+        tracing::debug!(%signature, resubmissions, last_valid_block_height, "sending transaction");
+        let mut send_config = config;
+        let mut landed = false;
+        loop {
+            if !landed {
+                client
+                    .send_transaction_with_config(&tx, send_config)
+                    .await?;
+                // rebroadcasts skip preflight, which would reject a
+                // transaction that was processed in the meantime
+                send_config.skip_preflight = true;
+            }
+            crate::time::sleep(CONFIRM_POLL_INTERVAL).await;
+            landed = match signature_status(client, &signature).await? {
+                Some(status) => {
+                    if let Some(err) = status.err {
+                        return Err(TransactionError::new(err, vec![]).into());
+                    }
+                    if status.satisfies_commitment(commitment) {
+                        return Ok(signature);
+                    }
+                    true
+                }
+                None => false,
+            };
+            if landed
+                || client
+                    .get_block_height_with_commitment(CommitmentConfig::finalized())
+                    .await?
+                    <= last_valid_block_height
+            {
+                continue;
+            }
+            // The transaction can not land anymore once its blockhash
+            // expired, but it may have landed right before, so check one
+            // last time before signing a new one
+            landed = signature_status(client, &signature).await?.is_some();
+            if !landed {
+                break;
+            }
+        }
+        if resubmissions >= max_resubmissions {
+            return Err(Error::TransactionExpired(signature));
+        }
+        let (blockhash, block_height) = client
+            .get_latest_blockhash_with_commitment(commitment)
+            .await?;
+        msg.set_recent_blockhash(blockhash);
+        last_valid_block_height = block_height;
+        resubmissions += 1;
+    }
+}
+
+#[cfg(feature = "rpc")]
+async fn signature_status<C: SolanaRpc>(
+    client: &C,
+    signature: &Signature,
+) -> Result<Option<solana_transaction_status::TransactionStatus>, Error> {
+    Ok(client
+        .get_signature_statuses_with_history(&[*signature])
+        .await?
+        .into_iter()
+        .next()
+        .flatten())
+}
+
+#[cfg(all(test, feature = "client"))]
+mod tests {
+    use super::*;
+    use crate::{
+        client::mock::MockRpc,
+        keypair::Keypair,
+        message,
+        solana_sdk::{hash::Hash, signer::Signer, system_instruction},
+        TransactionOpts,
+    };
+
+    async fn expiring_message(rpc: &MockRpc, keypair: &Keypair) -> (VersionedMessage, u64) {
+        let opts = TransactionOpts {
+            lut_addresses: vec![],
+            ..Default::default()
+        };
+        let ix = system_instruction::transfer(&keypair.pubkey(), &Pubkey::new_unique(), 1);
+        rpc.set_block_height(100);
+        let message = message::mk_message(rpc, &[ix], &opts, &keypair.pubkey())
+            .await
+            .expect("message");
+        // the blockhash expires before the transaction lands
+        rpc.set_current_block_height(101);
+        rpc.set_latest_blockhash(Hash::new_unique(), 200);
+        message
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn expired_transaction_is_resubmitted() {
+        let rpc = MockRpc::new();
+        let keypair = Keypair::generate();
+        let (msg, last_valid_block_height) = expiring_message(&rpc, &keypair).await;
+
+        let signature = send_and_confirm(
+            &rpc,
+            msg,
+            last_valid_block_height,
+            &[&*keypair],
+            1,
+            RpcSendTransactionConfig::default(),
+        )
+        .await
+        .expect("send and confirm");
+        let sent = rpc.sent_transactions();
+        assert_eq!(2, sent.len());
+        assert_ne!(
+            sent[0].message.recent_blockhash(),
+            sent[1].message.recent_blockhash()
+        );
+        assert_eq!(sent[1].signatures[0], signature);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn expired_transaction_fails_without_resubmissions() {
+        let rpc = MockRpc::new();
+        let keypair = Keypair::generate();
+        let (msg, last_valid_block_height) = expiring_message(&rpc, &keypair).await;
+
+        let result = send_and_confirm(
+            &rpc,
+            msg,
+            last_valid_block_height,
+            &[&*keypair],
+            0,
+            RpcSendTransactionConfig::default(),
+        )
+        .await;
+        assert!(matches!(result, Err(Error::TransactionExpired(_))));
+        assert_eq!(1, rpc.sent_transactions().len());
+    }
+}
@@ -0,0 +1,351 @@
+use crate::{
+    anchor_lang::{AnchorDeserialize, Discriminator},
+    client::SolanaRpcClient,
+    data_credits,
+    error::{DecodeError, Error},
+    keypair::{Pubkey, Signature},
+    reward::ClaimableToken,
+    solana_client::rpc_config::RpcTransactionConfig,
+    solana_sdk::commitment_config::CommitmentConfig,
+    token::{Token, TokenAmount},
+};
+use serde::Serialize;
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, EncodedConfirmedTransactionWithStatusMeta,
+    EncodedTransaction, UiInstruction, UiMessage, UiParsedInstruction, UiTransactionEncoding,
+    UiTransactionTokenBalance,
+};
+use std::{collections::HashMap, str::FromStr};
+
+/// Instruction discriminators of the bubblegum program, which does not
+/// expose them
+const BUBBLEGUM_TRANSFER: [u8; 8] = [163, 52, 200, 231, 140, 3, 69, 186];
+const BUBBLEGUM_BURN: [u8; 8] = [116, 110, 29, 56, 107, 219, 42, 93];
+/// Offset of the leaf nonce in bubblegum transfer and burn instruction data,
+/// after the discriminator, root, data hash and creator hash
+const BUBBLEGUM_NONCE_OFFSET: usize = 8 + 3 * 32;
+
+/// Something a committed transaction did in one of the Helium programs.
+///
+/// The Helium programs do not emit anchor events for these, so outcomes are
+/// decoded from the instructions of a transaction and the token balance
+/// changes in its status meta.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Outcome {
+    /// Rewards paid out by a lazy distributor. The recipient is only known
+    /// when a single account received the token.
+    RewardsClaimed {
+        amount: TokenAmount,
+        #[serde(with = "crate::keypair::serde_opt_pubkey")]
+        recipient: Option<Pubkey>,
+    },
+    HotspotTransferred {
+        #[serde(with = "crate::keypair::serde_pubkey")]
+        asset: Pubkey,
+        #[serde(with = "crate::keypair::serde_pubkey")]
+        from: Pubkey,
+        #[serde(with = "crate::keypair::serde_pubkey")]
+        to: Pubkey,
+    },
+    HotspotBurned {
+        #[serde(with = "crate::keypair::serde_pubkey")]
+        asset: Pubkey,
+        #[serde(with = "crate::keypair::serde_pubkey")]
+        owner: Pubkey,
+    },
+    DcMinted {
+        amount: TokenAmount,
+        #[serde(with = "crate::keypair::serde_opt_pubkey")]
+        recipient: Option<Pubkey>,
+    },
+    DcDelegated {
+        amount: TokenAmount,
+        router_key: String,
+    },
+    DcBurned {
+        amount: TokenAmount,
+    },
+}
+
+/// Get the outcomes of a committed transaction. A failed transaction has no
+/// outcomes.
+pub async fn get<C: AsRef<SolanaRpcClient>>(
+    client: &C,
+    signature: &Signature,
+) -> Result<Vec<Outcome>, Error> {
+    let txn = client
+        .as_ref()
+        .get_transaction_with_config(
+            signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::JsonParsed),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+        .await?;
+    Ok(from_transaction(&txn)?)
+}
+
+/// Decode the outcomes of a transaction fetched with the `JsonParsed`
+/// encoding
+pub fn from_transaction(
+    txn: &EncodedConfirmedTransactionWithStatusMeta,
+) -> Result<Vec<Outcome>, DecodeError> {
+    let Some(meta) = &txn.transaction.meta else {
+        return Err(DecodeError::other("no transaction status found"));
+    };
+    if meta.err.is_some() {
+        return Ok(vec![]);
+    }
+    let EncodedTransaction::Json(ui_txn) = &txn.transaction.transaction else {
+        return Err(DecodeError::other("not a json encoded transaction"));
+    };
+    let UiMessage::Parsed(ui_msg) = &ui_txn.message else {
+        return Err(DecodeError::other("not a parsed transaction message"));
+    };
+    let balance_changes =
+        BalanceChanges::from_meta(&meta.pre_token_balances, &meta.post_token_balances)?;
+
+    let mut outcomes = vec![];
+    for ui_ix in &ui_msg.instructions {
+        let UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(decoded)) = ui_ix else {
+            continue;
+        };
+        let program_id = Pubkey::from_str(&decoded.program_id)?;
+        let accounts = decoded
+            .accounts
+            .iter()
+            .map(|account| Pubkey::from_str(account))
+            .collect::<Result<Vec<_>, _>>()?;
+        let data = solana_sdk::bs58::decode(&decoded.data).into_vec()?;
+        if let Some(outcome) = from_instruction(&program_id, &accounts, &data, &balance_changes)? {
+            outcomes.push(outcome);
+        }
+    }
+    outcomes.extend(balance_changes.rewards_claimed());
+    Ok(outcomes)
+}
+
+/// Decode the outcome of a single instruction, using the token balance
+/// changes of its transaction for amounts the instruction does not carry
+fn from_instruction(
+    program_id: &Pubkey,
+    accounts: &[Pubkey],
+    data: &[u8],
+    balance_changes: &BalanceChanges,
+) -> Result<Option<Outcome>, DecodeError> {
+    if data.len() < 8 {
+        return Ok(None);
+    }
+    let mut discriminator: [u8; 8] = Default::default();
+    discriminator.copy_from_slice(&data[..8]);
+    let mut args = &data[8..];
+    let account = |index: usize| {
+        accounts
+            .get(index)
+            .copied()
+            .ok_or_else(|| DecodeError::other("missing account in instruction"))
+    };
+
+    let outcome = if *program_id == mpl_bubblegum::ID {
+        match discriminator {
+            BUBBLEGUM_TRANSFER => Outcome::HotspotTransferred {
+                asset: mpl_bubblegum::utils::get_asset_id(&account(4)?, leaf_nonce(data)?),
+                from: account(1)?,
+                to: account(3)?,
+            },
+            BUBBLEGUM_BURN => Outcome::HotspotBurned {
+                asset: mpl_bubblegum::utils::get_asset_id(&account(3)?, leaf_nonce(data)?),
+                owner: account(1)?,
+            },
+            _ => return Ok(None),
+        }
+    } else if *program_id == data_credits::id() {
+        match discriminator {
+            data_credits::instruction::MintDataCreditsV0::DISCRIMINATOR => {
+                let (recipient, amount) = balance_changes.received(Token::Dc.mint());
+                Outcome::DcMinted {
+                    amount: TokenAmount::from_u64(Token::Dc, amount),
+                    recipient,
+                }
+            }
+            data_credits::instruction::DelegateDataCreditsV0::DISCRIMINATOR => {
+                let args = data_credits::DelegateDataCreditsArgsV0::deserialize(&mut args)?;
+                Outcome::DcDelegated {
+                    amount: TokenAmount::from_u64(Token::Dc, args.amount),
+                    router_key: args.router_key,
+                }
+            }
+            data_credits::instruction::BurnWithoutTrackingV0::DISCRIMINATOR => {
+                let args = data_credits::BurnWithoutTrackingArgsV0::deserialize(&mut args)?;
+                Outcome::DcBurned {
+                    amount: TokenAmount::from_u64(Token::Dc, args.amount),
+                }
+            }
+            data_credits::instruction::BurnDelegatedDataCreditsV0::DISCRIMINATOR => {
+                let args = data_credits::BurnDelegatedDataCreditsArgsV0::deserialize(&mut args)?;
+                Outcome::DcBurned {
+                    amount: TokenAmount::from_u64(Token::Dc, args.amount),
+                }
+            }
+            _ => return Ok(None),
+        }
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(outcome))
+}
+
+fn leaf_nonce(data: &[u8]) -> Result<u64, DecodeError> {
+    data.get(BUBBLEGUM_NONCE_OFFSET..BUBBLEGUM_NONCE_OFFSET + 8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or_else(|| DecodeError::other("missing leaf nonce"))
+}
+
+/// Token balance changes of a transaction by mint and owner
+#[derive(Debug, Default)]
+struct BalanceChanges(HashMap<(Pubkey, Option<Pubkey>), i128>);
+
+impl BalanceChanges {
+    fn from_meta(
+        pre_balances: &OptionSerializer<Vec<UiTransactionTokenBalance>>,
+        post_balances: &OptionSerializer<Vec<UiTransactionTokenBalance>>,
+    ) -> Result<Self, DecodeError> {
+        let mut changes = Self::default();
+        let (OptionSerializer::Some(pre_balances), OptionSerializer::Some(post_balances)) =
+            (pre_balances, post_balances)
+        else {
+            return Ok(changes);
+        };
+        let mut add = |balance: &UiTransactionTokenBalance, sign: i128| {
+            let mint = Pubkey::from_str(&balance.mint)?;
+            let owner = match &balance.owner {
+                OptionSerializer::Some(owner) => Some(Pubkey::from_str(owner)?),
+                _ => None,
+            };
+            let amount: i128 = balance.ui_token_amount.amount.parse().unwrap_or_default();
+            *changes.0.entry((mint, owner)).or_default() += sign * amount;
+            Ok::<_, DecodeError>(())
+        };
+        pre_balances
+            .iter()
+            .try_for_each(|balance| add(balance, -1))?;
+        post_balances
+            .iter()
+            .try_for_each(|balance| add(balance, 1))?;
+        Ok(changes)
+    }
+
+    fn change(&self, mint: &Pubkey, owner: &Pubkey) -> i128 {
+        self.0
+            .get(&(*mint, Some(*owner)))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The total amount of a mint received, along with the receiving owner
+    /// if there was only one
+    fn received(&self, mint: &Pubkey) -> (Option<Pubkey>, u64) {
+        self.received_except(mint, None)
+    }
+
+    fn received_except(&self, mint: &Pubkey, except: Option<&Pubkey>) -> (Option<Pubkey>, u64) {
+        let receivers = self
+            .0
+            .iter()
+            .filter(|((change_mint, owner), change)| {
+                change_mint == mint && **change > 0 && owner.as_ref() != except
+            })
+            .collect::<Vec<_>>();
+        let amount = receivers.iter().map(|(_, change)| **change).sum::<i128>();
+        let recipient = match receivers.as_slice() {
+            [((_, owner), _)] => *owner,
+            _ => None,
+        };
+        (recipient, u64::try_from(amount).unwrap_or_default())
+    }
+
+    /// Rewards paid out of the lazy distributor escrows
+    fn rewards_claimed(&self) -> Vec<Outcome> {
+        [
+            ClaimableToken::Hnt,
+            ClaimableToken::Iot,
+            ClaimableToken::Mobile,
+        ]
+        .into_iter()
+        .filter_map(|token| {
+            let lazy_distributor_key = token.lazy_distributor_key();
+            let paid = u64::try_from(-self.change(token.mint(), &lazy_distributor_key)).ok()?;
+            if paid == 0 {
+                return None;
+            }
+            let (recipient, _) = self.received_except(token.mint(), Some(&lazy_distributor_key));
+            Some(Outcome::RewardsClaimed {
+                amount: TokenAmount::from_u64(token.into(), paid),
+                recipient,
+            })
+        })
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dao::SubDao;
+
+    #[test]
+    fn decodes_instructions() {
+        let owner = Pubkey::new_unique();
+        let ix = crate::dc::delegate_instruction(SubDao::Iot, "router", 100, &owner);
+        let accounts = ix
+            .accounts
+            .iter()
+            .map(|meta| meta.pubkey)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            Some(Outcome::DcDelegated {
+                amount: TokenAmount::from_u64(Token::Dc, 100),
+                router_key: "router".to_string(),
+            }),
+            from_instruction(&ix.program_id, &accounts, &ix.data, &Default::default())
+                .expect("delegate outcome")
+        );
+
+        let (merkle_tree, recipient) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let ix = mpl_bubblegum::instructions::Transfer {
+            tree_config: Pubkey::new_unique(),
+            leaf_owner: (owner, true),
+            leaf_delegate: (owner, false),
+            new_leaf_owner: recipient,
+            merkle_tree,
+            log_wrapper: Pubkey::new_unique(),
+            compression_program: Pubkey::new_unique(),
+            system_program: Pubkey::new_unique(),
+        }
+        .instruction(mpl_bubblegum::instructions::TransferInstructionArgs {
+            root: [0; 32],
+            data_hash: [0; 32],
+            creator_hash: [0; 32],
+            nonce: 42,
+            index: 42,
+        });
+        let accounts = ix
+            .accounts
+            .iter()
+            .map(|meta| meta.pubkey)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            Some(Outcome::HotspotTransferred {
+                asset: mpl_bubblegum::utils::get_asset_id(&merkle_tree, 42),
+                from: owner,
+                to: recipient,
+            }),
+            from_instruction(&ix.program_id, &accounts, &ix.data, &Default::default())
+                .expect("transfer outcome")
+        );
+    }
+}
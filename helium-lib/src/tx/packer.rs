@@ -0,0 +1,267 @@
+use crate::{
+    client::SolanaRpc,
+    error::{EncodeError, Error},
+    keypair::Pubkey,
+    message::{self, DurableNonce, VersionedMessage},
+    priority_fee,
+    solana_sdk::{
+        address_lookup_table::AddressLookupTableAccount,
+        instruction::{AccountMeta, Instruction},
+    },
+    TransactionOpts,
+};
+
+/// Packs instructions into as few transactions as possible.
+///
+/// Instructions are added in order along with the compute units they need,
+/// and each one goes into the current transaction as long as that stays
+/// within the packet size and compute unit limits, including room for the
+/// compute budget instructions and the nonce advance instruction when a
+/// durable nonce is used. Otherwise a new transaction is started.
+/// Instructions that depend on each other should be added as one group
+/// with [`TransactionPacker::push_group`] so they end up in the same
+/// transaction.
+#[derive(Debug, Clone)]
+pub struct TransactionPacker {
+    payer: Pubkey,
+    signers: usize,
+    lut_accounts: Vec<AddressLookupTableAccount>,
+    durable_nonce: Option<DurableNonce>,
+    max_compute_units: u32,
+    batches: Vec<Batch>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Batch {
+    ixs: Vec<Instruction>,
+    compute_units: u32,
+}
+
+impl TransactionPacker {
+    /// A packer for transactions paid and signed by the given payer, without
+    /// lookup tables
+    pub fn new(payer: Pubkey) -> Self {
+        Self {
+            payer,
+            signers: 1,
+            lut_accounts: vec![],
+            durable_nonce: None,
+            max_compute_units: priority_fee::MAX_COMPUTE_UNITS,
+            batches: vec![],
+        }
+    }
+
+    /// A packer for the fee payer, lookup tables and durable nonce of the
+    /// given options
+    pub async fn for_opts<C: SolanaRpc>(
+        client: &C,
+        payer: Pubkey,
+        opts: &TransactionOpts,
+    ) -> Result<Self, Error> {
+        let lut_accounts = message::get_lut_accounts(client, &opts.lut_addresses).await?;
+        let mut signers = vec![payer];
+        signers.extend(opts.fee_payer);
+        signers.extend(opts.durable_nonce.map(|nonce| nonce.authority));
+        signers.sort_unstable();
+        signers.dedup();
+        Ok(Self::new(opts.fee_payer.unwrap_or(payer))
+            .with_lut_accounts(lut_accounts)
+            .with_durable_nonce(opts.durable_nonce)
+            .with_signers(signers.len()))
+    }
+
+    /// Set the number of signatures every transaction needs
+    pub fn with_signers(mut self, signers: usize) -> Self {
+        self.signers = signers.max(1);
+        self
+    }
+
+    /// Count one more signature every transaction needs, such as a co-signing
+    /// authority
+    pub fn with_cosigner(mut self) -> Self {
+        self.signers += 1;
+        self
+    }
+
+    pub fn with_lut_accounts(mut self, lut_accounts: Vec<AddressLookupTableAccount>) -> Self {
+        self.lut_accounts = lut_accounts;
+        self
+    }
+
+    /// Leave room for the advance instruction of the given durable nonce in
+    /// every transaction
+    pub fn with_durable_nonce(mut self, durable_nonce: Option<DurableNonce>) -> Self {
+        self.durable_nonce = durable_nonce;
+        self
+    }
+
+    pub fn with_max_compute_units(mut self, max_compute_units: u32) -> Self {
+        self.max_compute_units = max_compute_units.min(priority_fee::MAX_COMPUTE_UNITS);
+        self
+    }
+
+    /// Add an instruction that needs the given compute units
+    pub fn push(&mut self, ix: Instruction, compute_units: u32) -> Result<(), Error> {
+        self.push_group(vec![ix], compute_units)
+    }
+
+    /// Add instructions that have to be in the same transaction, and need
+    /// the given compute units together. Fails if the instructions do not
+    /// fit in a transaction on their own.
+    pub fn push_group(&mut self, ixs: Vec<Instruction>, compute_units: u32) -> Result<(), Error> {
+        let (payer, signers, lut_accounts) = (&self.payer, self.signers, &self.lut_accounts);
+        let (max_compute_units, durable_nonce) = (self.max_compute_units, self.durable_nonce);
+        let fits = |batch: &Batch| {
+            let mut candidate: Vec<Instruction> = durable_nonce
+                .iter()
+                .map(DurableNonce::advance_instruction)
+                .collect();
+            candidate.extend(budget_instructions(batch.compute_units + compute_units));
+            candidate.extend(batch.ixs.iter().cloned());
+            candidate.extend(ixs.iter().cloned());
+            batch.compute_units + compute_units <= max_compute_units
+                && message::fits_in_transaction_with_luts(&candidate, payer, signers, lut_accounts)
+        };
+        match self.batches.last_mut() {
+            Some(batch) if fits(batch) => {
+                batch.ixs.extend(ixs);
+                batch.compute_units += compute_units;
+            }
+            _ if fits(&Batch::default()) => self.batches.push(Batch { ixs, compute_units }),
+            _ => return Err(EncodeError::other("instructions do not fit in a transaction").into()),
+        }
+        Ok(())
+    }
+
+    /// The number of transactions the instructions are packed into so far
+    pub fn len(&self) -> usize {
+        self.batches.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.batches.is_empty()
+    }
+
+    /// The packed instructions of every transaction, without compute budget
+    /// instructions, along with the compute units they need
+    pub fn batches(&self) -> impl Iterator<Item = (&[Instruction], u32)> {
+        self.batches
+            .iter()
+            .map(|batch| (batch.ixs.as_slice(), batch.compute_units))
+    }
+
+    /// Construct the message of the packed transaction at the given index,
+    /// with a compute budget and a priority fee for the accounts it writes,
+    /// along with the last block height the message is valid for.
+    ///
+    /// Every message gets a fresh blockhash, so when sending a number of
+    /// transactions construct each one just before sending it.
+    pub async fn message<C: SolanaRpc>(
+        &self,
+        client: &C,
+        index: usize,
+        opts: &TransactionOpts,
+    ) -> Result<(VersionedMessage, u64), Error> {
+        let batch = self
+            .batches
+            .get(index)
+            .ok_or_else(|| EncodeError::other(format!("no packed transaction {index}")))?;
+        let accounts: Vec<AccountMeta> = batch
+            .ixs
+            .iter()
+            .flat_map(|ix| ix.accounts.iter().cloned())
+            .collect();
+        let mut ixs = vec![
+            priority_fee::compute_budget_instruction(batch.compute_units),
+            priority_fee::compute_price_instruction_for_accounts(client, &accounts, opts).await?,
+        ];
+        ixs.extend(batch.ixs.iter().cloned());
+        message::mk_message(client, &ixs, opts, &self.payer).await
+    }
+
+    /// Construct a message for every packed transaction. See
+    /// [`TransactionPacker::message`].
+    pub async fn messages<C: SolanaRpc>(
+        &self,
+        client: &C,
+        opts: &TransactionOpts,
+    ) -> Result<Vec<(VersionedMessage, u64)>, Error> {
+        let mut messages = Vec::with_capacity(self.batches.len());
+        for index in 0..self.batches.len() {
+            messages.push(self.message(client, index, opts).await?);
+        }
+        Ok(messages)
+    }
+}
+
+/// Compute budget instructions as they end up in a packed transaction. The
+/// price is a placeholder of the same encoded size as the real price.
+fn budget_instructions(compute_units: u32) -> Vec<Instruction> {
+    vec![
+        priority_fee::compute_budget_instruction(compute_units),
+        priority_fee::compute_price_instruction(0),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solana_sdk::system_instruction;
+
+    #[test]
+    fn packs_into_fewest_transactions() {
+        let payer = Pubkey::new_unique();
+        let mut packer = TransactionPacker::new(payer);
+        let transfers = (0..100)
+            .map(|_| system_instruction::transfer(&payer, &Pubkey::new_unique(), 1))
+            .collect::<Vec<_>>();
+        for ix in &transfers {
+            packer.push(ix.clone(), 1_000).expect("push");
+        }
+        assert!(packer.len() > 1);
+        let packed = packer.batches().map(|(ixs, _)| ixs.len()).sum::<usize>();
+        assert_eq!(transfers.len(), packed);
+        // every transaction but the last is full
+        let batches = packer.batches().collect::<Vec<_>>();
+        for (ixs, compute_units) in &batches[..batches.len() - 1] {
+            let mut candidate = budget_instructions(*compute_units);
+            candidate.extend(ixs.iter().cloned());
+            assert!(message::fits_in_transaction(&candidate, &payer, 1));
+            candidate.push(system_instruction::transfer(
+                &payer,
+                &Pubkey::new_unique(),
+                1,
+            ));
+            assert!(!message::fits_in_transaction(&candidate, &payer, 1));
+        }
+
+        let mut packer = TransactionPacker::new(payer).with_max_compute_units(2_500);
+        for ix in transfers.iter().take(5) {
+            packer.push(ix.clone(), 1_000).expect("push");
+        }
+        assert_eq!(3, packer.len());
+        assert!(packer.push(transfers[0].clone(), 3_000).is_err());
+    }
+
+    #[test]
+    fn leaves_room_for_nonce_advance() {
+        let payer = Pubkey::new_unique();
+        let durable_nonce = DurableNonce {
+            account: Pubkey::new_unique(),
+            authority: payer,
+        };
+        let transfers = (0..100)
+            .map(|_| system_instruction::transfer(&payer, &Pubkey::new_unique(), 1))
+            .collect::<Vec<_>>();
+        let mut packer = TransactionPacker::new(payer).with_durable_nonce(Some(durable_nonce));
+        for ix in &transfers {
+            packer.push(ix.clone(), 1_000).expect("push");
+        }
+        for (ixs, compute_units) in packer.batches() {
+            let mut candidate = vec![durable_nonce.advance_instruction()];
+            candidate.extend(budget_instructions(compute_units));
+            candidate.extend(ixs.iter().cloned());
+            assert!(message::fits_in_transaction(&candidate, &payer, 1));
+        }
+    }
+}
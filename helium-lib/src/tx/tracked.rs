@@ -0,0 +1,286 @@
+use super::{outcome, CONFIRM_POLL_INTERVAL};
+use crate::{
+    client::SolanaRpcClient,
+    error::{Error, TransactionError},
+    keypair::Signature,
+    solana_client::rpc_config::{RpcSendTransactionConfig, RpcTransactionConfig},
+    solana_sdk::{commitment_config::CommitmentConfig, transaction::VersionedTransaction},
+};
+use serde::ser::SerializeStruct;
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, TransactionConfirmationStatus, UiTransactionEncoding,
+};
+
+/// The state of a tracked transaction, as last seen.
+///
+/// Landing and decoding the outcomes of a transaction are tracked
+/// separately: outcomes can only be fetched once a transaction is
+/// confirmed, and fetching or decoding them can fail while the transaction
+/// itself landed just fine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrackedStatus {
+    /// Not seen by the network (yet)
+    Pending,
+    /// Landed at the given commitment, without decoded outcomes (yet)
+    Landed(TransactionConfirmationStatus),
+    /// Landed at the given commitment, with its outcomes decoded
+    Decoded(TransactionConfirmationStatus),
+}
+
+impl TrackedStatus {
+    /// The commitment the transaction reached, if it landed
+    pub fn commitment(&self) -> Option<&TransactionConfirmationStatus> {
+        match self {
+            Self::Pending => None,
+            Self::Landed(commitment) | Self::Decoded(commitment) => Some(commitment),
+        }
+    }
+
+    pub fn is_landed(&self) -> bool {
+        self.commitment().is_some()
+    }
+}
+
+/// A transaction submitted to the network, along with its confirmation
+/// state as last seen.
+///
+/// The state is refreshed with [`TrackedTransaction::status`], or by
+/// waiting for a commitment with [`TrackedTransaction::confirm`]. A
+/// transaction that failed on chain is confirmed like any other, with its
+/// decoded failure in `error`.
+#[derive(Debug)]
+pub struct TrackedTransaction {
+    pub signature: Signature,
+    /// The slot the transaction was processed in, once seen
+    pub slot: Option<u64>,
+    /// The commitment the transaction reached, once seen
+    pub commitment: Option<TransactionConfirmationStatus>,
+    /// The failure of a transaction that was processed but failed
+    pub error: Option<TransactionError>,
+    /// What the transaction did, once it is confirmed
+    pub outcomes: Option<Vec<outcome::Outcome>>,
+    /// The last block height the transaction can land at, if known
+    pub last_valid_block_height: Option<u64>,
+}
+
+impl TrackedTransaction {
+    pub fn new(signature: Signature) -> Self {
+        Self {
+            signature,
+            slot: None,
+            commitment: None,
+            error: None,
+            outcomes: None,
+            last_valid_block_height: None,
+        }
+    }
+
+    /// Lets [`TrackedTransaction::confirm`] fail once the transaction can
+    /// no longer land, rather than waiting indefinitely
+    pub fn with_last_valid_block_height(mut self, last_valid_block_height: u64) -> Self {
+        self.last_valid_block_height = Some(last_valid_block_height);
+        self
+    }
+
+    /// Send a signed transaction and start tracking it
+    pub async fn send<C: AsRef<SolanaRpcClient>>(
+        client: &C,
+        tx: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> Result<Self, Error> {
+        let signature = client
+            .as_ref()
+            .send_transaction_with_config(tx, config)
+            .await?;
+        Ok(Self::new(signature))
+    }
+
+    /// Whether the transaction was seen without failing
+    pub fn is_success(&self) -> bool {
+        self.commitment.is_some() && self.error.is_none()
+    }
+
+    /// The state of the transaction as last seen
+    pub fn state(&self) -> TrackedStatus {
+        match (&self.commitment, &self.outcomes) {
+            (None, _) => TrackedStatus::Pending,
+            (Some(commitment), None) => TrackedStatus::Landed(commitment.clone()),
+            (Some(commitment), Some(_)) => TrackedStatus::Decoded(commitment.clone()),
+        }
+    }
+
+    /// Refresh the state of the transaction from the network.
+    ///
+    /// Once the transaction is confirmed, or has failed, it is fetched to
+    /// decode its outcomes and the logs of the error it failed with. This
+    /// is best effort: a transaction that can not be fetched (yet), or not
+    /// be decoded, still counts as landed, and leaves `outcomes` unset to be
+    /// tried again on the next refresh.
+    pub async fn status<C: AsRef<SolanaRpcClient>>(
+        &mut self,
+        client: &C,
+    ) -> Result<TrackedStatus, Error> {
+        let client = client.as_ref();
+        let Some(status) = client
+            .get_signature_statuses(&[self.signature])
+            .await?
+            .value
+            .into_iter()
+            .next()
+            .flatten()
+        else {
+            return Ok(TrackedStatus::Pending);
+        };
+        let commitment = status.confirmation_status();
+        self.slot = Some(status.slot);
+        self.commitment = Some(commitment.clone());
+        let confirmed = satisfies(&commitment, CommitmentConfig::confirmed());
+        if self.outcomes.is_none() && (confirmed || status.err.is_some()) {
+            // Transactions can only be fetched once confirmed, so a failure
+            // seen before that is decoded without logs until then
+            let txn = if confirmed {
+                self.fetch(client)
+                    .await
+                    // getTransaction often has nothing yet right after
+                    // confirmation
+                    .inspect_err(|err| {
+                        tracing::debug!(
+                            signature = %self.signature,
+                            %err,
+                            "transaction not fetched"
+                        )
+                    })
+                    .ok()
+            } else {
+                None
+            };
+            if let Some(err) = status.err {
+                let logs = txn
+                    .as_ref()
+                    .and_then(|txn| txn.transaction.meta.as_ref())
+                    .and_then(|meta| meta.log_messages.clone().into())
+                    .unwrap_or_default();
+                self.error = Some(TransactionError::new(err, logs));
+            }
+            if let Some(txn) = txn {
+                match outcome::from_transaction(&txn) {
+                    Ok(outcomes) => self.outcomes = Some(outcomes),
+                    Err(err) => tracing::warn!(
+                        signature = %self.signature,
+                        %err,
+                        "decoding transaction outcomes failed"
+                    ),
+                }
+            }
+        }
+        Ok(self.state())
+    }
+
+    /// Wait for the transaction to reach the given commitment.
+    ///
+    /// Fails with [`Error::TransactionExpired`] when the transaction has not
+    /// been seen by its last valid block height, if that is known.
+    pub async fn confirm<C: AsRef<SolanaRpcClient>>(
+        &mut self,
+        client: &C,
+        commitment: CommitmentConfig,
+    ) -> Result<(), Error> {
+        loop {
+            match self.status(client).await? {
+                TrackedStatus::Pending => {
+                    if let Some(last_valid_block_height) = self.last_valid_block_height {
+                        let block_height = client.as_ref().get_block_height().await?;
+                        // Check once more now that the blockhash expired,
+                        // in case the transaction landed right before
+                        if block_height > last_valid_block_height
+                            && !self.status(client).await?.is_landed()
+                        {
+                            return Err(Error::TransactionExpired(self.signature));
+                        }
+                    }
+                }
+                status => {
+                    if status
+                        .commitment()
+                        .is_some_and(|status| satisfies(status, commitment))
+                    {
+                        return Ok(());
+                    }
+                }
+            }
+            crate::time::sleep(CONFIRM_POLL_INTERVAL).await;
+        }
+    }
+
+    async fn fetch(
+        &self,
+        client: &SolanaRpcClient,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta, Error> {
+        Ok(client
+            .get_transaction_with_config(
+                &self.signature,
+                RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::JsonParsed),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+            .await?)
+    }
+}
+
+fn satisfies(status: &TransactionConfirmationStatus, commitment: CommitmentConfig) -> bool {
+    match status {
+        TransactionConfirmationStatus::Finalized => true,
+        TransactionConfirmationStatus::Confirmed => !commitment.is_finalized(),
+        TransactionConfirmationStatus::Processed => !commitment.is_at_least_confirmed(),
+    }
+}
+
+impl serde::Serialize for TrackedTransaction {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("TrackedTransaction", 5)?;
+        state.serialize_field("signature", &self.signature.to_string())?;
+        state.serialize_field("slot", &self.slot)?;
+        state.serialize_field("commitment", &self.commitment)?;
+        state.serialize_field("error", &self.error.as_ref().map(|error| error.to_string()))?;
+        state.serialize_field("outcomes", &self.outcomes)?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commitment_satisfaction() {
+        use TransactionConfirmationStatus::*;
+        let finalized = CommitmentConfig::finalized();
+        let confirmed = CommitmentConfig::confirmed();
+        let processed = CommitmentConfig::processed();
+        assert!(satisfies(&Processed, processed));
+        assert!(!satisfies(&Processed, confirmed));
+        assert!(satisfies(&Confirmed, confirmed));
+        assert!(!satisfies(&Confirmed, finalized));
+        assert!(satisfies(&Finalized, finalized));
+    }
+
+    #[test]
+    fn landed_without_outcomes() {
+        let mut tracked = TrackedTransaction::new(Signature::default());
+        assert_eq!(TrackedStatus::Pending, tracked.state());
+        tracked.commitment = Some(TransactionConfirmationStatus::Confirmed);
+        assert_eq!(
+            TrackedStatus::Landed(TransactionConfirmationStatus::Confirmed),
+            tracked.state()
+        );
+        assert!(tracked.state().is_landed());
+        assert!(tracked.is_success());
+        tracked.outcomes = Some(vec![]);
+        assert_eq!(
+            TrackedStatus::Decoded(TransactionConfirmationStatus::Confirmed),
+            tracked.state()
+        );
+    }
+}
@@ -0,0 +1,183 @@
+//! Events for a watched wallet address.
+//!
+//! Token payments are decoded from changes to the address and its associated
+//! token accounts, as delivered by account subscriptions. Hotspot transfers
+//! and Data Credit delegations leave no trace in those accounts, so they are
+//! decoded from the [`Outcome`]s of the transactions that mention the
+//! address.
+
+use crate::{
+    error::Error,
+    keypair::Pubkey,
+    solana_sdk::account::Account,
+    token::{self, Token, TokenAmount, TokenBalance},
+    tx::outcome::Outcome,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    /// The balance of a token went up. SOL changes include fees.
+    TokenReceived { amount: TokenAmount },
+    /// The balance of a token went down
+    TokenSent { amount: TokenAmount },
+    HotspotReceived {
+        #[serde(with = "crate::keypair::serde_pubkey")]
+        asset: Pubkey,
+        #[serde(with = "crate::keypair::serde_pubkey")]
+        from: Pubkey,
+    },
+    HotspotSent {
+        #[serde(with = "crate::keypair::serde_pubkey")]
+        asset: Pubkey,
+        #[serde(with = "crate::keypair::serde_pubkey")]
+        to: Pubkey,
+    },
+    HotspotBurned {
+        #[serde(with = "crate::keypair::serde_pubkey")]
+        asset: Pubkey,
+    },
+    DcDelegated {
+        amount: TokenAmount,
+        router_key: String,
+    },
+}
+
+/// The token balances of watched accounts, turning account updates into
+/// balance change events
+#[derive(Debug, Default)]
+pub struct Balances(HashMap<Pubkey, TokenAmount>);
+
+impl Balances {
+    /// Start from the given balances. Accounts without a balance are
+    /// assumed to be empty.
+    pub fn new(balances: Vec<TokenBalance>) -> Self {
+        Self(
+            balances
+                .into_iter()
+                .map(|balance| (balance.address, balance.amount))
+                .collect(),
+        )
+    }
+
+    /// The last known balance of an account
+    pub fn get(&self, address: &Pubkey) -> Option<TokenAmount> {
+        self.0.get(address).copied()
+    }
+
+    /// Apply an update of the given account of a token, returning the event
+    /// for the balance change if there was one. A closed account has no
+    /// balance left.
+    pub fn update(
+        &mut self,
+        address: &Pubkey,
+        token: Token,
+        account: Option<Account>,
+    ) -> Result<Option<Event>, Error> {
+        let amount = match account {
+            Some(account) if account.lamports > 0 => {
+                token::balance_from_account(address, account)?.amount
+            }
+            _ => token.amount(0),
+        };
+        let previous = self
+            .0
+            .insert(*address, amount)
+            .map_or(0, |previous| previous.amount);
+        let event = match amount.amount.cmp(&previous) {
+            std::cmp::Ordering::Greater => Event::TokenReceived {
+                amount: token.amount(amount.amount - previous),
+            },
+            std::cmp::Ordering::Less => Event::TokenSent {
+                amount: token.amount(previous - amount.amount),
+            },
+            std::cmp::Ordering::Equal => return Ok(None),
+        };
+        Ok(Some(event))
+    }
+}
+
+/// The events for an address in the outcomes of a transaction that mentions
+/// it. Token movements are left to [`Balances`], which sees every change.
+pub fn outcome_events(address: &Pubkey, outcomes: Vec<Outcome>) -> Vec<Event> {
+    outcomes
+        .into_iter()
+        .filter_map(|outcome| match outcome {
+            Outcome::HotspotTransferred { asset, from, to } if to == *address => {
+                Some(Event::HotspotReceived { asset, from })
+            }
+            Outcome::HotspotTransferred { asset, from, to } if from == *address => {
+                Some(Event::HotspotSent { asset, to })
+            }
+            Outcome::HotspotBurned { asset, owner } if owner == *address => {
+                Some(Event::HotspotBurned { asset })
+            }
+            // Only the owner of the delegated DC signs a delegation
+            Outcome::DcDelegated { amount, router_key } => {
+                Some(Event::DcDelegated { amount, router_key })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn balance_events() {
+        let address = Pubkey::new_unique();
+        let mut balances = Balances::new(vec![Token::Sol.to_balance(address, 500)]);
+        let account = |lamports| Account {
+            lamports,
+            owner: crate::solana_sdk::system_program::ID,
+            ..Default::default()
+        };
+        assert_eq!(
+            Some(Event::TokenReceived {
+                amount: Token::Sol.amount(250)
+            }),
+            balances
+                .update(&address, Token::Sol, Some(account(750)))
+                .expect("update")
+        );
+        assert_eq!(
+            None,
+            balances
+                .update(&address, Token::Sol, Some(account(750)))
+                .expect("update")
+        );
+        assert_eq!(
+            Some(Event::TokenSent {
+                amount: Token::Sol.amount(750)
+            }),
+            balances.update(&address, Token::Sol, None).expect("update")
+        );
+    }
+
+    #[test]
+    fn hotspot_events() {
+        let (address, other, asset) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let events = outcome_events(
+            &address,
+            vec![
+                Outcome::HotspotTransferred {
+                    asset,
+                    from: other,
+                    to: address,
+                },
+                Outcome::DcBurned {
+                    amount: Token::Dc.amount(10),
+                },
+            ],
+        );
+        assert_eq!(vec![Event::HotspotReceived { asset, from: other }], events);
+    }
+}
@@ -0,0 +1,83 @@
+/*
+ * C ABI for Helium wallet files and signing.
+ *
+ * All functions return HELIUM_OK on success or one of the other status
+ * codes on failure, in which case helium_last_error() describes the
+ * failure. Keypairs returned by the library are freed with
+ * helium_keypair_free, and buffers with helium_buffer_free.
+ */
+#ifndef HELIUM_WALLET_H
+#define HELIUM_WALLET_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define HELIUM_OK 0
+#define HELIUM_ERR_INVALID_ARGUMENT 1
+#define HELIUM_ERR_WALLET 2
+#define HELIUM_ERR_SIGN 3
+#define HELIUM_ERR_PANIC 4
+
+/* Length of an ed25519 signature */
+#define HELIUM_SIGNATURE_LEN 64
+/* Buffer size that fits any base58 address, including the terminating NUL */
+#define HELIUM_ADDRESS_LEN 64
+
+typedef struct HeliumKeypair HeliumKeypair;
+
+typedef struct HeliumBuffer {
+    uint8_t *data;
+    size_t len;
+} HeliumBuffer;
+
+/*
+ * Create a new keypair and encrypt it with the given password. The content
+ * of the wallet file is returned in wallet_out, and the keypair in
+ * keypair_out.
+ */
+int32_t helium_wallet_create(const char *password, HeliumBuffer *wallet_out,
+                             HeliumKeypair **keypair_out);
+
+/* Decrypt the content of a basic wallet file with its password */
+int32_t helium_wallet_decrypt(const uint8_t *wallet, size_t wallet_len,
+                              const char *password, HeliumKeypair **keypair_out);
+
+void helium_keypair_free(HeliumKeypair *keypair);
+
+/* Write the NUL terminated Solana address of the keypair into out */
+int32_t helium_keypair_address(const HeliumKeypair *keypair, char *out, size_t out_len);
+
+/* Write the NUL terminated Helium address of the keypair into out */
+int32_t helium_keypair_helium_address(const HeliumKeypair *keypair, char *out,
+                                      size_t out_len);
+
+/* Sign an arbitrary message */
+int32_t helium_keypair_sign_message(const HeliumKeypair *keypair, const uint8_t *message,
+                                    size_t message_len,
+                                    uint8_t signature_out[HELIUM_SIGNATURE_LEN]);
+
+/*
+ * Add the signature of the keypair to a bincode serialized Solana versioned
+ * transaction. The keypair must be one of the required signers of the
+ * transaction. The signed transaction is returned in tx_out.
+ */
+int32_t helium_keypair_sign_transaction(const HeliumKeypair *keypair, const uint8_t *tx,
+                                        size_t tx_len, HeliumBuffer *tx_out);
+
+void helium_buffer_free(HeliumBuffer buffer);
+
+/*
+ * The message of the last failure on the calling thread, or NULL. The
+ * string is valid until the next call into the library on the same thread.
+ */
+const char *helium_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* HELIUM_WALLET_H */
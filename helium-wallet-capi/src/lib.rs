@@ -0,0 +1,367 @@
+//! C ABI for Helium wallet files and signing, declared in
+//! `include/helium_wallet.h`.
+//!
+//! The ABI only uses opaque keypair handles, plain buffers and status
+//! codes, so it stays stable as helium-lib changes. Panics are caught at the
+//! boundary and reported as [`HELIUM_ERR_PANIC`].
+
+use helium_lib::{
+    keypair::{self, Keypair, Signer},
+    solana_sdk::transaction::VersionedTransaction,
+    tx,
+};
+use helium_wallet_format::{
+    format::{self, Format},
+    pwhash::PwHash,
+    wallet::Wallet,
+};
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CStr, CString},
+    panic::{catch_unwind, UnwindSafe},
+    ptr, slice,
+    sync::Arc,
+};
+
+pub const HELIUM_OK: i32 = 0;
+pub const HELIUM_ERR_INVALID_ARGUMENT: i32 = 1;
+pub const HELIUM_ERR_WALLET: i32 = 2;
+pub const HELIUM_ERR_SIGN: i32 = 3;
+pub const HELIUM_ERR_PANIC: i32 = 4;
+
+const SIGNATURE_LEN: usize = 64;
+
+/// Opaque keypair handle
+pub struct HeliumKeypair(Arc<Keypair>);
+
+#[repr(C)]
+pub struct HeliumBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl HeliumBuffer {
+    fn from_vec(data: Vec<u8>) -> Self {
+        let mut data = data.into_boxed_slice();
+        let buffer = Self {
+            data: data.as_mut_ptr(),
+            len: data.len(),
+        };
+        std::mem::forget(data);
+        buffer
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+struct Failure {
+    status: i32,
+    message: String,
+}
+
+impl Failure {
+    fn new<S: ToString>(status: i32, message: S) -> Self {
+        Self {
+            status,
+            message: message.to_string(),
+        }
+    }
+
+    fn invalid<S: ToString>(message: S) -> Self {
+        Self::new(HELIUM_ERR_INVALID_ARGUMENT, message)
+    }
+}
+
+/// Run the body of an exported function, recording the failure message for
+/// `helium_last_error` and turning panics into a status
+fn guard<F: FnOnce() -> Result<(), Failure> + UnwindSafe>(f: F) -> i32 {
+    let (status, message) = match catch_unwind(f) {
+        Ok(Ok(())) => (HELIUM_OK, None),
+        Ok(Err(failure)) => (failure.status, Some(failure.message)),
+        Err(_) => (HELIUM_ERR_PANIC, Some("panic in helium wallet".to_string())),
+    };
+    let message = message.map(|message| {
+        CString::new(message.replace('\0', " ")).expect("no interior nul in message")
+    });
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+    status
+}
+
+fn init_sodium() {
+    static INIT: std::sync::Once = std::sync::Once::new();
+    INIT.call_once(|| sodiumoxide::init().expect("Failed to intialize sodium"))
+}
+
+unsafe fn str_arg<'a>(value: *const c_char, name: &str) -> Result<&'a str, Failure> {
+    if value.is_null() {
+        return Err(Failure::invalid(format!("{name} is null")));
+    }
+    CStr::from_ptr(value)
+        .to_str()
+        .map_err(|_| Failure::invalid(format!("{name} is not valid utf8")))
+}
+
+unsafe fn bytes_arg<'a>(data: *const u8, len: usize, name: &str) -> Result<&'a [u8], Failure> {
+    if data.is_null() {
+        return Err(Failure::invalid(format!("{name} is null")));
+    }
+    Ok(slice::from_raw_parts(data, len))
+}
+
+unsafe fn keypair_arg<'a>(keypair: *const HeliumKeypair) -> Result<&'a Keypair, Failure> {
+    keypair
+        .as_ref()
+        .map(|keypair| keypair.0.as_ref())
+        .ok_or_else(|| Failure::invalid("keypair is null"))
+}
+
+unsafe fn write_str(value: &str, out: *mut c_char, out_len: usize) -> Result<(), Failure> {
+    if out.is_null() {
+        return Err(Failure::invalid("out is null"));
+    }
+    if value.len() >= out_len {
+        return Err(Failure::invalid(format!(
+            "out needs room for {} bytes",
+            value.len() + 1
+        )));
+    }
+    ptr::copy_nonoverlapping(value.as_ptr(), out as *mut u8, value.len());
+    *out.add(value.len()) = 0;
+    Ok(())
+}
+
+/// # Safety
+///
+/// `password` must be a NUL terminated string, and `wallet_out` and
+/// `keypair_out` valid pointers to write to.
+#[no_mangle]
+pub unsafe extern "C" fn helium_wallet_create(
+    password: *const c_char,
+    wallet_out: *mut HeliumBuffer,
+    keypair_out: *mut *mut HeliumKeypair,
+) -> i32 {
+    guard(|| {
+        init_sodium();
+        let password = str_arg(password, "password")?;
+        if wallet_out.is_null() || keypair_out.is_null() {
+            return Err(Failure::invalid("output is null"));
+        }
+        let keypair = Keypair::generate();
+        let format = Format::Basic(format::Basic {
+            pwhash: PwHash::argon2id13_default(),
+        });
+        let wallet = Wallet::encrypt(&keypair, password.as_bytes(), format)
+            .map_err(|err| Failure::new(HELIUM_ERR_WALLET, err))?;
+        let mut data = vec![];
+        wallet
+            .write(&mut data)
+            .map_err(|err| Failure::new(HELIUM_ERR_WALLET, err))?;
+        *wallet_out = HeliumBuffer::from_vec(data);
+        *keypair_out = Box::into_raw(Box::new(HeliumKeypair(Arc::new(keypair))));
+        Ok(())
+    })
+}
+
+/// # Safety
+///
+/// `wallet` must point to `wallet_len` readable bytes, `password` must be a
+/// NUL terminated string and `keypair_out` a valid pointer to write to.
+#[no_mangle]
+pub unsafe extern "C" fn helium_wallet_decrypt(
+    wallet: *const u8,
+    wallet_len: usize,
+    password: *const c_char,
+    keypair_out: *mut *mut HeliumKeypair,
+) -> i32 {
+    guard(|| {
+        init_sodium();
+        let mut data = bytes_arg(wallet, wallet_len, "wallet")?;
+        let password = str_arg(password, "password")?;
+        if keypair_out.is_null() {
+            return Err(Failure::invalid("keypair_out is null"));
+        }
+        let wallet = Wallet::read(&mut data).map_err(|err| Failure::new(HELIUM_ERR_WALLET, err))?;
+        if wallet.is_sharded() {
+            return Err(Failure::new(
+                HELIUM_ERR_WALLET,
+                "sharded wallets are not supported",
+            ));
+        }
+        let keypair = wallet
+            .decrypt(password.as_bytes())
+            .map_err(|err| Failure::new(HELIUM_ERR_WALLET, err))?;
+        *keypair_out = Box::into_raw(Box::new(HeliumKeypair(keypair)));
+        Ok(())
+    })
+}
+
+/// # Safety
+///
+/// `keypair` must be null or a keypair returned by this library that was
+/// not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn helium_keypair_free(keypair: *mut HeliumKeypair) {
+    if !keypair.is_null() {
+        drop(Box::from_raw(keypair));
+    }
+}
+
+/// # Safety
+///
+/// `keypair` must be a live keypair and `out` must point to `out_len`
+/// writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn helium_keypair_address(
+    keypair: *const HeliumKeypair,
+    out: *mut c_char,
+    out_len: usize,
+) -> i32 {
+    guard(|| {
+        let keypair = keypair_arg(keypair)?;
+        write_str(&keypair.pubkey().to_string(), out, out_len)
+    })
+}
+
+/// # Safety
+///
+/// `keypair` must be a live keypair and `out` must point to `out_len`
+/// writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn helium_keypair_helium_address(
+    keypair: *const HeliumKeypair,
+    out: *mut c_char,
+    out_len: usize,
+) -> i32 {
+    guard(|| {
+        let keypair = keypair_arg(keypair)?;
+        let helium_key = keypair::to_helium_pubkey(&keypair.pubkey()).map_err(Failure::invalid)?;
+        write_str(&helium_key.to_string(), out, out_len)
+    })
+}
+
+/// # Safety
+///
+/// `keypair` must be a live keypair, `message` must point to `message_len`
+/// readable bytes and `signature_out` to 64 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn helium_keypair_sign_message(
+    keypair: *const HeliumKeypair,
+    message: *const u8,
+    message_len: usize,
+    signature_out: *mut u8,
+) -> i32 {
+    guard(|| {
+        let keypair = keypair_arg(keypair)?;
+        let message = bytes_arg(message, message_len, "message")?;
+        if signature_out.is_null() {
+            return Err(Failure::invalid("signature_out is null"));
+        }
+        let signature = keypair
+            .sign(message)
+            .map_err(|err| Failure::new(HELIUM_ERR_SIGN, err))?;
+        ptr::copy_nonoverlapping(signature.as_ref().as_ptr(), signature_out, SIGNATURE_LEN);
+        Ok(())
+    })
+}
+
+/// # Safety
+///
+/// `keypair` must be a live keypair, `tx` must point to `tx_len` readable
+/// bytes and `tx_out` must be a valid pointer to write to.
+#[no_mangle]
+pub unsafe extern "C" fn helium_keypair_sign_transaction(
+    keypair: *const HeliumKeypair,
+    tx: *const u8,
+    tx_len: usize,
+    tx_out: *mut HeliumBuffer,
+) -> i32 {
+    guard(|| {
+        let keypair = keypair_arg(keypair)?;
+        let data = bytes_arg(tx, tx_len, "tx")?;
+        if tx_out.is_null() {
+            return Err(Failure::invalid("tx_out is null"));
+        }
+        let mut transaction: VersionedTransaction = bincode::deserialize(data)
+            .map_err(|err| Failure::invalid(format!("transaction: {err}")))?;
+        tx::sign(&mut transaction, &[keypair]).map_err(|err| Failure::new(HELIUM_ERR_SIGN, err))?;
+        let data = bincode::serialize(&transaction)
+            .map_err(|err| Failure::new(HELIUM_ERR_SIGN, format!("transaction: {err}")))?;
+        *tx_out = HeliumBuffer::from_vec(data);
+        Ok(())
+    })
+}
+
+/// # Safety
+///
+/// `buffer` must have been returned by this library and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn helium_buffer_free(buffer: HeliumBuffer) {
+    if !buffer.data.is_null() {
+        drop(Box::from_raw(slice::from_raw_parts_mut(
+            buffer.data,
+            buffer.len,
+        )));
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn helium_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_decrypt_sign() {
+        let password = CString::new("pass123").unwrap();
+        let mut wallet = HeliumBuffer {
+            data: ptr::null_mut(),
+            len: 0,
+        };
+        let mut created = ptr::null_mut();
+        let mut decrypted = ptr::null_mut();
+        let mut signature = [0u8; SIGNATURE_LEN];
+        let message = b"hello";
+        unsafe {
+            assert_eq!(
+                HELIUM_OK,
+                helium_wallet_create(password.as_ptr(), &mut wallet, &mut created)
+            );
+            assert_eq!(
+                HELIUM_OK,
+                helium_wallet_decrypt(wallet.data, wallet.len, password.as_ptr(), &mut decrypted)
+            );
+            assert_eq!((*created).0.pubkey(), (*decrypted).0.pubkey());
+            assert_eq!(
+                HELIUM_OK,
+                helium_keypair_sign_message(
+                    decrypted,
+                    message.as_ptr(),
+                    message.len(),
+                    signature.as_mut_ptr()
+                )
+            );
+            let wrong = CString::new("wrong").unwrap();
+            let mut failed = ptr::null_mut();
+            assert_eq!(
+                HELIUM_ERR_WALLET,
+                helium_wallet_decrypt(wallet.data, wallet.len, wrong.as_ptr(), &mut failed)
+            );
+            assert!(!helium_last_error().is_null());
+            let pubkey = (*decrypted).0.pubkey();
+            helium_buffer_free(wallet);
+            helium_keypair_free(created);
+            helium_keypair_free(decrypted);
+            let signature = helium_lib::keypair::Signature::from(signature);
+            assert!(signature.verify(pubkey.as_ref(), message));
+        }
+    }
+}
@@ -0,0 +1,28 @@
+//! The Helium wallet file format: password hashing, encryption and
+//! sharding of wallet keypairs.
+//!
+//! This only depends on the keypair types of helium-lib, without its
+//! clients, so bindings can read and write wallet files without pulling in
+//! the wallet CLI.
+
+use std::fmt;
+
+pub mod format;
+pub mod pwhash;
+pub mod read_write;
+mod result;
+pub mod wallet;
+
+pub use wallet::Wallet;
+
+/// A wallet failed to decrypt, almost always because of a wrong password
+#[derive(Debug)]
+pub struct DecryptError;
+
+impl fmt::Display for DecryptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Failed to decrypt wallet")
+    }
+}
+
+impl std::error::Error for DecryptError {}
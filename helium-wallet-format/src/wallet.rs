@@ -3,6 +3,7 @@ use crate::{
     pwhash::PwHash,
     read_write::ReadWrite,
     result::{anyhow, bail, Error, Result},
+    DecryptError,
 };
 use aes_gcm::{aead::generic_array::GenericArray, AeadInPlace, Aes256Gcm, KeyInit};
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
@@ -112,7 +113,7 @@ impl Wallet {
             )
             .is_err()
         {
-            bail!("Failed to decrypt wallet");
+            return Err(DecryptError.into());
         }
         let keypair = Self::read_keypair(&mut Cursor::new(buffer), self.kind)?;
         Ok(Arc::new(keypair))
@@ -433,7 +434,10 @@ fn open_output_file(filename: &Path, create: bool) -> io::Result<fs::File> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::cmd::phrase_to_words;
+
+    fn phrase_to_words(phrase: &str) -> Vec<&str> {
+        phrase.split_whitespace().collect()
+    }
 
     #[test]
     fn rountrip_basic() {
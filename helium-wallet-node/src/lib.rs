@@ -0,0 +1,287 @@
+//! The `@helium/wallet-rs` Node.js package.
+//!
+//! Build it with `npm run build` from this directory, which generates the
+//! native module along with `index.js` and its TypeScript declarations:
+//!
+//! ```js
+//! const { Client, Keypair, decryptWallet } = require("@helium/wallet-rs");
+//!
+//! const keypair = decryptWallet(fs.readFileSync("wallet.key"), "password");
+//! const client = new Client("m");
+//! const txn = await client.transfer(keypair, "<payee>", "hnt", 150_000_000n);
+//! console.log(await client.send(txn));
+//! ```
+//!
+//! Token amounts are in the smallest unit of the token, lamports for SOL and
+//! bones for HNT, so they are exact. They are taken as a `BigInt` and
+//! returned as decimal strings.
+//!
+//! Transactions are returned as bincode serialized, signed, versioned
+//! transactions so they can be stored or inspected before being sent.
+//! Network calls return promises driven by a runtime shared by all clients.
+
+use helium_lib::{
+    entity_key::{EncodedEntityKey, EntityKeyEncoding},
+    keypair::{self, Pubkey, Signer},
+    reward::{self, ClaimableToken},
+    solana_sdk::transaction::VersionedTransaction,
+    token::{self, Token, TokenAmount},
+    TransactionOpts,
+};
+use helium_wallet_format::{
+    format::{self, Format},
+    pwhash::PwHash,
+    wallet::Wallet,
+};
+use napi::{
+    bindgen_prelude::{BigInt, Buffer},
+    Env, JsObject,
+};
+use napi_derive::napi;
+use std::{collections::HashMap, str::FromStr, sync::Arc};
+
+type Result<T> = napi::Result<T>;
+
+fn to_napi_err<E: std::fmt::Display>(err: E) -> napi::Error {
+    napi::Error::from_reason(err.to_string())
+}
+
+fn init_sodium() {
+    static INIT: std::sync::Once = std::sync::Once::new();
+    INIT.call_once(|| sodiumoxide::init().expect("Failed to intialize sodium"))
+}
+
+/// Convert mnemonic words, 24 words or the 12 words of the Helium mobile
+/// app, to the entropy they encode
+#[napi]
+pub fn mnemonic_to_entropy(words: Vec<String>) -> Result<Buffer> {
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    let entropy = helium_mnemonic::mnemonic_to_entropy(&words).map_err(to_napi_err)?;
+    Ok(entropy.to_vec().into())
+}
+
+/// Convert 32 bytes of entropy to the equivalent 24 mnemonic words
+#[napi]
+pub fn entropy_to_mnemonic(entropy: Buffer) -> Result<Vec<String>> {
+    helium_mnemonic::entropy_to_mnemonic(&entropy).map_err(to_napi_err)
+}
+
+#[napi]
+pub struct Keypair {
+    inner: Arc<keypair::Keypair>,
+}
+
+#[napi]
+impl Keypair {
+    #[napi(factory)]
+    pub fn generate() -> Self {
+        Self {
+            inner: Arc::new(keypair::Keypair::generate()),
+        }
+    }
+
+    /// Recreate a keypair from its mnemonic words
+    #[napi(factory)]
+    pub fn from_words(words: Vec<String>) -> Result<Self> {
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        keypair::Keypair::from_words(&words)
+            .map(|inner| Self { inner })
+            .map_err(to_napi_err)
+    }
+
+    /// The mnemonic words of the keypair
+    #[napi]
+    pub fn words(&self) -> Result<Vec<String>> {
+        let phrase = self.inner.phrase().map_err(to_napi_err)?;
+        Ok(phrase.split(' ').map(ToString::to_string).collect())
+    }
+
+    /// The Solana address of the keypair
+    #[napi(getter)]
+    pub fn address(&self) -> String {
+        self.inner.pubkey().to_string()
+    }
+
+    /// The Helium address of the keypair
+    #[napi(getter)]
+    pub fn helium_address(&self) -> Result<String> {
+        keypair::to_helium_pubkey(&self.inner.pubkey())
+            .map(|key| key.to_string())
+            .map_err(to_napi_err)
+    }
+
+    /// Sign an arbitrary message, returning the ed25519 signature
+    #[napi]
+    pub fn sign_message(&self, message: Buffer) -> Result<Buffer> {
+        let signature = self.inner.sign(&message).map_err(to_napi_err)?;
+        Ok(signature.as_ref().to_vec().into())
+    }
+}
+
+/// Encrypt a keypair with a password into the content of a basic wallet
+/// file, as written by `helium-wallet create basic`
+#[napi]
+pub fn encrypt_wallet(keypair: &Keypair, password: String) -> Result<Buffer> {
+    init_sodium();
+    let format = Format::Basic(format::Basic {
+        pwhash: PwHash::argon2id13_default(),
+    });
+    let wallet =
+        Wallet::encrypt(&keypair.inner, password.as_bytes(), format).map_err(to_napi_err)?;
+    let mut data = vec![];
+    wallet.write(&mut data).map_err(to_napi_err)?;
+    Ok(data.into())
+}
+
+/// Decrypt the content of a basic wallet file with its password
+#[napi]
+pub fn decrypt_wallet(data: Buffer, password: String) -> Result<Keypair> {
+    init_sodium();
+    let mut reader: &[u8] = &data;
+    let wallet = Wallet::read(&mut reader).map_err(to_napi_err)?;
+    if wallet.is_sharded() {
+        return Err(napi::Error::from_reason(
+            "sharded wallets are not supported",
+        ));
+    }
+    wallet
+        .decrypt(password.as_bytes())
+        .map(|inner| Keypair { inner })
+        .map_err(to_napi_err)
+}
+
+/// A connection to a Solana RPC and DAS provider
+#[napi]
+pub struct Client {
+    inner: helium_lib::client::Client,
+}
+
+#[napi]
+impl Client {
+    /// Connect to the given RPC URL, or "m" and "d" for the default mainnet
+    /// and devnet providers. Clients share the key to asset and asset proof
+    /// caches of the process, so all clients have to use the same RPC URL.
+    #[napi(constructor)]
+    pub fn new(url: Option<String>) -> Result<Self> {
+        let inner = helium_lib::client::Client::try_from(url.as_deref().unwrap_or("m"))
+            .map_err(to_napi_err)?;
+        helium_lib::init(inner.solana_client.clone()).map_err(to_napi_err)?;
+        Ok(Self { inner })
+    }
+
+    /// The token balances of an address, in the smallest unit of each token by
+    /// token name
+    #[napi(ts_return_type = "Promise<Record<string, string>>")]
+    pub fn balances(&self, env: Env, address: String) -> Result<JsObject> {
+        let address = Pubkey::from_str(&address).map_err(to_napi_err)?;
+        let client = self.inner.clone();
+        env.spawn_future(async move {
+            let balances =
+                token::balance_for_addresses(&client, &Token::associated_token_adresses(&address))
+                    .await
+                    .map_err(to_napi_err)?;
+            Ok(balances
+                .into_iter()
+                .map(|balance| {
+                    (
+                        balance.amount.token.to_string(),
+                        balance.amount.amount.to_string(),
+                    )
+                })
+                .collect::<HashMap<String, String>>())
+        })
+    }
+
+    /// Build a signed transaction transferring an amount of "sol", "hnt",
+    /// "iot" or "mobile", in the smallest unit of the token, to a Solana
+    /// address
+    #[napi(ts_return_type = "Promise<Buffer>")]
+    pub fn transfer(
+        &self,
+        env: Env,
+        keypair: &Keypair,
+        payee: String,
+        token: String,
+        amount: BigInt,
+    ) -> Result<JsObject> {
+        let payee = Pubkey::from_str(&payee).map_err(to_napi_err)?;
+        let token = Token::transferrable_value_parser(&token).map_err(to_napi_err)?;
+        let (negative, amount, lossless) = amount.get_u64();
+        if negative || !lossless {
+            return Err(napi::Error::from_reason(
+                "amount must be a non-negative 64 bit integer",
+            ));
+        }
+        let amount = TokenAmount::from_u64(token, amount);
+        let (client, keypair) = (self.inner.clone(), keypair.inner.clone());
+        env.spawn_future(async move {
+            let (tx, _) = token::transfer(
+                &client,
+                &[(payee, amount)],
+                &keypair,
+                &TransactionOpts::default(),
+            )
+            .await
+            .map_err(to_napi_err)?;
+            serialize(&tx)
+        })
+    }
+
+    /// Build a signed transaction claiming the pending "hnt", "iot" or
+    /// "mobile" rewards of a Hotspot, given by its Helium address. Resolves
+    /// to null when there is nothing to claim.
+    #[napi(ts_return_type = "Promise<Buffer | null>")]
+    pub fn claim_rewards(
+        &self,
+        env: Env,
+        keypair: &Keypair,
+        token: String,
+        hotspot: String,
+    ) -> Result<JsObject> {
+        let token = match token.as_str() {
+            "hnt" => ClaimableToken::Hnt,
+            "iot" => ClaimableToken::Iot,
+            "mobile" => ClaimableToken::Mobile,
+            other => return Err(napi::Error::from_reason(format!("invalid token: {other}"))),
+        };
+        let entity_key = EncodedEntityKey {
+            encoding: EntityKeyEncoding::B58,
+            entity_key: hotspot,
+        };
+        let (client, keypair) = (self.inner.clone(), keypair.inner.clone());
+        env.spawn_future(async move {
+            let claim = reward::claim(
+                &client,
+                token,
+                None,
+                &entity_key,
+                &keypair,
+                &TransactionOpts::default(),
+            )
+            .await
+            .map_err(to_napi_err)?;
+            claim.map(|(tx, _)| serialize(&tx)).transpose()
+        })
+    }
+
+    /// Send a serialized transaction, resolving to its signature
+    #[napi(ts_return_type = "Promise<string>")]
+    pub fn send(&self, env: Env, transaction: Buffer) -> Result<JsObject> {
+        let tx: VersionedTransaction = bincode::deserialize(&transaction).map_err(to_napi_err)?;
+        let client = self.inner.clone();
+        env.spawn_future(async move {
+            let signature = client
+                .solana_client
+                .send_transaction(&tx)
+                .await
+                .map_err(to_napi_err)?;
+            Ok(signature.to_string())
+        })
+    }
+}
+
+fn serialize(tx: &VersionedTransaction) -> Result<Buffer> {
+    bincode::serialize(tx)
+        .map(Buffer::from)
+        .map_err(to_napi_err)
+}
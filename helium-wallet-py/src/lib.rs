@@ -0,0 +1,255 @@
+//! The `helium_wallet` Python package.
+//!
+//! Build and install it into the active virtualenv with `maturin develop`
+//! from this directory. Network calls block the calling thread, without
+//! holding the GIL, on a runtime shared by all clients:
+//!
+//! ```python
+//! import helium_wallet
+//!
+//! keypair = helium_wallet.read_wallet("wallet.key", "password")
+//! client = helium_wallet.Client("m")
+//! print(client.balances(keypair.address))
+//! txn = client.transfer(keypair, "<payee>", "hnt", 150_000_000)
+//! print(client.send(txn))
+//! ```
+//!
+//! Token amounts are integers in the smallest unit of the token, lamports for
+//! SOL and bones for HNT, so they are exact.
+//!
+//! Transactions are returned as bincode serialized, signed, versioned
+//! transactions so they can be stored or inspected before being sent.
+
+use helium_lib::{
+    entity_key::{EncodedEntityKey, EntityKeyEncoding},
+    keypair::{self, Pubkey, Signer},
+    reward::{self, ClaimableToken},
+    solana_sdk::transaction::VersionedTransaction,
+    token::{self, Token, TokenAmount},
+    TransactionOpts,
+};
+use helium_wallet_format::wallet::Wallet;
+use pyo3::{create_exception, exceptions::PyException, prelude::*, types::PyBytes};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    str::FromStr,
+    sync::{Arc, OnceLock},
+};
+
+create_exception!(helium_wallet, HeliumError, PyException);
+
+fn to_py_err<E: std::fmt::Display>(err: E) -> PyErr {
+    HeliumError::new_err(err.to_string())
+}
+
+/// The runtime all network calls block on
+fn runtime() -> &'static tokio::runtime::Runtime {
+    static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime")
+    })
+}
+
+fn init_sodium() {
+    static INIT: std::sync::Once = std::sync::Once::new();
+    INIT.call_once(|| sodiumoxide::init().expect("Failed to intialize sodium"))
+}
+
+#[pyclass(module = "helium_wallet", frozen)]
+struct Keypair(Arc<keypair::Keypair>);
+
+#[pymethods]
+impl Keypair {
+    #[staticmethod]
+    fn generate() -> Self {
+        Self(Arc::new(keypair::Keypair::generate()))
+    }
+
+    /// Recreate a keypair from its mnemonic words, either 24 words or the 12
+    /// words of the Helium mobile app
+    #[staticmethod]
+    fn from_words(words: Vec<String>) -> PyResult<Self> {
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        keypair::Keypair::from_words(&words)
+            .map(Self)
+            .map_err(to_py_err)
+    }
+
+    /// The mnemonic words of the keypair
+    fn words(&self) -> PyResult<Vec<String>> {
+        let phrase = self.0.phrase().map_err(to_py_err)?;
+        Ok(phrase.split(' ').map(ToString::to_string).collect())
+    }
+
+    /// The Solana address of the keypair
+    #[getter]
+    fn address(&self) -> String {
+        self.0.pubkey().to_string()
+    }
+
+    /// The Helium address of the keypair
+    #[getter]
+    fn helium_address(&self) -> PyResult<String> {
+        keypair::to_helium_pubkey(&self.0.pubkey())
+            .map(|key| key.to_string())
+            .map_err(to_py_err)
+    }
+
+    /// Sign an arbitrary message, returning the ed25519 signature
+    fn sign_message<'py>(&self, py: Python<'py>, message: &[u8]) -> PyResult<Bound<'py, PyBytes>> {
+        let signature = self.0.sign(message).map_err(to_py_err)?;
+        Ok(PyBytes::new_bound(py, signature.as_ref()))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Keypair({})", self.0.pubkey())
+    }
+}
+
+/// Decrypt the content of a basic wallet file with its password
+#[pyfunction]
+fn decrypt_wallet(data: &[u8], password: &str) -> PyResult<Keypair> {
+    init_sodium();
+    let mut reader = data;
+    let wallet = Wallet::read(&mut reader).map_err(to_py_err)?;
+    if wallet.is_sharded() {
+        return Err(HeliumError::new_err("sharded wallets are not supported"));
+    }
+    wallet
+        .decrypt(password.as_bytes())
+        .map(Keypair)
+        .map_err(to_py_err)
+}
+
+/// Read and decrypt a basic wallet file
+#[pyfunction]
+fn read_wallet(path: PathBuf, password: &str) -> PyResult<Keypair> {
+    let data = std::fs::read(path)?;
+    decrypt_wallet(&data, password)
+}
+
+/// A connection to a Solana RPC and DAS provider
+#[pyclass(module = "helium_wallet", frozen)]
+struct Client(helium_lib::client::Client);
+
+#[pymethods]
+impl Client {
+    /// Connect to the given RPC URL, or "m" and "d" for the default mainnet
+    /// and devnet providers. Clients share the key to asset and asset proof
+    /// caches of the process, so all clients have to use the same RPC URL.
+    #[new]
+    #[pyo3(signature = (url = "m"))]
+    fn new(url: &str) -> PyResult<Self> {
+        let client = helium_lib::client::Client::try_from(url).map_err(to_py_err)?;
+        helium_lib::init(client.solana_client.clone()).map_err(to_py_err)?;
+        Ok(Self(client))
+    }
+
+    /// The token balances of an address, in the smallest unit of each token by
+    /// token name
+    fn balances(&self, py: Python<'_>, address: &str) -> PyResult<HashMap<String, u64>> {
+        let address = Pubkey::from_str(address).map_err(to_py_err)?;
+        let balances = py
+            .allow_threads(|| {
+                runtime().block_on(token::balance_for_addresses(
+                    &self.0,
+                    &Token::associated_token_adresses(&address),
+                ))
+            })
+            .map_err(to_py_err)?;
+        Ok(balances
+            .into_iter()
+            .map(|balance| (balance.amount.token.to_string(), balance.amount.amount))
+            .collect())
+    }
+
+    /// Build a signed transaction transferring an amount of "sol", "hnt",
+    /// "iot" or "mobile", in the smallest unit of the token, to a Solana
+    /// address
+    fn transfer<'py>(
+        &self,
+        py: Python<'py>,
+        keypair: &Keypair,
+        payee: &str,
+        token: &str,
+        amount: u64,
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        let payee = Pubkey::from_str(payee).map_err(to_py_err)?;
+        let token = Token::transferrable_value_parser(token).map_err(to_py_err)?;
+        let amount = TokenAmount::from_u64(token, amount);
+        let (tx, _) = py
+            .allow_threads(|| {
+                runtime().block_on(token::transfer(
+                    &self.0,
+                    &[(payee, amount)],
+                    &keypair.0,
+                    &TransactionOpts::default(),
+                ))
+            })
+            .map_err(to_py_err)?;
+        serialize(py, &tx)
+    }
+
+    /// Build a signed transaction claiming the pending "hnt", "iot" or
+    /// "mobile" rewards of a Hotspot, given by its Helium address. Returns
+    /// None when there is nothing to claim.
+    fn claim_rewards<'py>(
+        &self,
+        py: Python<'py>,
+        keypair: &Keypair,
+        token: &str,
+        hotspot: &str,
+    ) -> PyResult<Option<Bound<'py, PyBytes>>> {
+        let token = match token {
+            "hnt" => ClaimableToken::Hnt,
+            "iot" => ClaimableToken::Iot,
+            "mobile" => ClaimableToken::Mobile,
+            other => return Err(HeliumError::new_err(format!("invalid token: {other}"))),
+        };
+        let entity_key = EncodedEntityKey {
+            encoding: EntityKeyEncoding::B58,
+            entity_key: hotspot.to_string(),
+        };
+        let claim = py
+            .allow_threads(|| {
+                runtime().block_on(reward::claim(
+                    &self.0,
+                    token,
+                    None,
+                    &entity_key,
+                    &keypair.0,
+                    &TransactionOpts::default(),
+                ))
+            })
+            .map_err(to_py_err)?;
+        claim.map(|(tx, _)| serialize(py, &tx)).transpose()
+    }
+
+    /// Send a serialized transaction, returning its signature
+    fn send(&self, py: Python<'_>, transaction: &[u8]) -> PyResult<String> {
+        let tx: VersionedTransaction = bincode::deserialize(transaction).map_err(to_py_err)?;
+        let signature = py
+            .allow_threads(|| runtime().block_on(self.0.solana_client.send_transaction(&tx)))
+            .map_err(to_py_err)?;
+        Ok(signature.to_string())
+    }
+}
+
+fn serialize<'py>(py: Python<'py>, tx: &VersionedTransaction) -> PyResult<Bound<'py, PyBytes>> {
+    let data = bincode::serialize(tx).map_err(to_py_err)?;
+    Ok(PyBytes::new_bound(py, &data))
+}
+
+#[pymodule]
+fn helium_wallet(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("HeliumError", m.py().get_type_bound::<HeliumError>())?;
+    m.add_class::<Keypair>()?;
+    m.add_class::<Client>()?;
+    m.add_function(wrap_pyfunction!(decrypt_wallet, m)?)?;
+    m.add_function(wrap_pyfunction!(read_wallet, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,242 @@
+//! Append-only audit log of signed transactions.
+//!
+//! Every transaction the wallet signs is recorded as a line of JSON with the
+//! command that signed it, the wallet in use, the hash of the transaction
+//! message, its signature and its outcome. Each entry carries the SHA-256
+//! hash of the line before it, so removing or changing an entry breaks the
+//! chain from there on, which [`verify`] detects.
+//!
+//! Removing entries from the end of the log can not be detected from the log
+//! alone, so keep a copy of the last hash reported by [`verify`] elsewhere.
+//!
+//! Several wallet processes can share a log. Every append holds an exclusive
+//! lock on the file and links to the last entry read under that lock.
+
+use crate::result::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use helium_lib::{
+    keypair::{Pubkey, Signature},
+    solana_sdk::transaction::VersionedTransaction,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// The previous hash of the first entry in a log
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    /// Position of the entry in the log, starting at 0
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    /// The subcommand that signed the transaction, like "rewards claim"
+    pub command: String,
+    /// The public key of the wallet file in use, if any
+    pub wallet: Option<String>,
+    /// Hex encoded SHA-256 hash of the serialized transaction message
+    pub message_hash: String,
+    pub signature: String,
+    /// Signers whose signature the transaction carries
+    pub signers: Vec<String>,
+    /// "submitted", "confirmed", "simulated", "partially_signed" or "failed",
+    /// or "signed" for transactions signed by the remote signer
+    pub outcome: String,
+    pub error: Option<String>,
+    /// Hex encoded SHA-256 hash of the previous line of the log
+    pub prev_hash: String,
+}
+
+struct AuditLog {
+    path: PathBuf,
+    command: String,
+    wallet: Option<Pubkey>,
+}
+
+static AUDIT_LOG: Mutex<Option<AuditLog>> = Mutex::new(None);
+
+/// Record the transactions signed by the given command in the log at the
+/// given path
+pub fn init(path: PathBuf, command: String, wallet: Option<Pubkey>) {
+    *AUDIT_LOG.lock().expect("audit lock") = Some(AuditLog {
+        path,
+        command,
+        wallet,
+    });
+}
+
+/// Append an entry for a signed transaction, if the audit log is enabled
+pub fn record(tx: &VersionedTransaction, outcome: &str, error: Option<String>) -> Result {
+    let guard = AUDIT_LOG.lock().expect("audit lock");
+    let Some(log) = guard.as_ref() else {
+        return Ok(());
+    };
+    let context = || format!("writing audit log {}", log.path.display());
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(&log.path)
+        .with_context(context)?;
+    // Another process may have appended since the last entry of this one, so
+    // the tail is read again under the lock. The lock is released when the
+    // file is closed.
+    file.lock().with_context(context)?;
+    let (seq, prev_hash) = match last_entry(BufReader::new(&file))
+        .with_context(|| format!("reading audit log {}", log.path.display()))?
+    {
+        Some((entry, hash)) => (entry.seq + 1, hash),
+        None => (0, GENESIS_HASH.to_string()),
+    };
+    let signers = tx
+        .message
+        .static_account_keys()
+        .iter()
+        .zip(&tx.signatures)
+        .filter(|(_, signature)| **signature != Signature::default())
+        .map(|(key, _)| key.to_string())
+        .collect();
+    let entry = Entry {
+        seq,
+        timestamp: Utc::now(),
+        command: log.command.clone(),
+        wallet: log.wallet.map(|wallet| wallet.to_string()),
+        message_hash: hex::encode(Sha256::digest(tx.message.serialize())),
+        signature: tx
+            .signatures
+            .first()
+            .copied()
+            .unwrap_or_default()
+            .to_string(),
+        signers,
+        outcome: outcome.to_string(),
+        error,
+        prev_hash,
+    };
+    let line = serde_json::to_string(&entry)?;
+    file.write_all(format!("{line}\n").as_bytes())
+        .and_then(|_| file.sync_data())
+        .with_context(context)?;
+    Ok(())
+}
+
+fn line_hash(line: &str) -> String {
+    hex::encode(Sha256::digest(line.as_bytes()))
+}
+
+/// The last entry of a log and the hash of its line
+fn last_entry<R: BufRead>(reader: R) -> Result<Option<(Entry, String)>> {
+    let mut last = None;
+    for line in reader.lines() {
+        last = Some(line?);
+    }
+    last.map(|line| Ok((serde_json::from_str(&line)?, line_hash(&line))))
+        .transpose()
+}
+
+/// Summary of a verified log
+#[derive(Debug, Serialize)]
+pub struct Verified {
+    pub entries: u64,
+    /// Hash of the last line, to compare against later verifications
+    pub last_hash: String,
+}
+
+/// Check that every entry of a log links to the line before it and that
+/// entries are numbered without gaps
+pub fn verify(path: &Path) -> Result<Verified> {
+    let file = fs::File::open(path).with_context(|| format!("opening {}", path.display()))?;
+    verify_lines(BufReader::new(file))
+}
+
+fn verify_lines<R: BufRead>(reader: R) -> Result<Verified> {
+    let mut prev_hash = GENESIS_HASH.to_string();
+    let mut entries = 0;
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line_number = index + 1;
+        let entry: Entry = serde_json::from_str(&line)
+            .map_err(|err| anyhow!("line {line_number}: invalid entry: {err}"))?;
+        if entry.seq != entries {
+            bail!(
+                "line {line_number}: expected entry {entries}, found entry {}",
+                entry.seq
+            );
+        }
+        if entry.prev_hash != prev_hash {
+            bail!(
+                "line {line_number}: hash chain broken, the previous line was changed or removed"
+            );
+        }
+        prev_hash = line_hash(&line);
+        entries += 1;
+    }
+    Ok(Verified {
+        entries,
+        last_hash: prev_hash,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(seq: u64, prev_hash: String) -> String {
+        serde_json::to_string(&Entry {
+            seq,
+            timestamp: Utc::now(),
+            command: "transfer".to_string(),
+            wallet: None,
+            message_hash: String::new(),
+            signature: Signature::default().to_string(),
+            signers: vec![],
+            outcome: "confirmed".to_string(),
+            error: None,
+            prev_hash,
+        })
+        .expect("entry")
+    }
+
+    #[test]
+    fn hash_chain() {
+        let first = entry(0, GENESIS_HASH.to_string());
+        let second = entry(1, line_hash(&first));
+        let log = format!("{first}\n{second}\n");
+        let verified = verify_lines(log.as_bytes()).expect("valid log");
+        assert_eq!(2, verified.entries);
+        assert_eq!(line_hash(&second), verified.last_hash);
+
+        let tampered = log.replace("confirmed", "failed");
+        assert!(verify_lines(tampered.as_bytes()).is_err());
+        assert!(verify_lines(format!("{second}\n").as_bytes()).is_err());
+    }
+
+    #[test]
+    fn appends_follow_other_writers() {
+        let path = std::env::temp_dir().join(format!("audit-{}.log", std::process::id()));
+        let _ = fs::remove_file(&path);
+        init(path.clone(), "transfer".to_string(), None);
+        let tx = VersionedTransaction::default();
+        record(&tx, "confirmed", None).expect("first entry");
+
+        // Another process appends to the same log
+        let (_, hash) = last_entry(BufReader::new(fs::File::open(&path).expect("log")))
+            .expect("last entry")
+            .expect("an entry");
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .expect("log");
+        writeln!(file, "{}", entry(1, hash)).expect("other entry");
+
+        record(&tx, "confirmed", None).expect("third entry");
+        let verified = verify(&path).expect("valid log");
+        assert_eq!(3, verified.entries);
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,29 @@
+use crate::cmd::*;
+
+pub mod rent;
+
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    #[command(subcommand)]
+    cmd: AccountsCommand,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        self.cmd.run(opts).await
+    }
+}
+
+/// Diagnostics on the Solana accounts related to a wallet
+#[derive(Debug, clap::Subcommand)]
+pub enum AccountsCommand {
+    Rent(rent::Cmd),
+}
+
+impl AccountsCommand {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Self::Rent(cmd) => cmd.run(opts).await,
+        }
+    }
+}
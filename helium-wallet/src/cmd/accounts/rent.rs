@@ -0,0 +1,48 @@
+use crate::cmd::*;
+use helium_lib::{
+    keypair::Pubkey,
+    rent,
+    token::{self, Token},
+};
+
+#[derive(Debug, clap::Args)]
+/// List the accounts a wallet holds rent for, their rent-exempt minimums, and
+/// how much SOL can be recovered by closing them.
+///
+/// Token accounts without a token balance and durable nonce accounts are
+/// reported as closable. Key to asset accounts are not included since the
+/// account that funded them is not recorded on chain.
+pub struct Cmd {
+    /// Address to list accounts for. Defaults to the wallet address
+    address: Option<Pubkey>,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let address = match self.address {
+            Some(address) => address,
+            None => opts.load_wallet()?.public_key,
+        };
+        let client = opts.client()?;
+        let sol_balance = token::balance_for_address(&client, &address)
+            .await?
+            .map(|balance| balance.amount)
+            .unwrap_or_else(|| Token::Sol.amount(0));
+        let accounts = rent::accounts_for_owner(&client, &address).await?;
+        let locked: u64 = accounts.iter().map(|account| account.lamports.amount).sum();
+        let recoverable: u64 = accounts
+            .iter()
+            .filter(|account| account.closable)
+            .map(|account| account.lamports.amount)
+            .sum();
+
+        let json = json!({
+            "address": address.to_string(),
+            "sol": sol_balance,
+            "locked": Token::Sol.amount(locked),
+            "recoverable": Token::Sol.amount(recoverable),
+            "accounts": accounts,
+        });
+        print_json(&json)
+    }
+}
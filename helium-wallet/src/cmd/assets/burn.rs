@@ -1,14 +1,22 @@
 use crate::cmd::*;
-use helium_lib::{asset, dao, entity_key};
+use helium_lib::{
+    asset::{self, Asset},
+    keypair::{Pubkey, Signer},
+};
 
 #[derive(Clone, Debug, clap::Args)]
-/// Burn a given asset (NFT)
+/// Burn an asset (NFT) held by the wallet
+///
+/// Compressed NFTs are burned outright. For regular NFTs the token is burned
+/// and its token account closed, which returns the rent of the account to
+/// the wallet. Before committing, the asset is shown and the burn has to be
+/// confirmed, unless --yes is given.
 pub struct Cmd {
-    /// Subdao for command
-    subdao: dao::SubDao,
-    /// Entity key of asset to burn
-    #[clap(flatten)]
-    entity_key: entity_key::EncodedEntityKey,
+    /// Id of the asset to burn
+    asset: Pubkey,
+    /// Burn without asking for confirmation
+    #[arg(long)]
+    yes: bool,
     /// Commit the transaction
     #[command(flatten)]
     commit: CommitOpts,
@@ -19,15 +27,47 @@ impl Cmd {
         let client = opts.client()?;
         let password = get_wallet_password(false)?;
         let keypair = opts.load_keypair(password.as_bytes())?;
-        let asset = asset::for_entity_key(&client, &self.entity_key.as_entity_key()?).await?;
+        let asset = asset::get(&client, &self.asset).await?;
+        if asset.ownership.owner != keypair.pubkey() {
+            bail!("asset {} is not owned by this wallet", self.asset);
+        }
+        if self.commit.commit && !self.yes && !confirm_burn(&asset)? {
+            bail!("burn cancelled");
+        }
         let (tx, _) = asset::burn(
             &client,
             &asset.id,
             &keypair,
-            &self.commit.transaction_opts(&client),
+            &self.commit.transaction_opts(&client)?,
         )
         .await?;
 
         print_json(&self.commit.maybe_commit(tx, &client).await?.to_json())
     }
 }
+
+/// Show the asset about to be burned and ask for confirmation
+fn confirm_burn(asset: &Asset) -> Result<bool> {
+    let metadata = &asset.content.metadata;
+    let collection = asset
+        .grouping
+        .iter()
+        .find(|group| group.group_key == "collection")
+        .map(|group| group.group_value.to_string());
+    eprintln!("Asset:      {}", asset.id);
+    eprintln!("Name:       {}", metadata.name);
+    eprintln!("Symbol:     {}", metadata.symbol);
+    eprintln!("Collection: {}", collection.as_deref().unwrap_or("none"));
+    eprintln!(
+        "Compressed: {}",
+        asset.compression.compressed.map_or_else(
+            || "unknown".to_string(),
+            |compressed| compressed.to_string()
+        )
+    );
+    eprintln!("Metadata:   {}", asset.content.json_uri);
+    Ok(dialoguer::Confirm::new()
+        .with_prompt("Burn this asset? This can not be undone")
+        .default(false)
+        .interact()?)
+}
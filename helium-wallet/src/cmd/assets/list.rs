@@ -0,0 +1,79 @@
+use crate::cmd::*;
+use futures::TryStreamExt;
+use helium_lib::{
+    asset::{self, Asset},
+    client::DasSearchAssetsParams,
+    keypair::{self, Pubkey},
+};
+
+#[derive(Clone, Debug, clap::Args)]
+/// List the assets (NFTs) held by the active or a given wallet
+///
+/// Lists both compressed NFTs, like Hotspots, and regular NFTs, like staking
+/// positions. Burnt assets are left out.
+pub struct Cmd {
+    /// The alternate wallet to list the assets of
+    wallet: Option<Pubkey>,
+    /// Only list assets in the given collection
+    #[arg(long)]
+    collection: Option<Pubkey>,
+    /// Only list compressed NFTs
+    #[arg(long)]
+    compressed_only: bool,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let owner = match self.wallet {
+            Some(wallet) => wallet,
+            None => opts.load_wallet()?.public_key,
+        };
+        let client = opts.client()?;
+        let mut params = match self.collection {
+            Some(collection) => DasSearchAssetsParams::for_collection(collection),
+            None => DasSearchAssetsParams::default(),
+        }
+        .with_owner(owner)
+        .with_burnt(false);
+        if self.compressed_only {
+            params = params.with_compressed(true);
+        }
+        let assets: Vec<AssetSummary> = asset::search_stream(&client, params)
+            .map_ok(AssetSummary::from)
+            .try_collect()
+            .await?;
+        print_json(&json!({
+            "address": owner.to_string(),
+            "assets": assets,
+        }))
+    }
+}
+
+#[derive(serde::Serialize)]
+struct AssetSummary {
+    #[serde(with = "keypair::serde_pubkey")]
+    id: Pubkey,
+    name: String,
+    symbol: String,
+    #[serde(with = "keypair::serde_opt_pubkey")]
+    collection: Option<Pubkey>,
+    /// Unknown when DAS does not report it
+    compressed: Option<bool>,
+}
+
+impl From<Asset> for AssetSummary {
+    fn from(value: Asset) -> Self {
+        let collection = value
+            .grouping
+            .iter()
+            .find(|group| group.group_key == "collection")
+            .map(|group| group.group_value);
+        Self {
+            id: value.id,
+            name: value.content.metadata.name,
+            symbol: value.content.metadata.symbol,
+            collection,
+            compressed: value.compression.compressed,
+        }
+    }
+}
@@ -2,7 +2,9 @@ use crate::cmd::*;
 
 pub mod burn;
 pub mod info;
+pub mod list;
 pub mod rewards;
+pub mod transfer;
 
 #[derive(Debug, clap::Args)]
 pub struct Cmd {
@@ -21,7 +23,9 @@ impl Cmd {
 pub enum AssetCommand {
     Rewards(rewards::Cmd),
     Info(info::Cmd),
+    List(list::Cmd),
     Burn(burn::Cmd),
+    Transfer(transfer::Cmd),
 }
 
 impl AssetCommand {
@@ -29,7 +33,9 @@ impl AssetCommand {
         match self {
             Self::Rewards(cmd) => cmd.run(opts).await,
             Self::Info(cmd) => cmd.run(opts).await,
+            Self::List(cmd) => cmd.run(opts).await,
             Self::Burn(cmd) => cmd.run(opts).await,
+            Self::Transfer(cmd) => cmd.run(opts).await,
         }
     }
 }
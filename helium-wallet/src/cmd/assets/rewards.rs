@@ -1,6 +1,10 @@
-use crate::cmd::*;
+use crate::cmd::{rewards::FeeEstimate, *};
 use anyhow::Context;
-use helium_lib::{entity_key, reward, reward::ClaimableToken, token::TokenAmount};
+use helium_lib::{
+    entity_key, reward,
+    reward::ClaimableToken,
+    token::{Token, TokenAmount},
+};
 
 #[derive(Debug, Clone, clap::Args)]
 pub struct Cmd {
@@ -45,6 +49,12 @@ pub struct ClaimCmd {
     /// If not specific the full pending amount is claimed, limited by the maximum
     /// claim amount for the subdao
     pub amount: Option<f64>,
+    /// Skip the claim if less than this amount, in tokens, is pending
+    ///
+    /// The skip decision is reported with the estimated transaction fee as a
+    /// fraction of the value of the pending rewards.
+    #[arg(long)]
+    pub min: Option<f64>,
     /// Commit the claim transaction.
     #[command(flatten)]
     pub commit: CommitOpts,
@@ -55,7 +65,31 @@ impl ClaimCmd {
         let password = get_wallet_password(false)?;
         let keypair = opts.load_keypair(password.as_bytes())?;
         let client = opts.client()?;
-        let transaction_opts = self.commit.transaction_opts(&client);
+        let transaction_opts = self.commit.transaction_opts(&client)?;
+
+        if let Some(min) = self.min {
+            let min = TokenAmount::from_f64(self.token.into(), min);
+            let pending = reward::pending(
+                &client,
+                self.token,
+                &[self.entity_key.entity_key.clone()],
+                self.entity_key.encoding.into(),
+            )
+            .await?
+            .remove(&self.entity_key.entity_key)
+            .map(|reward| reward.reward)
+            .unwrap_or_else(|| Token::from(self.token).amount(0));
+            if pending.amount < min.amount {
+                let fees = FeeEstimate::new(&client, self.token, &transaction_opts).await?;
+                return print_json(&json!({
+                    "result": "skipped",
+                    "reason": "below minimum",
+                    "pending": pending,
+                    "fee": fees.fee,
+                    "fee_ratio": fees.ratio(&pending),
+                }));
+            }
+        }
 
         let token_amount = self
             .amount
@@ -0,0 +1,41 @@
+use crate::cmd::*;
+use helium_lib::{
+    asset,
+    keypair::{Pubkey, Signer},
+};
+
+#[derive(Clone, Debug, clap::Args)]
+/// Transfer an asset (NFT) to another owner
+///
+/// Works for compressed NFTs, like Hotspots and maker NFTs, as well as for
+/// regular NFTs like staking positions. Programmable NFTs are not supported.
+pub struct Cmd {
+    /// Id of the asset to transfer
+    asset: Pubkey,
+    /// Solana address of the recipient of the asset
+    recipient: Pubkey,
+    /// Commit the transfer
+    #[command(flatten)]
+    commit: CommitOpts,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let password = get_wallet_password(false)?;
+        let keypair = opts.load_keypair(password.as_bytes())?;
+        if keypair.pubkey() == self.recipient {
+            bail!("recipient already owner of asset");
+        }
+        let client = opts.client()?;
+        let transaction_opts = self.commit.transaction_opts(&client)?;
+        let (tx, _) = asset::transfer(
+            &client,
+            &self.asset,
+            &self.recipient,
+            &keypair,
+            &transaction_opts,
+        )
+        .await?;
+        print_json(&self.commit.maybe_commit(tx, &client).await?.to_json())
+    }
+}
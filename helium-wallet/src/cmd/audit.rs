@@ -0,0 +1,48 @@
+use crate::{audit, cmd::*};
+
+/// Work with the audit log of signed transactions kept with --audit-log
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    #[command(subcommand)]
+    cmd: AuditCommand,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        self.cmd.run(opts).await
+    }
+}
+
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum AuditCommand {
+    Verify(VerifyCmd),
+}
+
+impl AuditCommand {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Self::Verify(cmd) => cmd.run(opts).await,
+        }
+    }
+}
+
+/// Check the hash chain of an audit log
+///
+/// Fails at the first entry that does not link to the line before it. The
+/// hash of the last entry is printed so it can be kept elsewhere and
+/// compared on the next verification, which detects entries removed from
+/// the end of the log.
+#[derive(Debug, Clone, clap::Args)]
+pub struct VerifyCmd {
+    /// Audit log to verify. Defaults to the --audit-log file
+    path: Option<PathBuf>,
+}
+
+impl VerifyCmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let Some(path) = self.path.as_ref().or(opts.audit_log.as_ref()) else {
+            bail!("no audit log given");
+        };
+        print_json(&audit::verify(path)?)
+    }
+}
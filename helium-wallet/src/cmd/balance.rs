@@ -1,33 +1,237 @@
-use crate::cmd::*;
+use crate::{cmd::*, wallet::Wallet};
 use helium_lib::{
     keypair::Pubkey,
+    positions,
     token::{self, Token},
 };
+use tokio::sync::Semaphore;
+
+/// Number of addresses whose token accounts are fetched in a single task. This
+/// keeps the token account lookups for a task within a single
+/// `getMultipleAccounts` call.
+const ADDRESSES_PER_TASK: usize = 20;
 
 #[derive(Debug, clap::Args)]
 /// Get the balance for a wallet or a given public key. The balance is given for
-/// each of the Helium related holdings of a given Solana address
+/// each of the Helium related holdings of a given Solana address.
+///
+/// When multiple addresses or a directory of wallet files are given the
+/// balances for all of them are fetched in batched, concurrent requests.
+/// Failures to read a wallet file or fetch the balance for an address are
+/// reported in the output for that entry instead of aborting the command.
+///
+/// Locked (veHNT) positions held by each address are listed along with the
+/// subdao they are delegated to, if any. The reported HNT total includes both
+/// the liquid and the locked HNT.
 pub struct Cmd {
+    /// Address to get the balance for
     address: Option<Pubkey>,
+    /// Additional address(es) to get the balance for
+    #[arg(short = 'a', long = "address", number_of_values(1))]
+    addresses: Vec<Pubkey>,
+    /// Directory of wallet files to get balances for
+    #[arg(long, conflicts_with_all = ["address", "addresses"])]
+    dir: Option<PathBuf>,
+    /// Maximum number of concurrent balance requests
+    #[arg(long, default_value_t = 5)]
+    concurrency: usize,
+    /// Output format. Table and csv output list the liquid balance of each
+    /// token and the HNT total, without positions.
+    #[arg(long, value_enum, default_value_t)]
+    format: OutputFormat,
 }
 
 impl Cmd {
     pub async fn run(&self, opts: Opts) -> Result {
-        let address = if let Some(address) = self.address {
-            address
-        } else {
-            let wallet = opts.load_wallet()?;
-            wallet.public_key
-        };
+        let mut addresses: Vec<Pubkey> = self
+            .address
+            .into_iter()
+            .chain(self.addresses.iter().copied())
+            .collect();
+        let mut json: Vec<serde_json::Value> = vec![];
+        if addresses.is_empty() {
+            match &self.dir {
+                Some(dir) => {
+                    for file in wallet_files(dir)? {
+                        match read_wallet(&file) {
+                            Ok(wallet) => addresses.push(wallet.public_key),
+                            Err(err) => json.push(json!({
+                                "file": file,
+                                "error": err.to_string(),
+                            })),
+                        }
+                    }
+                }
+                // The given files are shards of a single wallet
+                None => addresses.push(opts.load_wallet()?.public_key),
+            }
+        }
 
         let client = opts.client()?;
-        let balances =
-            token::balance_for_addresses(&client, &Token::associated_token_adresses(&address))
-                .await?;
-        let json = json!({
+        let semaphore = Arc::new(Semaphore::new(self.concurrency.max(1)));
+        let tasks: Vec<_> = addresses
+            .chunks(ADDRESSES_PER_TASK)
+            .map(|chunk| {
+                let client = client.clone();
+                let chunk = chunk.to_vec();
+                let semaphore = semaphore.clone();
+                tokio::spawn(async move {
+                    let _permit = semaphore.acquire_owned().await?;
+                    Ok::<_, Error>(balances_json(&client, &chunk).await)
+                })
+            })
+            .collect();
+        let mut balances = Vec::with_capacity(addresses.len());
+        for task in tasks {
+            balances.extend(task.await??);
+        }
+        json.splice(0..0, balances);
+
+        match self.format {
+            OutputFormat::Json if json.len() == 1 => print_json(&json.remove(0)),
+            OutputFormat::Json => print_json(&json),
+            OutputFormat::Table | OutputFormat::Csv => {
+                print_rows(&balances_table(&json), self.format)
+            }
+        }
+    }
+}
+
+/// Tabulate balance entries with a column per token. Entries that failed
+/// have empty balances and the failure in the error column.
+fn balances_table(entries: &[serde_json::Value]) -> prettytable::Table {
+    let tokens = Token::all();
+    let mut table = prettytable::Table::new();
+    table.set_format(*prettytable::format::consts::FORMAT_CLEAN);
+    let mut titles = prettytable::row!["Address"];
+    for token in &tokens {
+        titles.add_cell(prettytable::Cell::new(&token.to_string()));
+    }
+    titles.add_cell(prettytable::Cell::new("Total HNT"));
+    titles.add_cell(prettytable::Cell::new("Error"));
+    table.set_titles(titles);
+
+    for entry in entries {
+        let address = entry
+            .get("address")
+            .or_else(|| entry.get("file"))
+            .and_then(|address| address.as_str())
+            .unwrap_or_default();
+        let amount = |amount: &serde_json::Value| match entry.get("balance") {
+            Some(_) => amount.as_f64().unwrap_or_default().to_string(),
+            None => String::new(),
+        };
+        let mut row = prettytable::row![address];
+        for token in &tokens {
+            let balance = &entry["balance"][token.to_string()]["amount"];
+            row.add_cell(prettytable::Cell::new(&amount(balance)));
+        }
+        row.add_cell(prettytable::Cell::new(&amount(
+            &entry["total_hnt"]["amount"],
+        )));
+        row.add_cell(prettytable::Cell::new(
+            entry["error"].as_str().unwrap_or_default(),
+        ));
+        table.add_row(row);
+    }
+    table
+}
+
+fn wallet_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<PathBuf>>>()?;
+    files.retain(|path| path.is_file());
+    files.sort();
+    Ok(files)
+}
+
+fn read_wallet(path: &Path) -> Result<Wallet> {
+    let mut reader = fs::File::open(path)?;
+    Wallet::read(&mut reader)
+}
+
+/// Fetch the balances and positions for the given addresses, reporting a
+/// failure for an address as an error entry in the result.
+pub(crate) async fn balances_json(
+    client: &client::Client,
+    addresses: &[Pubkey],
+) -> Vec<serde_json::Value> {
+    let error_json = |address: &Pubkey, err: &helium_lib::error::Error| {
+        json!({
+            "address": address.to_string(),
+            "error": err.to_string(),
+        })
+    };
+    let token_addresses: Vec<Pubkey> = addresses
+        .iter()
+        .flat_map(Token::associated_token_adresses)
+        .collect();
+    let balances = match token::balances_for_many(client, &token_addresses).await {
+        Ok(balances) => balances,
+        Err(err) => {
+            return addresses
+                .iter()
+                .map(|address| error_json(address, &err))
+                .collect()
+        }
+    };
+
+    let mut result = Vec::with_capacity(addresses.len());
+    for (address, balances) in addresses.iter().zip(balances.chunks(Token::all().len())) {
+        let positions = match positions::for_owner(client, address).await {
+            Ok(positions) => positions,
+            Err(err) => {
+                result.push(error_json(address, &err));
+                continue;
+            }
+        };
+        let balances: Vec<token::TokenBalance> = balances.iter().flatten().cloned().collect();
+        let total_hnt = balances
+            .iter()
+            .map(|balance| &balance.amount)
+            .chain(positions.iter().map(|position| &position.amount))
+            .filter(|amount| amount.token == Token::Hnt)
+            .map(|amount| amount.amount)
+            .sum();
+        result.push(json!({
             "address": address.to_string(),
             "balance": token::TokenBalanceMap::from(balances),
-        });
-        print_json(&json)
+            "positions": positions,
+            "total_hnt": Token::Hnt.amount(total_hnt),
+        }));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn balances_csv() {
+        let entries = vec![
+            json!({
+                "address": "addr",
+                "balance": {
+                    "hnt": {"address": "ata", "amount": 1.5},
+                    "dc": {"address": "ata", "amount": 100},
+                },
+                "positions": [],
+                "total_hnt": {"token": "hnt", "amount": 1.5},
+            }),
+            json!({"file": "missing.key", "error": "not found"}),
+        ];
+        let csv = balances_table(&entries)
+            .to_csv(Vec::new())
+            .expect("csv")
+            .into_inner()
+            .expect("csv");
+        assert_eq!(
+            String::from_utf8(csv).expect("utf8"),
+            "Address,hnt,iot,mobile,dc,sol,Total HNT,Error\n\
+             addr,1.5,0,0,100,0,1.5,\n\
+             missing.key,,,,,,,not found\n"
+        );
     }
 }
@@ -0,0 +1,23 @@
+use crate::cmd::*;
+use helium_lib::boosting;
+
+#[derive(Debug, clap::Args)]
+/// Show the global hex boosting configuration
+///
+/// This includes the price oracle and payment mint used to pay for boosts,
+/// the USD price per boost period and the minimum number of periods of a
+/// new boost.
+pub struct Cmd {}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let client = opts.client()?;
+        let config = boosting::boost_config(&client).await?;
+        let mut json = json!(config);
+        json["period_duration"] =
+            humantime::format_duration(std::time::Duration::from_secs(config.period_length.into()))
+                .to_string()
+                .into();
+        print_json(&json)
+    }
+}
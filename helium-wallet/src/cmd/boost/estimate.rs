@@ -0,0 +1,45 @@
+use crate::cmd::*;
+use helium_lib::boosting;
+
+#[derive(Debug, clap::Args)]
+/// Estimate the cost of boosting a hex in MOBILE and USD
+///
+/// Boosts cost the same for all device types. The estimate uses the current
+/// MOBILE oracle price, so the MOBILE cost of an actual boost may differ
+/// slightly.
+pub struct Cmd {
+    /// Number of periods to boost for
+    #[arg(long)]
+    periods: u16,
+    /// Boost multiplier applied to each period
+    #[arg(long, default_value_t = 1)]
+    multiplier: u8,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        if self.multiplier == 0 {
+            bail!("multiplier must be at least 1");
+        }
+        let client = opts.client()?;
+        let config = boosting::boost_config(&client).await?;
+        // cost is linear in the multiplier, so a boost at Nx costs the same as
+        // N times as many 1x periods
+        let periods = self
+            .periods
+            .checked_mul(self.multiplier.into())
+            .ok_or_else(|| anyhow!("too many boosted periods"))?;
+        let cost = boosting::estimate_cost(&client, periods).await?;
+        let json = json!({
+            "periods": self.periods,
+            "multiplier": self.multiplier,
+            "minimum_periods": config.minimum_periods,
+            "period_length": humantime::format_duration(std::time::Duration::from_secs(
+                config.period_length.into()
+            ))
+            .to_string(),
+            "cost": cost,
+        });
+        print_json(&json)
+    }
+}
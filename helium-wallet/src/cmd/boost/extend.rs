@@ -0,0 +1,71 @@
+use crate::cmd::*;
+use helium_lib::{
+    boosting::{self, BoostDeviceType},
+    h3o,
+};
+
+#[derive(Debug, clap::Args)]
+/// Extend the existing boost of a hex by a number of periods
+///
+/// The boost does not have to have been started by this wallet; this wallet
+/// only pays for the added periods. The plan, including the MOBILE cost of
+/// the added periods and the new end of the boost, is always printed.
+/// Without --commit the extension is only simulated.
+pub struct Cmd {
+    /// The boosted H3 hex to extend
+    #[arg(long)]
+    hex: h3o::CellIndex,
+    /// Number of periods to add to the boost
+    #[arg(long)]
+    periods: u16,
+    /// Device type of the boost to extend. Required when the hex is boosted
+    /// for more than one device type
+    #[arg(long, value_enum)]
+    device_type: Option<BoostDeviceType>,
+    /// Boost multiplier for the added periods. Defaults to the multiplier of
+    /// the last boosted period
+    #[arg(long)]
+    multiplier: Option<u8>,
+    #[command(flatten)]
+    carrier: super::CarrierOpts,
+    /// Commit the extension
+    #[command(flatten)]
+    commit: CommitOpts,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let password = get_wallet_password(false)?;
+        let keypair = opts.load_keypair(password.as_bytes())?;
+        let client = opts.client()?;
+        let transaction_opts = self.commit.transaction_opts(&client)?;
+
+        let (carrier, authority) = self.carrier.load(&client, &keypair).await?;
+        let mut boosted = boosting::boosted_hexes(&client, &carrier.address, self.hex).await?;
+        if let Some(device_type) = self.device_type {
+            boosted.retain(|boosted| boosted.device_type == Some(device_type));
+        }
+        let boosted = match boosted.as_slice() {
+            [] => bail!("hex {} is not boosted, use boost start", self.hex),
+            [boosted] => boosted,
+            _ => bail!(
+                "hex {} is boosted for multiple device types, use --device-type",
+                self.hex
+            ),
+        };
+        let (tx, _, plan) = boosting::extend_boost(
+            &client,
+            &carrier,
+            boosted,
+            self.periods,
+            self.multiplier,
+            &keypair,
+            authority.as_deref(),
+            &transaction_opts,
+        )
+        .await?;
+        let mut json = self.commit.maybe_commit(tx, &client).await?.to_json();
+        json["boost"] = json!(plan);
+        print_json(&json)
+    }
+}
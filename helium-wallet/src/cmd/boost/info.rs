@@ -0,0 +1,67 @@
+use crate::cmd::*;
+use helium_lib::{
+    boosting::{self, BoostDeviceType},
+    h3o,
+};
+
+#[derive(Debug, clap::Args)]
+/// Show the boosts of a hex
+///
+/// Any hex can be looked up, whether or not it was boosted by this wallet.
+/// Device types the hex has not been boosted for are reported as null.
+/// Boosts made before device types were introduced apply to all device
+/// types, and are shown for each device type without a newer boost.
+pub struct Cmd {
+    /// The H3 hex to look up
+    hex: h3o::CellIndex,
+    /// Only show boosts for the given device type
+    #[arg(long, value_enum)]
+    device_type: Option<BoostDeviceType>,
+    /// Name of the carrier the hex is boosted under
+    #[arg(long, default_value = boosting::HELIUM_MOBILE_CARRIER)]
+    carrier_name: String,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let client = opts.client()?;
+        let config = boosting::boost_config(&client).await?;
+        let carrier = boosting::carrier_key(&self.carrier_name);
+        let device_types = self
+            .device_type
+            .map(|device_type| vec![device_type])
+            .unwrap_or_else(|| BoostDeviceType::ALL.to_vec());
+        let now = chrono::Utc::now();
+        let boosted_hexes = boosting::boosted_hexes(&client, &carrier, self.hex).await?;
+        let mut boosts = serde_json::Map::new();
+        for device_type in device_types {
+            let boosted = boosted_hexes
+                .iter()
+                .find(|boosted| boosted.device_type == Some(device_type))
+                .or_else(|| {
+                    boosted_hexes
+                        .iter()
+                        .find(|boosted| boosted.device_type.is_none())
+                });
+            let value = boosted.map(|boosted| {
+                let current_period = boosted.current_period(&config, now);
+                json!({
+                    "address": boosted.address.to_string(),
+                    "version": boosted.version,
+                    "start": boosted.start,
+                    "end": boosted.end(&config),
+                    "current_period": current_period,
+                    "current_multiplier": boosted.boosts_by_period.get(current_period),
+                    "boosts_by_period": boosted.boosts_by_period,
+                })
+            });
+            boosts.insert(device_type.to_string(), json!(value));
+        }
+        let json = json!({
+            "hex": self.hex.to_string(),
+            "boosted": boosts.values().any(|value| !value.is_null()),
+            "boosts": boosts,
+        });
+        print_json(&json)
+    }
+}
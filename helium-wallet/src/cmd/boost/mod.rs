@@ -0,0 +1,90 @@
+use crate::cmd::*;
+use helium_lib::{
+    boosting::{self, Carrier},
+    keypair::Signer,
+};
+
+pub mod config;
+pub mod estimate;
+pub mod extend;
+pub mod info;
+pub mod start;
+
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    #[command(subcommand)]
+    cmd: BoostCommand,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        self.cmd.run(opts).await
+    }
+}
+
+/// Boost Helium Mobile coverage of hexes
+#[derive(Debug, clap::Subcommand)]
+pub enum BoostCommand {
+    Start(start::Cmd),
+    Extend(extend::Cmd),
+    Estimate(estimate::Cmd),
+    Info(info::Cmd),
+    Config(config::Cmd),
+}
+
+impl BoostCommand {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Self::Start(cmd) => cmd.run(opts).await,
+            Self::Extend(cmd) => cmd.run(opts).await,
+            Self::Estimate(cmd) => cmd.run(opts).await,
+            Self::Info(cmd) => cmd.run(opts).await,
+            Self::Config(cmd) => cmd.run(opts).await,
+        }
+    }
+}
+
+/// Options selecting the carrier to boost under
+#[derive(Debug, Clone, clap::Args)]
+pub struct CarrierOpts {
+    /// Name of the carrier to boost under
+    #[arg(long, default_value = boosting::HELIUM_MOBILE_CARRIER)]
+    carrier_name: String,
+    /// Wallet file of the hex boost authority of the carrier. Required
+    /// unless the boosting wallet is the hex boost authority itself
+    #[arg(long)]
+    carrier: Option<PathBuf>,
+}
+
+impl CarrierOpts {
+    /// Load the carrier and, if given, the carrier authority keypair,
+    /// checking that one of the wallets can sign for the carrier
+    pub async fn load(
+        &self,
+        client: &client::Client,
+        payer: &Keypair,
+    ) -> Result<(Carrier, Option<Arc<Keypair>>)> {
+        let carrier = boosting::carrier(client, &self.carrier_name).await?;
+        if !carrier.approved {
+            bail!("carrier {} is not approved", carrier.name);
+        }
+        let authority = match &self.carrier {
+            Some(path) => {
+                let wallet = Wallet::read(&mut fs::File::open(path)?)?;
+                let password = get_password("Carrier Wallet Password", false)?;
+                Some(wallet.decrypt(password.as_bytes())?)
+            }
+            None => None,
+        };
+        let signer = authority.as_deref().unwrap_or(payer);
+        if signer.pubkey() != carrier.hexboost_authority {
+            bail!(
+                "{} is not the hex boost authority {} of carrier {}, use --carrier",
+                signer.pubkey(),
+                carrier.hexboost_authority,
+                carrier.name
+            );
+        }
+        Ok((carrier, authority))
+    }
+}
@@ -0,0 +1,187 @@
+use crate::cmd::*;
+use helium_lib::{
+    boosting::{self, BoostDeviceType, HexBoost},
+    h3o,
+};
+use rust_decimal::Decimal;
+
+#[derive(Debug, clap::Args)]
+/// Boost one or more hexes for a number of periods, paying in MOBILE
+///
+/// The boost plan, including the total MOBILE cost at the current MOBILE
+/// price and the end of the boosted periods, is always printed. Without
+/// --commit the boost is only simulated, so the plan can be reviewed before
+/// committing. When committing, the plan is shown and has to be confirmed,
+/// unless --yes is given.
+///
+/// When boosting hexes from a file, as many boosts as fit are packed in each
+/// transaction and the result is reported for every hex.
+pub struct Cmd {
+    /// The H3 hex to boost
+    #[arg(long, required_unless_present = "file", conflicts_with = "file")]
+    hex: Option<h3o::CellIndex>,
+    /// File with the H3 hexes to boost, one per line. Empty lines and lines
+    /// starting with # are ignored
+    #[arg(long)]
+    file: Option<PathBuf>,
+    /// Number of periods to boost the hexes for
+    #[arg(long)]
+    periods: u16,
+    /// Device type to boost the hexes for
+    #[arg(long, value_enum)]
+    device_type: BoostDeviceType,
+    /// Boost multiplier applied to each period
+    #[arg(long, default_value_t = 1)]
+    multiplier: u8,
+    #[command(flatten)]
+    carrier: super::CarrierOpts,
+    /// Boost without asking for confirmation
+    #[arg(long)]
+    yes: bool,
+    /// Commit the boost
+    #[command(flatten)]
+    commit: CommitOpts,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        if self.periods == 0 {
+            bail!("periods must be at least 1");
+        }
+        let password = get_wallet_password(false)?;
+        let keypair = opts.load_keypair(password.as_bytes())?;
+        let client = opts.client()?;
+        let transaction_opts = self.commit.transaction_opts(&client)?;
+        let (carrier, authority) = self.carrier.load(&client, &keypair).await?;
+
+        let boost = |hex| {
+            HexBoost::new(hex, self.device_type, self.periods).with_multiplier(self.multiplier)
+        };
+        if let Some(hex) = self.hex {
+            let (tx, _, plan) = boosting::boost_hex(
+                &client,
+                &carrier,
+                &boost(hex),
+                &keypair,
+                authority.as_deref(),
+                &transaction_opts,
+            )
+            .await?;
+            if self.commit.commit && !self.yes && !confirm_boost(&plan)? {
+                bail!("boost cancelled");
+            }
+            let mut json = self.commit.maybe_commit(tx, &client).await?.to_json();
+            json["boost"] = json!(plan);
+            return print_json(&json);
+        }
+
+        let boosts = self
+            .read_hexes()?
+            .into_iter()
+            .map(boost)
+            .collect::<Vec<_>>();
+        if boosts.is_empty() {
+            bail!("no hexes to boost");
+        }
+        let packed = boosting::pack_boosts(
+            &client,
+            &carrier,
+            &boosts,
+            &keypair.pubkey(),
+            &transaction_opts,
+        )
+        .await?;
+        let plans = packed.plans.iter().flatten().collect::<Vec<_>>();
+        if self.commit.commit && !plans.is_empty() && !self.yes && !confirm_boosts(&plans)? {
+            bail!("boost cancelled");
+        }
+        let mut results = packed
+            .rejected
+            .iter()
+            .map(|(boost, err)| {
+                json!({
+                    "hex": boost.hex.to_string(),
+                    "result": "error",
+                    "error": err.to_string(),
+                })
+            })
+            .collect::<Vec<_>>();
+        for (index, plans) in packed.plans.iter().enumerate() {
+            // Each transaction is built just before it is sent so it gets a
+            // fresh blockhash
+            let commit = match packed
+                .transaction(
+                    &client,
+                    index,
+                    &keypair,
+                    authority.as_deref(),
+                    &transaction_opts,
+                )
+                .await
+            {
+                Ok((tx, _)) => self.commit.maybe_commit(tx, &client).await,
+                Err(err) => Err(err.into()),
+            }
+            .to_json();
+            results.extend(plans.iter().map(|plan| {
+                json!({
+                    "hex": plan.hex.to_string(),
+                    "boost": plan,
+                    "commit": commit,
+                })
+            }));
+        }
+        print_json(&results)
+    }
+
+    fn read_hexes(&self) -> Result<Vec<h3o::CellIndex>> {
+        let Some(path) = &self.file else {
+            return Ok(vec![]);
+        };
+        fs::read_to_string(path)?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                line.parse::<h3o::CellIndex>()
+                    .map_err(|err| anyhow!("invalid hex {line}: {err}"))
+            })
+            .collect()
+    }
+}
+
+/// Show a boost plan and ask whether to go ahead
+fn confirm_boost(plan: &boosting::BoostPlan) -> Result<bool> {
+    eprintln!("Hex:         {}", plan.hex);
+    eprintln!("Device type: {:?}", plan.device_type);
+    eprintln!("Periods:     {} to {}", plan.periods.0, plan.periods.1);
+    if let Some(end) = plan.end {
+        eprintln!("Ends:        {end}");
+    }
+    eprintln!("Cost:        {}", output::format_amount(&plan.cost.mobile));
+    eprintln!("Cost (USD):  {}", plan.cost.usd);
+    Ok(dialoguer::Confirm::new()
+        .with_prompt("Boost at this cost?")
+        .default(false)
+        .interact()?)
+}
+
+/// Show the total cost of boosting a number of hexes and ask whether to go
+/// ahead
+fn confirm_boosts(plans: &[&boosting::BoostPlan]) -> Result<bool> {
+    let mobile = plans
+        .iter()
+        .map(|plan| plan.cost.mobile.amount)
+        .sum::<u64>();
+    let usd = plans.iter().map(|plan| plan.cost.usd).sum::<Decimal>();
+    eprintln!("Hexes:       {}", plans.len());
+    eprintln!(
+        "Cost:        {}",
+        output::format_amount(&Token::Mobile.amount(mobile))
+    );
+    eprintln!("Cost (USD):  {usd}");
+    Ok(dialoguer::Confirm::new()
+        .with_prompt("Boost all hexes at this cost?")
+        .default(false)
+        .interact()?)
+}
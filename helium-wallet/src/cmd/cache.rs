@@ -0,0 +1,67 @@
+use crate::cmd::*;
+
+/// Manage the on-disk cache of Hotspot key to asset mappings
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    #[command(subcommand)]
+    cmd: CacheCommand,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        self.cmd.run(opts).await
+    }
+}
+
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum CacheCommand {
+    Clear(ClearCmd),
+}
+
+impl CacheCommand {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Self::Clear(cmd) => cmd.run(opts).await,
+        }
+    }
+}
+
+/// The directory cached data is kept in: `$XDG_CACHE_HOME/helium-wallet`,
+/// or `~/.cache/helium-wallet` when that is not set
+pub fn dir() -> Option<PathBuf> {
+    env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .map(|dir| dir.join("helium-wallet"))
+}
+
+/// Remove all cached data, for every cluster
+#[derive(Debug, Clone, clap::Args)]
+pub struct ClearCmd {}
+
+impl ClearCmd {
+    pub async fn run(&self, _opts: Opts) -> Result {
+        let Some(dir) = dir() else {
+            bail!("no cache directory found");
+        };
+        let mut removed = vec![];
+        match fs::read_dir(&dir) {
+            Ok(entries) => {
+                for entry in entries {
+                    let path = entry?.path();
+                    if path.is_file() {
+                        fs::remove_file(&path)?;
+                        removed.push(path.display().to_string());
+                    }
+                }
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => (),
+            Err(err) => return Err(err.into()),
+        }
+        print_json(&json!({
+            "directory": dir.display().to_string(),
+            "removed": removed,
+        }))
+    }
+}
@@ -0,0 +1,79 @@
+use crate::cmd::*;
+use futures::TryStreamExt;
+use helium_lib::{
+    dao::{self, epoch_info::SubDaoEpochInfo, SubDao},
+    stats,
+};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum HistoryFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Debug, clap::Args)]
+/// Get the per epoch history of a subdao
+///
+/// Each epoch record holds the HNT emitted to the subdao, the veHNT delegated
+/// at the start of the epoch, the DC burned and the utility score. Records are
+/// written as CSV by default, to stdout or the given output file.
+pub struct Cmd {
+    /// Subdao to get the history for
+    #[arg(value_enum)]
+    subdao: SubDao,
+    /// Period to get the history for, e.g. "7d" or "90d"
+    #[arg(long, default_value = "30d", value_parser = humantime::parse_duration)]
+    period: Duration,
+    /// First epoch to include. Overrides --period
+    #[arg(long)]
+    from_epoch: Option<u64>,
+    /// Last epoch to include. Defaults to the current epoch
+    #[arg(long, requires = "from_epoch")]
+    to_epoch: Option<u64>,
+    /// Output format
+    #[arg(long, value_enum, default_value_t = HistoryFormat::Csv)]
+    format: HistoryFormat,
+    /// File to write the history to
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let client = opts.client()?;
+        let epochs = match self.from_epoch {
+            Some(from) => {
+                let to = self
+                    .to_epoch
+                    .unwrap_or_else(|| dao::epoch_for(chrono::Utc::now()));
+                if to < from {
+                    bail!("--to-epoch must not be before --from-epoch");
+                }
+                from..to + 1
+            }
+            None => stats::epochs_for_period(self.period),
+        };
+        let records: Vec<SubDaoEpochInfo> = dao::epoch_history(&client, self.subdao, epochs)
+            .try_collect()
+            .await?;
+
+        let writer: Box<dyn io::Write> = match &self.output {
+            Some(path) => Box::new(fs::File::create(path)?),
+            None => Box::new(io::stdout()),
+        };
+        match self.format {
+            HistoryFormat::Json => {
+                serde_json::to_writer_pretty(writer, &records)?;
+            }
+            HistoryFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(writer);
+                for record in &records {
+                    writer.serialize(record)?;
+                }
+                writer.flush()?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use crate::cmd::*;
+use chrono::Utc;
+use helium_lib::dao::{self, epoch_info, SubDao};
+
+#[derive(Debug, clap::Args)]
+/// Get the current epoch, emissions, utility score inputs, delegated veHNT
+/// and treasury balances of the DAO and its subdaos
+///
+/// Epoch info is reported for the current epoch, which is still in progress,
+/// and the last completed epoch, which holds the final utility scores and
+/// issued rewards.
+pub struct Cmd {
+    /// Subdao to get info for. Defaults to both IOT and MOBILE
+    #[arg(value_enum)]
+    subdao: Option<SubDao>,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let client = opts.client()?;
+        let subdaos = match self.subdao {
+            Some(subdao) => vec![subdao],
+            None => SubDao::all().to_vec(),
+        };
+        let current = dao::epoch_for(Utc::now());
+        let epochs = [current, current.saturating_sub(1)];
+
+        let dao_epochs = epoch_info::dao(&client, &epochs).await?;
+        let mut subdao_json = vec![];
+        for subdao in subdaos {
+            let info = epoch_info::sub_dao_info(&client, subdao).await?;
+            let subdao_epochs = epoch_info::sub_dao(&client, subdao, &epochs).await?;
+            let mut json = json!(info);
+            json["current_epoch"] = json!(subdao_epochs[0]);
+            json["last_epoch"] = json!(subdao_epochs[1]);
+            subdao_json.push(json);
+        }
+        print_json(&json!({
+            "epoch": current,
+            "epoch_start": dao::epoch_start(current),
+            "dao": {
+                "current_epoch": dao_epochs[0],
+                "last_epoch": dao_epochs[1],
+            },
+            "subdaos": subdao_json,
+        }))
+    }
+}
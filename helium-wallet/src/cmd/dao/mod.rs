@@ -0,0 +1,32 @@
+use crate::cmd::*;
+
+pub mod history;
+pub mod info;
+
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    #[command(subcommand)]
+    cmd: DaoCommand,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        self.cmd.run(opts).await
+    }
+}
+
+/// Inspect the state of the Helium DAO and its subdaos
+#[derive(Debug, clap::Subcommand)]
+pub enum DaoCommand {
+    Info(info::Cmd),
+    History(history::Cmd),
+}
+
+impl DaoCommand {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Self::Info(cmd) => cmd.run(opts).await,
+            Self::History(cmd) => cmd.run(opts).await,
+        }
+    }
+}
@@ -17,7 +17,7 @@ impl Cmd {
         let password = get_wallet_password(false)?;
         let keypair = opts.load_keypair(password.as_bytes())?;
         let client = opts.client()?;
-        let transaction_opts = self.commit.transaction_opts(&client);
+        let transaction_opts = self.commit.transaction_opts(&client)?;
 
         let (tx, _) = dc::burn(&client, self.dc, &keypair, &transaction_opts).await?;
         print_json(&self.commit.maybe_commit(tx, &client).await?.to_json())
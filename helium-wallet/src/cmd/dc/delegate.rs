@@ -3,15 +3,47 @@ use helium_lib::{dao::SubDao, dc};
 
 #[derive(Debug, Clone, clap::Args)]
 /// Delegate DC from this wallet to a given router
+///
+/// The DC are moved into the escrow account of the router payer key in the
+/// given subdao, topping up the balance the router's traffic is paid from.
+///
+/// Delegation is one way. Delegated DC can only be burned for the router's
+/// traffic and can not be undelegated or moved to another router payer key,
+/// so only delegate what the router is expected to use.
+///
+/// The subdao, payer key and amount can also be given positionally, in that
+/// order.
 pub struct Cmd {
-    /// Subdao to delegate DC to
-    subdao: SubDao,
+    /// Subdao to delegate DC to [default: iot]
+    #[arg(long, value_enum, conflicts_with = "subdao_arg")]
+    subdao: Option<SubDao>,
 
-    /// Public Helium payer key to delegate to
-    payer: String,
+    /// Public Helium payer key of the router to delegate to
+    #[arg(
+        long,
+        visible_alias = "payer",
+        required_unless_present = "payer_arg",
+        conflicts_with = "payer_arg"
+    )]
+    router: Option<String>,
 
-    /// Amount of DC to delgate
-    dc: u64,
+    /// Amount of DC to delegate
+    #[arg(
+        long,
+        visible_alias = "dc",
+        required_unless_present = "dc_arg",
+        conflicts_with = "dc_arg"
+    )]
+    amount: Option<u64>,
+
+    #[arg(value_enum, hide = true)]
+    subdao_arg: Option<SubDao>,
+
+    #[arg(hide = true)]
+    payer_arg: Option<String>,
+
+    #[arg(hide = true)]
+    dc_arg: Option<u64>,
 
     /// Commit the delegation
     #[command(flatten)]
@@ -23,17 +55,21 @@ impl Cmd {
         let password = get_wallet_password(false)?;
         let keypair = opts.load_keypair(password.as_bytes())?;
 
+        let subdao = self.subdao.or(self.subdao_arg).unwrap_or(SubDao::Iot);
+        let router = self
+            .router
+            .as_ref()
+            .or(self.payer_arg.as_ref())
+            .ok_or_else(|| anyhow!("router payer key required"))?;
+        let amount = self
+            .amount
+            .or(self.dc_arg)
+            .ok_or_else(|| anyhow!("amount required"))?;
+
         let client = opts.client()?;
-        let transaction_opts = self.commit.transaction_opts(&client);
-        let (tx, _) = dc::delegate(
-            &client,
-            self.subdao,
-            &self.payer,
-            self.dc,
-            &keypair,
-            &transaction_opts,
-        )
-        .await?;
+        let transaction_opts = self.commit.transaction_opts(&client)?;
+        let (tx, _) =
+            dc::delegate(&client, subdao, router, amount, &keypair, &transaction_opts).await?;
         print_json(&self.commit.maybe_commit(tx, &client).await?.to_json())
     }
 }
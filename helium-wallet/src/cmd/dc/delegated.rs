@@ -0,0 +1,37 @@
+use crate::cmd::*;
+use helium_lib::{dao::SubDao, dc};
+
+#[derive(Debug, Clone, clap::Args)]
+/// List the delegated DC balances of one or more router payer keys
+pub struct Cmd {
+    /// Public Helium payer key(s) of the routers to list balances for
+    #[arg(long = "router", number_of_values(1), required = true)]
+    routers: Vec<String>,
+
+    /// Subdao(s) to list delegated balances in
+    ///
+    /// Defaults to both IOT and MOBILE.
+    #[arg(long, value_enum, number_of_values(1))]
+    subdao: Vec<SubDao>,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let client = opts.client()?;
+        let subdaos = if self.subdao.is_empty() {
+            SubDao::all().to_vec()
+        } else {
+            self.subdao.clone()
+        };
+        let escrows = subdaos
+            .iter()
+            .flat_map(|subdao| {
+                self.routers
+                    .iter()
+                    .map(|router| dc::escrow_account(*subdao, router))
+            })
+            .collect();
+        let json = dc::delegated_balances(&client, escrows).await?;
+        print_json(&json)
+    }
+}
@@ -0,0 +1,80 @@
+use crate::cmd::*;
+use helium_lib::{
+    dao::SubDao,
+    dc,
+    hotspot::{info, HotspotMode, MobileDeviceType},
+    token::{self, Token, TokenAmount},
+};
+
+#[derive(Debug, Clone, clap::Args)]
+/// Estimate the conversion between HNT and Data Credits (DC)
+///
+/// The conversion uses the current HNT price from the price oracle. Given a
+/// subdao, the DC fees for onboarding a Hotspot and asserting its location
+/// are estimated as well, along with the HNT that would need to be burned to
+/// cover them.
+pub struct Cmd {
+    /// Amount of HNT to convert to DC
+    #[arg(long, conflicts_with = "dc")]
+    hnt: Option<f64>,
+
+    /// Amount of DC to convert to HNT
+    #[arg(long, conflicts_with = "hnt")]
+    dc: Option<u64>,
+
+    /// Subdao to estimate Hotspot onboarding and assert fees for
+    #[arg(long, value_enum)]
+    subdao: Option<SubDao>,
+
+    /// Mode of the Hotspot to estimate fees for
+    #[arg(long, value_enum, default_value_t = HotspotMode::Full, requires = "subdao")]
+    mode: HotspotMode,
+
+    /// Device type of the Mobile Hotspot to estimate fees for
+    #[arg(long, value_enum, default_value_t, requires = "subdao")]
+    device_type: MobileDeviceType,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let amount = match (self.hnt, self.dc) {
+            (Some(hnt), None) => Some(TokenAmount::from_f64(Token::Hnt, hnt)),
+            (None, Some(dc)) => Some(Token::Dc.amount(dc)),
+            _ => None,
+        };
+        if amount.is_none() && self.subdao.is_none() {
+            bail!("Must specify an HNT or DC amount or a subdao");
+        }
+
+        let client = opts.client()?;
+        let price = token::price::get(&client, Token::Hnt).await?;
+        let mut json = json!({
+            "hnt_price": price.price,
+            "timestamp": price.timestamp,
+        });
+        if let Some(amount) = amount {
+            let estimate = dc::Estimate::from_price(amount, &price)?;
+            json["hnt"] = json!(estimate.hnt);
+            json["dc"] = json!(estimate.dc);
+            json["usd"] = json!(estimate.usd);
+        }
+        if let Some(subdao) = self.subdao {
+            let fees = info::fees(&client, subdao, self.mode, self.device_type).await?;
+            let fee_json = |fee: TokenAmount| -> Result<serde_json::Value> {
+                let estimate = dc::Estimate::from_price(fee, &price)?;
+                Ok(json!({
+                    "dc": estimate.dc,
+                    "hnt": estimate.hnt,
+                    "usd": estimate.usd,
+                }))
+            };
+            json["fees"] = json!({
+                "subdao": subdao,
+                "mode": self.mode,
+                "onboard": fee_json(fees.onboard)?,
+                "assert": fee_json(fees.location)?,
+            });
+        }
+        print_json(&json)
+    }
+}
@@ -1,5 +1,6 @@
 use crate::cmd::*;
 use helium_lib::{
+    dao::SubDao,
     dc,
     keypair::Pubkey,
     token::{Token, TokenAmount},
@@ -9,15 +10,27 @@ use helium_lib::{
 /// Mint HNT to Data Credits (DC) from this wallet to a given payee's wallet.
 ///
 /// Either the amount of HNT to burn or the amount of DC expected after the burn
-/// can be specified.
+/// can be specified. When a number of DC is requested the HNT price oracle is
+/// used to report the estimated amount of HNT that will be burned.
+///
+/// With --router the minted DC are delegated to the given router payer key in
+/// the same transaction, which requires the number of DC to be given.
 pub struct Cmd {
     /// Account address to send the resulting DC to. Defaults to the active
     /// wallet.
-    #[arg(long)]
+    #[arg(long, visible_alias = "to", conflicts_with = "router")]
     payee: Option<Pubkey>,
 
+    /// Public Helium payer key of a router to delegate the minted DC to
+    #[arg(long, requires_all = ["subdao", "dc"])]
+    router: Option<String>,
+
+    /// Subdao to delegate the minted DC in
+    #[arg(long, value_enum)]
+    subdao: Option<SubDao>,
+
     /// Amount of HNT to convert to DC
-    #[arg(long, conflicts_with = "dc")]
+    #[arg(long, visible_alias = "amount", conflicts_with = "dc")]
     hnt: Option<f64>,
 
     /// Amount of DC to create from the HNT in the wallet
@@ -41,10 +54,31 @@ impl Cmd {
             (None, Some(dc)) => TokenAmount::from_u64(Token::Dc, dc),
             _ => return Err(anyhow!("Must specify either HNT or DC")),
         };
-        let transaction_opts = self.commit.transaction_opts(&client);
+        let transaction_opts = self.commit.transaction_opts(&client)?;
 
         let keypair = wallet.decrypt(password.as_bytes())?;
-        let (tx, _) = dc::mint(&client, amount, payee, &keypair, &transaction_opts).await?;
-        print_json(&self.commit.maybe_commit(tx, &client).await?.to_json())
+        let (tx, _) = match (&self.router, self.subdao) {
+            (Some(router), Some(subdao)) => {
+                dc::mint_and_delegate(
+                    &client,
+                    subdao,
+                    router,
+                    amount.amount,
+                    &keypair,
+                    &transaction_opts,
+                )
+                .await?
+            }
+            _ => dc::mint(&client, amount, payee, &keypair, &transaction_opts).await?,
+        };
+
+        let mut json = self.commit.maybe_commit(tx, &client).await?.to_json();
+        if amount.token == Token::Dc {
+            json["estimate"] = json!(dc::estimate(&client, amount).await?);
+        }
+        if let Some(router) = &self.router {
+            json["router"] = router.as_str().into();
+        }
+        print_json(&json)
     }
 }
@@ -2,8 +2,11 @@ use crate::cmd::*;
 
 mod burn;
 mod delegate;
+mod delegated;
+mod estimate;
 mod mint;
 mod price;
+mod redelegate;
 
 #[derive(Debug, clap::Args)]
 pub struct Cmd {
@@ -21,8 +24,11 @@ impl Cmd {
 /// Commands on Data Credits
 pub enum DcCommand {
     Price(price::Cmd),
+    Estimate(estimate::Cmd),
     Mint(mint::Cmd),
     Delegate(delegate::Cmd),
+    Delegated(delegated::Cmd),
+    Redelegate(redelegate::Cmd),
     Burn(burn::Cmd),
 }
 
@@ -30,8 +36,11 @@ impl DcCommand {
     pub async fn run(&self, opts: Opts) -> Result {
         match self {
             Self::Price(cmd) => cmd.run(opts).await,
+            Self::Estimate(cmd) => cmd.run(opts).await,
             Self::Mint(cmd) => cmd.run(opts).await,
             Self::Delegate(cmd) => cmd.run(opts).await,
+            Self::Delegated(cmd) => cmd.run(opts).await,
+            Self::Redelegate(cmd) => cmd.run(opts).await,
             Self::Burn(cmd) => cmd.run(opts).await,
         }
     }
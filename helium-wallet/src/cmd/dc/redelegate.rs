@@ -0,0 +1,43 @@
+use crate::cmd::*;
+use helium_lib::{dao::SubDao, dc};
+
+#[derive(Debug, Clone, clap::Args)]
+/// Move delegated DC from one router payer key to another (not supported)
+///
+/// The data credits program has no instruction to undelegate or redelegate
+/// DC. Delegated DC stay in the escrow account of the router payer key until
+/// the router's traffic burns them, and the escrow account can not be closed
+/// to recover its rent. This command only reports the delegated balance that
+/// can not be moved and fails.
+///
+/// To fund the new router payer key, delegate DC to it with `dc delegate`.
+pub struct Cmd {
+    /// Subdao the DC are delegated in
+    #[arg(long, value_enum, default_value_t = SubDao::Iot)]
+    subdao: SubDao,
+
+    /// Public Helium payer key of the router the DC are delegated to
+    #[arg(long)]
+    from: String,
+
+    /// Public Helium payer key of the router to move the DC to
+    #[arg(long)]
+    to: String,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let client = opts.client()?;
+        let delegated = dc::delegated_balance(&client, self.subdao, &self.from).await?;
+        bail!(
+            "{} DC delegated to {} in {} can not be moved to {}: the data credits program \
+             has no instruction to undelegate or redelegate DC. Delegate DC to {} with \
+             `dc delegate` instead.",
+            delegated.balance.amount,
+            self.from,
+            self.subdao,
+            self.to,
+            self.to
+        )
+    }
+}
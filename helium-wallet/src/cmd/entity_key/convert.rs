@@ -0,0 +1,55 @@
+use crate::cmd::*;
+use helium_lib::{
+    dao::Dao,
+    entity_key::{self, EncodedEntityKey, EntityKeyEncoding},
+};
+use std::collections::BTreeMap;
+
+#[derive(Clone, Debug, clap::Args)]
+/// Show an entity key in all of its encodings
+///
+/// Encodings that do not apply to the key, like UTF-8 for a Hotspot key or
+/// the Solana address for a carrier key, are left out. The address of the
+/// key to asset account for the entity is included as "kta".
+pub struct Cmd {
+    /// The entity key to convert
+    entity_key: String,
+    /// Encoding of the given entity key
+    #[arg(long, value_enum, default_value_t = EntityKeyEncoding::B58)]
+    encoding: EntityKeyEncoding,
+    /// Only output the entity key in this encoding
+    #[arg(long, value_enum)]
+    to: Option<EntityKeyEncoding>,
+}
+
+impl Cmd {
+    pub async fn run(&self, _opts: Opts) -> Result {
+        let encoded = EncodedEntityKey {
+            encoding: self.encoding,
+            entity_key: self.entity_key.clone(),
+        };
+        if let Some(to) = self.to {
+            let converted = encoded.convert(to)?;
+            return print_json(&BTreeMap::from([(to.to_string(), converted.entity_key)]));
+        }
+        let entity_key = encoded.as_entity_key()?;
+        let mut forms: BTreeMap<String, String> = [
+            EntityKeyEncoding::B58,
+            EntityKeyEncoding::UTF8,
+            EntityKeyEncoding::Hex,
+            EntityKeyEncoding::Solana,
+        ]
+        .into_iter()
+        .filter_map(|encoding| {
+            entity_key::encode(&entity_key, encoding)
+                .ok()
+                .map(|encoded| (encoding.to_string(), encoded))
+        })
+        .collect();
+        forms.insert(
+            "kta".to_string(),
+            Dao::Hnt.entity_key_to_kta_key(&entity_key).to_string(),
+        );
+        print_json(&forms)
+    }
+}
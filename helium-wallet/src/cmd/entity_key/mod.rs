@@ -0,0 +1,29 @@
+use crate::cmd::*;
+
+pub mod convert;
+
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    #[command(subcommand)]
+    cmd: EntityKeyCommand,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        self.cmd.run(opts).await
+    }
+}
+
+#[derive(Debug, Clone, clap::Subcommand)]
+/// Commands on entity keys
+pub enum EntityKeyCommand {
+    Convert(convert::Cmd),
+}
+
+impl EntityKeyCommand {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Self::Convert(cmd) => cmd.run(opts).await,
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use super::set_update_location;
 use crate::{cmd::*, txn_envelope::TxnEnvelope};
 use chrono::{DateTime, Utc};
 use helium_crypto::{KeyTag, PublicKey};
@@ -5,7 +6,8 @@ use helium_lib::{
     asset,
     client::{VERIFIER_URL_DEVNET, VERIFIER_URL_MAINNET},
     dao::SubDao,
-    hotspot::{self, cert, HotspotInfoUpdate},
+    h3o,
+    hotspot::{self, cert, HotspotInfoUpdate, MobileDeploymentInfo},
 };
 use helium_proto::BlockchainTxnAddGatewayV1;
 use rand::rngs::OsRng;
@@ -62,6 +64,13 @@ struct IotCmd {
     #[arg(long)]
     lon: Option<f64>,
 
+    /// H3 cell index (hex) of the Hotspot location to assert.
+    ///
+    /// An alternative to latitude and longitude. The cell must be at
+    /// resolution 12.
+    #[arg(long, conflicts_with_all = ["lat", "lon"])]
+    hex: Option<h3o::CellIndex>,
+
     /// The antenna gain for the asserted IoT Hotspot in dBi, with one digit of
     /// accuracy.
     ///
@@ -111,7 +120,7 @@ async fn perform_add(
         "d" | "devnet" => VERIFIER_URL_DEVNET,
         url => url,
     };
-    let transaction_opts = &commit.transaction_opts(&client);
+    let transaction_opts = &commit.transaction_opts(&client)?;
 
     if !hotspot_issued {
         let (tx, _) =
@@ -125,6 +134,8 @@ async fn perform_add(
     // Without this, the command will always fail for brand new hotspots when --commit is not
     // enabled, as it cannot find the key_to_asset account or asset account.
     if hotspot_issued || commit.commit {
+        let deployment_info = update.deployment_info().clone();
+        let location = update.location().clone();
         let (tx, _) = hotspot::dataonly::onboard(
             &client,
             subdao,
@@ -134,7 +145,28 @@ async fn perform_add(
             transaction_opts,
         )
         .await?;
-        print_json(&commit.maybe_commit(tx, &client).await?.to_json())
+        // The deployment info update needs the onboarded info account, so
+        // wait for the onboard to confirm before building it
+        let response = if deployment_info.is_some() {
+            commit.maybe_commit_confirmed(tx, &client).await?
+        } else {
+            commit.maybe_commit(tx, &client).await?
+        };
+        let mut json = response.to_json();
+        if let Some(location) = location {
+            json["location"] = serde_json::to_value(location)?;
+        }
+        print_json(&json)?;
+        // Data-only onboarding does not carry deployment info, so set it with a
+        // follow-up update once the onboard has been confirmed.
+        if deployment_info.is_some() && commit.commit {
+            let update = HotspotInfoUpdate::for_subdao(subdao).set_deployment_info(deployment_info);
+            let (tx, _) =
+                hotspot::direct_update(&client, &gateway, update, &keypair, transaction_opts)
+                    .await?;
+            print_json(&commit.maybe_commit(tx, &client).await?.to_json())?;
+        }
+        Ok(())
     } else {
         Ok(())
     }
@@ -145,8 +177,8 @@ impl IotCmd {
         let txn = BlockchainTxnAddGatewayV1::from_envelope(&self.txn)?;
         let update = HotspotInfoUpdate::for_subdao(SubDao::Iot)
             .set_gain(self.gain)
-            .set_elevation(self.elevation)
-            .set_geo(self.lat, self.lon)?;
+            .set_elevation(self.elevation);
+        let update = set_update_location(update, self.lat, self.lon, self.hex)?;
         perform_add(
             SubDao::Iot,
             txn,
@@ -207,6 +239,9 @@ impl MobileToken {
 ///
 /// Issues the mobile Hotspot NFT and onboards it given the created data-only transaction.
 /// Location details are optional
+///
+/// WiFi deployment details are optional. When given they are set in a
+/// separate update transaction after the onboard is committed.
 #[derive(Debug, Clone, clap::Args)]
 struct MobileOnboard {
     /// Latitude of Hotspot location to assert.
@@ -222,6 +257,17 @@ struct MobileOnboard {
     /// example: "--lon=-xx.xxxxxxx".
     #[arg(long)]
     lon: Option<f64>,
+
+    /// H3 cell index (hex) of the Hotspot location to assert.
+    ///
+    /// An alternative to latitude and longitude. The cell must be at
+    /// resolution 12.
+    #[arg(long, conflicts_with_all = ["lat", "lon"])]
+    hex: Option<h3o::CellIndex>,
+
+    #[command(flatten)]
+    deployment: WifiDeploymentArgs,
+
     /// Base64 encoded add Hotspot token.
     ///
     /// The token is generated by the 'token' command
@@ -242,7 +288,9 @@ struct MobileOnboard {
 impl MobileOnboard {
     pub async fn run(&self, opts: Opts) -> Result {
         let txn = BlockchainTxnAddGatewayV1::from_envelope(&self.token)?;
-        let update = HotspotInfoUpdate::for_subdao(SubDao::Mobile).set_geo(self.lat, self.lon)?;
+        let update = HotspotInfoUpdate::for_subdao(SubDao::Mobile)
+            .set_deployment_info(self.deployment.deployment_info()?);
+        let update = set_update_location(update, self.lat, self.lon, self.hex)?;
         perform_add(
             SubDao::Mobile,
             txn,
@@ -255,6 +303,53 @@ impl MobileOnboard {
     }
 }
 
+/// WiFi deployment details for a mobile Hotspot
+#[derive(Debug, Clone, clap::Args)]
+pub struct WifiDeploymentArgs {
+    /// The antenna identifier of the WiFi Hotspot
+    #[arg(long)]
+    antenna: Option<u32>,
+
+    /// The height of the WiFi Hotspot above ground level in whole meters
+    #[arg(long = "deployment-elevation")]
+    elevation: Option<i32>,
+
+    /// The azimuth of the WiFi Hotspot antenna in degrees from north
+    #[arg(long)]
+    azimuth: Option<f64>,
+
+    /// The mechanical down tilt of the WiFi Hotspot antenna in degrees
+    #[arg(long)]
+    mechanical_down_tilt: Option<f64>,
+
+    /// The electrical down tilt of the WiFi Hotspot antenna in degrees
+    #[arg(long)]
+    electrical_down_tilt: Option<f64>,
+}
+
+impl WifiDeploymentArgs {
+    /// Returns the WiFi deployment info if any of the deployment details were
+    /// given. Unspecified details default to zero.
+    pub fn deployment_info(&self) -> Result<Option<MobileDeploymentInfo>> {
+        if self.antenna.is_none()
+            && self.elevation.is_none()
+            && self.azimuth.is_none()
+            && self.mechanical_down_tilt.is_none()
+            && self.electrical_down_tilt.is_none()
+        {
+            return Ok(None);
+        }
+        let info = MobileDeploymentInfo::wifi(
+            self.antenna.unwrap_or_default(),
+            self.elevation.unwrap_or_default(),
+            self.azimuth.unwrap_or_default(),
+            self.mechanical_down_tilt.unwrap_or_default(),
+            self.electrical_down_tilt.unwrap_or_default(),
+        )?;
+        Ok(Some(info))
+    }
+}
+
 /// Fetches or creates the cert for a mobile only data hotspot
 ///
 ///
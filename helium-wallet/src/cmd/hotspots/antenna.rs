@@ -0,0 +1,59 @@
+use crate::cmd::*;
+use helium_lib::{
+    dao::SubDao,
+    hotspot::{self, HotspotInfoUpdate},
+};
+
+#[derive(Clone, Debug, clap::Args)]
+/// Update only the antenna gain and elevation of an IoT Hotspot
+///
+/// The asserted location is left as is, which makes the update fee free. The
+/// update is signed by the owner directly, without going through the full
+/// assert flow of an onboarding server.
+pub struct Cmd {
+    /// Key of the Hotspot to update
+    gateway: helium_crypto::PublicKey,
+
+    /// The antenna gain in dBi, with one digit of accuracy.
+    ///
+    /// Note that the gain is truncated to the nearest 0.1 dBi.
+    #[arg(long, required_unless_present = "elevation")]
+    gain: Option<f64>,
+
+    /// The elevation in meters above ground level.
+    ///
+    /// For negative values use '=', for example: "--elevation=-xx".
+    #[arg(long)]
+    elevation: Option<i32>,
+
+    /// Commit the update.
+    #[command(flatten)]
+    commit: CommitOpts,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let password = get_wallet_password(false)?;
+        let keypair = opts.load_keypair(password.as_bytes())?;
+        let client = opts.client()?;
+
+        let update = HotspotInfoUpdate::for_subdao(SubDao::Iot)
+            .set_gain(self.gain)
+            .set_elevation(self.elevation);
+        // Also checks that the Hotspot is onboarded to IoT
+        let fee = hotspot::info::update_fee(&client, &self.gateway, &update).await?;
+        if fee.amount > 0 {
+            bail!(
+                "update would be charged a location fee of {} DC",
+                fee.amount
+            );
+        }
+        let transaction_opts = self.commit.transaction_opts(&client)?;
+        let (tx, _) =
+            hotspot::direct_update(&client, &self.gateway, update, &keypair, &transaction_opts)
+                .await?;
+        let mut json = self.commit.maybe_commit(tx, &client).await?.to_json();
+        json["fee"] = serde_json::to_value(fee)?;
+        print_json(&json)
+    }
+}
@@ -22,7 +22,7 @@ impl Cmd {
             &client,
             &self.address,
             &keypair,
-            &self.commit.transaction_opts(&client),
+            &self.commit.transaction_opts(&client)?,
         )
         .await?;
 
@@ -0,0 +1,161 @@
+use crate::cmd::*;
+use futures::TryStreamExt;
+use helium_lib::{
+    dao::SubDao,
+    entity_key::KeySerialization,
+    hotspot::{self, Hotspot},
+    keypair::Pubkey,
+    reward::{self, ClaimableToken},
+    token::TokenAmount,
+};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum ExportFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Clone, Debug, clap::Args)]
+/// Export all Hotspots owned by the active or a given wallet to a file
+///
+/// Each Hotspot is written with its name, ecc key, asset id, the subdaos it is
+/// onboarded to, its asserted location, gain and elevation, and its pending
+/// IOT and MOBILE rewards. Rewards are written as exact decimal amounts in
+/// whole tokens.
+pub struct Cmd {
+    /// The alternate wallet to export Hotspots for
+    wallet: Option<Pubkey>,
+    /// File to write the export to
+    #[arg(short, long)]
+    output: PathBuf,
+    /// Format of the export.
+    ///
+    /// Defaults to json for files with a .json extension and csv otherwise.
+    #[arg(long, value_enum)]
+    format: Option<ExportFormat>,
+}
+
+#[derive(Debug, Serialize)]
+struct FleetEntry {
+    name: String,
+    key: String,
+    asset: String,
+    subdaos: String,
+    location: Option<String>,
+    gain: Option<Decimal>,
+    elevation: Option<i32>,
+    pending_iot: Decimal,
+    pending_mobile: Decimal,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let owner = if let Some(wallet) = self.wallet {
+            wallet
+        } else {
+            opts.load_wallet()?.public_key
+        };
+        let format = self.format.unwrap_or_else(|| {
+            match self.output.extension().and_then(|ext| ext.to_str()) {
+                Some("json") => ExportFormat::Json,
+                _ => ExportFormat::Csv,
+            }
+        });
+        let client = opts.client()?;
+        let progress = Progress::new("hotspots", None);
+        let hotspots: Vec<Hotspot> = hotspot::for_owner_stream(&client, &owner, None)
+            .inspect_ok(|_| progress.success())
+            .try_collect()
+            .await?;
+        progress.finish();
+        let hotspots = hotspot::with_info(&client, &SubDao::all(), hotspots).await?;
+
+        let entity_key_strings: Vec<String> = hotspots
+            .iter()
+            .map(|hotspot| hotspot.key.to_string())
+            .collect();
+        let pending_iot =
+            pending_rewards(&client, ClaimableToken::Iot, &entity_key_strings).await?;
+        let pending_mobile =
+            pending_rewards(&client, ClaimableToken::Mobile, &entity_key_strings).await?;
+        let pending = |rewards: &HashMap<String, TokenAmount>, key: &str| {
+            rewards
+                .get(key)
+                .map(|amount| {
+                    Decimal::from_i128_with_scale(
+                        amount.amount.into(),
+                        amount.token.decimals().into(),
+                    )
+                })
+                .unwrap_or_default()
+        };
+
+        let entries: Vec<FleetEntry> = hotspots
+            .into_iter()
+            .zip(entity_key_strings)
+            .map(|(hotspot, key)| {
+                let info = hotspot.info.unwrap_or_default();
+                let mut subdaos: Vec<SubDao> = info.keys().copied().collect();
+                subdaos.sort_by_key(|subdao| subdao.to_string());
+                // Prefer the IOT details where a Hotspot is onboarded to both
+                let primary = subdaos.first().and_then(|subdao| info.get(subdao));
+                FleetEntry {
+                    name: hotspot.name,
+                    asset: hotspot.asset.to_string(),
+                    subdaos: subdaos
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(";"),
+                    location: primary
+                        .and_then(|info| info.location().as_ref())
+                        .map(|location| location.location.to_string()),
+                    gain: primary.and_then(|info| *info.gain()),
+                    elevation: primary.and_then(|info| *info.elevation()),
+                    pending_iot: pending(&pending_iot, &key),
+                    pending_mobile: pending(&pending_mobile, &key),
+                    key,
+                }
+            })
+            .collect();
+
+        match format {
+            ExportFormat::Json => {
+                let file = fs::File::create(&self.output)?;
+                serde_json::to_writer_pretty(file, &entries)?;
+            }
+            ExportFormat::Csv => {
+                let mut writer = csv::Writer::from_path(&self.output)?;
+                for entry in &entries {
+                    writer.serialize(entry)?;
+                }
+                writer.flush()?;
+            }
+        }
+
+        print_json(&json!({
+            "address": owner.to_string(),
+            "hotspots": entries.len(),
+            "output": self.output,
+        }))
+    }
+}
+
+async fn pending_rewards(
+    client: &client::Client,
+    token: ClaimableToken,
+    entity_key_strings: &[String],
+) -> Result<HashMap<String, TokenAmount>> {
+    if entity_key_strings.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let pending = reward::pending(client, token, entity_key_strings, KeySerialization::B58)
+        .await?
+        .into_iter()
+        .map(|(key, reward)| (key, reward.reward))
+        .collect();
+    Ok(pending)
+}
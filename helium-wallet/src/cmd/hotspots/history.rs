@@ -0,0 +1,66 @@
+use crate::cmd::*;
+use helium_lib::hotspot::{self, history::HotspotEventKind};
+
+#[derive(Clone, Debug, clap::Args)]
+/// Get the ownership, onboarding and assert history of a Hotspot
+///
+/// Events are listed oldest first with the block, time and signature of the
+/// transaction they were part of.
+///
+/// NOTE: Hotspots that were onboarded before the Solana transition will
+/// not include events from before the transition.
+pub struct Cmd {
+    /// The Hotspot to fetch the history for
+    address: helium_crypto::PublicKey,
+    /// Output format
+    #[arg(long, value_enum, default_value_t)]
+    format: OutputFormat,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let client = opts.client()?;
+        let events = hotspot::history::history(&client, &self.address).await?;
+        match self.format {
+            OutputFormat::Json => print_json(&json!({
+                "address": self.address.to_string(),
+                "history": events,
+            })),
+            OutputFormat::Table | OutputFormat::Csv => {
+                let mut table = prettytable::Table::new();
+                table.set_format(*prettytable::format::consts::FORMAT_CLEAN);
+                table.set_titles(prettytable::row![
+                    "Block",
+                    "Time",
+                    "Event",
+                    "Subdao",
+                    "Owner",
+                    "Signature"
+                ]);
+                for event in &events {
+                    let (kind, subdao, owner) = match &event.kind {
+                        HotspotEventKind::Issued => ("issued", None, None),
+                        HotspotEventKind::Transferred { owner } => {
+                            ("transferred", None, Some(owner))
+                        }
+                        HotspotEventKind::Onboarded { sub_dao, .. } => {
+                            ("onboarded", Some(sub_dao), None)
+                        }
+                        HotspotEventKind::Asserted { sub_dao, .. } => {
+                            ("asserted", Some(sub_dao), None)
+                        }
+                    };
+                    table.add_row(prettytable::row![
+                        event.block,
+                        event.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                        kind,
+                        subdao.map(ToString::to_string).unwrap_or_default(),
+                        owner.map(ToString::to_string).unwrap_or_default(),
+                        event.signature
+                    ]);
+                }
+                print_rows(&table, self.format)
+            }
+        }
+    }
+}
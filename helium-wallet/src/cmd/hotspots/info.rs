@@ -1,16 +1,42 @@
 use crate::cmd::*;
-use helium_lib::{dao::SubDao, hotspot};
+use helium_lib::{
+    client::{ONBOARDING_URL_DEVNET, ONBOARDING_URL_MAINNET},
+    hotspot,
+};
 
 #[derive(Clone, Debug, clap::Args)]
 /// Get details for a given Hotspot
+///
+/// This merges the Hotspot asset and its key to asset account, the IoT and
+/// Mobile info and status for the Hotspot, and the maker that onboarded it.
 pub struct Cmd {
     address: helium_crypto::PublicKey,
+
+    /// The onboarding server to use to look up the maker of the Hotspot.
+    ///
+    /// Shortcuts like "m" or "d" select the default onboarding server for that
+    /// network. Defaults to the onboarding server for the network of the API
+    /// URL.
+    #[arg(long)]
+    onboarding: Option<String>,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t)]
+    format: OutputFormat,
 }
 
 impl Cmd {
     pub async fn run(&self, opts: Opts) -> Result {
         let client = opts.client()?;
-        let hotspot = hotspot::get_with_info(&client, &SubDao::all(), &self.address).await?;
-        print_json(&hotspot)
+        let server = match self.onboarding.as_deref() {
+            Some("m" | "mainnet-beta") => ONBOARDING_URL_MAINNET,
+            Some("d" | "devnet") => ONBOARDING_URL_DEVNET,
+            Some(url) => url,
+            None if client::is_devnet(&opts.url) => ONBOARDING_URL_DEVNET,
+            None => ONBOARDING_URL_MAINNET,
+        }
+        .to_string();
+        let details = hotspot::details(&client, Some(server), &self.address).await?;
+        print_output(&details, self.format)
     }
 }
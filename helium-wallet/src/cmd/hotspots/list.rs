@@ -1,11 +1,42 @@
 use crate::cmd::*;
-use helium_lib::{hotspot, keypair::Pubkey};
+use futures::{future, TryStreamExt};
+use helium_lib::{
+    client::DasSortBy,
+    dao::SubDao,
+    hotspot::{self, Hotspot},
+    keypair::Pubkey,
+};
 
 #[derive(Clone, Debug, clap::Args)]
 /// Get the list of Hotspots for the active or a given wallet
+///
+/// Hotspots are fetched page by page so owners with large fleets can be listed
+/// in full.
 pub struct Cmd {
     /// The alternate wallet to get the list of Hotspots for
     wallet: Option<Pubkey>,
+    /// Only list Hotspots onboarded to the given subdao
+    #[arg(long, value_enum)]
+    subdao: Option<SubDao>,
+    /// Only list Hotspots whose name contains the given text (case-insensitive)
+    #[arg(long)]
+    search: Option<String>,
+    /// Order to list Hotspots in
+    #[arg(long, value_enum)]
+    sort: Option<SortOrder>,
+    /// Output format
+    #[arg(long, value_enum, default_value_t)]
+    format: OutputFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum SortOrder {
+    /// Sort by Hotspot name
+    Name,
+    /// Sort by asserted location, Hotspots without a location are listed last
+    Location,
+    /// Sort by the time the Hotspot was added
+    Added,
 }
 
 impl Cmd {
@@ -17,11 +48,74 @@ impl Cmd {
             wallet.public_key
         };
         let client = opts.client()?;
-        let hotspots = hotspot::for_owner(&client, &owner).await?;
-        let json = json!( {
-            "address": owner.to_string(),
-            "hotspots": hotspots,
-        });
-        print_json(&json)
+        let sort_by = (self.sort == Some(SortOrder::Added)).then(DasSortBy::created);
+        let search = self.search.as_deref().map(normalize_name);
+        let hotspots: Vec<Hotspot> = hotspot::for_owner_stream(&client, &owner, sort_by)
+            .try_filter(|hotspot| {
+                let matches = match &search {
+                    Some(search) => normalize_name(&hotspot.name).contains(search),
+                    None => true,
+                };
+                future::ready(matches)
+            })
+            .try_collect()
+            .await?;
+
+        let subdaos = match (self.subdao, self.sort) {
+            (Some(subdao), _) => vec![subdao],
+            (None, Some(SortOrder::Location)) => SubDao::all().to_vec(),
+            _ => vec![],
+        };
+        let mut hotspots = if subdaos.is_empty() {
+            hotspots
+        } else {
+            hotspot::with_info(&client, &subdaos, hotspots).await?
+        };
+        if let Some(subdao) = self.subdao {
+            hotspots.retain(|hotspot| {
+                hotspot
+                    .info
+                    .as_ref()
+                    .is_some_and(|info| info.contains_key(&subdao))
+            });
+        }
+        match self.sort {
+            Some(SortOrder::Name) => hotspots.sort_by(|a, b| a.name.cmp(&b.name)),
+            Some(SortOrder::Location) => hotspots.sort_by_key(|hotspot| {
+                let location = hotspot.info.as_ref().and_then(|info| {
+                    subdaos
+                        .iter()
+                        .find_map(|subdao| info.get(subdao).and_then(|info| info.location_u64()))
+                });
+                (location.is_none(), location)
+            }),
+            // Already sorted by DAS, or unsorted
+            Some(SortOrder::Added) | None => (),
+        }
+
+        match self.format {
+            OutputFormat::Json => print_json(&json!( {
+                "address": owner.to_string(),
+                "hotspots": hotspots,
+            })),
+            OutputFormat::Table | OutputFormat::Csv => {
+                let mut table = prettytable::Table::new();
+                table.set_format(*prettytable::format::consts::FORMAT_CLEAN);
+                table.set_titles(prettytable::row!["Hotspot", "Name", "Asset", "Owner"]);
+                for hotspot in &hotspots {
+                    table.add_row(prettytable::row![
+                        hotspot.key,
+                        hotspot.name,
+                        hotspot.asset,
+                        hotspot.owner
+                    ]);
+                }
+                print_rows(&table, self.format)
+            }
+        }
     }
 }
+
+fn normalize_name(name: &str) -> String {
+    name.to_lowercase().replace('-', " ")
+}
@@ -1,7 +1,11 @@
 use crate::cmd::*;
+use helium_lib::{h3o, hotspot::HotspotInfoUpdate};
 
 mod add;
+mod antenna;
 mod burn;
+mod export;
+mod history;
 mod info;
 mod list;
 mod rewards;
@@ -24,7 +28,9 @@ impl Cmd {
 #[derive(Debug, Clone, clap::Subcommand)]
 /// Commands on Hotspots
 pub enum HotspotCommand {
+    #[command(visible_alias = "assert")]
     Update(update::Cmd),
+    Antenna(antenna::Cmd),
     Add(Box<add::Cmd>),
     List(list::Cmd),
     Info(info::Cmd),
@@ -32,12 +38,15 @@ pub enum HotspotCommand {
     Rewards(rewards::Cmd),
     Transfer(transfer::Cmd),
     Burn(burn::Cmd),
+    Export(export::Cmd),
+    History(history::Cmd),
 }
 
 impl HotspotCommand {
     pub async fn run(&self, opts: Opts) -> Result {
         match self {
             Self::Update(cmd) => cmd.run(opts).await,
+            Self::Antenna(cmd) => cmd.run(opts).await,
             Self::Add(cmd) => cmd.run(opts).await,
             Self::List(cmd) => cmd.run(opts).await,
             Self::Info(cmd) => cmd.run(opts).await,
@@ -45,6 +54,23 @@ impl HotspotCommand {
             Self::Rewards(cmd) => cmd.run(opts).await,
             Self::Transfer(cmd) => cmd.run(opts).await,
             Self::Burn(cmd) => cmd.run(opts).await,
+            Self::Export(cmd) => cmd.run(opts).await,
+            Self::History(cmd) => cmd.run(opts).await,
         }
     }
 }
+
+/// Set the location of the given update from either a latitude/longitude pair
+/// or an H3 cell index.
+fn set_update_location(
+    update: HotspotInfoUpdate,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    hex: Option<h3o::CellIndex>,
+) -> Result<HotspotInfoUpdate> {
+    let update = match hex {
+        Some(hex) => update.set_cell(Some(hex))?,
+        None => update.set_geo(lat, lon)?,
+    };
+    Ok(update)
+}
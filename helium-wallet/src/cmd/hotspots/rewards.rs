@@ -4,8 +4,9 @@ use helium_lib::{
     entity_key::{EncodedEntityKey, KeySerialization},
     hotspot,
     keypair::Pubkey,
-    reward,
+    reward::{self, OracleReward},
 };
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, clap::Args)]
 pub struct Cmd {
@@ -65,6 +66,9 @@ pub struct PendingCmd {
     /// Wallet to look up hotspots for
     #[arg(long)]
     owner: Option<Pubkey>,
+    /// Output format
+    #[arg(long, value_enum, default_value_t)]
+    format: OutputFormat,
 }
 
 impl PendingCmd {
@@ -86,7 +90,12 @@ impl PendingCmd {
         )
         .await?;
 
-        print_json(&pending)
+        match self.format {
+            OutputFormat::Json => print_json(&pending),
+            OutputFormat::Table | OutputFormat::Csv => {
+                print_rows(&rewards_table(pending.iter(), self.format), self.format)
+            }
+        }
     }
 }
 
@@ -102,6 +111,9 @@ pub struct LifetimeCmd {
     /// Wallet to look up hotspots for
     #[arg(long)]
     owner: Option<Pubkey>,
+    /// Output format
+    #[arg(long, value_enum, default_value_t)]
+    format: OutputFormat,
 }
 
 impl LifetimeCmd {
@@ -117,7 +129,18 @@ impl LifetimeCmd {
         let entity_key_strings = hotspots_to_entity_key_strings(&hotspots);
         let rewards = reward::lifetime(&client, self.token, &entity_key_strings).await?;
 
-        print_json(&rewards)
+        match self.format {
+            OutputFormat::Json => print_json(&rewards),
+            OutputFormat::Table | OutputFormat::Csv => print_rows(
+                &rewards_table(
+                    rewards
+                        .iter()
+                        .flat_map(|(key, rewards)| rewards.iter().map(move |reward| (key, reward))),
+                    self.format,
+                ),
+                self.format,
+            ),
+        }
     }
 }
 
@@ -162,3 +185,26 @@ fn hotspots_to_entity_key_strings(public_keys: &[helium_crypto::PublicKey]) -> V
         .map(|key| key.to_string())
         .collect::<Vec<String>>()
 }
+
+/// Tabulate oracle rewards by Hotspot, one row per Hotspot and oracle
+fn rewards_table<'a>(
+    rewards: impl Iterator<Item = (&'a String, &'a OracleReward)>,
+    format: OutputFormat,
+) -> prettytable::Table {
+    let mut sorted: BTreeMap<(&String, u16), &OracleReward> = BTreeMap::new();
+    for (key, reward) in rewards {
+        sorted.insert((key, reward.index), reward);
+    }
+    let mut table = prettytable::Table::new();
+    table.set_format(*prettytable::format::consts::FORMAT_CLEAN);
+    table.set_titles(prettytable::row!["Hotspot", "Oracle", "Token", "Amount"]);
+    for ((key, _), reward) in sorted {
+        table.add_row(prettytable::row![
+            key,
+            reward.oracle.key,
+            reward.reward.token,
+            amount_cell(&reward.reward, format)
+        ]);
+    }
+    table
+}
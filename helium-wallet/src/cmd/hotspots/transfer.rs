@@ -24,7 +24,7 @@ impl Cmd {
             bail!("recipient already owner of hotspot");
         }
         let client = opts.client()?;
-        let transaction_opts = self.commit.transaction_opts(&client);
+        let transaction_opts = self.commit.transaction_opts(&client)?;
         let (tx, _) = hotspot::transfer(
             &client,
             &self.address,
@@ -1,8 +1,11 @@
+use super::{add::WifiDeploymentArgs, set_update_location};
 use crate::cmd::*;
 use helium_lib::{
     client::{ONBOARDING_URL_DEVNET, ONBOARDING_URL_MAINNET},
     dao::SubDao,
-    hotspot::{self, HotspotInfoUpdate},
+    geocode::{self, Geocoder},
+    h3o,
+    hotspot::{self, HotspotInfoUpdate, HotspotLocation},
 };
 
 #[derive(Debug, Clone, clap::Args)]
@@ -11,6 +14,14 @@ use helium_lib::{
 /// The original transaction is
 /// created by the Hotspot miner and supplied here for owner signing. Use an
 /// onboarding key to get the transaction signed by the DeWi staking server.
+///
+/// Mobile Hotspots can additionally assert WiFi deployment details. The Data
+/// Credit fee for the assertion is included in the output, so a run without
+/// --commit shows the fee before it is charged.
+///
+/// Updates that only change the gain or elevation, and leave the location as
+/// asserted, are fee free. See `hotspots antenna` for such updates. A warning
+/// is shown before committing when a location re-assert fee applies.
 pub struct Cmd {
     /// The subdao to assert the Hotspot on
     subdao: SubDao,
@@ -32,6 +43,29 @@ pub struct Cmd {
     #[arg(long)]
     lon: Option<f64>,
 
+    /// Street address of the Hotspot location to assert.
+    ///
+    /// The address is resolved to coordinates using the geocoder. The resolved
+    /// address, coordinates and H3 hex are shown before the assert is built,
+    /// and have to be confirmed before committing unless --yes is given.
+    #[arg(long, conflicts_with_all = ["lat", "lon"])]
+    address: Option<String>,
+
+    /// Assert a geocoded location without asking for confirmation
+    #[arg(long, requires = "address")]
+    yes: bool,
+
+    /// H3 cell index (hex) of the Hotspot location to assert.
+    ///
+    /// An alternative to latitude and longitude. The cell must be at
+    /// resolution 12.
+    #[arg(long, conflicts_with_all = ["lat", "lon", "address"])]
+    hex: Option<h3o::CellIndex>,
+
+    /// Url of the Nominatim server used to resolve a street address.
+    #[arg(long, default_value = geocode::NOMINATIM_URL)]
+    geocoder: String,
+
     /// The antenna gain for the asserted Hotspot in dBi, with one digit of
     /// accuracy.
     ///
@@ -47,6 +81,13 @@ pub struct Cmd {
     #[arg(long)]
     elevation: Option<i32>,
 
+    #[command(flatten)]
+    deployment: WifiDeploymentArgs,
+
+    /// Refuse the update if it would be charged a location re-assert fee.
+    #[arg(long)]
+    fee_free: bool,
+
     /// The onboarding server to use for asserting the hotspot.
     ///
     /// If the API URL is specified with a shortcut like "m" or "d", the
@@ -73,13 +114,53 @@ impl Cmd {
             .to_string()
         });
 
+        let deployment_info = self.deployment.deployment_info()?;
+        if deployment_info.is_some() && self.subdao != SubDao::Mobile {
+            bail!("deployment details are only supported for mobile hotspots");
+        }
+        let geocoded = match &self.address {
+            Some(address) => Some(
+                geocode::Nominatim::new(&self.geocoder)
+                    .geocode(address)
+                    .await?
+                    .ok_or_else(|| anyhow!("address not found: {address}"))?,
+            ),
+            None => None,
+        };
+        let (lat, lon) = match &geocoded {
+            Some(geocoded) => (Some(geocoded.lat), Some(geocoded.lon)),
+            None => (self.lat, self.lon),
+        };
         let update = HotspotInfoUpdate::for_subdao(self.subdao)
             .set_gain(self.gain)
             .set_elevation(self.elevation)
-            .set_geo(self.lat, self.lon)?;
+            .set_deployment_info(deployment_info);
+        let update = set_update_location(update, lat, lon, self.hex)?;
+        if let (Some(geocoded), Some(location)) = (&geocoded, update.location()) {
+            if !confirm_location(geocoded, location, self.commit.commit && !self.yes)? {
+                bail!("assert cancelled");
+            }
+        }
+        let resolved = geocoded.map(|geocoded| {
+            json!({
+                "address": geocoded.address,
+                "lat": geocoded.lat,
+                "lon": geocoded.lon,
+            })
+        });
 
         let client = opts.client()?;
-        let transaction_opts = self.commit.transaction_opts(&client);
+        let fee = hotspot::info::update_fee(&client, &self.gateway, &update).await?;
+        let warning = (fee.amount > 0)
+            .then(|| format!("location re-assert fee of {} DC applies", fee.amount));
+        if let Some(warning) = &warning {
+            if self.fee_free {
+                bail!("{warning}, refusing update");
+            }
+            eprintln!("Warning: {warning}");
+        }
+        let location = update.location().clone();
+        let transaction_opts = self.commit.transaction_opts(&client)?;
         let tx = hotspot::update(
             &client,
             server,
@@ -90,6 +171,36 @@ impl Cmd {
         )
         .await?;
 
-        print_json(&self.commit.maybe_commit(tx, &client).await.to_json())
+        let mut json = self.commit.maybe_commit(tx, &client).await.to_json();
+        json["fee"] = serde_json::to_value(fee)?;
+        if let Some(warning) = warning {
+            json["warning"] = warning.into();
+        }
+        if let Some(location) = location {
+            json["location"] = serde_json::to_value(location)?;
+        }
+        if let Some(resolved) = resolved {
+            json["geocoded"] = resolved;
+        }
+        print_json(&json)
+    }
+}
+
+/// Show a geocoded location so a wrong match can be caught, asking whether to
+/// go ahead if requested
+fn confirm_location(
+    geocoded: &geocode::GeocodedAddress,
+    location: &HotspotLocation,
+    ask: bool,
+) -> Result<bool> {
+    eprintln!("Address:  {}", geocoded.address);
+    eprintln!("Lat/lon:  {}, {}", geocoded.lat, geocoded.lon);
+    eprintln!("H3 hex:   {}", location.location);
+    if !ask {
+        return Ok(true);
     }
+    Ok(dialoguer::Confirm::new()
+        .with_prompt("Assert this location?")
+        .default(false)
+        .interact()?)
 }
@@ -1,5 +1,5 @@
 use crate::{
-    cmd::{print_json, Opts},
+    cmd::{print_json, print_output, Opts, OutputFormat},
     result::{Error, Result},
     wallet::Wallet,
 };
@@ -12,6 +12,9 @@ pub struct Cmd {
     /// Display QR code for a given single wallet.
     #[arg(long)]
     qr: bool,
+    /// Output format
+    #[arg(long, value_enum, default_value_t)]
+    format: OutputFormat,
 }
 
 impl Cmd {
@@ -20,21 +23,24 @@ impl Cmd {
         if self.qr {
             print_qr(wallet.public_key.to_string()).map_err(Error::from)
         } else {
-            print_wallet(&wallet)
+            print_output(&wallet_json(&wallet)?, self.format)
         }
     }
 }
 
 pub(crate) fn print_wallet(wallet: &Wallet) -> Result {
+    print_json(&wallet_json(wallet)?)
+}
+
+fn wallet_json(wallet: &Wallet) -> Result<serde_json::Value> {
     let helium_address = wallet.helium_address()?;
     let address = wallet.address()?;
-    let json = json!({
+    Ok(json!({
         "sharded": wallet.is_sharded(),
         "pwhash": wallet.pwhash().to_string(),
         "address": {
             "solana": address,
             "helium": helium_address,
         },
-    });
-    print_json(&json)
+    }))
 }
@@ -0,0 +1,29 @@
+use crate::cmd::*;
+
+pub mod onboard;
+
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    #[command(subcommand)]
+    cmd: MakerCommand,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        self.cmd.run(opts).await
+    }
+}
+
+/// Commands for Hotspot makers
+#[derive(Debug, clap::Subcommand)]
+pub enum MakerCommand {
+    Onboard(onboard::Cmd),
+}
+
+impl MakerCommand {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Self::Onboard(cmd) => cmd.run(opts).await,
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use super::OnboardingOpts;
+use crate::cmd::*;
+use helium_lib::onboarding;
+
+#[derive(Debug, clap::Args)]
+/// Register a Hotspot key with the onboarding server
+///
+/// Registered Hotspots can be issued and onboarded with fees paid by the
+/// maker. Requires the maker's API key.
+pub struct Cmd {
+    /// The onboarding key of the Hotspot
+    onboarding_key: helium_crypto::PublicKey,
+    /// MAC address of the Hotspot's wireless interface
+    #[arg(long)]
+    mac_wlan0: Option<String>,
+    /// MAC address of the Hotspot's ethernet interface
+    #[arg(long)]
+    mac_eth0: Option<String>,
+    /// Serial number of the Hotspot's Raspberry Pi
+    #[arg(long)]
+    rpi_serial: Option<String>,
+    /// Helium serial number of the Hotspot
+    #[arg(long)]
+    helium_serial: Option<String>,
+    /// Manufacturing batch of the Hotspot
+    #[arg(long)]
+    batch: Option<String>,
+    #[command(flatten)]
+    onboarding: OnboardingOpts,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let client = self.onboarding.client(&opts);
+        let hotspot = client
+            .create_hotspot(&onboarding::CreateHotspot {
+                onboarding_key: self.onboarding_key.clone(),
+                mac_wlan0: self.mac_wlan0.clone(),
+                mac_eth0: self.mac_eth0.clone(),
+                rpi_serial: self.rpi_serial.clone(),
+                helium_serial: self.helium_serial.clone(),
+                batch: self.batch.clone(),
+            })
+            .await?;
+        print_json(&hotspot)
+    }
+}
@@ -0,0 +1,81 @@
+use crate::cmd::*;
+use helium_lib::{
+    client::{ONBOARDING_URL_DEVNET, ONBOARDING_URL_MAINNET},
+    onboarding,
+};
+
+pub mod create;
+pub mod quote;
+pub mod status;
+
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    #[command(subcommand)]
+    cmd: OnboardCommand,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        self.cmd.run(opts).await
+    }
+}
+
+/// Register and onboard Hotspots through the maker onboarding server
+#[derive(Debug, clap::Subcommand)]
+pub enum OnboardCommand {
+    Create(create::Cmd),
+    Quote(quote::Cmd),
+    Status(status::Cmd),
+}
+
+impl OnboardCommand {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Self::Create(cmd) => cmd.run(opts).await,
+            Self::Quote(cmd) => cmd.run(opts).await,
+            Self::Status(cmd) => cmd.run(opts).await,
+        }
+    }
+}
+
+/// Options for talking to the onboarding server
+#[derive(Debug, Clone, clap::Args)]
+pub struct OnboardingOpts {
+    /// The onboarding server to use.
+    ///
+    /// Shortcuts like "m" or "d" select the default onboarding server for that
+    /// network. Defaults to the onboarding server for the network of the API
+    /// URL.
+    #[arg(long)]
+    onboarding: Option<String>,
+
+    /// Maker API key for the onboarding server. Defaults to the
+    /// HELIUM_MAKER_API_KEY environment variable
+    #[arg(long)]
+    api_key: Option<String>,
+
+    /// Number of times to retry failed onboarding server requests
+    #[arg(long, default_value_t = onboarding::DEFAULT_RETRIES)]
+    retries: u32,
+}
+
+impl OnboardingOpts {
+    pub fn client(&self, opts: &Opts) -> onboarding::Client {
+        let server = match self.onboarding.as_deref() {
+            Some("m" | "mainnet-beta") => ONBOARDING_URL_MAINNET,
+            Some("d" | "devnet") => ONBOARDING_URL_DEVNET,
+            Some(url) => url,
+            None if client::is_devnet(&opts.url) => ONBOARDING_URL_DEVNET,
+            None => ONBOARDING_URL_MAINNET,
+        };
+        let client = onboarding::Client::new(server).with_retries(self.retries);
+        match self
+            .api_key
+            .clone()
+            .or_else(|| std::env::var("HELIUM_MAKER_API_KEY").ok())
+        {
+            Some(api_key) => client.with_api_key(&api_key),
+            None => client,
+        }
+    }
+}
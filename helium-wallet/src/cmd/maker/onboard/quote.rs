@@ -0,0 +1,45 @@
+use super::OnboardingOpts;
+use crate::cmd::*;
+use helium_lib::{
+    dao::SubDao,
+    hotspot::{HotspotMode, MobileDeviceType},
+    onboarding,
+};
+
+#[derive(Debug, clap::Args)]
+/// Quote the maker paid onboarding of a registered Hotspot
+///
+/// Reports the onboarding and location fees for the subdao and whether the
+/// maker's Data Credit balance covers them.
+pub struct Cmd {
+    /// The key of the Hotspot to quote onboarding for
+    address: helium_crypto::PublicKey,
+    /// Subdao to onboard to
+    #[arg(long, value_enum)]
+    subdao: SubDao,
+    /// Mode of the Hotspot
+    #[arg(long, value_enum, default_value_t = HotspotMode::Full)]
+    mode: HotspotMode,
+    /// Device type of a Mobile Hotspot
+    #[arg(long, value_enum, default_value_t)]
+    device_type: MobileDeviceType,
+    #[command(flatten)]
+    onboarding: OnboardingOpts,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let client = opts.client()?;
+        let onboarding_client = self.onboarding.client(&opts);
+        let quote = onboarding::quote(
+            &client,
+            &onboarding_client,
+            &self.address,
+            self.subdao,
+            self.mode,
+            self.device_type,
+        )
+        .await?;
+        print_json(&quote)
+    }
+}
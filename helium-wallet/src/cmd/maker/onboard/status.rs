@@ -0,0 +1,32 @@
+use super::OnboardingOpts;
+use crate::cmd::*;
+use helium_lib::onboarding;
+
+#[derive(Debug, clap::Args)]
+/// Get the onboarding status of Hotspot keys
+///
+/// Reports whether each key is registered with the onboarding server, issued
+/// on chain and onboarded to the IOT or MOBILE subdao.
+pub struct Cmd {
+    /// The key(s) of the Hotspots to check
+    #[arg(required = true)]
+    addresses: Vec<helium_crypto::PublicKey>,
+    #[command(flatten)]
+    onboarding: OnboardingOpts,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let client = opts.client()?;
+        let onboarding_client = self.onboarding.client(&opts);
+        let mut statuses = vec![];
+        for address in &self.addresses {
+            let status = onboarding::key_status(&client, &onboarding_client, address).await?;
+            statuses.push(json!({
+                "address": address.to_string(),
+                "status": status,
+            }));
+        }
+        print_json(&statuses)
+    }
+}
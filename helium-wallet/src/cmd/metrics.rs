@@ -0,0 +1,234 @@
+//! Prometheus metrics of the long running commands.
+//!
+//! Metrics are collected for every command but only exposed, in the
+//! Prometheus text format, by `serve` and by `watch` and `rewards autoclaim`
+//! when started with --metrics.
+
+use crate::cmd::*;
+use axum::{http::header, response::IntoResponse, routing::get, Router};
+use helium_lib::{client::sender, solana_client::rpc_request::RpcRequest};
+use std::{collections::BTreeMap, fmt::Write, net::SocketAddr, sync::Mutex, time::Duration};
+
+/// Upper bounds of the RPC latency histogram buckets, in seconds
+const LATENCY_BUCKETS: [f64; 10] = [0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+#[derive(Debug, clap::Args)]
+pub struct MetricsOpts {
+    /// Address to serve Prometheus metrics on at /metrics, e.g.
+    /// "127.0.0.1:9090"
+    #[arg(long)]
+    metrics: Option<SocketAddr>,
+}
+
+impl MetricsOpts {
+    /// Start serving metrics in the background, if enabled
+    pub async fn serve(&self) -> Result {
+        let Some(listen) = self.metrics else {
+            return Ok(());
+        };
+        init();
+        let listener = tokio::net::TcpListener::bind(listen).await?;
+        tracing::info!(%listen, "serving metrics");
+        tokio::spawn(async move {
+            if let Err(err) =
+                axum::serve(listener, Router::new().route("/metrics", get(handler))).await
+            {
+                eprintln!("metrics server failed: {err}");
+            }
+        });
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct Histogram {
+    /// Observations per bucket, not cumulative
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: f64) {
+        if let Some(bucket) = LATENCY_BUCKETS.iter().position(|bound| value <= *bound) {
+            self.buckets[bucket] += 1;
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+}
+
+#[derive(Default)]
+struct Metrics {
+    /// RPC request latency by method and outcome
+    rpc_requests: BTreeMap<(String, &'static str), Histogram>,
+    /// Transactions by status
+    transactions: BTreeMap<&'static str, u64>,
+    fees_lamports: u64,
+    /// Reward claims and the claimed amount, where known, by token
+    reward_claims: BTreeMap<Token, (u64, u64)>,
+}
+
+static METRICS: Mutex<Metrics> = Mutex::new(Metrics {
+    rpc_requests: BTreeMap::new(),
+    transactions: BTreeMap::new(),
+    fees_lamports: 0,
+    reward_claims: BTreeMap::new(),
+});
+
+fn metrics() -> std::sync::MutexGuard<'static, Metrics> {
+    METRICS.lock().expect("metrics lock")
+}
+
+/// Start collecting RPC request metrics
+pub fn init() {
+    sender::set_request_observer(rpc_request);
+}
+
+fn rpc_request(request: &RpcRequest, elapsed: Duration, ok: bool) {
+    let status = if ok { "ok" } else { "error" };
+    metrics()
+        .rpc_requests
+        .entry((request.to_string(), status))
+        .or_default()
+        .observe(elapsed.as_secs_f64());
+}
+
+fn transaction(status: &'static str) {
+    *metrics().transactions.entry(status).or_default() += 1;
+}
+
+/// Count a sent transaction and the fee it pays
+pub fn transaction_submitted(fee: u64) {
+    transaction("submitted");
+    metrics().fees_lamports += fee;
+}
+
+pub fn transaction_confirmed() {
+    transaction("confirmed");
+}
+
+/// Count a transaction that failed to send or failed on chain
+pub fn transaction_failed() {
+    transaction("failed");
+}
+
+/// Count a committed reward claim, with the claimed amount if known
+pub fn reward_claimed(token: Token, amount: Option<u64>) {
+    let mut metrics = metrics();
+    let (claims, claimed) = metrics.reward_claims.entry(token).or_default();
+    *claims += 1;
+    *claimed += amount.unwrap_or_default();
+}
+
+/// The metrics in the Prometheus text exposition format
+pub fn render() -> String {
+    let metrics = metrics();
+    let mut out = String::new();
+    // Writing to a String does not fail
+    let _ = render_to(&metrics, &mut out);
+    out
+}
+
+fn render_to(metrics: &Metrics, out: &mut String) -> std::fmt::Result {
+    let name = "helium_wallet_rpc_request_duration_seconds";
+    writeln!(
+        out,
+        "# HELP {name} Duration of Solana RPC requests, including retries"
+    )?;
+    writeln!(out, "# TYPE {name} histogram")?;
+    for ((method, status), histogram) in &metrics.rpc_requests {
+        let labels = format!("method=\"{method}\",status=\"{status}\"");
+        let mut cumulative = 0;
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets) {
+            cumulative += count;
+            writeln!(out, "{name}_bucket{{{labels},le=\"{bound}\"}} {cumulative}")?;
+        }
+        writeln!(
+            out,
+            "{name}_bucket{{{labels},le=\"+Inf\"}} {}",
+            histogram.count
+        )?;
+        writeln!(out, "{name}_sum{{{labels}}} {}", histogram.sum)?;
+        writeln!(out, "{name}_count{{{labels}}} {}", histogram.count)?;
+    }
+
+    let name = "helium_wallet_transactions_total";
+    writeln!(
+        out,
+        "# HELP {name} Transactions submitted, confirmed and failed"
+    )?;
+    writeln!(out, "# TYPE {name} counter")?;
+    for status in ["submitted", "confirmed", "failed"] {
+        let count = metrics
+            .transactions
+            .get(status)
+            .copied()
+            .unwrap_or_default();
+        writeln!(out, "{name}{{status=\"{status}\"}} {count}")?;
+    }
+
+    let name = "helium_wallet_fees_paid_lamports_total";
+    writeln!(
+        out,
+        "# HELP {name} Fees of submitted transactions in lamports"
+    )?;
+    writeln!(out, "# TYPE {name} counter")?;
+    writeln!(out, "{name} {}", metrics.fees_lamports)?;
+
+    let claims_name = "helium_wallet_reward_claims_total";
+    let claimed_name = "helium_wallet_rewards_claimed_total";
+    writeln!(out, "# HELP {claims_name} Committed reward claims")?;
+    writeln!(out, "# TYPE {claims_name} counter")?;
+    for (token, (claims, _)) in &metrics.reward_claims {
+        writeln!(out, "{claims_name}{{token=\"{token}\"}} {claims}")?;
+    }
+    writeln!(out, "# HELP {claimed_name} Claimed rewards in whole tokens")?;
+    writeln!(out, "# TYPE {claimed_name} counter")?;
+    for (token, (_, claimed)) in &metrics.reward_claims {
+        let claimed = f64::from(&token.amount(*claimed));
+        writeln!(out, "{claimed_name}{{token=\"{token}\"}} {claimed}")?;
+    }
+    Ok(())
+}
+
+/// Handler of the /metrics route
+pub async fn handler() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        render(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exposition() {
+        let mut metrics = Metrics::default();
+        let mut histogram = Histogram::default();
+        histogram.observe(0.02);
+        histogram.observe(20.0);
+        metrics
+            .rpc_requests
+            .insert(("getSlot".to_string(), "ok"), histogram);
+        metrics.transactions.insert("submitted", 2);
+        metrics.reward_claims.insert(Token::Iot, (1, 1_500_000));
+        let mut out = String::new();
+        render_to(&metrics, &mut out).expect("render");
+
+        let lines: Vec<&str> = out.lines().collect();
+        for line in [
+            r#"helium_wallet_rpc_request_duration_seconds_bucket{method="getSlot",status="ok",le="0.01"} 0"#,
+            r#"helium_wallet_rpc_request_duration_seconds_bucket{method="getSlot",status="ok",le="0.025"} 1"#,
+            r#"helium_wallet_rpc_request_duration_seconds_bucket{method="getSlot",status="ok",le="10"} 1"#,
+            r#"helium_wallet_rpc_request_duration_seconds_bucket{method="getSlot",status="ok",le="+Inf"} 2"#,
+            r#"helium_wallet_transactions_total{status="submitted"} 2"#,
+            r#"helium_wallet_transactions_total{status="failed"} 0"#,
+            r#"helium_wallet_rewards_claimed_total{token="iot"} 1.5"#,
+        ] {
+            assert!(lines.contains(&line), "missing {line}");
+        }
+    }
+}
@@ -1,18 +1,21 @@
 use crate::{
+    error_code::ErrorCode,
+    remote_signer::{self, RemoteSigner},
     result::{anyhow, bail, Error, Result},
     wallet::Wallet,
 };
 use helium_lib::{
     b64,
     client::{self, SolanaRpcClient},
+    error::TransactionError,
     keypair::Keypair,
     message, priority_fee,
-    solana_client::{
-        self, rpc_config::RpcSendTransactionConfig, rpc_request::RpcResponseErrorData,
-        rpc_response::RpcSimulateTransactionResult,
+    solana_client::{self, rpc_config::RpcSendTransactionConfig},
+    solana_sdk::{
+        commitment_config::CommitmentConfig, signer::Signer, transaction::VersionedTransaction,
     },
-    solana_sdk::transaction::VersionedTransaction,
-    TransactionOpts,
+    token::{Token, TokenAmount},
+    tx, TransactionOpts,
 };
 use serde_json::json;
 use std::{
@@ -22,23 +25,51 @@ use std::{
     sync::Arc,
 };
 
+pub mod accounts;
 pub mod assets;
+pub mod audit;
 pub mod balance;
+pub mod boost;
 pub mod burn;
+pub mod cache;
 pub mod create;
+pub mod dao;
 pub mod dc;
+pub mod entity_key;
 pub mod export;
 pub mod hotspots;
 pub mod info;
+pub mod maker;
 pub mod memo;
+pub mod metrics;
+pub mod oracle;
+pub mod oui;
+pub mod output;
 pub mod price;
+pub mod rewards;
 pub mod router;
+pub mod serve;
 pub mod sign;
+pub mod signer;
+pub mod stake;
+pub mod stats;
 pub mod transfer;
+pub mod treasury;
+pub mod tui;
 pub mod upgrade;
+pub mod validators;
+pub mod vote;
+pub mod watch;
+pub mod webhook;
+
+pub use metrics::MetricsOpts;
+pub use output::{
+    amount_cell, print_json, print_output, print_rows, print_table, OutputFormat, Progress,
+};
+pub use webhook::{Webhook, WebhookOpts};
 
 /// Common options for most wallet commands
-#[derive(Debug, clap::Args, Clone)]
+#[derive(clap::Args, Clone)]
 pub struct Opts {
     /// File(s) to use
     #[arg(
@@ -49,9 +80,127 @@ pub struct Opts {
     )]
     files: Vec<PathBuf>,
 
-    /// Solana RPC URL to use.
+    /// Solana RPC URL to use. A comma separated list of URLs uses the
+    /// fastest healthy one and fails over to the others on errors.
     #[arg(long, default_value = "m")]
     url: String,
+
+    /// Number of times to retry Solana RPC requests that fail for transient
+    /// reasons like rate limits or timeouts.
+    #[arg(long, default_value_t = 3)]
+    rpc_retries: u32,
+
+    /// Commitment level for reads, blockhashes and confirmation waits.
+    /// Defaults to finalized.
+    #[arg(long, value_enum)]
+    commitment: Option<Commitment>,
+
+    /// Timeout for a single Solana RPC or DAS request.
+    #[arg(long, default_value = "30s", value_parser = humantime::parse_duration)]
+    timeout: std::time::Duration,
+
+    /// Overall deadline for the command, after which it fails with a
+    /// timeout error. No deadline by default.
+    #[arg(long, value_parser = humantime::parse_duration)]
+    pub deadline: Option<std::time::Duration>,
+
+    /// Header to add to every Solana RPC and DAS request, like
+    /// 'x-api-key: <key>' for providers that authenticate with a header.
+    /// Can be given multiple times.
+    #[arg(long = "rpc-header", value_name = "NAME: VALUE", value_parser = parse_header)]
+    rpc_headers: Vec<(String, String)>,
+
+    /// Log to stderr. Use -v for sent transactions, -vv to add RPC requests,
+    /// retries, timings and fees and -vvv for everything. RUST_LOG overrides
+    /// what gets logged.
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Log as JSON lines, so the logs of unattended runs can be processed
+    /// later. Logs at the -v level unless a higher level is given.
+    #[arg(long)]
+    pub log_json: bool,
+
+    /// Fetch asset proofs from DAS for every operation instead of reusing
+    /// proofs that are still valid, and skip the on-disk cache of Hotspot
+    /// key to asset mappings.
+    #[arg(long)]
+    pub no_cache: bool,
+
+    /// How long Hotspot key to asset mappings are reused from the on-disk
+    /// cache before being fetched again.
+    #[arg(long, default_value = "30days", value_parser = humantime::parse_duration)]
+    pub cache_ttl: std::time::Duration,
+
+    /// Write the JSON, table or CSV output of the command to this file
+    /// instead of stdout. The file is replaced in one step when the command
+    /// completes and a short summary is printed instead.
+    #[arg(long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    /// Print only the signatures of sent transactions, one per line, and
+    /// errors as JSON on stderr. Transactions that still need signatures are
+    /// printed base64 encoded instead. Commands that do not produce
+    /// transactions print their output as usual.
+    #[arg(short, long)]
+    pub quiet: bool,
+
+    /// Do not use colors, even when printing to a terminal. Colors are also
+    /// turned off by setting the NO_COLOR environment variable.
+    #[arg(long)]
+    pub no_color: bool,
+
+    /// Append every transaction signed by the wallet to this hash chained
+    /// JSON lines audit log. Check it with `audit verify`.
+    #[arg(long, value_name = "PATH")]
+    pub audit_log: Option<PathBuf>,
+}
+
+/// RPC header values often hold API keys, so only header names are shown
+impl std::fmt::Debug for Opts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Opts")
+            .field("files", &self.files)
+            .field("url", &self.url)
+            .field("rpc_retries", &self.rpc_retries)
+            .field("commitment", &self.commitment)
+            .field("timeout", &self.timeout)
+            .field("deadline", &self.deadline)
+            .field("rpc_headers", &client::redacted_headers(&self.rpc_headers))
+            .field("verbose", &self.verbose)
+            .field("log_json", &self.log_json)
+            .field("no_cache", &self.no_cache)
+            .field("cache_ttl", &self.cache_ttl)
+            .field("output", &self.output)
+            .field("quiet", &self.quiet)
+            .field("no_color", &self.no_color)
+            .field("audit_log", &self.audit_log)
+            .finish()
+    }
+}
+
+fn parse_header(s: &str) -> Result<(String, String)> {
+    let (name, value) = s
+        .split_once(':')
+        .ok_or_else(|| anyhow!("expected a header as 'name: value'"))?;
+    Ok((name.trim().to_string(), value.trim().to_string()))
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum Commitment {
+    Processed,
+    Confirmed,
+    Finalized,
+}
+
+impl From<Commitment> for CommitmentConfig {
+    fn from(value: Commitment) -> Self {
+        match value {
+            Commitment::Processed => Self::processed(),
+            Commitment::Confirmed => Self::confirmed(),
+            Commitment::Finalized => Self::finalized(),
+        }
+    }
 }
 
 impl Opts {
@@ -80,7 +229,36 @@ impl Opts {
     }
 
     pub fn client(&self) -> Result<client::Client> {
-        Ok(client::Client::try_from(self.url.as_str())?)
+        let retry_policy =
+            client::RetryPolicy::default().with_max_attempts(self.rpc_retries.saturating_add(1));
+        let mut builder = client::Client::builder(&self.url)
+            .retry_policy(retry_policy)
+            .timeout(self.timeout);
+        if let Some(commitment) = self.commitment {
+            builder = builder.commitment(commitment.into());
+        }
+        let builder = self
+            .rpc_headers
+            .iter()
+            .fold(builder, |builder, (name, value)| {
+                builder.header(name, value)
+            });
+        Ok(builder.build()?)
+    }
+
+    /// The on-disk KTA cache file for the cluster in use, if a cache
+    /// directory could be determined. Clusters get separate files since the
+    /// same keys can map to different assets on each of them.
+    pub fn kta_cache_path(&self) -> Option<PathBuf> {
+        use sha2::{Digest, Sha256};
+        let cluster = match self.url.as_str() {
+            url @ ("m" | "d") => url.to_string(),
+            url => Sha256::digest(url.as_bytes())[..8]
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect(),
+        };
+        cache::dir().map(|dir| dir.join(format!("kta-{cluster}.bin")))
     }
 }
 
@@ -95,62 +273,213 @@ pub struct CommitOpts {
     /// Maximum priority fee in micro lamports
     #[arg(long, default_value_t = priority_fee::MAX_PRIORITY_FEE)]
     max_priority_fee: u64,
+    /// How to choose the priority fee: auto, helius, percentile:<0-100> of
+    /// recent fees, or fixed:<micro lamports>
+    #[arg(long, default_value_t = priority_fee::PriorityFeeStrategy::Auto)]
+    priority_fee_strategy: priority_fee::PriorityFeeStrategy,
+    /// Maximum SOL to spend on the fees of all transactions of this command.
+    /// The command fails when a transaction would exceed it.
+    #[arg(long)]
+    fee_budget: Option<f64>,
+    /// The fee budget shared by all transaction options of this command
+    #[arg(skip)]
+    fee_budget_state: Arc<std::sync::OnceLock<Arc<priority_fee::FeeBudget>>>,
+    /// Pay transaction fees from another account: a wallet file, or the
+    /// public key of a fee payer that signs elsewhere. Transactions that
+    /// still need the signature of the fee payer are output base64 encoded
+    /// instead of committed.
+    #[arg(long, value_name = "WALLET|PUBKEY")]
+    fee_payer: Option<String>,
+    /// The fee payer, loaded once for all transactions of this command
+    #[arg(skip)]
+    fee_payer_state: Arc<std::sync::OnceLock<FeePayer>>,
+    /// Remote signer to request missing signatures from, like that of a fee
+    /// payer given as a public key. The bearer token for the signer is read
+    /// from the HELIUM_WALLET_SIGNER_TOKEN environment variable.
+    #[arg(long, value_name = "URL")]
+    signer_url: Option<String>,
+    /// The remote signer, connected once for all transactions of this command
+    #[arg(skip)]
+    signer_state: Arc<tokio::sync::OnceCell<RemoteSigner>>,
+    /// Margin over the simulated compute units used as the compute unit
+    /// limit, as a fraction
+    #[arg(long, default_value_t = priority_fee::DEFAULT_COMPUTE_UNIT_MARGIN)]
+    compute_unit_margin: f64,
+    /// Keep the default compute unit limit instead of simulating to size it
+    #[arg(long)]
+    no_compute_unit_simulation: bool,
+    /// Fixed compute unit limit for transactions, skipping the simulation
+    #[arg(long)]
+    compute_unit_limit: Option<u32>,
     /// Commit the transaction
     #[arg(long)]
     commit: bool,
 }
 
+#[derive(Debug, Clone)]
+enum FeePayer {
+    Keypair(Arc<Keypair>),
+    Pubkey(helium_lib::keypair::Pubkey),
+}
+
+impl FeePayer {
+    fn pubkey(&self) -> helium_lib::keypair::Pubkey {
+        match self {
+            Self::Keypair(keypair) => keypair.pubkey(),
+            Self::Pubkey(pubkey) => *pubkey,
+        }
+    }
+}
+
 impl CommitOpts {
+    fn fee_payer(&self) -> Result<Option<&FeePayer>> {
+        let Some(fee_payer) = &self.fee_payer else {
+            return Ok(None);
+        };
+        if let Some(loaded) = self.fee_payer_state.get() {
+            return Ok(Some(loaded));
+        }
+        let loaded = match fee_payer.parse() {
+            Ok(pubkey) => FeePayer::Pubkey(pubkey),
+            Err(_) => {
+                let wallet = Wallet::read(&mut fs::File::open(fee_payer)?)?;
+                let password = get_password("Fee Payer Wallet Password", false)?;
+                FeePayer::Keypair(wallet.decrypt(password.as_bytes())?)
+            }
+        };
+        Ok(Some(self.fee_payer_state.get_or_init(|| loaded)))
+    }
+
+    async fn remote_signer(&self) -> Result<Option<&RemoteSigner>> {
+        let Some(url) = &self.signer_url else {
+            return Ok(None);
+        };
+        let signer = self
+            .signer_state
+            .get_or_try_init(|| async {
+                let auth_token = env::var(remote_signer::AUTH_TOKEN_ENV).ok();
+                RemoteSigner::connect(url, auth_token.as_deref()).await
+            })
+            .await?;
+        Ok(Some(signer))
+    }
+
     pub async fn maybe_commit<C: AsRef<client::SolanaRpcClient>, T: Into<VersionedTransaction>>(
         &self,
         tx: T,
         client: &C,
     ) -> Result<CommitResponse> {
-        fn context_err(client_err: solana_client::client_error::ClientError) -> Error {
-            let mut captured_logs: Option<Vec<String>> = None;
-            let mut error_message: Option<String> = None;
-            if let solana_client::client_error::ClientErrorKind::RpcError(
-                solana_client::rpc_request::RpcError::RpcResponseError {
-                    data:
-                        RpcResponseErrorData::SendTransactionPreflightFailure(
-                            RpcSimulateTransactionResult { logs, .. },
-                        ),
-                    message,
-                    ..
-                },
-            ) = &client_err.kind
-            {
-                logs.clone_into(&mut captured_logs);
-                error_message = Some(message.clone());
+        self.commit_with(tx, client, false).await
+    }
+
+    /// Like [`Self::maybe_commit`], but waits for a committed transaction to
+    /// be confirmed. Used when later transactions depend on this one.
+    pub async fn maybe_commit_confirmed<
+        C: AsRef<client::SolanaRpcClient>,
+        T: Into<VersionedTransaction>,
+    >(
+        &self,
+        tx: T,
+        client: &C,
+    ) -> Result<CommitResponse> {
+        self.commit_with(tx, client, true).await
+    }
+
+    async fn commit_with<C: AsRef<client::SolanaRpcClient>, T: Into<VersionedTransaction>>(
+        &self,
+        tx: T,
+        client: &C,
+        confirm: bool,
+    ) -> Result<CommitResponse> {
+        let mut versioned_tx: VersionedTransaction = tx.into();
+        if let Some(FeePayer::Keypair(fee_payer)) = self.fee_payer()? {
+            if tx::missing_signers(&versioned_tx).contains(&fee_payer.pubkey()) {
+                tx::sign(&mut versioned_tx, &[&**fee_payer])?;
             }
-            let mut mapped = Error::from(client_err);
-            if let Some(message) = error_message {
-                mapped = mapped.context(message);
+        }
+        if let Some(signer) = self.remote_signer().await? {
+            if tx::missing_signers(&versioned_tx).contains(&signer.pubkey()) {
+                versioned_tx = signer.sign_transaction(&versioned_tx).await?;
             }
-            if let Some(logs) = captured_logs.as_ref() {
-                if let Ok(serialized_logs) = serde_json::to_string(logs) {
-                    mapped = mapped.context(serialized_logs);
-                }
+        }
+        let result = self.submit(&versioned_tx, client, confirm).await;
+        let (outcome, error) = match &result {
+            Ok(CommitResponse::Transaction(tracked)) if tracked.commitment.is_some() => {
+                ("confirmed", None)
             }
-            mapped
+            Ok(CommitResponse::Transaction(_)) => ("submitted", None),
+            Ok(CommitResponse::PartiallySigned { .. }) => ("partially_signed", None),
+            Ok(CommitResponse::None) => ("simulated", None),
+            Err(err) => ("failed", Some(format!("{err:#}"))),
+        };
+        // The transaction may have landed, so name it when it can not be
+        // recorded
+        crate::audit::record(&versioned_tx, outcome, error).map_err(|err| {
+            err.context(format!(
+                "transaction {} was {outcome}",
+                versioned_tx.signatures[0]
+            ))
+        })?;
+        result
+    }
+
+    /// Send or simulate a transaction, or return it for other signers to
+    /// sign when signatures are missing
+    async fn submit<C: AsRef<client::SolanaRpcClient>>(
+        &self,
+        versioned_tx: &VersionedTransaction,
+        client: &C,
+        confirm: bool,
+    ) -> Result<CommitResponse> {
+        fn context_err(client_err: solana_client::client_error::ClientError) -> Error {
+            with_logs(helium_lib::error::Error::from(client_err))
         }
 
-        let versioned_tx = tx.into();
+        let missing_signers = tx::missing_signers(versioned_tx);
+        if !missing_signers.is_empty() {
+            return Ok(CommitResponse::PartiallySigned {
+                transaction: b64::encode(bincode::serialize(versioned_tx)?),
+                missing_signers,
+            });
+        }
         if self.commit {
             let config = RpcSendTransactionConfig {
                 skip_preflight: self.skip_preflight,
                 ..Default::default()
             };
-            client
+            // The latest blockhash expires no earlier than the one the
+            // transaction was built with, so its block height bounds the wait
+            // for confirmation
+            let (_, last_valid_block_height) = client
                 .as_ref()
-                .send_transaction_with_config(&versioned_tx, config)
+                .get_latest_blockhash_with_commitment(client.as_ref().commitment())
+                .await
+                .map_err(context_err)?;
+            let mut tracked = tx::TrackedTransaction::send(client, versioned_tx, config)
                 .await
-                .map(Into::into)
-                .map_err(context_err)
+                .inspect_err(|_| metrics::transaction_failed())
+                .map_err(with_logs)?
+                .with_last_valid_block_height(last_valid_block_height);
+            tracing::info!(signature = %tracked.signature, "sent transaction");
+            metrics::transaction_submitted(priority_fee::message_fee(&versioned_tx.message));
+            if confirm {
+                let confirmed = tracked
+                    .confirm(client, client.as_ref().commitment())
+                    .await
+                    .and_then(|_| tracked.error.take().map_or(Ok(()), |err| Err(err.into())));
+                match confirmed {
+                    Ok(()) => metrics::transaction_confirmed(),
+                    Err(err) => {
+                        metrics::transaction_failed();
+                        return Err(with_logs(err));
+                    }
+                }
+            }
+            Ok(tracked.into())
         } else {
             client
                 .as_ref()
-                .simulate_transaction(&versioned_tx)
+                .simulate_transaction(versioned_tx)
                 .await
                 .map_err(context_err)?
                 .value
@@ -158,16 +487,44 @@ impl CommitOpts {
         }
     }
 
-    pub fn transaction_opts<C: AsRef<SolanaRpcClient>>(&self, client: &C) -> TransactionOpts {
-        TransactionOpts {
-            min_priority_fee: self.min_priority_fee,
-            max_priority_fee: self.max_priority_fee,
-            lut_addresses: if client::is_devnet(&client.as_ref().url()) {
-                vec![message::COMMON_LUT_DEVNET]
-            } else {
-                vec![message::COMMON_LUT]
-            },
+    pub fn transaction_opts<C: AsRef<SolanaRpcClient>>(
+        &self,
+        client: &C,
+    ) -> Result<TransactionOpts> {
+        let lut_address = if client::is_devnet(&client.as_ref().url()) {
+            message::COMMON_LUT_DEVNET
+        } else {
+            message::COMMON_LUT
+        };
+        let builder = TransactionOpts::builder()
+            .priority_fee_range(self.min_priority_fee, self.max_priority_fee)
+            .priority_fee_strategy(self.priority_fee_strategy)
+            .compute_unit_margin(
+                (!self.no_compute_unit_simulation).then_some(self.compute_unit_margin),
+            )
+            .lut_addresses(vec![lut_address]);
+        let builder = match self.compute_unit_limit {
+            Some(compute_unit_limit) => builder.compute_unit_limit(compute_unit_limit),
+            None => builder,
+        };
+        let builder = match self.fee_payer()? {
+            Some(fee_payer) => builder.fee_payer(fee_payer.pubkey()),
+            None => builder,
+        };
+        let opts = match self.fee_budget {
+            Some(budget) => builder.fee_budget(
+                self.fee_budget_state
+                    .get_or_init(|| {
+                        Arc::new(priority_fee::FeeBudget::new(
+                            TokenAmount::from_f64(Token::Sol, budget).amount,
+                        ))
+                    })
+                    .clone(),
+            ),
+            None => builder,
         }
+        .build();
+        Ok(opts)
     }
 }
 
@@ -225,20 +582,21 @@ pub fn get_file_extension(filename: &Path) -> String {
         .to_string()
 }
 
-pub fn print_json<T: ?Sized + serde::Serialize>(value: &T) -> Result {
-    println!("{}", serde_json::to_string_pretty(value)?);
-    Ok(())
-}
-
 #[derive(Debug, serde::Serialize)]
 pub enum CommitResponse {
-    Signature(helium_lib::keypair::Signature),
+    Transaction(tx::TrackedTransaction),
+    /// A base64 encoded transaction that still needs the signatures of
+    /// the given signers
+    PartiallySigned {
+        transaction: String,
+        missing_signers: Vec<helium_lib::keypair::Pubkey>,
+    },
     None,
 }
 
-impl From<helium_lib::keypair::Signature> for CommitResponse {
-    fn from(value: helium_lib::keypair::Signature) -> Self {
-        Self::Signature(value)
+impl From<tx::TrackedTransaction> for CommitResponse {
+    fn from(value: tx::TrackedTransaction) -> Self {
+        Self::Transaction(value)
     }
 }
 
@@ -248,19 +606,47 @@ impl TryFrom<solana_client::rpc_response::RpcSimulateTransactionResult> for Comm
         value: solana_client::rpc_response::RpcSimulateTransactionResult,
     ) -> Result<CommitResponse> {
         if let Some(err) = value.err {
-            Err(err.into())
+            let err = TransactionError::new(err, value.logs.unwrap_or_default());
+            Err(with_logs(err.into()))
         } else {
             Ok(Self::None)
         }
     }
 }
 
+/// Convert a library error, adding the program logs of a failed transaction
+/// as context so they are shown along with the error
+fn with_logs(err: helium_lib::error::Error) -> Error {
+    let logs = err
+        .transaction_error()
+        .filter(|txn_err| !txn_err.logs.is_empty())
+        .and_then(|txn_err| serde_json::to_string(&txn_err.logs).ok());
+    match logs {
+        Some(logs) => Error::from(err).context(logs),
+        None => Error::from(err),
+    }
+}
+
 impl ToJson for CommitResponse {
     fn to_json(&self) -> serde_json::Value {
         match self {
-            Self::Signature(signature) => json!({
+            Self::Transaction(tracked) => json!({
                 "result": "ok",
-                "txid": signature.to_string(),
+                "txid": tracked.signature.to_string(),
+                "slot": tracked.slot,
+                "commitment": tracked.commitment,
+                "outcomes": tracked.outcomes,
+            }),
+            Self::PartiallySigned {
+                transaction,
+                missing_signers,
+            } => json!({
+                "result": "partially_signed",
+                "transaction": transaction,
+                "missing_signers": missing_signers
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>(),
             }),
             Self::None => json!({"result": "ok"}),
         }
@@ -271,10 +657,24 @@ impl ToJson for Result<CommitResponse> {
     fn to_json(&self) -> serde_json::Value {
         match self {
             Ok(response) => response.to_json(),
-            Err(err) => json!({
-                "result": "error",
-                "error": err.to_string()
-            }),
+            Err(err) => match err.downcast_ref::<helium_lib::error::Error>() {
+                Some(lib_err @ helium_lib::error::Error::Transaction(txn_err)) => json!({
+                    "result": "error",
+                    "error": lib_err.to_string(),
+                    "code": ErrorCode::of_lib(lib_err),
+                    "instruction": txn_err.instruction,
+                    "program_error": txn_err.program_error.as_ref().map(|program_error| json!({
+                        "code": program_error.code,
+                        "name": program_error.name,
+                    })),
+                    "logs": txn_err.logs,
+                }),
+                _ => json!({
+                    "result": "error",
+                    "error": err.to_string(),
+                    "code": ErrorCode::of(err),
+                }),
+            },
         }
     }
 }
@@ -0,0 +1,29 @@
+use crate::cmd::*;
+
+pub mod set_rewards;
+
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    #[command(subcommand)]
+    cmd: OracleCommand,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        self.cmd.run(opts).await
+    }
+}
+
+/// Commands for operators of a rewards oracle
+#[derive(Debug, clap::Subcommand)]
+pub enum OracleCommand {
+    SetRewards(set_rewards::Cmd),
+}
+
+impl OracleCommand {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Self::SetRewards(cmd) => cmd.run(opts).await,
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use crate::cmd::*;
+use futures::{stream, StreamExt};
+use helium_lib::{
+    entity_key::{EncodedEntityKey, EntityKeyEncoding},
+    reward::{self, ClaimableToken},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, clap::Args)]
+/// Set the current lifetime rewards for a batch of entities
+///
+/// The wallet must be one of the oracles of the token's lazy distributor. The
+/// input is a CSV file with an "entity_key" and a "rewards" column, where
+/// rewards are the total lifetime rewards of the entity in the smallest unit
+/// of the token. Each entity is set in its own transaction and entities
+/// without an initialized recipient account are reported as failed.
+pub struct Cmd {
+    /// CSV file with the entity keys and lifetime rewards to set
+    input: PathBuf,
+    /// Token to set rewards for
+    #[arg(long, value_enum)]
+    token: ClaimableToken,
+    /// Encoding of the entity keys in the input file
+    #[arg(long, value_enum, default_value_t = EntityKeyEncoding::B58)]
+    encoding: EntityKeyEncoding,
+    /// Maximum number of transactions to prepare and submit concurrently
+    #[arg(long, default_value_t = 5)]
+    concurrency: usize,
+    /// Commit the rewards transactions.
+    #[command(flatten)]
+    commit: CommitOpts,
+}
+
+#[derive(Debug, Deserialize)]
+struct RewardsEntry {
+    entity_key: String,
+    rewards: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct RewardsStatus {
+    entity_key: String,
+    rewards: u64,
+    #[serde(flatten)]
+    result: serde_json::Value,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let entries: Vec<RewardsEntry> = csv::Reader::from_path(&self.input)?
+            .deserialize()
+            .collect::<std::result::Result<_, _>>()?;
+        let password = get_wallet_password(false)?;
+        let keypair = opts.load_keypair(password.as_bytes())?;
+        let client = opts.client()?;
+        let transaction_opts = self.commit.transaction_opts(&client)?;
+
+        let statuses: Vec<RewardsStatus> = stream::iter(entries)
+            .map(|entry| {
+                let client = &client;
+                let keypair = &keypair;
+                let transaction_opts = &transaction_opts;
+                async move {
+                    let entity_key = EncodedEntityKey {
+                        entity_key: entry.entity_key.clone(),
+                        encoding: self.encoding,
+                    };
+                    let result = match entity_key.as_entity_key() {
+                        Ok(entity_key) => match reward::set_current_rewards(
+                            client,
+                            self.token,
+                            &entity_key,
+                            entry.rewards,
+                            keypair,
+                            transaction_opts,
+                        )
+                        .await
+                        {
+                            Ok((tx, _)) => self.commit.maybe_commit(tx, client).await.to_json(),
+                            Err(err) => json!({"result": "error", "error": err.to_string()}),
+                        },
+                        Err(err) => json!({"result": "error", "error": err.to_string()}),
+                    };
+                    RewardsStatus {
+                        entity_key: entry.entity_key,
+                        rewards: entry.rewards,
+                        result,
+                    }
+                }
+            })
+            .buffered(self.concurrency.max(1))
+            .collect()
+            .await;
+
+        print_json(&statuses)
+    }
+}
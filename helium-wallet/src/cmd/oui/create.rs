@@ -0,0 +1,46 @@
+use crate::cmd::*;
+use helium_lib::{
+    keypair::{Pubkey, Signer},
+    oui,
+};
+
+#[derive(Debug, clap::Args)]
+/// Create a new organization in a net id
+///
+/// The organization is assigned the next available OUI and the OUI fee is
+/// paid in IOT from this wallet. The organization needs to be approved by the
+/// routing manager before it can be used.
+pub struct Cmd {
+    /// The net id to create the organization in
+    #[arg(long)]
+    net_id: u32,
+    /// Authority of the new organization. Defaults to the active wallet
+    #[arg(long)]
+    authority: Option<Pubkey>,
+    /// Commit the transaction
+    #[command(flatten)]
+    commit: CommitOpts,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let password = get_wallet_password(false)?;
+        let keypair = opts.load_keypair(password.as_bytes())?;
+        let client = opts.client()?;
+        let transaction_opts = self.commit.transaction_opts(&client)?;
+        let authority = self.authority.unwrap_or_else(|| keypair.pubkey());
+
+        let (tx, _, oui) = oui::create(
+            &client,
+            self.net_id,
+            &authority,
+            &keypair,
+            &transaction_opts,
+        )
+        .await?;
+        let mut json = self.commit.maybe_commit(tx, &client).await?.to_json();
+        json["oui"] = oui.into();
+        json["organization"] = oui::organization_key(oui).to_string().into();
+        print_json(&json)
+    }
+}
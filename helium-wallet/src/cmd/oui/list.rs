@@ -0,0 +1,66 @@
+use crate::cmd::*;
+use helium_lib::{keypair::Pubkey, oui};
+
+#[derive(Debug, clap::Args)]
+/// List organizations and their devaddr constraints
+///
+/// Lists the organizations of the given authority, or the active wallet by
+/// default. A single organization can be looked up by OUI instead.
+pub struct Cmd {
+    /// Authority to list organizations for
+    #[arg(long, conflicts_with = "oui")]
+    authority: Option<Pubkey>,
+    /// The OUI of a single organization to show
+    #[arg(long)]
+    oui: Option<u64>,
+    /// Output format
+    #[arg(long, value_enum, default_value_t)]
+    format: OutputFormat,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let client = opts.client()?;
+        let organizations = match (self.oui, self.authority) {
+            (Some(oui), _) => vec![oui::get(&client, oui).await?],
+            (None, Some(authority)) => oui::for_authority(&client, &authority).await?,
+            (None, None) => {
+                let wallet = opts.load_wallet()?;
+                oui::for_authority(&client, &wallet.public_key).await?
+            }
+        };
+
+        match self.format {
+            OutputFormat::Json => print_json(&organizations),
+            OutputFormat::Table | OutputFormat::Csv => {
+                let mut table = prettytable::Table::new();
+                table.set_format(*prettytable::format::consts::FORMAT_CLEAN);
+                table.set_titles(prettytable::row![
+                    "OUI",
+                    "Net ID",
+                    "Authority",
+                    "Approved",
+                    "Devaddrs"
+                ]);
+                for organization in &organizations {
+                    let devaddrs = organization
+                        .devaddr_constraints
+                        .iter()
+                        .map(|constraint| {
+                            format!("{:08x}-{:08x}", constraint.start_addr, constraint.end_addr)
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    table.add_row(prettytable::row![
+                        organization.oui,
+                        format!("{:06x}", organization.net_id),
+                        organization.authority,
+                        organization.approved,
+                        devaddrs
+                    ]);
+                }
+                print_rows(&table, self.format)
+            }
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use crate::cmd::*;
+
+pub mod create;
+pub mod list;
+pub mod routing;
+pub mod update;
+
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    #[command(subcommand)]
+    cmd: OuiCommand,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        self.cmd.run(opts).await
+    }
+}
+
+/// Manage organizations (OUIs), their devaddr constraints and their routes
+#[derive(Debug, clap::Subcommand)]
+pub enum OuiCommand {
+    Create(create::Cmd),
+    Update(update::Cmd),
+    List(list::Cmd),
+    Routing(routing::Cmd),
+}
+
+impl OuiCommand {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Self::Create(cmd) => cmd.run(opts).await,
+            Self::Update(cmd) => cmd.run(opts).await,
+            Self::List(cmd) => cmd.run(opts).await,
+            Self::Routing(cmd) => cmd.run(opts).await,
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use crate::cmd::*;
+use helium_lib::{
+    client::config::{route, IOT_CONFIG_URL_MAINNET},
+    oui::{self, RoutingUpdate},
+};
+use std::sync::Arc;
+
+#[derive(Debug, clap::Args)]
+/// Update the EUIs and session key filters of an organization's route
+///
+/// The changes are read from a JSON file of the form:
+///
+///   {"euis": {"add": [{"app_eui": "..", "dev_eui": ".."}], "remove": [..]},
+///    "skfs": {"add": [{"devaddr": "..", "session_key": "..", "max_copies": 1}],
+///             "remove": [..]}}
+///
+/// with EUIs and devaddrs in hex. Added session key filters must be for
+/// devaddrs in the organization's devaddr constraints.
+///
+/// Without --commit the resulting routing configuration is printed without
+/// changing the route. Requests are signed by this wallet, which must be the
+/// organization's authority or one of its delegate keys.
+pub struct Cmd {
+    /// The OUI of the organization owning the route
+    oui: u64,
+    /// The id of the route to update
+    #[arg(long)]
+    route: String,
+    /// JSON file with the EUI and session key filter changes
+    #[arg(long)]
+    file: PathBuf,
+    /// URL of the IoT config service
+    #[arg(long, default_value = IOT_CONFIG_URL_MAINNET)]
+    config: String,
+    /// Public key of the IoT config service to verify responses with
+    #[arg(long)]
+    config_key: Option<helium_crypto::PublicKey>,
+    /// Apply the changes to the route
+    #[arg(long)]
+    commit: bool,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let update: RoutingUpdate = serde_json::from_reader(fs::File::open(&self.file)?)?;
+        if update.is_empty() {
+            bail!(
+                "No EUI or session key filter changes in {}",
+                self.file.display()
+            );
+        }
+        let client = opts.client()?;
+        let organization = oui::get(&client, self.oui).await?;
+        update.check_devaddrs(&organization.devaddr_constraints)?;
+
+        let password = get_wallet_password(false)?;
+        let keypair = opts.load_keypair(password.as_bytes())?;
+        let mut route_client = route::Client::new(
+            &self.config,
+            self.config_key.clone(),
+            Arc::new(keypair.to_helium_keypair()?),
+        )?;
+        let current = route_client.config(&self.route).await?;
+        let result = current.apply(&update);
+        let mut json = json!({
+            "oui": self.oui,
+            "route": self.route,
+            "euis": {
+                "added": update.euis.add.len(),
+                "removed": update.euis.remove.len(),
+                "total": result.euis.len(),
+            },
+            "skfs": {
+                "added": update.skfs.add.len(),
+                "removed": update.skfs.remove.len(),
+                "total": result.skfs.len(),
+            },
+        });
+        if self.commit {
+            route_client.update(&self.route, &update).await?;
+            json["result"] = "ok".into();
+        } else {
+            json["config"] = json!(result);
+        }
+        print_json(&json)
+    }
+}
@@ -0,0 +1,51 @@
+use crate::cmd::*;
+use helium_lib::{keypair::Pubkey, oui};
+
+#[derive(Debug, clap::Args)]
+/// Update an organization
+///
+/// Devaddrs are added to an organization in multiples of 8 from its net id, with
+/// the devaddr fee paid in IOT from this wallet. Adding devaddrs and changing
+/// the authority can be combined, in which case the devaddrs are added before
+/// the authority changes.
+pub struct Cmd {
+    /// The OUI of the organization to update
+    oui: u64,
+    /// Number of devaddrs to add to the organization, a multiple of 8
+    #[arg(long)]
+    add_devaddr: Option<u64>,
+    /// Transfer the organization to a new authority
+    #[arg(long)]
+    authority: Option<Pubkey>,
+    /// Commit the transaction
+    #[command(flatten)]
+    commit: CommitOpts,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let mut update = oui::OrganizationUpdate::default();
+        if let Some(devaddrs) = self.add_devaddr {
+            update = update.add_devaddrs(devaddrs);
+        }
+        if let Some(authority) = self.authority {
+            update = update.set_authority(authority);
+        }
+        if update.is_empty() {
+            bail!("Must specify devaddrs to add or a new authority");
+        }
+
+        let password = get_wallet_password(false)?;
+        let keypair = opts.load_keypair(password.as_bytes())?;
+        let client = opts.client()?;
+        let transaction_opts = self.commit.transaction_opts(&client)?;
+
+        let (tx, _) = oui::update(&client, self.oui, update, &keypair, &transaction_opts).await?;
+        let mut json = self.commit.maybe_commit(tx, &client).await?.to_json();
+        json["oui"] = self.oui.into();
+        if let Some(devaddrs) = self.add_devaddr {
+            json["devaddrs_added"] = devaddrs.into();
+        }
+        print_json(&json)
+    }
+}
@@ -0,0 +1,456 @@
+//! Printing of command output.
+//!
+//! Commands print their results with [`print_json`], [`print_output`] or
+//! [`print_rows`], which take care of the global output options: `--quiet`,
+//! `--output` and colors. Colors are only used for terminals and can be
+//! turned off with `--no-color` or the `NO_COLOR` environment variable.
+
+use crate::result::{Error, Result};
+use helium_lib::token::TokenAmount;
+use rust_decimal::Decimal;
+use std::{
+    env, fs, io,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Print transaction results in the short form of `--quiet`
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+pub fn print_json<T: ?Sized + serde::Serialize>(value: &T) -> Result {
+    if is_quiet() {
+        let json = serde_json::to_value(value)?;
+        if let Some(lines) = quiet_lines(&json) {
+            return emit(lines.as_bytes(), || json_summary(&json));
+        }
+    }
+    let mut data = serde_json::to_string_pretty(value)?;
+    data.push('\n');
+    if console::colors_enabled() {
+        data = colorize_json(&data);
+    }
+    emit(data.as_bytes(), || {
+        serde_json::to_value(value)
+            .map(|json| json_summary(&json))
+            .unwrap_or_default()
+    })
+}
+
+/// Command output captured for `--output`, written to the output file when
+/// the command completes
+static CAPTURED_OUTPUT: Mutex<Option<CapturedOutput>> = Mutex::new(None);
+
+#[derive(Default)]
+struct CapturedOutput {
+    data: Vec<u8>,
+    /// Summary of the last printed output
+    summary: String,
+}
+
+/// Capture the output of the print functions instead of writing it to stdout
+pub fn capture_output() {
+    *CAPTURED_OUTPUT.lock().expect("output lock") = Some(CapturedOutput::default());
+}
+
+fn emit(data: &[u8], summary: impl FnOnce() -> String) -> Result {
+    use std::io::Write;
+    match CAPTURED_OUTPUT.lock().expect("output lock").as_mut() {
+        Some(captured) => {
+            captured.data.extend_from_slice(data);
+            captured.summary = summary();
+        }
+        None => io::stdout().write_all(data)?,
+    }
+    Ok(())
+}
+
+/// Write captured output to the given file and print a summary of it. The
+/// output goes to a temporary file next to it first, so the file is either
+/// left as it was or fully replaced.
+pub fn write_captured_output(path: &Path) -> Result {
+    use std::io::Write;
+    let Some(captured) = CAPTURED_OUTPUT.lock().expect("output lock").take() else {
+        return Ok(());
+    };
+    if captured.data.is_empty() {
+        println!("No output to write to {}", path.display());
+        return Ok(());
+    }
+    let tmp_path = path.with_extension(format!("tmp.{}", std::process::id()));
+    let written = fs::File::create(&tmp_path).and_then(|mut file| {
+        file.write_all(&captured.data)?;
+        file.sync_all()
+    });
+    if let Err(err) = written.and_then(|_| fs::rename(&tmp_path, path)) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(Error::from(err).context(format!("writing {}", path.display())));
+    }
+    println!("Wrote {} to {}", captured.summary, path.display());
+    Ok(())
+}
+
+/// The quiet form of output with transaction results: the signature or
+/// partially signed transaction of every result, one per line. Failed
+/// results are printed to stderr as they are found. Returns None for output
+/// without transaction results.
+fn quiet_lines(json: &serde_json::Value) -> Option<String> {
+    fn collect(json: &serde_json::Value, lines: &mut Vec<String>) -> bool {
+        match json {
+            serde_json::Value::Object(map) => {
+                let field = |name| map.get(name).and_then(|value| value.as_str());
+                match field("result") {
+                    Some("error") => {
+                        eprintln!("{json}");
+                        true
+                    }
+                    Some(_) => {
+                        if let Some(line) = field("txid").or_else(|| field("transaction")) {
+                            lines.push(line.to_string());
+                        }
+                        true
+                    }
+                    None => map
+                        .values()
+                        .fold(false, |found, value| collect(value, lines) || found),
+                }
+            }
+            serde_json::Value::Array(values) => values
+                .iter()
+                .fold(false, |found, value| collect(value, lines) || found),
+            _ => false,
+        }
+    }
+
+    let mut lines = vec![];
+    collect(json, &mut lines).then(|| {
+        lines
+            .iter()
+            .map(|line| format!("{line}\n"))
+            .collect::<String>()
+    })
+}
+
+fn json_summary(json: &serde_json::Value) -> String {
+    match json {
+        serde_json::Value::Array(entries) => format!("{} entries", entries.len()),
+        serde_json::Value::Object(map) => {
+            let field = |name| map.get(name).and_then(|value| value.as_str());
+            match (field("result"), field("txid")) {
+                (Some(result), Some(txid)) => format!("result {result} ({txid})"),
+                (Some(result), None) => format!("result {result}"),
+                _ => format!("{} fields", map.len()),
+            }
+        }
+        _ => "output".to_string(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Json,
+    Table,
+    /// Comma separated values with a header row, for spreadsheets
+    Csv,
+}
+
+pub fn print_output<T: ?Sized + serde::Serialize>(value: &T, format: OutputFormat) -> Result {
+    match format {
+        OutputFormat::Json => print_json(value),
+        OutputFormat::Table | OutputFormat::Csv => print_rows(&field_table(value)?, format),
+    }
+}
+
+/// Print a value as a two column table of field names and values. Nested
+/// fields are flattened into dotted field names.
+pub fn print_table<T: ?Sized + serde::Serialize>(value: &T) -> Result {
+    print_rows(&field_table(value)?, OutputFormat::Table)
+}
+
+/// Print a table, or its titles and rows as CSV for the csv output format.
+/// The titles of a table are its CSV column headers, so they should not
+/// change between releases.
+pub fn print_rows(table: &prettytable::Table, format: OutputFormat) -> Result {
+    let mut data = vec![];
+    if format == OutputFormat::Csv {
+        table.to_csv(&mut data)?.flush()?;
+    } else if console::colors_enabled() {
+        let mut table = table.clone();
+        for row in table.row_iter_mut() {
+            for cell in row.iter_mut() {
+                let content = cell.get_content();
+                if let Some(style) = status_style(&content) {
+                    *cell = prettytable::Cell::new(&style.apply_to(&content).to_string());
+                }
+            }
+        }
+        table.print(&mut data)?;
+    } else {
+        table.print(&mut data)?;
+    }
+    emit(&data, || format!("{} rows", table.len()))
+}
+
+fn field_table<T: ?Sized + serde::Serialize>(value: &T) -> Result<prettytable::Table> {
+    fn flatten(prefix: &str, value: &serde_json::Value, rows: &mut Vec<(String, String)>) {
+        let field = |key: &str| {
+            if prefix.is_empty() {
+                key.to_string()
+            } else {
+                format!("{prefix}.{key}")
+            }
+        };
+        match value {
+            serde_json::Value::Object(map) => map
+                .iter()
+                .for_each(|(key, value)| flatten(&field(key), value, rows)),
+            serde_json::Value::Array(values) => values
+                .iter()
+                .enumerate()
+                .for_each(|(index, value)| flatten(&field(&index.to_string()), value, rows)),
+            serde_json::Value::String(str) => rows.push((prefix.to_string(), str.clone())),
+            serde_json::Value::Null => (),
+            other => rows.push((prefix.to_string(), other.to_string())),
+        }
+    }
+
+    let mut rows = vec![];
+    flatten("", &serde_json::to_value(value)?, &mut rows);
+    let mut table = prettytable::Table::new();
+    table.set_format(*prettytable::format::consts::FORMAT_CLEAN);
+    table.set_titles(prettytable::row!["Field", "Value"]);
+    for (field, value) in rows {
+        table.add_row(prettytable::row![field, value]);
+    }
+    Ok(table)
+}
+
+/// Turn off colors when requested. Otherwise colors are used when the output
+/// goes to a terminal.
+pub fn init_color(no_color: bool) {
+    let no_color = no_color || env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    if no_color {
+        console::set_colors_enabled(false);
+        console::set_colors_enabled_stderr(false);
+    }
+}
+
+/// Style a status by outcome: green for success, yellow for results that
+/// still need something to happen and red for failures.
+pub fn style_status(status: &str) -> String {
+    match status_style(status) {
+        Some(style) => style.apply_to(status).to_string(),
+        None => status.to_string(),
+    }
+}
+
+fn status_style(status: &str) -> Option<console::Style> {
+    let style = console::Style::new();
+    match status {
+        "ok" | "confirmed" | "finalized" => Some(style.green()),
+        "processed" | "pending" | "partially_signed" | "skipped" => Some(style.yellow()),
+        "error" | "failed" => Some(style.red()),
+        _ => None,
+    }
+}
+
+/// Format a token amount for people: thousands separated, with as many
+/// decimals as needed and no more than the token has
+pub fn format_amount(amount: &TokenAmount) -> String {
+    let decimals = usize::from(amount.token.decimals());
+    let scale = 10_u64.pow(decimals as u32);
+    let (whole, fraction) = (amount.amount / scale, amount.amount % scale);
+    let digits = whole.to_string();
+    let mut whole = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index) % 3 == 0 {
+            whole.push(',');
+        }
+        whole.push(digit);
+    }
+    if fraction == 0 {
+        return whole;
+    }
+    let fraction = format!("{fraction:0decimals$}");
+    format!("{whole}.{}", fraction.trim_end_matches('0'))
+}
+
+/// A token amount as a table cell: formatted for people in tables and as an
+/// exact plain number in csv output
+pub fn amount_cell(amount: &TokenAmount, format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Csv => {
+            Decimal::from_i128_with_scale(amount.amount.into(), amount.token.decimals().into())
+                .normalize()
+                .to_string()
+        }
+        OutputFormat::Json | OutputFormat::Table => format_amount(amount),
+    }
+}
+
+/// Color the status values of pretty printed JSON
+fn colorize_json(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let value = line.trim_start();
+            let indent = &line[..line.len() - value.len()];
+            for key in ["\"result\": \"", "\"commitment\": \""] {
+                let Some(rest) = value.strip_prefix(key) else {
+                    continue;
+                };
+                if let Some((status, rest)) = rest.split_once('"') {
+                    return format!("{indent}{key}{}\"{rest}\n", style_status(status));
+                }
+            }
+            format!("{line}\n")
+        })
+        .collect()
+}
+
+/// How often progress is reported when stderr is not a terminal
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Progress of a bulk operation, counting the items that succeeded and
+/// failed. On a terminal this is a progress bar with an ETA. Otherwise a JSON
+/// progress line is printed to stderr every 10 seconds and when done.
+/// Nothing is shown with `--quiet`.
+pub struct Progress {
+    label: &'static str,
+    total: Option<u64>,
+    bar: Option<indicatif::ProgressBar>,
+    succeeded: AtomicU64,
+    failed: AtomicU64,
+    last_report: Mutex<Instant>,
+}
+
+impl Progress {
+    /// Start tracking progress for the given number of items, or an unknown
+    /// number of items if not given
+    pub fn new(label: &'static str, total: Option<u64>) -> Self {
+        let bar = (!is_quiet() && console::Term::stderr().is_term()).then(|| {
+            let (bar, template) = match total {
+                Some(total) => (
+                    indicatif::ProgressBar::new(total),
+                    "{prefix} [{bar:40}] {pos}/{len} ({eta}) {msg}",
+                ),
+                None => (
+                    indicatif::ProgressBar::new_spinner(),
+                    "{spinner} {prefix} {pos} {msg}",
+                ),
+            };
+            let style = indicatif::ProgressStyle::with_template(template)
+                .expect("progress template")
+                .progress_chars("=> ");
+            bar.set_style(style);
+            bar.set_prefix(label);
+            if total.is_none() {
+                bar.enable_steady_tick(Duration::from_millis(100));
+            }
+            bar
+        });
+        Self {
+            label,
+            total,
+            bar,
+            succeeded: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+            last_report: Mutex::new(Instant::now()),
+        }
+    }
+
+    pub fn success(&self) {
+        self.succeeded.fetch_add(1, Ordering::Relaxed);
+        self.update();
+    }
+
+    pub fn failure(&self) {
+        self.failed.fetch_add(1, Ordering::Relaxed);
+        self.update();
+    }
+
+    pub fn finish(&self) {
+        match &self.bar {
+            Some(bar) => bar.finish_and_clear(),
+            None => self.report(),
+        }
+    }
+
+    fn counts(&self) -> (u64, u64) {
+        (
+            self.succeeded.load(Ordering::Relaxed),
+            self.failed.load(Ordering::Relaxed),
+        )
+    }
+
+    fn update(&self) {
+        let (succeeded, failed) = self.counts();
+        match &self.bar {
+            Some(bar) => {
+                bar.set_position(succeeded + failed);
+                bar.set_message(format!("{succeeded} ok, {failed} failed"));
+            }
+            None => {
+                let mut last_report = self.last_report.lock().expect("progress lock");
+                if last_report.elapsed() >= PROGRESS_INTERVAL {
+                    *last_report = Instant::now();
+                    self.report();
+                }
+            }
+        }
+    }
+
+    fn report(&self) {
+        if is_quiet() {
+            return;
+        }
+        let (succeeded, failed) = self.counts();
+        eprintln!(
+            "{}",
+            serde_json::json!({
+                "progress": self.label,
+                "done": succeeded + failed,
+                "total": self.total,
+                "succeeded": succeeded,
+                "failed": failed,
+            })
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use helium_lib::token::Token;
+
+    #[test]
+    fn amounts() {
+        assert_eq!("0", format_amount(&Token::Hnt.amount(0)));
+        assert_eq!(
+            "1,234.5",
+            format_amount(&Token::Hnt.amount(123_450_000_000))
+        );
+        assert_eq!("0.000001", format_amount(&Token::Iot.amount(1)));
+        assert_eq!("100,000", format_amount(&Token::Dc.amount(100_000)));
+        assert_eq!(
+            "1234.5",
+            amount_cell(&Token::Hnt.amount(123_450_000_000), OutputFormat::Csv)
+        );
+        assert_eq!(
+            "184467440737.09551615",
+            amount_cell(&Token::Hnt.amount(u64::MAX), OutputFormat::Csv)
+        );
+        assert_eq!("0", amount_cell(&Token::Sol.amount(0), OutputFormat::Csv));
+    }
+}
@@ -0,0 +1,180 @@
+use crate::cmd::*;
+use chrono::Utc;
+use futures::StreamExt;
+use helium_lib::{
+    dao::SubDao,
+    entity_key::{EncodedEntityKey, EntityKeyEncoding, KeySerialization},
+    hotspot,
+    keypair::{Keypair, Signer},
+    reward::{self, ClaimOutcome, ClaimableToken},
+    token::{Token, TokenAmount},
+};
+use std::time::Duration;
+
+#[derive(Debug, clap::Args)]
+/// Periodically claim pending rewards for Hotspots
+///
+/// Runs until interrupted. Every interval the pending rewards of the given
+/// Hotspots, or all Hotspots owned by the wallet, are looked up and each
+/// Hotspot with at least the minimum amount pending is claimed. A JSON summary
+/// of each round is written to stdout as a single line and, if a webhook is
+/// given, posted to it.
+///
+/// Claims are only submitted with --commit. Without it each round simulates
+/// the claims it would make.
+pub struct Cmd {
+    /// Hotspot(s) to claim rewards for
+    ///
+    /// Defaults to all Hotspots owned by the wallet at the start of each
+    /// round.
+    #[arg(long = "hotspot", number_of_values(1))]
+    hotspots: Vec<helium_crypto::PublicKey>,
+    /// Subdao(s) to claim rewards for
+    ///
+    /// Defaults to both IOT and MOBILE.
+    #[arg(long, value_enum, number_of_values(1))]
+    subdao: Vec<SubDao>,
+    /// Time between claim rounds, e.g. "12h" or "1d"
+    #[arg(long, default_value = "24h", value_parser = humantime::parse_duration)]
+    interval: Duration,
+    /// Minimum pending amount, in tokens, for a Hotspot to be claimed
+    #[arg(long, visible_alias = "min", default_value_t = 0.0)]
+    min_amount: f64,
+    /// Post the summary of each claim round to a webhook
+    #[command(flatten)]
+    webhook: WebhookOpts,
+    #[command(flatten)]
+    metrics: MetricsOpts,
+    /// Commit the claim transactions.
+    #[command(flatten)]
+    commit: CommitOpts,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let password = get_wallet_password(false)?;
+        let keypair = opts.load_keypair(password.as_bytes())?;
+        let client = opts.client()?;
+        let subdaos = if self.subdao.is_empty() {
+            SubDao::all().to_vec()
+        } else {
+            self.subdao.clone()
+        };
+        let webhook = self.webhook.webhook()?;
+        self.metrics.serve().await?;
+
+        let mut interval = tokio::time::interval(self.interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => return Ok(()),
+                _ = interval.tick() => (),
+            }
+            let summary = match self.claim_round(&client, &keypair, &subdaos).await {
+                Ok(claims) => json!({
+                    "timestamp": Utc::now(),
+                    "event": "claim_round",
+                    "claims": claims,
+                }),
+                // A failed round is reported and retried at the next interval
+                Err(err) => json!({
+                    "timestamp": Utc::now(),
+                    "event": "claim_round_failed",
+                    "error": err.to_string(),
+                    "code": ErrorCode::of(&err),
+                }),
+            };
+            println!("{}", serde_json::to_string(&summary)?);
+            if let Some(webhook) = &webhook {
+                webhook.notify(&summary).await;
+            }
+        }
+    }
+
+    async fn claim_round(
+        &self,
+        client: &client::Client,
+        keypair: &Keypair,
+        subdaos: &[SubDao],
+    ) -> Result<Vec<serde_json::Value>> {
+        let hotspots: Vec<String> = if self.hotspots.is_empty() {
+            hotspot::for_owner(client, &keypair.pubkey())
+                .await?
+                .into_iter()
+                .map(|hotspot| hotspot.key.to_string())
+                .collect()
+        } else {
+            self.hotspots.iter().map(ToString::to_string).collect()
+        };
+        let transaction_opts = self.commit.transaction_opts(client)?;
+
+        let mut claims = vec![];
+        for subdao in subdaos {
+            let token = ClaimableToken::from(*subdao);
+            let min_amount = TokenAmount::from_f64(Token::from(token), self.min_amount);
+            let pending =
+                reward::pending_many(client, token, &hotspots, KeySerialization::B58).await?;
+            let entity_keys = pending
+                .iter()
+                .filter(|(_, pending)| pending.reward.amount >= min_amount.amount)
+                .map(|(hotspot, _)| EncodedEntityKey {
+                    entity_key: hotspot.clone(),
+                    encoding: EntityKeyEncoding::B58,
+                })
+                .collect();
+            let mut results = std::pin::pin!(reward::claim_many(
+                client,
+                token,
+                entity_keys,
+                keypair,
+                &transaction_opts,
+                Default::default(),
+            ));
+            while let Some(result) = results.next().await {
+                let outcomes: Vec<(TokenAmount, serde_json::Value)> = match result.outcome {
+                    ClaimOutcome::Ready { txn, amounts, .. } => {
+                        let result = self.commit.maybe_commit(txn, client).await;
+                        if let Ok(CommitResponse::Transaction(_)) = result {
+                            for amount in &amounts {
+                                metrics::reward_claimed(amount.token, Some(amount.amount));
+                            }
+                        }
+                        let claim = result.to_json();
+                        amounts
+                            .into_iter()
+                            .map(|amount| (amount, claim.clone()))
+                            .collect()
+                    }
+                    ClaimOutcome::Skipped => result
+                        .entity_keys
+                        .iter()
+                        .map(|_| (Token::from(token).amount(0), json!({"result": "skipped"})))
+                        .collect(),
+                    ClaimOutcome::Failed(err) => {
+                        let claim = json!({
+                            "result": "error",
+                            "error": err.to_string(),
+                            "code": ErrorCode::of_lib(&err),
+                        });
+                        result
+                            .entity_keys
+                            .iter()
+                            .map(|entity_key| {
+                                (pending[&entity_key.to_string()].reward, claim.clone())
+                            })
+                            .collect()
+                    }
+                };
+                for (entity_key, (amount, claim)) in result.entity_keys.iter().zip(outcomes) {
+                    claims.push(json!({
+                        "hotspot": entity_key.to_string(),
+                        "subdao": subdao,
+                        "amount": amount,
+                        "claim": claim,
+                    }));
+                }
+            }
+        }
+        Ok(claims)
+    }
+}
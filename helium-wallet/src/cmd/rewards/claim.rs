@@ -0,0 +1,426 @@
+use super::FeeEstimate;
+use crate::cmd::*;
+use futures::{stream, StreamExt};
+use helium_lib::{
+    dao::SubDao,
+    entity_key::{EncodedEntityKey, EntityKeyEncoding, KeySerialization},
+    hotspot,
+    keypair::{Keypair, Pubkey, Signer},
+    kta,
+    reward::{self, ClaimOutcome, ClaimableToken},
+    token::{Token, TokenAmount},
+};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, clap::Args)]
+/// Claim pending rewards for one or more Hotspots
+///
+/// The current rewards for each Hotspot are fetched from the rewards oracles
+/// and distributed by the lazy distributor. Claims are packed into as few
+/// transactions as fit within transaction size and compute limits, with a
+/// bounded number of claims prepared at a time. Hotspots without pending
+/// rewards, or with less pending than the given minimum, are skipped. Claims
+/// for multiple subdaos are interleaved and totalled per subdao. Each claim
+/// reports its estimated fee as a fraction of the value of the claimed
+/// rewards when token prices are available.
+///
+/// With --destination, each Hotspot's rewards destination is first updated to
+/// the given wallet where it differs, so that this and all future claims land
+/// in that wallet instead of the Hotspot owner's. Hotspots whose destination
+/// could not be updated are not claimed.
+pub struct Cmd {
+    /// Hotspot(s) to claim rewards for
+    #[arg(
+        long = "hotspot",
+        number_of_values(1),
+        required_unless_present = "all",
+        conflicts_with = "all"
+    )]
+    hotspots: Vec<helium_crypto::PublicKey>,
+    /// Claim rewards for all Hotspots owned by the wallet
+    #[arg(long)]
+    all: bool,
+    /// Subdao(s) to claim rewards for
+    ///
+    /// Defaults to both IOT and MOBILE.
+    #[arg(long, value_enum, number_of_values(1))]
+    subdao: Vec<SubDao>,
+    /// Minimum pending amount, in tokens, for a Hotspot to be claimed
+    ///
+    /// Use this to skip Hotspots whose transaction fees would exceed the value
+    /// of their rewards.
+    #[arg(long, default_value_t = 0.0)]
+    min: f64,
+    /// Wallet to distribute rewards to instead of the Hotspot owner
+    #[arg(long)]
+    destination: Option<Pubkey>,
+    /// Maximum number of claims to prepare and submit concurrently
+    #[arg(long, default_value_t = 5)]
+    concurrency: usize,
+    /// Output format
+    #[arg(long, value_enum, default_value_t)]
+    format: OutputFormat,
+    /// Commit the claim transactions.
+    #[command(flatten)]
+    commit: CommitOpts,
+}
+
+#[derive(Debug, Serialize)]
+struct ClaimSummary {
+    hotspot: String,
+    subdao: SubDao,
+    amount: TokenAmount,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fee_ratio: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    destination: Option<serde_json::Value>,
+    claim: serde_json::Value,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let password = get_wallet_password(false)?;
+        let keypair = opts.load_keypair(password.as_bytes())?;
+        let client = opts.client()?;
+        let transaction_opts = self.commit.transaction_opts(&client)?;
+        let subdaos = if self.subdao.is_empty() {
+            SubDao::all().to_vec()
+        } else {
+            self.subdao.clone()
+        };
+
+        let hotspots: Vec<String> = if self.all {
+            hotspot::for_owner(&client, &keypair.pubkey())
+                .await?
+                .into_iter()
+                .map(|hotspot| hotspot.key.to_string())
+                .collect()
+        } else {
+            self.hotspots.iter().map(ToString::to_string).collect()
+        };
+
+        // Look up pending rewards up front so Hotspots without rewards are
+        // skipped without building a transaction for them
+        let mut pending: HashMap<(SubDao, String), TokenAmount> = HashMap::new();
+        for subdao in &subdaos {
+            let token = ClaimableToken::from(*subdao);
+            pending.extend(
+                reward::pending_many(&client, token, &hotspots, KeySerialization::B58)
+                    .await?
+                    .into_iter()
+                    .map(|(hotspot, reward)| ((*subdao, hotspot), reward.reward)),
+            );
+        }
+        let mut fees: HashMap<SubDao, FeeEstimate> = HashMap::new();
+        for subdao in &subdaos {
+            let estimate =
+                FeeEstimate::new(&client, ClaimableToken::from(*subdao), &transaction_opts).await?;
+            fees.insert(*subdao, estimate);
+        }
+        let fee_ratio = |subdao: &SubDao, amount: &TokenAmount| {
+            fees.get(subdao).and_then(|fees| fees.ratio(amount))
+        };
+
+        let mut skipped = vec![];
+        let mut claimable: Vec<(SubDao, String)> = vec![];
+        for subdao in &subdaos {
+            let min = TokenAmount::from_f64(ClaimableToken::from(*subdao).into(), self.min);
+            for hotspot in &hotspots {
+                let key = (*subdao, hotspot.clone());
+                match pending.get(&key) {
+                    Some(amount) if amount.amount < min.amount => skipped.push(ClaimSummary {
+                        hotspot: hotspot.clone(),
+                        subdao: *subdao,
+                        amount: *amount,
+                        fee_ratio: fee_ratio(subdao, amount),
+                        destination: None,
+                        claim: json!({"result": "skipped", "reason": "below minimum"}),
+                    }),
+                    Some(_) => claimable.push(key),
+                    None => (),
+                }
+            }
+        }
+
+        let mut destinations: HashMap<(SubDao, String), serde_json::Value> = match self.destination
+        {
+            Some(destination) => {
+                stream::iter(&claimable)
+                    .map(|(subdao, hotspot)| {
+                        let client = &client;
+                        let keypair = &keypair;
+                        let transaction_opts = &transaction_opts;
+                        async move {
+                            let result = self
+                                .update_destination(
+                                    client,
+                                    ClaimableToken::from(*subdao),
+                                    &EncodedEntityKey {
+                                        entity_key: hotspot.clone(),
+                                        encoding: EntityKeyEncoding::B58,
+                                    },
+                                    &destination,
+                                    keypair,
+                                    transaction_opts,
+                                )
+                                .await
+                                .unwrap_or_else(|err| {
+                                    json!({
+                                        "result": "error",
+                                        "error": err.to_string(),
+                                        "code": ErrorCode::of(&err),
+                                    })
+                                });
+                            ((*subdao, hotspot.clone()), result)
+                        }
+                    })
+                    .buffer_unordered(self.concurrency.max(1))
+                    .collect()
+                    .await
+            }
+            None => HashMap::new(),
+        };
+        // Claiming a Hotspot whose destination could not be updated would pay
+        // the old destination
+        claimable.retain(|key| {
+            let Some(destination) = destinations.get(key) else {
+                return true;
+            };
+            if destination["result"] != "error" {
+                return true;
+            }
+            skipped.push(ClaimSummary {
+                hotspot: key.1.clone(),
+                subdao: key.0,
+                amount: pending[key],
+                fee_ratio: fee_ratio(&key.0, &pending[key]),
+                destination: destinations.remove(key),
+                claim: json!({"result": "skipped", "reason": "destination update failed"}),
+            });
+            false
+        });
+
+        // Share the concurrency budget between the subdaos being claimed
+        let claim_opts = reward::ClaimManyOpts {
+            concurrency: (self.concurrency / subdaos.len()).max(1),
+            ..Default::default()
+        };
+        let claim_streams = subdaos.iter().map(|subdao| {
+            let subdao = *subdao;
+            let entity_keys = claimable
+                .iter()
+                .filter(|(claim_subdao, _)| *claim_subdao == subdao)
+                .map(|(_, hotspot)| EncodedEntityKey {
+                    entity_key: hotspot.clone(),
+                    encoding: EntityKeyEncoding::B58,
+                })
+                .collect();
+            Box::pin(
+                reward::claim_many(
+                    &client,
+                    ClaimableToken::from(subdao),
+                    entity_keys,
+                    &keypair,
+                    &transaction_opts,
+                    claim_opts,
+                )
+                .map(move |result| (subdao, result)),
+            )
+        });
+        let progress = Progress::new("claims", Some(claimable.len() as u64));
+        let mut claims: Vec<ClaimSummary> = stream::select_all(claim_streams)
+            .then(|(subdao, result)| {
+                let client = &client;
+                let progress = &progress;
+                // The hotspots claimed together, with their destination update
+                // and pending amount
+                let hotspots: Vec<_> = result
+                    .entity_keys
+                    .iter()
+                    .map(|entity_key| {
+                        let key = (subdao, entity_key.to_string());
+                        let destination = destinations.remove(&key);
+                        let amount = pending
+                            .get(&key)
+                            .copied()
+                            .unwrap_or_else(|| Token::from(ClaimableToken::from(subdao)).amount(0));
+                        (key.1, destination, amount)
+                    })
+                    .collect();
+                async move {
+                    let (claim, claimed) = match result.outcome {
+                        ClaimOutcome::Ready { txn, amounts, .. } => (
+                            self.commit.maybe_commit(txn, client).await.to_json(),
+                            Some(amounts),
+                        ),
+                        ClaimOutcome::Skipped => (json!({"result": "skipped"}), None),
+                        ClaimOutcome::Failed(err) => (
+                            json!({
+                                "result": "error",
+                                "error": err.to_string(),
+                                "code": ErrorCode::of_lib(&err),
+                            }),
+                            None,
+                        ),
+                    };
+                    let summaries: Vec<ClaimSummary> = hotspots
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, (hotspot, destination, pending))| {
+                            if claim["result"] == "error" {
+                                progress.failure();
+                            } else {
+                                progress.success();
+                            }
+                            let amount = claimed
+                                .as_ref()
+                                .and_then(|amounts| amounts.get(index).copied())
+                                .unwrap_or(pending);
+                            ClaimSummary {
+                                hotspot,
+                                subdao,
+                                fee_ratio: fee_ratio(&subdao, &amount),
+                                amount,
+                                destination,
+                                claim: claim.clone(),
+                            }
+                        })
+                        .collect();
+                    stream::iter(summaries)
+                }
+            })
+            .flatten()
+            .collect()
+            .await;
+        progress.finish();
+
+        // Only successful claims count in the claimed totals. Skipped and
+        // failed Hotspots are reported but not counted.
+        let mut totals: BTreeMap<Token, TokenAmount> = subdaos
+            .iter()
+            .map(|subdao| {
+                let token = Token::from(ClaimableToken::from(*subdao));
+                (token, token.amount(0))
+            })
+            .collect();
+        let mut failed = 0;
+        for claim in &claims {
+            match claim.claim["result"].as_str() {
+                Some("error") => failed += 1,
+                Some("skipped") => (),
+                _ => {
+                    if let Some(total) = totals.get_mut(&claim.amount.token) {
+                        total.amount += claim.amount.amount;
+                    }
+                }
+            }
+        }
+        claims.extend(skipped);
+
+        match self.format {
+            OutputFormat::Json => print_json(&json!({
+                "claims": claims,
+                "total": totals.values().collect::<Vec<_>>(),
+                "failed": failed,
+            })),
+            OutputFormat::Table | OutputFormat::Csv => {
+                let mut table = prettytable::Table::new();
+                table.set_format(*prettytable::format::consts::FORMAT_CLEAN);
+                table.set_titles(prettytable::row![
+                    "Hotspot",
+                    "Subdao",
+                    "Amount",
+                    "Fee Ratio",
+                    "Result"
+                ]);
+                for claim in &claims {
+                    let result = claim
+                        .claim
+                        .get("txid")
+                        .or_else(|| claim.claim.get("error"))
+                        .or_else(|| claim.claim.get("result"))
+                        .and_then(|value| value.as_str())
+                        .unwrap_or_default();
+                    table.add_row(prettytable::row![
+                        claim.hotspot,
+                        claim.subdao,
+                        amount_cell(&claim.amount, self.format),
+                        claim
+                            .fee_ratio
+                            .map(|ratio| format!("{ratio:.4}"))
+                            .unwrap_or_default(),
+                        result
+                    ]);
+                }
+                if self.format == OutputFormat::Table {
+                    for (token, total) in &totals {
+                        table.add_row(prettytable::row![
+                            "Total",
+                            token,
+                            amount_cell(total, self.format),
+                            "",
+                            ""
+                        ]);
+                    }
+                    if failed > 0 {
+                        table.add_row(prettytable::row!["Failed", "", failed, "", ""]);
+                    }
+                }
+                print_rows(&table, self.format)
+            }
+        }
+    }
+}
+
+impl Cmd {
+    /// Point the recipient of the given Hotspot at the destination wallet,
+    /// initializing the recipient first if the Hotspot has never been claimed
+    /// for. Each step is confirmed before the next is sent.
+    async fn update_destination(
+        &self,
+        client: &client::Client,
+        token: ClaimableToken,
+        entity_key: &EncodedEntityKey,
+        destination: &Pubkey,
+        keypair: &Keypair,
+        transaction_opts: &TransactionOpts,
+    ) -> Result<serde_json::Value> {
+        let entity_key = entity_key.as_entity_key()?;
+        let kta = kta::for_entity_key(&entity_key).await?;
+        let mut result = json!({});
+        match reward::recipient::for_kta(client, token, &kta).await? {
+            Some(recipient) if recipient.destination == *destination => {
+                result["result"] = "unchanged".into();
+                return Ok(result);
+            }
+            Some(_) => (),
+            None => {
+                let (tx, _) =
+                    reward::recipient::init(client, token, &entity_key, keypair, transaction_opts)
+                        .await?;
+                result["init"] = self
+                    .commit
+                    .maybe_commit_confirmed(tx, client)
+                    .await?
+                    .to_json();
+            }
+        }
+        let (tx, _) = reward::recipient::update_destination(
+            client,
+            token,
+            &entity_key,
+            destination,
+            keypair,
+            transaction_opts,
+        )
+        .await?;
+        // The claim that follows must not land before the new destination
+        result["update"] = self
+            .commit
+            .maybe_commit_confirmed(tx, client)
+            .await?
+            .to_json();
+        Ok(result)
+    }
+}
@@ -0,0 +1,78 @@
+use crate::cmd::*;
+use chrono::NaiveDate;
+use helium_lib::{
+    dao::SubDao,
+    reward::{self, ClaimableToken},
+    token::Token,
+};
+
+#[derive(Debug, clap::Args)]
+/// List rewards claimed for a Hotspot
+///
+/// Claims are decoded from the past reward distribution transactions of the
+/// Hotspot's recipient account, newest first, giving a dated ledger of the
+/// amounts distributed and the wallet they were distributed to.
+pub struct Cmd {
+    /// Key of the Hotspot to list claimed rewards for
+    hotspot: helium_crypto::PublicKey,
+    /// Subdao to list claimed rewards for
+    #[arg(long, value_enum)]
+    subdao: SubDao,
+    /// Only include claims made on or after this date (YYYY-MM-DD, UTC)
+    #[arg(long)]
+    from: Option<NaiveDate>,
+    /// Output format
+    #[arg(long, value_enum, default_value_t)]
+    format: OutputFormat,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let client = opts.client()?;
+        let token = ClaimableToken::from(self.subdao);
+        let since = self
+            .from
+            .and_then(|from| from.and_hms_opt(0, 0, 0))
+            .map(|from| from.and_utc());
+        let claims = reward::history(&client, token, &self.hotspot, since).await?;
+        let total = Token::from(token).amount(claims.iter().map(|claim| claim.amount.amount).sum());
+
+        match self.format {
+            OutputFormat::Json => print_json(&json!({
+                "hotspot": self.hotspot.to_string(),
+                "claims": claims,
+                "total": total,
+            })),
+            OutputFormat::Table | OutputFormat::Csv => {
+                let mut table = prettytable::Table::new();
+                table.set_format(*prettytable::format::consts::FORMAT_CLEAN);
+                table.set_titles(prettytable::row![
+                    "Date",
+                    "Amount",
+                    "Destination",
+                    "Signature"
+                ]);
+                for claim in &claims {
+                    table.add_row(prettytable::row![
+                        claim.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                        amount_cell(&claim.amount, self.format),
+                        claim
+                            .destination
+                            .map(|destination| destination.to_string())
+                            .unwrap_or_default(),
+                        claim.signature
+                    ]);
+                }
+                if self.format == OutputFormat::Table {
+                    table.add_row(prettytable::row![
+                        "Total",
+                        amount_cell(&total, self.format),
+                        "",
+                        ""
+                    ]);
+                }
+                print_rows(&table, self.format)
+            }
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use crate::cmd::*;
+use helium_lib::{
+    reward::{self, ClaimableToken},
+    token::{self, Token, TokenAmount},
+};
+use rust_decimal::prelude::ToPrimitive;
+
+pub mod autoclaim;
+pub mod claim;
+pub mod history;
+pub mod pending;
+
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    #[command(subcommand)]
+    cmd: RewardsCommand,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        self.cmd.run(opts).await
+    }
+}
+
+/// Commands on Hotspot rewards
+#[derive(Debug, clap::Subcommand)]
+pub enum RewardsCommand {
+    Autoclaim(autoclaim::Cmd),
+    Claim(claim::Cmd),
+    History(history::Cmd),
+    Pending(pending::Cmd),
+}
+
+impl RewardsCommand {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Self::Autoclaim(cmd) => cmd.run(opts).await,
+            Self::Claim(cmd) => cmd.run(opts).await,
+            Self::History(cmd) => cmd.run(opts).await,
+            Self::Pending(cmd) => cmd.run(opts).await,
+        }
+    }
+}
+
+/// The estimated fee of a claim transaction along with the prices needed to
+/// compare it to the value of the claimed rewards
+#[derive(Debug, Clone)]
+pub struct FeeEstimate {
+    pub fee: TokenAmount,
+    sol_price: Option<f64>,
+    token_price: Option<f64>,
+}
+
+impl FeeEstimate {
+    pub async fn new(
+        client: &client::Client,
+        token: ClaimableToken,
+        transaction_opts: &TransactionOpts,
+    ) -> Result<Self> {
+        let fee = reward::claim_fee(client, token, transaction_opts).await?;
+        // Prices are only used to report the fee ratio, so an unavailable
+        // price leaves the ratio out rather than failing the claim
+        let price = |token| async move {
+            token::price::get(client, token)
+                .await
+                .ok()
+                .and_then(|price| price.price.to_f64())
+        };
+        Ok(Self {
+            fee,
+            sol_price: price(Token::Sol).await,
+            token_price: price(token.into()).await,
+        })
+    }
+
+    /// The estimated fee as a fraction of the value of the given reward
+    pub fn ratio(&self, reward: &TokenAmount) -> Option<f64> {
+        let fee_value = f64::from(&self.fee) * self.sol_price?;
+        let reward_value = f64::from(reward) * self.token_price?;
+        (reward_value > 0.0).then(|| fee_value / reward_value)
+    }
+}
@@ -0,0 +1,117 @@
+use crate::cmd::*;
+use helium_lib::{
+    dao::SubDao,
+    entity_key::KeySerialization,
+    hotspot,
+    keypair::Pubkey,
+    reward::{self, ClaimableToken},
+    token::{Token, TokenAmount},
+};
+use std::collections::BTreeMap;
+
+#[derive(Debug, clap::Args)]
+/// List unclaimed rewards for Hotspots and their total
+///
+/// Pending rewards are the rewards reported by the rewards oracles less what
+/// has already been claimed to each Hotspot's recipient account. Nothing is
+/// signed or submitted.
+pub struct Cmd {
+    /// Hotspot(s) to list pending rewards for
+    ///
+    /// Defaults to all Hotspots owned by the given or active wallet.
+    #[arg(long = "hotspot", number_of_values(1))]
+    hotspots: Vec<helium_crypto::PublicKey>,
+    /// Wallet to look up Hotspots for
+    #[arg(long, conflicts_with = "hotspots")]
+    owner: Option<Pubkey>,
+    /// Only list rewards for the given subdao(s)
+    ///
+    /// Defaults to IOT, MOBILE and HNT rewards.
+    #[arg(long, value_enum, number_of_values(1))]
+    subdao: Vec<SubDao>,
+    /// Output format
+    #[arg(long, value_enum, default_value_t)]
+    format: OutputFormat,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let client = opts.client()?;
+        let hotspots: Vec<String> = if self.hotspots.is_empty() {
+            let owner = match self.owner {
+                Some(owner) => owner,
+                None => opts.load_wallet()?.public_key,
+            };
+            hotspot::for_owner(&client, &owner)
+                .await?
+                .into_iter()
+                .map(|hotspot| hotspot.key.to_string())
+                .collect()
+        } else {
+            self.hotspots.iter().map(ToString::to_string).collect()
+        };
+        let tokens: Vec<ClaimableToken> = if self.subdao.is_empty() {
+            vec![
+                ClaimableToken::Iot,
+                ClaimableToken::Mobile,
+                ClaimableToken::Hnt,
+            ]
+        } else {
+            self.subdao
+                .iter()
+                .copied()
+                .map(ClaimableToken::from)
+                .collect()
+        };
+
+        let mut pending: BTreeMap<String, BTreeMap<Token, TokenAmount>> = BTreeMap::new();
+        let mut total: BTreeMap<Token, TokenAmount> = BTreeMap::new();
+        for token in &tokens {
+            let rewards =
+                reward::pending_many(&client, *token, &hotspots, KeySerialization::B58).await?;
+            total.insert((*token).into(), Token::from(*token).amount(0));
+            for (hotspot, reward) in rewards {
+                if let Some(token_total) = total.get_mut(&reward.reward.token) {
+                    token_total.amount += reward.reward.amount;
+                }
+                pending
+                    .entry(hotspot)
+                    .or_default()
+                    .insert(reward.reward.token, reward.reward);
+            }
+        }
+
+        match self.format {
+            OutputFormat::Json => print_json(&json!({
+                "hotspots": pending,
+                "total": total.values().collect::<Vec<_>>(),
+            })),
+            OutputFormat::Table | OutputFormat::Csv => {
+                let mut table = prettytable::Table::new();
+                table.set_format(*prettytable::format::consts::FORMAT_CLEAN);
+                let mut titles = prettytable::Row::empty();
+                titles.add_cell(prettytable::Cell::new("Hotspot"));
+                for token in total.keys() {
+                    titles.add_cell(prettytable::Cell::new(&token.to_string()));
+                }
+                table.set_titles(titles);
+                let mut add_row = |name: &str, amounts: &BTreeMap<Token, TokenAmount>| {
+                    let mut row = prettytable::Row::empty();
+                    row.add_cell(prettytable::Cell::new(name));
+                    for token in total.keys() {
+                        let amount = amounts.get(token).copied().unwrap_or(token.amount(0));
+                        row.add_cell(prettytable::Cell::new(&amount_cell(&amount, self.format)));
+                    }
+                    table.add_row(row);
+                };
+                for (hotspot, amounts) in &pending {
+                    add_row(hotspot, amounts);
+                }
+                if self.format == OutputFormat::Table {
+                    add_row("Total", &total);
+                }
+                print_rows(&table, self.format)
+            }
+        }
+    }
+}
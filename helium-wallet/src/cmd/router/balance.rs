@@ -1,29 +1,121 @@
 use crate::cmd::*;
-use helium_lib::{dao::SubDao, token};
+use chrono::Utc;
+use futures::StreamExt;
+use helium_lib::{
+    dao::SubDao,
+    dc,
+    solana_account_decoder::UiAccountEncoding,
+    solana_client::{nonblocking::pubsub_client::PubsubClient, rpc_config::RpcAccountInfoConfig},
+    solana_sdk::account::Account,
+};
 
 #[derive(Debug, clap::Args)]
 /// Get the Delegated DC balance for a given router key. The balance is in Data
 /// Credits (DC)
+///
+/// With --watch the balance is followed over the RPC node's websocket endpoint
+/// and a JSON line is printed for every change until interrupted. With
+/// --alert-below an alert is printed, and posted to the webhook if given,
+/// whenever the balance crosses the threshold in either direction.
 pub struct Cmd {
     #[arg(value_enum)]
     pub subdao: SubDao,
     pub router_key: String,
+    /// Keep watching the balance for changes
+    #[arg(long)]
+    pub watch: bool,
+    /// Alert when the balance drops below this number of DC
+    #[arg(long, requires = "watch")]
+    pub alert_below: Option<u64>,
+    /// URL to post balance alerts to
+    #[arg(long, requires = "alert_below")]
+    pub webhook: Option<String>,
+    /// Websocket url of the RPC node. Defaults to the websocket endpoint of
+    /// the RPC url in use.
+    #[arg(long, requires = "watch")]
+    pub ws_url: Option<String>,
 }
 
 impl Cmd {
     pub async fn run(&self, opts: Opts) -> Result {
-        let delegated_dc_key = self.subdao.delegated_dc_key(&self.router_key);
-        let escrow_key = self.subdao.escrow_key(&delegated_dc_key);
         let client = opts.client()?;
-        let balance = token::balance_for_address(&client, &escrow_key)
-            .await?
-            .map(|balance| balance.amount);
-        let json = json!({
+        let delegated = dc::delegated_balance(&client, self.subdao, &self.router_key).await?;
+        if !self.watch {
+            return print_json(&delegated);
+        }
+        println!("{}", serde_json::to_string(&delegated)?);
+        let escrow = delegated.escrow;
+        let balance = delegated.balance.amount;
+
+        let ws_url = self
+            .ws_url
+            .clone()
+            .unwrap_or_else(|| client::websocket_url(&client.solana_client.url()));
+        let pubsub = PubsubClient::new(&ws_url).await?;
+        let (mut updates, unsubscribe) = pubsub
+            .account_subscribe(
+                &escrow.escrow_key,
+                Some(RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    commitment: Some(client.solana_client.commitment()),
+                    ..Default::default()
+                }),
+            )
+            .await?;
+
+        let webhook = self.webhook.as_deref().map(Webhook::new).transpose()?;
+        let mut below = self.alert_below.map(|threshold| balance < threshold);
+        if below == Some(true) {
+            self.alert(&webhook, balance).await?;
+        }
+        loop {
+            let update = tokio::select! {
+                _ = tokio::signal::ctrl_c() => break,
+                update = updates.next() => update,
+            };
+            let Some(update) = update else {
+                bail!("balance subscription closed");
+            };
+            let balance = escrow
+                .clone()
+                .balance(update.value.decode::<Account>())?
+                .balance
+                .amount;
+            println!(
+                "{}",
+                serde_json::to_string(&json!({
+                    "timestamp": Utc::now(),
+                    "slot": update.context.slot,
+                    "balance": balance,
+                }))?
+            );
+            if let Some(threshold) = self.alert_below {
+                let now_below = balance < threshold;
+                if below != Some(now_below) {
+                    below = Some(now_below);
+                    self.alert(&webhook, balance).await?;
+                }
+            }
+        }
+        unsubscribe().await;
+        Ok(())
+    }
+
+    /// Report that the balance crossed the alert threshold
+    async fn alert(&self, webhook: &Option<Webhook>, balance: u64) -> Result {
+        let threshold = self.alert_below.unwrap_or_default();
+        let alert = json!({
+            "timestamp": Utc::now(),
+            "alert": if balance < threshold { "below" } else { "above" },
             "router": self.router_key,
-            "delegated_dc_key": delegated_dc_key.to_string(),
-            "escrow_key": escrow_key.to_string(),
-            "balance": balance.unwrap_or_default(),
+            "subdao": self.subdao,
+            "threshold": threshold,
+            "balance": balance,
         });
-        print_json(&json)
+        println!("{}", serde_json::to_string(&alert)?);
+        if let Some(webhook) = webhook {
+            webhook.notify(&alert).await;
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,313 @@
+use crate::cmd::{balance::balances_json, transfer::Payee, *};
+use axum::{
+    extract::{Path as UrlPath, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use helium_lib::{
+    entity_key::{EncodedEntityKey, EntityKeyEncoding, KeySerialization},
+    hotspot,
+    keypair::{Keypair, Pubkey},
+    reward::{self, ClaimableToken},
+    token::{self, Token, TokenAmount},
+};
+use serde::Deserialize;
+use std::{collections::BTreeMap, net::SocketAddr};
+
+/// Environment variable the auth token for signing endpoints is read from
+/// when not given on the command line
+const AUTH_TOKEN_ENV: &str = "HELIUM_WALLET_SERVE_TOKEN";
+
+#[derive(Debug, clap::Args)]
+/// Serve wallet information and operations over a local HTTP JSON API
+///
+/// Read endpoints are always available:
+///
+///   GET  /balance/{address}
+///
+///   GET  /hotspots/{owner}
+///
+///   GET  /rewards/pending/{owner}[?subdao=iot|mobile|hnt]
+///
+///   GET  /metrics        Prometheus metrics
+///
+/// With --unlock the wallet is decrypted once at startup and the signing
+/// endpoints are enabled. These require an 'Authorization: Bearer <token>'
+/// header with the token given by --auth-token or the
+/// HELIUM_WALLET_SERVE_TOKEN environment variable:
+///
+///   POST /pay            [{"address": .., "amount": .., "token": ..}, ..]
+///
+///   POST /rewards/claim  {"hotspots": [..], "subdao": "iot|mobile|hnt"}
+///
+/// Transactions are only submitted when the server is started with
+/// --commit, and are simulated otherwise.
+pub struct Cmd {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    listen: SocketAddr,
+    /// Decrypt the wallet and enable the signing endpoints
+    #[arg(long)]
+    unlock: bool,
+    /// Token that signing requests must present as a bearer token
+    #[arg(long, requires = "unlock")]
+    auth_token: Option<String>,
+    /// Commit options for transactions built by signing endpoints
+    #[command(flatten)]
+    commit: CommitOpts,
+}
+
+#[derive(Clone)]
+struct ServerState {
+    client: client::Client,
+    signer: Option<Arc<ServerSigner>>,
+    commit: CommitOpts,
+}
+
+struct ServerSigner {
+    keypair: Arc<Keypair>,
+    auth_token: String,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let client = opts.client()?;
+        let signer = if self.unlock {
+            let auth_token = match &self.auth_token {
+                Some(auth_token) => auth_token.clone(),
+                None => env::var(AUTH_TOKEN_ENV).map_err(|_| {
+                    anyhow!("--unlock requires --auth-token or {AUTH_TOKEN_ENV} to be set")
+                })?,
+            };
+            if auth_token.is_empty() {
+                bail!("auth token must not be empty");
+            }
+            let password = get_wallet_password(false)?;
+            let keypair = opts.load_keypair(password.as_bytes())?;
+            Some(Arc::new(ServerSigner {
+                keypair,
+                auth_token,
+            }))
+        } else {
+            None
+        };
+        if signer.is_some() && !self.listen.ip().is_loopback() {
+            eprintln!(
+                "warning: signing endpoints are exposed on non-loopback address {}",
+                self.listen
+            );
+        }
+        metrics::init();
+        let state = ServerState {
+            client,
+            signer,
+            commit: self.commit.clone(),
+        };
+        let app = Router::new()
+            .route("/balance/:address", get(balance))
+            .route("/hotspots/:owner", get(hotspots))
+            .route("/rewards/pending/:owner", get(pending_rewards))
+            .route("/pay", post(pay))
+            .route("/rewards/claim", post(claim_rewards))
+            .route("/metrics", get(metrics::handler))
+            .with_state(state);
+        let listener = tokio::net::TcpListener::bind(self.listen).await?;
+        tracing::info!(listen = %self.listen, "serving");
+        axum::serve(listener, app).await?;
+        Ok(())
+    }
+}
+
+/// An error response with a JSON body
+struct ApiError(StatusCode, String);
+
+impl ApiError {
+    fn unauthorized(msg: &str) -> Self {
+        Self(StatusCode::UNAUTHORIZED, msg.to_string())
+    }
+}
+
+impl<E: Into<Error>> From<E> for ApiError {
+    fn from(err: E) -> Self {
+        Self(StatusCode::INTERNAL_SERVER_ERROR, err.into().to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({"result": "error", "error": self.1}))).into_response()
+    }
+}
+
+type ApiResult = std::result::Result<Json<serde_json::Value>, ApiError>;
+
+impl ServerState {
+    /// The keypair for a signing request, after checking the request's
+    /// bearer token
+    fn authorize(&self, headers: &HeaderMap) -> std::result::Result<&Keypair, ApiError> {
+        let signer = self.signer.as_ref().ok_or_else(|| {
+            ApiError(
+                StatusCode::FORBIDDEN,
+                "signing is disabled, start the server with --unlock".to_string(),
+            )
+        })?;
+        let token = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| ApiError::unauthorized("missing bearer token"))?;
+        if !constant_time_eq(token.as_bytes(), signer.auth_token.as_bytes()) {
+            return Err(ApiError::unauthorized("invalid bearer token"));
+        }
+        Ok(&signer.keypair)
+    }
+}
+
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+fn parse_pubkey(value: &str) -> std::result::Result<Pubkey, ApiError> {
+    value
+        .parse()
+        .map_err(|_| ApiError(StatusCode::BAD_REQUEST, format!("invalid address {value}")))
+}
+
+async fn balance(State(state): State<ServerState>, UrlPath(address): UrlPath<String>) -> ApiResult {
+    let address = parse_pubkey(&address)?;
+    let mut balances = balances_json(&state.client, &[address]).await;
+    Ok(Json(balances.remove(0)))
+}
+
+async fn hotspots(State(state): State<ServerState>, UrlPath(owner): UrlPath<String>) -> ApiResult {
+    let owner = parse_pubkey(&owner)?;
+    let hotspots = hotspot::for_owner(&state.client, &owner).await?;
+    Ok(Json(json!(hotspots)))
+}
+
+#[derive(Debug, Deserialize)]
+struct PendingQuery {
+    subdao: Option<ClaimableToken>,
+}
+
+async fn pending_rewards(
+    State(state): State<ServerState>,
+    UrlPath(owner): UrlPath<String>,
+    Query(query): Query<PendingQuery>,
+) -> ApiResult {
+    let owner = parse_pubkey(&owner)?;
+    let hotspots: Vec<String> = hotspot::for_owner(&state.client, &owner)
+        .await?
+        .into_iter()
+        .map(|hotspot| hotspot.key.to_string())
+        .collect();
+    let tokens = match query.subdao {
+        Some(token) => vec![token],
+        None => vec![
+            ClaimableToken::Iot,
+            ClaimableToken::Mobile,
+            ClaimableToken::Hnt,
+        ],
+    };
+    let mut pending: BTreeMap<String, BTreeMap<Token, TokenAmount>> = BTreeMap::new();
+    let mut total: BTreeMap<Token, TokenAmount> = BTreeMap::new();
+    for token in tokens {
+        total.insert(token.into(), Token::from(token).amount(0));
+        let rewards =
+            reward::pending_many(&state.client, token, &hotspots, KeySerialization::B58).await?;
+        for (hotspot, reward) in rewards {
+            if let Some(token_total) = total.get_mut(&reward.reward.token) {
+                token_total.amount += reward.reward.amount;
+            }
+            pending
+                .entry(hotspot)
+                .or_default()
+                .insert(reward.reward.token, reward.reward);
+        }
+    }
+    Ok(Json(json!({
+        "hotspots": pending,
+        "total": total.values().collect::<Vec<_>>(),
+    })))
+}
+
+async fn pay(
+    State(state): State<ServerState>,
+    headers: HeaderMap,
+    Json(payees): Json<Vec<Payee>>,
+) -> ApiResult {
+    let keypair = state.authorize(&headers)?;
+    if payees.is_empty() {
+        return Err(ApiError(
+            StatusCode::BAD_REQUEST,
+            "no payees given".to_string(),
+        ));
+    }
+    let payments: Vec<(Pubkey, TokenAmount)> = payees.iter().map(Payee::payment).collect();
+    let txn_opts = state.commit.transaction_opts(&state.client)?;
+    let (tx, _) = token::transfer(&state.client, &payments, keypair, &txn_opts).await?;
+    Ok(Json(
+        state.commit.maybe_commit(tx, &state.client).await.to_json(),
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+struct ClaimRequest {
+    hotspots: Vec<helium_crypto::PublicKey>,
+    subdao: ClaimableToken,
+}
+
+async fn claim_rewards(
+    State(state): State<ServerState>,
+    headers: HeaderMap,
+    Json(request): Json<ClaimRequest>,
+) -> ApiResult {
+    let keypair = state.authorize(&headers)?;
+    let txn_opts = state.commit.transaction_opts(&state.client)?;
+    let mut claims = vec![];
+    for hotspot in &request.hotspots {
+        let entity_key = EncodedEntityKey {
+            entity_key: hotspot.to_string(),
+            encoding: EntityKeyEncoding::B58,
+        };
+        let claim = match reward::claim(
+            &state.client,
+            request.subdao,
+            None,
+            &entity_key,
+            keypair,
+            &txn_opts,
+        )
+        .await
+        {
+            Ok(Some((tx, _))) => {
+                let result = state.commit.maybe_commit(tx, &state.client).await;
+                if let Ok(CommitResponse::Transaction(_)) = result {
+                    metrics::reward_claimed(request.subdao.into(), None);
+                }
+                result.to_json()
+            }
+            Ok(None) => json!({"result": "skipped"}),
+            Err(err) => json!({"result": "error", "error": err.to_string()}),
+        };
+        claims.push(json!({
+            "hotspot": hotspot.to_string(),
+            "claim": claim,
+        }));
+    }
+    Ok(Json(json!({ "claims": claims })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_comparison() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+    }
+}
@@ -0,0 +1,201 @@
+use crate::{
+    cmd::{serve::constant_time_eq, *},
+    remote_signer::{
+        proto::{self, remote_signer_server::RemoteSignerServer},
+        Policy, AUTH_TOKEN_ENV,
+    },
+};
+use helium_lib::keypair::{Keypair, Pubkey};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use std::{net::SocketAddr, str::FromStr};
+use tonic::{Request, Response, Status};
+
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    #[command(subcommand)]
+    cmd: SignerCommand,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        self.cmd.run(opts).await
+    }
+}
+
+/// Commands for running a remote signer
+#[derive(Debug, clap::Subcommand)]
+pub enum SignerCommand {
+    Serve(Serve),
+}
+
+impl SignerCommand {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Self::Serve(cmd) => cmd.run(opts).await,
+        }
+    }
+}
+
+#[derive(Debug, clap::Args)]
+/// Serve the wallet as a gRPC remote signer
+///
+/// The wallet is decrypted once at startup. Transactions are only signed
+/// when they invoke allowed programs and stay within the transfer limits.
+/// Token delegations and authority changes are never signed. Other wallet
+/// instances use the signer with --signer-url.
+///
+/// Clients must present the auth token, given by --auth-token or the
+/// HELIUM_WALLET_SIGNER_TOKEN environment variable, as a bearer token.
+pub struct Serve {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:50051")]
+    listen: SocketAddr,
+    /// Token that clients must present as a bearer token
+    #[arg(long)]
+    auth_token: Option<String>,
+    /// Additional program that transactions may invoke. Can be given
+    /// multiple times.
+    #[arg(long = "allow-program", number_of_values(1))]
+    allow_programs: Vec<Pubkey>,
+    /// Maximum SOL a single transaction may transfer out of the wallet,
+    /// including the SOL given to accounts it creates
+    #[arg(long)]
+    max_transfer: Option<f64>,
+    /// Maximum of a token a single transaction may transfer out of the
+    /// wallet, like 'hnt=100'. Can be given multiple times. Once any token
+    /// has a maximum, transfers of other SPL tokens are refused.
+    #[arg(
+        long = "max-token-transfer",
+        value_name = "TOKEN=AMOUNT",
+        value_parser = parse_token_max
+    )]
+    max_token_transfers: Vec<(Token, u64)>,
+    /// Sign arbitrary messages in addition to transactions
+    #[arg(long)]
+    allow_messages: bool,
+}
+
+impl Serve {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let auth_token = match &self.auth_token {
+            Some(auth_token) => auth_token.clone(),
+            None => env::var(AUTH_TOKEN_ENV)
+                .map_err(|_| anyhow!("--auth-token or {AUTH_TOKEN_ENV} is required"))?,
+        };
+        if auth_token.is_empty() {
+            bail!("auth token must not be empty");
+        }
+        let password = get_wallet_password(false)?;
+        let keypair = opts.load_keypair(password.as_bytes())?;
+        let mut policy = Policy {
+            max_transfer_lamports: self
+                .max_transfer
+                .map(|sol| TokenAmount::from_f64(Token::Sol, sol).amount),
+            max_token_transfers: self.max_token_transfers.iter().copied().collect(),
+            ..Default::default()
+        };
+        policy
+            .allowed_programs
+            .extend(self.allow_programs.iter().copied());
+
+        let expected = format!("Bearer {auth_token}");
+        let authorize = move |request: Request<()>| {
+            let authorized = request
+                .metadata()
+                .get("authorization")
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|value| constant_time_eq(value.as_bytes(), expected.as_bytes()));
+            if authorized {
+                Ok(request)
+            } else {
+                Err(Status::unauthenticated("invalid or missing bearer token"))
+            }
+        };
+        let service = SignerService {
+            keypair,
+            policy,
+            allow_messages: self.allow_messages,
+        };
+        tracing::info!(listen = %self.listen, pubkey = %service.keypair.pubkey(), "serving signer");
+        tonic::transport::Server::builder()
+            .add_service(RemoteSignerServer::with_interceptor(service, authorize))
+            .serve(self.listen)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Parse a token maximum given as 'TOKEN=AMOUNT' into the token and the
+/// amount in its smallest unit
+fn parse_token_max(s: &str) -> Result<(Token, u64)> {
+    let (token, amount) = s
+        .split_once('=')
+        .ok_or_else(|| anyhow!("expected a token maximum as 'TOKEN=AMOUNT'"))?;
+    let token = Token::from_str(token.trim())?;
+    if token == Token::Sol {
+        bail!("use --max-transfer for the maximum SOL");
+    }
+    let amount = Decimal::from_str(amount.trim())?;
+    let amount = amount
+        .checked_mul(Decimal::from(10_u64.pow(token.decimals().into())))
+        .and_then(|amount| amount.to_u64())
+        .ok_or_else(|| anyhow!("invalid {token} amount {amount}"))?;
+    Ok((token, amount))
+}
+
+struct SignerService {
+    keypair: Arc<Keypair>,
+    policy: Policy,
+    allow_messages: bool,
+}
+
+#[tonic::async_trait]
+impl proto::remote_signer_server::RemoteSigner for SignerService {
+    async fn public_key(
+        &self,
+        _request: Request<proto::PublicKeyReq>,
+    ) -> std::result::Result<Response<proto::PublicKeyRes>, Status> {
+        Ok(Response::new(proto::PublicKeyRes {
+            pubkey: self.keypair.pubkey().to_bytes().to_vec(),
+        }))
+    }
+
+    async fn sign_message(
+        &self,
+        request: Request<proto::SignMessageReq>,
+    ) -> std::result::Result<Response<proto::SignMessageRes>, Status> {
+        if !self.allow_messages {
+            return Err(Status::permission_denied("message signing is disabled"));
+        }
+        let signature = self
+            .keypair
+            .sign(&request.into_inner().message)
+            .map_err(|err| Status::internal(err.to_string()))?;
+        Ok(Response::new(proto::SignMessageRes {
+            signature: signature.as_ref().to_vec(),
+        }))
+    }
+
+    async fn sign_transaction(
+        &self,
+        request: Request<proto::SignTransactionReq>,
+    ) -> std::result::Result<Response<proto::SignTransactionRes>, Status> {
+        let mut transaction: VersionedTransaction =
+            bincode::deserialize(&request.into_inner().transaction)
+                .map_err(|err| Status::invalid_argument(format!("transaction: {err}")))?;
+        let pubkey = self.keypair.pubkey();
+        if let Err(err) = self.policy.check(&transaction, &pubkey) {
+            tracing::info!(%err, "rejected transaction");
+            return Err(Status::permission_denied(err.to_string()));
+        }
+        tx::sign(&mut transaction, &[&*self.keypair])
+            .map_err(|err| Status::internal(err.to_string()))?;
+        tracing::info!(signature = %transaction.signatures[0], "signed transaction");
+        // A transaction that can not be audited is not handed out
+        crate::audit::record(&transaction, "signed", None)
+            .map_err(|err| Status::internal(format!("{err:#}")))?;
+        let transaction =
+            bincode::serialize(&transaction).map_err(|err| Status::internal(err.to_string()))?;
+        Ok(Response::new(proto::SignTransactionRes { transaction }))
+    }
+}
@@ -0,0 +1,119 @@
+use crate::cmd::*;
+use helium_lib::{
+    keypair::{Pubkey, Signer},
+    positions,
+};
+
+#[derive(Debug, clap::Args)]
+/// Claim delegation rewards for delegated veHNT positions
+///
+/// Rewards are claimed per epoch, for every completed epoch since the last
+/// claim, with as many epochs claimed in a single transaction as fit. When
+/// more epochs are claimable, each transaction is confirmed before the next
+/// one is sent, and the remaining epochs of a position are skipped once a claim
+/// fails. Without a position all delegated positions of the wallet are
+/// claimed.
+pub struct Cmd {
+    /// Position or position mint address to claim rewards for
+    position: Option<Pubkey>,
+    /// Commit the claim transactions
+    #[command(flatten)]
+    commit: CommitOpts,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let password = get_wallet_password(false)?;
+        let keypair = opts.load_keypair(password.as_bytes())?;
+        let client = opts.client()?;
+        let transaction_opts = self.commit.transaction_opts(&client)?;
+        let owner = keypair.pubkey();
+
+        let delegated = match &self.position {
+            Some(address) => {
+                let position = super::positions::find(&client, &owner, address).await?;
+                if !position.is_delegated() {
+                    bail!("position {} is not delegated", position.key);
+                }
+                vec![position]
+            }
+            None => positions::for_owner(&client, &owner)
+                .await?
+                .into_iter()
+                .filter(|position| position.is_delegated())
+                .collect(),
+        };
+        let (_, _, weights) = positions::hnt_registrar(&client).await?;
+
+        let mut claimables = vec![];
+        for position in &delegated {
+            if let Some(claimable) =
+                positions::claimable_rewards(&client, position, &weights).await?
+            {
+                let packed = positions::pack_claims(
+                    &client,
+                    position,
+                    &claimable.epochs,
+                    &owner,
+                    &transaction_opts,
+                )
+                .await?;
+                claimables.push((position, claimable, packed));
+            }
+        }
+        let total = claimables
+            .iter()
+            .map(|(_, _, packed)| packed.len() as u64)
+            .sum();
+        let progress = Progress::new("claims", Some(total));
+
+        let mut claims = vec![];
+        for (position, claimable, packed) in claimables {
+            let mut results = vec![];
+            let mut failed = false;
+            for (index, epochs) in packed.epochs.iter().enumerate() {
+                if failed {
+                    results.push(json!({
+                        "result": "skipped",
+                        "reason": "earlier claim failed",
+                        "epochs": epochs,
+                    }));
+                    progress.failure();
+                    continue;
+                }
+                let result = match packed
+                    .transaction(&client, index, &keypair, &transaction_opts)
+                    .await
+                {
+                    Ok((tx, _)) => self.commit.maybe_commit_confirmed(tx, &client).await,
+                    Err(err) => Err(err.into()),
+                };
+                let mut json = match result {
+                    Ok(response) => {
+                        progress.success();
+                        response.to_json()
+                    }
+                    Err(err) => {
+                        failed = true;
+                        progress.failure();
+                        json!({
+                            "result": "error",
+                            "error": err.to_string(),
+                            "code": ErrorCode::of(&err),
+                        })
+                    }
+                };
+                json["epochs"] = json!(epochs);
+                results.push(json);
+            }
+            claims.push(json!({
+                "position": position.key.to_string(),
+                "subdao": claimable.sub_dao,
+                "amount": claimable.amount,
+                "claims": results,
+            }));
+        }
+        progress.finish();
+        print_json(&claims)
+    }
+}
@@ -0,0 +1,62 @@
+use crate::cmd::*;
+use helium_lib::{
+    dao::SubDao,
+    positions::{self, LockupKind, PositionArgs},
+    token::{Token, TokenAmount},
+};
+
+#[derive(Debug, clap::Args)]
+/// Lock HNT in a new veHNT position
+///
+/// The position is a voter stake registry NFT minted to this wallet. The HNT
+/// stays locked until the lockup ends, and the voting power (veHNT) of the
+/// position decays as the unlock date approaches. With --constant the lockup
+/// does not decay until the position is explicitly unlocked.
+///
+/// The reported veHNT, multiplier and unlock date are estimated at the time
+/// the transaction is constructed.
+pub struct Cmd {
+    /// Amount of HNT to lock
+    #[arg(long)]
+    amount: f64,
+    /// Lockup duration in days
+    #[arg(long)]
+    duration: u32,
+    /// Use a constant (non-decaying) lockup instead of a cliff lockup
+    #[arg(long)]
+    constant: bool,
+    /// Subdao to delegate the new position to
+    #[arg(long, value_enum)]
+    delegate: Option<SubDao>,
+    /// Commit the transaction
+    #[command(flatten)]
+    commit: CommitOpts,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        if self.amount <= 0.0 {
+            bail!("amount must be positive");
+        }
+        let password = get_wallet_password(false)?;
+        let keypair = opts.load_keypair(password.as_bytes())?;
+        let client = opts.client()?;
+        let transaction_opts = self.commit.transaction_opts(&client)?;
+        let args = PositionArgs {
+            amount: TokenAmount::from_f64(Token::Hnt, self.amount),
+            kind: if self.constant {
+                LockupKind::Constant
+            } else {
+                LockupKind::Cliff
+            },
+            days: self.duration,
+            delegate: self.delegate,
+        };
+
+        let (tx, _, position) =
+            positions::create(&client, args, &keypair, &transaction_opts).await?;
+        let mut json = self.commit.maybe_commit(tx, &client).await?.to_json();
+        json["position"] = serde_json::to_value(&position)?;
+        print_json(&json)
+    }
+}
@@ -0,0 +1,123 @@
+use crate::cmd::*;
+use helium_lib::{
+    dao::SubDao,
+    keypair::{Keypair, Pubkey, Signer},
+    positions::{self, Position},
+};
+
+#[derive(Debug, clap::Args)]
+/// Delegate a veHNT position to a subdao
+///
+/// A position that is already delegated to another subdao is undelegated
+/// first. Any unclaimed delegation rewards are claimed before the delegation
+/// changes, as required by the subdao program. Recent epochs are claimed in
+/// the same transaction as the change, older ones in separate transactions
+/// that are confirmed first.
+pub struct Cmd {
+    /// Position or position mint address to delegate
+    position: Pubkey,
+    /// Subdao to delegate the position to
+    #[arg(long, value_enum)]
+    subdao: SubDao,
+    /// Commit the transactions
+    #[command(flatten)]
+    commit: CommitOpts,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let password = get_wallet_password(false)?;
+        let keypair = opts.load_keypair(password.as_bytes())?;
+        let client = opts.client()?;
+        let position = super::positions::find(&client, &keypair.pubkey(), &self.position).await?;
+        let json = change_delegation(
+            &client,
+            &keypair,
+            &position,
+            Some(self.subdao),
+            &self.commit,
+        )
+        .await?;
+        print_json(&json)
+    }
+}
+
+/// Change the delegation of the given position, claiming any rewards that
+/// do not fit in the delegation transaction first.
+///
+/// Once those claims are confirmed the position is fetched again so the
+/// delegation builds on the claimed state. Without committing, the claims
+/// that are needed are reported instead of a delegation that would fail
+/// before they land.
+pub async fn change_delegation(
+    client: &client::Client,
+    keypair: &Keypair,
+    position: &Position,
+    sub_dao: Option<SubDao>,
+    commit: &CommitOpts,
+) -> Result<serde_json::Value> {
+    let transaction_opts = commit.transaction_opts(client)?;
+    let mut claims = vec![];
+    let mut refreshed = None;
+    if position.is_delegated() {
+        let (_, _, weights) = positions::hnt_registrar(client).await?;
+        if let Some(claimable) = positions::claimable_rewards(client, position, &weights).await? {
+            // The most recent epochs are claimed along with the change itself
+            let catch_up = claimable
+                .epochs
+                .len()
+                .saturating_sub(positions::MAX_CLAIM_EPOCHS_PER_TXN);
+            if catch_up > 0 {
+                let packed = positions::pack_claims(
+                    client,
+                    position,
+                    &claimable.epochs[..catch_up],
+                    &keypair.pubkey(),
+                    &transaction_opts,
+                )
+                .await?;
+                for (index, epochs) in packed.epochs.iter().enumerate() {
+                    let (tx, _) = packed
+                        .transaction(client, index, keypair, &transaction_opts)
+                        .await?;
+                    let mut json = commit.maybe_commit_confirmed(tx, client).await?.to_json();
+                    json["epochs"] = json!(epochs);
+                    claims.push(json);
+                }
+                if !commit.commit {
+                    return Ok(json!({
+                        "position": position.key.to_string(),
+                        "from": position.delegation.as_ref().map(|delegation| delegation.sub_dao),
+                        "to": sub_dao,
+                        "result": {
+                            "result": "claims_required",
+                            "reason": "rewards must be claimed before the delegation can change",
+                        },
+                        "claims": claims,
+                    }));
+                }
+                refreshed =
+                    Some(super::positions::find(client, &keypair.pubkey(), &position.key).await?);
+            }
+        }
+    }
+    let current = refreshed.as_ref().unwrap_or(position);
+
+    let delegation = async {
+        let (tx, _) =
+            positions::delegate(client, current, sub_dao, keypair, &transaction_opts).await?;
+        commit.maybe_commit(tx, client).await
+    }
+    .await
+    .to_json();
+    let mut json = json!({
+        "position": position.key.to_string(),
+        "from": position.delegation.as_ref().map(|delegation| delegation.sub_dao),
+        "to": sub_dao,
+        "result": delegation,
+    });
+    if !claims.is_empty() {
+        json["claims"] = json!(claims);
+    }
+    Ok(json)
+}
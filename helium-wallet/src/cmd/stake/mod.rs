@@ -0,0 +1,41 @@
+use crate::cmd::*;
+
+pub mod claim;
+pub mod create;
+pub mod delegate;
+pub mod positions;
+pub mod undelegate;
+
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    #[command(subcommand)]
+    cmd: StakeCommand,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        self.cmd.run(opts).await
+    }
+}
+
+/// Lock HNT in veHNT positions and manage them
+#[derive(Debug, clap::Subcommand)]
+pub enum StakeCommand {
+    Create(create::Cmd),
+    Positions(positions::Cmd),
+    Claim(claim::Cmd),
+    Delegate(delegate::Cmd),
+    Undelegate(undelegate::Cmd),
+}
+
+impl StakeCommand {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Self::Create(cmd) => cmd.run(opts).await,
+            Self::Positions(cmd) => cmd.run(opts).await,
+            Self::Claim(cmd) => cmd.run(opts).await,
+            Self::Delegate(cmd) => cmd.run(opts).await,
+            Self::Undelegate(cmd) => cmd.run(opts).await,
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use crate::cmd::*;
+use helium_lib::{keypair::Pubkey, positions};
+
+#[derive(Debug, clap::Args)]
+/// List the veHNT positions of a wallet
+///
+/// For delegated positions the delegation rewards that can be claimed are
+/// estimated from the completed epochs since the last claim.
+pub struct Cmd {
+    /// Wallet address to list positions for. Defaults to the active wallet
+    address: Option<Pubkey>,
+    /// Output format
+    #[arg(long, value_enum, default_value_t)]
+    format: OutputFormat,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let client = opts.client()?;
+        let address = match self.address {
+            Some(address) => address,
+            None => opts.load_wallet()?.public_key,
+        };
+        let positions = positions::for_owner(&client, &address).await?;
+        let mut claimables = vec![];
+        if positions.iter().any(|position| position.is_delegated()) {
+            let (_, _, weights) = positions::hnt_registrar(&client).await?;
+            for position in &positions {
+                claimables.push(positions::claimable_rewards(&client, position, &weights).await?);
+            }
+        } else {
+            claimables.resize(positions.len(), None);
+        }
+
+        match self.format {
+            OutputFormat::Json => {
+                let json: Vec<serde_json::Value> = positions
+                    .iter()
+                    .zip(&claimables)
+                    .map(|(position, claimable)| {
+                        let mut json = json!(position);
+                        if let Some(claimable) = claimable {
+                            json["claimable"] = json!(claimable);
+                        }
+                        json
+                    })
+                    .collect();
+                print_json(&json!({
+                    "address": address.to_string(),
+                    "positions": json,
+                }))
+            }
+            OutputFormat::Table | OutputFormat::Csv => {
+                let mut table = prettytable::Table::new();
+                table.set_format(*prettytable::format::consts::FORMAT_CLEAN);
+                table.set_titles(prettytable::row![
+                    "Position",
+                    "Amount",
+                    "Lockup",
+                    "Expiry",
+                    "Delegation",
+                    "Claimable"
+                ]);
+                for (position, claimable) in positions.iter().zip(&claimables) {
+                    table.add_row(prettytable::row![
+                        position.key,
+                        amount_cell(&position.amount, self.format),
+                        position.lockup.kind,
+                        position.lockup.end.format("%Y-%m-%d"),
+                        position
+                            .delegation
+                            .as_ref()
+                            .map(|delegation| delegation.sub_dao.to_string())
+                            .unwrap_or_default(),
+                        claimable
+                            .as_ref()
+                            .map(|claimable| format!(
+                                "{} {} ({} epochs)",
+                                amount_cell(&claimable.amount, self.format),
+                                claimable.amount.token,
+                                claimable.epochs.len()
+                            ))
+                            .unwrap_or_default()
+                    ]);
+                }
+                print_rows(&table, self.format)
+            }
+        }
+    }
+}
+
+/// Find a position of the given owner by its position or mint address
+pub async fn find(
+    client: &client::Client,
+    owner: &Pubkey,
+    address: &Pubkey,
+) -> Result<positions::Position> {
+    positions::for_owner(client, owner)
+        .await?
+        .into_iter()
+        .find(|position| position.key == *address || position.mint == *address)
+        .ok_or_else(|| anyhow!("position {address} not found for {owner}"))
+}
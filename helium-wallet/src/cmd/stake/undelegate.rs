@@ -0,0 +1,31 @@
+use crate::cmd::*;
+use helium_lib::keypair::{Pubkey, Signer};
+
+#[derive(Debug, clap::Args)]
+/// Undelegate a veHNT position from its subdao
+///
+/// Any unclaimed delegation rewards are claimed before the delegation is
+/// closed.
+pub struct Cmd {
+    /// Position or position mint address to undelegate
+    position: Pubkey,
+    /// Commit the transactions
+    #[command(flatten)]
+    commit: CommitOpts,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let password = get_wallet_password(false)?;
+        let keypair = opts.load_keypair(password.as_bytes())?;
+        let client = opts.client()?;
+        let position = super::positions::find(&client, &keypair.pubkey(), &self.position).await?;
+        if !position.is_delegated() {
+            bail!("position {} is not delegated", position.key);
+        }
+        let json =
+            super::delegate::change_delegation(&client, &keypair, &position, None, &self.commit)
+                .await?;
+        print_json(&json)
+    }
+}
@@ -0,0 +1,101 @@
+use crate::cmd::*;
+use helium_lib::{dao::SubDao, stats, token::Token};
+use std::time::Duration;
+
+#[derive(Debug, clap::Args)]
+/// Get the Data Credits (DC) burned per subdao over a period
+///
+/// Burn totals are aggregated from the on-chain epoch info accounts of each
+/// subdao, one per daily rewards epoch, along with the HNT emitted by the DAO
+/// over the same epochs. The period includes the current epoch, which is
+/// still in progress.
+pub struct Cmd {
+    /// Period to aggregate over, e.g. "7d" or "30d"
+    #[arg(long, default_value = "30d", value_parser = humantime::parse_duration)]
+    period: Duration,
+    /// Subdao(s) to get burn totals for
+    ///
+    /// Defaults to both IOT and MOBILE.
+    #[arg(long, value_enum, number_of_values(1))]
+    subdao: Vec<SubDao>,
+    /// Include the per epoch breakdown
+    #[arg(long)]
+    epochs: bool,
+    /// Output format
+    #[arg(long, value_enum, default_value_t)]
+    format: OutputFormat,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let client = opts.client()?;
+        let subdaos = if self.subdao.is_empty() {
+            SubDao::all().to_vec()
+        } else {
+            self.subdao.clone()
+        };
+        let epochs = stats::epochs_for_period(self.period);
+
+        let mut burns = vec![];
+        for subdao in subdaos {
+            let mut burn = stats::dc_burn(&client, subdao, epochs.clone()).await?;
+            if !self.epochs {
+                burn.epochs.clear();
+            }
+            burns.push(burn);
+        }
+        let emissions = stats::emissions(&client, epochs.clone()).await?;
+        let hnt_emitted = Token::Hnt.amount(
+            emissions
+                .iter()
+                .map(|emission| emission.hnt_emitted.amount)
+                .sum(),
+        );
+
+        match self.format {
+            OutputFormat::Json => {
+                let mut json = json!({
+                    "start_epoch": epochs.start,
+                    "end_epoch": epochs.end - 1,
+                    "subdaos": burns,
+                    "hnt_emitted": hnt_emitted,
+                });
+                if self.epochs {
+                    json["emissions"] = json!(emissions);
+                }
+                print_json(&json)
+            }
+            OutputFormat::Table | OutputFormat::Csv => {
+                let mut table = prettytable::Table::new();
+                table.set_format(*prettytable::format::consts::FORMAT_CLEAN);
+                table.set_titles(prettytable::row!["Subdao", "Epoch", "DC Burned", "USD"]);
+                for burn in &burns {
+                    for epoch in &burn.epochs {
+                        table.add_row(prettytable::row![
+                            burn.subdao,
+                            epoch.epoch,
+                            epoch.dc_burned.amount,
+                            ""
+                        ]);
+                    }
+                    table.add_row(prettytable::row![
+                        burn.subdao,
+                        "Total",
+                        burn.dc_burned.amount,
+                        burn.usd.round_dp(2)
+                    ]);
+                }
+                print_rows(&table, self.format)?;
+                if self.format == OutputFormat::Table {
+                    println!(
+                        "HNT emitted in epochs {}-{}: {}",
+                        epochs.start,
+                        epochs.end - 1,
+                        f64::from(&hnt_emitted)
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+}
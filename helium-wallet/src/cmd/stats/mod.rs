@@ -0,0 +1,29 @@
+use crate::cmd::*;
+
+pub mod dc_burn;
+
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    #[command(subcommand)]
+    cmd: StatsCommand,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        self.cmd.run(opts).await
+    }
+}
+
+/// Network statistics from on-chain DAO accounts
+#[derive(Debug, clap::Subcommand)]
+pub enum StatsCommand {
+    DcBurn(dc_burn::Cmd),
+}
+
+impl StatsCommand {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Self::DcBurn(cmd) => cmd.run(opts).await,
+        }
+    }
+}
@@ -87,24 +87,49 @@ impl PayCmd {
         let password = get_wallet_password(false)?;
         let keypair = opts.load_keypair(password.as_bytes())?;
         let client = opts.client()?;
-        let txn_opts = self.commit().transaction_opts(&client);
+        let txn_opts = self.commit().transaction_opts(&client)?;
 
-        let (tx, _) = token::transfer(&client, &payments, &keypair, &txn_opts).await?;
+        let packed =
+            token::pack_transfers(&client, &payments, &keypair.pubkey(), &txn_opts).await?;
+        if packed.len() == 1 {
+            let (tx, _) = packed.transaction(&client, 0, &keypair, &txn_opts).await?;
+            return print_json(&self.commit().maybe_commit(tx, &client).await?.to_json());
+        }
 
-        print_json(&self.commit().maybe_commit(tx, &client).await?.to_json())
+        // Payments that do not fit in one transaction are sent in as few
+        // transactions as possible, each built just before it is sent so it
+        // gets a fresh blockhash
+        let mut results = vec![];
+        for (index, transfers) in packed.transfers.iter().enumerate() {
+            let commit = match packed
+                .transaction(&client, index, &keypair, &txn_opts)
+                .await
+            {
+                Ok((tx, _)) => self.commit().maybe_commit(tx, &client).await,
+                Err(err) => Err(err.into()),
+            }
+            .to_json();
+            results.push(json!({
+                "payments": transfers
+                    .iter()
+                    .map(|(payee, amount)| json!({
+                        "address": payee.to_string(),
+                        "amount": amount,
+                    }))
+                    .collect::<Vec<_>>(),
+                "commit": commit,
+            }));
+        }
+        print_json(&results)
     }
 
     fn collect_payments(&self) -> Result<Vec<(Pubkey, TokenAmount)>> {
         match &self {
-            Self::One(one) => Ok(vec![(one.payee.address, one.payee.token_amount())]),
+            Self::One(one) => Ok(vec![one.payee.payment()]),
             Self::Multi(multi) => {
                 let file = std::fs::File::open(multi.path.clone())?;
                 let payees: Vec<Payee> = serde_json::from_reader(file)?;
-                let payments = payees
-                    .iter()
-                    .map(|p| (p.address, p.token_amount()))
-                    .collect();
-                Ok(payments)
+                Ok(payees.iter().map(Payee::payment).collect())
             }
         }
     }
@@ -133,6 +158,10 @@ impl Payee {
     pub fn token_amount(&self) -> TokenAmount {
         TokenAmount::from_f64(self.token, self.amount)
     }
+
+    pub fn payment(&self) -> (Pubkey, TokenAmount) {
+        (self.address, self.token_amount())
+    }
 }
 
 #[cfg(test)]
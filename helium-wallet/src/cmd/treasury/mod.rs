@@ -0,0 +1,29 @@
+use crate::cmd::*;
+
+pub mod swap;
+
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    #[command(subcommand)]
+    cmd: TreasuryCommand,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        self.cmd.run(opts).await
+    }
+}
+
+/// Redeem subdao tokens with the subdao treasuries
+#[derive(Debug, clap::Subcommand)]
+pub enum TreasuryCommand {
+    Swap(swap::Cmd),
+}
+
+impl TreasuryCommand {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Self::Swap(cmd) => cmd.run(opts).await,
+        }
+    }
+}
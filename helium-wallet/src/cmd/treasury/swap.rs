@@ -0,0 +1,68 @@
+use crate::cmd::*;
+use helium_lib::{
+    token::{Token, TokenAmount},
+    treasury,
+};
+
+#[derive(Debug, clap::Args)]
+/// Redeem IOT or MOBILE for HNT at the on-chain treasury price
+///
+/// The quote, including the minimum HNT accepted after slippage, is always
+/// printed. Without --commit the redemption is only simulated. Before
+/// committing, the quote is shown and has to be confirmed, unless --yes is
+/// given.
+pub struct Cmd {
+    /// Token to redeem
+    #[arg(long, value_parser = Token::redeemable_value_parser)]
+    token: Token,
+    /// Amount of tokens to redeem
+    #[arg(long)]
+    amount: f64,
+    /// Allowed slippage in basis points
+    #[arg(long, default_value_t = 50)]
+    slippage_bps: u16,
+    /// Redeem without asking for confirmation
+    #[arg(long)]
+    yes: bool,
+    /// Commit the redemption
+    #[command(flatten)]
+    commit: CommitOpts,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        if self.amount <= 0.0 {
+            bail!("amount must be positive");
+        }
+        let password = get_wallet_password(false)?;
+        let keypair = opts.load_keypair(password.as_bytes())?;
+        let client = opts.client()?;
+        let transaction_opts = self.commit.transaction_opts(&client)?;
+
+        let amount = TokenAmount::from_f64(self.token, self.amount);
+        let quote = treasury::quote(&client, amount, self.slippage_bps).await?;
+        if quote.output.amount == 0 {
+            bail!("amount too small to redeem for any HNT");
+        }
+        if self.commit.commit && !self.yes && !confirm_quote(&quote)? {
+            bail!("redemption cancelled");
+        }
+        let (tx, _) = treasury::swap(&client, &quote, &keypair, &transaction_opts).await?;
+        let mut json = self.commit.maybe_commit(tx, &client).await?.to_json();
+        json["quote"] = json!(quote);
+        print_json(&json)
+    }
+}
+
+/// Show a redemption quote and ask whether to go ahead
+fn confirm_quote(quote: &treasury::Quote) -> Result<bool> {
+    eprintln!("Redeem:      {}", output::format_amount(&quote.amount));
+    eprintln!("Expected:    {}", output::format_amount(&quote.output));
+    eprintln!("Minimum:     {}", output::format_amount(&quote.min_output));
+    eprintln!("Price:       {} HNT", quote.price);
+    eprintln!("Slippage:    {} bps", quote.slippage_bps);
+    Ok(dialoguer::Confirm::new()
+        .with_prompt("Redeem at this quote?")
+        .default(false)
+        .interact()?)
+}
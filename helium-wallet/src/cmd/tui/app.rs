@@ -0,0 +1,551 @@
+use crate::cmd::*;
+use futures::{StreamExt, TryStreamExt};
+use helium_lib::{
+    entity_key::{EncodedEntityKey, EntityKeyEncoding, KeySerialization},
+    hotspot::{self, Hotspot},
+    keypair::{Pubkey, Signer},
+    reward::{self, ClaimOutcome, ClaimableToken},
+    solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config,
+    token,
+};
+use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Number of recent transactions shown
+const RECENT_TRANSACTIONS: usize = 20;
+/// Tokens that Hotspots earn rewards in
+const REWARD_TOKENS: [ClaimableToken; 3] = [
+    ClaimableToken::Iot,
+    ClaimableToken::Mobile,
+    ClaimableToken::Hnt,
+];
+
+/// Updates for the dashboard from the terminal and from background tasks
+pub enum Message {
+    Input(Event),
+    Balances(Result<Vec<TokenAmount>>),
+    /// Number of Hotspots found so far while listing them
+    HotspotsLoading(usize),
+    Hotspots(Result<Vec<HotspotRow>>),
+    Transactions(Result<Vec<TransactionRow>>),
+    /// Progress of a running action
+    Progress(String),
+    /// A finished action and its result
+    Done(String),
+}
+
+pub struct HotspotRow {
+    pub hotspot: Hotspot,
+    pub pending: BTreeMap<Token, TokenAmount>,
+}
+
+pub struct TransactionRow {
+    pub signature: String,
+    pub block_time: Option<i64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Refresh,
+    ClaimSelected,
+    ClaimAll,
+    Pay,
+}
+
+impl Action {
+    pub const ALL: [Self; 4] = [
+        Self::Refresh,
+        Self::ClaimSelected,
+        Self::ClaimAll,
+        Self::Pay,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Refresh => "Refresh",
+            Self::ClaimSelected => "Claim rewards of selected Hotspot",
+            Self::ClaimAll => "Claim rewards of all Hotspots",
+            Self::Pay => "Pay",
+        }
+    }
+}
+
+pub enum Mode {
+    Normal,
+    /// The action palette with the selected action
+    Palette(usize),
+    Pay(PayForm),
+}
+
+/// The fields of a payment being entered
+#[derive(Debug, Default)]
+pub struct PayForm {
+    pub address: String,
+    pub amount: String,
+    pub token: String,
+    /// Index of the field being edited
+    pub focus: usize,
+}
+
+impl PayForm {
+    pub const LABELS: [&'static str; 3] = ["Address", "Amount", "Token"];
+
+    pub fn fields(&self) -> [&str; 3] {
+        [&self.address, &self.amount, &self.token]
+    }
+
+    fn focused(&mut self) -> &mut String {
+        match self.focus {
+            0 => &mut self.address,
+            1 => &mut self.amount,
+            _ => &mut self.token,
+        }
+    }
+
+    /// The payment entered. The token defaults to HNT.
+    fn payment(&self) -> Result<(Pubkey, TokenAmount)> {
+        let address = Pubkey::from_str(self.address.trim())
+            .map_err(|_| anyhow!("invalid address: {}", self.address))?;
+        let token = match self.token.trim() {
+            "" => Token::Hnt,
+            token => Token::transferrable_value_parser(&token.to_lowercase())?,
+        };
+        let amount: f64 = self
+            .amount
+            .trim()
+            .parse()
+            .map_err(|_| anyhow!("invalid amount: {}", self.amount))?;
+        if amount <= 0.0 {
+            bail!("amount must be positive");
+        }
+        Ok((address, TokenAmount::from_f64(token, amount)))
+    }
+}
+
+/// Shared by the background tasks of the dashboard
+struct Context {
+    client: client::Client,
+    keypair: Arc<Keypair>,
+    commit: CommitOpts,
+    sender: UnboundedSender<Message>,
+}
+
+impl Context {
+    fn send(&self, message: Message) {
+        // The dashboard is gone when sending fails, leaving nobody to tell
+        let _ = self.sender.send(message);
+    }
+}
+
+pub struct App {
+    context: Arc<Context>,
+    pub balances: Vec<TokenAmount>,
+    pub hotspots: Vec<HotspotRow>,
+    pub transactions: Vec<TransactionRow>,
+    /// Index of the selected Hotspot
+    pub selected: usize,
+    pub mode: Mode,
+    pub status: String,
+    /// Whether a claim or payment is running
+    pub busy: bool,
+    pub quit: bool,
+}
+
+impl App {
+    pub fn new(
+        client: client::Client,
+        keypair: Arc<Keypair>,
+        commit: CommitOpts,
+        sender: UnboundedSender<Message>,
+    ) -> Self {
+        Self {
+            context: Arc::new(Context {
+                client,
+                keypair,
+                commit,
+                sender,
+            }),
+            balances: vec![],
+            hotspots: vec![],
+            transactions: vec![],
+            selected: 0,
+            mode: Mode::Normal,
+            status: "Loading...".to_string(),
+            busy: false,
+            quit: false,
+        }
+    }
+
+    pub fn address(&self) -> Pubkey {
+        self.context.keypair.pubkey()
+    }
+
+    pub fn committing(&self) -> bool {
+        self.context.commit.commit
+    }
+
+    pub fn handle(&mut self, message: Message) {
+        match message {
+            Message::Input(Event::Key(key)) if key.kind == KeyEventKind::Press => self.key(key),
+            Message::Input(_) => (),
+            Message::Balances(Ok(balances)) => self.balances = balances,
+            Message::HotspotsLoading(count) => {
+                self.status = format!("Loading Hotspots... {count}");
+            }
+            Message::Hotspots(Ok(hotspots)) => {
+                self.status = format!("{} Hotspots", hotspots.len());
+                self.hotspots = hotspots;
+                self.selected = self.selected.min(self.hotspots.len().saturating_sub(1));
+            }
+            Message::Transactions(Ok(transactions)) => self.transactions = transactions,
+            Message::Balances(Err(err))
+            | Message::Hotspots(Err(err))
+            | Message::Transactions(Err(err)) => self.status = format!("Error: {err:#}"),
+            Message::Progress(status) => self.status = status,
+            Message::Done(status) => {
+                self.status = status;
+                self.busy = false;
+                self.refresh();
+            }
+        }
+    }
+
+    fn key(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return;
+        }
+        match &mut self.mode {
+            Mode::Normal => match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+                KeyCode::Char(':') => self.mode = Mode::Palette(0),
+                KeyCode::Char('r') => self.refresh(),
+                KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+                KeyCode::Down | KeyCode::Char('j') => {
+                    if self.selected + 1 < self.hotspots.len() {
+                        self.selected += 1;
+                    }
+                }
+                _ => (),
+            },
+            Mode::Palette(index) => match key.code {
+                KeyCode::Esc => self.mode = Mode::Normal,
+                KeyCode::Up => *index = index.saturating_sub(1),
+                KeyCode::Down => *index = (*index + 1).min(Action::ALL.len() - 1),
+                KeyCode::Enter => {
+                    let action = Action::ALL[*index];
+                    self.mode = Mode::Normal;
+                    self.run(action);
+                }
+                _ => (),
+            },
+            Mode::Pay(form) => match key.code {
+                KeyCode::Esc => self.mode = Mode::Normal,
+                KeyCode::Tab | KeyCode::Down => form.focus = (form.focus + 1) % 3,
+                KeyCode::BackTab | KeyCode::Up => form.focus = (form.focus + 2) % 3,
+                KeyCode::Backspace => {
+                    form.focused().pop();
+                }
+                KeyCode::Char(c) => form.focused().push(c),
+                KeyCode::Enter => match form.payment() {
+                    Ok(payment) => {
+                        self.mode = Mode::Normal;
+                        self.pay(payment);
+                    }
+                    Err(err) => self.status = format!("Error: {err:#}"),
+                },
+                _ => (),
+            },
+        }
+    }
+
+    fn run(&mut self, action: Action) {
+        if action != Action::Refresh && self.busy {
+            self.status = "Waiting for the running action to finish".to_string();
+            return;
+        }
+        match action {
+            Action::Refresh => self.refresh(),
+            Action::ClaimSelected => match self.hotspots.get(self.selected) {
+                Some(row) => {
+                    let hotspots = vec![row.hotspot.key.to_string()];
+                    self.claim(hotspots);
+                }
+                None => self.status = "No Hotspot selected".to_string(),
+            },
+            Action::ClaimAll => {
+                let hotspots = self
+                    .hotspots
+                    .iter()
+                    .map(|row| row.hotspot.key.to_string())
+                    .collect();
+                self.claim(hotspots);
+            }
+            Action::Pay => self.mode = Mode::Pay(PayForm::default()),
+        }
+    }
+
+    /// Reload balances, Hotspots and transactions in the background
+    pub fn refresh(&mut self) {
+        let address = self.address();
+        let context = self.context.clone();
+        tokio::spawn(async move {
+            let balances = load_balances(&context.client, &address).await;
+            context.send(Message::Balances(balances));
+        });
+        let context = self.context.clone();
+        tokio::spawn(async move {
+            let hotspots = load_hotspots(&context, &address).await;
+            context.send(Message::Hotspots(hotspots));
+        });
+        let context = self.context.clone();
+        tokio::spawn(async move {
+            let transactions = load_transactions(&context.client, &address).await;
+            context.send(Message::Transactions(transactions));
+        });
+    }
+
+    fn claim(&mut self, hotspots: Vec<String>) {
+        let pending: Vec<(ClaimableToken, Vec<String>)> = REWARD_TOKENS
+            .into_iter()
+            .map(|token| {
+                let hotspots = self
+                    .hotspots
+                    .iter()
+                    .filter(|row| {
+                        hotspots.contains(&row.hotspot.key.to_string())
+                            && row
+                                .pending
+                                .get(&token.into())
+                                .is_some_and(|amount| amount.amount > 0)
+                    })
+                    .map(|row| row.hotspot.key.to_string())
+                    .collect::<Vec<_>>();
+                (token, hotspots)
+            })
+            .filter(|(_, hotspots)| !hotspots.is_empty())
+            .collect();
+        if pending.is_empty() {
+            self.status = "No pending rewards to claim".to_string();
+            return;
+        }
+        self.busy = true;
+        self.status = "Claiming rewards...".to_string();
+        let context = self.context.clone();
+        tokio::spawn(async move {
+            let status = match claim_rewards(&context, pending).await {
+                Ok(status) => status,
+                Err(err) => format!("Claim failed: {err:#}"),
+            };
+            context.send(Message::Done(status));
+        });
+    }
+
+    fn pay(&mut self, (payee, amount): (Pubkey, TokenAmount)) {
+        if self.busy {
+            self.status = "Waiting for the running action to finish".to_string();
+            return;
+        }
+        self.busy = true;
+        self.status = format!("Paying {} to {payee}...", output::format_amount(&amount));
+        let context = self.context.clone();
+        tokio::spawn(async move {
+            let status = match send_payment(&context, payee, amount).await {
+                Ok(status) => status,
+                Err(err) => format!("Payment failed: {err:#}"),
+            };
+            context.send(Message::Done(status));
+        });
+    }
+}
+
+async fn load_balances(client: &client::Client, address: &Pubkey) -> Result<Vec<TokenAmount>> {
+    let balances =
+        token::balance_for_addresses(client, &Token::associated_token_adresses(address)).await?;
+    Ok(Token::all()
+        .into_iter()
+        .map(|token| {
+            balances
+                .iter()
+                .find(|balance| balance.amount.token == token)
+                .map_or(token.amount(0), |balance| balance.amount)
+        })
+        .collect())
+}
+
+/// The Hotspots of the wallet with their pending rewards. Hotspots are listed
+/// page by page, reporting progress as they come in.
+async fn load_hotspots(context: &Context, address: &Pubkey) -> Result<Vec<HotspotRow>> {
+    let mut count = 0;
+    let hotspots: Vec<Hotspot> = hotspot::for_owner_stream(&context.client, address, None)
+        .inspect_ok(|_| {
+            count += 1;
+            context.send(Message::HotspotsLoading(count));
+        })
+        .try_collect()
+        .await?;
+    let keys: Vec<String> = hotspots
+        .iter()
+        .map(|hotspot| hotspot.key.to_string())
+        .collect();
+    let mut pending: HashMap<String, BTreeMap<Token, TokenAmount>> = HashMap::new();
+    for token in REWARD_TOKENS {
+        let rewards =
+            reward::pending_many(&context.client, token, &keys, KeySerialization::B58).await?;
+        for (key, reward) in rewards {
+            pending
+                .entry(key)
+                .or_default()
+                .insert(reward.reward.token, reward.reward);
+        }
+    }
+    Ok(hotspots
+        .into_iter()
+        .map(|hotspot| HotspotRow {
+            pending: pending.remove(&hotspot.key.to_string()).unwrap_or_default(),
+            hotspot,
+        })
+        .collect())
+}
+
+async fn load_transactions(
+    client: &client::Client,
+    address: &Pubkey,
+) -> Result<Vec<TransactionRow>> {
+    let signatures = client
+        .solana_client
+        .get_signatures_for_address_with_config(
+            address,
+            GetConfirmedSignaturesForAddress2Config {
+                limit: Some(RECENT_TRANSACTIONS),
+                ..Default::default()
+            },
+        )
+        .await?;
+    Ok(signatures
+        .into_iter()
+        .map(|signature| TransactionRow {
+            signature: signature.signature,
+            block_time: signature.block_time,
+            error: signature.err.map(|err| err.to_string()),
+        })
+        .collect())
+}
+
+/// Claim the rewards of the given Hotspots per token, returning a summary
+async fn claim_rewards(
+    context: &Context,
+    pending: Vec<(ClaimableToken, Vec<String>)>,
+) -> Result<String> {
+    let client = &context.client;
+    let transaction_opts = context.commit.transaction_opts(client)?;
+    let (mut claimed, mut failed) = (0, 0);
+    for (token, hotspots) in pending {
+        let entity_keys = hotspots
+            .into_iter()
+            .map(|hotspot| EncodedEntityKey {
+                entity_key: hotspot,
+                encoding: EntityKeyEncoding::B58,
+            })
+            .collect();
+        let mut results = std::pin::pin!(reward::claim_many(
+            client,
+            token,
+            entity_keys,
+            &context.keypair,
+            &transaction_opts,
+            Default::default(),
+        ));
+        while let Some(result) = results.next().await {
+            context.send(Message::Progress(format!(
+                "Claiming {} rewards {}/{}",
+                Token::from(token),
+                result.completed,
+                result.total
+            )));
+            match result.outcome {
+                ClaimOutcome::Ready { txn, amounts, .. } => {
+                    match context.commit.maybe_commit(txn, client).await {
+                        Ok(response) => {
+                            if let CommitResponse::Transaction(_) = response {
+                                for amount in &amounts {
+                                    metrics::reward_claimed(amount.token, Some(amount.amount));
+                                }
+                            }
+                            claimed += amounts.len();
+                        }
+                        Err(_) => failed += amounts.len(),
+                    }
+                }
+                ClaimOutcome::Skipped => (),
+                ClaimOutcome::Failed(_) => failed += result.entity_keys.len(),
+            }
+        }
+    }
+    let verb = if context.commit.commit {
+        "Claimed"
+    } else {
+        "Simulated"
+    };
+    Ok(format!("{verb} {claimed} claims, {failed} failed"))
+}
+
+async fn send_payment(context: &Context, payee: Pubkey, amount: TokenAmount) -> Result<String> {
+    let client = &context.client;
+    let transaction_opts = context.commit.transaction_opts(client)?;
+    let (tx, _) = token::transfer(
+        client,
+        &[(payee, amount)],
+        &context.keypair,
+        &transaction_opts,
+    )
+    .await?;
+    let amount = output::format_amount(&amount);
+    Ok(match context.commit.maybe_commit(tx, client).await? {
+        CommitResponse::Transaction(tracked) => {
+            format!("Paid {amount} to {payee}: {}", tracked.signature)
+        }
+        CommitResponse::PartiallySigned {
+            missing_signers, ..
+        } => format!(
+            "Payment needs the signatures of {}",
+            missing_signers
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        CommitResponse::None => format!("Simulated paying {amount} to {payee}"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pay_form() {
+        let address = Pubkey::new_unique();
+        let form = |amount: &str, token: &str| PayForm {
+            address: address.to_string(),
+            amount: amount.to_string(),
+            token: token.to_string(),
+            focus: 0,
+        };
+        assert_eq!(
+            (address, Token::Hnt.amount(150_000_000)),
+            form("1.5", "").payment().expect("payment")
+        );
+        assert_eq!(
+            (address, Token::Iot.amount(2_000_000)),
+            form(" 2 ", "IOT").payment().expect("payment")
+        );
+        assert!(form("0", "").payment().is_err());
+        assert!(form("1", "dc").payment().is_err());
+    }
+}
@@ -0,0 +1,82 @@
+use crate::cmd::*;
+use ratatui::crossterm::event;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+mod app;
+mod ui;
+
+use app::{App, Message};
+
+#[derive(Debug, clap::Args)]
+/// Interactive dashboard for the wallet
+///
+/// Shows the token balances of the wallet, its Hotspots with their pending
+/// rewards and its recent transactions, refreshed periodically. Use the arrow
+/// keys to select a Hotspot, ':' to open the action palette to claim rewards
+/// or send a payment, 'r' to refresh and 'q' to quit.
+///
+/// Claims and payments are only submitted with --commit. Without it the
+/// actions simulate their transactions.
+pub struct Cmd {
+    /// Time between refreshes of the dashboard, e.g. "30s" or "5m"
+    #[arg(long, default_value = "1m", value_parser = humantime::parse_duration)]
+    refresh: Duration,
+    /// Commit the claim and payment transactions.
+    #[command(flatten)]
+    commit: CommitOpts,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let password = get_wallet_password(false)?;
+        let keypair = opts.load_keypair(password.as_bytes())?;
+        let client = opts.client()?;
+        // Loads the fee payer, which may prompt for a password, before the
+        // terminal is taken over
+        self.commit.transaction_opts(&client)?;
+
+        let (sender, mut messages) = mpsc::unbounded_channel();
+        let mut app = App::new(client, keypair, self.commit.clone(), sender.clone());
+        let mut terminal = ratatui::try_init()?;
+        let _restore = RestoreTerminal;
+        std::thread::spawn(move || read_input(sender));
+
+        let mut refresh = tokio::time::interval(self.refresh);
+        refresh.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        while !app.quit {
+            terminal.draw(|frame| ui::draw(frame, &app))?;
+            tokio::select! {
+                _ = refresh.tick() => app.refresh(),
+                Some(message) = messages.recv() => app.handle(message),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Restores the terminal when the dashboard ends, including on errors
+struct RestoreTerminal;
+
+impl Drop for RestoreTerminal {
+    fn drop(&mut self) {
+        ratatui::restore();
+    }
+}
+
+/// Forward terminal events to the dashboard until it is gone. Polling with a
+/// timeout lets the thread notice a closed dashboard without further input.
+fn read_input(sender: mpsc::UnboundedSender<Message>) {
+    while !sender.is_closed() {
+        match event::poll(Duration::from_millis(250)) {
+            Ok(false) => continue,
+            Ok(true) => match event::read() {
+                Ok(event) => {
+                    let _ = sender.send(Message::Input(event));
+                }
+                Err(_) => return,
+            },
+            Err(_) => return,
+        }
+    }
+}
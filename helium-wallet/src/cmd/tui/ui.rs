@@ -0,0 +1,171 @@
+use super::app::{Action, App, Mode, PayForm};
+use crate::cmd::*;
+use chrono::DateTime;
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{Modifier, Style, Stylize},
+    text::Line,
+    widgets::{Block, Cell, Clear, List, ListItem, ListState, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+pub fn draw(frame: &mut Frame, app: &App) {
+    let [main, status] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+    let [left, hotspots] =
+        Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)]).areas(main);
+    let [balances, transactions] = Layout::vertical([
+        Constraint::Length(Token::all().len() as u16 + 2),
+        Constraint::Min(0),
+    ])
+    .areas(left);
+
+    draw_balances(frame, app, balances);
+    draw_transactions(frame, app, transactions);
+    draw_hotspots(frame, app, hotspots);
+    draw_status(frame, app, status);
+    match &app.mode {
+        Mode::Normal => (),
+        Mode::Palette(selected) => draw_palette(frame, *selected),
+        Mode::Pay(form) => draw_pay_form(frame, form),
+    }
+}
+
+fn draw_balances(frame: &mut Frame, app: &App, area: Rect) {
+    let rows = app.balances.iter().map(|balance| {
+        Row::new([
+            Cell::from(balance.token.to_string().to_uppercase()),
+            Cell::from(Line::from(output::format_amount(balance)).right_aligned()),
+        ])
+    });
+    let table = Table::new(rows, [Constraint::Length(8), Constraint::Min(0)])
+        .block(Block::bordered().title(format!(" Balances {} ", app.address())));
+    frame.render_widget(table, area);
+}
+
+fn draw_transactions(frame: &mut Frame, app: &App, area: Rect) {
+    let rows = app.transactions.iter().map(|transaction| {
+        let time = transaction
+            .block_time
+            .and_then(|time| DateTime::from_timestamp(time, 0))
+            .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        let status = match &transaction.error {
+            Some(_) => Cell::from("failed").red(),
+            None => Cell::from("ok").green(),
+        };
+        Row::new([time.into(), status, transaction.signature.clone().into()])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(16),
+            Constraint::Length(6),
+            Constraint::Min(0),
+        ],
+    )
+    .block(Block::bordered().title(" Recent transactions "));
+    frame.render_widget(table, area);
+}
+
+fn draw_hotspots(frame: &mut Frame, app: &App, area: Rect) {
+    let tokens = [Token::Iot, Token::Mobile, Token::Hnt];
+    let header = Row::new(
+        ["Name".to_string()]
+            .into_iter()
+            .chain(tokens.iter().map(|token| token.to_string().to_uppercase())),
+    )
+    .bold();
+    let rows = app.hotspots.iter().map(|row| {
+        Row::new(
+            [Cell::from(row.hotspot.name.clone())]
+                .into_iter()
+                .chain(tokens.iter().map(|token| {
+                    let pending = row.pending.get(token).copied().unwrap_or(token.amount(0));
+                    Cell::from(Line::from(output::format_amount(&pending)).right_aligned())
+                })),
+        )
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(20),
+            Constraint::Length(16),
+            Constraint::Length(16),
+            Constraint::Length(16),
+        ],
+    )
+    .header(header)
+    .block(Block::bordered().title(" Hotspots and pending rewards "))
+    .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+    let mut state =
+        TableState::default().with_selected((!app.hotspots.is_empty()).then_some(app.selected));
+    frame.render_stateful_widget(table, area, &mut state);
+}
+
+fn draw_status(frame: &mut Frame, app: &App, area: Rect) {
+    let mode = if app.committing() {
+        "commit".red()
+    } else {
+        "simulate".yellow()
+    };
+    let line = Line::from(vec![
+        " [".into(),
+        mode,
+        "] ".into(),
+        app.status.clone().into(),
+        "  (: actions, r refresh, q quit)".dim(),
+    ]);
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+fn draw_palette(frame: &mut Frame, selected: usize) {
+    let area = popup(frame.area(), 40, Action::ALL.len() as u16 + 2);
+    let items = Action::ALL
+        .iter()
+        .map(|action| ListItem::new(action.label()));
+    let list = List::new(items)
+        .block(Block::bordered().title(" Actions "))
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(
+        list,
+        area,
+        &mut ListState::default().with_selected(Some(selected)),
+    );
+}
+
+fn draw_pay_form(frame: &mut Frame, form: &PayForm) {
+    let area = popup(frame.area(), 60, PayForm::LABELS.len() as u16 + 2);
+    let lines: Vec<Line> = PayForm::LABELS
+        .iter()
+        .zip(form.fields())
+        .enumerate()
+        .map(|(index, (label, value))| {
+            let line = Line::from(format!("{label:>8}: {value}"));
+            if index == form.focus {
+                line.reversed()
+            } else {
+                line
+            }
+        })
+        .collect();
+    let paragraph = Paragraph::new(lines).block(
+        Block::bordered()
+            .title(" Pay ")
+            .title_bottom(" Tab next field, Enter send, Esc cancel "),
+    );
+    frame.render_widget(Clear, area);
+    frame.render_widget(paragraph, area);
+}
+
+/// A centered area of the given size within another
+fn popup(area: Rect, width: u16, height: u16) -> Rect {
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}
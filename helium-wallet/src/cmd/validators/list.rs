@@ -0,0 +1,51 @@
+use crate::cmd::*;
+use chrono::Utc;
+use helium_lib::{keypair::Pubkey, migration, positions};
+
+#[derive(Debug, clap::Args)]
+/// List the positions legacy validator stakes were converted into
+///
+/// At the Solana migration staked HNT, including validator stakes, was
+/// converted into veHNT genesis positions owned by the staking wallet. The
+/// migration was executed as a set of lazy transactions. Positions are
+/// matched to the executed migration transactions of the wallet, which are
+/// checked against the migration state on chain. The legacy validator
+/// addresses themselves are not recorded on chain.
+///
+/// This view is read only. Use the stake commands to manage the positions.
+pub struct Cmd {
+    /// Wallet address to list converted stakes for. Defaults to the active
+    /// wallet
+    address: Option<Pubkey>,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let client = opts.client()?;
+        let address = match self.address {
+            Some(address) => address,
+            None => opts.load_wallet()?.public_key,
+        };
+        let migrations = migration::for_wallet(&client, &address).await?;
+        let now = Utc::now();
+        let converted: Vec<serde_json::Value> = positions::for_owner(&client, &address)
+            .await?
+            .into_iter()
+            .filter_map(|position| {
+                let migration = migrations
+                    .iter()
+                    .find(|txn| txn.touches(&position.key) || txn.touches(&position.mint))?;
+                let mut json = json!(position);
+                json["migration"] = json!(migration);
+                json["genesis_multiplier_active"] =
+                    json!(position.genesis_end.is_some_and(|end| end > now));
+                json["unlocked"] = json!(position.lockup.end <= now);
+                Some(json)
+            })
+            .collect();
+        print_json(&json!({
+            "address": address.to_string(),
+            "positions": converted,
+        }))
+    }
+}
@@ -0,0 +1,29 @@
+use crate::cmd::*;
+
+pub mod list;
+
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    #[command(subcommand)]
+    cmd: ValidatorsCommand,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        self.cmd.run(opts).await
+    }
+}
+
+/// Inspect what legacy validator stakes were converted into
+#[derive(Debug, clap::Subcommand)]
+pub enum ValidatorsCommand {
+    List(list::Cmd),
+}
+
+impl ValidatorsCommand {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Self::List(cmd) => cmd.run(opts).await,
+        }
+    }
+}
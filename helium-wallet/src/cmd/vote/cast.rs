@@ -0,0 +1,87 @@
+use crate::cmd::*;
+use helium_lib::{
+    governance,
+    keypair::{Pubkey, Signer},
+    positions,
+    token::Token,
+};
+use rust_decimal::Decimal;
+
+#[derive(Debug, clap::Args)]
+/// Vote on an open proposal with all eligible veHNT positions
+///
+/// Every position of the wallet with voting power that has not yet voted for
+/// the choice, or for as many choices as the proposal allows per voter, is
+/// used. The voting weight applied by each position is
+/// reported along with the vote transactions.
+pub struct Cmd {
+    /// Address of the proposal to vote on
+    proposal: Pubkey,
+    /// Index of the choice to vote for, as shown by `vote list`
+    #[arg(long)]
+    choice: u16,
+    /// Commit the vote transactions
+    #[command(flatten)]
+    commit: CommitOpts,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let password = get_wallet_password(false)?;
+        let keypair = opts.load_keypair(password.as_bytes())?;
+        let client = opts.client()?;
+        let transaction_opts = self.commit.transaction_opts(&client)?;
+
+        let proposal = governance::get(&client, &self.proposal).await?;
+        let Some(choice) = proposal.choices.get(self.choice as usize) else {
+            bail!("proposal {} has no choice {}", self.proposal, self.choice);
+        };
+        if !proposal.is_open() {
+            bail!("proposal {} is not open for voting", self.proposal);
+        }
+        let (_, _, weights) = positions::hnt_registrar(&client).await?;
+        // Only HNT positions carry veHNT voting power
+        let owned: Vec<positions::Position> = positions::for_owner(&client, &keypair.pubkey())
+            .await?
+            .into_iter()
+            .filter(|position| position.amount.token == Token::Hnt)
+            .collect();
+        let votes =
+            governance::eligible_votes(&client, &proposal, self.choice, &owned, &weights).await?;
+        if votes.is_empty() {
+            bail!(
+                "no positions eligible to vote for this choice, positions can vote for at most {} choice(s) on this proposal",
+                proposal.max_choices_per_voter
+            );
+        }
+
+        let mut results = vec![];
+        for batch in votes.chunks(governance::MAX_VOTES_PER_TXN) {
+            let batch_positions: Vec<&positions::Position> =
+                batch.iter().map(|(position, _)| *position).collect();
+            let (tx, _) = governance::vote(
+                &client,
+                &self.proposal,
+                self.choice,
+                &batch_positions,
+                &keypair,
+                &transaction_opts,
+            )
+            .await?;
+            let mut json = self.commit.maybe_commit(tx, &client).await.to_json();
+            json["votes"] = json!(batch.iter().map(|(_, vote)| vote).collect::<Vec<_>>());
+            results.push(json);
+        }
+        let weight: Decimal = votes.iter().map(|(_, vote)| vote.weight).sum();
+        print_json(&json!({
+            "proposal": proposal.address.to_string(),
+            "name": proposal.name,
+            "choice": {
+                "index": choice.index,
+                "name": choice.name,
+            },
+            "weight": weight,
+            "transactions": results,
+        }))
+    }
+}
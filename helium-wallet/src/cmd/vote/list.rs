@@ -0,0 +1,50 @@
+use crate::cmd::*;
+use helium_lib::governance;
+
+#[derive(Debug, clap::Args)]
+/// List governance proposals
+///
+/// By default only proposals that are open for voting are listed.
+pub struct Cmd {
+    /// Include proposals that are not open for voting
+    #[arg(long)]
+    all: bool,
+    /// Name of the organization to list proposals for
+    #[arg(long, default_value = governance::HELIUM_ORGANIZATION)]
+    org: String,
+    /// Output format
+    #[arg(long, value_enum, default_value_t)]
+    format: OutputFormat,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let client = opts.client()?;
+        let proposals: Vec<governance::Proposal> =
+            governance::proposals(&client, &governance::organization_key(&self.org))
+                .await?
+                .into_iter()
+                .filter(|proposal| self.all || proposal.is_open())
+                .collect();
+
+        match self.format {
+            OutputFormat::Json => print_json(&proposals),
+            OutputFormat::Table | OutputFormat::Csv => {
+                let mut table = prettytable::Table::new();
+                table.set_format(*prettytable::format::consts::FORMAT_CLEAN);
+                table.set_titles(prettytable::row!["Proposal", "Name", "Choice", "veHNT"]);
+                for proposal in &proposals {
+                    for choice in &proposal.choices {
+                        table.add_row(prettytable::row![
+                            proposal.address,
+                            proposal.name,
+                            format!("{}: {}", choice.index, choice.name),
+                            choice.weight.round_dp(2)
+                        ]);
+                    }
+                }
+                print_rows(&table, self.format)
+            }
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use crate::cmd::*;
+
+pub mod cast;
+pub mod list;
+
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    #[command(subcommand)]
+    cmd: VoteCommand,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        self.cmd.run(opts).await
+    }
+}
+
+/// List and vote on governance proposals with veHNT positions
+#[derive(Debug, clap::Subcommand)]
+pub enum VoteCommand {
+    List(list::Cmd),
+    Cast(cast::Cmd),
+}
+
+impl VoteCommand {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Self::List(cmd) => cmd.run(opts).await,
+            Self::Cast(cmd) => cmd.run(opts).await,
+        }
+    }
+}
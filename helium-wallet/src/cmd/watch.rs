@@ -0,0 +1,236 @@
+use crate::cmd::*;
+use chrono::Utc;
+use futures::{future, stream, StreamExt};
+use helium_lib::{
+    keypair::{Pubkey, Signature},
+    solana_account_decoder::UiAccountEncoding,
+    solana_client::{
+        nonblocking::pubsub_client::PubsubClient,
+        rpc_config::{RpcAccountInfoConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter},
+    },
+    solana_sdk::account::Account,
+    token,
+    tx::outcome::{self, Outcome},
+    watch::{self, Balances},
+};
+use std::str::FromStr;
+
+#[derive(Debug, clap::Args)]
+/// Watch addresses for payments, Hotspot transfers and Data Credit delegations
+///
+/// The given addresses, or the wallet address if none are given, are followed
+/// over the RPC node's websocket endpoint and a JSON line is printed for every
+/// event until interrupted. Token payments are seen as changes to the token
+/// accounts of an address, other events are decoded from the transactions
+/// that mention it.
+///
+/// With --alert-below a low_balance event is printed whenever the SOL balance
+/// of an address, which pays for transaction fees, drops below the threshold.
+/// Events are posted to the webhook if given.
+pub struct Cmd {
+    /// Address(es) to watch
+    #[arg(short = 'a', long = "address", number_of_values(1))]
+    addresses: Vec<Pubkey>,
+    /// Websocket url of the RPC node. Defaults to the websocket endpoint of
+    /// the RPC url in use.
+    #[arg(long)]
+    ws_url: Option<String>,
+    /// Alert when the SOL balance of an address drops below this amount
+    #[arg(long)]
+    alert_below: Option<f64>,
+    #[command(flatten)]
+    webhook: WebhookOpts,
+    #[command(flatten)]
+    metrics: MetricsOpts,
+}
+
+enum Update {
+    Account {
+        address: Pubkey,
+        token: Token,
+        token_account: Pubkey,
+        slot: u64,
+        account: Option<Account>,
+    },
+    Transaction {
+        address: Pubkey,
+        slot: u64,
+        signature: String,
+    },
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let addresses = if self.addresses.is_empty() {
+            vec![opts.load_wallet()?.public_key]
+        } else {
+            self.addresses.clone()
+        };
+        let client = opts.client()?;
+        self.metrics.serve().await?;
+        let token_accounts = addresses
+            .iter()
+            .flat_map(|address| {
+                Token::all()
+                    .into_iter()
+                    .map(move |token| (*address, token, token.associated_token_adress(address)))
+            })
+            .collect::<Vec<_>>();
+        let mut balances = Balances::new(
+            token::balance_for_addresses(
+                &client,
+                &token_accounts
+                    .iter()
+                    .map(|(_, _, token_account)| *token_account)
+                    .collect::<Vec<_>>(),
+            )
+            .await?,
+        );
+
+        let ws_url = self
+            .ws_url
+            .clone()
+            .unwrap_or_else(|| client::websocket_url(&client.solana_client.url()));
+        let pubsub = PubsubClient::new(&ws_url).await?;
+        let mut subscriptions = vec![];
+        let mut unsubscribes = vec![];
+        for (address, token, token_account) in token_accounts {
+            let (updates, unsubscribe) = pubsub
+                .account_subscribe(
+                    &token_account,
+                    Some(RpcAccountInfoConfig {
+                        encoding: Some(UiAccountEncoding::Base64),
+                        commitment: Some(client.solana_client.commitment()),
+                        ..Default::default()
+                    }),
+                )
+                .await?;
+            subscriptions.push(
+                updates
+                    .map(move |update| Update::Account {
+                        address,
+                        token,
+                        token_account,
+                        slot: update.context.slot,
+                        account: update.value.decode::<Account>(),
+                    })
+                    .boxed(),
+            );
+            unsubscribes.push(unsubscribe);
+        }
+        for address in addresses {
+            let (updates, unsubscribe) = pubsub
+                .logs_subscribe(
+                    RpcTransactionLogsFilter::Mentions(vec![address.to_string()]),
+                    RpcTransactionLogsConfig {
+                        commitment: Some(client.solana_client.commitment()),
+                    },
+                )
+                .await?;
+            subscriptions.push(
+                updates
+                    .filter_map(move |update| {
+                        future::ready(update.value.err.is_none().then(|| Update::Transaction {
+                            address,
+                            slot: update.context.slot,
+                            signature: update.value.signature,
+                        }))
+                    })
+                    .boxed(),
+            );
+            unsubscribes.push(unsubscribe);
+        }
+
+        let webhook = self.webhook.webhook()?;
+        let alert_below = self
+            .alert_below
+            .map(|amount| TokenAmount::from_f64(Token::Sol, amount));
+        let mut updates = stream::select_all(subscriptions);
+        loop {
+            let update = tokio::select! {
+                _ = tokio::signal::ctrl_c() => break,
+                update = updates.next() => update,
+            };
+            let Some(update) = update else {
+                bail!("watch subscription closed");
+            };
+            match update {
+                Update::Account {
+                    address,
+                    token,
+                    token_account,
+                    slot,
+                    account,
+                } => {
+                    let previous = balances.get(&token_account);
+                    if let Some(event) = balances.update(&token_account, token, account)? {
+                        let event = serde_json::to_value(event)?;
+                        notify(&webhook, &address, slot, None, event).await?;
+                    }
+                    let balance = balances.get(&token_account);
+                    if let (Token::Sol, Some(threshold), Some(balance)) =
+                        (token, alert_below, balance)
+                    {
+                        let was_below = previous.is_some_and(|previous| previous < threshold);
+                        if balance < threshold && !was_below {
+                            let event = json!({
+                                "type": "low_balance",
+                                "balance": balance,
+                                "threshold": threshold,
+                            });
+                            notify(&webhook, &address, slot, None, event).await?;
+                        }
+                    }
+                }
+                Update::Transaction {
+                    address,
+                    slot,
+                    signature,
+                } => {
+                    // A transaction that can not be decoded should not end
+                    // the watch
+                    match outcomes(&client, &signature).await {
+                        Ok(outcomes) => {
+                            for event in watch::outcome_events(&address, outcomes) {
+                                let event = serde_json::to_value(event)?;
+                                notify(&webhook, &address, slot, Some(&signature), event).await?;
+                            }
+                        }
+                        Err(err) => eprintln!("failed to decode transaction {signature}: {err}"),
+                    }
+                }
+            }
+        }
+        for unsubscribe in unsubscribes {
+            unsubscribe().await;
+        }
+        Ok(())
+    }
+}
+
+async fn outcomes(client: &client::Client, signature: &str) -> Result<Vec<Outcome>> {
+    let signature = Signature::from_str(signature)?;
+    Ok(outcome::get(client, &signature).await?)
+}
+
+/// Print an event and post it to the webhook, if any
+async fn notify(
+    webhook: &Option<Webhook>,
+    address: &Pubkey,
+    slot: u64,
+    signature: Option<&str>,
+    event: serde_json::Value,
+) -> Result {
+    let payload = json!({
+        "timestamp": Utc::now(),
+        "address": address.to_string(),
+        "slot": slot,
+        "signature": signature,
+        "event": event,
+    });
+    println!("{}", serde_json::to_string(&payload)?);
+    if let Some(webhook) = webhook {
+        webhook.notify(&payload).await;
+    }
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use crate::cmd::*;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::time::Duration;
+
+/// Environment variable with the secret to sign webhook payloads with
+pub const WEBHOOK_SECRET_ENV: &str = "HELIUM_WALLET_WEBHOOK_SECRET";
+/// Header carrying the hex encoded HMAC-SHA256 of the payload
+pub const SIGNATURE_HEADER: &str = "X-Helium-Signature-256";
+/// Time a webhook has to answer before the notification is given up on, so a
+/// hanging endpoint does not stall the command posting to it
+pub const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, clap::Args)]
+pub struct WebhookOpts {
+    /// URL to post JSON notifications to. Payloads are signed when the
+    /// HELIUM_WALLET_WEBHOOK_SECRET environment variable is set.
+    #[arg(long)]
+    webhook: Option<String>,
+}
+
+impl WebhookOpts {
+    pub fn webhook(&self) -> Result<Option<Webhook>> {
+        self.webhook.as_deref().map(Webhook::new).transpose()
+    }
+}
+
+/// Posts notifications of long running commands to a webhook.
+///
+/// With a secret every payload carries a `sha256=<hex>` HMAC-SHA256 signature
+/// of the request body in the `X-Helium-Signature-256` header, so receivers
+/// can check it came from this wallet.
+pub struct Webhook {
+    client: reqwest::Client,
+    url: String,
+    secret: Option<String>,
+}
+
+impl Webhook {
+    pub fn new(url: &str) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()?;
+        Ok(Self {
+            client,
+            url: url.to_string(),
+            secret: env::var(WEBHOOK_SECRET_ENV).ok(),
+        })
+    }
+
+    /// Post a payload. A failed notification is reported on stderr rather
+    /// than ending the command.
+    pub async fn notify(&self, payload: &serde_json::Value) {
+        let body = payload.to_string();
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        if let Some(secret) = &self.secret {
+            request = request.header(SIGNATURE_HEADER, sign(secret, body.as_bytes()));
+        }
+        if let Err(err) = request
+            .body(body)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+        {
+            eprintln!("webhook notification failed: {err}");
+        }
+    }
+}
+
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac accepts any key length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            sign("Jefe", b"what do ya want for nothing?")
+        );
+    }
+}
@@ -0,0 +1,173 @@
+//! Stable error codes for scripts driving the wallet.
+//!
+//! Every failure is sorted into an [`ErrorCode`], which is the exit status of
+//! the process and the `code` of errors in JSON output. Codes and their exit
+//! statuses are only ever added to, so scripts can rely on them across
+//! releases.
+
+use crate::result::Error;
+use helium_lib::{
+    error::{Error as LibError, TransactionError},
+    solana_client::client_error::{ClientError, ClientErrorKind},
+    solana_sdk::transaction::TransactionError as SolanaTransactionError,
+};
+pub use helium_wallet_format::DecryptError;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// Any failure not covered by a more specific code
+    Other,
+    /// An account can not cover the transfer, fee or rent of a transaction
+    InsufficientFunds,
+    /// A transaction expired before it landed
+    BlockhashExpired,
+    /// The Solana RPC or another service could not be reached or is
+    /// overloaded
+    RpcUnavailable,
+    /// The wallet could not be decrypted with the given password
+    BadPassword,
+    /// A transaction failed in simulation, in preflight or on chain
+    SimulationFailed,
+    /// The command did not complete within its --deadline
+    Timeout,
+    /// A transaction would exceed the --fee-budget
+    FeeBudgetExceeded,
+}
+
+impl ErrorCode {
+    /// The code of an error, from the first error in its chain that has a
+    /// known cause
+    pub fn of(err: &Error) -> Self {
+        err.chain()
+            .find_map(|cause| {
+                if cause.is::<DecryptError>() {
+                    return Some(Self::BadPassword);
+                }
+                if let Some(err) = cause.downcast_ref::<LibError>() {
+                    return Some(Self::of_lib(err));
+                }
+                if let Some(err) = cause.downcast_ref::<ClientError>() {
+                    return Self::of_client(err);
+                }
+                cause
+                    .downcast_ref::<reqwest::Error>()
+                    .and_then(Self::of_reqwest)
+            })
+            .unwrap_or(Self::Other)
+    }
+
+    pub fn of_lib(err: &LibError) -> Self {
+        match err {
+            LibError::Transaction(err) => Self::of_transaction(err),
+            LibError::TransactionExpired(_) => Self::BlockhashExpired,
+            LibError::Timeout(_) => Self::Timeout,
+            LibError::FeeBudgetExceeded { .. } => Self::FeeBudgetExceeded,
+            LibError::Solana(err) => Self::of_client(err).unwrap_or(Self::Other),
+            LibError::Rest(err) => Self::of_reqwest(err).unwrap_or(Self::Other),
+            _ => Self::Other,
+        }
+    }
+
+    fn of_transaction(err: &TransactionError) -> Self {
+        let insufficient = err.logs.iter().any(|log| {
+            let log = log.to_lowercase();
+            log.contains("insufficient funds") || log.contains("insufficient lamports")
+        });
+        match err.error {
+            SolanaTransactionError::InsufficientFundsForFee
+            | SolanaTransactionError::InsufficientFundsForRent { .. } => Self::InsufficientFunds,
+            SolanaTransactionError::BlockhashNotFound => Self::BlockhashExpired,
+            _ if insufficient => Self::InsufficientFunds,
+            _ => Self::SimulationFailed,
+        }
+    }
+
+    fn of_client(err: &ClientError) -> Option<Self> {
+        match err.kind() {
+            ClientErrorKind::Io(_) => Some(Self::RpcUnavailable),
+            ClientErrorKind::Reqwest(err) => Self::of_reqwest(err),
+            ClientErrorKind::TransactionError(err) => Some(match err {
+                SolanaTransactionError::InsufficientFundsForFee
+                | SolanaTransactionError::InsufficientFundsForRent { .. } => {
+                    Self::InsufficientFunds
+                }
+                SolanaTransactionError::BlockhashNotFound => Self::BlockhashExpired,
+                _ => Self::SimulationFailed,
+            }),
+            _ => None,
+        }
+    }
+
+    fn of_reqwest(err: &reqwest::Error) -> Option<Self> {
+        let overloaded = err
+            .status()
+            .is_some_and(|status| status.is_server_error() || status.as_u16() == 429);
+        (err.is_connect() || err.is_timeout() || overloaded).then_some(Self::RpcUnavailable)
+    }
+
+    /// The exit status of the process for this code
+    pub fn exit_code(self) -> i32 {
+        // 2 is the exit status of invalid command line arguments
+        match self {
+            Self::Other => 1,
+            Self::InsufficientFunds => 3,
+            Self::BlockhashExpired => 4,
+            Self::RpcUnavailable => 5,
+            Self::BadPassword => 6,
+            Self::SimulationFailed => 7,
+            Self::Timeout => 8,
+            Self::FeeBudgetExceeded => 9,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Other => "other",
+            Self::InsufficientFunds => "insufficient_funds",
+            Self::BlockhashExpired => "blockhash_expired",
+            Self::RpcUnavailable => "rpc_unavailable",
+            Self::BadPassword => "bad_password",
+            Self::SimulationFailed => "simulation_failed",
+            Self::Timeout => "timeout",
+            Self::FeeBudgetExceeded => "fee_budget_exceeded",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes() {
+        let err = Error::from(DecryptError).context("loading wallet");
+        assert_eq!(ErrorCode::BadPassword, ErrorCode::of(&err));
+        assert_eq!(6, ErrorCode::of(&err).exit_code());
+
+        let expired = TransactionError::new(SolanaTransactionError::BlockhashNotFound, vec![]);
+        assert_eq!(
+            ErrorCode::BlockhashExpired,
+            ErrorCode::of(&LibError::from(expired).into())
+        );
+        let failed = TransactionError::new(
+            SolanaTransactionError::AccountNotFound,
+            vec!["Program log: Error: insufficient funds".to_string()],
+        );
+        assert_eq!(
+            ErrorCode::InsufficientFunds,
+            ErrorCode::of(&LibError::from(failed).into())
+        );
+        assert_eq!(
+            ErrorCode::Other,
+            ErrorCode::of(&crate::result::anyhow!("other"))
+        );
+    }
+}
@@ -1,7 +1,8 @@
+pub mod audit;
 pub mod cmd;
-pub mod format;
-pub mod pwhash;
-pub mod read_write;
+pub mod error_code;
+pub mod remote_signer;
 pub mod result;
 pub mod txn_envelope;
-pub mod wallet;
+
+pub use helium_wallet_format::{format, pwhash, read_write, wallet};
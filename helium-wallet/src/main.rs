@@ -1,9 +1,11 @@
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches};
 use helium_wallet::{
     cmd::{
-        assets, balance, burn, create, dc, export, hotspots, info, memo, price, router, sign,
-        transfer, upgrade, Opts,
+        self, accounts, assets, audit, balance, boost, burn, cache, create, dao, dc, entity_key,
+        export, hotspots, info, maker, memo, oracle, oui, price, rewards, router, serve, sign,
+        signer, stake, stats, transfer, treasury, tui, upgrade, validators, vote, watch, Opts,
     },
+    error_code::ErrorCode,
     result::Result,
 };
 
@@ -13,7 +15,7 @@ fn init() {
     START.call_once(|| sodiumoxide::init().expect("Failed to intialize sodium"))
 }
 
-#[derive(Debug, Parser)]
+#[derive(Debug, clap::Parser)]
 #[command(version = env!("CARGO_PKG_VERSION"))]
 #[command(name = env!("CARGO_BIN_NAME"))]
 pub struct Cli {
@@ -40,21 +42,114 @@ pub enum Cmd {
     Sign(sign::Cmd),
     Memo(memo::Cmd),
     Assets(assets::Cmd),
+    Audit(audit::Cmd),
+    Accounts(accounts::Cmd),
+    Rewards(rewards::Cmd),
+    Oracle(oracle::Cmd),
+    Oui(oui::Cmd),
+    Maker(maker::Cmd),
+    Stats(stats::Cmd),
+    Stake(stake::Cmd),
+    Vote(vote::Cmd),
+    Dao(dao::Cmd),
+    Treasury(treasury::Cmd),
+    Boost(boost::Cmd),
+    Validators(validators::Cmd),
+    Cache(cache::Cmd),
+    EntityKey(entity_key::Cmd),
+    Serve(serve::Cmd),
+    Signer(signer::Cmd),
+    Watch(watch::Cmd),
+    Tui(tui::Cmd),
 }
 
 #[allow(clippy::needless_return)]
 #[tokio::main]
 async fn main() -> Result {
     init();
-    let cli = Cli::parse();
-    cli.run().await
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    if cli.opts.verbose > 0 || cli.opts.log_json {
+        init_tracing(cli.opts.verbose, cli.opts.log_json);
+    }
+    let quiet = cli.opts.quiet;
+    cmd::output::set_quiet(quiet);
+    cmd::output::init_color(cli.opts.no_color || cli.opts.output.is_some());
+    if let Some(path) = &cli.opts.audit_log {
+        let wallet = cli.opts.load_wallet().ok().map(|wallet| wallet.public_key);
+        helium_wallet::audit::init(path.clone(), command_name(&matches), wallet);
+    }
+    let output = cli.opts.output.clone();
+    if output.is_some() {
+        cmd::output::capture_output();
+    }
+    let result = match cli.opts.deadline {
+        Some(deadline) => helium_lib::client::with_deadline(deadline, cli.run()).await,
+        None => cli.run().await,
+    };
+    // Output of a failed or timed out command is kept too, like the
+    // transactions that were sent before the failure
+    let result = match &output {
+        Some(path) => cmd::output::write_captured_output(path).and(result),
+        None => result,
+    };
+    if let Err(err) = result {
+        let code = ErrorCode::of(&err);
+        if quiet {
+            let error = serde_json::json!({"code": code, "message": format!("{err:#}")});
+            eprintln!("{}", serde_json::json!({ "error": error }));
+        } else {
+            eprintln!("Error ({code}): {err:?}");
+        }
+        std::process::exit(code.exit_code());
+    }
+    Ok(())
+}
+
+/// The subcommand names of a command line, like "rewards claim"
+fn command_name(matches: &clap::ArgMatches) -> String {
+    std::iter::successors(matches.subcommand(), |(_, matches)| matches.subcommand())
+        .map(|(name, _)| name)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn init_tracing(verbose: u8, json: bool) {
+    use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
+    let level = match verbose {
+        0 | 1 => "info",
+        2 => "debug",
+        _ => "trace",
+    };
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| {
+        EnvFilter::new(format!("warn,helium_lib={level},helium_wallet={level}"))
+    });
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr);
+    if json {
+        builder.json().init();
+    } else {
+        builder.init();
+    }
 }
 
 impl Cli {
     async fn run(self) -> Result {
         let client = self.opts.client()?;
         helium_lib::init(client.solana_client)?;
-        match self.cmd {
+        helium_lib::asset::proof::set_cache_enabled(!self.opts.no_cache);
+        // Clearing the cache must not write it back afterwards
+        let kta_cache_path = (!self.opts.no_cache && !matches!(self.cmd, Cmd::Cache(_)))
+            .then(|| self.opts.kta_cache_path())
+            .flatten();
+        if let Some(path) = &kta_cache_path {
+            if let Err(err) = helium_lib::kta::load(path, self.opts.cache_ttl) {
+                tracing::debug!(%err, "ignoring unreadable kta cache");
+            }
+        }
+        let result = match self.cmd {
             Cmd::Info(cmd) => cmd.run(self.opts).await,
             Cmd::Balance(cmd) => cmd.run(self.opts).await,
             Cmd::Upgrade(cmd) => cmd.run(self.opts).await,
@@ -69,6 +164,31 @@ impl Cli {
             Cmd::Sign(cmd) => cmd.run(self.opts).await,
             Cmd::Memo(cmd) => cmd.run(self.opts).await,
             Cmd::Assets(cmd) => cmd.run(self.opts).await,
+            Cmd::Audit(cmd) => cmd.run(self.opts).await,
+            Cmd::Accounts(cmd) => cmd.run(self.opts).await,
+            Cmd::Rewards(cmd) => cmd.run(self.opts).await,
+            Cmd::Oracle(cmd) => cmd.run(self.opts).await,
+            Cmd::Oui(cmd) => cmd.run(self.opts).await,
+            Cmd::Maker(cmd) => cmd.run(self.opts).await,
+            Cmd::Stats(cmd) => cmd.run(self.opts).await,
+            Cmd::Stake(cmd) => cmd.run(self.opts).await,
+            Cmd::Vote(cmd) => cmd.run(self.opts).await,
+            Cmd::Dao(cmd) => cmd.run(self.opts).await,
+            Cmd::Treasury(cmd) => cmd.run(self.opts).await,
+            Cmd::Boost(cmd) => cmd.run(self.opts).await,
+            Cmd::Validators(cmd) => cmd.run(self.opts).await,
+            Cmd::Cache(cmd) => cmd.run(self.opts).await,
+            Cmd::EntityKey(cmd) => cmd.run(self.opts).await,
+            Cmd::Serve(cmd) => cmd.run(self.opts).await,
+            Cmd::Signer(cmd) => cmd.run(self.opts).await,
+            Cmd::Watch(cmd) => cmd.run(self.opts).await,
+            Cmd::Tui(cmd) => cmd.run(self.opts).await,
+        };
+        if let Some(path) = &kta_cache_path {
+            if let Err(err) = helium_lib::kta::save(path) {
+                tracing::debug!(%err, "failed to save kta cache");
+            }
         }
+        result
     }
 }
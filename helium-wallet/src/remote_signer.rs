@@ -0,0 +1,402 @@
+//! A signer that keeps the wallet on a separate host and signs over gRPC.
+//!
+//! The server side is `helium-wallet signer serve`, which checks every
+//! transaction against a [`Policy`] before signing it. Other wallet
+//! instances use [`RemoteSigner`] to request signatures with `--signer-url`.
+
+use crate::result::{anyhow, bail, Result};
+use helium_lib::{
+    anchor_spl::{self, token::spl_token::instruction::TokenInstruction},
+    circuit_breaker, data_credits, helium_entity_manager, helium_sub_daos, hexboosting,
+    iot_routing_manager,
+    keypair::{pubkey, Pubkey, Signature},
+    lazy_distributor, mobile_entity_manager, proposal, rewards_oracle,
+    solana_sdk::{
+        compute_budget, system_instruction::SystemInstruction, system_program,
+        transaction::VersionedTransaction,
+    },
+    token::Token,
+    treasury_management, voter_stake_registry,
+};
+use std::collections::{HashMap, HashSet};
+use tonic::{metadata::MetadataValue, transport::Channel, Request};
+
+pub mod proto {
+    tonic::include_proto!("helium.wallet.signer");
+}
+
+use proto::remote_signer_client::RemoteSignerClient;
+
+/// Environment variable holding the bearer token for the remote signer
+pub const AUTH_TOKEN_ENV: &str = "HELIUM_WALLET_SIGNER_TOKEN";
+
+const BUBBLEGUM_PROGRAM_ID: Pubkey = pubkey!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+const MEMO_PROGRAM_ID: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+/// Connection to a remote signer
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    client: RemoteSignerClient<Channel>,
+    auth_token: Option<MetadataValue<tonic::metadata::Ascii>>,
+    pubkey: Pubkey,
+}
+
+impl RemoteSigner {
+    /// Connect to the signer at the given URL and fetch its public key
+    pub async fn connect(url: &str, auth_token: Option<&str>) -> Result<Self> {
+        let client = RemoteSignerClient::connect(url.to_string()).await?;
+        let auth_token = auth_token
+            .map(|token| format!("Bearer {token}").parse())
+            .transpose()
+            .map_err(|_| anyhow!("invalid remote signer auth token"))?;
+        let mut signer = Self {
+            client,
+            auth_token,
+            pubkey: Pubkey::default(),
+        };
+        let response = signer
+            .client
+            .clone()
+            .public_key(signer.request(proto::PublicKeyReq {}))
+            .await?
+            .into_inner();
+        signer.pubkey = Pubkey::try_from(response.pubkey.as_slice())
+            .map_err(|_| anyhow!("invalid public key from remote signer"))?;
+        Ok(signer)
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    fn request<T>(&self, message: T) -> Request<T> {
+        let mut request = Request::new(message);
+        if let Some(auth_token) = &self.auth_token {
+            request
+                .metadata_mut()
+                .insert("authorization", auth_token.clone());
+        }
+        request
+    }
+
+    pub async fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        let response = self
+            .client
+            .clone()
+            .sign_message(self.request(proto::SignMessageReq {
+                message: message.to_vec(),
+            }))
+            .await?
+            .into_inner();
+        Signature::try_from(response.signature.as_slice())
+            .map_err(|_| anyhow!("invalid signature from remote signer"))
+    }
+
+    /// Have the remote signer add its signature to the given transaction
+    pub async fn sign_transaction(
+        &self,
+        tx: &VersionedTransaction,
+    ) -> Result<VersionedTransaction> {
+        let response = self
+            .client
+            .clone()
+            .sign_transaction(self.request(proto::SignTransactionReq {
+                transaction: bincode::serialize(tx)?,
+            }))
+            .await?
+            .into_inner();
+        let signed: VersionedTransaction = bincode::deserialize(&response.transaction)?;
+        if signed.message != tx.message {
+            bail!("remote signer returned a different transaction");
+        }
+        Ok(signed)
+    }
+}
+
+/// What a remote signer is willing to sign.
+///
+/// Token delegations and authority changes by the signer are never signed,
+/// since they would hand its tokens or accounts to someone else outside of
+/// any limit.
+#[derive(Debug, Clone)]
+pub struct Policy {
+    /// Programs that transactions may invoke
+    pub allowed_programs: HashSet<Pubkey>,
+    /// Maximum lamports a transaction may move out of the signer with system
+    /// transfers and account creations. Unlimited if not set.
+    pub max_transfer_lamports: Option<u64>,
+    /// Maximum amount of a token, in its smallest unit, a transaction may
+    /// move out of the token accounts of the signer. Tokens without a
+    /// maximum are unlimited. When any maximum is set, token transfers that
+    /// can not be attributed to a Helium token are refused.
+    pub max_token_transfers: HashMap<Token, u64>,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            allowed_programs: Self::default_programs().into_iter().collect(),
+            max_transfer_lamports: None,
+            max_token_transfers: HashMap::new(),
+        }
+    }
+}
+
+impl Policy {
+    /// The system, token and compute budget programs and the Helium
+    /// programs used by the wallet
+    pub fn default_programs() -> Vec<Pubkey> {
+        vec![
+            system_program::id(),
+            compute_budget::id(),
+            anchor_spl::token::ID,
+            anchor_spl::associated_token::ID,
+            MEMO_PROGRAM_ID,
+            BUBBLEGUM_PROGRAM_ID,
+            circuit_breaker::id(),
+            data_credits::id(),
+            helium_entity_manager::id(),
+            helium_sub_daos::id(),
+            hexboosting::id(),
+            iot_routing_manager::id(),
+            lazy_distributor::id(),
+            mobile_entity_manager::id(),
+            proposal::id(),
+            rewards_oracle::id(),
+            treasury_management::id(),
+            voter_stake_registry::id(),
+        ]
+    }
+
+    /// Check that a transaction may be signed by the given signer
+    pub fn check(&self, tx: &VersionedTransaction, signer: &Pubkey) -> Result<()> {
+        let keys = tx.message.static_account_keys();
+        let required = tx.message.header().num_required_signatures as usize;
+        if !keys[..required.min(keys.len())].contains(signer) {
+            bail!("{signer} is not a required signer");
+        }
+        let mut transferred: u64 = 0;
+        let mut tokens_transferred: HashMap<Token, u64> = HashMap::new();
+        for ix in tx.message.instructions() {
+            let program_id = keys
+                .get(ix.program_id_index as usize)
+                .ok_or_else(|| anyhow!("invalid program index"))?;
+            if !self.allowed_programs.contains(program_id) {
+                bail!("program {program_id} is not allowed");
+            }
+            // Accounts loaded from lookup tables are not resolved and never
+            // match the signer, which has to be a static key to sign
+            let account = |position: usize| {
+                ix.accounts
+                    .get(position)
+                    .and_then(|index| keys.get(*index as usize))
+            };
+            if *program_id == system_program::id() {
+                let (lamports, source_index) = match bincode::deserialize(&ix.data) {
+                    Ok(SystemInstruction::Transfer { lamports })
+                    | Ok(SystemInstruction::CreateAccount { lamports, .. })
+                    | Ok(SystemInstruction::CreateAccountWithSeed { lamports, .. }) => {
+                        (lamports, 0)
+                    }
+                    Ok(SystemInstruction::TransferWithSeed { lamports, .. }) => (lamports, 1),
+                    Ok(SystemInstruction::Assign { .. }) if account(0) == Some(signer) => {
+                        bail!("assigning {signer} to a program is not allowed")
+                    }
+                    _ => continue,
+                };
+                if account(source_index) == Some(signer) {
+                    transferred = transferred.saturating_add(lamports);
+                }
+            } else if *program_id == anchor_spl::token::ID {
+                // The amount moved, the mint if the instruction names it and
+                // the position of the authority
+                let (amount, mint, authority_index) = match TokenInstruction::unpack(&ix.data) {
+                    Ok(TokenInstruction::Transfer { amount }) => (amount, None, 2),
+                    Ok(TokenInstruction::TransferChecked { amount, .. }) => (amount, account(1), 3),
+                    Ok(TokenInstruction::Approve { .. }) if account(2) == Some(signer) => {
+                        bail!("token delegations by {signer} are not allowed")
+                    }
+                    Ok(TokenInstruction::ApproveChecked { .. }) if account(3) == Some(signer) => {
+                        bail!("token delegations by {signer} are not allowed")
+                    }
+                    Ok(TokenInstruction::SetAuthority { .. }) if account(1) == Some(signer) => {
+                        bail!("token authority changes by {signer} are not allowed")
+                    }
+                    _ => continue,
+                };
+                if account(authority_index) != Some(signer) {
+                    continue;
+                }
+                match transferred_token(signer, account(0), mint) {
+                    Some(token) => {
+                        let total = tokens_transferred.entry(token).or_default();
+                        *total = total.saturating_add(amount);
+                    }
+                    None if !self.max_token_transfers.is_empty() => {
+                        bail!("token transfer by {signer} is not of a known token account")
+                    }
+                    None => (),
+                }
+            }
+        }
+        if let Some(max) = self.max_transfer_lamports {
+            if transferred > max {
+                bail!("transfers of {transferred} lamports exceed the maximum of {max}")
+            }
+        }
+        for (token, transferred) in tokens_transferred {
+            match self.max_token_transfers.get(&token) {
+                Some(max) if transferred > *max => {
+                    bail!("transfers of {transferred} {token} exceed the maximum of {max}")
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The Helium token moved by a token transfer of the signer, from the mint
+/// of the transfer when given, or else from the associated token account it
+/// is sent from
+fn transferred_token(
+    signer: &Pubkey,
+    source: Option<&Pubkey>,
+    mint: Option<&Pubkey>,
+) -> Option<Token> {
+    [Token::Hnt, Token::Iot, Token::Mobile, Token::Dc]
+        .into_iter()
+        .find(|token| match mint {
+            Some(mint) => token.mint() == mint,
+            None => source == Some(&token.associated_token_adress(signer)),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use helium_lib::{
+        keypair::{Keypair, Signer},
+        message::VersionedMessage,
+        solana_sdk::{instruction::Instruction, message::v0, system_instruction},
+        tx,
+    };
+
+    fn transaction(payer: &Keypair, ixs: &[Instruction]) -> VersionedTransaction {
+        let message = v0::Message::try_compile(&payer.pubkey(), ixs, &[], Default::default())
+            .expect("message");
+        tx::partial_sign(VersionedMessage::V0(message), &[payer]).expect("transaction")
+    }
+
+    #[test]
+    fn policy_checks() {
+        let keypair = Keypair::generate();
+        let signer = keypair.pubkey();
+        let policy = Policy {
+            max_transfer_lamports: Some(1_000),
+            ..Default::default()
+        };
+        let transfer =
+            |lamports| system_instruction::transfer(&signer, &Pubkey::new_unique(), lamports);
+
+        let tx = transaction(&keypair, &[transfer(600)]);
+        assert!(policy.check(&tx, &signer).is_ok());
+        assert!(policy.check(&tx, &Pubkey::new_unique()).is_err());
+
+        let tx = transaction(&keypair, &[transfer(600), transfer(600)]);
+        assert!(policy.check(&tx, &signer).is_err());
+
+        let unknown = Instruction::new_with_bytes(Pubkey::new_unique(), &[], vec![]);
+        let tx = transaction(&keypair, &[unknown]);
+        assert!(policy.check(&tx, &signer).is_err());
+
+        let create = system_instruction::create_account(
+            &signer,
+            &Pubkey::new_unique(),
+            1_200,
+            0,
+            &system_program::id(),
+        );
+        let tx = transaction(&keypair, &[create]);
+        assert!(policy.check(&tx, &signer).is_err());
+    }
+
+    #[test]
+    fn token_policy_checks() {
+        use anchor_spl::token::spl_token::{self, instruction as token_instruction};
+        let keypair = Keypair::generate();
+        let signer = keypair.pubkey();
+        let policy = Policy {
+            max_token_transfers: HashMap::from([(Token::Hnt, 1_000)]),
+            ..Default::default()
+        };
+        let source = Token::Hnt.associated_token_adress(&signer);
+        let transfer = |amount| {
+            token_instruction::transfer(
+                &spl_token::id(),
+                &source,
+                &Pubkey::new_unique(),
+                &signer,
+                &[],
+                amount,
+            )
+            .expect("transfer")
+        };
+        let transfer_checked = |amount| {
+            token_instruction::transfer_checked(
+                &spl_token::id(),
+                &Pubkey::new_unique(),
+                Token::Hnt.mint(),
+                &Pubkey::new_unique(),
+                &signer,
+                &[],
+                amount,
+                Token::Hnt.decimals(),
+            )
+            .expect("transfer")
+        };
+
+        let tx = transaction(&keypair, &[transfer(600)]);
+        assert!(policy.check(&tx, &signer).is_ok());
+        let tx = transaction(&keypair, &[transfer(600), transfer_checked(600)]);
+        assert!(policy.check(&tx, &signer).is_err());
+        let tx = transaction(&keypair, &[transfer_checked(1_200)]);
+        assert!(policy.check(&tx, &signer).is_err());
+
+        let unknown_source = token_instruction::transfer(
+            &spl_token::id(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &signer,
+            &[],
+            1,
+        )
+        .expect("transfer");
+        let tx = transaction(&keypair, &[unknown_source]);
+        assert!(policy.check(&tx, &signer).is_err());
+
+        let approve = token_instruction::approve(
+            &spl_token::id(),
+            &source,
+            &Pubkey::new_unique(),
+            &signer,
+            &[],
+            1,
+        )
+        .expect("approve");
+        let tx = transaction(&keypair, &[approve]);
+        assert!(Policy::default().check(&tx, &signer).is_err());
+
+        let set_authority = token_instruction::set_authority(
+            &spl_token::id(),
+            &source,
+            Some(&Pubkey::new_unique()),
+            token_instruction::AuthorityType::AccountOwner,
+            &signer,
+            &[],
+        )
+        .expect("set authority");
+        let tx = transaction(&keypair, &[set_authority]);
+        assert!(Policy::default().check(&tx, &signer).is_err());
+    }
+}